@@ -0,0 +1,437 @@
+//! Todo APIの型付きクライアント
+//!
+//! リクエストボディ、レスポンスボディとパスは、サーバーと同じ`domain::api`で定義された型を共有する。
+use std::fmt::Display;
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, de::DeserializeOwned};
+
+use domain::api::paths;
+pub use domain::{
+    api::{
+        AccountBackupBody, CharacterClass, ConfirmEmailRequestBody, DigestQueryParams,
+        ImpersonationResponseBody, ImportResponseBody, LoginRequestBody, LoginResponseBody,
+        MERGE_PATCH_CONTENT_TYPE, PasswordPolicy, Patch, RefreshTokensRequestBody,
+        ServerStatusResponseBody, SignUpRequestBody, StatusCodeValue, TodoArchiveRequestBody,
+        TodoCommentCreateRequestBody, TodoCommentListQueryParams, TodoCreateRequestBody,
+        TodoListQueryParams, TodoReopenRequestBody, TodoTransferRequestBody,
+        TodoTransferResponseBody, TodoUpdateRequestBody, UpdateUserRequestBody, X_NEXT_CURSOR,
+    },
+    models::{DigestTodo, Todo, TodoComment, User, UserDigest, UserPreferences, UserProfile},
+    repositories::{ArchivedFilter, TodoListSort, TodoListView},
+};
+
+/// APIが返したエラーレスポンス
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiErrorBody {
+    /// HTTPステータスコード
+    pub status_code: StatusCode,
+    /// エラーメッセージ
+    pub messages: Vec<String>,
+}
+
+/// エラーレスポンスのボディ
+#[derive(Deserialize)]
+struct RawApiErrorBody {
+    #[serde(default)]
+    messages: Vec<String>,
+}
+
+/// APIクライアントエラー
+#[derive(Debug, thiserror::Error)]
+pub enum ApiClientError {
+    /// APIが成功以外のステータスコードを返した。
+    #[error("API error: {}, {:?}", .0.status_code, .0.messages)]
+    Api(ApiErrorBody),
+    /// リクエストの送信またはレスポンスの受信に失敗した。
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
+
+impl ApiClientError {
+    /// APIが返したエラーレスポンスを返す。
+    ///
+    /// 通信エラーの場合は`None`を返す。
+    pub fn api_error(&self) -> Option<&ApiErrorBody> {
+        match self {
+            ApiClientError::Api(body) => Some(body),
+            ApiClientError::Http(_) => None,
+        }
+    }
+}
+
+pub type ApiClientResult<T> = Result<T, ApiClientError>;
+
+/// Todo APIクライアント
+///
+/// クッキーによる認証を利用する場合は、クッキーストアを有効にした`reqwest::Client`を渡すこと。
+#[derive(Debug, Clone)]
+pub struct TodoApiClient {
+    /// APIのベースURL（例: `http://localhost:8000/api/v1`）
+    base_url: String,
+    /// HTTPクライアント
+    http_client: reqwest::Client,
+}
+
+impl TodoApiClient {
+    pub fn new(base_url: impl Into<String>, http_client: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.into(),
+            http_client,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// ユーザーを登録する。
+    pub async fn sign_up(&self, body: &SignUpRequestBody) -> ApiClientResult<User> {
+        let request = self
+            .http_client
+            .post(self.url(paths::USERS_SIGN_UP))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// パスワードポリシーを取得する。
+    pub async fn password_policy(&self) -> ApiClientResult<PasswordPolicy> {
+        let request = self.http_client.get(self.url(paths::USERS_PASSWORD_POLICY));
+        json_body(request).await
+    }
+
+    /// ログインする。
+    pub async fn login(&self, body: &LoginRequestBody) -> ApiClientResult<LoginResponseBody> {
+        let request = self
+            .http_client
+            .post(self.url(paths::USERS_LOGIN))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// ログインしているユーザーを取得する。
+    pub async fn me(&self) -> ApiClientResult<UserProfile> {
+        let request = self.http_client.get(self.url(paths::USERS_ME));
+        json_body(request).await
+    }
+
+    /// ログインしているユーザーを更新する。
+    pub async fn update_user(&self, body: &UpdateUserRequestBody) -> ApiClientResult<UserProfile> {
+        let request = self.http_client.patch(self.url(paths::USERS_ME)).json(body);
+        json_body(request).await
+    }
+
+    /// JSON Merge Patchで、ログインしているユーザーを更新する。
+    ///
+    /// `Patch::Null`を指定したフィールドは、値を消去するため`null`として送信する。
+    pub async fn merge_patch_user(
+        &self,
+        body: &UpdateUserRequestBody,
+    ) -> ApiClientResult<UserProfile> {
+        let request = self
+            .http_client
+            .patch(self.url(paths::USERS_ME))
+            .header(reqwest::header::CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE)
+            .json(body);
+        json_body(request).await
+    }
+
+    /// 確認トークンで、ログインしているユーザーのEメールアドレスの変更を確認する。
+    pub async fn confirm_email(
+        &self,
+        body: &ConfirmEmailRequestBody,
+    ) -> ApiClientResult<UserProfile> {
+        let request = self
+            .http_client
+            .post(self.url(paths::USERS_ME_CONFIRM_EMAIL))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// ログインしているユーザーの設定を取得する。
+    pub async fn preferences(&self) -> ApiClientResult<UserPreferences> {
+        let request = self.http_client.get(self.url(paths::USERS_ME_PREFERENCES));
+        json_body(request).await
+    }
+
+    /// ログインしているユーザーの設定を更新する。
+    pub async fn update_preferences(
+        &self,
+        body: &UserPreferences,
+    ) -> ApiClientResult<UserPreferences> {
+        let request = self
+            .http_client
+            .put(self.url(paths::USERS_ME_PREFERENCES))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// アクセストークンとリフレッシュトークンを更新する。
+    ///
+    /// `body`が`None`の場合は、クッキーに保存されたリフレッシュトークンを使用する。
+    pub async fn refresh_tokens(
+        &self,
+        body: Option<&RefreshTokensRequestBody>,
+    ) -> ApiClientResult<LoginResponseBody> {
+        let mut request = self.http_client.post(self.url(paths::USERS_REFRESH_TOKENS));
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        json_body(request).await
+    }
+
+    /// ログアウトする。
+    pub async fn logout(&self) -> ApiClientResult<()> {
+        let request = self.http_client.post(self.url(paths::USERS_LOGOUT));
+        empty_body(request).await
+    }
+
+    /// すべてのセッションからログアウトする。
+    pub async fn logout_all(&self) -> ApiClientResult<()> {
+        let request = self.http_client.post(self.url(paths::USERS_ME_LOGOUT_ALL));
+        empty_body(request).await
+    }
+
+    /// ログインしているユーザーの日次ダイジェストを取得する。
+    pub async fn digest(&self, params: &DigestQueryParams) -> ApiClientResult<UserDigest> {
+        let request = self
+            .http_client
+            .get(self.url(paths::USERS_ME_DIGEST))
+            .query(&params.query_pairs());
+        json_body(request).await
+    }
+
+    /// ログインしているユーザーのアカウントをエクスポートする。
+    pub async fn export_account(&self) -> ApiClientResult<AccountBackupBody> {
+        let request = self.http_client.get(self.url(paths::USERS_ME_EXPORT));
+        json_body(request).await
+    }
+
+    /// ログインしているユーザーのTodoをインポートする。
+    pub async fn import_account(
+        &self,
+        body: &AccountBackupBody,
+    ) -> ApiClientResult<ImportResponseBody> {
+        let request = self
+            .http_client
+            .post(self.url(paths::USERS_ME_IMPORT))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// Todoのリストを取得する。
+    ///
+    /// クエリパラメーターの値はURLエンコードして送信する。
+    pub async fn todos_list(&self, params: &TodoListQueryParams) -> ApiClientResult<Vec<Todo>> {
+        let request = self
+            .http_client
+            .get(self.url(paths::TODOS))
+            .query(&params.query_pairs());
+        json_body(request).await
+    }
+
+    /// カーソルで分割したTodoリストの1ページを取得する。
+    ///
+    /// Todoのリストと、`X-Next-Cursor`ヘッダーに格納された次のページのカーソルを返す。
+    /// 次のページがない場合、カーソルは`None`である。
+    pub async fn todos_list_by_cursor(
+        &self,
+        params: &TodoListQueryParams,
+    ) -> ApiClientResult<(Vec<Todo>, Option<String>)> {
+        let request = self
+            .http_client
+            .get(self.url(paths::TODOS))
+            .query(&params.query_pairs());
+        let response = error_for_status(request.send().await?).await?;
+        let next_cursor = response
+            .headers()
+            .get(X_NEXT_CURSOR)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        Ok((response.json::<Vec<Todo>>().await?, next_cursor))
+    }
+
+    /// TodoをIDで取得する。
+    pub async fn todo_get(&self, todo_id: impl Display) -> ApiClientResult<Todo> {
+        let request = self.http_client.get(self.url(&paths::todo_by_id(todo_id)));
+        json_body(request).await
+    }
+
+    /// Todoを作成する。
+    pub async fn todo_create(&self, body: &TodoCreateRequestBody) -> ApiClientResult<Todo> {
+        let request = self.http_client.post(self.url(paths::TODOS)).json(body);
+        json_body(request).await
+    }
+
+    /// Todoを更新する。
+    pub async fn todo_update(
+        &self,
+        todo_id: impl Display,
+        body: &TodoUpdateRequestBody,
+    ) -> ApiClientResult<Todo> {
+        let request = self
+            .http_client
+            .patch(self.url(&paths::todo_by_id(todo_id)))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// JSON Merge Patchで、Todoを更新する。
+    ///
+    /// `Patch::Null`を指定した説明と完了予定日は、値を消去するため`null`として送信する。
+    pub async fn merge_patch_todo(
+        &self,
+        todo_id: impl Display,
+        body: &TodoUpdateRequestBody,
+    ) -> ApiClientResult<Todo> {
+        let request = self
+            .http_client
+            .patch(self.url(&paths::todo_by_id(todo_id)))
+            .header(reqwest::header::CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE)
+            .json(body);
+        json_body(request).await
+    }
+
+    /// Todoを完了する。
+    pub async fn todo_complete(&self, todo_id: impl Display) -> ApiClientResult<Todo> {
+        let request = self
+            .http_client
+            .post(self.url(&paths::todo_complete(todo_id)));
+        json_body(request).await
+    }
+
+    /// 完了したTodoを再開する。
+    pub async fn todo_reopen(
+        &self,
+        todo_id: impl Display,
+        body: &TodoReopenRequestBody,
+    ) -> ApiClientResult<Todo> {
+        let request = self
+            .http_client
+            .post(self.url(&paths::todo_reopen(todo_id)))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// Todoをアーカイブ、またはアーカイブを解除する。
+    pub async fn todo_archive(
+        &self,
+        todo_id: impl Display,
+        body: &TodoArchiveRequestBody,
+    ) -> ApiClientResult<Todo> {
+        let request = self
+            .http_client
+            .post(self.url(&paths::todo_archive(todo_id)))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// Todoを削除する。
+    pub async fn todo_delete(&self, todo_id: impl Display) -> ApiClientResult<()> {
+        let request = self
+            .http_client
+            .delete(self.url(&paths::todo_by_id(todo_id)));
+        empty_body(request).await
+    }
+
+    /// Todoのコメントを、作成日時の降順で取得する。
+    pub async fn todo_comments(
+        &self,
+        todo_id: impl Display,
+        params: &TodoCommentListQueryParams,
+    ) -> ApiClientResult<Vec<TodoComment>> {
+        let request = self
+            .http_client
+            .get(self.url(&paths::todo_comments(todo_id)))
+            .query(&params.query_pairs());
+        json_body(request).await
+    }
+
+    /// Todoにコメントを登録する。
+    pub async fn todo_comment_create(
+        &self,
+        todo_id: impl Display,
+        body: &TodoCommentCreateRequestBody,
+    ) -> ApiClientResult<TodoComment> {
+        let request = self
+            .http_client
+            .post(self.url(&paths::todo_comments(todo_id)))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// コメントを削除する。
+    pub async fn todo_comment_delete(
+        &self,
+        todo_id: impl Display,
+        comment_id: impl Display,
+    ) -> ApiClientResult<()> {
+        let request = self
+            .http_client
+            .delete(self.url(&paths::todo_comment_by_id(todo_id, comment_id)));
+        empty_body(request).await
+    }
+
+    /// 管理者としてユーザーになりすますためのアクセストークンを発行する。
+    pub async fn admin_impersonate(
+        &self,
+        user_id: impl Display,
+    ) -> ApiClientResult<ImpersonationResponseBody> {
+        let request = self
+            .http_client
+            .post(self.url(&paths::admin_impersonate(user_id)));
+        json_body(request).await
+    }
+
+    /// 管理者としてユーザーのTodoを他のユーザーに移管する。
+    pub async fn admin_transfer_todos(
+        &self,
+        body: &TodoTransferRequestBody,
+    ) -> ApiClientResult<TodoTransferResponseBody> {
+        let request = self
+            .http_client
+            .post(self.url(paths::ADMIN_TODOS_TRANSFER))
+            .json(body);
+        json_body(request).await
+    }
+
+    /// 管理者としてサーバーの状態を取得する。
+    pub async fn admin_status(&self) -> ApiClientResult<ServerStatusResponseBody> {
+        let request = self.http_client.get(self.url(paths::ADMIN_STATUS));
+        json_body(request).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+/// リクエストを送信して、レスポンスボディをJSONとしてデシリアライズする。
+async fn json_body<T: DeserializeOwned>(request: RequestBuilder) -> ApiClientResult<T> {
+    let response = error_for_status(request.send().await?).await?;
+    Ok(response.json::<T>().await?)
+}
+
+/// リクエストを送信して、レスポンスボディを破棄する。
+async fn empty_body(request: RequestBuilder) -> ApiClientResult<()> {
+    error_for_status(request.send().await?).await?;
+    Ok(())
+}
+
+/// レスポンスのステータスコードが成功を示さない場合は、エラーレスポンスを返す。
+///
+/// レスポンスボディがAPIのエラーレスポンスの形式でない場合、エラーメッセージは空になる。
+async fn error_for_status(response: Response) -> ApiClientResult<Response> {
+    let status_code = response.status();
+    if status_code.is_success() {
+        return Ok(response);
+    }
+    let bytes = response.bytes().await?;
+    let messages = serde_json::from_slice::<RawApiErrorBody>(&bytes)
+        .map(|body| body.messages)
+        .unwrap_or_default();
+    Err(ApiClientError::Api(ApiErrorBody {
+        status_code,
+        messages,
+    }))
+}
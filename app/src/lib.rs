@@ -1,17 +1,24 @@
 pub mod routes;
 
-use std::time::Duration;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use anyhow::Context as _;
-use deadpool_redis::Config as RedisConfig;
+use axum::Router;
+use deadpool_redis::{Config as RedisConfig, PoolConfig, Runtime, Timeouts};
 use sqlx::postgres::PgPoolOptions;
 use tokio::net::TcpListener;
 use tracing::{Subscriber, subscriber::set_global_default};
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_log::LogTracer;
-use tracing_subscriber::{EnvFilter, Registry, fmt::MakeWriter, layer::SubscriberExt as _};
+use tracing_subscriber::{
+    EnvFilter, Registry, filter::LevelFilter, fmt::MakeWriter, layer::SubscriberExt as _,
+};
 
-use infra::settings::{DatabaseSettings, HttpSettings, RedisSettings};
+use domain::repositories::UserRepository;
+use infra::{
+    DailyDigestUseCaseImpl,
+    settings::{DatabaseSettings, DigestSettings, HttpSettings, LoginSettings, RedisSettings},
+};
 
 pub async fn bind_address(settings: &HttpSettings) -> anyhow::Result<(TcpListener, u16)> {
     let listener = TcpListener::bind(settings.bind_address())
@@ -36,27 +43,182 @@ pub async fn create_pg_pool(
         .context("Failed to connect to the database")
 }
 
+/// SQLiteコネクションプールを作成して、SQLiteデータベースをマイグレーションする。
+///
+/// データベースファイルが存在しない場合は作成する。書き込み中に読み込みをブロックしないようにWALモードを使用して、
+/// 書き込みが競合した場合は、コネクションの取得のタイムアウトまで待機する。
+#[cfg(feature = "lite")]
+pub async fn create_sqlite_pool(settings: &DatabaseSettings) -> anyhow::Result<sqlx::SqlitePool> {
+    use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+
+    let timeout = Duration::from_secs(settings.connection_timeout);
+    let options = SqliteConnectOptions::new()
+        .filename(&settings.sqlite_path)
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .busy_timeout(timeout)
+        .foreign_keys(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(settings.max_connections)
+        .acquire_timeout(timeout)
+        .connect_with(options)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to open the SQLite database: {}",
+                settings.sqlite_path
+            )
+        })?;
+    sqlx::migrate!("../migrations_sqlite")
+        .run(&pool)
+        .await
+        .context("Failed to migrate the SQLite database")?;
+    Ok(pool)
+}
+
+/// Redisコネクションプールを作成する。
+///
+/// Redisが停止しているときに、リクエストが長時間待たされないように、コネクションの取得、作成、再利用に
+/// タイムアウトを設定する。
 pub async fn create_redis_pool(settings: &RedisSettings) -> anyhow::Result<deadpool_redis::Pool> {
+    let timeout = Some(settings.connection_timeout());
+    let mut pool_config = PoolConfig::default();
+    pool_config.timeouts = Timeouts {
+        wait: timeout,
+        create: timeout,
+        recycle: timeout,
+    };
     let config = RedisConfig {
         url: Some(settings.uri()),
         connection: None,
-        pool: None,
+        pool: Some(pool_config),
     };
+    // タイムアウトを設定する場合は、ランタイムを指定する必要がある
     config
-        .create_pool(None)
+        .create_pool(Some(Runtime::Tokio1))
         .context("Failed to create Redis connection pool")
 }
 
+/// 古いログイン失敗履歴を定期的に削除するタスクを起動する。
+///
+/// # 引数
+///
+/// * `user_repo`: ユーザーリポジトリ
+/// * `settings`: ログイン設定
+pub fn spawn_login_failed_history_cleanup(
+    user_repo: Arc<dyn UserRepository>,
+    settings: LoginSettings,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(settings.cleanup_interval_seconds));
+        loop {
+            interval.tick().await;
+            let cutoff =
+                settings.stale_login_failed_history_cutoff(time::OffsetDateTime::now_utc());
+            match user_repo.delete_stale_login_failed_histories(cutoff).await {
+                Ok(deleted) => {
+                    tracing::info!("Deleted {} stale login failed histories", deleted)
+                }
+                Err(e) => tracing::error!(
+                    "Failed to delete stale login failed histories: {:#}",
+                    e.source
+                ),
+            }
+        }
+    })
+}
+
+/// 毎日、設定された時刻に日次ダイジェストを作成するタスクを起動する。
+///
+/// Eメールによる日次ダイジェストの送信は、まだ実装していない。
+///
+/// # 引数
+///
+/// * `use_case`: 日次ダイジェストユースケース
+/// * `settings`: 日次ダイジェスト設定
+pub fn spawn_daily_digest_generation(
+    use_case: DailyDigestUseCaseImpl,
+    settings: DigestSettings,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let now = time::OffsetDateTime::now_utc();
+            let run_at = settings.next_run_at(now);
+            let wait = (run_at - now).try_into().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            let date = run_at.date();
+            match use_case.generate(date).await {
+                Ok(digests) => {
+                    tracing::info!("Generated {} daily digests for {}", digests.len(), date)
+                }
+                Err(e) => tracing::error!("Failed to generate daily digests: {:#}", e.source),
+            }
+        }
+    })
+}
+
+/// `SIGINT`（Ctrl+C）または`SIGTERM`を受信するまで待機するフューチャーを返す。
+///
+/// HTTPサーバーのグレースフルシャットダウンに使用する。
+/// `SIGTERM`のハンドラーはこの関数を呼び出したときに登録するため、HTTPサーバーの起動前に受信した
+/// `SIGTERM`も取りこぼさない。
+pub fn shutdown_signal() -> impl Future<Output = ()> {
+    #[cfg(unix)]
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        .expect("Failed to install the SIGTERM handler");
+
+    async move {
+        let ctrl_c = async {
+            tokio::signal::ctrl_c()
+                .await
+                .expect("Failed to install the Ctrl+C handler");
+        };
+        #[cfg(unix)]
+        let terminate = async {
+            terminate.recv().await;
+        };
+        #[cfg(not(unix))]
+        let terminate = std::future::pending::<()>();
+
+        tokio::select! {
+            _ = ctrl_c => tracing::info!("Received SIGINT, shutting down the HTTP server"),
+            _ = terminate => tracing::info!("Received SIGTERM, shutting down the HTTP server"),
+        }
+    }
+}
+
+/// HTTPサーバーを起動して、`shutdown`が完了したら、処理中のリクエストを完了してから終了する。
+///
+/// # 引数
+///
+/// * `listener`: HTTPサーバーがリッスンするTCPリスナー
+/// * `router`: ルーター
+/// * `shutdown`: 完了したときにグレースフルシャットダウンを開始するフューチャー
+pub async fn serve<F>(listener: TcpListener, router: Router, shutdown: F) -> anyhow::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown)
+    .await
+    .context("Failed to start the HTTP server")
+}
+
 pub fn get_subscriber<Sink>(
     name: String,
-    log_level: log::Level,
+    log_level: tracing::Level,
     sink: Sink,
 ) -> impl Subscriber + Sync + Send
 where
     Sink: for<'a> MakeWriter<'a> + Send + Sync + 'static,
 {
-    let env_filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(log_level.to_string()));
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        EnvFilter::default().add_directive(LevelFilter::from(log_level).into())
+    });
     let formatting_layer = BunyanFormattingLayer::new(name, sink);
     Registry::default()
         .with(env_filter)
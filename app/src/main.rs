@@ -1,10 +1,17 @@
-use anyhow::Context as _;
-
-use infra::{AppState, settings::load_app_settings};
+use infra::{
+    AppState,
+    access_log::create_access_log_sink,
+    build_info::BuildInfo,
+    mail::create_mailer,
+    settings::{DatabaseEngine, load_app_settings},
+};
 
+#[cfg(feature = "lite")]
+use app::create_sqlite_pool;
 use app::{
     bind_address, create_pg_pool, create_redis_pool, get_subscriber, init_subscriber,
-    routes::create_router,
+    routes::{create_router, log_route_table},
+    serve, shutdown_signal, spawn_daily_digest_generation, spawn_login_failed_history_cleanup,
 };
 
 /// アプリケーションエントリーポイント
@@ -15,6 +22,13 @@ async fn main() -> anyhow::Result<()> {
 
     let subscriber = get_subscriber("rusty-todo".into(), app_settings.log_level, std::io::stdout);
     init_subscriber(subscriber);
+    let build_info = BuildInfo::current();
+    tracing::info!(
+        "Starting rusty-todo {} (git: {}, built at: {})",
+        build_info.version,
+        build_info.git_sha,
+        build_info.built_at
+    );
     tracing::info!("{:?}", app_settings);
 
     // HTTPサーバーがバインドするアドレスを構築
@@ -22,24 +36,47 @@ async fn main() -> anyhow::Result<()> {
     app_settings.http.port = port; // 実際にバインドしたポートを設定
     let address = app_settings.http.bind_address();
 
-    // データベースコネクションプールを作成
-    let pg_pool = create_pg_pool(&app_settings.database).await?;
-    // Redisコネクションプールを作成
-    let redis_pool = create_redis_pool(&app_settings.redis).await?;
-
     // ルーターを作成
-    let app_state = AppState {
-        app_settings,
-        pg_pool,
-        redis_pool,
+    let access_log_sink = create_access_log_sink(&app_settings.access_log)?;
+    // SMTPサーバーが設定されていない場合は、メールをログに記録するため、Eメールアドレスを変更できない
+    let mailer = create_mailer(&app_settings.mail)?;
+    if !mailer.delivers() {
+        tracing::warn!(
+            "The mail server is not configured, so users can not change their email address"
+        );
+    }
+    let mut app_state = match app_settings.database.engine {
+        DatabaseEngine::Postgres => {
+            // データベースコネクションプールを作成
+            let pg_pool = create_pg_pool(&app_settings.database).await?;
+            // Redisコネクションプールを作成
+            let redis_pool = create_redis_pool(&app_settings.redis).await?;
+            AppState::new(app_settings, pg_pool, redis_pool, mailer)
+        }
+        // SQLiteデータベースを作成またはマイグレーションして、トークンはプロセス内に保存
+        #[cfg(feature = "lite")]
+        DatabaseEngine::Sqlite => {
+            let pool = create_sqlite_pool(&app_settings.database).await?;
+            AppState::sqlite(app_settings, pool, mailer)
+        }
+        #[cfg(not(feature = "lite"))]
+        DatabaseEngine::Sqlite => unreachable!("the database engine was validated on loading"),
     };
+    app_state.access_log_sink = access_log_sink;
+
+    // 古いログイン失敗履歴を定期的に削除
+    spawn_login_failed_history_cleanup(app_state.repos.user.clone(), app_state.app_settings.login);
+    // 日次ダイジェストを毎日作成
+    if app_state.app_settings.digest.enabled {
+        spawn_daily_digest_generation(
+            app_state.digest_use_case.clone(),
+            app_state.app_settings.digest,
+        );
+    }
     let router = create_router(app_state);
+    log_route_table();
 
-    // HTTPサーバーを起動
+    // HTTPサーバーを起動して、SIGINTまたはSIGTERMを受信したら、処理中のリクエストを完了してから終了
     tracing::info!("HTTP server is running on {}", address);
-    axum::serve(listener, router)
-        .await
-        .context("Failed to start the HTTP server")?;
-
-    Ok(())
+    serve(listener, router, shutdown_signal()).await
 }
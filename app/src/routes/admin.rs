@@ -0,0 +1,16 @@
+use axum::http::Method;
+
+use infra::http::{
+    handler::admin::{impersonate, status, transfer_todos},
+    paths,
+};
+
+use crate::routes::ApiRoute;
+
+pub fn route_table() -> Vec<ApiRoute> {
+    vec![
+        ApiRoute::admin(Method::POST, paths::ADMIN_IMPERSONATE, impersonate),
+        ApiRoute::admin(Method::POST, paths::ADMIN_TODOS_TRANSFER, transfer_todos),
+        ApiRoute::admin(Method::GET, paths::ADMIN_STATUS, status),
+    ]
+}
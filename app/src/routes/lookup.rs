@@ -1,27 +1,21 @@
-use axum::{Router, middleware, routing::get};
+use axum::http::Method;
 
-use infra::{
-    AppState,
-    http::{
-        handler::lookup::{role, todo_status},
-        middleware::authorized_user_middleware,
-    },
+use infra::http::{
+    handler::lookup::{role, todo_status},
+    paths,
 };
 
-macro_rules! create_lookup_routes {
-    ($name:ident, $module:ident) => {
-        pub fn $name(app_status: infra::AppState) -> Router<AppState> {
-            Router::new()
-                .route("/", get($module::list))
-                .route("/{code}", get($module::by_code))
-                .layer(middleware::from_fn_with_state(
-                    app_status.clone(),
-                    authorized_user_middleware,
-                ))
-                .with_state(app_status)
-        }
-    };
-}
+use crate::routes::ApiRoute;
 
-create_lookup_routes!(create_role_routes, role);
-create_lookup_routes!(create_todo_status_routes, todo_status);
+pub fn route_table() -> Vec<ApiRoute> {
+    vec![
+        ApiRoute::authorized(Method::GET, paths::ROLES, role::list),
+        ApiRoute::authorized(Method::GET, paths::ROLE_BY_CODE, role::by_code),
+        ApiRoute::authorized(Method::GET, paths::TODO_STATUSES, todo_status::list),
+        ApiRoute::authorized(
+            Method::GET,
+            paths::TODO_STATUS_BY_CODE,
+            todo_status::by_code,
+        ),
+    ]
+}
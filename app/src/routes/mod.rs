@@ -1,31 +1,317 @@
+pub mod admin;
 pub mod lookup;
 pub mod todo;
 pub mod user;
 
+pub use infra::http::paths;
+
+use std::time::Duration;
+
 use axum::{
-    Router,
+    BoxError, Router,
+    error_handling::HandleErrorLayer,
+    extract::MatchedPath,
+    handler::Handler,
     http::{HeaderValue, Method, header},
-    routing::get,
+    middleware,
+    routing::{MethodFilter, MethodRouter, get, on},
 };
 
-use infra::{AppState, http::handler::health_check};
-use tower_http::cors::CorsLayer;
-use user::create_user_routes;
-
-use crate::routes::{
-    lookup::{create_role_routes, create_todo_status_routes},
-    todo::create_todo_routes,
+use infra::{
+    AppState,
+    http::{
+        ApiError,
+        handler::{api_fallback, health_check, metrics::metrics, todo::X_NEXT_CURSOR},
+        internal_server_error,
+        middleware::{
+            RequestId, X_APP_VERSION, X_TOKEN_EXPIRES_IN, X_TOKEN_REFRESH_SUGGESTED,
+            access_log_middleware, admin_user_middleware, app_version_middleware,
+            authorized_user_middleware, client_ip_middleware, request_id_middleware,
+            request_log_middleware,
+        },
+        request_timed_out,
+    },
+    settings::HttpSettings,
+};
+use tower::{ServiceBuilder, timeout::error::Elapsed};
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    limit::RequestBodyLimitLayer,
+    services::{ServeDir, ServeFile},
+    trace::TraceLayer,
 };
+use tracing::Span;
 
 /// ルーターを作成する。
 ///
+/// クライアントのIPアドレスを取得するため、ルーターは`into_make_service_with_connect_info::<SocketAddr>()`で
+/// サービスに変換して起動すること。
+///
 /// # 引数
 ///
-/// * `app_settings`: アプリケーション設定
-/// * `pool`: PostgreSQLコネクションプール
+/// * `app_state`: アプリケーションの状態
 pub fn create_router(app_state: AppState) -> Router {
+    let request_timeout = app_state.app_settings.http.request_timeout();
+    let max_body_size_bytes = app_state.app_settings.http.max_body_size_bytes;
+    let mut routes = create_api_routes(app_state.clone()).route(paths::METRICS, get(metrics));
+    // 静的ファイルを配信するディレクトリが指定されている場合は、APIのパス以外に静的ファイルを配信
+    if let Some(static_dir) = &app_state.app_settings.http.static_dir {
+        routes = routes.fallback_service(static_file_service(static_dir));
+    }
+    let cors = cors_layer(&app_state.app_settings.http);
+    let mut router = apply_layers(routes, request_timeout, max_body_size_bytes, cors);
+    // タイムアウトなどを含めた最終的なステータスコードを記録するため、リクエストログをタイムアウトより外側に適用
+    router = router.layer(middleware::from_fn(request_log_middleware));
+    // リクエストごとにスパンを作成して、リクエストログなどのイベントにリクエストIDを付与
+    // レスポンスのログは、リクエストログで出力するため出力しない
+    router = router.layer(
+        TraceLayer::new_for_http()
+            .make_span_with(make_request_span)
+            .on_response(()),
+    );
+    // アクセスログが有効な場合は、タイムアウトなどを含めた最終的なステータスコードを記録するため、スパンより外側に適用
+    if app_state.app_settings.access_log.enabled {
+        router = router.layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            access_log_middleware,
+        ));
+    }
+    // タイムアウトなどを含むすべてのレスポンスにアプリケーションのバージョンを設定
+    router = router.layer(middleware::from_fn_with_state(
+        app_state.clone(),
+        app_version_middleware,
+    ));
+    // スパンとアクセスログがリクエストIDを参照できるように、それらより外側に適用
+    router = router.layer(middleware::from_fn(request_id_middleware));
+    // アクセスログを含むすべてのミドルウェアとハンドラーがクライアントのIPアドレスを参照できるように、最も外側に適用
+    router = router.layer(middleware::from_fn(client_ip_middleware));
+    router.with_state(app_state)
+}
+
+/// リクエストごとのスパンを作成する。
+///
+/// クエリ文字列に機密情報が含まれる可能性があるため、URIではなくパスのみを記録する。
+/// ルートに一致した場合は、UUIDなどを含む実際のパスではなく、パステンプレートを記録する。
+fn make_request_span(request: &axum::extract::Request) -> Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|RequestId(id)| id.as_str())
+        .unwrap_or_default();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str())
+        .unwrap_or_else(|| request.uri().path());
+    tracing::info_span!("request", method = %request.method(), path, request_id)
+}
+
+/// ルートにアクセスできるユーザー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteAccess {
+    /// 認証していないユーザーを含むすべてのユーザー
+    Public,
+    /// 認証済みユーザー
+    Authorized,
+    /// 管理者
+    Admin,
+}
+
+/// APIのルート
+#[derive(Debug, Clone)]
+pub struct ApiRoute {
+    /// HTTPメソッド
+    pub method: Method,
+    /// パス
+    pub path: &'static str,
+    /// ルートにアクセスできるユーザー
+    pub access: RouteAccess,
+    /// HTTPメソッドのみを受け付けるハンドラー
+    handler: MethodRouter<AppState>,
+}
+
+impl ApiRoute {
+    /// 認証していないユーザーを含むすべてのユーザーがアクセスできるルートを作成する。
+    pub fn public<H, T>(method: Method, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, AppState>,
+        T: 'static,
+    {
+        Self::new(method, path, RouteAccess::Public, handler)
+    }
+
+    /// 認証済みユーザーのみがアクセスできるルートを作成する。
+    pub fn authorized<H, T>(method: Method, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, AppState>,
+        T: 'static,
+    {
+        Self::new(method, path, RouteAccess::Authorized, handler)
+    }
+
+    /// 管理者のみがアクセスできるルートを作成する。
+    pub fn admin<H, T>(method: Method, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, AppState>,
+        T: 'static,
+    {
+        Self::new(method, path, RouteAccess::Admin, handler)
+    }
+
+    fn new<H, T>(method: Method, path: &'static str, access: RouteAccess, handler: H) -> Self
+    where
+        H: Handler<T, AppState>,
+        T: 'static,
+    {
+        // ルートの一覧に記録したメソッドとハンドラーが受け付けるメソッドが一致するように、メソッドからフィルタを作成
+        let filter = MethodFilter::try_from(method.clone())
+            .unwrap_or_else(|_| panic!("The method can not be routed: {method}"));
+        Self {
+            method,
+            path,
+            access,
+            handler: on(filter, handler),
+        }
+    }
+}
+
+/// APIのルートの一覧を返す。
+///
+/// ルーターはこの一覧からルートを登録するため、この一覧がルーターに登録されたルートのすべてである。
+pub fn api_route_table() -> Vec<ApiRoute> {
+    let mut table = vec![ApiRoute::public(
+        Method::GET,
+        paths::HEALTH_CHECK,
+        health_check,
+    )];
+    table.extend(user::route_table());
+    table.extend(todo::route_table());
+    table.extend(admin::route_table());
+    table.extend(lookup::route_table());
+    table
+}
+
+/// ルーターに登録したAPIのルートの一覧をログに出力する。
+///
+/// ルートのパスの誤りに気付けるように、アプリケーションの起動時に呼び出す。
+pub fn log_route_table() {
+    for route in api_route_table() {
+        tracing::info!(
+            "Route: {} {}{}",
+            route.method,
+            paths::API_PREFIX,
+            route.path
+        );
+    }
+}
+
+/// 静的ファイルを配信するサービスを作成する。
+///
+/// 存在しないファイルがリクエストされた場合は、SPAのルーティングのために`index.html`を返す。
+///
+/// # 引数
+///
+/// * `static_dir`: 静的ファイルを配信するディレクトリ
+fn static_file_service(static_dir: &str) -> ServeDir<ServeFile> {
+    let index = std::path::Path::new(static_dir).join("index.html");
+    ServeDir::new(static_dir).fallback(ServeFile::new(index))
+}
+
+/// APIのルーターを作成する。
+///
+/// # 引数
+///
+/// * `app_state`: アプリケーションの状態
+pub fn create_api_routes(app_state: AppState) -> Router<AppState> {
+    let table = api_route_table();
+    let authorized_routes = register_routes(&table, RouteAccess::Authorized).layer(
+        middleware::from_fn_with_state(app_state.clone(), authorized_user_middleware),
+    );
+    // 管理者であるか確認する前に、認証済みユーザーであるか確認
+    let admin_routes = register_routes(&table, RouteAccess::Admin)
+        .layer(middleware::from_fn(admin_user_middleware))
+        .layer(middleware::from_fn_with_state(
+            app_state,
+            authorized_user_middleware,
+        ));
+    let routes = register_routes(&table, RouteAccess::Public)
+        .merge(authorized_routes)
+        .merge(admin_routes)
+        // APIのパスに一致しない場合は、静的ファイルではなくAPIエラーを返す
+        .fallback(api_fallback);
+    Router::new().nest(paths::API_PREFIX, routes)
+}
+
+/// ルートの一覧から、指定したユーザーがアクセスできるルートを登録したルーターを作成する。
+///
+/// 同じパスに異なるメソッドのルートを登録した場合、それらのルートは1つのパスにまとめられる。
+///
+/// # 引数
+///
+/// * `table`: ルートの一覧
+/// * `access`: ルートにアクセスできるユーザー
+fn register_routes(table: &[ApiRoute], access: RouteAccess) -> Router<AppState> {
+    table
+        .iter()
+        .filter(|route| route.access == access)
+        .fold(Router::new(), |router, route| {
+            router.route(route.path, route.handler.clone())
+        })
+}
+
+/// ルーターにミドルウェアを適用する。
+///
+/// # 引数
+///
+/// * `router`: ルーター
+/// * `request_timeout`: リクエストタイムアウト
+/// * `max_body_size_bytes`: リクエストボディの最大サイズ（バイト）
+/// * `cors`: CORSレイヤー、`None`の場合はCORSを無効にする
+pub fn apply_layers(
+    router: Router<AppState>,
+    request_timeout: Duration,
+    max_body_size_bytes: usize,
+    cors: Option<CorsLayer>,
+) -> Router<AppState> {
+    // タイムアウトした場合は503 Service Unavailable、リクエストボディが大きすぎる場合は413 Payload Too Largeを返す
+    let router = router
+        .layer(RequestBodyLimitLayer::new(max_body_size_bytes))
+        .layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(handle_timeout_error))
+                .timeout(request_timeout),
+        );
+    match cors {
+        Some(cors) => router.layer(cors),
+        None => router,
+    }
+}
+
+/// CORSレイヤーを作成する。
+///
+/// 許可するオリジンが設定されていない場合は、CORSを無効にするため`None`を返す。
+///
+/// # 引数
+///
+/// * `settings`: HTTPサーバー設定
+pub fn cors_layer(settings: &HttpSettings) -> Option<CorsLayer> {
+    // オリジンは`AppSettings::validate`で検証済みであるが、ヘッダーの値に変換できないオリジンは無視する
+    let origins = settings
+        .allowed_origins
+        .iter()
+        .filter_map(|origin| match origin.parse::<HeaderValue>() {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::warn!("Ignored the invalid allowed origin {origin}: {e}");
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    if origins.is_empty() {
+        return None;
+    }
     let cors = CorsLayer::new()
-        .allow_origin("http://localhost:5173".parse::<HeaderValue>().unwrap())
+        .allow_origin(AllowOrigin::list(origins))
         .allow_methods([
             Method::GET,
             Method::POST,
@@ -40,20 +326,24 @@ pub fn create_router(app_state: AppState) -> Router {
             header::AUTHORIZATION,
             header::COOKIE,
         ])
-        .allow_credentials(true);
-
-    let routes = axum::Router::new()
-        .route("/health-check", get(health_check))
-        .nest("/users", create_user_routes(app_state.clone()))
-        .nest("/todos", create_todo_routes(app_state.clone()))
-        .nest("/roles", create_role_routes(app_state.clone()))
-        .nest(
-            "/todo-statuses",
-            create_todo_status_routes(app_state.clone()),
-        );
+        .expose_headers([
+            X_TOKEN_EXPIRES_IN,
+            X_TOKEN_REFRESH_SUGGESTED,
+            X_APP_VERSION,
+            X_NEXT_CURSOR,
+        ])
+        .allow_credentials(true)
+        // ブラウザにプリフライトリクエストの結果をキャッシュさせて、プリフライトリクエストを減らす
+        .max_age(settings.cors_max_age());
+    Some(cors)
+}
 
-    Router::new()
-        .nest("/api/v1", routes)
-        .layer(cors)
-        .with_state(app_state)
+/// タイムアウトレイヤーが返したエラーをAPIエラーに変換する。
+async fn handle_timeout_error(error: BoxError) -> ApiError {
+    if error.is::<Elapsed>() {
+        tracing::warn!("The request timed out");
+        request_timed_out()
+    } else {
+        internal_server_error(anyhow::anyhow!(error))
+    }
 }
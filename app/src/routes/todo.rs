@@ -1,26 +1,27 @@
-use axum::{
-    Router, middleware,
-    routing::{get, post},
-};
+use axum::http::Method;
 
-use infra::{
-    AppState,
-    http::{
-        handler::todo::{archive, by_id, complete, create, delete, list, reopen, update},
-        middleware::authorized_user_middleware,
+use infra::http::{
+    handler::{
+        comment,
+        todo::{archive, by_id, complete, create, delete, list, reopen, update},
     },
+    paths,
 };
 
-pub fn create_todo_routes(app_state: AppState) -> Router<AppState> {
-    Router::new()
-        .route("/", get(list).post(create))
-        .route("/{todo_id}/complete", post(complete))
-        .route("/{todo_id}/reopen", post(reopen))
-        .route("/{todo_id}/archive", post(archive))
-        .route("/{todo_id}", get(by_id).patch(update).delete(delete))
-        .layer(middleware::from_fn_with_state(
-            app_state.clone(),
-            authorized_user_middleware,
-        ))
-        .with_state(app_state)
+use crate::routes::ApiRoute;
+
+pub fn route_table() -> Vec<ApiRoute> {
+    vec![
+        ApiRoute::authorized(Method::GET, paths::TODOS, list),
+        ApiRoute::authorized(Method::POST, paths::TODOS, create),
+        ApiRoute::authorized(Method::GET, paths::TODO_BY_ID, by_id),
+        ApiRoute::authorized(Method::PATCH, paths::TODO_BY_ID, update),
+        ApiRoute::authorized(Method::DELETE, paths::TODO_BY_ID, delete),
+        ApiRoute::authorized(Method::POST, paths::TODO_COMPLETE, complete),
+        ApiRoute::authorized(Method::POST, paths::TODO_REOPEN, reopen),
+        ApiRoute::authorized(Method::POST, paths::TODO_ARCHIVE, archive),
+        ApiRoute::authorized(Method::GET, paths::TODO_COMMENTS, comment::list),
+        ApiRoute::authorized(Method::POST, paths::TODO_COMMENTS, comment::create),
+        ApiRoute::authorized(Method::DELETE, paths::TODO_COMMENT_BY_ID, comment::delete),
+    ]
 }
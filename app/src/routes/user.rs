@@ -1,29 +1,30 @@
-use axum::{
-    Router, middleware,
-    routing::{get, post},
-};
+use axum::http::Method;
 
-use infra::{
-    AppState,
-    http::{
-        handler::user::{login, logout, me, refresh_tokens, sign_up, update},
-        middleware::authorized_user_middleware,
+use infra::http::{
+    handler::user::{
+        confirm_email, digest, export, import, login, logout, logout_all, me, password_policy,
+        preferences, refresh_tokens, sign_up, update, update_preferences,
     },
+    paths,
 };
 
-pub fn create_user_routes(app_state: AppState) -> Router<AppState> {
-    let router = Router::new()
-        .route("/sign-up", post(sign_up))
-        .route("/login", post(login))
-        .route("/refresh-tokens", post(refresh_tokens))
-        .with_state(app_state.clone());
-    let protected_router = Router::new()
-        .route("/me", get(me).patch(update))
-        .route("/logout", post(logout))
-        .layer(middleware::from_fn_with_state(
-            app_state.clone(),
-            authorized_user_middleware,
-        ))
-        .with_state(app_state);
-    Router::new().merge(router).merge(protected_router)
+use crate::routes::ApiRoute;
+
+pub fn route_table() -> Vec<ApiRoute> {
+    vec![
+        ApiRoute::public(Method::POST, paths::USERS_SIGN_UP, sign_up),
+        ApiRoute::public(Method::GET, paths::USERS_PASSWORD_POLICY, password_policy),
+        ApiRoute::public(Method::POST, paths::USERS_LOGIN, login),
+        ApiRoute::public(Method::POST, paths::USERS_REFRESH_TOKENS, refresh_tokens),
+        ApiRoute::authorized(Method::GET, paths::USERS_ME, me),
+        ApiRoute::authorized(Method::PATCH, paths::USERS_ME, update),
+        ApiRoute::authorized(Method::POST, paths::USERS_ME_CONFIRM_EMAIL, confirm_email),
+        ApiRoute::authorized(Method::GET, paths::USERS_ME_PREFERENCES, preferences),
+        ApiRoute::authorized(Method::PUT, paths::USERS_ME_PREFERENCES, update_preferences),
+        ApiRoute::authorized(Method::GET, paths::USERS_ME_DIGEST, digest),
+        ApiRoute::authorized(Method::GET, paths::USERS_ME_EXPORT, export),
+        ApiRoute::authorized(Method::POST, paths::USERS_ME_IMPORT, import),
+        ApiRoute::authorized(Method::POST, paths::USERS_LOGOUT, logout),
+        ApiRoute::authorized(Method::POST, paths::USERS_ME_LOGOUT_ALL, logout_all),
+    ]
 }
@@ -0,0 +1,94 @@
+//! 管理者向けAPIのリクエストボディとレスポンスボディ
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, serde::rfc3339};
+use uuid::Uuid;
+
+use utils::{serde::SecretStringSerde, serde_with::serde_as};
+
+#[serde_as(crate = "utils::serde_with")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationResponseBody {
+    #[serde_as(as = "SecretStringSerde")]
+    pub access_token: SecretString,
+    #[serde(with = "rfc3339")]
+    pub access_expired_at: OffsetDateTime,
+}
+
+/// Todo移管リクエストボディ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoTransferRequestBody {
+    /// 移管元のユーザーID
+    pub from_user_id: Uuid,
+    /// 移管先のユーザーID
+    pub to_user_id: Uuid,
+    /// 完了したTodoも移管するかどうか
+    #[serde(default)]
+    pub include_completed: bool,
+}
+
+/// Todo移管レスポンスボディ
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoTransferResponseBody {
+    /// 移管したTodoの数
+    pub transferred: u64,
+}
+
+/// サーバー状態レスポンスボディ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatusResponseBody {
+    /// アプリケーションのバージョン
+    pub version: String,
+    /// アプリケーションを起動してからの経過秒数
+    pub uptime_seconds: u64,
+    /// PostgreSQLの状態
+    pub postgres: DependencyPoolStatus,
+    /// Redisの状態
+    pub redis: DependencyPoolStatus,
+}
+
+/// サーバーが依存するサービスの状態と、コネクションプールの状態
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyPoolStatus {
+    /// 疎通の状態
+    pub status: DependencyStatus,
+    /// コネクションプールの状態
+    pub pool: PoolStatus,
+}
+
+/// サーバーが依存するサービスの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyStatus {
+    /// 正常
+    Ok,
+    /// 利用できない
+    Degraded,
+}
+
+/// コネクションプールの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatus {
+    /// プールが保持しているコネクションの数
+    pub size: usize,
+    /// アイドル状態のコネクションの数
+    pub idle: usize,
+    /// 使用中のコネクションの数
+    pub in_use: usize,
+}
+
+impl PoolStatus {
+    pub fn new(size: usize, idle: usize) -> Self {
+        Self {
+            size,
+            idle,
+            in_use: size.saturating_sub(idle),
+        }
+    }
+}
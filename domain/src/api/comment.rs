@@ -0,0 +1,45 @@
+//! Todoコメントのリクエストボディとクエリパラメーター
+use serde::{Deserialize, Serialize};
+
+use crate::{DomainResult, FieldErrors, models::CommentBody};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoCommentListQueryParams {
+    /// ページ番号（1から始まる）
+    #[serde(default)]
+    pub page: Option<u32>,
+    /// 1ページあたりのコメント数
+    #[serde(default)]
+    pub page_size: Option<u32>,
+}
+
+impl TodoCommentListQueryParams {
+    /// 指定されたクエリパラメーターを、名前と値の組で返す。
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+        if let Some(page) = self.page {
+            pairs.push(("page", page.to_string()));
+        }
+        if let Some(page_size) = self.page_size {
+            pairs.push(("pageSize", page_size.to_string()));
+        }
+        pairs
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoCommentCreateRequestBody {
+    pub body: String,
+}
+
+impl TodoCommentCreateRequestBody {
+    /// リクエストボディを検証して、コメント本文に変換する。
+    pub fn into_body(self) -> DomainResult<CommentBody> {
+        let mut errors = FieldErrors::default();
+        let body = errors.check("body", CommentBody::new(self.body));
+        errors.into_result()?;
+        Ok(body.unwrap())
+    }
+}
@@ -0,0 +1,123 @@
+//! JSON Merge Patch（RFC 7396）で部分更新するリクエストボディの型
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{DomainErrorKind, DomainResult, domain_error};
+
+/// JSON Merge Patchのメディアタイプ
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+
+/// 部分更新するフィールドの値
+///
+/// フィールドが存在しない場合は`Absent`、`null`が指定された場合は`Null`になる。
+/// フィールドが存在しない場合に`Absent`にするため、フィールドには`#[serde(default)]`を指定すること。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    /// フィールドが存在しないため、値を変更しない。
+    #[default]
+    Absent,
+    /// `null`が指定されたため、値を消去する。
+    Null,
+    /// 値を更新する。
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    /// フィールドが存在しないかどうかを返す。
+    ///
+    /// シリアライズするときに、フィールドを省略するために`#[serde(skip_serializing_if = "Patch::is_absent")]`で使用する。
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Patch::Absent)
+    }
+
+    /// 値を消去できないフィールドの値を返す。
+    ///
+    /// `null`が指定された場合は検証エラーを返す。
+    pub fn required(self) -> DomainResult<Option<T>> {
+        match self {
+            Patch::Absent => Ok(None),
+            Patch::Null => Err(domain_error(
+                DomainErrorKind::Validation,
+                "must not be null",
+            )),
+            Patch::Value(value) => Ok(Some(value)),
+        }
+    }
+
+    /// 値を消去できるフィールドの値を返す。
+    ///
+    /// フィールドが存在しない場合は`None`、`null`が指定された場合は`Some(None)`を返す。
+    pub fn nullable(self) -> Option<Option<T>> {
+        match self {
+            Patch::Absent => None,
+            Patch::Null => Some(None),
+            Patch::Value(value) => Some(Some(value)),
+        }
+    }
+
+    /// `null`を、フィールドが存在しない場合と同様に扱う。
+    pub fn ignore_null(self) -> Self {
+        match self {
+            Patch::Null => Patch::Absent,
+            patch => patch,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Patch::Value(value),
+            None => Patch::Null,
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Absent | Patch::Null => serializer.serialize_none(),
+            Patch::Value(value) => serializer.serialize_some(value),
+        }
+    }
+}
+
+/// 部分更新するリクエストボディ
+pub trait PatchBody {
+    /// すべてのフィールドの`null`を、フィールドが存在しない場合と同様に扱う。
+    fn ignore_nulls(self) -> Self;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Body {
+        #[serde(default)]
+        name: Patch<String>,
+    }
+
+    #[rstest::rstest]
+    #[case(r#"{}"#, Patch::Absent)]
+    #[case(r#"{"name": null}"#, Patch::Null)]
+    #[case(r#"{"name": "foo"}"#, Patch::Value(String::from("foo")))]
+    fn deserialize_patch(#[case] json: &str, #[case] expected: Patch<String>) {
+        let body = serde_json::from_str::<Body>(json).unwrap();
+        assert_eq!(body.name, expected);
+    }
+
+    #[test]
+    fn required_patch_rejects_null() {
+        assert_eq!(Patch::<i32>::Absent.required().unwrap(), None);
+        assert_eq!(Patch::Value(1).required().unwrap(), Some(1));
+        let error = Patch::<i32>::Null.required().unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+    }
+
+    #[test]
+    fn nullable_patch_distinguishes_null_from_absent() {
+        assert_eq!(Patch::<i32>::Absent.nullable(), None);
+        assert_eq!(Patch::<i32>::Null.nullable(), Some(None));
+        assert_eq!(Patch::Value(1).nullable(), Some(Some(1)));
+    }
+}
@@ -0,0 +1,17 @@
+//! APIのリクエストボディ、レスポンスボディとパス
+//!
+//! サーバーのハンドラーとAPIクライアントの両方で使用するため、インフラストラクチャに依存しない型のみを定義する。
+mod admin;
+mod comment;
+mod merge_patch;
+mod password;
+pub mod paths;
+mod todo;
+mod user;
+
+pub use admin::*;
+pub use comment::*;
+pub use merge_patch::*;
+pub use password::*;
+pub use todo::*;
+pub use user::*;
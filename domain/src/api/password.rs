@@ -0,0 +1,36 @@
+//! パスワードポリシーの型
+use serde::{Deserialize, Serialize};
+
+/// パスワードに含める必要がある文字の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CharacterClass {
+    /// 大文字のアルファベット
+    Uppercase,
+    /// 小文字のアルファベット
+    Lowercase,
+    /// 数字
+    Digit,
+    /// 記号
+    Symbol,
+}
+
+/// クライアントに公開するパスワードポリシー
+///
+/// ペッパーとハッシュ化のパラメーターは、クライアントに公開しないため含めない。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordPolicy {
+    /// パスワードの最小文字数
+    pub min_length: usize,
+    /// パスワードの最大文字数
+    pub max_length: usize,
+    /// パスワードに含める必要がある文字の種類
+    pub required_character_classes: Vec<CharacterClass>,
+    /// パスワードに使用可能な記号
+    pub symbols: String,
+    /// パスワードに同じ文字を含めることができる文字数
+    pub max_same_chars: u64,
+    /// パスワードに同じ文字が連続して続く文字数
+    pub max_repeated_chars: u8,
+}
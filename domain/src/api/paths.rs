@@ -0,0 +1,132 @@
+//! APIのルートのパス
+//!
+//! サーバーのルーターへのルートの登録と、APIクライアントのリクエストの両方で使用する。
+//! 定数は`API_PREFIX`からの相対パスで、`{...}`を含む定数はルーターに登録するパステンプレートである。
+//! パステンプレートに値を埋め込んだパスは、同名の関数で構築する。
+use std::fmt::Display;
+
+/// APIのパスの接頭辞
+pub const API_PREFIX: &str = "/api/v1";
+
+/// メトリクス（`API_PREFIX`を付けずに配信する）
+pub const METRICS: &str = "/metrics";
+
+/// ヘルスチェック
+pub const HEALTH_CHECK: &str = "/health-check";
+
+/// ユーザー登録
+pub const USERS_SIGN_UP: &str = "/users/sign-up";
+/// パスワードポリシー
+pub const USERS_PASSWORD_POLICY: &str = "/users/password-policy";
+/// ログイン
+pub const USERS_LOGIN: &str = "/users/login";
+/// トークンのリフレッシュ
+pub const USERS_REFRESH_TOKENS: &str = "/users/refresh-tokens";
+/// ログインしているユーザー
+pub const USERS_ME: &str = "/users/me";
+/// Eメールアドレスの確認
+pub const USERS_ME_CONFIRM_EMAIL: &str = "/users/me/confirm-email";
+/// ユーザー設定
+pub const USERS_ME_PREFERENCES: &str = "/users/me/preferences";
+/// 日次ダイジェスト
+pub const USERS_ME_DIGEST: &str = "/users/me/digest";
+/// アカウントのエクスポート
+pub const USERS_ME_EXPORT: &str = "/users/me/export";
+/// アカウントのインポート
+pub const USERS_ME_IMPORT: &str = "/users/me/import";
+/// ログアウト
+pub const USERS_LOGOUT: &str = "/users/logout";
+/// すべてのセッションからのログアウト
+pub const USERS_ME_LOGOUT_ALL: &str = "/users/me/logout-all";
+
+/// Todoリスト
+pub const TODOS: &str = "/todos";
+/// Todo
+pub const TODO_BY_ID: &str = "/todos/{todo_id}";
+/// Todoの完了
+pub const TODO_COMPLETE: &str = "/todos/{todo_id}/complete";
+/// Todoの再オープン
+pub const TODO_REOPEN: &str = "/todos/{todo_id}/reopen";
+/// Todoのアーカイブ
+pub const TODO_ARCHIVE: &str = "/todos/{todo_id}/archive";
+/// Todoコメントリスト
+pub const TODO_COMMENTS: &str = "/todos/{todo_id}/comments";
+/// Todoコメント
+pub const TODO_COMMENT_BY_ID: &str = "/todos/{todo_id}/comments/{comment_id}";
+
+/// ユーザーへのなりすまし
+pub const ADMIN_IMPERSONATE: &str = "/admin/users/{user_id}/impersonate";
+/// Todoの移管
+pub const ADMIN_TODOS_TRANSFER: &str = "/admin/todos/transfer";
+/// サーバーの状態
+pub const ADMIN_STATUS: &str = "/admin/status";
+
+/// ロールリスト
+pub const ROLES: &str = "/roles";
+/// ロール
+pub const ROLE_BY_CODE: &str = "/roles/{code}";
+/// Todoステータスリスト
+pub const TODO_STATUSES: &str = "/todo-statuses";
+/// Todoステータス
+pub const TODO_STATUS_BY_CODE: &str = "/todo-statuses/{code}";
+
+/// Todoのパスを返す。
+pub fn todo_by_id(todo_id: impl Display) -> String {
+    TODO_BY_ID.replace("{todo_id}", &todo_id.to_string())
+}
+
+/// Todoを完了するパスを返す。
+pub fn todo_complete(todo_id: impl Display) -> String {
+    TODO_COMPLETE.replace("{todo_id}", &todo_id.to_string())
+}
+
+/// Todoを再オープンするパスを返す。
+pub fn todo_reopen(todo_id: impl Display) -> String {
+    TODO_REOPEN.replace("{todo_id}", &todo_id.to_string())
+}
+
+/// Todoをアーカイブするパスを返す。
+pub fn todo_archive(todo_id: impl Display) -> String {
+    TODO_ARCHIVE.replace("{todo_id}", &todo_id.to_string())
+}
+
+/// Todoコメントリストのパスを返す。
+pub fn todo_comments(todo_id: impl Display) -> String {
+    TODO_COMMENTS.replace("{todo_id}", &todo_id.to_string())
+}
+
+/// Todoコメントのパスを返す。
+pub fn todo_comment_by_id(todo_id: impl Display, comment_id: impl Display) -> String {
+    TODO_COMMENT_BY_ID
+        .replace("{todo_id}", &todo_id.to_string())
+        .replace("{comment_id}", &comment_id.to_string())
+}
+
+/// ユーザーになりすますパスを返す。
+pub fn admin_impersonate(user_id: impl Display) -> String {
+    ADMIN_IMPERSONATE.replace("{user_id}", &user_id.to_string())
+}
+
+/// ロールのパスを返す。
+pub fn role_by_code(code: impl Display) -> String {
+    ROLE_BY_CODE.replace("{code}", &code.to_string())
+}
+
+/// Todoステータスのパスを返す。
+pub fn todo_status_by_code(code: impl Display) -> String {
+    TODO_STATUS_BY_CODE.replace("{code}", &code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_fill_the_templates() {
+        assert_eq!(todo_by_id("abc"), "/todos/abc");
+        assert_eq!(todo_complete("abc"), "/todos/abc/complete");
+        assert_eq!(todo_comment_by_id("abc", 1), "/todos/abc/comments/1");
+        assert_eq!(admin_impersonate("u"), "/admin/users/u/impersonate");
+        assert_eq!(todo_status_by_code(2), "/todo-statuses/2");
+    }
+}
@@ -0,0 +1,306 @@
+//! Todoのリクエストボディとクエリパラメーター
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use time::Date;
+
+use utils::{
+    serde::{deserialize_option_date, deserialize_option_split_comma, serialize_option_date},
+    time::DATE_FORMAT,
+};
+
+use crate::{
+    DomainError, DomainResult, FieldErrors, NumericOperator,
+    api::{Patch, PatchBody},
+    models::{
+        TodoDescription, TodoStatusCode, TodoTitle, invalid_todo_status_code, validate_due_date,
+    },
+    repositories::{ArchivedFilter, TodoCreateInput, TodoListSort, TodoListView, TodoUpdateInput},
+};
+
+/// 次のページのTodoリストを取得するカーソルを格納するヘッダーの名前
+pub const X_NEXT_CURSOR: &str = "x-next-cursor";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoListQueryParams {
+    /// 検索キーワード
+    #[serde(default)]
+    pub keyword: Option<String>,
+    /// 完了予定日検索の演算子
+    #[serde(default)]
+    pub op: Option<NumericOperator>,
+    /// 完了予定日の開始日
+    #[serde(default)]
+    pub from: Option<Date>,
+    /// 完了予定日の終了日
+    #[serde(default)]
+    pub to: Option<Date>,
+    /// タスクのステータス
+    #[serde(default, deserialize_with = "deserialize_option_split_comma")]
+    pub statuses: Option<Vec<i16>>,
+    /// アーカイブ状態による絞り込み
+    ///
+    /// 指定されていない場合は、アーカイブされていないタスクのみを返す。
+    #[serde(default)]
+    pub archived: Option<ArchivedFilter>,
+    /// 表示範囲
+    ///
+    /// 指定されていない場合は、完了していないタスクのみを返す。`statuses`が指定された場合は、`statuses`で
+    /// 絞り込み、表示範囲は並び順にのみ影響する。
+    #[serde(default)]
+    pub view: Option<TodoListView>,
+    /// 並び順
+    #[serde(default)]
+    pub sort: Option<TodoListSort>,
+    /// ページ番号（1から始まる）
+    #[serde(default)]
+    pub page: Option<u32>,
+    /// 1ページあたりのTodo数
+    #[serde(default)]
+    pub page_size: Option<u32>,
+    /// 前のページのレスポンスの`X-Next-Cursor`ヘッダーに格納されたカーソル
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// カーソルで分割するときの1ページあたりのTodo数
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+impl TodoListQueryParams {
+    /// 指定されたクエリパラメーターを、名前と値の組で返す。
+    ///
+    /// 値はURLエンコードされていないため、URLに含める場合は呼び出し側でエンコードすること。
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+        if let Some(keyword) = &self.keyword {
+            pairs.push(("keyword", keyword.clone()));
+        }
+        if let Some(op) = self.op {
+            pairs.push(("op", op.to_string()));
+        }
+        if let Some(from) = self.from {
+            pairs.push(("from", from.format(&DATE_FORMAT).unwrap()));
+        }
+        if let Some(to) = self.to {
+            pairs.push(("to", to.format(&DATE_FORMAT).unwrap()));
+        }
+        if let Some(statuses) = &self.statuses {
+            pairs.push((
+                "statuses",
+                statuses
+                    .iter()
+                    .map(|status| status.to_string())
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ));
+        }
+        if let Some(archived) = self.archived {
+            pairs.push(("archived", archived.to_string()));
+        }
+        if let Some(view) = self.view {
+            pairs.push(("view", view.to_string()));
+        }
+        if let Some(sort) = self.sort {
+            pairs.push(("sort", sort.to_string()));
+        }
+        if let Some(page) = self.page {
+            pairs.push(("page", page.to_string()));
+        }
+        if let Some(page_size) = self.page_size {
+            pairs.push(("pageSize", page_size.to_string()));
+        }
+        if let Some(cursor) = &self.cursor {
+            pairs.push(("cursor", cursor.clone()));
+        }
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+        pairs
+    }
+}
+
+impl std::fmt::Display for TodoListQueryParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params = self
+            .query_pairs()
+            .into_iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<String>>();
+        write!(f, "{}", params.join("&"))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoCreateRequestBody {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_option_date")]
+    #[serde(deserialize_with = "deserialize_option_date")]
+    pub due_date: Option<Date>,
+    /// 完了しておらず、アーカイブされていないTodoにタイトルが同じTodoがあっても作成するかどうか
+    #[serde(default)]
+    pub allow_duplicate: bool,
+}
+
+impl TodoCreateRequestBody {
+    /// リクエストボディを検証して、Todo作成入力に変換する。
+    ///
+    /// # 引数
+    ///
+    /// * `today`: 完了予定日を検証するときの基準日
+    pub fn into_input(self, today: Date) -> DomainResult<TodoCreateInput> {
+        let mut errors = FieldErrors::default();
+        let title = errors.check("title", TodoTitle::new(self.title));
+        let description = errors.check(
+            "description",
+            self.description.map(TodoDescription::new).transpose(),
+        );
+        let due_date = errors.check(
+            "dueDate",
+            self.due_date
+                .map(|due_date| validate_due_date(due_date, today))
+                .transpose(),
+        );
+        errors.into_result()?;
+        Ok(TodoCreateInput {
+            title: title.unwrap(),
+            description: description.unwrap(),
+            due_date: due_date.unwrap(),
+        })
+    }
+}
+
+/// Todoを更新するリクエストボディ
+///
+/// JSON Merge Patchで`null`を指定した場合、説明と完了予定日は値を消去する。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoUpdateRequestBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    pub description: Patch<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<StatusCodeValue>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[serde(serialize_with = "serialize_patch_date")]
+    #[serde(deserialize_with = "deserialize_patch_date")]
+    pub due_date: Patch<Date>,
+}
+
+impl PatchBody for TodoUpdateRequestBody {
+    fn ignore_nulls(self) -> Self {
+        Self {
+            description: self.description.ignore_null(),
+            due_date: self.due_date.ignore_null(),
+            ..self
+        }
+    }
+}
+
+/// 部分更新する日付を`yyyy-mm-dd`形式でシリアライズする。
+fn serialize_patch_date<S>(patch: &Patch<Date>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match patch {
+        Patch::Value(date) => serialize_option_date(&Some(*date), serializer),
+        Patch::Absent | Patch::Null => serializer.serialize_none(),
+    }
+}
+
+/// `yyyy-mm-dd`形式の部分更新する日付をデシリアライズする。
+///
+/// フィールドが存在しない場合は呼び出されないため、`#[serde(default)]`で`Patch::Absent`にすること。
+fn deserialize_patch_date<'de, D>(deserializer: D) -> Result<Patch<Date>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match deserialize_option_date(deserializer)? {
+        Some(date) => Patch::Value(date),
+        None => Patch::Null,
+    })
+}
+
+impl TodoUpdateRequestBody {
+    /// リクエストボディを検証して、Todo更新入力に変換する。
+    ///
+    /// # 引数
+    ///
+    /// * `today`: 完了予定日を検証するときの基準日
+    pub fn into_input(self, today: Date) -> DomainResult<TodoUpdateInput> {
+        let mut errors = FieldErrors::default();
+        let title = errors.check("title", self.title.map(TodoTitle::new).transpose());
+        let description = errors.check(
+            "description",
+            self.description
+                .nullable()
+                .map(|value| value.map(TodoDescription::new).transpose())
+                .transpose(),
+        );
+        let status_code = errors.check(
+            "statusCode",
+            self.status_code.map(TodoStatusCode::try_from).transpose(),
+        );
+        let due_date = errors.check(
+            "dueDate",
+            self.due_date
+                .nullable()
+                .map(|value| {
+                    value
+                        .map(|due_date| validate_due_date(due_date, today))
+                        .transpose()
+                })
+                .transpose(),
+        );
+        errors.into_result()?;
+        Ok(TodoUpdateInput {
+            title: title.unwrap(),
+            description: description.unwrap(),
+            status_code: status_code.unwrap(),
+            due_date: due_date.unwrap(),
+        })
+    }
+}
+
+/// リクエストボディで受け取ったTodo状態コード
+///
+/// 任意の幅の整数や整数以外の値を受け取ってもデシリアライズに失敗させず、検証時に400 Bad Requestを返す。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StatusCodeValue {
+    /// 整数
+    Integer(i64),
+    /// 整数以外の値
+    Other(serde_json::Value),
+}
+
+impl From<TodoStatusCode> for StatusCodeValue {
+    fn from(code: TodoStatusCode) -> Self {
+        StatusCodeValue::Integer(code as i64)
+    }
+}
+
+impl TryFrom<StatusCodeValue> for TodoStatusCode {
+    type Error = DomainError;
+
+    fn try_from(value: StatusCodeValue) -> Result<Self, Self::Error> {
+        match value {
+            StatusCodeValue::Integer(value) => TodoStatusCode::try_from(value),
+            StatusCodeValue::Other(value) => Err(invalid_todo_status_code(value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoReopenRequestBody {
+    pub todo_status_code: TodoStatusCode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoArchiveRequestBody {
+    pub archived: bool,
+}
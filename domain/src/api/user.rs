@@ -0,0 +1,155 @@
+//! ユーザーのリクエストボディ、レスポンスボディとクエリパラメーター
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime, serde::rfc3339};
+
+use utils::{
+    serde::{SecretStringSerde, deserialize_option_date},
+    serde_with::serde_as,
+    time::DATE_FORMAT,
+};
+
+use crate::{
+    DomainError, DomainResult, FieldErrors,
+    api::{Patch, PatchBody},
+    models::{Email, FamilyName, GivenName, Todo, User},
+    repositories::UserInput,
+};
+
+#[serde_as(crate = "utils::serde_with")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignUpRequestBody {
+    pub family_name: String,
+    pub given_name: String,
+    pub email: String,
+    #[serde_as(as = "SecretStringSerde")]
+    pub password: SecretString,
+}
+
+impl TryFrom<SignUpRequestBody> for UserInput {
+    type Error = DomainError;
+
+    fn try_from(input: SignUpRequestBody) -> DomainResult<Self> {
+        let mut errors = FieldErrors::default();
+        let family_name = errors.check("familyName", FamilyName::new(input.family_name));
+        let given_name = errors.check("givenName", GivenName::new(input.given_name));
+        let email = errors.check("email", Email::new(input.email));
+        errors.into_result()?;
+        Ok(UserInput {
+            family_name: family_name.unwrap(),
+            given_name: given_name.unwrap(),
+            email: email.unwrap(),
+        })
+    }
+}
+
+#[serde_as(crate = "utils::serde_with")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginRequestBody {
+    pub email: String,
+    #[serde_as(as = "SecretStringSerde")]
+    pub password: SecretString,
+}
+
+#[serde_as(crate = "utils::serde_with")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginResponseBody {
+    #[serde_as(as = "SecretStringSerde")]
+    pub access_token: SecretString,
+    #[serde(with = "rfc3339")]
+    pub access_expired_at: OffsetDateTime,
+    #[serde_as(as = "SecretStringSerde")]
+    pub refresh_token: SecretString,
+    #[serde(with = "rfc3339")]
+    pub refresh_expired_at: OffsetDateTime,
+}
+
+/// ユーザーを更新するリクエストボディ
+///
+/// JSON Merge Patchで`null`を指定した場合、値を消去できないフィールドは検証エラーになる。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateUserRequestBody {
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    pub family_name: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    pub given_name: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    pub email: Patch<String>,
+    /// ロケール
+    ///
+    /// JSON Merge Patchで`null`を指定した場合は、ロケールを消去する。
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    pub locale: Patch<String>,
+}
+
+impl PatchBody for UpdateUserRequestBody {
+    fn ignore_nulls(self) -> Self {
+        Self {
+            family_name: self.family_name.ignore_null(),
+            given_name: self.given_name.ignore_null(),
+            email: self.email.ignore_null(),
+            locale: self.locale.ignore_null(),
+        }
+    }
+}
+
+#[serde_as(crate = "utils::serde_with")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmEmailRequestBody {
+    #[serde_as(as = "SecretStringSerde")]
+    pub token: SecretString,
+}
+
+#[serde_as(crate = "utils::serde_with")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshTokensRequestBody {
+    #[serde_as(as = "SecretStringSerde")]
+    pub refresh_token: SecretString,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestQueryParams {
+    /// 日次ダイジェストの対象日
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_option_date")]
+    pub date: Option<Date>,
+}
+
+impl DigestQueryParams {
+    /// 指定されたクエリパラメーターを、名前と値の組で返す。
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+        if let Some(date) = self.date {
+            pairs.push(("date", date.format(&DATE_FORMAT).unwrap()));
+        }
+        pairs
+    }
+}
+
+/// アカウントのバックアップ
+///
+/// エクスポートしたときのレスポンスボディと、インポートするときのリクエストボディに使用する。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBackupBody {
+    /// ユーザー
+    pub user: User,
+    /// アーカイブされたTodoを含むユーザーのすべてのTodo
+    pub todos: Vec<Todo>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResponseBody {
+    /// 登録したTodoの数
+    pub created: usize,
+    /// 重複していたためスキップしたTodoの数
+    pub skipped: usize,
+}
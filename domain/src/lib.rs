@@ -1,3 +1,4 @@
+pub mod api;
 pub mod models;
 pub mod repositories;
 
@@ -18,8 +19,12 @@ pub enum DomainErrorKind {
     Unauthorized,
     /// 禁止された操作
     Forbidden,
+    /// 一意であるべき値が重複している
+    Conflict,
     /// リポジトリエラー
     Repository,
+    /// 外部のサービスを一時的に利用できない
+    Unavailable,
     /// 予期しないエラー
     Unexpected,
 }
@@ -31,7 +36,9 @@ impl std::fmt::Display for DomainErrorKind {
             DomainErrorKind::NotFound => write!(f, "Not Found"),
             DomainErrorKind::Unauthorized => write!(f, "Unauthorized"),
             DomainErrorKind::Forbidden => write!(f, "Forbidden"),
+            DomainErrorKind::Conflict => write!(f, "Conflict"),
             DomainErrorKind::Repository => write!(f, "Repository Error"),
+            DomainErrorKind::Unavailable => write!(f, "Service Unavailable"),
             DomainErrorKind::Unexpected => write!(f, "Unexpected Error"),
         }
     }
@@ -59,9 +66,97 @@ pub fn domain_error(kind: DomainErrorKind, message: &'static str) -> DomainError
     }
 }
 
+/// リポジトリエラーのメッセージ
+///
+/// テーブル名や制約名などのSQLの詳細は`source`にのみ保持して、メッセージには含めない。
+pub const REPOSITORY_ERROR_MESSAGE: &str = "Internal server error";
+
+/// Eメールアドレスが既に登録されているときのメッセージ
+///
+/// サインアップ前の確認と、同時にサインアップしたときの一意制約違反の両方で同じメッセージを返す。
+pub const EMAIL_ALREADY_IN_USE_MESSAGE: &str = "The email address is already in use";
+
+/// SQLxのエラーをドメインエラーに変換する。
+///
+/// 一意制約違反は、インフラストラクチャのエラーではなく値の重複であるため、データベースエンジンにかかわらず
+/// `Conflict`エラーに変換する。
+/// それ以外のエラーは、`Repository`エラーに変換する。
+///
+/// 孤児ルールにより、`DomainError`を定義するこのクレートで実装する。
+impl From<sqlx::Error> for DomainError {
+    fn from(e: sqlx::Error) -> Self {
+        let conflict = e
+            .as_database_error()
+            .filter(|db_err| db_err.is_unique_violation())
+            .map(|db_err| conflict_message(db_err.constraint()));
+        if let Some(message) = conflict {
+            return DomainError {
+                kind: DomainErrorKind::Conflict,
+                messages: vec![message.into()],
+                source: e.into(),
+            };
+        }
+        DomainError {
+            kind: DomainErrorKind::Repository,
+            messages: vec![REPOSITORY_ERROR_MESSAGE.into()],
+            source: e.into(),
+        }
+    }
+}
+
+/// 一意制約の名前から、重複した値を示すメッセージを返す。
+///
+/// 制約名をクライアントに返さないように、既知の制約名のみをメッセージに変換する。
+fn conflict_message(constraint: Option<&str>) -> &'static str {
+    match constraint {
+        Some("idx_users_email") => EMAIL_ALREADY_IN_USE_MESSAGE,
+        _ => "The resource already exists",
+    }
+}
+
 /// ドメイン結果
 pub type DomainResult<T> = Result<T, DomainError>;
 
+/// リクエストの各フィールドの検証エラー
+///
+/// すべてのフィールドを検証して、検証に失敗したフィールドごとに`フィールド名: 理由`形式のメッセージを収集する。
+#[derive(Debug, Default)]
+pub struct FieldErrors {
+    messages: Vec<Cow<'static, str>>,
+}
+
+impl FieldErrors {
+    /// フィールドの検証結果を確認する。
+    ///
+    /// 検証に失敗した場合は、フィールド名と理由を記録して`None`を返す。
+    pub fn check<T>(&mut self, field: &str, result: DomainResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.messages.extend(
+                    e.messages
+                        .iter()
+                        .map(|reason| format!("{field}: {reason}").into()),
+                );
+                None
+            }
+        }
+    }
+
+    /// 検証エラーが記録されている場合は、すべてのメッセージを含む検証エラーを返す。
+    pub fn into_result(self) -> DomainResult<()> {
+        if self.messages.is_empty() {
+            return Ok(());
+        }
+        let source = anyhow::anyhow!(self.messages.join(", "));
+        Err(DomainError {
+            kind: DomainErrorKind::Validation,
+            messages: self.messages,
+            source,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumDisplay, Deserialize)]
 #[enum_display(case = "Snake")]
 #[serde(rename_all = "snake_case")]
@@ -108,11 +203,21 @@ impl FromStr for NumericOperator {
             "lte" => Ok(NumericOperator::Lte),
             "between" => Ok(NumericOperator::Between),
             "not_between" => Ok(NumericOperator::NotBetween),
+            "is_null" => Ok(NumericOperator::IsNull),
+            "is_not_null" => Ok(NumericOperator::IsNotNull),
             _ => Err(format!("Unknown numeric operator: {s}")),
         }
     }
 }
 
+impl TryFrom<&str> for NumericOperator {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 /// 数値フィルター
 #[derive(Debug, Clone, Copy)]
 pub struct NumericFilter<T>
@@ -213,6 +318,55 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn sqlx_error_is_converted_to_repository_error() {
+        let error = DomainError::from(sqlx::Error::RowNotFound);
+        assert_eq!(error.kind, DomainErrorKind::Repository);
+        assert_eq!(error.messages, vec![REPOSITORY_ERROR_MESSAGE]);
+        assert!(error.source.downcast_ref::<sqlx::Error>().is_some());
+    }
+
+    #[rstest::rstest]
+    #[case(Some("idx_users_email"), EMAIL_ALREADY_IN_USE_MESSAGE)]
+    #[case(Some("todos_pkey"), "The resource already exists")]
+    #[case(None, "The resource already exists")]
+    fn conflict_message_from_constraint(#[case] constraint: Option<&str>, #[case] expected: &str) {
+        assert_eq!(conflict_message(constraint), expected);
+    }
+
+    #[test]
+    fn field_errors_collect_every_violation() {
+        let mut errors = FieldErrors::default();
+        let first = errors.check::<i32>(
+            "familyName",
+            Err(domain_error(DomainErrorKind::Validation, "too short")),
+        );
+        let second = errors.check("givenName", Ok::<_, DomainError>(1));
+        let third = errors.check::<i32>(
+            "email",
+            Err(domain_error(DomainErrorKind::Validation, "not an email")),
+        );
+        assert!(first.is_none());
+        assert_eq!(second, Some(1));
+        assert!(third.is_none());
+        let error = errors.into_result().unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+        assert_eq!(
+            error.messages,
+            vec!["familyName: too short", "email: not an email"]
+        );
+    }
+
+    #[test]
+    fn field_errors_ok_when_no_violation() {
+        let mut errors = FieldErrors::default();
+        assert_eq!(
+            errors.check("title", Ok::<_, DomainError>("title")),
+            Some("title")
+        );
+        assert!(errors.into_result().is_ok());
+    }
+
     #[rstest::rstest]
     #[case(" leading space", true)]
     #[case("trailing space ", true)]
@@ -233,6 +387,8 @@ mod tests {
     #[case(NumericOperator::Lte, "<=")]
     #[case(NumericOperator::Between, "BETWEEN")]
     #[case(NumericOperator::NotBetween, "NOT BETWEEN")]
+    #[case(NumericOperator::IsNull, "IS NULL")]
+    #[case(NumericOperator::IsNotNull, "IS NOT NULL")]
     fn numeric_operator_sql_ok(#[case] op: NumericOperator, #[case] expected: &str) {
         assert_eq!(op.sql(), expected);
     }
@@ -246,9 +402,49 @@ mod tests {
     #[case("lte", NumericOperator::Lte)]
     #[case("between", NumericOperator::Between)]
     #[case("not_between", NumericOperator::NotBetween)]
+    #[case("is_null", NumericOperator::IsNull)]
+    #[case("is_not_null", NumericOperator::IsNotNull)]
     fn numeric_operator_from_str_ok(#[case] op: &str, #[case] expected: NumericOperator) {
         let actual = NumericOperator::from_str(op).unwrap();
         assert_eq!(actual, expected);
+        let actual = NumericOperator::try_from(op).unwrap();
+        assert_eq!(actual, expected);
+        let actual = NumericOperator::try_from(op.to_uppercase().as_str()).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest::rstest]
+    #[case("")]
+    #[case("equal")]
+    #[case("is null")]
+    #[case("not-between")]
+    fn numeric_operator_try_from_err(#[case] op: &str) {
+        let err = NumericOperator::try_from(op).unwrap_err();
+        assert_eq!(err, format!("Unknown numeric operator: {op}"));
+        assert_eq!(NumericOperator::from_str(op).unwrap_err(), err);
+    }
+
+    #[rstest::rstest]
+    fn numeric_operator_display_and_from_str_round_trip(
+        #[values(
+            NumericOperator::Eq,
+            NumericOperator::Ne,
+            NumericOperator::Gt,
+            NumericOperator::Gte,
+            NumericOperator::Lt,
+            NumericOperator::Lte,
+            NumericOperator::Between,
+            NumericOperator::NotBetween,
+            NumericOperator::IsNull,
+            NumericOperator::IsNotNull
+        )]
+        op: NumericOperator,
+    ) {
+        let displayed = op.to_string();
+        assert_eq!(NumericOperator::from_str(&displayed).unwrap(), op);
+        let deserialized =
+            serde_json::from_str::<NumericOperator>(&format!("\"{displayed}\"")).unwrap();
+        assert_eq!(deserialized, op);
     }
 
     #[rstest::rstest]
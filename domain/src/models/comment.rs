@@ -0,0 +1,50 @@
+use garde::Validate as _;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::impl_string_primitive;
+use crate::models::primitives::Id;
+use crate::models::{TodoId, UserId};
+
+/// Todoコメント ID
+pub type TodoCommentId = Id<TodoComment>;
+
+/// コメント本文
+#[derive(Debug, Clone, garde::Validate)]
+pub struct CommentBody(#[garde(length(chars, min = 1, max = 1000))] pub String);
+impl_string_primitive!(CommentBody);
+
+/// Todoコメント
+///
+/// Todoの説明を編集せずに、Todoにメモを追記するために使用する。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoComment {
+    /// ID
+    pub id: TodoCommentId,
+    /// コメントしたTodoのID
+    pub todo_id: TodoId,
+    /// コメントしたユーザーのID
+    pub user_id: UserId,
+    /// 本文
+    pub body: CommentBody,
+    /// 作成日時
+    #[serde(with = "utils::serde::timestamp")]
+    pub created_at: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rstest::rstest]
+    #[case("a", true)]
+    #[case(&"a".repeat(1000), true)]
+    #[case(&"あ".repeat(1000), true)]
+    #[case("", false)]
+    #[case(" ", false)]
+    #[case(&"a".repeat(1001), false)]
+    fn comment_body_length(#[case] value: &str, #[case] expected: bool) {
+        assert_eq!(CommentBody::new(value.to_string()).is_ok(), expected);
+    }
+}
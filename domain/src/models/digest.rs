@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+use utils::serde::{deserialize_date, serialize_date};
+
+use crate::models::{TodoId, TodoTitle, UserId};
+
+/// ダイジェストに含めるTodo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestTodo {
+    /// ID
+    pub id: TodoId,
+    /// タイトル
+    pub title: TodoTitle,
+    /// 完了予定日
+    #[serde(serialize_with = "serialize_date")]
+    #[serde(deserialize_with = "deserialize_date")]
+    pub due_date: Date,
+}
+
+/// ユーザーごとの日次ダイジェスト
+///
+/// ダイジェストの対象日に完了予定日を迎える未完了のTodoと、完了予定日を過ぎた未完了のTodoをまとめる。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDigest {
+    /// ユーザーID
+    pub user_id: UserId,
+    /// ダイジェストの対象日
+    #[serde(serialize_with = "serialize_date")]
+    #[serde(deserialize_with = "deserialize_date")]
+    pub digest_date: Date,
+    /// 対象日に完了予定日を迎えるTodo
+    pub due_today: Vec<DigestTodo>,
+    /// 対象日より前に完了予定日を過ぎたTodo
+    pub overdue: Vec<DigestTodo>,
+}
+
+impl UserDigest {
+    /// 対象日の空のダイジェストを構築する。
+    pub fn new(user_id: UserId, digest_date: Date) -> Self {
+        Self {
+            user_id,
+            digest_date,
+            due_today: vec![],
+            overdue: vec![],
+        }
+    }
+
+    /// Todoを完了予定日に応じて、対象日に完了予定日を迎えるTodoか、完了予定日を過ぎたTodoに追加する。
+    ///
+    /// 完了予定日が対象日より後のTodoは追加しない。
+    pub fn push(&mut self, todo: DigestTodo) {
+        if todo.due_date == self.digest_date {
+            self.due_today.push(todo);
+        } else if todo.due_date < self.digest_date {
+            self.overdue.push(todo);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    fn digest_todo(title: &str, due_date: Date) -> DigestTodo {
+        DigestTodo {
+            id: TodoId::default(),
+            title: TodoTitle::new(title.to_string()).unwrap(),
+            due_date,
+        }
+    }
+
+    #[test]
+    fn user_digest_push_classifies_todos_by_due_date() {
+        let mut digest = UserDigest::new(UserId::default(), date!(2025 - 06 - 19));
+        digest.push(digest_todo("today", date!(2025 - 06 - 19)));
+        digest.push(digest_todo("overdue", date!(2025 - 06 - 12)));
+        digest.push(digest_todo("future", date!(2025 - 06 - 20)));
+        assert_eq!(digest.due_today.len(), 1);
+        assert_eq!(digest.due_today[0].title.0, "today");
+        assert_eq!(digest.overdue.len(), 1);
+        assert_eq!(digest.overdue[0].title.0, "overdue");
+    }
+
+    #[test]
+    fn user_digest_serializes_dates_as_plain_dates() {
+        let mut digest = UserDigest::new(UserId::default(), date!(2025 - 06 - 19));
+        digest.push(digest_todo("today", date!(2025 - 06 - 19)));
+        let value = serde_json::to_value(&digest).unwrap();
+        assert_eq!(value["digestDate"], "2025-06-19");
+        assert_eq!(value["dueToday"][0]["dueDate"], "2025-06-19");
+        assert_eq!(serde_json::from_value::<UserDigest>(value).unwrap(), digest);
+    }
+}
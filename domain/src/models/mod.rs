@@ -1,7 +1,11 @@
+mod comment;
+mod digest;
 pub mod primitives;
 mod todo;
 mod user;
 
+pub use comment::*;
+pub use digest::*;
 pub use todo::*;
 pub use user::*;
 
@@ -19,10 +23,24 @@ macro_rules! sqlx_encode_value {
             }
         }
 
+        impl<'r> sqlx::Decode<'r, sqlx::Postgres> for $name {
+            fn decode(
+                value: sqlx::postgres::PgValueRef<'r>,
+            ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+                // 範囲外のコードはパニックせずにデコードエラーとして返す
+                let code = <$raw_ty as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+                Ok($name::try_from(code)?)
+            }
+        }
+
         impl sqlx::Type<sqlx::Postgres> for $name {
             fn type_info() -> sqlx::postgres::PgTypeInfo {
                 sqlx::postgres::PgTypeInfo::with_oid(sqlx::postgres::types::Oid($oid))
             }
+
+            fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+                <$raw_ty as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+            }
         }
     };
 }
@@ -31,6 +31,18 @@ impl<T> PartialEq for Id<T> {
 
 impl<T> Eq for Id<T> {}
 
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
 impl<T> PartialEq<Uuid> for Id<T> {
     fn eq(&self, other: &Uuid) -> bool {
         self.0 == *other
@@ -83,7 +95,10 @@ macro_rules! impl_string_primitive {
                     Ok(_) => Ok(value),
                     Err(e) => Err($crate::DomainError {
                         kind: $crate::DomainErrorKind::Validation,
-                        messages: vec![e.to_string().into()],
+                        messages: e
+                            .iter()
+                            .map(|(_, error)| error.to_string().into())
+                            .collect(),
                         source: e.into(),
                     }),
                 }
@@ -147,9 +162,40 @@ macro_rules! impl_string_primitive {
     };
 }
 
+/// 整数をラップしたプリミティブを実装する。
+///
+/// 既定では、デシリアライズするときに値を検証する。`lenient`を指定した場合は、データベースに保存された範囲外の値を
+/// 含むレスポンスを受け付けられるように、デシリアライズするときに値を検証しない。
 #[macro_export]
 macro_rules! impl_int_primitive {
     ($name:ident, $ty:ty) => {
+        $crate::impl_int_primitive!(@common $name, $ty);
+
+        impl<'de> serde::de::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let value: $ty = <$ty>::deserialize(deserializer)?;
+                $name::new(value).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+
+    ($name:ident, $ty:ty, lenient) => {
+        $crate::impl_int_primitive!(@common $name, $ty);
+
+        impl<'de> serde::de::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                <$ty>::deserialize(deserializer).map(Self)
+            }
+        }
+    };
+
+    (@common $name:ident, $ty:ty) => {
         impl $name {
             pub fn new(value: $ty) -> $crate::DomainResult<Self> {
                 let value = Self(value);
@@ -157,7 +203,10 @@ macro_rules! impl_int_primitive {
                     Ok(_) => Ok(value),
                     Err(e) => Err($crate::DomainError {
                         kind: $crate::DomainErrorKind::Validation,
-                        messages: vec![e.to_string().into()],
+                        messages: e
+                            .iter()
+                            .map(|(_, error)| error.to_string().into())
+                            .collect(),
                         source: e.into(),
                     }),
                 }
@@ -205,31 +254,7 @@ macro_rules! impl_int_primitive {
             where
                 S: serde::ser::Serializer,
             {
-                match std::any::type_name_of_val(&self.0) {
-                    "i16" => serializer
-                        .serialize_i16(self.0.try_into().map_err(serde::ser::Error::custom)?),
-                    "i32" => serializer
-                        .serialize_i32(self.0.try_into().map_err(serde::ser::Error::custom)?),
-                    "i64" => serializer
-                        .serialize_i64(self.0.try_into().map_err(serde::ser::Error::custom)?),
-                    "u16" => serializer
-                        .serialize_u16(self.0.try_into().map_err(serde::ser::Error::custom)?),
-                    "u32" => serializer
-                        .serialize_u32(self.0.try_into().map_err(serde::ser::Error::custom)?),
-                    "u64" => serializer
-                        .serialize_u64(self.0.try_into().map_err(serde::ser::Error::custom)?),
-                    _ => Err(serde::ser::Error::custom("Unsupported integer type")),
-                }
-            }
-        }
-
-        impl<'de> serde::de::Deserialize<'de> for $name {
-            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                let value: $ty = <$ty>::deserialize(deserializer)?;
-                $name::new(value).map_err(serde::de::Error::custom)
+                <$ty as serde::ser::Serialize>::serialize(&self.0, serializer)
             }
         }
     };
@@ -241,9 +266,11 @@ pub struct Description(#[garde(length(chars, min = 1, max = 255))] pub String);
 impl_string_primitive!(Description);
 
 /// 表示順
+///
+/// 表示順はデータベースに保存された値をそのまま返すため、範囲外の値を含むレスポンスも受け付ける。
 #[derive(Debug, Clone, garde::Validate)]
 pub struct DisplayOrder(#[garde(range(min=1,max=i16::MAX))] pub i16);
-impl_int_primitive!(DisplayOrder, i16);
+impl_int_primitive!(DisplayOrder, i16, lenient);
 
 #[cfg(test)]
 mod tests {
@@ -293,6 +320,33 @@ mod tests {
         assert_eq!(map.get(&key2).unwrap(), "value2");
     }
 
+    #[test]
+    fn id_sort_ok() {
+        let id1 = Id::<i32>::from(Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap());
+        let id2 = Id::<i32>::from(Uuid::parse_str("7f000000-0000-0000-0000-000000000000").unwrap());
+        let id3 = Id::<i32>::from(Uuid::parse_str("ffffffff-ffff-ffff-ffff-ffffffffffff").unwrap());
+        let mut ids = vec![id3, id1, id2];
+        ids.sort();
+        assert_eq!(ids, vec![id1, id2, id3]);
+        assert!(id1 < id2);
+        assert!(id3 > id2);
+    }
+
+    #[test]
+    fn id_btree_map_key_ok() {
+        use std::collections::BTreeMap;
+        let key1 =
+            Id::<i32>::from(Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap());
+        let key2 =
+            Id::<i32>::from(Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap());
+        let mut map = BTreeMap::new();
+        map.insert(key2, String::from("value2"));
+        map.insert(key1, String::from("value1"));
+        assert_eq!(map.get(&key1).unwrap(), "value1");
+        assert_eq!(map.get(&key2).unwrap(), "value2");
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![key1, key2]);
+    }
+
     #[test]
     fn id_display_ok() {
         let id = Id::<String>::default();
@@ -383,7 +437,57 @@ mod tests {
         assert_eq!(primitive.0, "test");
     }
 
+    #[derive(Debug, Clone, garde::Validate)]
+    struct I16Primitive(#[garde(range(min = 1, max = 10))] i16);
+    impl_int_primitive!(I16Primitive, i16);
+
     #[derive(Debug, Clone, garde::Validate)]
     struct I32Primitive(#[garde(range(min = 1, max = 10))] i32);
     impl_int_primitive!(I32Primitive, i32);
+
+    #[derive(Debug, Clone, garde::Validate)]
+    struct I64Primitive(#[garde(range(min = 1, max = 10))] i64);
+    impl_int_primitive!(I64Primitive, i64);
+
+    #[test]
+    fn int_primitive_serialize_as_plain_integer() {
+        assert_eq!(
+            serde_json::to_string(&I16Primitive::new(3).unwrap()).unwrap(),
+            "3"
+        );
+        assert_eq!(
+            serde_json::to_string(&I32Primitive::new(5).unwrap()).unwrap(),
+            "5"
+        );
+        assert_eq!(
+            serde_json::to_string(&I64Primitive::new(10).unwrap()).unwrap(),
+            "10"
+        );
+    }
+
+    #[test]
+    fn int_primitive_deserialize_validates_the_value() {
+        assert_eq!(serde_json::from_str::<I16Primitive>("3").unwrap(), 3);
+        assert_eq!(serde_json::from_str::<I32Primitive>("5").unwrap(), 5);
+        assert_eq!(serde_json::from_str::<I64Primitive>("10").unwrap(), 10);
+        assert!(serde_json::from_str::<I16Primitive>("0").is_err());
+        assert!(serde_json::from_str::<I32Primitive>("11").is_err());
+        assert!(serde_json::from_str::<I64Primitive>("-1").is_err());
+    }
+
+    #[test]
+    fn int_primitive_new_rejects_out_of_range_values() {
+        assert!(I32Primitive::new(0).is_err());
+        assert!(I32Primitive::try_from(11).is_err());
+        assert_eq!(I32Primitive::new(1).unwrap().to_string(), "1");
+    }
+
+    #[test]
+    fn display_order_deserialize_accepts_out_of_range_values() {
+        let display_order = serde_json::from_str::<DisplayOrder>("0").unwrap();
+        assert_eq!(display_order, 0);
+        assert_eq!(serde_json::to_string(&display_order).unwrap(), "0");
+        // APIの入力として検証する場合は、範囲外の値を拒否する
+        assert!(DisplayOrder::new(0).is_err());
+    }
 }
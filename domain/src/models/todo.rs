@@ -4,9 +4,12 @@ use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use time::{Date, OffsetDateTime};
 
-use utils::serde::{
-    deserialize_option_date, deserialize_option_offset_datetime, serialize_option_date,
-    serialize_option_offset_datetime,
+use utils::{
+    serde::{
+        deserialize_option_date, deserialize_option_offset_datetime, serialize_option_date,
+        serialize_option_offset_datetime,
+    },
+    time::DATE_FORMAT,
 };
 
 use crate::models::primitives::{Description, DisplayOrder, Id};
@@ -58,11 +61,60 @@ impl TryFrom<i16> for TodoStatusCode {
             3 => Ok(TodoStatusCode::Completed),
             4 => Ok(TodoStatusCode::Cancelled),
             5 => Ok(TodoStatusCode::OnHold),
-            _ => Err(domain_error(
-                DomainErrorKind::Validation,
-                "Invalid todo status code",
-            )),
+            _ => Err(invalid_todo_status_code(value)),
+        }
+    }
+}
+
+impl TryFrom<i64> for TodoStatusCode {
+    type Error = DomainError;
+
+    /// `i16`に変換する前に範囲を確認するため、`65537`のような値が`1`に切り詰められることはない。
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        let value = i16::try_from(value).map_err(|_| invalid_todo_status_code(value))?;
+        TodoStatusCode::try_from(value)
+    }
+}
+
+impl TryFrom<String> for TodoStatusCode {
+    type Error = DomainError;
+
+    /// 数値（`"1"`など）、または`Display`が出力するスネークケースの名前（`"not_started"`など）から変換する。
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim();
+        if let Ok(code) = value.parse::<i64>() {
+            return TodoStatusCode::try_from(code);
         }
+        TodoStatusCode::ALL
+            .into_iter()
+            .find(|code| code.to_string() == value)
+            .ok_or_else(|| invalid_todo_status_code(format!("\"{value}\"")))
+    }
+}
+
+impl TodoStatusCode {
+    /// すべてのTodo状態コード
+    pub const ALL: [TodoStatusCode; 5] = [
+        TodoStatusCode::NotStarted,
+        TodoStatusCode::InProgress,
+        TodoStatusCode::Completed,
+        TodoStatusCode::Cancelled,
+        TodoStatusCode::OnHold,
+    ];
+}
+
+/// 有効なTodo状態コードとその名前を列挙した検証エラーを返す。
+pub fn invalid_todo_status_code<T: std::fmt::Display>(value: T) -> DomainError {
+    let allowed = TodoStatusCode::ALL
+        .iter()
+        .map(|code| format!("{} ({})", *code as i16, code))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let message = format!("Invalid todo status code: {value}, allowed values are {allowed}");
+    DomainError {
+        kind: DomainErrorKind::Validation,
+        messages: vec![message.clone().into()],
+        source: anyhow::anyhow!(message),
     }
 }
 
@@ -73,6 +125,63 @@ sqlx_encode_value!(TodoStatusCode, i16, 21);
 pub const COMPLETABLE_TODO_STATUS_CODES: [TodoStatusCode; 2] =
     [TodoStatusCode::NotStarted, TodoStatusCode::InProgress];
 
+/// 完了予定日として許容する、基準日より前の年数
+pub const DUE_DATE_MAX_YEARS_IN_PAST: i32 = 1;
+
+/// 完了予定日として許容する、基準日より後の年数
+pub const DUE_DATE_MAX_YEARS_IN_FUTURE: i32 = 50;
+
+/// 完了予定日として許容する範囲を返す。
+///
+/// 基準日が2月29日で、範囲の境界が閏年でない場合、境界は2月28日になる。
+///
+/// # 引数
+///
+/// * `today`: 基準日
+///
+/// # 戻り値
+///
+/// 許容する最も前の完了予定日と、最も後の完了予定日
+pub fn due_date_range(today: Date) -> (Date, Date) {
+    let shift_years = |years: i32| {
+        let year = today.year() + years;
+        today
+            .replace_year(year)
+            .or_else(|_| Date::from_calendar_date(year, today.month(), 28))
+            .unwrap()
+    };
+    (
+        shift_years(-DUE_DATE_MAX_YEARS_IN_PAST),
+        shift_years(DUE_DATE_MAX_YEARS_IN_FUTURE),
+    )
+}
+
+/// 完了予定日が、基準日から許容する範囲内にあるか検証する。
+///
+/// 年の入力を誤った完了予定日を登録しないように、完了予定日は基準日の`DUE_DATE_MAX_YEARS_IN_PAST`年前から、
+/// `DUE_DATE_MAX_YEARS_IN_FUTURE`年後までの範囲でなければならない。
+///
+/// # 引数
+///
+/// * `due_date`: 完了予定日
+/// * `today`: 基準日
+pub fn validate_due_date(due_date: Date, today: Date) -> DomainResult<Date> {
+    let (min, max) = due_date_range(today);
+    if min <= due_date && due_date <= max {
+        return Ok(due_date);
+    }
+    let message = format!(
+        "must be between {} and {}",
+        min.format(&DATE_FORMAT).unwrap(),
+        max.format(&DATE_FORMAT).unwrap()
+    );
+    Err(DomainError {
+        kind: DomainErrorKind::Validation,
+        messages: vec![message.clone().into()],
+        source: anyhow::anyhow!(message),
+    })
+}
+
 /// Todo状態名
 #[derive(Debug, Clone, garde::Validate)]
 pub struct TodoStatusName(#[garde(length(chars, min = 1, max = 50))] pub String);
@@ -103,10 +212,10 @@ pub struct Todo {
     /// アーカイブ済み
     pub archived: bool,
     /// 作成日時
-    #[serde(with = "time::serde::rfc3339")]
+    #[serde(with = "utils::serde::timestamp")]
     pub created_at: OffsetDateTime,
     /// 更新日時
-    #[serde(with = "time::serde::rfc3339")]
+    #[serde(with = "utils::serde::timestamp")]
     pub updated_at: OffsetDateTime,
 }
 
@@ -151,7 +260,7 @@ impl Todo {
     ///   - 完了後は、更新できないため。
     /// - 完了したTodoは更新できない。
     /// - アーカイブされたTodoは、更新できない。
-    fn validate(&self) -> DomainResult<()> {
+    pub fn validate(&self) -> DomainResult<()> {
         // 作成日時は更新日時と同じか、更新日時よりも前でなくてはならない。
         if self.created_at > self.updated_at {
             return Err(domain_error(
@@ -191,6 +300,60 @@ impl Todo {
 
         Ok(())
     }
+
+    /// Todoを完了できるか確認する。
+    ///
+    /// 未着手または進行中で、アーカイブされていないTodoのみ完了できる。
+    pub fn ensure_completable(&self) -> DomainResult<()> {
+        if !COMPLETABLE_TODO_STATUS_CODES.contains(&self.status.code) || self.archived {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "Only todos with status 'NotStarted' or 'InProgress' can be completed, and archived todos cannot be completed",
+            ));
+        }
+        Ok(())
+    }
+
+    /// 完了したTodoを再開できるか確認する。
+    ///
+    /// 完了していて、アーカイブされていないTodoのみ再開できる。
+    pub fn ensure_reopenable(&self) -> DomainResult<()> {
+        if self.status.code != TodoStatusCode::Completed {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "Only completed todos can be reopened",
+            ));
+        }
+        if self.archived {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "Archived todos cannot be reopened",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Todoのアーカイブ状態を変更できるか確認する。
+    ///
+    /// アーカイブする場合はアーカイブされていないTodo、アーカイブを解除する場合はアーカイブされたTodoのみ変更できる。
+    ///
+    /// # 引数
+    ///
+    /// * `archived`: 変更後のアーカイブ状態
+    pub fn ensure_archivable(&self, archived: bool) -> DomainResult<()> {
+        if archived && self.archived {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "Todo is already archived",
+            ));
+        } else if !archived && !self.archived {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "Todo is not archived",
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// Todo状態
@@ -206,13 +369,28 @@ pub struct TodoStatus {
     /// Todo状態の順序
     pub display_order: DisplayOrder,
     /// Todo状態の作成日時
-    #[serde(with = "time::serde::rfc3339")]
+    #[serde(with = "utils::serde::timestamp")]
     pub created_at: OffsetDateTime,
     /// Todo状態の更新日時
-    #[serde(with = "time::serde::rfc3339")]
+    #[serde(with = "utils::serde::timestamp")]
     pub updated_at: OffsetDateTime,
 }
 
+/// Todo状態はコードで識別するため、コードのみを比較する。
+impl PartialEq for TodoStatus {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code
+    }
+}
+
+impl Eq for TodoStatus {}
+
+impl std::hash::Hash for TodoStatus {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.code.hash(state);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,7 +398,10 @@ mod tests {
         Role, RoleCode, RoleName,
         user::{Email, FamilyName, GivenName, UserId},
     };
-    use time::{Duration, macros::datetime};
+    use time::{
+        Duration,
+        macros::{date, datetime},
+    };
     use uuid::Uuid;
 
     fn create_user() -> User {
@@ -239,6 +420,9 @@ mod tests {
             },
             active: true,
             last_login_at: None,
+            locked_at: None,
+            lock_reason: None,
+            locale: None,
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
         }
@@ -340,4 +524,133 @@ mod tests {
             assert!(result.is_err());
         }
     }
+
+    fn create_todo_status(code: TodoStatusCode, name: &str) -> TodoStatus {
+        TodoStatus {
+            code,
+            name: TodoStatusName(name.to_string()),
+            description: None,
+            display_order: DisplayOrder(1),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn hash_of<T: std::hash::Hash>(value: &T) -> u64 {
+        use std::hash::{DefaultHasher, Hasher as _};
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn todo_status_hash_by_code() {
+        let status1 = create_todo_status(TodoStatusCode::NotStarted, "未着手");
+        let status2 = create_todo_status(TodoStatusCode::NotStarted, "別名");
+        let status3 = create_todo_status(TodoStatusCode::Completed, "完了");
+        assert_eq!(status1, status2);
+        assert_eq!(hash_of(&status1), hash_of(&status2));
+        assert_ne!(status1, status3);
+    }
+
+    #[test]
+    fn todo_status_hash_set_deduplicates_by_code() {
+        use std::collections::HashSet;
+        let statuses = HashSet::from([
+            create_todo_status(TodoStatusCode::NotStarted, "未着手"),
+            create_todo_status(TodoStatusCode::NotStarted, "別名"),
+            create_todo_status(TodoStatusCode::InProgress, "進行中"),
+        ]);
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses.contains(&create_todo_status(TodoStatusCode::InProgress, "any")));
+    }
+
+    #[rstest::rstest]
+    #[case(TodoStatusCode::NotStarted, "not_started")]
+    #[case(TodoStatusCode::InProgress, "in_progress")]
+    #[case(TodoStatusCode::Completed, "completed")]
+    #[case(TodoStatusCode::Cancelled, "cancelled")]
+    #[case(TodoStatusCode::OnHold, "on_hold")]
+    fn todo_status_code_display_and_try_from_string(
+        #[case] code: TodoStatusCode,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(code.to_string(), expected);
+        assert_eq!(TodoStatusCode::try_from(code.to_string()).unwrap(), code);
+        assert_eq!(
+            TodoStatusCode::try_from((code as i16).to_string()).unwrap(),
+            code
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("")]
+    #[case("0")]
+    #[case("6")]
+    #[case("-1")]
+    #[case("65537")]
+    #[case("1.5")]
+    #[case("abc")]
+    #[case("NotStarted")]
+    fn todo_status_code_try_from_invalid_string(#[case] value: &str) {
+        let error = TodoStatusCode::try_from(value.to_string()).unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+        assert!(
+            error.messages[0].contains("Invalid todo status code"),
+            "{:?}",
+            error.messages
+        );
+    }
+
+    #[rstest::rstest]
+    #[case(1, Some(TodoStatusCode::NotStarted))]
+    #[case(5, Some(TodoStatusCode::OnHold))]
+    #[case(0, None)]
+    #[case(6, None)]
+    #[case(-1, None)]
+    #[case(32768, None)]
+    // 65537は`i16`に切り詰めると1になる
+    #[case(65537, None)]
+    fn todo_status_code_try_from_i64(#[case] value: i64, #[case] expected: Option<TodoStatusCode>) {
+        match (TodoStatusCode::try_from(value), expected) {
+            (Ok(code), Some(expected)) => assert_eq!(code, expected),
+            (Err(e), None) => {
+                assert_eq!(e.kind, DomainErrorKind::Validation);
+                let message = e.messages[0].to_string();
+                assert!(message.contains(&value.to_string()), "{message}");
+                assert!(message.contains("1 (not_started)"), "{message}");
+                assert!(message.contains("5 (on_hold)"), "{message}");
+            }
+            (result, expected) => {
+                panic!("{:?} != {:?}", result.map_err(|e| e.to_string()), expected)
+            }
+        }
+    }
+
+    #[rstest::rstest]
+    #[case(date!(2024 - 06 - 15), true)]
+    #[case(date!(2024 - 06 - 14), false)]
+    #[case(date!(2075 - 06 - 15), true)]
+    #[case(date!(2075 - 06 - 16), false)]
+    fn validate_due_date_at_boundaries(#[case] due_date: Date, #[case] valid: bool) {
+        let today = date!(2025 - 06 - 15);
+        match validate_due_date(due_date, today) {
+            Ok(validated) => {
+                assert!(valid, "{due_date} must be rejected");
+                assert_eq!(validated, due_date);
+            }
+            Err(e) => {
+                assert!(!valid, "{due_date} must be accepted");
+                assert_eq!(e.kind, DomainErrorKind::Validation);
+                assert_eq!(e.messages[0], "must be between 2024-06-15 and 2075-06-15");
+            }
+        }
+    }
+
+    #[test]
+    fn due_date_range_from_leap_day() {
+        let (min, max) = due_date_range(date!(2024 - 02 - 29));
+        assert_eq!(min, date!(2023 - 02 - 28));
+        assert_eq!(max, date!(2074 - 02 - 28));
+    }
 }
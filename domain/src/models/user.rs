@@ -1,3 +1,5 @@
+use std::{collections::HashSet, str::FromStr};
+
 use enum_display::EnumDisplay;
 use garde::Validate as _;
 use secrecy::{ExposeSecret, SecretString};
@@ -10,7 +12,11 @@ use utils::serde::{deserialize_option_offset_datetime, serialize_option_offset_d
 use super::primitives::Id;
 use crate::{
     DomainError, DomainErrorKind, DomainResult, domain_error, impl_string_primitive,
-    models::primitives::{Description, DisplayOrder},
+    models::{
+        TodoStatusCode,
+        primitives::{Description, DisplayOrder},
+    },
+    repositories::TodoListSort,
     sqlx_encode_value,
 };
 
@@ -70,6 +76,50 @@ pub struct AccessToken(pub SecretString);
 #[derive(Debug, Clone)]
 pub struct RefreshToken(pub SecretString);
 
+/// ユーザーがロックされた理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumDisplay, Serialize, Deserialize)]
+#[enum_display(case = "Snake")]
+#[serde(rename_all = "snake_case")]
+pub enum LockReason {
+    /// 連続ログイン試行許容回数を超えてログインに失敗した
+    TooManyFailedAttempts,
+    /// 管理者がロックした
+    AdminAction,
+}
+
+impl LockReason {
+    /// データベースに記録する文字列を返す。
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LockReason::TooManyFailedAttempts => "too_many_failed_attempts",
+            LockReason::AdminAction => "admin_action",
+        }
+    }
+
+    /// ユーザーに提示する説明を返す。
+    pub fn description(&self) -> &'static str {
+        match self {
+            LockReason::TooManyFailedAttempts => "too many failed login attempts",
+            LockReason::AdminAction => "an administrator action",
+        }
+    }
+}
+
+impl TryFrom<&str> for LockReason {
+    type Error = DomainError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "too_many_failed_attempts" => Ok(LockReason::TooManyFailedAttempts),
+            "admin_action" => Ok(LockReason::AdminAction),
+            _ => Err(domain_error(
+                DomainErrorKind::Validation,
+                "Invalid lock reason",
+            )),
+        }
+    }
+}
+
 /// ユーザー
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -90,14 +140,116 @@ pub struct User {
     #[serde(serialize_with = "serialize_option_offset_datetime")]
     #[serde(deserialize_with = "deserialize_option_offset_datetime")]
     pub last_login_at: Option<OffsetDateTime>,
+    /// ロックされた日時
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_option_offset_datetime")]
+    #[serde(deserialize_with = "deserialize_option_offset_datetime")]
+    pub locked_at: Option<OffsetDateTime>,
+    /// ロックされた理由
+    #[serde(default)]
+    pub lock_reason: Option<LockReason>,
+    /// ロケール（`ja`、`en-US`などの言語タグ）
+    #[serde(default)]
+    pub locale: Option<String>,
     /// 作成日時
-    #[serde(with = "time::serde::rfc3339")]
+    #[serde(with = "utils::serde::timestamp")]
     pub created_at: OffsetDateTime,
     /// 更新日時
-    #[serde(with = "time::serde::rfc3339")]
+    #[serde(with = "utils::serde::timestamp")]
     pub updated_at: OffsetDateTime,
 }
 
+impl User {
+    /// ユーザーの氏名（苗字と名前を空白で連結した文字列）を返す。
+    pub fn full_name(&self) -> String {
+        format!("{} {}", self.family_name, self.given_name)
+    }
+
+    /// ロケールに応じた順序で、ユーザーの氏名を返す。
+    ///
+    /// 日本語（`ja`、または`ja-JP`など`ja-`で始まる言語タグ）の場合は苗字、名前の順で、それ以外の場合は名前、
+    /// 苗字の順で空白で連結する。
+    pub fn display_name(&self, locale: &str) -> String {
+        let japanese = locale
+            .split('-')
+            .next()
+            .is_some_and(|language| language.eq_ignore_ascii_case("ja"));
+        if japanese {
+            self.full_name()
+        } else {
+            format!("{} {}", self.given_name, self.family_name)
+        }
+    }
+}
+
+/// ロケールの最大文字数
+pub const LOCALE_MAX_LENGTH: usize = 35;
+
+const INVALID_LOCALE_MESSAGE: &str = "must be a language tag such as ja or en-US";
+
+/// ロケールを検証する。
+///
+/// ロケールは、英数字を`-`で区切った35文字以下の言語タグでなければならない。
+pub fn validate_locale(locale: String) -> DomainResult<String> {
+    let valid = locale.len() <= LOCALE_MAX_LENGTH
+        && locale.split('-').all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    if !valid {
+        return Err(domain_error(
+            DomainErrorKind::Validation,
+            INVALID_LOCALE_MESSAGE,
+        ));
+    }
+    Ok(locale)
+}
+
+/// ユーザーのプロファイル
+///
+/// ユーザーと、確認されるまで変更されない確認待ちのEメールアドレスを表現する。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserProfile {
+    /// ユーザー
+    ///
+    /// シリアライズするときに、ユーザーの氏名を`fullName`として、ユーザーのロケールに応じた順序の氏名を
+    /// `displayName`として追加する。
+    #[serde(flatten, serialize_with = "serialize_user_names")]
+    pub user: User,
+    /// 確認待ちのEメールアドレス
+    pub pending_email: Option<Email>,
+}
+
+/// ユーザーに氏名と表示名を加えてシリアライズする。
+fn serialize_user_names<S>(user: &User, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct UserWithNames<'a> {
+        #[serde(flatten)]
+        user: &'a User,
+        full_name: String,
+        display_name: String,
+    }
+
+    UserWithNames {
+        user,
+        full_name: user.full_name(),
+        display_name: user.display_name(user.locale.as_deref().unwrap_or_default()),
+    }
+    .serialize(serializer)
+}
+
+impl std::ops::Deref for UserProfile {
+    type Target = User;
+
+    fn deref(&self) -> &Self::Target {
+        &self.user
+    }
+}
+
 /// ログイン失敗履歴
 ///
 /// 連続ログイン試行許容時間内に、ログインに失敗した回数を記録する。
@@ -115,6 +267,40 @@ pub struct LoginFailedHistory {
     pub updated_at: OffsetDateTime,
 }
 
+/// ユーザーの設定
+///
+/// Todoリストを取得するときに、クエリパラメーターで指定されなかった条件の既定値として使用する。
+/// 保存されていない項目は既定値で補完する。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UserPreferences {
+    /// 既定で絞り込むTodoの状態
+    ///
+    /// 空の場合は状態で絞り込まない。
+    pub default_statuses: Vec<TodoStatusCode>,
+    /// 既定の並び順
+    pub default_sort: TodoListSort,
+    /// 既定でアーカイブされたTodoを含めるかどうか
+    pub show_archived: bool,
+}
+
+const USER_PREFERENCES_DUPLICATE_STATUSES: &str =
+    "defaultStatuses: each todo status code must be specified only once";
+
+impl UserPreferences {
+    /// ユーザーの設定を検証する。
+    pub fn validate(&self) -> DomainResult<()> {
+        let mut seen = HashSet::new();
+        if self.default_statuses.iter().any(|code| !seen.insert(*code)) {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                USER_PREFERENCES_DUPLICATE_STATUSES,
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// ロールコード
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, Hash, EnumDisplay, Serialize_repr, Deserialize_repr,
@@ -141,6 +327,37 @@ impl TryFrom<i16> for RoleCode {
     }
 }
 
+impl FromStr for RoleCode {
+    type Err = DomainError;
+
+    /// 数値（`"1"`など）、または`Display`が出力するスネークケースの名前（`"admin"`など）から変換する。
+    ///
+    /// 名前の大文字と小文字は区別しない。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(code) = s.parse::<i16>() {
+            return RoleCode::try_from(code);
+        }
+        RoleCode::ALL
+            .into_iter()
+            .find(|code| code.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| domain_error(DomainErrorKind::Validation, "Invalid role code"))
+    }
+}
+
+impl TryFrom<String> for RoleCode {
+    type Error = DomainError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl RoleCode {
+    /// すべてのロールコード
+    pub const ALL: [RoleCode; 2] = [RoleCode::Admin, RoleCode::User];
+}
+
 // OID 21 is the OID for `int2` in PostgreSQL, which corresponds to i16
 sqlx_encode_value!(RoleCode, i16, 21);
 
@@ -167,9 +384,219 @@ pub struct Role {
     /// 表示順
     pub display_order: DisplayOrder,
     /// 作成日時
-    #[serde(with = "time::serde::rfc3339")]
+    #[serde(with = "utils::serde::timestamp")]
     pub created_at: OffsetDateTime,
     /// 更新日時
-    #[serde(with = "time::serde::rfc3339")]
+    #[serde(with = "utils::serde::timestamp")]
     pub updated_at: OffsetDateTime,
 }
+
+/// ロールはコードで識別するため、コードのみを比較する。
+impl PartialEq for Role {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code
+    }
+}
+
+impl Eq for Role {}
+
+impl std::hash::Hash for Role {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.code.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_role(code: RoleCode, name: &str) -> Role {
+        Role {
+            code,
+            name: RoleName(name.to_string()),
+            description: None,
+            display_order: DisplayOrder(1),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::admin(RoleCode::Admin, "admin")]
+    #[case::user(RoleCode::User, "user")]
+    fn role_code_displays_as_snake_case_name(#[case] code: RoleCode, #[case] expected: &str) {
+        assert_eq!(code.to_string(), expected);
+    }
+
+    #[rstest::rstest]
+    #[case::admin("admin", RoleCode::Admin)]
+    #[case::user("user", RoleCode::User)]
+    #[case::upper_case("ADMIN", RoleCode::Admin)]
+    #[case::mixed_case("User", RoleCode::User)]
+    #[case::surrounding_spaces(" admin ", RoleCode::Admin)]
+    #[case::admin_code("1", RoleCode::Admin)]
+    #[case::user_code("2", RoleCode::User)]
+    fn role_code_can_be_parsed_from_str(#[case] value: &str, #[case] expected: RoleCode) {
+        assert_eq!(value.parse::<RoleCode>().unwrap(), expected);
+        assert_eq!(RoleCode::try_from(value.to_string()).unwrap(), expected);
+    }
+
+    #[rstest::rstest]
+    #[case::empty("")]
+    #[case::unknown_name("guest")]
+    #[case::unknown_code("3")]
+    #[case::zero_code("0")]
+    #[case::out_of_range_code("40000")]
+    #[case::partial_name("adm")]
+    fn role_code_can_not_be_parsed_from_invalid_str(#[case] value: &str) {
+        let err = value.parse::<RoleCode>().unwrap_err();
+        assert_eq!(err.kind, DomainErrorKind::Validation);
+        assert_eq!(err.messages[0], "Invalid role code");
+        assert!(RoleCode::try_from(value.to_string()).is_err());
+    }
+
+    #[test]
+    fn role_code_round_trips_through_display() {
+        for code in RoleCode::ALL {
+            assert_eq!(code.to_string().parse::<RoleCode>().unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn role_hash_set_deduplicates_by_code() {
+        let roles = HashSet::from([
+            create_role(RoleCode::Admin, "管理者"),
+            create_role(RoleCode::Admin, "別名"),
+            create_role(RoleCode::User, "ユーザー"),
+        ]);
+        assert_eq!(roles.len(), 2);
+        assert!(roles.contains(&create_role(RoleCode::User, "any")));
+    }
+
+    fn create_user() -> User {
+        User {
+            id: UserId::default(),
+            family_name: FamilyName("山田".to_string()),
+            given_name: GivenName("太郎".to_string()),
+            email: Email("taro@example.com".to_string()),
+            role: create_role(RoleCode::User, "ユーザー"),
+            active: true,
+            last_login_at: None,
+            locked_at: None,
+            lock_reason: None,
+            locale: None,
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    #[test]
+    fn user_full_name_joins_family_and_given_names() {
+        assert_eq!(create_user().full_name(), "山田 太郎");
+    }
+
+    #[rstest::rstest]
+    #[case::ja("ja", "山田 太郎")]
+    #[case::ja_jp("ja-JP", "山田 太郎")]
+    #[case::ja_uppercase("JA", "山田 太郎")]
+    #[case::en("en", "太郎 山田")]
+    #[case::en_us("en-US", "太郎 山田")]
+    #[case::jam("jam", "太郎 山田")]
+    #[case::empty("", "太郎 山田")]
+    fn user_display_name_orders_names_by_locale(#[case] locale: &str, #[case] expected: &str) {
+        assert_eq!(create_user().display_name(locale), expected);
+    }
+
+    #[rstest::rstest]
+    #[case("ja", true)]
+    #[case("en-US", true)]
+    #[case("zh-Hant-TW", true)]
+    #[case("", false)]
+    #[case("en_US", false)]
+    #[case("en-", false)]
+    #[case("ja-<script>", false)]
+    #[case("abcdefghi", false)]
+    fn validate_locale_accepts_only_language_tags(#[case] locale: &str, #[case] ok: bool) {
+        match validate_locale(locale.to_string()) {
+            Ok(validated) => {
+                assert!(ok);
+                assert_eq!(validated, locale);
+            }
+            Err(e) => {
+                assert!(!ok);
+                assert_eq!(e.kind, DomainErrorKind::Validation);
+                assert_eq!(e.messages, vec![INVALID_LOCALE_MESSAGE]);
+            }
+        }
+    }
+
+    #[test]
+    fn user_profile_serializes_full_name() {
+        let profile = UserProfile {
+            user: create_user(),
+            pending_email: None,
+        };
+        let json = serde_json::to_value(&profile).unwrap();
+        assert_eq!(json["fullName"], "山田 太郎");
+        assert_eq!(json["displayName"], "太郎 山田");
+        assert_eq!(json["familyName"], "山田");
+        assert_eq!(json["givenName"], "太郎");
+        assert!(json.get("user").is_none());
+        // The computed field is ignored on deserialization
+        let deserialized = serde_json::from_value::<UserProfile>(json).unwrap();
+        assert_eq!(deserialized.full_name(), "山田 太郎");
+    }
+
+    #[test]
+    fn user_preferences_fill_missing_fields_with_defaults() {
+        let preferences = serde_json::from_str::<UserPreferences>("{}").unwrap();
+        assert_eq!(preferences, UserPreferences::default());
+        let preferences =
+            serde_json::from_str::<UserPreferences>(r#"{"defaultStatuses": [1, 2]}"#).unwrap();
+        assert_eq!(
+            preferences.default_statuses,
+            vec![TodoStatusCode::NotStarted, TodoStatusCode::InProgress]
+        );
+        assert_eq!(preferences.default_sort, TodoListSort::DueDate);
+        assert!(!preferences.show_archived);
+    }
+
+    #[test]
+    fn user_preferences_serde_round_trip() {
+        let preferences = UserPreferences {
+            default_statuses: vec![TodoStatusCode::Completed],
+            default_sort: TodoListSort::UpdatedAt,
+            show_archived: true,
+        };
+        let json = serde_json::to_value(&preferences).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "defaultStatuses": [3],
+                "defaultSort": "updated_at",
+                "showArchived": true,
+            })
+        );
+        let deserialized = serde_json::from_value::<UserPreferences>(json).unwrap();
+        assert_eq!(deserialized, preferences);
+    }
+
+    #[rstest::rstest]
+    #[case(vec![], true)]
+    #[case(vec![TodoStatusCode::NotStarted, TodoStatusCode::InProgress], true)]
+    #[case(vec![TodoStatusCode::NotStarted, TodoStatusCode::NotStarted], false)]
+    fn user_preferences_validate(#[case] default_statuses: Vec<TodoStatusCode>, #[case] ok: bool) {
+        let preferences = UserPreferences {
+            default_statuses,
+            ..Default::default()
+        };
+        match preferences.validate() {
+            Ok(()) => assert!(ok),
+            Err(e) => {
+                assert!(!ok);
+                assert_eq!(e.kind, DomainErrorKind::Validation);
+                assert_eq!(e.messages, vec![USER_PREFERENCES_DUPLICATE_STATUSES]);
+            }
+        }
+    }
+}
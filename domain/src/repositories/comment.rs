@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use crate::{
+    DomainErrorKind, DomainResult, domain_error,
+    models::{CommentBody, TodoComment, TodoCommentId, TodoId, UserId},
+};
+
+#[async_trait::async_trait]
+pub trait TodoCommentRepository: std::fmt::Debug + Send + Sync {
+    /// Todoのコメントを、作成日時の降順でリストする。
+    async fn list(&self, input: TodoCommentListInput) -> DomainResult<Vec<TodoComment>>;
+
+    /// コメントを取得する。
+    async fn by_id(&self, id: TodoCommentId) -> DomainResult<Option<TodoComment>>;
+
+    /// Todoにコメントを登録する。
+    async fn create(
+        &self,
+        todo_id: TodoId,
+        user_id: UserId,
+        body: CommentBody,
+    ) -> DomainResult<TodoComment>;
+
+    /// コメントを削除する。
+    async fn delete(&self, id: TodoCommentId) -> DomainResult<()>;
+}
+
+#[async_trait::async_trait]
+impl<T> TodoCommentRepository for Arc<T>
+where
+    T: TodoCommentRepository + ?Sized,
+{
+    async fn list(&self, input: TodoCommentListInput) -> DomainResult<Vec<TodoComment>> {
+        (**self).list(input).await
+    }
+
+    async fn by_id(&self, id: TodoCommentId) -> DomainResult<Option<TodoComment>> {
+        (**self).by_id(id).await
+    }
+
+    async fn create(
+        &self,
+        todo_id: TodoId,
+        user_id: UserId,
+        body: CommentBody,
+    ) -> DomainResult<TodoComment> {
+        (**self).create(todo_id, user_id, body).await
+    }
+
+    async fn delete(&self, id: TodoCommentId) -> DomainResult<()> {
+        (**self).delete(id).await
+    }
+}
+
+/// コメントをリストするときのページ番号の既定値
+pub const DEFAULT_COMMENT_PAGE: u32 = 1;
+
+/// コメントをリストするときの1ページあたりのコメント数の既定値
+pub const DEFAULT_COMMENT_PAGE_SIZE: u32 = 20;
+
+/// コメントをリストするときに指定できる1ページあたりのコメント数の最大値
+pub const MAX_COMMENT_PAGE_SIZE: u32 = 100;
+
+/// コメントをリストするときの条件
+#[derive(Debug, Clone, Copy)]
+pub struct TodoCommentListInput {
+    /// TodoのID
+    pub todo_id: TodoId,
+    /// ページ番号（1から始まる）
+    pub page: u32,
+    /// 1ページあたりのコメント数
+    pub page_size: u32,
+}
+
+impl TodoCommentListInput {
+    /// コメントをリストするときの条件を構築する。
+    ///
+    /// ページ番号と1ページあたりのコメント数が指定されていない場合は、既定値を使用する。
+    /// ページ番号が0の場合、または1ページあたりのコメント数が1から`MAX_COMMENT_PAGE_SIZE`の範囲外の場合は、
+    /// 検証エラーを返す。
+    pub fn new(todo_id: TodoId, page: Option<u32>, page_size: Option<u32>) -> DomainResult<Self> {
+        let page = page.unwrap_or(DEFAULT_COMMENT_PAGE);
+        if page == 0 {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "page: must be greater than or equal to 1",
+            ));
+        }
+        let page_size = page_size.unwrap_or(DEFAULT_COMMENT_PAGE_SIZE);
+        if !(1..=MAX_COMMENT_PAGE_SIZE).contains(&page_size) {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "pageSize: must be between 1 and 100",
+            ));
+        }
+        Ok(Self {
+            todo_id,
+            page,
+            page_size,
+        })
+    }
+
+    /// スキップするコメントの数を返す。
+    pub fn offset(&self) -> u64 {
+        (self.page as u64 - 1) * self.page_size as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn todo_comment_list_input_uses_defaults() {
+        let input = TodoCommentListInput::new(TodoId::default(), None, None).unwrap();
+        assert_eq!(input.page, DEFAULT_COMMENT_PAGE);
+        assert_eq!(input.page_size, DEFAULT_COMMENT_PAGE_SIZE);
+        assert_eq!(input.offset(), 0);
+    }
+
+    #[rstest::rstest]
+    #[case(1, 1, 0)]
+    #[case(2, 20, 20)]
+    #[case(3, 100, 200)]
+    fn todo_comment_list_input_offset(
+        #[case] page: u32,
+        #[case] page_size: u32,
+        #[case] expected: u64,
+    ) {
+        let input = TodoCommentListInput::new(TodoId::default(), Some(page), Some(page_size));
+        assert_eq!(input.unwrap().offset(), expected);
+    }
+
+    #[rstest::rstest]
+    #[case(Some(0), None)]
+    #[case(None, Some(0))]
+    #[case(None, Some(MAX_COMMENT_PAGE_SIZE + 1))]
+    fn todo_comment_list_input_rejects_invalid_page(
+        #[case] page: Option<u32>,
+        #[case] page_size: Option<u32>,
+    ) {
+        let error = TodoCommentListInput::new(TodoId::default(), page, page_size).unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use time::Date;
+
+use crate::{
+    DomainResult,
+    models::{UserDigest, UserId},
+};
+
+#[async_trait::async_trait]
+pub trait DailyDigestRepository: std::fmt::Debug + Send + Sync {
+    /// 対象日の日次ダイジェストを登録する。
+    ///
+    /// 同じユーザーの同じ対象日のダイジェストが既に登録されている場合は、内容を更新する。
+    /// 対象日のダイジェストを何度登録しても同じ結果になるように、`digests`に含まれないユーザーの対象日の
+    /// ダイジェストは削除する。
+    async fn upsert(&self, date: Date, digests: &[UserDigest]) -> DomainResult<()>;
+
+    /// ユーザーの指定された対象日の日次ダイジェストを取得する。
+    async fn by_user_and_date(
+        &self,
+        user_id: UserId,
+        date: Date,
+    ) -> DomainResult<Option<UserDigest>>;
+}
+
+#[async_trait::async_trait]
+impl<T> DailyDigestRepository for Arc<T>
+where
+    T: DailyDigestRepository + ?Sized,
+{
+    async fn upsert(&self, date: Date, digests: &[UserDigest]) -> DomainResult<()> {
+        (**self).upsert(date, digests).await
+    }
+
+    async fn by_user_and_date(
+        &self,
+        user_id: UserId,
+        date: Date,
+    ) -> DomainResult<Option<UserDigest>> {
+        (**self).by_user_and_date(user_id, date).await
+    }
+}
@@ -1,10 +1,30 @@
+use std::sync::Arc;
+
 use crate::DomainResult;
 
 #[async_trait::async_trait]
-pub trait LookupRepository {
+pub trait LookupRepository: std::fmt::Debug + Send + Sync {
     type Entity;
     type Code;
 
     async fn list(&self) -> DomainResult<Vec<Self::Entity>>;
     async fn by_code(&self, code: &Self::Code) -> DomainResult<Option<Self::Entity>>;
 }
+
+#[async_trait::async_trait]
+impl<T> LookupRepository for Arc<T>
+where
+    T: LookupRepository + ?Sized,
+    T::Code: Sync,
+{
+    type Entity = T::Entity;
+    type Code = T::Code;
+
+    async fn list(&self) -> DomainResult<Vec<Self::Entity>> {
+        (**self).list().await
+    }
+
+    async fn by_code(&self, code: &Self::Code) -> DomainResult<Option<Self::Entity>> {
+        (**self).by_code(code).await
+    }
+}
@@ -1,8 +1,12 @@
+mod comment;
+mod digest;
 mod lookup;
 mod todo;
 mod token;
 mod user;
 
+pub use comment::*;
+pub use digest::*;
 pub use lookup::*;
 pub use todo::*;
 pub use token::*;
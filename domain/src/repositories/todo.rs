@@ -1,13 +1,19 @@
-use time::Date;
+use std::sync::Arc;
+
+use enum_display::EnumDisplay;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::{Date, OffsetDateTime};
+
+use utils::serde::{deserialize_option_date, serialize_option_date};
 
 use crate::{
-    DateFilter, DomainErrorKind, DomainResult, NUMERIC_FILTER_MISSING_FROM, NumericOperator,
-    domain_error,
-    models::{Todo, TodoDescription, TodoId, TodoStatusCode, TodoTitle, UserId},
+    DateFilter, DomainErrorKind, DomainResult, NumericOperator, domain_error,
+    models::{Todo, TodoDescription, TodoId, TodoStatusCode, TodoTitle, UserDigest, UserId},
 };
 
 #[async_trait::async_trait]
-pub trait TodoRepository {
+pub trait TodoRepository: std::fmt::Debug + Send + Sync {
     /// Todoをリストする。
     async fn list(&self, input: TodoListInput) -> DomainResult<Vec<Todo>>;
 
@@ -17,6 +23,14 @@ pub trait TodoRepository {
     /// Todoを新規作成する。
     async fn create(&self, user_id: UserId, input: TodoCreateInput) -> DomainResult<Todo>;
 
+    /// ユーザーの完了しておらず、アーカイブされていないTodoのうち、タイトルが大文字と小文字を区別せずに
+    /// 一致するTodoをリストする。
+    async fn find_active_by_title(
+        &self,
+        user_id: UserId,
+        title: &TodoTitle,
+    ) -> DomainResult<Vec<Todo>>;
+
     /// Todoを更新する。
     ///
     /// Todoの状態は未着手、進行中、キャンセル、保留のみに変更できる。
@@ -24,18 +38,147 @@ pub trait TodoRepository {
     async fn update(&self, id: TodoId, todo: TodoUpdateInput) -> DomainResult<Todo>;
 
     /// Todoを完了する。
+    ///
+    /// Todoを完了できない場合は、検証エラーを返す。
     async fn complete(&self, id: TodoId) -> DomainResult<Todo>;
 
     /// 完了状態のTodoを他の状態に変更する。
+    ///
+    /// Todoを再開できない場合は、検証エラーを返す。
     async fn reopen(&self, id: TodoId, status: TodoStatusCode) -> DomainResult<Todo>;
 
     /// Todoをアーカイブする。
+    ///
+    /// Todoのアーカイブ状態を変更できない場合は、検証エラーを返す。
     async fn archive(&self, id: TodoId, archived: bool) -> DomainResult<Todo>;
 
     /// Todoを削除する
     async fn delete(&self, id: TodoId) -> DomainResult<()>;
+
+    /// ユーザーのTodoの所有者を、他のユーザーに変更する。
+    ///
+    /// `include_completed`が`false`の場合は、完了したTodoの所有者を変更しない。
+    /// すべてのTodoの所有者を1つのトランザクションで変更して、所有者を変更したTodoの数を返す。
+    async fn transfer_ownership(
+        &self,
+        from: UserId,
+        to: UserId,
+        include_completed: bool,
+    ) -> DomainResult<u64>;
+
+    /// アーカイブされたTodoを含めて、ユーザーのすべてのTodoをリストする。
+    async fn list_all_including_archived(&self, user_id: UserId) -> DomainResult<Vec<Todo>>;
+
+    /// Todoを一括で登録する。
+    ///
+    /// すべてのTodoを1つのトランザクションで登録して、登録したTodoの数を返す。
+    /// タイトルと完了予定日が同じTodoがユーザーに既に存在する場合、そのTodoは登録しない。
+    /// 同じユーザーへの登録が同時に実行された場合でも、同じTodoを重複して登録しない。
+    async fn import(&self, user_id: UserId, inputs: Vec<TodoImportInput>) -> DomainResult<u64>;
+
+    /// すべてのユーザーについて、指定された日付の日次ダイジェストを作成する。
+    ///
+    /// 未着手または進行中で、アーカイブされていないTodoのうち、完了予定日が指定された日付以前のTodoを
+    /// ユーザーごとにまとめる。ロックされているユーザーと、対象のTodoがないユーザーのダイジェストは作成しない。
+    async fn due_digest(&self, date: Date) -> DomainResult<Vec<UserDigest>>;
 }
 
+/// データベースエンジンに応じて選択したTodoリポジトリを、トレイトオブジェクトのまま使用できるようにする。
+#[async_trait::async_trait]
+impl<T> TodoRepository for Arc<T>
+where
+    T: TodoRepository + ?Sized,
+{
+    async fn list(&self, input: TodoListInput) -> DomainResult<Vec<Todo>> {
+        (**self).list(input).await
+    }
+
+    async fn by_id(&self, id: TodoId) -> DomainResult<Option<Todo>> {
+        (**self).by_id(id).await
+    }
+
+    async fn create(&self, user_id: UserId, input: TodoCreateInput) -> DomainResult<Todo> {
+        (**self).create(user_id, input).await
+    }
+
+    async fn find_active_by_title(
+        &self,
+        user_id: UserId,
+        title: &TodoTitle,
+    ) -> DomainResult<Vec<Todo>> {
+        (**self).find_active_by_title(user_id, title).await
+    }
+
+    async fn update(&self, id: TodoId, todo: TodoUpdateInput) -> DomainResult<Todo> {
+        (**self).update(id, todo).await
+    }
+
+    async fn complete(&self, id: TodoId) -> DomainResult<Todo> {
+        (**self).complete(id).await
+    }
+
+    async fn reopen(&self, id: TodoId, status: TodoStatusCode) -> DomainResult<Todo> {
+        (**self).reopen(id, status).await
+    }
+
+    async fn archive(&self, id: TodoId, archived: bool) -> DomainResult<Todo> {
+        (**self).archive(id, archived).await
+    }
+
+    async fn delete(&self, id: TodoId) -> DomainResult<()> {
+        (**self).delete(id).await
+    }
+
+    async fn transfer_ownership(
+        &self,
+        from: UserId,
+        to: UserId,
+        include_completed: bool,
+    ) -> DomainResult<u64> {
+        (**self)
+            .transfer_ownership(from, to, include_completed)
+            .await
+    }
+
+    async fn list_all_including_archived(&self, user_id: UserId) -> DomainResult<Vec<Todo>> {
+        (**self).list_all_including_archived(user_id).await
+    }
+
+    async fn import(&self, user_id: UserId, inputs: Vec<TodoImportInput>) -> DomainResult<u64> {
+        (**self).import(user_id, inputs).await
+    }
+
+    async fn due_digest(&self, date: Date) -> DomainResult<Vec<UserDigest>> {
+        (**self).due_digest(date).await
+    }
+}
+
+/// Todoをリストするときに指定できるキーワードの最大文字数
+pub const TODO_LIST_KEYWORD_MAX_LENGTH: usize = 100;
+
+/// Todoをリストするときのページ番号の既定値
+pub const DEFAULT_TODO_PAGE: u32 = 1;
+
+/// Todoをリストするときの1ページあたりのTodo数の既定値
+pub const DEFAULT_TODO_PAGE_SIZE: u32 = 50;
+
+/// Todoをリストするときに指定できる1ページあたりのTodo数の最大値
+pub const MAX_TODO_PAGE_SIZE: u32 = 200;
+
+/// ページ番号が0の場合のエラーメッセージ
+pub const TODO_LIST_PAGE_TOO_SMALL: &str = "Page must be greater than or equal to 1";
+
+/// 1ページあたりのTodo数が0の場合のエラーメッセージ
+pub const TODO_LIST_PAGE_SIZE_TOO_SMALL: &str = "Page size must be greater than or equal to 1";
+
+/// 1ページあたりのTodo数が`MAX_TODO_PAGE_SIZE`を超える場合のエラーメッセージ
+pub const TODO_LIST_PAGE_SIZE_TOO_LARGE: &str = "Page size cannot exceed 200";
+
+/// カーソルを発行したときと絞り込み条件または並び順が異なる場合のエラーメッセージ
+pub const TODO_LIST_CURSOR_FILTER_CHANGED: &str =
+    "cursor: the filters or the sort order have changed, restart the listing without the cursor";
+
+#[derive(Debug, Clone)]
 pub struct TodoListInput {
     /// ユーザーID
     pub user_id: UserId,
@@ -45,11 +188,233 @@ pub struct TodoListInput {
     pub filter: Option<DateFilter>,
     /// 状態コード
     pub statuses: Option<Vec<TodoStatusCode>>,
-    /// アーカイブ
-    pub archived: Option<bool>,
+    /// アーカイブ状態による絞り込み
+    pub archived: ArchivedFilter,
+    /// 表示範囲
+    ///
+    /// 状態コードが指定されている場合は状態コードで絞り込み、表示範囲は並び順にのみ影響する。
+    pub view: TodoListView,
+    /// 並び順
+    pub sort: TodoListSort,
+    /// ページ
+    ///
+    /// 指定されていない場合は、条件に一致するすべてのTodoを返す。
+    pub page: Option<TodoListPage>,
+    /// カーソルによるページ
+    ///
+    /// ページと同時に指定できない。
+    pub keyset: Option<TodoListKeyset>,
+}
+
+/// Todoリストのページ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TodoListPage {
+    /// ページ番号（1から始まる）
+    pub page: u32,
+    /// 1ページあたりのTodo数
+    pub page_size: u32,
+}
+
+impl TodoListPage {
+    /// Todoリストのページを構築する。
+    ///
+    /// ページ番号と1ページあたりのTodo数のどちらも指定されていない場合は、ページに分割しないため`None`を返す。
+    /// どちらか一方のみ指定されている場合は、指定されていない方に既定値を使用する。
+    pub fn new(page: Option<u32>, page_size: Option<u32>) -> DomainResult<Option<Self>> {
+        if page.is_none() && page_size.is_none() {
+            return Ok(None);
+        }
+        let page = Self {
+            page: page.unwrap_or(DEFAULT_TODO_PAGE),
+            page_size: page_size.unwrap_or(DEFAULT_TODO_PAGE_SIZE),
+        };
+        page.validate()?;
+        Ok(Some(page))
+    }
+
+    /// ページ番号が0の場合、または1ページあたりのTodo数が1から`MAX_TODO_PAGE_SIZE`の範囲外の場合は、
+    /// 検証エラーを返す。
+    pub fn validate(&self) -> DomainResult<()> {
+        if self.page == 0 {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                TODO_LIST_PAGE_TOO_SMALL,
+            ));
+        }
+        if self.page_size == 0 {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                TODO_LIST_PAGE_SIZE_TOO_SMALL,
+            ));
+        }
+        if MAX_TODO_PAGE_SIZE < self.page_size {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                TODO_LIST_PAGE_SIZE_TOO_LARGE,
+            ));
+        }
+        Ok(())
+    }
+
+    /// スキップするTodoの数を返す。
+    pub fn offset(&self) -> u64 {
+        (self.page as u64 - 1) * self.page_size as u64
+    }
+}
+
+/// カーソルで分割したTodoリストのページ
+///
+/// ページ番号で分割する場合と異なり、ページを取得する間にTodoが登録または削除されても、Todoが重複したり
+/// 欠落したりしない。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoListKeyset {
+    /// 前のページの最後のTodoを示すカーソル
+    ///
+    /// 指定されていない場合は、最初のページを返す。
+    pub after: Option<TodoListCursor>,
+    /// 1ページあたりのTodo数
+    pub limit: u32,
+}
+
+impl TodoListKeyset {
+    /// カーソルで分割したTodoリストのページを構築する。
+    ///
+    /// カーソルと1ページあたりのTodo数のどちらも指定されていない場合は、カーソルで分割しないため`None`を返す。
+    /// 1ページあたりのTodo数が指定されていない場合は、既定値を使用する。
+    pub fn new(after: Option<TodoListCursor>, limit: Option<u32>) -> DomainResult<Option<Self>> {
+        if after.is_none() && limit.is_none() {
+            return Ok(None);
+        }
+        let keyset = Self {
+            after,
+            limit: limit.unwrap_or(DEFAULT_TODO_PAGE_SIZE),
+        };
+        keyset.validate()?;
+        Ok(Some(keyset))
+    }
+
+    /// 1ページあたりのTodo数が1から`MAX_TODO_PAGE_SIZE`の範囲外の場合は、検証エラーを返す。
+    pub fn validate(&self) -> DomainResult<()> {
+        if !(1..=MAX_TODO_PAGE_SIZE).contains(&self.limit) {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "limit: must be between 1 and 200",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Todoリストのカーソル
+///
+/// ページの最後のTodoの並び順のキーと、カーソルを発行したときの絞り込み条件と並び順のハッシュを保持する。
+/// 異なる条件のTodoリストにカーソルを使用できないように、ハッシュが一致しない場合は検証エラーにする。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoListCursor {
+    /// 絞り込み条件と並び順のハッシュ
+    pub filter_hash: String,
+    /// 完了したTodoであるか
+    pub completed: bool,
+    /// 完了予定日
+    #[serde(serialize_with = "serialize_option_date")]
+    #[serde(deserialize_with = "deserialize_option_date")]
+    pub due_date: Option<Date>,
+    /// 更新日時
+    ///
+    /// 並び順のキーを正確に復元するため、レスポンスの日時と異なり精度を切り捨てない。
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    /// 作成日時
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// TodoのID
+    pub id: TodoId,
+}
+
+impl TodoListCursor {
+    /// Todoリストの条件で取得したTodoを示すカーソルを構築する。
+    pub fn new(input: &TodoListInput, todo: &Todo) -> Self {
+        Self {
+            filter_hash: input.filter_hash(),
+            completed: todo.status.code == TodoStatusCode::Completed,
+            due_date: todo.due_date,
+            updated_at: todo.updated_at,
+            created_at: todo.created_at,
+            id: todo.id,
+        }
+    }
+}
+
+/// アーカイブ状態によるTodoの絞り込み
+///
+/// クエリパラメーターでは、`false`、`true`、`all`で指定する。指定されていない場合は、アーカイブされていない
+/// Todoのみを対象とする。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArchivedFilter {
+    /// アーカイブされていないTodoのみ
+    #[default]
+    #[serde(rename = "false")]
+    Active,
+    /// アーカイブされたTodoのみ
+    #[serde(rename = "true")]
+    Archived,
+    /// アーカイブの有無にかかわらずすべてのTodo
+    #[serde(rename = "all")]
+    All,
+}
+
+impl std::fmt::Display for ArchivedFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchivedFilter::Active => write!(f, "false"),
+            ArchivedFilter::Archived => write!(f, "true"),
+            ArchivedFilter::All => write!(f, "all"),
+        }
+    }
+}
+
+/// Todoリストの表示範囲
+///
+/// クエリパラメーターでは、`active`、`completed`、`all`で指定する。状態コードで絞り込む場合は、状態コードを
+/// 優先して、表示範囲は並び順にのみ影響する。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, EnumDisplay, Serialize, Deserialize)]
+#[enum_display(case = "Snake")]
+#[serde(rename_all = "snake_case")]
+pub enum TodoListView {
+    /// 完了していないTodoのみ
+    #[default]
+    Active,
+    /// 完了したTodoのみ
+    Completed,
+    /// 完了の有無にかかわらずすべてのTodo（完了していないTodoを先に並べる）
+    All,
+}
+
+/// Todoリストの並び順
+///
+/// クエリパラメーターでは、`due_date`、`created_at`、`updated_at`で指定する。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, EnumDisplay, Serialize, Deserialize)]
+#[enum_display(case = "Snake")]
+#[serde(rename_all = "snake_case")]
+pub enum TodoListSort {
+    /// 完了予定日の昇順（完了予定日がないTodoは最後）
+    #[default]
+    DueDate,
+    /// 作成日時の降順
+    CreatedAt,
+    /// 更新日時の降順
+    UpdatedAt,
 }
 
 impl TodoListInput {
+    /// Todoをリストするときの条件を構築する。
+    ///
+    /// `op`が指定されている場合は、`op`に応じて`from`と`to`が指定されているか検証して、条件が矛盾している場合は
+    /// 検証エラーを返す。
+    /// `page`と`page_size`は`TodoListPage::new`でページに変換して、ページ番号が0の場合、または1ページあたりの
+    /// Todo数が0または`MAX_TODO_PAGE_SIZE`を超える場合は検証エラーを返す。
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_id: UserId,
         keyword: Option<String>,
@@ -57,22 +422,88 @@ impl TodoListInput {
         from: Option<Date>,
         to: Option<Date>,
         statuses: Option<Vec<TodoStatusCode>>,
-        archived: Option<bool>,
+        archived: Option<ArchivedFilter>,
+        page: Option<u32>,
+        page_size: Option<u32>,
     ) -> DomainResult<Self> {
-        if op.is_some() && from.is_none() {
-            return Err(domain_error(
-                DomainErrorKind::Validation,
-                NUMERIC_FILTER_MISSING_FROM,
-            ));
-        }
         let due_date_filter = op.map(|op| DateFilter::new(op, from, to)).transpose()?;
-        Ok(Self {
+        let page = TodoListPage::new(page, page_size)?;
+        let input = Self {
             user_id,
             keyword,
             filter: due_date_filter,
             statuses,
-            archived,
-        })
+            archived: archived.unwrap_or_default(),
+            view: TodoListView::default(),
+            sort: TodoListSort::default(),
+            page,
+            keyset: None,
+        };
+        input.validate()?;
+        Ok(input)
+    }
+
+    /// Todoをリストするときの条件を検証する。
+    ///
+    /// キーワードが`TODO_LIST_KEYWORD_MAX_LENGTH`文字を超えていないか確認する。
+    /// また、フィールドが直接設定された場合に備えて、完了予定日の絞り込み条件とページが、`DateFilter::new`と
+    /// `TodoListPage::new`と同じ規則を満たしているか確認する。
+    pub fn validate(&self) -> DomainResult<()> {
+        let keyword_too_long = self
+            .keyword
+            .as_ref()
+            .is_some_and(|keyword| TODO_LIST_KEYWORD_MAX_LENGTH < keyword.chars().count());
+        if keyword_too_long {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "keyword: must be 100 characters or less",
+            ));
+        }
+        if let Some(filter) = &self.filter {
+            DateFilter::new(filter.op, filter.from, filter.to)?;
+        }
+        if let Some(page) = &self.page {
+            page.validate()?;
+        }
+        if let Some(keyset) = &self.keyset {
+            if self.page.is_some() {
+                return Err(domain_error(
+                    DomainErrorKind::Validation,
+                    "cursor: can not be combined with page and pageSize",
+                ));
+            }
+            keyset.validate()?;
+            let filter_changed = keyset
+                .after
+                .as_ref()
+                .is_some_and(|cursor| cursor.filter_hash != self.filter_hash());
+            if filter_changed {
+                return Err(domain_error(
+                    DomainErrorKind::Validation,
+                    TODO_LIST_CURSOR_FILTER_CHANGED,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 絞り込み条件と並び順のハッシュを返す。
+    ///
+    /// カーソルを発行したときと異なる条件でTodoをリストしていないか確認するために使用する。
+    pub fn filter_hash(&self) -> String {
+        let source = format!(
+            "{}|{:?}|{:?}|{:?}|{}|{}|{}",
+            self.user_id,
+            self.keyword,
+            self.filter,
+            self.statuses,
+            self.archived,
+            self.view,
+            self.sort
+        );
+        let mut hasher = Sha256::new();
+        hasher.update(source.as_bytes());
+        format!("{:x}", hasher.finalize())[..16].to_string()
     }
 
     pub fn new_with_user_id(user_id: UserId) -> Self {
@@ -81,7 +512,11 @@ impl TodoListInput {
             keyword: None,
             filter: None,
             statuses: None,
-            archived: None,
+            archived: ArchivedFilter::default(),
+            view: TodoListView::default(),
+            sort: TodoListSort::default(),
+            page: None,
+            keyset: None,
         }
     }
 }
@@ -95,13 +530,534 @@ pub struct TodoCreateInput {
     pub due_date: Option<time::Date>,
 }
 
+impl TodoCreateInput {
+    /// 作成するTodoの完了予定日が、基準日より前でないか検証する。
+    ///
+    /// # 引数
+    ///
+    /// * `today`: 完了予定日を検証するときの基準日
+    pub fn validate(&self, today: Date) -> DomainResult<()> {
+        match self.due_date {
+            Some(due_date) if due_date < today => Err(domain_error(
+                DomainErrorKind::Validation,
+                "due_date must not be in the past",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
 pub struct TodoUpdateInput {
     /// タイトル
     pub title: Option<TodoTitle>,
     /// 説明
-    pub description: Option<TodoDescription>,
+    ///
+    /// `Some(None)`の場合は、説明を消去する。
+    pub description: Option<Option<TodoDescription>>,
     /// 状態コード
     pub status_code: Option<TodoStatusCode>,
     /// 完了予定日
+    ///
+    /// `Some(None)`の場合は、完了予定日を消去する。
+    pub due_date: Option<Option<Date>>,
+}
+
+impl TodoUpdateInput {
+    /// 現在のTodoに更新内容を重ねて、すべての項目を設定したTodo更新入力を返す。
+    ///
+    /// 更新内容に指定されていない項目は、現在のTodoの値を引き継ぐ。
+    pub fn merge(self, current: &Todo) -> Self {
+        Self {
+            title: self.title.or_else(|| Some(current.title.clone())),
+            description: Some(
+                self.description
+                    .unwrap_or_else(|| current.description.clone()),
+            ),
+            status_code: self.status_code.or(Some(current.status.code)),
+            due_date: Some(self.due_date.unwrap_or(current.due_date)),
+        }
+    }
+}
+
+pub struct TodoImportInput {
+    /// タイトル
+    pub title: TodoTitle,
+    /// 説明
+    pub description: Option<TodoDescription>,
+    /// 状態コード
+    pub status_code: TodoStatusCode,
+    /// 完了予定日
     pub due_date: Option<Date>,
+    /// 完了日時
+    pub completed_at: Option<OffsetDateTime>,
+    /// アーカイブ
+    pub archived: bool,
+    /// 作成日時
+    pub created_at: OffsetDateTime,
+    /// 更新日時
+    pub updated_at: OffsetDateTime,
+}
+
+impl From<Todo> for TodoImportInput {
+    fn from(todo: Todo) -> Self {
+        Self {
+            title: todo.title,
+            description: todo.description,
+            status_code: todo.status.code,
+            due_date: todo.due_date,
+            completed_at: todo.completed_at,
+            archived: todo.archived,
+            created_at: todo.created_at,
+            updated_at: todo.updated_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{date, datetime};
+
+    use super::*;
+    use crate::models::{
+        Email, FamilyName, GivenName, Role, RoleCode, RoleName, TodoStatus, TodoStatusName, User,
+        primitives::DisplayOrder,
+    };
+
+    fn create_todo() -> Todo {
+        // 完了予定日よりも前に作成されたTodo
+        let created_at = datetime!(2025-06-01 09:00:00 UTC);
+        let user = User {
+            id: UserId::default(),
+            family_name: FamilyName::new(String::from("Doe")).unwrap(),
+            given_name: GivenName::new(String::from("John")).unwrap(),
+            email: Email::new(String::from("doe@example.com")).unwrap(),
+            role: Role {
+                code: RoleCode::User,
+                name: RoleName(String::from("ユーザー")),
+                description: None,
+                display_order: DisplayOrder(2),
+                created_at,
+                updated_at: created_at,
+            },
+            active: true,
+            last_login_at: None,
+            locked_at: None,
+            lock_reason: None,
+            locale: None,
+            created_at,
+            updated_at: created_at,
+        };
+        let status = TodoStatus {
+            code: TodoStatusCode::InProgress,
+            name: TodoStatusName(String::from("進行中")),
+            description: None,
+            display_order: DisplayOrder(2),
+            created_at,
+            updated_at: created_at,
+        };
+        Todo::new(
+            TodoId::default(),
+            user,
+            TodoTitle::new(String::from("current title")).unwrap(),
+            Some(TodoDescription::new(String::from("current description")).unwrap()),
+            status,
+            Some(date!(2025 - 06 - 30)),
+            None,
+            false,
+            created_at,
+            created_at,
+        )
+        .unwrap()
+    }
+
+    fn title(value: &str) -> TodoTitle {
+        TodoTitle::new(String::from(value)).unwrap()
+    }
+
+    fn description(value: &str) -> TodoDescription {
+        TodoDescription::new(String::from(value)).unwrap()
+    }
+
+    #[rstest::rstest]
+    #[case::keep_everything(
+        TodoUpdateInput { title: None, description: None, status_code: None, due_date: None },
+        "current title",
+        Some("current description"),
+        TodoStatusCode::InProgress,
+        Some(date!(2025 - 06 - 30))
+    )]
+    #[case::replace_everything(
+        TodoUpdateInput {
+            title: Some(title("new title")),
+            description: Some(Some(description("new description"))),
+            status_code: Some(TodoStatusCode::OnHold),
+            due_date: Some(Some(date!(2025 - 07 - 31))),
+        },
+        "new title",
+        Some("new description"),
+        TodoStatusCode::OnHold,
+        Some(date!(2025 - 07 - 31))
+    )]
+    #[case::replace_only_the_title(
+        TodoUpdateInput {
+            title: Some(title("new title")),
+            description: None,
+            status_code: None,
+            due_date: None,
+        },
+        "new title",
+        Some("current description"),
+        TodoStatusCode::InProgress,
+        Some(date!(2025 - 06 - 30))
+    )]
+    #[case::clear_the_description(
+        TodoUpdateInput { title: None, description: Some(None), status_code: None, due_date: None },
+        "current title",
+        None,
+        TodoStatusCode::InProgress,
+        Some(date!(2025 - 06 - 30))
+    )]
+    #[case::clear_the_due_date(
+        TodoUpdateInput { title: None, description: None, status_code: None, due_date: Some(None) },
+        "current title",
+        Some("current description"),
+        TodoStatusCode::InProgress,
+        None
+    )]
+    fn todo_update_input_merge(
+        #[case] input: TodoUpdateInput,
+        #[case] expected_title: &str,
+        #[case] expected_description: Option<&str>,
+        #[case] expected_status_code: TodoStatusCode,
+        #[case] expected_due_date: Option<Date>,
+    ) {
+        let merged = input.merge(&create_todo());
+        assert_eq!(merged.title.unwrap().0, expected_title);
+        assert_eq!(
+            merged.description.unwrap().map(|description| description.0),
+            expected_description.map(String::from)
+        );
+        assert_eq!(merged.status_code, Some(expected_status_code));
+        assert_eq!(merged.due_date, Some(expected_due_date));
+    }
+
+    #[rstest::rstest]
+    #[case(None, true)]
+    #[case(Some(date!(2025 - 06 - 14)), false)]
+    #[case(Some(date!(2025 - 06 - 15)), true)]
+    #[case(Some(date!(2025 - 06 - 16)), true)]
+    fn todo_create_input_validate(#[case] due_date: Option<Date>, #[case] expected: bool) {
+        let input = TodoCreateInput {
+            title: TodoTitle::new(String::from("title")).unwrap(),
+            description: None,
+            due_date,
+        };
+        assert_eq!(input.validate(date!(2025 - 06 - 15)).is_ok(), expected);
+    }
+
+    #[test]
+    fn todo_list_page_is_none_without_page_and_page_size() {
+        assert_eq!(TodoListPage::new(None, None).unwrap(), None);
+    }
+
+    #[rstest::rstest]
+    #[case(Some(1), None, 1, DEFAULT_TODO_PAGE_SIZE, 0)]
+    #[case(None, Some(1), DEFAULT_TODO_PAGE, 1, 0)]
+    #[case(Some(3), Some(MAX_TODO_PAGE_SIZE), 3, MAX_TODO_PAGE_SIZE, 400)]
+    fn todo_list_page_new_ok(
+        #[case] page: Option<u32>,
+        #[case] page_size: Option<u32>,
+        #[case] expected_page: u32,
+        #[case] expected_page_size: u32,
+        #[case] expected_offset: u64,
+    ) {
+        let page = TodoListPage::new(page, page_size).unwrap().unwrap();
+        assert_eq!(page.page, expected_page);
+        assert_eq!(page.page_size, expected_page_size);
+        assert_eq!(page.offset(), expected_offset);
+    }
+
+    #[rstest::rstest]
+    #[case(Some(0), None, TODO_LIST_PAGE_TOO_SMALL)]
+    #[case(None, Some(0), TODO_LIST_PAGE_SIZE_TOO_SMALL)]
+    #[case(None, Some(MAX_TODO_PAGE_SIZE + 1), TODO_LIST_PAGE_SIZE_TOO_LARGE)]
+    #[case(Some(1), Some(999_999), TODO_LIST_PAGE_SIZE_TOO_LARGE)]
+    fn todo_list_input_new_rejects_invalid_page(
+        #[case] page: Option<u32>,
+        #[case] page_size: Option<u32>,
+        #[case] expected: &str,
+    ) {
+        let result = TodoListInput::new(
+            UserId::default(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            page,
+            page_size,
+        );
+        let Err(error) = result else {
+            panic!("page={page:?} and page_size={page_size:?} must be rejected");
+        };
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+        assert_eq!(error.messages, vec![expected]);
+    }
+
+    #[test]
+    fn todo_list_input_new_accepts_the_largest_page_size() {
+        let input = TodoListInput::new(
+            UserId::default(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(1),
+            Some(MAX_TODO_PAGE_SIZE),
+        )
+        .unwrap();
+        assert_eq!(
+            input.page,
+            Some(TodoListPage {
+                page: 1,
+                page_size: MAX_TODO_PAGE_SIZE
+            })
+        );
+    }
+
+    #[test]
+    fn todo_list_input_validate_rejects_the_page_set_directly() {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        input.page = Some(TodoListPage {
+            page: 1,
+            page_size: MAX_TODO_PAGE_SIZE + 1,
+        });
+        let error = input.validate().unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+    }
+
+    fn create_cursor(filter_hash: String) -> TodoListCursor {
+        TodoListCursor {
+            filter_hash,
+            completed: false,
+            due_date: Some(date!(2025 - 06 - 30)),
+            updated_at: OffsetDateTime::now_utc(),
+            created_at: OffsetDateTime::now_utc(),
+            id: TodoId::default(),
+        }
+    }
+
+    #[rstest::rstest]
+    #[case(None, None, None)]
+    #[case(None, Some(10), Some(10))]
+    #[case(Some(create_cursor(String::new())), None, Some(DEFAULT_TODO_PAGE_SIZE))]
+    fn todo_list_keyset_new_ok(
+        #[case] after: Option<TodoListCursor>,
+        #[case] limit: Option<u32>,
+        #[case] expected_limit: Option<u32>,
+    ) {
+        let keyset = TodoListKeyset::new(after, limit).unwrap();
+        assert_eq!(keyset.map(|keyset| keyset.limit), expected_limit);
+    }
+
+    #[rstest::rstest]
+    #[case(0)]
+    #[case(MAX_TODO_PAGE_SIZE + 1)]
+    fn todo_list_keyset_new_err(#[case] limit: u32) {
+        let error = TodoListKeyset::new(None, Some(limit)).unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+        assert_eq!(error.messages, vec!["limit: must be between 1 and 200"]);
+    }
+
+    #[test]
+    fn todo_list_input_validate_rejects_the_keyset_with_the_page() {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        input.page = TodoListPage::new(Some(1), None).unwrap();
+        input.keyset = TodoListKeyset::new(None, Some(10)).unwrap();
+        let error = input.validate().unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+        assert_eq!(
+            error.messages,
+            vec!["cursor: can not be combined with page and pageSize"]
+        );
+    }
+
+    #[test]
+    fn todo_list_input_validate_accepts_the_cursor_issued_for_the_same_filters() {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        input.keyword = Some(String::from("会議"));
+        let cursor = create_cursor(input.filter_hash());
+        input.keyset = TodoListKeyset::new(Some(cursor), Some(10)).unwrap();
+        assert!(input.validate().is_ok());
+    }
+
+    #[rstest::rstest]
+    #[case::keyword(|input: &mut TodoListInput| input.keyword = Some(String::from("資料")))]
+    #[case::statuses(|input: &mut TodoListInput| input.statuses = Some(vec![TodoStatusCode::OnHold]))]
+    #[case::archived(|input: &mut TodoListInput| input.archived = ArchivedFilter::All)]
+    #[case::view(|input: &mut TodoListInput| input.view = TodoListView::All)]
+    #[case::sort(|input: &mut TodoListInput| input.sort = TodoListSort::CreatedAt)]
+    fn todo_list_input_validate_rejects_the_cursor_issued_for_other_filters(
+        #[case] change: fn(&mut TodoListInput),
+    ) {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        let cursor = create_cursor(input.filter_hash());
+        change(&mut input);
+        input.keyset = TodoListKeyset::new(Some(cursor), Some(10)).unwrap();
+        let error = input.validate().unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+        assert_eq!(error.messages, vec![TODO_LIST_CURSOR_FILTER_CHANGED]);
+    }
+
+    #[test]
+    fn todo_list_cursor_serde_round_trip() {
+        let cursor = create_cursor(String::from("0123456789abcdef"));
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TodoListCursor>(&json).unwrap(),
+            cursor
+        );
+    }
+
+    #[rstest::rstest]
+    #[case(NumericOperator::Eq, None, None)]
+    #[case(NumericOperator::Ne, None, Some(date!(2025 - 06 - 30)))]
+    #[case(NumericOperator::Gt, None, None)]
+    #[case(NumericOperator::Gte, None, None)]
+    #[case(NumericOperator::Lt, None, None)]
+    #[case(NumericOperator::Lte, None, None)]
+    #[case(NumericOperator::Between, None, None)]
+    #[case(NumericOperator::Between, None, Some(date!(2025 - 06 - 30)))]
+    #[case(NumericOperator::Between, Some(date!(2025 - 06 - 01)), None)]
+    #[case(NumericOperator::NotBetween, None, None)]
+    #[case(NumericOperator::NotBetween, Some(date!(2025 - 06 - 01)), None)]
+    #[case(
+        NumericOperator::Between,
+        Some(date!(2025 - 06 - 30)),
+        Some(date!(2025 - 06 - 01))
+    )]
+    fn todo_list_input_new_err(
+        #[case] op: NumericOperator,
+        #[case] from: Option<Date>,
+        #[case] to: Option<Date>,
+    ) {
+        let result = TodoListInput::new(
+            UserId::default(),
+            None,
+            Some(op),
+            from,
+            to,
+            None,
+            None,
+            None,
+            None,
+        );
+        let Err(error) = result else {
+            panic!("{op} with from={from:?} and to={to:?} must be rejected");
+        };
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+    }
+
+    #[rstest::rstest]
+    #[case(None, None, None)]
+    #[case(Some(NumericOperator::Eq), Some(date!(2025 - 06 - 01)), None)]
+    #[case(
+        Some(NumericOperator::Between),
+        Some(date!(2025 - 06 - 01)),
+        Some(date!(2025 - 06 - 30))
+    )]
+    #[case(Some(NumericOperator::IsNull), None, None)]
+    #[case(Some(NumericOperator::IsNotNull), None, None)]
+    fn todo_list_input_new_ok(
+        #[case] op: Option<NumericOperator>,
+        #[case] from: Option<Date>,
+        #[case] to: Option<Date>,
+    ) {
+        let result = TodoListInput::new(
+            UserId::default(),
+            None,
+            op,
+            from,
+            to,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[rstest::rstest]
+    #[case("%")]
+    #[case("_")]
+    #[case("100%_\\")]
+    #[case(&"あ".repeat(TODO_LIST_KEYWORD_MAX_LENGTH))]
+    fn todo_list_input_new_accepts_keyword(#[case] keyword: &str) {
+        let result = TodoListInput::new(
+            UserId::default(),
+            Some(keyword.to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn todo_list_input_new_rejects_too_long_keyword() {
+        let keyword = "あ".repeat(TODO_LIST_KEYWORD_MAX_LENGTH + 1);
+        let result = TodoListInput::new(
+            UserId::default(),
+            Some(keyword),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        let Err(error) = result else {
+            panic!("a keyword longer than the maximum length must be rejected");
+        };
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+        assert_eq!(
+            error.messages,
+            vec!["keyword: must be 100 characters or less"]
+        );
+    }
+
+    #[test]
+    fn todo_list_input_validate_rejects_inconsistent_filter() {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        assert!(input.validate().is_ok());
+        input.filter = Some(DateFilter {
+            op: NumericOperator::Between,
+            from: Some(date!(2025 - 06 - 01)),
+            to: None,
+        });
+        let error = input.validate().unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::Validation);
+    }
+
+    #[rstest::rstest]
+    #[case(TodoListSort::DueDate, "due_date")]
+    #[case(TodoListSort::CreatedAt, "created_at")]
+    #[case(TodoListSort::UpdatedAt, "updated_at")]
+    fn todo_list_sort_display_and_serde_round_trip(
+        #[case] sort: TodoListSort,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(sort.to_string(), expected);
+        let json = serde_json::to_string(&sort).unwrap();
+        assert_eq!(json, format!("\"{expected}\""));
+        assert_eq!(serde_json::from_str::<TodoListSort>(&json).unwrap(), sort);
+    }
 }
@@ -1,4 +1,4 @@
-use std::str::FromStr as _;
+use std::{str::FromStr as _, sync::Arc};
 
 use async_trait::async_trait;
 use enum_display::EnumDisplay;
@@ -6,12 +6,12 @@ use secrecy::{ExposeSecret as _, SecretString};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
-use crate::models::UserId;
+use crate::models::{Email, UserId};
 use crate::{DomainError, DomainErrorKind, DomainResult, domain_error};
 
 /// トークンリポジトリ
 #[async_trait]
-pub trait TokenRepository: Sync + Send {
+pub trait TokenRepository: std::fmt::Debug + Sync + Send {
     /// アクセストークンとリフレッシュトークンを登録する。
     ///
     /// # 引数
@@ -24,6 +24,15 @@ pub trait TokenRepository: Sync + Send {
         refresh_token_info: &AuthTokenInfo,
     ) -> DomainResult<()>;
 
+    /// 単独のトークンを登録する。
+    ///
+    /// なりすましトークンのように、リフレッシュトークンと対にならないトークンを登録するときに使用する。
+    ///
+    /// # 引数
+    ///
+    /// * `token_info` - トークンの情報
+    async fn register_token(&self, token_info: &AuthTokenInfo) -> DomainResult<()>;
+
     /// トークンからユーザーIDとトークンの種類を取得する。
     ///
     /// # 引数
@@ -35,8 +44,91 @@ pub trait TokenRepository: Sync + Send {
     /// ユーザーIDとトークンの種類
     async fn get_token_content(&self, token: &SecretString) -> DomainResult<Option<TokenContent>>;
 
+    /// 認証情報の残りの生存期間（秒）を取得する。
+    ///
+    /// 認証情報が存在しない場合、または生存期間が設定されていない場合は`None`を返す。
+    ///
+    /// # 引数
+    ///
+    /// * `key` - トークンをハッシュ化した文字列
+    async fn get_token_ttl(&self, key: &SecretString) -> DomainResult<Option<u64>>;
+
     /// 認証情報を削除する。
     async fn delete_token_content(&self, key: &SecretString) -> DomainResult<()>;
+
+    /// 確認待ちのEメールアドレスを登録する。
+    ///
+    /// 確認トークンの認証情報と確認待ちのEメールアドレスを、同じ生存期間で登録する。
+    /// 確認待ちのEメールアドレスが既に登録されている場合は、以前の確認トークンを無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `email` - 確認待ちのEメールアドレス
+    /// * `token_info` - 確認トークンの情報
+    async fn register_pending_email(
+        &self,
+        user_id: UserId,
+        email: &Email,
+        token_info: &AuthTokenInfo,
+    ) -> DomainResult<()>;
+
+    /// ユーザーの確認待ちのEメールアドレスを取得する。
+    async fn pending_email(&self, user_id: UserId) -> DomainResult<Option<PendingEmail>>;
+
+    /// ユーザーの確認待ちのEメールアドレスと、その確認トークンの認証情報を削除する。
+    async fn delete_pending_email(&self, user_id: UserId) -> DomainResult<()>;
+}
+
+#[async_trait]
+impl<T> TokenRepository for Arc<T>
+where
+    T: TokenRepository + ?Sized,
+{
+    async fn register_token_pair<'a>(
+        &self,
+        access_token_info: &AuthTokenInfo,
+        refresh_token_info: &AuthTokenInfo,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_token_pair(access_token_info, refresh_token_info)
+            .await
+    }
+
+    async fn register_token(&self, token_info: &AuthTokenInfo) -> DomainResult<()> {
+        (**self).register_token(token_info).await
+    }
+
+    async fn get_token_content(&self, token: &SecretString) -> DomainResult<Option<TokenContent>> {
+        (**self).get_token_content(token).await
+    }
+
+    async fn get_token_ttl(&self, key: &SecretString) -> DomainResult<Option<u64>> {
+        (**self).get_token_ttl(key).await
+    }
+
+    async fn delete_token_content(&self, key: &SecretString) -> DomainResult<()> {
+        (**self).delete_token_content(key).await
+    }
+
+    async fn register_pending_email(
+        &self,
+        user_id: UserId,
+        email: &Email,
+        token_info: &AuthTokenInfo,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_pending_email(user_id, email, token_info)
+            .await
+    }
+
+    async fn pending_email(&self, user_id: UserId) -> DomainResult<Option<PendingEmail>> {
+        (**self).pending_email(user_id).await
+    }
+
+    async fn delete_pending_email(&self, user_id: UserId) -> DomainResult<()> {
+        (**self).delete_pending_email(user_id).await
+    }
 }
 
 /// トークンコンテンツ
@@ -48,6 +140,10 @@ pub struct TokenContent {
     pub user_id: UserId,
     /// トークンの種類
     pub token_type: TokenType,
+    /// なりすましている管理者のユーザーID
+    ///
+    /// トークンの種類がなりすましの場合のみ`Some`になる。
+    pub impersonated_by: Option<UserId>,
 }
 
 /// トークンの種類
@@ -58,6 +154,10 @@ pub enum TokenType {
     Access,
     /// リフレッシュトークン
     Refresh,
+    /// 管理者がユーザーになりすますためのアクセストークン
+    Impersonation,
+    /// Eメールアドレスの変更を確認するトークン
+    EmailChange,
 }
 
 impl TryFrom<&str> for TokenType {
@@ -67,6 +167,8 @@ impl TryFrom<&str> for TokenType {
         match value {
             "access" => Ok(Self::Access),
             "refresh" => Ok(Self::Refresh),
+            "impersonation" => Ok(Self::Impersonation),
+            "emailchange" => Ok(Self::EmailChange),
             _ => {
                 let messages = format!("{value} is not a valid token type");
                 Err(DomainError {
@@ -99,6 +201,31 @@ pub fn generate_auth_token_info(
     }
 }
 
+/// トークンリポジトリに登録するなりすましトークンの認証情報のキーと値を生成する。
+///
+/// # 引数
+///
+/// * `user_id` - なりすまされるユーザーのユーザーID
+/// * `impersonated_by` - なりすます管理者のユーザーID
+/// * `token` - なりすましトークン
+/// * `max_age` - なりすましトークンの生存期間（秒）
+pub fn generate_impersonation_token_info(
+    user_id: UserId,
+    impersonated_by: UserId,
+    token: &SecretString,
+    max_age: u64,
+) -> AuthTokenInfo {
+    AuthTokenInfo {
+        key: generate_auth_token_info_key(token),
+        value: format!(
+            "{}:{}",
+            generate_auth_token_info_value(user_id, TokenType::Impersonation),
+            impersonated_by.0
+        ),
+        max_age,
+    }
+}
+
 /// トークンリポジトリに登録する認証情報のキーを生成する。
 ///
 /// # 引数
@@ -123,13 +250,54 @@ fn generate_auth_token_info_value(user_id: UserId, token_type: TokenType) -> Str
     format!("{}:{}", user_id.0, token_type)
 }
 
+/// 確認待ちのEメールアドレス
+#[derive(Debug, Clone)]
+pub struct PendingEmail {
+    /// 確認待ちのEメールアドレス
+    pub email: Email,
+    /// 確認トークンをハッシュ化した文字列
+    pub token_key: SecretString,
+}
+
+/// トークンリポジトリに登録する確認待ちのEメールアドレスのキーを生成する。
+pub fn generate_pending_email_key(user_id: UserId) -> String {
+    format!("pending_email:{}", user_id.0)
+}
+
+/// トークンリポジトリに登録する確認待ちのEメールアドレスの値を生成する。
+///
+/// # 戻り値
+///
+/// 確認トークンをハッシュ化した文字列とEメールアドレスを組み合わせた文字列
+pub fn generate_pending_email_value(email: &Email, token_key: &SecretString) -> String {
+    format!("{}:{}", token_key.expose_secret(), email.0)
+}
+
+const PENDING_EMAIL_INVALID: &str = "The pending email in the redis value is invalid";
+
+/// 確認待ちのEメールアドレスの値を、確認トークンをハッシュ化した文字列とEメールアドレスに分割する。
+pub fn divide_pending_email_value(value: &str) -> DomainResult<PendingEmail> {
+    // 確認トークンをハッシュ化した文字列は16進数であるため、最初の`:`で分割する
+    let (token_key, email) = value
+        .split_once(':')
+        .ok_or_else(|| domain_error(DomainErrorKind::Unexpected, PENDING_EMAIL_INVALID))?;
+    let email = Email::new(email.to_string())
+        .map_err(|_| domain_error(DomainErrorKind::Unexpected, PENDING_EMAIL_INVALID))?;
+    Ok(PendingEmail {
+        email,
+        token_key: SecretString::new(token_key.into()),
+    })
+}
+
 const USER_ID_NOT_FOUND: &str = "The user id was not found in the redis value";
 const USER_ID_INVALID: &str = "The user id in the redis value is invalid";
 const TOKEN_TYPE_NOT_FOUND: &str = "The token type was not found in the redis value";
 const TOKEN_TYPE_INVALID: &str = "The token type in the redis value is invalid";
+const IMPERSONATOR_ID_NOT_FOUND: &str = "The impersonator id was not found in the redis value";
+const IMPERSONATOR_ID_INVALID: &str = "The impersonator id in the redis value is invalid";
 
-/// 認証情報をユーザーIDのとトークンの種類に分割する。
-pub fn divide_auth_token_info(value: &str) -> DomainResult<(UserId, TokenType)> {
+/// 認証情報をユーザーID、トークンの種類、なりすましている管理者のユーザーIDに分割する。
+pub fn divide_auth_token_info(value: &str) -> DomainResult<TokenContent> {
     let mut values = value.split(':');
     // ユーザーIDを取得
     let user_id = values
@@ -148,7 +316,23 @@ pub fn divide_auth_token_info(value: &str) -> DomainResult<(UserId, TokenType)>
         .ok_or_else(|| domain_error(DomainErrorKind::Unexpected, TOKEN_TYPE_NOT_FOUND))?;
     let token_type = TokenType::try_from(token_type)
         .map_err(|_| domain_error(DomainErrorKind::Unexpected, TOKEN_TYPE_INVALID))?;
-    Ok((user_id, token_type))
+    // なりすましトークンの場合は、なりすましている管理者のユーザーIDを取得
+    let impersonated_by = match token_type {
+        TokenType::Impersonation => {
+            let impersonated_by = values.next().ok_or_else(|| {
+                domain_error(DomainErrorKind::Unexpected, IMPERSONATOR_ID_NOT_FOUND)
+            })?;
+            let impersonated_by = Uuid::from_str(impersonated_by)
+                .map_err(|_| domain_error(DomainErrorKind::Unexpected, IMPERSONATOR_ID_INVALID))?;
+            Some(UserId::from(impersonated_by))
+        }
+        TokenType::Access | TokenType::Refresh | TokenType::EmailChange => None,
+    };
+    Ok(TokenContent {
+        user_id,
+        token_type,
+        impersonated_by,
+    })
 }
 
 #[cfg(test)]
@@ -172,9 +356,69 @@ mod tests {
         let expected_user_id = UserId::default();
         let expected_token_type = TokenType::Refresh;
         let input = format!("{expected_user_id}:{expected_token_type}");
-        let (user_id, token_type) = divide_auth_token_info(&input)?;
-        assert_eq!(expected_user_id, user_id);
-        assert_eq!(expected_token_type, token_type);
+        let content = divide_auth_token_info(&input)?;
+        assert_eq!(expected_user_id, content.user_id);
+        assert_eq!(expected_token_type, content.token_type);
+        assert!(content.impersonated_by.is_none());
+        Ok(())
+    }
+
+    /// なりすましトークンの認証情報から、なりすましている管理者のユーザーIDを取得できることを確認
+    #[test]
+    fn divide_impersonation_token_info_ok() -> anyhow::Result<()> {
+        let user_id = UserId::from(Uuid::new_v4());
+        let admin_id = UserId::from(Uuid::new_v4());
+        let token = SecretString::new("impersonation-token".into());
+        let info = generate_impersonation_token_info(user_id, admin_id, &token, 60);
+        assert_eq!(info.value, format!("{user_id}:impersonation:{admin_id}"));
+        let content = divide_auth_token_info(&info.value)?;
+        assert_eq!(content.user_id, user_id);
+        assert_eq!(content.token_type, TokenType::Impersonation);
+        assert_eq!(content.impersonated_by, Some(admin_id));
+        Ok(())
+    }
+
+    /// 確認待ちのEメールアドレスの値を、確認トークンのキーとEメールアドレスに分割できることを確認
+    #[test]
+    fn divide_pending_email_value_ok() -> anyhow::Result<()> {
+        let email = Email::new(String::from("new@example.com"))?;
+        let token_key = generate_auth_token_info_key(&SecretString::new("token".into()));
+        let value = generate_pending_email_value(&email, &token_key);
+        let pending = divide_pending_email_value(&value)?;
+        assert_eq!(pending.email, email);
+        assert_eq!(pending.token_key.expose_secret(), token_key.expose_secret());
         Ok(())
     }
+
+    /// 不正な確認待ちのEメールアドレスの値を拒否することを確認
+    #[rstest::rstest]
+    #[case("new@example.com")]
+    #[case("0123abcd:invalid-email")]
+    fn divide_pending_email_value_err(#[case] value: &str) {
+        let err = divide_pending_email_value(value).unwrap_err();
+        assert_eq!(err.kind, DomainErrorKind::Unexpected);
+        assert_eq!(err.messages, vec![PENDING_EMAIL_INVALID]);
+    }
+
+    /// Eメールアドレスの変更を確認するトークンの認証情報を分割できることを確認
+    #[test]
+    fn divide_email_change_token_info_ok() -> anyhow::Result<()> {
+        let user_id = UserId::default();
+        let token = SecretString::new("email-change-token".into());
+        let info = generate_auth_token_info(user_id, &token, TokenType::EmailChange, 60);
+        let content = divide_auth_token_info(&info.value)?;
+        assert_eq!(content.user_id, user_id);
+        assert_eq!(content.token_type, TokenType::EmailChange);
+        assert!(content.impersonated_by.is_none());
+        Ok(())
+    }
+
+    /// なりすましている管理者のユーザーIDがないなりすましトークンの認証情報を拒否することを確認
+    #[test]
+    fn divide_impersonation_token_info_without_impersonator_err() {
+        let input = format!("{}:impersonation", UserId::default());
+        let err = divide_auth_token_info(&input).unwrap_err();
+        assert_eq!(err.kind, DomainErrorKind::Unexpected);
+        assert_eq!(err.messages, vec![IMPERSONATOR_ID_NOT_FOUND]);
+    }
 }
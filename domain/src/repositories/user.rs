@@ -1,14 +1,18 @@
+use std::sync::Arc;
+
 use secrecy::SecretString;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::{
     DomainResult,
-    models::{Email, FamilyName, GivenName, LoginFailedHistory, PHCString, User, UserId},
+    models::{
+        Email, FamilyName, GivenName, LoginFailedHistory, PHCString, User, UserId, UserPreferences,
+    },
 };
 
 #[async_trait::async_trait]
-pub trait UserRepository {
+pub trait UserRepository: std::fmt::Debug + Send + Sync {
     /// ユーザーを新規作成する。
     async fn create(&self, user: UserInput, hashed_password: PHCString) -> DomainResult<User>;
 
@@ -19,9 +23,28 @@ pub trait UserRepository {
     async fn by_email(&self, email: &Email) -> DomainResult<Option<User>>;
 
     /// ユーザーを更新する。
+    ///
+    /// Eメールアドレスは確認されてから`update_email`で更新するため、このメソッドでは更新しない。
     async fn update(&self, id: UserId, user: UpdateUserInput) -> DomainResult<User>;
 
+    /// ユーザーのEメールアドレスを更新する。
+    async fn update_email(&self, id: UserId, email: Email) -> DomainResult<User>;
+
+    /// ユーザーの設定を取得する。
+    async fn preferences(&self, id: UserId) -> DomainResult<UserPreferences>;
+
+    /// ユーザーの設定を更新する。
+    async fn update_preferences(
+        &self,
+        id: UserId,
+        preferences: &UserPreferences,
+    ) -> DomainResult<UserPreferences>;
+
     /// ユーザーの最終ログイン日時を更新して、認証情報を登録するとともに、ログイン失敗履歴を削除する。
+    ///
+    /// `max_sessions`が0より大きい場合は、同じトランザクションでユーザーのセッション数が`max_sessions`以下になるように
+    /// 古いセッションを削除して、削除したアクセストークンとリフレッシュトークンのキーを返す。
+    #[allow(clippy::too_many_arguments)]
     async fn handle_logged_in(
         &self,
         id: UserId,
@@ -30,11 +53,34 @@ pub trait UserRepository {
         access_expired_at: OffsetDateTime,
         refresh_key: &SecretString,
         refresh_expired_at: OffsetDateTime,
+        max_sessions: u32,
+    ) -> DomainResult<Vec<SecretString>>;
+
+    /// ユーザーの認証情報を登録する。
+    ///
+    /// 最終ログイン日時とログイン失敗履歴は変更しない。トークンをリフレッシュするときに使用する。
+    async fn register_token_pair_for_user(
+        &self,
+        id: UserId,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
     ) -> DomainResult<()>;
 
     /// ユーザーがログインしたときに生成したアクセストークンとリフレッシュトークンを取得する。
     async fn user_tokens_by_id(&self, id: UserId) -> DomainResult<Vec<UserToken>>;
 
+    /// ユーザーの有効なセッションのうち、新しい`keep_n`個を残して古いセッションを削除する。
+    ///
+    /// セッションは、同時に登録されたアクセストークンとリフレッシュトークンの組で、リフレッシュトークンの有効期限が
+    /// 切れていないものである。削除したアクセストークンとリフレッシュトークンのキーを返す。
+    async fn prune_oldest_sessions(
+        &self,
+        id: UserId,
+        keep_n: u32,
+    ) -> DomainResult<Vec<SecretString>>;
+
     /// ユーザーがログインしたときに生成したアクセストークンとリフレッシュトークンを削除する。
     async fn delete_user_tokens_by_id(&self, id: UserId) -> DomainResult<Vec<SecretString>>;
 
@@ -52,6 +98,8 @@ pub trait UserRepository {
     async fn delete(&self, id: UserId) -> DomainResult<()>;
 
     /// ユーザーのログイン失敗履歴を登録する。
+    ///
+    /// ユーザーのログイン失敗履歴が既に存在する場合は、ログイン試行回数と最初にログインを試行した日時を更新する。
     async fn create_login_failure_history(
         &self,
         user_id: UserId,
@@ -68,13 +116,24 @@ pub trait UserRepository {
     /// ユーザーのログイン試行回数をインクリメントする。
     ///
     /// ユーザーのログイン試行回数をインクリメントして、インクリメント後のログイン試行回数が、最大ログイン試行回数を超えた
-    /// 場合は、ユーザーをロックする。
+    /// 場合は、ユーザーをロックして、ロックした日時と理由を記録する。
     async fn increment_number_of_login_attempts(
         &self,
         user_id: UserId,
         max_attempts: u32,
     ) -> DomainResult<()>;
 
+    /// ログイン失敗によるロックが期限切れの場合は、ユーザーのロックを解除する。
+    ///
+    /// 指定された日時以前にログイン失敗によってロックされたユーザーのロックを解除して、ログイン失敗履歴を削除する。
+    /// ロックした理由が記録されていない古いロックは、ログイン失敗によるロックとみなす。
+    /// 管理者によるロックは解除しない。ロックを解除した場合は`true`を返す。
+    async fn reactivate_if_lockout_expired(
+        &self,
+        id: UserId,
+        locked_before: OffsetDateTime,
+    ) -> DomainResult<bool>;
+
     /// ユーザーのログイン失敗履歴をリセットする。
     ///
     /// 連続ログイン試行回数を1に設定して、最初にログインを試行した日時を指定された日時に更新する。
@@ -83,6 +142,183 @@ pub trait UserRepository {
         user_id: UserId,
         attempted_at: OffsetDateTime,
     ) -> DomainResult<()>;
+
+    /// 古いログイン失敗履歴を削除する。
+    ///
+    /// 最初にログインを試行した日時が、指定された日時より前のログイン失敗履歴を削除して、削除した数を返す。
+    async fn delete_stale_login_failed_histories(
+        &self,
+        cutoff: OffsetDateTime,
+    ) -> DomainResult<u64>;
+}
+
+/// 実行時に実装を切り替えられるように、`Arc`で共有したユーザーリポジトリを、ユーザーリポジトリとして扱う。
+#[async_trait::async_trait]
+impl<T> UserRepository for Arc<T>
+where
+    T: UserRepository + ?Sized,
+{
+    async fn create(&self, user: UserInput, hashed_password: PHCString) -> DomainResult<User> {
+        (**self).create(user, hashed_password).await
+    }
+
+    async fn by_id(&self, id: UserId) -> DomainResult<Option<User>> {
+        (**self).by_id(id).await
+    }
+
+    async fn by_email(&self, email: &Email) -> DomainResult<Option<User>> {
+        (**self).by_email(email).await
+    }
+
+    async fn update(&self, id: UserId, user: UpdateUserInput) -> DomainResult<User> {
+        (**self).update(id, user).await
+    }
+
+    async fn update_email(&self, id: UserId, email: Email) -> DomainResult<User> {
+        (**self).update_email(id, email).await
+    }
+
+    async fn preferences(&self, id: UserId) -> DomainResult<UserPreferences> {
+        (**self).preferences(id).await
+    }
+
+    async fn update_preferences(
+        &self,
+        id: UserId,
+        preferences: &UserPreferences,
+    ) -> DomainResult<UserPreferences> {
+        (**self).update_preferences(id, preferences).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_logged_in(
+        &self,
+        id: UserId,
+        logged_in_at: OffsetDateTime,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
+        max_sessions: u32,
+    ) -> DomainResult<Vec<SecretString>> {
+        (**self)
+            .handle_logged_in(
+                id,
+                logged_in_at,
+                access_key,
+                access_expired_at,
+                refresh_key,
+                refresh_expired_at,
+                max_sessions,
+            )
+            .await
+    }
+
+    async fn register_token_pair_for_user(
+        &self,
+        id: UserId,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_token_pair_for_user(
+                id,
+                access_key,
+                access_expired_at,
+                refresh_key,
+                refresh_expired_at,
+            )
+            .await
+    }
+
+    async fn user_tokens_by_id(&self, id: UserId) -> DomainResult<Vec<UserToken>> {
+        (**self).user_tokens_by_id(id).await
+    }
+
+    async fn prune_oldest_sessions(
+        &self,
+        id: UserId,
+        keep_n: u32,
+    ) -> DomainResult<Vec<SecretString>> {
+        (**self).prune_oldest_sessions(id, keep_n).await
+    }
+
+    async fn delete_user_tokens_by_id(&self, id: UserId) -> DomainResult<Vec<SecretString>> {
+        (**self).delete_user_tokens_by_id(id).await
+    }
+
+    async fn get_hashed_password(&self, id: UserId) -> DomainResult<PHCString> {
+        (**self).get_hashed_password(id).await
+    }
+
+    async fn update_hashed_password(
+        &self,
+        id: UserId,
+        hashed_password: PHCString,
+    ) -> DomainResult<()> {
+        (**self).update_hashed_password(id, hashed_password).await
+    }
+
+    async fn delete(&self, id: UserId) -> DomainResult<()> {
+        (**self).delete(id).await
+    }
+
+    async fn create_login_failure_history(
+        &self,
+        user_id: UserId,
+        number_of_attempts: i32,
+        attempted_at: OffsetDateTime,
+    ) -> DomainResult<LoginFailedHistory> {
+        (**self)
+            .create_login_failure_history(user_id, number_of_attempts, attempted_at)
+            .await
+    }
+
+    async fn get_login_failed_history(
+        &self,
+        user_id: UserId,
+    ) -> DomainResult<Option<LoginFailedHistory>> {
+        (**self).get_login_failed_history(user_id).await
+    }
+
+    async fn increment_number_of_login_attempts(
+        &self,
+        user_id: UserId,
+        max_attempts: u32,
+    ) -> DomainResult<()> {
+        (**self)
+            .increment_number_of_login_attempts(user_id, max_attempts)
+            .await
+    }
+
+    async fn reactivate_if_lockout_expired(
+        &self,
+        id: UserId,
+        locked_before: OffsetDateTime,
+    ) -> DomainResult<bool> {
+        (**self)
+            .reactivate_if_lockout_expired(id, locked_before)
+            .await
+    }
+
+    async fn reset_login_failed_history(
+        &self,
+        user_id: UserId,
+        attempted_at: OffsetDateTime,
+    ) -> DomainResult<()> {
+        (**self)
+            .reset_login_failed_history(user_id, attempted_at)
+            .await
+    }
+
+    async fn delete_stale_login_failed_histories(
+        &self,
+        cutoff: OffsetDateTime,
+    ) -> DomainResult<u64> {
+        (**self).delete_stale_login_failed_histories(cutoff).await
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -96,7 +332,10 @@ pub struct UserInput {
 pub struct UpdateUserInput {
     pub family_name: Option<FamilyName>,
     pub given_name: Option<GivenName>,
-    pub email: Option<Email>,
+    /// ロケール
+    ///
+    /// `None`の場合は変更せず、`Some(None)`の場合は消去する。
+    pub locale: Option<Option<String>>,
 }
 
 pub struct UserToken {
@@ -0,0 +1,55 @@
+//! アプリケーションのビルド情報を環境変数に埋め込むビルドスクリプト
+//!
+//! 次の環境変数を`env!`で参照できるように設定する。
+//!
+//! * `BUILD_GIT_SHA`: ビルドしたコミットの短いハッシュ
+//! * `BUILD_TIMESTAMP`: ビルドした日時（UNIXエポックからの秒数）
+//!
+//! `.git`ディレクトリが存在しないDockerイメージなどでビルドする場合は、環境変数`GIT_SHA`でコミットの
+//! ハッシュを指定する。再現可能なビルドのために、環境変数`SOURCE_DATE_EPOCH`でビルドした日時を指定できる。
+use std::{
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    // コミットやブランチを切り替えたときに再実行
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+
+    println!("cargo:rustc-env=BUILD_GIT_SHA={}", git_sha());
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", build_timestamp());
+}
+
+/// ビルドしたコミットの短いハッシュを返す。
+///
+/// 環境変数`GIT_SHA`、`git rev-parse`の順に取得して、どちらも取得できない場合は`unknown`を返す。
+fn git_sha() -> String {
+    if let Some(sha) = std::env::var("GIT_SHA").ok().filter(|sha| !sha.is_empty()) {
+        return sha;
+    }
+    Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|sha| sha.trim().to_string())
+        .filter(|sha| !sha.is_empty())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+/// ビルドした日時をUNIXエポックからの秒数で返す。
+fn build_timestamp() -> u64 {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default()
+        })
+}
@@ -0,0 +1,175 @@
+use std::{fs::File, io::Write as _, sync::Mutex};
+
+use anyhow::Context as _;
+use serde::Serialize;
+use time::{OffsetDateTime, format_description::BorrowedFormatItem, macros::format_description};
+
+use crate::settings::{AccessLogFormat, AccessLogSettings};
+
+/// アクセスログ
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessLogEntry {
+    /// リクエストを受け付けた日時
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    /// HTTPメソッド
+    pub method: String,
+    /// リクエストパス（クエリ文字列を含まない）
+    pub path: String,
+    /// HTTPバージョン
+    #[serde(skip)]
+    pub version: String,
+    /// レスポンスのステータスコード
+    pub status: u16,
+    /// リクエストを処理した時間（ミリ秒）
+    pub duration_ms: u64,
+    /// クライアントのIPアドレス
+    pub ip: Option<String>,
+    /// リクエストID
+    pub request_id: String,
+    /// `Referer`ヘッダー
+    #[serde(skip)]
+    pub referer: Option<String>,
+    /// `User-Agent`ヘッダー
+    #[serde(skip)]
+    pub user_agent: Option<String>,
+}
+
+/// Combined Log Formatの日時の書式
+const COMBINED_TIMESTAMP_FORMAT: &[BorrowedFormatItem<'static>] = format_description!(
+    "[day]/[month repr:short]/[year]:[hour]:[minute]:[second] [offset_hour sign:mandatory][offset_minute]"
+);
+
+impl AccessLogFormat {
+    /// アクセスログを書式に従って1行の文字列にする。
+    pub fn render(self, entry: &AccessLogEntry) -> String {
+        match self {
+            AccessLogFormat::Json => serde_json::to_string(entry).unwrap_or_default(),
+            AccessLogFormat::Combined => {
+                let timestamp = entry
+                    .timestamp
+                    .format(COMBINED_TIMESTAMP_FORMAT)
+                    .unwrap_or_default();
+                format!(
+                    r#"{} - - [{}] "{} {} {}" {} - "{}" "{}""#,
+                    entry.ip.as_deref().unwrap_or("-"),
+                    timestamp,
+                    entry.method,
+                    entry.path,
+                    entry.version,
+                    entry.status,
+                    entry.referer.as_deref().unwrap_or("-"),
+                    entry.user_agent.as_deref().unwrap_or("-"),
+                )
+            }
+        }
+    }
+}
+
+/// アクセスログの出力先
+///
+/// アクセスログは、アプリケーションのログと分けて出力する。
+pub trait AccessLogSink: std::fmt::Debug + Send + Sync {
+    /// アクセスログを1行書き込む。
+    fn write(&self, line: &str);
+}
+
+/// 標準出力にアクセスログを書き込む出力先
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutAccessLogSink;
+
+impl AccessLogSink for StdoutAccessLogSink {
+    fn write(&self, line: &str) {
+        let mut stdout = std::io::stdout().lock();
+        if let Err(e) = writeln!(stdout, "{}", line) {
+            tracing::error!("Failed to write the access log: {}", e);
+        }
+    }
+}
+
+/// ファイルにアクセスログを追記する出力先
+#[derive(Debug)]
+pub struct FileAccessLogSink {
+    file: Mutex<File>,
+}
+
+impl FileAccessLogSink {
+    /// アクセスログを追記するファイルを開く。
+    ///
+    /// ファイルが存在しない場合は作成する。
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open the access log file: {}", path))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AccessLogSink for FileAccessLogSink {
+    fn write(&self, line: &str) {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{}", line) {
+            tracing::error!("Failed to write the access log: {}", e);
+        }
+    }
+}
+
+/// アクセスログ設定から、アクセスログの出力先を作成する。
+///
+/// ファイルのパスが指定されていない場合は、標準出力に出力する。
+pub fn create_access_log_sink(
+    settings: &AccessLogSettings,
+) -> anyhow::Result<std::sync::Arc<dyn AccessLogSink>> {
+    match &settings.path {
+        Some(path) => Ok(std::sync::Arc::new(FileAccessLogSink::open(path)?)),
+        None => Ok(std::sync::Arc::new(StdoutAccessLogSink)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    fn entry() -> AccessLogEntry {
+        AccessLogEntry {
+            timestamp: datetime!(2025-06-01 12:34:56 UTC),
+            method: String::from("GET"),
+            path: String::from("/api/v1/todos"),
+            version: String::from("HTTP/1.1"),
+            status: 200,
+            duration_ms: 42,
+            ip: Some(String::from("127.0.0.1")),
+            request_id: String::from("5f0c6f3e-7a63-4b1e-9d59-5c1f4ad1d0e1"),
+            referer: None,
+            user_agent: Some(String::from("curl/8.0")),
+        }
+    }
+
+    #[test]
+    fn render_json_access_log() {
+        let line = AccessLogFormat::Json.render(&entry());
+        let value = serde_json::from_str::<serde_json::Value>(&line).unwrap();
+        assert_eq!(value["timestamp"], "2025-06-01T12:34:56Z");
+        assert_eq!(value["method"], "GET");
+        assert_eq!(value["path"], "/api/v1/todos");
+        assert_eq!(value["status"], 200);
+        assert_eq!(value["duration_ms"], 42);
+        assert_eq!(value["ip"], "127.0.0.1");
+        assert_eq!(value["request_id"], "5f0c6f3e-7a63-4b1e-9d59-5c1f4ad1d0e1");
+    }
+
+    #[test]
+    fn render_combined_access_log() {
+        let line = AccessLogFormat::Combined.render(&entry());
+        assert_eq!(
+            line,
+            r#"127.0.0.1 - - [01/Jun/2025:12:34:56 +0000] "GET /api/v1/todos HTTP/1.1" 200 - "-" "curl/8.0""#
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! アプリケーションのビルド情報
+//!
+//! コミットのハッシュとビルドした日時は、`build.rs`がコンパイル時に環境変数として埋め込む。
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, serde::rfc3339};
+
+/// コミットのハッシュを取得できなかったときの値
+const UNKNOWN_GIT_SHA: &str = "unknown";
+
+/// アプリケーションのビルド情報
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    /// アプリケーションのバージョン
+    pub version: String,
+    /// ビルドしたコミットの短いハッシュ
+    pub git_sha: String,
+    /// ビルドした日時
+    #[serde(with = "rfc3339")]
+    pub built_at: OffsetDateTime,
+}
+
+impl BuildInfo {
+    /// コンパイル時に埋め込んだビルド情報を返す。
+    pub fn current() -> Self {
+        let built_at = env!("BUILD_TIMESTAMP")
+            .parse::<i64>()
+            .ok()
+            .and_then(|timestamp| OffsetDateTime::from_unix_timestamp(timestamp).ok())
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+        Self {
+            version: env!("CARGO_PKG_VERSION").into(),
+            git_sha: env!("BUILD_GIT_SHA").into(),
+            built_at,
+        }
+    }
+
+    /// `X-App-Version`ヘッダーに設定するバージョンを返す。
+    ///
+    /// コミットのハッシュをセマンティックバージョニングのビルドメタデータとして付与する（例: `0.1.0+1a2b3c4`）。
+    /// コミットのハッシュを取得できなかった場合は、バージョンのみを返す。
+    pub fn version_with_git_sha(&self) -> String {
+        if self.git_sha == UNKNOWN_GIT_SHA {
+            self.version.clone()
+        } else {
+            format!("{}+{}", self.version, self.git_sha)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_with_git_sha_appends_the_git_sha_as_build_metadata() {
+        let mut build_info = BuildInfo {
+            version: String::from("0.1.0"),
+            git_sha: String::from("1a2b3c4"),
+            built_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        assert_eq!(build_info.version_with_git_sha(), "0.1.0+1a2b3c4");
+        build_info.git_sha = String::from(UNKNOWN_GIT_SHA);
+        assert_eq!(build_info.version_with_git_sha(), "0.1.0");
+    }
+}
@@ -0,0 +1,141 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use domain::{
+    DomainResult,
+    api::{
+        DependencyPoolStatus, DependencyStatus, ImpersonationResponseBody,
+        ServerStatusResponseBody, TodoTransferRequestBody, TodoTransferResponseBody,
+    },
+    models::UserId,
+    repositories::{
+        TokenRepository as _, TokenType, UserRepository as _, generate_impersonation_token_info,
+    },
+};
+use use_case::AuthorizedUser;
+
+use crate::{
+    AppState,
+    http::{ApiError, ApiResult, not_found, user_locked},
+    jwt::{Claim, generate_token},
+};
+
+/// 管理者がユーザーになりすますためのアクセストークンを発行する。
+///
+/// なりすましトークンは読み取り専用で、有効期限は`token.impersonation_max_age`で指定する。
+/// なりすましトークンはクッキーに登録せず、レスポンスボディでのみ返す。
+#[tracing::instrument(skip(app_state))]
+pub async fn impersonate(
+    State(app_state): State<AppState>,
+    Extension(admin): Extension<AuthorizedUser>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<ImpersonationResponseBody>> {
+    let requested_at = OffsetDateTime::now_utc();
+    let settings = &app_state.app_settings.token;
+    let user_repo = app_state.repos.user.clone();
+    let token_repo = app_state.repos.token.clone();
+    // なりすますユーザーを取得
+    let user = user_repo
+        .by_id(UserId::from(user_id))
+        .await?
+        .ok_or_else(|| not_found("User"))?;
+    // ユーザーがロックされている場合は、423 Lockedを返す
+    if !user.active {
+        return Err(user_locked());
+    }
+    // なりすましトークンを生成して、トークンリポジトリに登録
+    let access_expired_at = requested_at + Duration::seconds(settings.impersonation_max_age);
+    let claim = Claim::new(
+        user.id,
+        TokenType::Impersonation,
+        requested_at,
+        access_expired_at,
+    );
+    let access_token = generate_token(claim, settings)?;
+    let token_info = generate_impersonation_token_info(
+        user.id,
+        admin.id,
+        &access_token,
+        settings.impersonation_max_age as u64,
+    );
+    token_repo.register_token(&token_info).await?;
+    tracing::info!(
+        user_id = %user.id,
+        impersonated_by = %admin.id,
+        "Impersonation granted"
+    );
+    Ok(Json(ImpersonationResponseBody {
+        access_token,
+        access_expired_at,
+    }))
+}
+
+/// ユーザーのTodoを、他のユーザーに移管する。
+///
+/// 退職したユーザーのTodoを引き継ぐときなどに使用する。
+/// すべてのTodoを1つのトランザクションで移管して、移管したTodoの数を返す。
+#[tracing::instrument(skip(app_state))]
+pub async fn transfer_todos(
+    State(app_state): State<AppState>,
+    Extension(admin): Extension<AuthorizedUser>,
+    Json(body): Json<TodoTransferRequestBody>,
+) -> ApiResult<Json<TodoTransferResponseBody>> {
+    let from = UserId::from(body.from_user_id);
+    let to = UserId::from(body.to_user_id);
+    let user_repo = app_state.repos.user.clone();
+    // 移管元と移管先のユーザーが存在することを確認
+    if user_repo.by_id(from).await?.is_none() {
+        return Err(not_found("Source user"));
+    }
+    if user_repo.by_id(to).await?.is_none() {
+        return Err(not_found("Destination user"));
+    }
+    let transferred = app_state
+        .todo_use_case
+        .transfer_ownership(from, to, body.include_completed)
+        .await
+        .map_err(ApiError::from)?;
+    tracing::info!(
+        from_user_id = %from,
+        to_user_id = %to,
+        include_completed = body.include_completed,
+        transferred,
+        transferred_by = %admin.id,
+        "Todos transferred"
+    );
+    Ok(Json(TodoTransferResponseBody { transferred }))
+}
+
+/// サーバーの状態を返す。
+///
+/// アプリケーションのバージョン、起動してからの経過秒数、PostgreSQLとRedisのコネクションプールの状態と疎通を返す。
+pub async fn status(State(app_state): State<AppState>) -> Json<ServerStatusResponseBody> {
+    let postgres = DependencyPoolStatus {
+        status: dependency_status("database", app_state.ping_database().await),
+        pool: app_state.database_pool_status(),
+    };
+    let redis = DependencyPoolStatus {
+        status: dependency_status("redis", app_state.ping_token_store().await),
+        pool: app_state.token_store_pool_status(),
+    };
+    Json(ServerStatusResponseBody {
+        version: app_state.build_info.version.clone(),
+        uptime_seconds: app_state.uptime().as_secs(),
+        postgres,
+        redis,
+    })
+}
+
+fn dependency_status(name: &str, result: DomainResult<()>) -> DependencyStatus {
+    match result {
+        Ok(()) => DependencyStatus::Ok,
+        Err(e) => {
+            tracing::warn!("The {} is degraded: {:#}", name, e.source);
+            DependencyStatus::Degraded
+        }
+    }
+}
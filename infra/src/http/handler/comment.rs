@@ -0,0 +1,84 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use domain::{
+    api::{TodoCommentCreateRequestBody, TodoCommentListQueryParams},
+    models::{TodoComment, TodoCommentId, TodoId},
+    repositories::TodoCommentListInput,
+};
+use use_case::AuthorizedUser;
+
+use crate::{AppState, http::ApiResult};
+
+/// Todoのコメントを、作成日時の降順でリストする。
+#[tracing::instrument(skip(app_state))]
+pub async fn list(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthorizedUser>,
+    todo_id: Path<Uuid>,
+    query: Query<TodoCommentListQueryParams>,
+) -> ApiResult<Json<Vec<TodoComment>>> {
+    let todo_id = TodoId::from(todo_id.0);
+    let input = TodoCommentListInput::new(todo_id, query.page, query.page_size)?;
+    let use_case = &app_state.comment_use_case;
+    let comments = use_case.list(auth_user, input).await?;
+    Ok(Json(comments))
+}
+
+/// Todoにコメントを登録する。
+#[tracing::instrument(skip(app_state))]
+pub async fn create(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthorizedUser>,
+    todo_id: Path<Uuid>,
+    Json(body): Json<TodoCommentCreateRequestBody>,
+) -> ApiResult<impl IntoResponse> {
+    let todo_id = TodoId::from(todo_id.0);
+    let body = body.into_body()?;
+    let use_case = &app_state.comment_use_case;
+    let comment = use_case.create(auth_user, todo_id, body).await?;
+    Ok((StatusCode::CREATED, Json(comment)))
+}
+
+/// コメントを削除する。
+#[tracing::instrument(skip(app_state))]
+pub async fn delete(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthorizedUser>,
+    Path((todo_id, comment_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<impl IntoResponse> {
+    let todo_id = TodoId::from(todo_id);
+    let comment_id = TodoCommentId::from(comment_id);
+    let use_case = &app_state.comment_use_case;
+    use_case.delete(auth_user, todo_id, comment_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::ApiError;
+
+    use super::*;
+
+    #[test]
+    fn todo_comment_create_request_body_with_empty_body() {
+        let body = TodoCommentCreateRequestBody {
+            body: String::from("  "),
+        };
+        let Err(error) = body.into_body().map_err(ApiError::from) else {
+            panic!("empty body must be rejected");
+        };
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(error.messages.len(), 1);
+        assert!(
+            error.messages[0].starts_with("body: "),
+            "{:?}",
+            error.messages
+        );
+    }
+}
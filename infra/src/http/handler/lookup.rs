@@ -1,37 +1,70 @@
-use axum::{Json, extract::State};
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderName, header::CACHE_CONTROL},
+};
 
 use crate::{
     AppState,
-    http::{ApiError, ApiResult},
+    http::{ApiError, ApiResult, not_found},
+    settings::LookupSettings,
 };
 
+/// 参照データのレスポンスに付与する`Cache-Control`ヘッダー
+type CacheControlHeader = [(HeaderName, String); 1];
+
+fn cache_control_header(settings: &LookupSettings) -> CacheControlHeader {
+    [(CACHE_CONTROL, settings.cache_control())]
+}
+
 pub mod role {
     use super::*;
 
     use domain::models::{Role, RoleCode};
     use use_case::lookup::{LookupUseCase, RoleUseCase};
 
-    use crate::postgres::repositories::PgRoleRepository;
+    /// キャッシュ、またはデータベースからロールのリストを取得する。
+    async fn cached_list(app_state: &AppState) -> ApiResult<Vec<Role>> {
+        let settings = &app_state.app_settings.lookup;
+        app_state
+            .lookup_cache
+            .roles
+            .get_or_try_load(settings.cache_ttl(), || async {
+                let repo = app_state.repos.role.clone();
+                let use_case = RoleUseCase { repo };
+                use_case.list().await
+            })
+            .await
+            .map_err(ApiError::from)
+    }
 
-    #[tracing::instrument(skip(app_state))]
-    pub async fn list(State(app_state): State<AppState>) -> ApiResult<Json<Vec<Role>>> {
-        let pool = app_state.pg_pool.clone();
-        let repo = PgRoleRepository { pool };
-        let use_case = RoleUseCase { repo };
-        Ok(Json(use_case.list().await.map_err(ApiError::from)?))
+    pub async fn list(
+        State(app_state): State<AppState>,
+    ) -> ApiResult<(CacheControlHeader, Json<Vec<Role>>)> {
+        let roles = cached_list(&app_state).await?;
+        Ok((
+            cache_control_header(&app_state.app_settings.lookup),
+            Json(roles),
+        ))
     }
 
-    #[tracing::instrument(skip(app_state))]
+    /// コード、またはスネークケースの名前（`admin`など）でロールを取得する。
+    ///
+    /// 存在しないコードや名前が指定された場合は、`404 Not Found`を返す。
     pub async fn by_code(
         State(app_state): State<AppState>,
-        code: axum::extract::Path<i16>,
-    ) -> ApiResult<Json<Option<Role>>> {
-        let code = RoleCode::try_from(code.0).map_err(ApiError::from)?;
-        let pool = app_state.pg_pool.clone();
-        let repo = PgRoleRepository { pool };
-        let use_case = RoleUseCase { repo };
-        let role = use_case.by_code(&code).await.map_err(ApiError::from)?;
-        Ok(Json(role))
+        code: axum::extract::Path<String>,
+    ) -> ApiResult<(CacheControlHeader, Json<Role>)> {
+        let code = RoleCode::try_from(code.0).map_err(|_| not_found("role"))?;
+        let role = cached_list(&app_state)
+            .await?
+            .into_iter()
+            .find(|role| role.code == code)
+            .ok_or_else(|| not_found("role"))?;
+        Ok((
+            cache_control_header(&app_state.app_settings.lookup),
+            Json(role),
+        ))
     }
 }
 
@@ -41,26 +74,47 @@ pub mod todo_status {
     use domain::models::{TodoStatus, TodoStatusCode};
     use use_case::lookup::{LookupUseCase, TodoStatusUseCase};
 
-    use crate::postgres::repositories::PgTodoStatusRepository;
+    /// キャッシュ、またはデータベースからTodo状態のリストを取得する。
+    async fn cached_list(app_state: &AppState) -> ApiResult<Vec<TodoStatus>> {
+        let settings = &app_state.app_settings.lookup;
+        app_state
+            .lookup_cache
+            .todo_statuses
+            .get_or_try_load(settings.cache_ttl(), || async {
+                let repo = app_state.repos.todo_status.clone();
+                let use_case = TodoStatusUseCase { repo };
+                use_case.list().await
+            })
+            .await
+            .map_err(ApiError::from)
+    }
 
-    #[tracing::instrument(skip(app_state))]
-    pub async fn list(State(app_state): State<AppState>) -> ApiResult<Json<Vec<TodoStatus>>> {
-        let pool = app_state.pg_pool.clone();
-        let repo = PgTodoStatusRepository { pool };
-        let use_case = TodoStatusUseCase { repo };
-        Ok(Json(use_case.list().await.map_err(ApiError::from)?))
+    pub async fn list(
+        State(app_state): State<AppState>,
+    ) -> ApiResult<(CacheControlHeader, Json<Vec<TodoStatus>>)> {
+        let todo_statuses = cached_list(&app_state).await?;
+        Ok((
+            cache_control_header(&app_state.app_settings.lookup),
+            Json(todo_statuses),
+        ))
     }
 
-    #[tracing::instrument(skip(app_state))]
+    /// コード、またはスネークケースの名前（`in_progress`など）でTodo状態を取得する。
+    ///
+    /// 存在しないコードや名前が指定された場合は、`404 Not Found`を返す。
     pub async fn by_code(
         State(app_state): State<AppState>,
-        code: axum::extract::Path<i16>,
-    ) -> ApiResult<Json<Option<TodoStatus>>> {
-        let code = TodoStatusCode::try_from(code.0).map_err(ApiError::from)?;
-        let pool = app_state.pg_pool.clone();
-        let repo = PgTodoStatusRepository { pool };
-        let use_case = TodoStatusUseCase { repo };
-        let role = use_case.by_code(&code).await.map_err(ApiError::from)?;
-        Ok(Json(role))
+        code: axum::extract::Path<String>,
+    ) -> ApiResult<(CacheControlHeader, Json<TodoStatus>)> {
+        let code = TodoStatusCode::try_from(code.0).map_err(|_| not_found("todo status"))?;
+        let todo_status = cached_list(&app_state)
+            .await?
+            .into_iter()
+            .find(|todo_status| todo_status.code == code)
+            .ok_or_else(|| not_found("todo status"))?;
+        Ok((
+            cache_control_header(&app_state.app_settings.lookup),
+            Json(todo_status),
+        ))
     }
 }
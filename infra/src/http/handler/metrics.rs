@@ -0,0 +1,55 @@
+use std::fmt::Write as _;
+
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use crate::AppState;
+
+/// Prometheusのテキスト形式のメディアタイプ
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// コネクションプールのメトリクスを、Prometheusのテキスト形式で返す。
+///
+/// SQLiteを使用している場合は、`pg_pool_*`にSQLiteのコネクションプールの状態を返して、`redis_pool_*`は0を返す。
+pub async fn metrics(State(app_state): State<AppState>) -> impl IntoResponse {
+    let pg = app_state.database_pool_status();
+    let redis = app_state.token_store_pool_status();
+    let gauges = [
+        (
+            "pg_pool_size",
+            "Number of connections currently held by the PostgreSQL pool",
+            pg.size,
+        ),
+        (
+            "pg_pool_idle",
+            "Number of idle connections in the PostgreSQL pool",
+            pg.idle,
+        ),
+        (
+            "pg_pool_in_use",
+            "Number of connections in use in the PostgreSQL pool",
+            pg.in_use,
+        ),
+        (
+            "redis_pool_size",
+            "Number of connections currently held by the Redis pool",
+            redis.size,
+        ),
+        (
+            "redis_pool_idle",
+            "Number of idle connections in the Redis pool",
+            redis.idle,
+        ),
+        (
+            "redis_pool_in_use",
+            "Number of connections in use in the Redis pool",
+            redis.in_use,
+        ),
+    ];
+    let mut body = String::new();
+    for (name, help, value) in gauges {
+        let _ = writeln!(body, "# HELP {} {}", name, help);
+        let _ = writeln!(body, "# TYPE {} gauge", name);
+        let _ = writeln!(body, "{} {}", name, value);
+    }
+    ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], body)
+}
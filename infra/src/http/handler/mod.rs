@@ -1,35 +1,85 @@
+pub mod admin;
+pub mod comment;
 pub mod lookup;
+pub mod metrics;
 pub mod todo;
 pub mod user;
 
-use use_case::{todo::TodoUseCase, user::UserUseCase};
+use std::time::Duration;
+
+use axum::{Json, extract::State, http::StatusCode};
+use serde::{Deserialize, Serialize};
+
+use domain::api::DependencyStatus;
 
 use crate::{
     AppState,
-    postgres::repositories::{PgTodoRepository, PgUserRepository},
-    redis::token::RedisTokenRepository,
+    build_info::BuildInfo,
+    http::{ApiError, not_found},
 };
 
+/// Redisの状態を確認するときのタイムアウト
+const REDIS_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
 /// ヘルスチェックハンドラ
-#[tracing::instrument()]
-pub async fn health_check() -> &'static str {
-    "Ok, the server is running!"
+///
+/// Redisに`PING`を送信して、応答がない場合やタイムアウトした場合は503 Service Unavailableを返す。
+/// Redisの代わりにインプロセスのトークンストアを使用している場合は、常に200 OKを返す。
+/// どのビルドが稼働しているか確認できるように、レスポンスボディにアプリケーションのビルド情報を含める。
+pub async fn health_check(
+    State(app_state): State<AppState>,
+) -> (StatusCode, Json<HealthCheckResponseBody>) {
+    let redis = match app_state.redis_token_repo() {
+        Some(token_repo) => {
+            match tokio::time::timeout(REDIS_HEALTH_CHECK_TIMEOUT, token_repo.ping()).await {
+                Ok(Ok(())) => DependencyStatus::Ok,
+                Ok(Err(e)) => {
+                    tracing::warn!("The redis is degraded: {:#}", e.source);
+                    DependencyStatus::Degraded
+                }
+                Err(_) => {
+                    tracing::warn!("The redis is degraded: PING timed out");
+                    DependencyStatus::Degraded
+                }
+            }
+        }
+        None => DependencyStatus::Ok,
+    };
+    let (status_code, message) = match redis {
+        DependencyStatus::Ok => (StatusCode::OK, "Ok, the server is running!"),
+        DependencyStatus::Degraded => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The server is running, but some dependencies are degraded",
+        ),
+    };
+    let body = HealthCheckResponseBody {
+        status: redis,
+        message: message.into(),
+        redis,
+        build: app_state.build_info.clone(),
+    };
+    (status_code, Json(body))
 }
 
-type UserUseCaseImpl = UserUseCase<PgUserRepository, RedisTokenRepository>;
-
-fn user_use_case(app_state: &AppState) -> UserUseCaseImpl {
-    let user_repo = PgUserRepository::new(app_state.pg_pool.clone());
-    let token_repo = RedisTokenRepository::new(app_state.redis_pool.clone());
-    UserUseCase {
-        user_repo,
-        token_repo,
-    }
+/// ヘルスチェックのレスポンスボディ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckResponseBody {
+    /// サーバーの状態
+    ///
+    /// 依存するサービスのいずれかが利用できない場合は`degraded`になる。
+    pub status: DependencyStatus,
+    /// メッセージ
+    pub message: String,
+    /// Redisの状態
+    pub redis: DependencyStatus,
+    /// アプリケーションのビルド情報
+    #[serde(flatten)]
+    pub build: BuildInfo,
 }
 
-type TodoUseCaseImpl = TodoUseCase<PgTodoRepository>;
-
-fn todo_use_case(app_state: &AppState) -> TodoUseCaseImpl {
-    let todo_repo = PgTodoRepository::new(app_state.pg_pool.clone());
-    TodoUseCase { todo_repo }
+/// APIのパスに一致するルートが存在しない場合のハンドラ
+///
+/// 静的ファイルのフォールバックとして`index.html`を返さないように、JSON形式のAPIエラーを返す。
+pub async fn api_fallback() -> ApiError {
+    not_found("Resource")
 }
@@ -1,35 +1,45 @@
 use axum::{
     Extension, Json,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::IntoResponse,
 };
-use serde::{Deserialize, Serialize};
-use time::Date;
+use jwt::{FromBase64 as _, ToBase64 as _};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use domain::{
-    NumericOperator,
-    models::{Todo, TodoDescription, TodoId, TodoStatusCode, TodoTitle},
-    repositories::{TodoCreateInput, TodoListInput, TodoUpdateInput},
+    api::{
+        self, TodoArchiveRequestBody, TodoCreateRequestBody, TodoListQueryParams,
+        TodoReopenRequestBody, TodoUpdateRequestBody,
+    },
+    models::{Todo, TodoId, TodoStatusCode},
+    repositories::{ArchivedFilter, TodoListCursor, TodoListInput, TodoListKeyset},
 };
 use use_case::AuthorizedUser;
-use utils::{
-    serde::{deserialize_option_date, deserialize_option_split_comma, serialize_option_date},
-    time::DATE_FORMAT,
-};
 
 use crate::{
     AppState,
-    http::{ApiError, ApiResult, handler::todo_use_case, not_found},
+    http::{ApiError, ApiResult, bad_request, merge_patch::MergePatch, not_found},
 };
 
+/// 次のページのTodoリストを取得するカーソルを格納するヘッダー
+pub const X_NEXT_CURSOR: HeaderName = HeaderName::from_static(api::X_NEXT_CURSOR);
+
+/// ログインしているユーザーのTodoリストを返す。
+///
+/// 状態コード（`statuses`）と表示範囲（`view`）の両方が指定された場合は、状態コードで絞り込み、表示範囲は
+/// 並び順にのみ影響する。ユーザーの設定に保存された既定の状態コードも、指定された状態コードと同様に扱う。
+/// 表示範囲が指定されていない場合は、完了していないTodoのみを返す。
+/// ページ番号（`page`）と1ページあたりのTodo数（`pageSize`）のどちらも指定されていない場合は、ページに分割しない。
+/// カーソル（`cursor`）または1ページあたりのTodo数（`limit`）が指定された場合は、カーソルで分割して、
+/// 1ページ分のTodoを返したときに、次のページのカーソルを`X-Next-Cursor`ヘッダーに格納する。
 #[tracing::instrument(skip(app_state))]
 pub async fn list(
     State(app_state): State<AppState>,
     Extension(user): Extension<AuthorizedUser>,
     query: Query<TodoListQueryParams>,
-) -> ApiResult<Json<Vec<Todo>>> {
+) -> ApiResult<(HeaderMap, Json<Vec<Todo>>)> {
     let TodoListQueryParams {
         keyword,
         op,
@@ -37,6 +47,12 @@ pub async fn list(
         to,
         statuses,
         archived,
+        view,
+        sort,
+        page,
+        page_size,
+        cursor,
+        limit,
     } = query.0;
 
     let statuses = if let Some(statuses) = statuses {
@@ -50,11 +66,48 @@ pub async fn list(
     } else {
         None
     };
-    let input = TodoListInput::new(user.0.id, keyword, op, from, to, statuses, archived)
-        .map_err(ApiError::from)?;
-    let use_case = todo_use_case(&app_state);
-    let todos = use_case.list(input).await.map_err(ApiError::from)?;
-    Ok(Json(todos))
+    // クエリパラメーターで指定されなかった条件には、ユーザーの設定を適用
+    let preferences = app_state.user_use_case.preferences(&user).await?;
+    let statuses = statuses.or_else(|| {
+        (!preferences.default_statuses.is_empty()).then(|| preferences.default_statuses.clone())
+    });
+    let archived = archived.or(preferences.show_archived.then_some(ArchivedFilter::All));
+    let mut input = TodoListInput::new(
+        user.id, keyword, op, from, to, statuses, archived, page, page_size,
+    )
+    .map_err(ApiError::from)?;
+    input.view = view.unwrap_or_default();
+    input.sort = sort.unwrap_or(preferences.default_sort);
+    let after = cursor.as_deref().map(decode_todo_list_cursor).transpose()?;
+    input.keyset = TodoListKeyset::new(after, limit).map_err(ApiError::from)?;
+    // 絞り込み条件と並び順がカーソルを発行したときと一致するか確認
+    input.validate().map_err(ApiError::from)?;
+    let use_case = &app_state.todo_use_case;
+    let todos = use_case.list(input.clone()).await.map_err(ApiError::from)?;
+    // 1ページ分のTodoを返す場合は、最後のTodoを示すカーソルを返す
+    let mut headers = HeaderMap::new();
+    let next_cursor = input
+        .keyset
+        .as_ref()
+        .filter(|keyset| todos.len() == keyset.limit as usize)
+        .and_then(|_| todos.last())
+        .map(|todo| encode_todo_list_cursor(&TodoListCursor::new(&input, todo)));
+    if let Some(next_cursor) = next_cursor {
+        headers.insert(X_NEXT_CURSOR, HeaderValue::from_str(&next_cursor).unwrap());
+    }
+    Ok((headers, Json(todos)))
+}
+
+/// Todoリストのカーソルを、URLに含められる不透明な文字列に変換する。
+fn encode_todo_list_cursor(cursor: &TodoListCursor) -> String {
+    cursor.to_base64().unwrap().into_owned()
+}
+
+/// 文字列からTodoリストのカーソルを復元する。
+///
+/// 文字列が`encode_todo_list_cursor`で生成されたものでない場合は、400 Bad Requestを返す。
+fn decode_todo_list_cursor(cursor: &str) -> ApiResult<TodoListCursor> {
+    TodoListCursor::from_base64(cursor).map_err(|_| bad_request("cursor: malformed".into()))
 }
 
 #[tracing::instrument(skip(app_state))]
@@ -64,7 +117,7 @@ pub async fn by_id(
     todo_id: Path<Uuid>,
 ) -> ApiResult<Json<Todo>> {
     let todo_id = TodoId::from(todo_id.0);
-    let use_case = todo_use_case(&app_state);
+    let use_case = &app_state.todo_use_case;
     let todo = use_case
         .by_id(auth_user, todo_id)
         .await
@@ -81,25 +134,33 @@ pub async fn create(
     Extension(auth_user): Extension<AuthorizedUser>,
     Json(body): Json<TodoCreateRequestBody>,
 ) -> ApiResult<impl IntoResponse> {
-    let input = TodoCreateInput::try_from(body)?;
-    let use_case = todo_use_case(&app_state);
+    let allow_duplicate = body.allow_duplicate;
+    let input = body
+        .into_input(OffsetDateTime::now_utc().date())
+        .map_err(ApiError::from)?;
+    let use_case = &app_state.todo_use_case;
     let todo = use_case
-        .create(auth_user, input)
+        .create(auth_user, input, allow_duplicate)
         .await
         .map_err(ApiError::from)?;
     Ok((StatusCode::CREATED, Json(todo)))
 }
 
+/// Todoを更新する。
+///
+/// `Content-Type`が`application/merge-patch+json`の場合は、`null`を指定した説明と完了予定日を消去する。
 #[tracing::instrument(skip(app_state))]
 pub async fn update(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthorizedUser>,
     todo_id: Path<Uuid>,
-    Json(body): Json<TodoUpdateRequestBody>,
+    MergePatch(body): MergePatch<TodoUpdateRequestBody>,
 ) -> ApiResult<Json<Todo>> {
     let todo_id = TodoId::from(todo_id.0);
-    let input = TodoUpdateInput::try_from(body)?;
-    let use_case = todo_use_case(&app_state);
+    let input = body
+        .into_input(OffsetDateTime::now_utc().date())
+        .map_err(ApiError::from)?;
+    let use_case = &app_state.todo_use_case;
     let updated_todo = use_case
         .update(auth_user, todo_id, input)
         .await
@@ -114,7 +175,7 @@ pub async fn complete(
     todo_id: Path<Uuid>,
 ) -> ApiResult<Json<Todo>> {
     let todo_id = TodoId::from(todo_id.0);
-    let use_case = todo_use_case(&app_state);
+    let use_case = &app_state.todo_use_case;
     let completed_todo = use_case
         .complete(auth_user, todo_id)
         .await
@@ -130,7 +191,7 @@ pub async fn reopen(
     Json(body): Json<TodoReopenRequestBody>,
 ) -> ApiResult<Json<Todo>> {
     let todo_id = TodoId::from(todo_id.0);
-    let use_case = todo_use_case(&app_state);
+    let use_case = &app_state.todo_use_case;
     let reopened_todo = use_case
         .reopen(auth_user, todo_id, body.todo_status_code)
         .await
@@ -146,7 +207,7 @@ pub async fn archive(
     Json(body): Json<TodoArchiveRequestBody>,
 ) -> ApiResult<Json<Todo>> {
     let todo_id = TodoId::from(todo_id.0);
-    let use_case = todo_use_case(&app_state);
+    let use_case = &app_state.todo_use_case;
     let reopened_todo = use_case
         .archive(auth_user, todo_id, body.archived)
         .await
@@ -161,7 +222,7 @@ pub async fn delete(
     todo_id: Path<Uuid>,
 ) -> ApiResult<impl IntoResponse> {
     let todo_id = TodoId::from(todo_id.0);
-    let use_case = todo_use_case(&app_state);
+    let use_case = &app_state.todo_use_case;
     let _ = use_case
         .delete(auth_user, todo_id)
         .await
@@ -169,127 +230,209 @@ pub async fn delete(
     Ok(StatusCode::NO_CONTENT)
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct TodoListQueryParams {
-    /// 検索キーワード
-    pub keyword: Option<String>,
-    /// 完了予定日検索の演算子
-    pub op: Option<NumericOperator>,
-    /// 完了予定日の開始日
-    pub from: Option<Date>,
-    /// 完了予定日の終了日
-    pub to: Option<Date>,
-    /// タスクのステータス
-    #[serde(default, deserialize_with = "deserialize_option_split_comma")]
-    pub statuses: Option<Vec<i16>>,
-    /// アーカイブされたタスクを含めるかどうか
-    pub archived: Option<bool>,
-}
+#[cfg(test)]
+mod tests {
+    use time::{Date, macros::date};
 
-impl std::fmt::Display for TodoListQueryParams {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut params: Vec<String> = vec![];
-        if let Some(keyword) = &self.keyword {
-            params.push(format!("keyword={}", keyword));
-        }
-        if let Some(op) = self.op {
-            params.push(format!("op={}", op));
-        }
-        if let Some(from) = self.from {
-            params.push(format!("from={}", from.format(&DATE_FORMAT).unwrap()));
-        }
-        if let Some(to) = self.to {
-            params.push(format!("to={}", to.format(&DATE_FORMAT).unwrap()));
-        }
-        if let Some(statuses) = &self.statuses {
-            params.push(format!(
-                "statuses={}",
-                statuses
-                    .iter()
-                    .map(|status| status.to_string())
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ));
-        }
-        if let Some(archived) = self.archived {
-            params.push(format!("archived={}", archived));
+    use super::*;
+
+    fn today() -> Date {
+        date!(2025 - 06 - 15)
+    }
+
+    #[rstest::rstest]
+    #[case(r#"{"statusCode": 0}"#, "0")]
+    #[case(r#"{"statusCode": 6}"#, "6")]
+    #[case(r#"{"statusCode": 32768}"#, "32768")]
+    #[case(r#"{"statusCode": -1}"#, "-1")]
+    #[case(r#"{"statusCode": 65537}"#, "65537")]
+    #[case(r#"{"statusCode": "abc"}"#, "\"abc\"")]
+    fn todo_update_request_body_with_invalid_status_code(#[case] json: &str, #[case] value: &str) {
+        let body: TodoUpdateRequestBody = serde_json::from_str(json).unwrap();
+        let Err(error) = body.into_input(today()).map_err(ApiError::from) else {
+            panic!("status code must be rejected: {value}");
+        };
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(error.messages.len(), 1);
+        let message = &error.messages[0];
+        assert!(
+            message.starts_with(&format!("statusCode: Invalid todo status code: {value}")),
+            "{message}"
+        );
+        assert!(message.contains("3 (completed)"), "{message}");
+    }
+
+    #[rstest::rstest]
+    #[case(r#"{"statusCode": 1}"#, Some(TodoStatusCode::NotStarted))]
+    #[case(r#"{"statusCode": 5}"#, Some(TodoStatusCode::OnHold))]
+    #[case(r#"{}"#, None)]
+    fn todo_update_request_body_with_valid_status_code(
+        #[case] json: &str,
+        #[case] expected: Option<TodoStatusCode>,
+    ) {
+        let body: TodoUpdateRequestBody = serde_json::from_str(json).unwrap();
+        let Ok(input) = body.into_input(today()) else {
+            panic!("status code must be accepted: {json}");
+        };
+        assert_eq!(input.status_code, expected);
+    }
+
+    #[rstest::rstest]
+    #[case(r#"{"title": "title", "dueDate": "2024-06-15"}"#, None)]
+    #[case(r#"{"title": "title", "dueDate": "2075-06-15"}"#, None)]
+    #[case(
+        r#"{"title": "title", "dueDate": "2024-06-14"}"#,
+        Some("dueDate: must be between 2024-06-15 and 2075-06-15")
+    )]
+    #[case(
+        r#"{"title": "title", "dueDate": "2075-06-16"}"#,
+        Some("dueDate: must be between 2024-06-15 and 2075-06-15")
+    )]
+    fn todo_create_request_body_due_date_range(#[case] json: &str, #[case] expected: Option<&str>) {
+        let body: TodoCreateRequestBody = serde_json::from_str(json).unwrap();
+        match (body.into_input(today()).map_err(ApiError::from), expected) {
+            (Ok(_), None) => {}
+            (Err(error), Some(expected)) => {
+                assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+                assert_eq!(error.messages, vec![expected]);
+            }
+            (Ok(_), Some(expected)) => panic!("due date must be rejected: {expected}"),
+            (Err(error), None) => panic!("due date must be accepted: {:?}", error.messages),
         }
-        write!(f, "{}", params.join("&"))
     }
-}
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct TodoCreateRequestBody {
-    pub title: String,
-    pub description: Option<String>,
-    #[serde(default)]
-    #[serde(serialize_with = "serialize_option_date")]
-    #[serde(deserialize_with = "deserialize_option_date")]
-    pub due_date: Option<Date>,
-}
+    #[rstest::rstest]
+    #[case::empty_title(r#"{"title": ""}"#, "title: ")]
+    #[case::blank_title(r#"{"title": "   "}"#, "title: ")]
+    #[case::too_long_title(&format!(r#"{{"title": "{}"}}"#, "a".repeat(101)), "title: ")]
+    #[case::too_long_description(
+        &format!(r#"{{"title": "title", "description": "{}"}}"#, "a".repeat(401)),
+        "description: "
+    )]
+    fn todo_create_request_body_with_invalid_field(#[case] json: &str, #[case] prefix: &str) {
+        let body: TodoCreateRequestBody = serde_json::from_str(json).unwrap();
+        let Err(error) = body.into_input(today()).map_err(ApiError::from) else {
+            panic!("request body must be rejected: {json}");
+        };
+        // gardeによる検証エラーは、422 Unprocessable Entityではなく400 Bad Requestになる
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(error.messages.len(), 1, "{:?}", error.messages);
+        assert!(
+            error.messages[0].starts_with(prefix),
+            "{}",
+            error.messages[0]
+        );
+    }
 
-impl TryFrom<TodoCreateRequestBody> for TodoCreateInput {
-    type Error = ApiError;
+    #[test]
+    fn todo_create_request_body_accepts_the_longest_fields() {
+        let body = TodoCreateRequestBody {
+            title: "あ".repeat(100),
+            description: Some("あ".repeat(400)),
+            due_date: None,
+            allow_duplicate: false,
+        };
+        let input = body.into_input(today()).unwrap();
+        assert_eq!(input.title.chars().count(), 100);
+        assert_eq!(input.description.unwrap().chars().count(), 400);
+    }
 
-    fn try_from(value: TodoCreateRequestBody) -> Result<Self, Self::Error> {
-        Ok(TodoCreateInput {
-            title: TodoTitle::new(value.title).map_err(ApiError::from)?,
-            description: value
-                .description
-                .map(TodoDescription::new)
-                .transpose()
-                .map_err(ApiError::from)?,
-            due_date: value.due_date,
-        })
+    /// タイトルと説明の前後の空白は、`impl_string_primitive!`が取り除く。
+    #[test]
+    fn todo_create_request_body_trims_the_title_and_description() {
+        let body = TodoCreateRequestBody {
+            title: String::from("  title\t"),
+            description: Some(String::from("\n description ")),
+            due_date: None,
+            allow_duplicate: false,
+        };
+        let input = body.into_input(today()).unwrap();
+        assert_eq!(input.title, "title");
+        assert_eq!(input.description.unwrap(), "description");
     }
-}
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct TodoUpdateRequestBody {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub title: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub status_code: Option<i16>,
-    #[serde(default)]
-    #[serde(serialize_with = "serialize_option_date")]
-    #[serde(deserialize_with = "deserialize_option_date")]
-    pub due_date: Option<Date>,
-}
+    #[rstest::rstest]
+    #[case(r#"{"dueDate": "2024-06-14"}"#)]
+    #[case(r#"{"dueDate": "2075-06-16"}"#)]
+    fn todo_update_request_body_with_due_date_out_of_range(#[case] json: &str) {
+        let body: TodoUpdateRequestBody = serde_json::from_str(json).unwrap();
+        let Err(error) = body.into_input(today()).map_err(ApiError::from) else {
+            panic!("due date must be rejected: {json}");
+        };
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            error.messages,
+            vec!["dueDate: must be between 2024-06-15 and 2075-06-15"]
+        );
+    }
 
-impl TryFrom<TodoUpdateRequestBody> for TodoUpdateInput {
-    type Error = ApiError;
+    #[test]
+    fn todo_list_query_params_without_fields() {
+        let params: TodoListQueryParams = serde_json::from_str("{}").unwrap();
+        assert!(params.keyword.is_none());
+        assert!(params.op.is_none());
+        assert!(params.from.is_none());
+        assert!(params.to.is_none());
+        assert!(params.statuses.is_none());
+        assert!(params.archived.is_none());
+        assert!(params.sort.is_none());
+        assert!(params.page.is_none());
+        assert!(params.page_size.is_none());
+        assert!(params.cursor.is_none());
+        assert!(params.limit.is_none());
+    }
 
-    fn try_from(body: TodoUpdateRequestBody) -> Result<Self, Self::Error> {
-        Ok(TodoUpdateInput {
-            title: body
-                .title
-                .map(|title| TodoTitle::new(title).map_err(ApiError::from))
-                .transpose()?,
-            description: body
-                .description
-                .map(|desc| TodoDescription::new(desc).map_err(ApiError::from))
-                .transpose()?,
-            status_code: body
-                .status_code
-                .map(|code| TodoStatusCode::try_from(code).map_err(ApiError::from))
-                .transpose()?,
-            due_date: body.due_date,
-        })
+    #[test]
+    fn todo_list_cursor_round_trip() {
+        let cursor = TodoListCursor {
+            filter_hash: String::from("0123456789abcdef"),
+            completed: true,
+            due_date: Some(date!(2025 - 06 - 30)),
+            updated_at: OffsetDateTime::now_utc(),
+            created_at: OffsetDateTime::now_utc(),
+            id: TodoId::default(),
+        };
+        let encoded = encode_todo_list_cursor(&cursor);
+        assert!(
+            encoded
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "{encoded}"
+        );
+        assert_eq!(decode_todo_list_cursor(&encoded).unwrap(), cursor);
     }
-}
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct TodoReopenRequestBody {
-    pub todo_status_code: TodoStatusCode,
-}
+    #[rstest::rstest]
+    #[case::not_base64("not a cursor")]
+    #[case::not_json("bm90IGpzb24")]
+    #[case::missing_fields("eyJpZCI6MX0")]
+    fn todo_list_cursor_malformed(#[case] cursor: &str) {
+        let error = decode_todo_list_cursor(cursor).unwrap_err();
+        assert_eq!(error.messages, vec!["cursor: malformed"]);
+    }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct TodoArchiveRequestBody {
-    pub archived: bool,
+    #[test]
+    fn todo_create_request_body_without_optional_fields() {
+        let body: TodoCreateRequestBody = serde_json::from_str(r#"{"title": "title"}"#).unwrap();
+        assert!(body.description.is_none());
+        assert!(body.due_date.is_none());
+    }
+
+    #[test]
+    fn todo_update_request_body_without_fields() {
+        let body: TodoUpdateRequestBody = serde_json::from_str("{}").unwrap();
+        assert!(body.title.is_none());
+        assert!(body.description.is_absent());
+        assert!(body.status_code.is_none());
+        assert!(body.due_date.is_absent());
+    }
+
+    #[test]
+    fn todo_update_request_body_clears_the_description_and_due_date_with_null() {
+        let json = r#"{"description": null, "dueDate": null}"#;
+        let body: TodoUpdateRequestBody = serde_json::from_str(json).unwrap();
+        let input = body.into_input(today()).unwrap();
+        assert!(matches!(input.description, Some(None)));
+        assert_eq!(input.due_date, Some(None));
+    }
 }
@@ -1,40 +1,48 @@
-use std::borrow::Cow;
+use std::sync::Arc;
 
 use axum::{
     Extension, Json,
     body::Body,
-    extract::State,
+    extract::{Query, State},
     http::{HeaderValue, Response, StatusCode, header},
     response::IntoResponse,
 };
 use axum_extra::extract::CookieJar;
-use cookie::{Cookie, SameSite};
 use secrecy::{ExposeSecret as _, SecretString};
-use serde::{Deserialize, Serialize};
-use time::{Duration, OffsetDateTime, serde::rfc3339};
+use time::{Duration, OffsetDateTime};
 
 use domain::{
-    DomainError, DomainResult,
-    models::{Email, FamilyName, GivenName, User, UserId},
+    DomainError, DomainResult, FieldErrors,
+    api::{
+        AccountBackupBody, ConfirmEmailRequestBody, DigestQueryParams, ImportResponseBody,
+        LoginRequestBody, LoginResponseBody, PasswordPolicy, RefreshTokensRequestBody,
+        SignUpRequestBody, UpdateUserRequestBody,
+    },
+    models::{
+        Email, FamilyName, GivenName, UserDigest, UserId, UserPreferences, UserProfile,
+        validate_locale,
+    },
     repositories::{
-        TokenRepository as _, TokenType, UpdateUserInput, UserInput, UserRepository,
+        TokenRepository, TokenType, UpdateUserInput, UserInput, UserRepository,
         generate_auth_token_info, generate_auth_token_info_key,
     },
 };
-use use_case::AuthorizedUser;
-use utils::serde::{deserialize_secret_string, serialize_secret_string};
+use use_case::{
+    AuthorizedUser,
+    token::{TokenIssuance, TokenIssuer, TokenPairToIssue},
+};
 
 use crate::{
     AppState,
     http::{
-        ApiError, ApiResult, COOKIE_ACCESS_TOKEN_KEY, COOKIE_REFRESH_TOKEN_KEY, bad_request,
-        handler::user_use_case, internal_server_error, login_failed, unauthorized, user_locked,
+        ApiError, ApiErrorKind, ApiResult, COOKIE_REFRESH_TOKEN_KEY, CookieFactory, bad_request,
+        login_failed, merge_patch::MergePatch, middleware::ClientIp, not_found, unauthorized,
+        user_locked, user_locked_for_owner,
     },
-    jwt::generate_token_pair,
-    password::{RawPassword, create_hashed_password, verify_password},
-    postgres::repositories::PgUserRepository,
-    redis::token::RedisTokenRepository,
-    settings::{AppSettings, HttpProtocol},
+    jwt::{Claim, generate_token, generate_token_pair, retrieve_claim_from_token},
+    mail::Mail,
+    password::{RawPassword, hash_password_async, verify_password_async},
+    settings::AppSettings,
 };
 
 #[tracing::instrument(skip(app_state))]
@@ -43,14 +51,19 @@ pub async fn sign_up(
     Json(body): Json<SignUpRequestBody>,
 ) -> ApiResult<impl IntoResponse> {
     // パスワードの検証とハッシュ化
-    let raw_password = RawPassword::new(&app_state.app_settings.password, body.password.clone())
-        .map_err(ApiError::from)?;
-    let hashed_password = create_hashed_password(&app_state.app_settings.password, &raw_password)
+    // パスワードが不正な場合は、クライアントがエラーとともにルールを表示できるように、パスワードポリシーを返す
+    let raw_password = RawPassword::new(&app_state.password_settings, body.password.clone())
+        .map_err(|e| {
+            let policy = app_state.password_settings.policy();
+            ApiError::from(e).with_field("passwordPolicy", policy)
+        })?;
+    let hashed_password = hash_password_async(app_state.password_settings.clone(), raw_password)
+        .await
         .map_err(ApiError::from)?;
     // リクエストボディをUserInputに変換
     let input = UserInput::try_from(body).map_err(ApiError::from)?;
     // ユーザーを登録
-    let use_case = user_use_case(&app_state);
+    let use_case = &app_state.user_use_case;
     let user = use_case
         .sign_up(input, hashed_password)
         .await
@@ -58,66 +71,186 @@ pub async fn sign_up(
     Ok((StatusCode::CREATED, Json(user)))
 }
 
+/// パスワードポリシーを返す。
+///
+/// ペッパーとハッシュ化のパラメーターは返さない。
+pub async fn password_policy(State(app_state): State<AppState>) -> Json<PasswordPolicy> {
+    Json(app_state.password_settings.policy())
+}
+
+/// ログインする。
+///
+/// パスワードが一致しない場合は、ユーザーIDとクライアントのIPアドレスを警告として記録する。
 #[tracing::instrument(skip(app_state))]
 pub async fn login(
     State(app_state): State<AppState>,
+    client_ip: Option<Extension<ClientIp>>,
     Json(body): Json<LoginRequestBody>,
 ) -> ApiResult<Response<Body>> {
     let requested_at = OffsetDateTime::now_utc();
     let settings = &app_state.app_settings;
-    let user_repo = PgUserRepository::new(app_state.pg_pool.clone());
-    let token_repo = RedisTokenRepository::new(app_state.redis_pool.clone());
+    let user_repo = app_state.repos.user.clone();
+    let token_repo = app_state.repos.token.clone();
     // Eメールアドレスからユーザーを取得して、取得できなかった場合は400 Bad Requestを返す
     let email = Email::new(body.email).map_err(|_| bad_request("Invalid email address".into()))?;
-    let user = user_repo
-        .by_email(&email)
-        .await
-        .map_err(internal_server_error)?
-        .ok_or_else(login_failed)?;
-    // ユーザーのアクティブフラグを確認して、無効な場合は423 Lockedを返す
-    if !user.active {
-        return Err(user_locked());
-    }
+    let user = user_repo.by_email(&email).await?.ok_or_else(login_failed)?;
     // ユーザーのハッシュ化されたパスワードを取得
-    let hashed_password = user_repo
-        .get_hashed_password(user.id)
-        .await
-        .map_err(internal_server_error)?;
+    let hashed_password = user_repo.get_hashed_password(user.id).await?;
     // ユーザーのパスワードを検証
-    let raw_password = RawPassword::new(&app_state.app_settings.password, body.password)
+    // 応答時間からロック状態を推測されないように、ユーザーの状態に関わらず、状態を確認する前にパスワードを検証する
+    let raw_password = RawPassword::new(&app_state.password_settings, body.password)
         .map_err(|_| login_failed())?;
-    if verify_password(&raw_password, &settings.password.pepper, &hashed_password)
-        .map_err(internal_server_error)?
-    {
-        generate_tokens_response(settings, user_repo, token_repo, user.id, requested_at).await
+    let pepper = app_state.password_settings.pepper.clone();
+    let password_matched = verify_password_async(raw_password, pepper, hashed_password).await?;
+    // ログイン失敗によるロックが期限切れの場合は、ロックを解除してログインを続ける
+    let active = match settings.login.lockout_expired_cutoff(requested_at) {
+        Some(cutoff) if !user.active => {
+            user_repo
+                .reactivate_if_lockout_expired(user.id, cutoff)
+                .await?
+        }
+        _ => user.active,
+    };
+    // ユーザーのアクティブフラグを確認して、無効な場合は423 Lockedを返す
+    // パスワードの検証結果に関わらず同じ応答を返すため、ロックされた日時と理由は返さない
+    // ロック状態を明かさない設定の場合は、Eメールアドレスの存在とロック状態を推測されないように、パスワードが一致
+    // しない場合と同じ400 Bad Requestを返す
+    if !active {
+        return Err(if settings.login.reveal_lock_status {
+            user_locked()
+        } else {
+            login_failed()
+        });
+    }
+    if password_matched {
+        generate_tokens_response(
+            settings,
+            user_repo,
+            token_repo,
+            user.id,
+            requested_at,
+            TokenIssuance::Login {
+                logged_in_at: requested_at,
+                max_sessions: settings.token.max_sessions_per_user,
+            },
+        )
+        .await
     } else {
+        let client_ip = client_ip.map(|Extension(ClientIp(ip))| ip);
+        tracing::warn!(user_id = %user.id, client_ip = ?client_ip, "The password did not match");
         handle_password_unmatched(settings, user_repo, user.id, requested_at).await
     }
 }
 
-#[tracing::instrument]
+#[tracing::instrument(skip(app_state))]
 pub async fn me(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthorizedUser>,
-) -> ApiResult<Json<User>> {
-    let use_case = user_use_case(&app_state);
-    let user = use_case.me(auth_user);
-    Ok(Json(user))
+) -> ApiResult<Json<UserProfile>> {
+    let use_case = &app_state.user_use_case;
+    let profile = use_case.profile(use_case.me(auth_user)).await?;
+    Ok(Json(profile))
 }
 
+/// メールを送信できないため、Eメールアドレスを変更できない場合のエラーメッセージ
+pub const EMAIL_CHANGE_NOT_AVAILABLE_MESSAGE: &str =
+    "The email address can not be changed because the mail server is not configured";
+
+/// ユーザーを更新する。
+///
+/// Eメールアドレスはすぐに変更せず、新しいEメールアドレスに確認トークンを送信する。
+/// Eメールアドレスは、確認トークンで確認されたときに変更する。
+/// 確認トークンを届けられるメール送信者が設定されていない場合は、Eメールアドレスの変更を`501 Not Implemented`で
+/// 拒否して、苗字と名前も更新しない。
 #[tracing::instrument(skip(app_state))]
 pub async fn update(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthorizedUser>,
-    Json(body): Json<UpdateUserRequestBody>,
-) -> ApiResult<Json<User>> {
-    let input = UpdateUserInput::try_from(body)?;
-    let use_case = user_use_case(&app_state);
-    let user = use_case
-        .update(auth_user, input)
-        .await
-        .map_err(internal_server_error)?;
-    Ok(Json(user))
+    MergePatch(body): MergePatch<UpdateUserRequestBody>,
+) -> ApiResult<Json<UserProfile>> {
+    let UpdateUserRequest { input, email } = UpdateUserRequest::try_from(body)?;
+    if email.is_some() && !app_state.mailer.delivers() {
+        return Err(ApiError::new(
+            ApiErrorKind::NotImplemented,
+            EMAIL_CHANGE_NOT_AVAILABLE_MESSAGE,
+        ));
+    }
+    // Eメールアドレスの変更を要求
+    if let Some(email) = email {
+        request_email_change(&app_state, &auth_user, email).await?;
+    }
+    // 苗字と名前を更新
+    let use_case = &app_state.user_use_case;
+    let user = use_case.update(auth_user, input).await?;
+    let profile = use_case.profile(user).await?;
+    Ok(Json(profile))
+}
+
+/// Eメールアドレスの変更を確認するトークンを生成して、新しいEメールアドレスに送信する。
+async fn request_email_change(
+    app_state: &AppState,
+    auth_user: &AuthorizedUser,
+    email: Email,
+) -> ApiResult<()> {
+    let settings = &app_state.app_settings.token;
+    let issued_at = OffsetDateTime::now_utc();
+    let expired_at = issued_at + Duration::seconds(settings.email_change_max_age);
+    let claim = Claim::new(auth_user.id, TokenType::EmailChange, issued_at, expired_at);
+    let token = generate_token(claim, settings)?;
+    let token_info = generate_auth_token_info(
+        auth_user.id,
+        &token,
+        TokenType::EmailChange,
+        settings.email_change_max_age as u64,
+    );
+    app_state
+        .user_use_case
+        .request_email_change(auth_user, &email, &token_info)
+        .await?;
+    let mail = Mail {
+        to: email,
+        subject: String::from("Eメールアドレスの確認"),
+        body: format!(
+            "次の確認トークンで、Eメールアドレスの変更を確認してください。\n\n{}",
+            token.expose_secret()
+        ),
+    };
+    app_state.mailer.send(mail).await?;
+    Ok(())
+}
+
+/// 確認トークンで、確認待ちのEメールアドレスへの変更を確認する。
+#[tracing::instrument(skip(app_state, body))]
+pub async fn confirm_email(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthorizedUser>,
+    Json(body): Json<ConfirmEmailRequestBody>,
+) -> ApiResult<Json<UserProfile>> {
+    let use_case = &app_state.user_use_case;
+    let user = use_case.confirm_email(auth_user, &body.token).await?;
+    let profile = use_case.profile(user).await?;
+    Ok(Json(profile))
+}
+
+#[tracing::instrument(skip(app_state))]
+pub async fn preferences(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthorizedUser>,
+) -> ApiResult<Json<UserPreferences>> {
+    let use_case = &app_state.user_use_case;
+    let preferences = use_case.preferences(&auth_user).await?;
+    Ok(Json(preferences))
+}
+
+#[tracing::instrument(skip(app_state))]
+pub async fn update_preferences(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthorizedUser>,
+    Json(body): Json<UserPreferences>,
+) -> ApiResult<Json<UserPreferences>> {
+    let use_case = &app_state.user_use_case;
+    let preferences = use_case.update_preferences(auth_user, body).await?;
+    Ok(Json(preferences))
 }
 
 #[tracing::instrument(skip(app_state))]
@@ -140,31 +273,44 @@ pub async fn refresh_tokens(
     }
     // リフレッシュトークンが見つからない場合は、401 Unauthorizedを返す
     let refresh_token = refresh_token.ok_or_else(unauthorized)?;
-    // トークンリポジトリからリフレッシュトークンをキーに認証情報を取得
+    // リフレッシュトークンを検証して、トークンの種類がリフレッシュトークン以外の場合は、400 Bad Requestを返す
+    // `typ`クレイムを含まない以前に発行されたトークンは、トークンリポジトリに登録されたトークンの種類で判定
     let settings = &app_state.app_settings;
-    let token_repo = RedisTokenRepository::new(app_state.redis_pool.clone());
+    let claim = retrieve_claim_from_token(&refresh_token, &settings.token).map_err(|e| {
+        tracing::debug!("{e}");
+        unauthorized()
+    })?;
+    if claim.token_type.is_some_and(|t| t != TokenType::Refresh) {
+        return Err(bad_request("Invalid refresh token".into()));
+    }
+    // トークンリポジトリからリフレッシュトークンをキーに認証情報を取得
+    let token_repo = app_state.repos.token.clone();
     let token_key = generate_auth_token_info_key(&refresh_token);
     let token_content = token_repo
         .get_token_content(&token_key)
-        .await
-        .map_err(internal_server_error)?
+        .await?
         .ok_or_else(unauthorized)?;
     if token_content.token_type != TokenType::Refresh {
         return Err(bad_request("Invalid refresh token".into()));
     }
     // ユーザーリポジトリからユーザーを取得
-    let user_repo = PgUserRepository::new(app_state.pg_pool.clone());
-    let user = user_repo
-        .by_id(token_content.user_id)
-        .await
-        .map_err(internal_server_error)?;
+    let user_repo = app_state.repos.user.clone();
+    let user = user_repo.by_id(token_content.user_id).await?;
     let user = user.ok_or_else(unauthorized)?;
-    // ユーザーがロックされている場合は、423 Lockedを返す
+    // ユーザーがロックされている場合は、ロックされた日時と理由を含めて423 Lockedを返す
     if !user.active {
-        return Err(user_locked());
+        return Err(user_locked_for_owner(&user));
     }
     // アクセストークンとリフレッシュトークンを含めたレスポンスを返す
-    generate_tokens_response(settings, user_repo, token_repo, user.id, requested_at).await
+    generate_tokens_response(
+        settings,
+        user_repo,
+        token_repo,
+        user.id,
+        requested_at,
+        TokenIssuance::Refresh,
+    )
+    .await
 }
 
 #[tracing::instrument(skip(app_state))]
@@ -172,140 +318,168 @@ pub async fn logout(
     State(app_state): State<AppState>,
     Extension(user): Extension<AuthorizedUser>,
 ) -> ApiResult<impl IntoResponse> {
+    revoke_all_tokens_response(&app_state, user.id).await
+}
+
+/// ユーザーのすべてのセッションを無効化する。
+///
+/// アカウントが不正に利用された疑いがある場合に、すべての端末からログアウトするために使用する。
+#[tracing::instrument(skip(app_state))]
+pub async fn logout_all(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthorizedUser>,
+) -> ApiResult<impl IntoResponse> {
+    let response = revoke_all_tokens_response(&app_state, user.id).await?;
+    tracing::info!(user_id = %user.id, "All sessions were revoked");
+    Ok(response)
+}
+
+/// ユーザーが発行したすべてのアクセストークンとリフレッシュトークンを削除して、クッキーを無効化するレスポンスを返す。
+async fn revoke_all_tokens_response(
+    app_state: &AppState,
+    user_id: UserId,
+) -> ApiResult<(StatusCode, Response<Body>)> {
     // ユーザーリポジトリからユーザーのハッシュ化されたアクセストークンとリフレッシュトークンを削除
-    let user_repo = PgUserRepository::new(app_state.pg_pool.clone());
-    let token_keys = user_repo
-        .delete_user_tokens_by_id(user.0.id)
-        .await
-        .map_err(internal_server_error)?;
+    let user_repo = app_state.repos.user.clone();
+    let token_keys = user_repo.delete_user_tokens_by_id(user_id).await?;
     // トークンリポジトリから認証情報を削除
-    let token_repo = RedisTokenRepository::new(app_state.redis_pool.clone());
+    let token_repo = app_state.repos.token.clone();
     for key in token_keys.iter() {
         token_repo.delete_token_content(key).await?;
     }
     // レスポンスを作成
     let mut response = Response::new(Body::empty());
     *response.status_mut() = StatusCode::NO_CONTENT;
-    response.headers_mut().insert(
-        header::SET_COOKIE,
-        Cookie::build((COOKIE_ACCESS_TOKEN_KEY, ""))
-            .domain(&app_state.app_settings.http.host)
-            .path("/")
-            .http_only(true)
-            .secure(app_state.app_settings.http.protocol == HttpProtocol::Https)
-            .same_site(SameSite::Strict)
-            .max_age(Duration::ZERO)
-            .build()
-            .to_string()
-            .parse::<HeaderValue>()
-            .unwrap(),
-    );
-    response.headers_mut().append(
-        header::SET_COOKIE,
-        Cookie::build((COOKIE_REFRESH_TOKEN_KEY, ""))
-            .domain(&app_state.app_settings.http.host)
-            .path("/")
-            .http_only(true)
-            .secure(app_state.app_settings.http.protocol == HttpProtocol::Https)
-            .same_site(SameSite::Strict)
-            .max_age(Duration::ZERO)
-            .build()
-            .to_string()
-            .parse::<HeaderValue>()
-            .unwrap(),
-    );
+    let cookie_factory = CookieFactory::new(&app_state.app_settings.http);
+    for cookie in cookie_factory.clear_pair() {
+        response.headers_mut().append(
+            header::SET_COOKIE,
+            cookie.to_string().parse::<HeaderValue>().unwrap(),
+        );
+    }
     Ok((StatusCode::NO_CONTENT, response))
 }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct SignUpRequestBody {
-    pub family_name: String,
-    pub given_name: String,
-    pub email: String,
-    pub password: SecretString,
-}
-
-impl TryFrom<SignUpRequestBody> for UserInput {
-    type Error = DomainError;
-
-    fn try_from(input: SignUpRequestBody) -> DomainResult<Self> {
-        Ok(UserInput {
-            family_name: FamilyName::new(input.family_name)?,
-            given_name: GivenName::new(input.given_name)?,
-            email: Email::new(input.email)?,
-        })
+/// 認証されたユーザーの日次ダイジェストを取得する。
+///
+/// 対象日が指定されていない場合は、今日（UTC）の日次ダイジェストを返す。
+#[tracing::instrument(skip(app_state))]
+pub async fn digest(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthorizedUser>,
+    Query(query): Query<DigestQueryParams>,
+) -> ApiResult<Json<UserDigest>> {
+    let date = query
+        .date
+        .unwrap_or_else(|| OffsetDateTime::now_utc().date());
+    let use_case = &app_state.digest_use_case;
+    match use_case.by_date(auth_user, date).await? {
+        Some(digest) => Ok(Json(digest)),
+        None => Err(not_found("Digest")),
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LoginRequestBody {
-    email: String,
-    password: SecretString,
+#[tracing::instrument(skip(app_state))]
+pub async fn export(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthorizedUser>,
+) -> ApiResult<Json<AccountBackupBody>> {
+    let use_case = &app_state.todo_use_case;
+    let todos = use_case.export(auth_user.clone()).await?;
+    Ok(Json(AccountBackupBody {
+        user: auth_user.user,
+        todos,
+    }))
 }
 
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LoginResponseBody {
-    #[serde(serialize_with = "serialize_secret_string")]
-    access_token: SecretString,
-    #[serde(serialize_with = "rfc3339::serialize")]
-    access_expired_at: OffsetDateTime,
-    #[serde(serialize_with = "serialize_secret_string")]
-    refresh_token: SecretString,
-    #[serde(serialize_with = "rfc3339::serialize")]
-    refresh_expired_at: OffsetDateTime,
+#[tracing::instrument(skip(app_state, body))]
+pub async fn import(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthorizedUser>,
+    Json(body): Json<AccountBackupBody>,
+) -> ApiResult<Json<ImportResponseBody>> {
+    // プロファイルはインポートせず、Todoのみをインポート
+    let use_case = &app_state.todo_use_case;
+    let summary = use_case.import(auth_user, body.todos).await?;
+    Ok(Json(ImportResponseBody {
+        created: summary.created,
+        skipped: summary.skipped,
+    }))
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct UpdateUserRequestBody {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub family_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub given_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub email: Option<String>,
+/// ユーザーの更新要求
+///
+/// Eメールアドレスは確認されてから変更するため、`UpdateUserInput`とは別に保持する。
+struct UpdateUserRequest {
+    input: UpdateUserInput,
+    email: Option<Email>,
 }
 
-impl TryFrom<UpdateUserRequestBody> for UpdateUserInput {
+impl TryFrom<UpdateUserRequestBody> for UpdateUserRequest {
     type Error = DomainError;
 
     fn try_from(input: UpdateUserRequestBody) -> DomainResult<Self> {
-        Ok(UpdateUserInput {
-            family_name: input.family_name.map(FamilyName::new).transpose()?,
-            given_name: input.given_name.map(GivenName::new).transpose()?,
-            email: input.email.map(Email::new).transpose()?,
+        let mut errors = FieldErrors::default();
+        let family_name = errors.check(
+            "familyName",
+            input
+                .family_name
+                .required()
+                .and_then(|value| value.map(FamilyName::new).transpose()),
+        );
+        let given_name = errors.check(
+            "givenName",
+            input
+                .given_name
+                .required()
+                .and_then(|value| value.map(GivenName::new).transpose()),
+        );
+        let email = errors.check(
+            "email",
+            input
+                .email
+                .required()
+                .and_then(|value| value.map(Email::new).transpose()),
+        );
+        let locale = errors.check(
+            "locale",
+            input
+                .locale
+                .nullable()
+                .map(|value| value.map(validate_locale).transpose())
+                .transpose(),
+        );
+        errors.into_result()?;
+        Ok(UpdateUserRequest {
+            input: UpdateUserInput {
+                family_name: family_name.unwrap(),
+                given_name: given_name.unwrap(),
+                locale: locale.unwrap(),
+            },
+            email: email.unwrap(),
         })
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct RefreshTokensRequestBody {
-    #[serde(serialize_with = "serialize_secret_string")]
-    #[serde(deserialize_with = "deserialize_secret_string")]
-    pub refresh_token: SecretString,
-}
-
 async fn generate_tokens_response(
     settings: &AppSettings,
-    user_repo: PgUserRepository,
-    token_repo: RedisTokenRepository,
+    user_repo: Arc<dyn UserRepository>,
+    token_repo: Arc<dyn TokenRepository>,
     user_id: UserId,
     requested_at: OffsetDateTime,
+    issuance: TokenIssuance,
 ) -> ApiResult<Response<Body>> {
     // アクセストークンとリフレッシュトークンを生成
     let access_expired_at = requested_at + Duration::seconds(settings.token.access_max_age);
     let refresh_expired_at = requested_at + Duration::seconds(settings.token.refresh_max_age);
     let token_pair = generate_token_pair(
         user_id,
+        requested_at,
         access_expired_at,
         refresh_expired_at,
-        &settings.token.jwt_secret,
+        &settings.token,
     )?;
-    // トークンリポジトリに認証情報を登録
+    // トークンリポジトリとユーザーリポジトリに登録する認証情報を生成
     let access_token_info = generate_auth_token_info(
         user_id,
         &token_pair.access.0,
@@ -318,22 +492,26 @@ async fn generate_tokens_response(
         TokenType::Refresh,
         settings.token.refresh_max_age as u64,
     );
-    token_repo
-        .register_token_pair(&access_token_info, &refresh_token_info)
-        .await
-        .map_err(internal_server_error)?;
-    // ユーザーの最終ログイン日時を更新して、認証情報を登録するとともに、ログイン失敗履歴を削除
-    user_repo
-        .handle_logged_in(
-            user_id,
-            requested_at,
-            &access_token_info.key,
-            access_expired_at,
-            &refresh_token_info.key,
-            refresh_expired_at,
-        )
-        .await
-        .map_err(internal_server_error)?;
+    // トークンリポジトリとユーザーリポジトリに認証情報を登録
+    // ユーザーリポジトリへの登録に失敗した場合は、トークンリポジトリに登録した認証情報を削除
+    let issuer = TokenIssuer {
+        user_repo,
+        token_repo,
+    };
+    let tokens = TokenPairToIssue {
+        access: &access_token_info,
+        access_expired_at,
+        refresh: &refresh_token_info,
+        refresh_expired_at,
+    };
+    let pruned_keys = issuer.issue(user_id, &tokens, issuance).await?;
+    if !pruned_keys.is_empty() {
+        tracing::info!(
+            %user_id,
+            pruned_tokens = pruned_keys.len(),
+            "Oldest sessions were pruned"
+        );
+    }
     // レスポンスを作成
     let response_body = LoginResponseBody {
         access_token: token_pair.access.0,
@@ -342,17 +520,12 @@ async fn generate_tokens_response(
         refresh_expired_at,
     };
     let mut response = Json(response_body.clone()).into_response();
-    let access_cookie = create_cookie(
-        settings.http.protocol,
-        &settings.http.host,
-        COOKIE_ACCESS_TOKEN_KEY,
+    let cookie_factory = CookieFactory::new(&settings.http);
+    let access_cookie = cookie_factory.access_cookie(
         &response_body.access_token,
         Duration::seconds(settings.token.access_max_age),
     );
-    let refresh_cookie = create_cookie(
-        settings.http.protocol,
-        &settings.http.host,
-        COOKIE_REFRESH_TOKEN_KEY,
+    let refresh_cookie = cookie_factory.refresh_cookie(
         &response_body.refresh_token,
         Duration::seconds(settings.token.refresh_max_age),
     );
@@ -369,7 +542,7 @@ async fn generate_tokens_response(
 
 async fn handle_password_unmatched(
     settings: &AppSettings,
-    user_repo: PgUserRepository,
+    user_repo: Arc<dyn UserRepository>,
     user_id: UserId,
     requested_at: OffsetDateTime,
 ) -> ApiResult<Response<Body>> {
@@ -407,23 +580,3 @@ async fn handle_password_unmatched(
     }
     Err(login_failed())
 }
-
-fn create_cookie<'c, N>(
-    protocol: HttpProtocol,
-    domain: &'c str,
-    name: N,
-    value: &'c SecretString,
-    max_age: Duration,
-) -> Cookie<'c>
-where
-    N: Into<Cow<'c, str>>,
-{
-    let cookie = Cookie::build((name.into(), value.expose_secret()))
-        .domain(domain)
-        .path("/")
-        .http_only(true)
-        .secure(protocol == HttpProtocol::Https)
-        .same_site(SameSite::Strict)
-        .max_age(max_age);
-    cookie.build()
-}
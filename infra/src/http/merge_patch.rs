@@ -0,0 +1,58 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::header,
+};
+use serde::de::DeserializeOwned;
+
+use domain::api::{MERGE_PATCH_CONTENT_TYPE, PatchBody};
+
+use crate::http::{ApiError, ApiErrorKind, bad_request};
+
+/// JSON Merge Patch（RFC 7396）を受け付けるエクストラクター
+///
+/// `Content-Type`が`application/merge-patch+json`の場合、`null`はフィールドの値を消去することを示す。
+/// `Content-Type`が`application/json`の場合は、これまでと同様に`null`を無視する。
+pub struct MergePatch<T>(pub T);
+
+impl<T, S> FromRequest<S> for MergePatch<T>
+where
+    T: DeserializeOwned + PatchBody,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let merge_patch = match media_type(&request).as_deref() {
+            Some(MERGE_PATCH_CONTENT_TYPE) => true,
+            Some("application/json") => false,
+            _ => {
+                return Err(ApiError::new(
+                    ApiErrorKind::UnsupportedMediaType,
+                    format!(
+                        "Content-Type must be application/json or {}",
+                        MERGE_PATCH_CONTENT_TYPE
+                    ),
+                ));
+            }
+        };
+        let bytes = Bytes::from_request(request, state)
+            .await
+            .map_err(|e| bad_request(e.body_text().into()))?;
+        let body = serde_json::from_slice::<T>(&bytes).map_err(|e| {
+            bad_request(format!("Failed to deserialize the request body: {}", e).into())
+        })?;
+        if merge_patch {
+            Ok(MergePatch(body))
+        } else {
+            Ok(MergePatch(body.ignore_nulls()))
+        }
+    }
+}
+
+/// リクエストの`Content-Type`から、パラメーターを除いたメディアタイプを小文字で返す。
+fn media_type(request: &Request) -> Option<String> {
+    let content_type = request.headers().get(header::CONTENT_TYPE)?.to_str().ok()?;
+    let media_type = content_type.split(';').next()?.trim();
+    Some(media_type.to_ascii_lowercase())
+}
@@ -1,7 +1,13 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Instant,
+};
+
 use axum::{
     RequestExt as _,
-    extract::{Request, State},
-    http::StatusCode,
+    body::HttpBody as _,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderName, HeaderValue, Method, header},
     middleware::Next,
     response::{IntoResponse as _, Response},
 };
@@ -11,17 +17,24 @@ use axum_extra::{
     headers::{Authorization, authorization::Bearer},
 };
 use secrecy::SecretString;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
-use domain::repositories::{
-    TokenRepository as _, TokenType, UserRepository as _, generate_auth_token_info_key,
+use domain::{
+    models::{RoleCode, UserId},
+    repositories::{
+        TokenRepository as _, TokenType, UserRepository as _, generate_auth_token_info_key,
+    },
 };
 use use_case::AuthorizedUser;
 
 use crate::{
     AppState,
-    http::{ApiError, COOKIE_ACCESS_TOKEN_KEY, internal_server_error, user_locked},
-    postgres::repositories::PgUserRepository,
-    redis::token::RedisTokenRepository,
+    access_log::AccessLogEntry,
+    http::{
+        ApiError, ApiErrorKind, COOKIE_ACCESS_TOKEN_KEY, admin_required, impersonation_read_only,
+        user_locked, user_locked_for_owner,
+    },
 };
 
 /// HTTPリクエストヘッダーからアクセストークンを取り出し、アクセストークンの有効性を確認するミドルウェア
@@ -30,6 +43,9 @@ use crate::{
 /// クッキーにアクセストークンが登録されている場合は、クッキーのアクセストークンを検証する。
 /// クッキーにアクセストークンが登録されていない場合は、AuthorizationヘッダーのBearerトークンを検証する。
 /// したがって、アクセストークンは、クッキーが優先される。
+///
+/// 管理者が発行したなりすましトークンも、アクセストークンとして受け付ける。
+/// なりすましトークンによるリクエストは読み取り専用であるため、参照以外のリクエストは403 Forbiddenを返す。
 pub async fn authorized_user_middleware(
     State(app_state): State<AppState>,
     cookie_jar: CookieJar,
@@ -41,70 +57,141 @@ pub async fn authorized_user_middleware(
         Some(token) => token,
         None => {
             // トークンが見つからない場合は、401 Unauthorizedを返す
-            return ApiError {
-                status_code: StatusCode::UNAUTHORIZED,
-                messages: vec!["Access token is missing".into()],
-            }
-            .into_response();
+            return ApiError::new(ApiErrorKind::Unauthorized, "Access token is missing")
+                .into_response();
         }
     };
     // トークンリポジトリからトークンをキーにトークンコンテンツを取得
-    let token_repository = RedisTokenRepository::new(app_state.redis_pool);
+    let token_repository = app_state.repos.token.clone();
     let key = generate_auth_token_info_key(&token);
     let token_content = match token_repository.get_token_content(&key).await {
         Ok(content) => content,
         Err(e) => {
-            // トークンコンテンツを取得するときにエラーが発生した場合は、500 Internal Server Errorを返す
-            return internal_server_error(e).into_response();
+            // トークンコンテンツを取得するときにエラーが発生した場合は、500 Internal Server Error、
+            // Redisに接続できない場合は503 Service Unavailableを返す
+            return ApiError::from(e).into_response();
         }
     };
     // トークンコンテンツを取得できなかった場合は、トークンの有効期限が切れているか、無効なトークンであるため、
     // 401 Unauthorizedを返す
     if token_content.is_none() {
-        return ApiError {
-            status_code: StatusCode::UNAUTHORIZED,
-            messages: vec!["Invalid or expired access token".into()],
-        }
+        return ApiError::new(
+            ApiErrorKind::Unauthorized,
+            "Invalid or expired access token",
+        )
         .into_response();
     }
     let token_content = token_content.unwrap();
+    // アクセストークンの残りの有効期間を取得
+    let expires_in = match token_repository.get_token_ttl(&key).await {
+        Ok(expires_in) => expires_in,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
     // トークンコンテンツからアクセストークン（とみなしているトークン）が、本当にアクセストークンか確認して、
     // もしアクセストークンでなければ、400 Bad Requestを返す
-    // トークンコンテンツは、アクセストークンであればTokenType::Access、リフレッシュトークンであればTokenType::Refreshを持つ
-    if token_content.token_type != TokenType::Access {
-        return ApiError {
-            status_code: StatusCode::BAD_REQUEST,
-            messages: vec!["Invalid access token".into()],
-        }
-        .into_response();
+    // トークンコンテンツは、アクセストークンであればTokenType::Access、リフレッシュトークンであればTokenType::Refresh、
+    // なりすましトークンであればTokenType::Impersonationを持つ
+    if !matches!(
+        token_content.token_type,
+        TokenType::Access | TokenType::Impersonation
+    ) {
+        return ApiError::new(ApiErrorKind::BadRequest, "Invalid access token").into_response();
     }
     // アクセストークンが有効であるため、ユーザーを取得
-    let user_repository = PgUserRepository::new(app_state.pg_pool);
+    let user_repository = app_state.repos.user.clone();
     let user = user_repository.by_id(token_content.user_id).await;
     // ユーザーを取得するときにエラーが発生した場合は、500 Internal Server Errorを返す
     if user.is_err() {
-        return internal_server_error(user.err().unwrap()).into_response();
+        return ApiError::from(user.err().unwrap()).into_response();
     }
     let user = user.unwrap();
     // ユーザーが存在しない場合は、404 Not Foundを返す
     if user.is_none() {
-        return ApiError {
-            status_code: StatusCode::NOT_FOUND,
-            messages: vec!["User not found".into()],
-        }
-        .into_response();
+        return ApiError::new(ApiErrorKind::NotFound, "User not found").into_response();
     }
     let user = user.unwrap();
     if !user.active {
         // ユーザーがロックされている場合は、423 Lockedを返す
-        return user_locked().into_response();
+        // ロックされた日時と理由は、なりすましではなくアカウントの所有者がリクエストした場合のみ返す
+        return match token_content.impersonated_by {
+            Some(_) => user_locked(),
+            None => user_locked_for_owner(&user),
+        }
+        .into_response();
     }
 
+    // なりすましトークンの場合は、リクエストを記録して、参照以外のリクエストを拒否
+    let auth_user = match token_content.impersonated_by {
+        Some(impersonated_by) => {
+            tracing::info!(
+                user_id = %user.id,
+                impersonated_by = %impersonated_by,
+                method = %request.method(),
+                uri = %request.uri(),
+                "Impersonated request"
+            );
+            if !is_read_only_method(request.method()) {
+                return impersonation_read_only().into_response();
+            }
+            AuthorizedUser::impersonated(user, impersonated_by)
+        }
+        None => AuthorizedUser::from(user),
+    };
+
     // 認証済みユーザーであることが確認できたため、リクエストにユーザー登録
-    request.extensions_mut().insert(AuthorizedUser(user));
+    let user_id = auth_user.user.id;
+    request.extensions_mut().insert(auth_user);
+    let mut response = next.run(request).await;
+    // リクエストログにユーザーIDを記録するため、レスポンスにユーザーIDを登録
+    response
+        .extensions_mut()
+        .insert(AuthenticatedUserId(user_id));
+    // アクセストークンの残りの有効期間をレスポンスヘッダーに設定
+    if let Some(expires_in) = expires_in {
+        let headers = response.headers_mut();
+        headers.insert(X_TOKEN_EXPIRES_IN, HeaderValue::from(expires_in));
+        if expires_in < app_state.app_settings.token.refresh_suggested_threshold as u64 {
+            headers.insert(X_TOKEN_REFRESH_SUGGESTED, HeaderValue::from_static("true"));
+        }
+    }
+    response
+}
+
+/// 認証済みユーザーのユーザーID
+///
+/// `authorized_user_middleware`がレスポンスに登録して、`request_log_middleware`がログに記録する。
+#[derive(Debug, Clone, Copy)]
+pub struct AuthenticatedUserId(pub UserId);
+
+/// アクセストークンの残りの有効期間（秒）を格納するヘッダー
+pub const X_TOKEN_EXPIRES_IN: HeaderName = HeaderName::from_static("x-token-expires-in");
+
+/// アクセストークンのリフレッシュを促すヘッダー
+pub const X_TOKEN_REFRESH_SUGGESTED: HeaderName =
+    HeaderName::from_static("x-token-refresh-suggested");
+
+/// 認証済みユーザーが管理者であることを確認するミドルウェア
+///
+/// `authorized_user_middleware`の後に実行されなければならない。
+/// 管理者でない場合、またはなりすましトークンによるリクエストの場合は、403 Forbiddenを返す。
+pub async fn admin_user_middleware(request: Request, next: Next) -> Response {
+    let is_admin = request
+        .extensions()
+        .get::<AuthorizedUser>()
+        .is_some_and(|auth_user| {
+            auth_user.role.code == RoleCode::Admin && !auth_user.is_impersonated()
+        });
+    if !is_admin {
+        return admin_required().into_response();
+    }
     next.run(request).await
 }
 
+/// リソースを変更しないHTTPメソッドであるか確認する。
+fn is_read_only_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
 async fn get_access_token_from_request(
     cookie_jar: &CookieJar,
     request: &mut Request,
@@ -124,3 +211,171 @@ async fn get_access_token_from_request(
         Err(_) => None,
     }
 }
+
+/// クライアントのIPアドレス
+///
+/// `client_ip_middleware`がリクエストの拡張に登録するため、ハンドラーは`Extension<ClientIp>`で取得できる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// 接続情報からクライアントのIPアドレスを取り出して、リクエストの拡張に登録するミドルウェア
+///
+/// ルーターを`into_make_service_with_connect_info::<SocketAddr>()`でサービスに変換していない場合、
+/// 接続情報が存在しないため、クライアントのIPアドレスを登録しない。
+pub async fn client_ip_middleware(mut request: Request, next: Next) -> Response {
+    let connect_info = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .copied();
+    if let Some(ConnectInfo(addr)) = connect_info {
+        request.extensions_mut().insert(ClientIp(addr.ip()));
+    }
+    next.run(request).await
+}
+
+/// リクエストIDを格納するヘッダー
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// クライアントが指定したリクエストIDとして受け付ける最大文字数
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// リクエストID
+///
+/// `request_id_middleware`がリクエストの拡張に登録するため、ハンドラーやミドルウェアは`Extension<RequestId>`で取得できる。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// リクエストIDをリクエストの拡張に登録して、レスポンスの`X-Request-Id`ヘッダーに設定するミドルウェア
+///
+/// リクエストIDは`X-Request-Id`ヘッダーから取得して、存在しない場合や不正な場合は生成する。
+/// リクエストごとのスパンやアクセスログにリクエストIDを含めるため、それらのレイヤーより外側に適用する。
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| {
+            !value.is_empty()
+                && value.len() <= MAX_REQUEST_ID_LENGTH
+                && value.chars().all(|c| c.is_ascii_graphic())
+        })
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    request
+        .extensions_mut()
+        .insert(RequestId(request_id.clone()));
+    let mut response = next.run(request).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(X_REQUEST_ID, value);
+    }
+    response
+}
+
+/// アプリケーションのバージョンを格納するヘッダー
+pub const X_APP_VERSION: HeaderName = HeaderName::from_static("x-app-version");
+
+/// レスポンスの`X-App-Version`ヘッダーに、アプリケーションのバージョンとコミットのハッシュを設定するミドルウェア
+///
+/// タイムアウトなどのエラーレスポンスにもヘッダーを設定するため、タイムアウトより外側に適用する。
+pub async fn app_version_middleware(
+    State(app_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    if let Ok(value) = HeaderValue::from_str(&app_state.build_info.version_with_git_sha()) {
+        response.headers_mut().insert(X_APP_VERSION, value);
+    }
+    response
+}
+
+/// リクエストごとにアクセスログを出力するミドルウェア
+///
+/// リクエストIDは`request_id_middleware`が登録したものを記録するため、このミドルウェアより外側に
+/// `request_id_middleware`を適用すること。
+pub async fn access_log_middleware(
+    State(app_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started_at = Instant::now();
+    let timestamp = OffsetDateTime::now_utc();
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|RequestId(id)| id.clone())
+        .unwrap_or_default();
+    let ip = request
+        .extensions()
+        .get::<ClientIp>()
+        .map(|ClientIp(ip)| ip.to_string());
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let version = format!("{:?}", request.version());
+    let referer = header_value(&request, header::REFERER);
+    let user_agent = header_value(&request, header::USER_AGENT);
+
+    let response = next.run(request).await;
+
+    let entry = AccessLogEntry {
+        timestamp,
+        method,
+        path,
+        version,
+        status: response.status().as_u16(),
+        duration_ms: started_at.elapsed().as_millis() as u64,
+        ip,
+        request_id,
+        referer,
+        user_agent,
+    };
+    let settings = &app_state.app_settings.access_log;
+    app_state
+        .access_log_sink
+        .write(&settings.format.render(&entry));
+    response
+}
+
+/// ルートに一致しなかったリクエストのパスとして記録する値
+const UNMATCHED_PATH: &str = "<unmatched>";
+
+/// リクエストごとに、1つのイベントとしてリクエストログを出力するミドルウェア
+///
+/// メソッド、パステンプレート、ステータスコード、処理時間（ミリ秒）、認証済みユーザーのユーザーID、
+/// レスポンスボディのサイズを記録する。
+/// ログのカーディナリティを抑えるため、UUIDなどを含む実際のパスではなく、`MatchedPath`のパステンプレートを
+/// 記録する。`MatchedPath`はルーティングした後に登録されるため、このミドルウェアは`Router::layer`で適用すること。
+pub async fn request_log_middleware(request: Request, next: Next) -> Response {
+    let started_at = Instant::now();
+    let method = request.method().clone();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+
+    let response = next.run(request).await;
+
+    let user_id = response
+        .extensions()
+        .get::<AuthenticatedUserId>()
+        .map(|AuthenticatedUserId(id)| *id);
+    tracing::info!(
+        method = %method,
+        path = path.as_deref().unwrap_or(UNMATCHED_PATH),
+        status = response.status().as_u16(),
+        latency_ms = started_at.elapsed().as_millis() as u64,
+        user_id = user_id.map(tracing::field::display),
+        response_size = response.body().size_hint().exact(),
+        "Request completed"
+    );
+    response
+}
+
+/// リクエストヘッダーの値を文字列で取得する。
+fn header_value(request: &Request, name: HeaderName) -> Option<String> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
@@ -1,5 +1,7 @@
 pub mod handler;
+pub mod merge_patch;
 pub mod middleware;
+pub mod paths;
 
 use std::borrow::Cow;
 
@@ -8,42 +10,183 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use cookie::{Cookie, SameSite};
+use secrecy::{ExposeSecret as _, SecretString};
+use time::{Duration, OffsetDateTime, format_description::well_known::Rfc3339};
 
-use domain::{DomainError, DomainErrorKind};
+use domain::{DomainError, DomainErrorKind, models::User};
+use utils::log::sanitize_log_message;
+
+use crate::settings::{HttpProtocol, HttpSettings};
 
 /// API結果
 type ApiResult<T> = Result<T, ApiError>;
 
+/// APIエラーの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiErrorKind {
+    /// リクエストが不正
+    BadRequest,
+    /// 認証されていない
+    Unauthorized,
+    /// 禁止された操作
+    Forbidden,
+    /// リソースが存在しない
+    NotFound,
+    /// リソースが競合している
+    Conflict,
+    /// ユーザーがロックされている
+    Locked,
+    /// サーバー内部のエラー
+    InternalServerError,
+    /// サービスを一時的に利用できない
+    ServiceUnavailable,
+    /// サポートしていないメディアタイプ
+    UnsupportedMediaType,
+    /// サーバーが機能を提供していない
+    NotImplemented,
+}
+
+impl ApiErrorKind {
+    /// APIエラーの種類に対応するHTTPステータスコードを返す。
+    pub fn status_code(self) -> StatusCode {
+        match self {
+            ApiErrorKind::BadRequest => StatusCode::BAD_REQUEST,
+            ApiErrorKind::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiErrorKind::Forbidden => StatusCode::FORBIDDEN,
+            ApiErrorKind::NotFound => StatusCode::NOT_FOUND,
+            ApiErrorKind::Conflict => StatusCode::CONFLICT,
+            ApiErrorKind::Locked => StatusCode::LOCKED,
+            ApiErrorKind::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiErrorKind::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiErrorKind::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiErrorKind::NotImplemented => StatusCode::NOT_IMPLEMENTED,
+        }
+    }
+}
+
+impl From<DomainErrorKind> for ApiErrorKind {
+    fn from(kind: DomainErrorKind) -> Self {
+        match kind {
+            DomainErrorKind::Validation => ApiErrorKind::BadRequest,
+            DomainErrorKind::NotFound => ApiErrorKind::NotFound,
+            DomainErrorKind::Unauthorized => ApiErrorKind::Unauthorized,
+            DomainErrorKind::Forbidden => ApiErrorKind::Forbidden,
+            DomainErrorKind::Conflict => ApiErrorKind::Conflict,
+            DomainErrorKind::Unavailable => ApiErrorKind::ServiceUnavailable,
+            DomainErrorKind::Repository | DomainErrorKind::Unexpected => {
+                ApiErrorKind::InternalServerError
+            }
+        }
+    }
+}
+
 /// APIエラー
 pub struct ApiError {
-    /// HTTPステータスコード
-    pub status_code: StatusCode,
+    /// APIエラーの種類
+    pub kind: ApiErrorKind,
     /// エラーメッセージ
     pub messages: Vec<Cow<'static, str>>,
+    /// クライアントに返さずにサーバーでのみ記録するエラーの詳細
+    pub detail: Option<anyhow::Error>,
+    /// エラーレスポンスのボディに`messages`とともに追加するフィールド
+    pub fields: serde_json::Map<String, serde_json::Value>,
 }
 
+impl ApiError {
+    /// メッセージを1つ持つAPIエラーを構築する。
+    pub fn new(kind: ApiErrorKind, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            kind,
+            messages: vec![message.into()],
+            detail: None,
+            fields: serde_json::Map::new(),
+        }
+    }
+
+    /// エラーレスポンスのボディにフィールドを追加する。
+    ///
+    /// `messages`フィールドは上書きできない。
+    pub fn with_field(mut self, key: &str, value: impl serde::Serialize) -> Self {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.fields.insert(key.to_string(), value);
+        }
+        self
+    }
+
+    /// APIエラーのHTTPステータスコードを返す。
+    pub fn status_code(&self) -> StatusCode {
+        self.kind.status_code()
+    }
+}
+
+/// リポジトリエラーや予期しないエラーが発生したときに、クライアントに返すメッセージ
+pub const INTERNAL_SERVER_ERROR_MESSAGE: &str = "Internal server error";
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let body = serde_json::json!({
-            "messages": self.messages,
-        });
-        (self.status_code, Json(body)).into_response()
+        let status_code = self.status_code();
+        if let Some(detail) = &self.detail {
+            tracing::error!(
+                kind = ?self.kind,
+                status_code = %status_code,
+                error_chain = %sanitize_log_message(&error_chain(detail)),
+                "{}",
+                sanitize_log_message(&detail.to_string())
+            );
+        }
+        // メッセージにはユーザーが入力した文字列が含まれることがあるため、制御文字を取り除いてから返す
+        let messages = self
+            .messages
+            .iter()
+            .map(|message| sanitize_log_message(message))
+            .collect::<Vec<_>>();
+        let mut body = self.fields;
+        body.insert(String::from("messages"), serde_json::json!(messages));
+        (status_code, Json(body)).into_response()
     }
 }
 
+/// エラーと、そのエラーの原因をすべてたどって、`: `で連結した文字列を返す。
+///
+/// リポジトリエラーの場合、制約名などを含むデータベースのエラーは原因に含まれるため、ログにはこの文字列を記録する。
+pub fn error_chain(error: &anyhow::Error) -> String {
+    error
+        .chain()
+        .map(|cause| cause.to_string())
+        .collect::<Vec<_>>()
+        .join(": ")
+}
+
+/// ドメインエラーをAPIエラーに変換する。
+///
+/// ハンドラーはドメインエラーをこの変換のみでAPIエラーにすること。リポジトリエラーと予期しないエラーは、
+/// 詳細をクライアントに返さずにサーバーでのみ記録する。外部のサービスを利用できないエラーは、
+/// メッセージをクライアントに返して、詳細をサーバーで記録する。
 impl From<DomainError> for ApiError {
     fn from(error: DomainError) -> Self {
-        let status_code = match error.kind {
-            DomainErrorKind::Validation => StatusCode::BAD_REQUEST,
-            DomainErrorKind::NotFound => StatusCode::NOT_FOUND,
-            DomainErrorKind::Unauthorized => StatusCode::UNAUTHORIZED,
-            DomainErrorKind::Forbidden => StatusCode::FORBIDDEN,
-            DomainErrorKind::Repository => StatusCode::INTERNAL_SERVER_ERROR,
-            DomainErrorKind::Unexpected => StatusCode::INTERNAL_SERVER_ERROR,
-        };
+        let kind = ApiErrorKind::from(error.kind);
+        if kind == ApiErrorKind::InternalServerError {
+            return ApiError {
+                kind,
+                messages: vec![INTERNAL_SERVER_ERROR_MESSAGE.into()],
+                detail: Some(error.source.context(error.messages.join(", "))),
+                fields: serde_json::Map::new(),
+            };
+        }
+        if kind == ApiErrorKind::ServiceUnavailable {
+            return ApiError {
+                kind,
+                messages: error.messages,
+                detail: Some(error.source),
+                fields: serde_json::Map::new(),
+            };
+        }
         Self {
-            status_code,
+            kind,
             messages: error.messages,
+            detail: None,
+            fields: serde_json::Map::new(),
         }
     }
 }
@@ -52,50 +195,299 @@ impl From<DomainError> for ApiError {
 pub const COOKIE_ACCESS_TOKEN_KEY: &str = "access_token";
 pub const COOKIE_REFRESH_TOKEN_KEY: &str = "refresh_token";
 
-pub fn bad_request(message: Cow<'static, str>) -> ApiError {
-    ApiError {
-        status_code: StatusCode::BAD_REQUEST,
-        messages: vec![message],
+/// アクセストークンとリフレッシュトークンを格納するクッキーを生成するファクトリ
+///
+/// クッキーを登録するときと削除するときで、ドメインなどの属性が一致するように、すべてのクッキーをこのファクトリで
+/// 生成する。
+#[derive(Debug, Clone)]
+pub struct CookieFactory {
+    /// クッキーのドメイン
+    domain: String,
+    /// `Secure`属性を付与するかどうか
+    secure: bool,
+}
+
+impl CookieFactory {
+    pub fn new(settings: &HttpSettings) -> Self {
+        Self {
+            domain: settings.host.clone(),
+            secure: settings.protocol == HttpProtocol::Https,
+        }
+    }
+
+    /// アクセストークンを格納するクッキーを生成する。
+    pub fn access_cookie(&self, value: &SecretString, max_age: Duration) -> Cookie<'static> {
+        self.build(COOKIE_ACCESS_TOKEN_KEY, value.expose_secret().to_string())
+            .max_age(max_age)
+            .build()
+    }
+
+    /// リフレッシュトークンを格納するクッキーを生成する。
+    pub fn refresh_cookie(&self, value: &SecretString, max_age: Duration) -> Cookie<'static> {
+        self.build(COOKIE_REFRESH_TOKEN_KEY, value.expose_secret().to_string())
+            .max_age(max_age)
+            .build()
     }
+
+    /// アクセストークンとリフレッシュトークンのクッキーを削除するクッキーを生成する。
+    ///
+    /// `Max-Age`のみでは削除しないクライアントがあるため、`Max-Age=0`と過去の`Expires`の両方を指定する。
+    pub fn clear_pair(&self) -> [Cookie<'static>; 2] {
+        [COOKIE_ACCESS_TOKEN_KEY, COOKIE_REFRESH_TOKEN_KEY].map(|name| {
+            self.build(name, String::new())
+                .max_age(Duration::ZERO)
+                .expires(OffsetDateTime::UNIX_EPOCH)
+                .build()
+        })
+    }
+
+    fn build(&self, name: &'static str, value: String) -> cookie::CookieBuilder<'static> {
+        Cookie::build((name, value))
+            .domain(self.domain.clone())
+            .path("/")
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(SameSite::Strict)
+    }
+}
+
+pub fn bad_request(message: Cow<'static, str>) -> ApiError {
+    ApiError::new(ApiErrorKind::BadRequest, message)
 }
 
 pub fn not_found(name: &str) -> ApiError {
-    ApiError {
-        status_code: StatusCode::NOT_FOUND,
-        messages: vec![format!("{} not found", name).into()],
-    }
+    ApiError::new(ApiErrorKind::NotFound, format!("{} not found", name))
+}
+
+const REQUEST_TIMED_OUT_MESSAGE: &str = "The request timed out. Please try again later";
+
+pub fn request_timed_out() -> ApiError {
+    ApiError::new(ApiErrorKind::ServiceUnavailable, REQUEST_TIMED_OUT_MESSAGE)
 }
 
 const LOGIN_FAILED_MESSAGE: &str = "Login failed. Please check your email and password";
 
 pub fn login_failed() -> ApiError {
-    ApiError {
-        status_code: StatusCode::BAD_REQUEST,
-        messages: vec![LOGIN_FAILED_MESSAGE.into()],
-    }
+    ApiError::new(ApiErrorKind::BadRequest, LOGIN_FAILED_MESSAGE)
 }
 
 const USER_CREDENTIALS_INVALID_MESSAGE: &str = "User credentials are invalid or missing";
 
 pub fn unauthorized() -> ApiError {
-    ApiError {
-        status_code: StatusCode::UNAUTHORIZED,
-        messages: vec![USER_CREDENTIALS_INVALID_MESSAGE.into()],
-    }
+    ApiError::new(ApiErrorKind::Unauthorized, USER_CREDENTIALS_INVALID_MESSAGE)
 }
 
 const USER_LOCKED_MESSAGE: &str = "User is locked";
 
 pub fn user_locked() -> ApiError {
+    ApiError::new(ApiErrorKind::Locked, USER_LOCKED_MESSAGE)
+}
+
+/// アカウントの所有者に返す、ロックされた日時と理由を含めたエラーを返す。
+///
+/// ロックされた日時と理由は所有者以外に公開しないため、ログインなど所有者であることを確認できない場合は
+/// `user_locked`を使用すること。ロックされた日時または理由が記録されていない場合は`user_locked`と同じである。
+pub fn user_locked_for_owner(user: &User) -> ApiError {
+    let (Some(locked_at), Some(reason)) = (user.locked_at, user.lock_reason) else {
+        return user_locked();
+    };
+    let Ok(locked_at) = locked_at.format(&Rfc3339) else {
+        return user_locked();
+    };
+    ApiError::new(
+        ApiErrorKind::Locked,
+        format!(
+            "Your account was locked on {} due to {}",
+            locked_at,
+            reason.description()
+        ),
+    )
+}
+
+const ADMIN_REQUIRED_MESSAGE: &str = "Administrator privileges are required";
+
+pub fn admin_required() -> ApiError {
+    ApiError::new(ApiErrorKind::Forbidden, ADMIN_REQUIRED_MESSAGE)
+}
+
+const IMPERSONATION_READ_ONLY_MESSAGE: &str = "Impersonated sessions are read-only";
+
+pub fn impersonation_read_only() -> ApiError {
+    ApiError::new(ApiErrorKind::Forbidden, IMPERSONATION_READ_ONLY_MESSAGE)
+}
+
+/// ドメインエラー以外のエラーを500 Internal Server ErrorのAPIエラーに変換する。
+///
+/// ドメインエラーは種類を失わないように`ApiError::from`で変換すること。
+pub fn internal_server_error<E: Into<anyhow::Error>>(err: E) -> ApiError {
     ApiError {
-        status_code: StatusCode::LOCKED,
-        messages: vec![USER_LOCKED_MESSAGE.into()],
+        kind: ApiErrorKind::InternalServerError,
+        messages: vec![INTERNAL_SERVER_ERROR_MESSAGE.into()],
+        detail: Some(err.into()),
+        fields: serde_json::Map::new(),
     }
 }
 
-pub fn internal_server_error<E: std::error::Error>(err: E) -> ApiError {
-    ApiError {
-        status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        messages: vec![err.to_string().into()],
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookie_factory(protocol: HttpProtocol) -> CookieFactory {
+        CookieFactory::new(&HttpSettings {
+            protocol,
+            host: String::from("example.com"),
+            port: 8000,
+            ..HttpSettings::default()
+        })
+    }
+
+    #[rstest::rstest]
+    #[case(HttpProtocol::Http, false)]
+    #[case(HttpProtocol::Https, true)]
+    fn cookie_factory_builds_token_cookies(
+        #[case] protocol: HttpProtocol,
+        #[case] expected_secure: bool,
+    ) {
+        let factory = cookie_factory(protocol);
+        let value = SecretString::new("token".into());
+        let cookies = [
+            (
+                factory.access_cookie(&value, Duration::seconds(300)),
+                COOKIE_ACCESS_TOKEN_KEY,
+                300,
+            ),
+            (
+                factory.refresh_cookie(&value, Duration::seconds(3600)),
+                COOKIE_REFRESH_TOKEN_KEY,
+                3600,
+            ),
+        ];
+        for (cookie, name, max_age) in cookies {
+            assert_eq!(cookie.name(), name);
+            assert_eq!(cookie.value(), "token");
+            assert_eq!(cookie.domain(), Some("example.com"));
+            assert_eq!(cookie.path(), Some("/"));
+            assert_eq!(cookie.http_only(), Some(true));
+            assert_eq!(cookie.secure(), Some(expected_secure));
+            assert_eq!(cookie.same_site(), Some(SameSite::Strict));
+            assert_eq!(cookie.max_age(), Some(Duration::seconds(max_age)));
+            assert!(cookie.expires().is_none());
+        }
+    }
+
+    #[test]
+    fn cookie_factory_clears_token_cookies() {
+        let factory = cookie_factory(HttpProtocol::Https);
+        let [access, refresh] = factory.clear_pair();
+        assert_eq!(access.name(), COOKIE_ACCESS_TOKEN_KEY);
+        assert_eq!(refresh.name(), COOKIE_REFRESH_TOKEN_KEY);
+        for cookie in [access, refresh] {
+            assert_eq!(cookie.value(), "");
+            assert_eq!(cookie.domain(), Some("example.com"));
+            assert_eq!(cookie.path(), Some("/"));
+            assert_eq!(cookie.max_age(), Some(Duration::ZERO));
+            assert_eq!(cookie.expires_datetime(), Some(OffsetDateTime::UNIX_EPOCH));
+            let header = cookie.to_string();
+            assert!(header.contains("Max-Age=0"), "{header}");
+            assert!(
+                header.contains("Expires=Thu, 01 Jan 1970 00:00:00 GMT"),
+                "{header}"
+            );
+        }
+    }
+
+    #[rstest::rstest]
+    #[case(DomainErrorKind::Repository)]
+    #[case(DomainErrorKind::Unexpected)]
+    fn internal_errors_do_not_expose_details(#[case] kind: DomainErrorKind) {
+        let error = DomainError {
+            kind,
+            messages: vec![
+                "duplicate key value violates unique constraint \"users_email_key\"".into(),
+            ],
+            source: anyhow::anyhow!("relation \"users\" does not exist"),
+        };
+        let api_error = ApiError::from(error);
+        assert_eq!(api_error.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(api_error.messages, vec![INTERNAL_SERVER_ERROR_MESSAGE]);
+        let detail = format!("{:#}", api_error.detail.unwrap());
+        assert!(detail.contains("users_email_key"), "{detail}");
+        assert!(
+            detail.contains("relation \"users\" does not exist"),
+            "{detail}"
+        );
+    }
+
+    #[test]
+    fn error_chain_joins_all_causes() {
+        let error = anyhow::anyhow!("relation \"todos\" does not exist")
+            .context("Failed to list todos")
+            .context("Repository error");
+        assert_eq!(
+            error_chain(&error),
+            "Repository error: Failed to list todos: relation \"todos\" does not exist"
+        );
+    }
+
+    #[rstest::rstest]
+    #[case(DomainErrorKind::Validation, StatusCode::BAD_REQUEST)]
+    #[case(DomainErrorKind::NotFound, StatusCode::NOT_FOUND)]
+    #[case(DomainErrorKind::Unauthorized, StatusCode::UNAUTHORIZED)]
+    #[case(DomainErrorKind::Forbidden, StatusCode::FORBIDDEN)]
+    #[case(DomainErrorKind::Conflict, StatusCode::CONFLICT)]
+    #[case(DomainErrorKind::Unavailable, StatusCode::SERVICE_UNAVAILABLE)]
+    #[case(DomainErrorKind::Repository, StatusCode::INTERNAL_SERVER_ERROR)]
+    #[case(DomainErrorKind::Unexpected, StatusCode::INTERNAL_SERVER_ERROR)]
+    fn domain_error_kinds_map_to_status_codes(
+        #[case] kind: DomainErrorKind,
+        #[case] expected: StatusCode,
+    ) {
+        let api_error = ApiError::from(domain::domain_error(kind.clone(), "error"));
+        assert_eq!(api_error.kind, ApiErrorKind::from(kind));
+        assert_eq!(api_error.status_code(), expected);
+    }
+
+    #[test]
+    fn validation_errors_keep_messages() {
+        let error = domain::domain_error(DomainErrorKind::Validation, "title: too long");
+        let api_error = ApiError::from(error);
+        assert_eq!(api_error.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(api_error.messages, vec!["title: too long"]);
+        assert!(api_error.detail.is_none());
+    }
+
+    #[tokio::test]
+    async fn error_response_body_does_not_contain_control_characters() {
+        let api_error = ApiError {
+            kind: ApiErrorKind::BadRequest,
+            messages: vec!["keyword: \"a\nb\0c\u{202E}\" is invalid".into()],
+            detail: None,
+            fields: serde_json::Map::new(),
+        };
+        let response = api_error.into_response();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = serde_json::from_slice::<serde_json::Value>(&bytes).unwrap();
+        assert_eq!(body["messages"][0], "keyword: \"a bc\" is invalid");
+    }
+
+    #[tokio::test]
+    async fn error_response_body_contains_the_additional_fields() {
+        let api_error = bad_request("password: too short".into())
+            .with_field("hint", serde_json::json!({ "minLength": 8 }))
+            .with_field("messages", "overwritten");
+        let response = api_error.into_response();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = serde_json::from_slice::<serde_json::Value>(&bytes).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "messages": ["password: too short"],
+                "hint": { "minLength": 8 },
+            })
+        );
     }
 }
@@ -0,0 +1,4 @@
+//! APIのルートのパス
+//!
+//! パスの定数と関数は、APIクライアントと共有するため`domain::api::paths`で定義する。
+pub use domain::api::paths::*;
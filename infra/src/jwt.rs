@@ -10,10 +10,21 @@ use uuid::Uuid;
 use domain::{
     DomainError, DomainErrorKind, DomainResult,
     models::{AccessToken, RefreshToken, UserId},
+    repositories::TokenType,
 };
 
+use crate::settings::TokenSettings;
+
 const SUBJECT_KEY: &str = "sub";
 const EXPIRATION_KEY: &str = "exp";
+const ISSUED_AT_KEY: &str = "iat";
+const ISSUER_KEY: &str = "iss";
+const TOKEN_TYPE_KEY: &str = "typ";
+
+/// JWTの署名に使用するアルゴリズム
+///
+/// JWTのヘッダーに記録されたアルゴリズムがこのアルゴリズムと異なる場合は、JWTを拒否する。
+const SIGNING_ALGORITHM: AlgorithmType = AlgorithmType::Hs384;
 
 /// トークンペア
 #[derive(Debug, Clone)]
@@ -29,61 +40,96 @@ type HmacKey = Hmac<Sha384>;
 pub struct Claim {
     /// ユーザーID
     pub user_id: UserId,
+    /// トークンの種類
+    ///
+    /// `typ`クレイムを含まない、以前に発行されたJWTの場合は`None`になる。
+    pub token_type: Option<TokenType>,
+    /// 発行日時を示すUNIXエポック秒
+    ///
+    /// `iat`クレイムを含まない、以前に発行されたJWTの場合は`None`になる。
+    pub issued_at: Option<u64>,
     /// 有効期限を示すUNIXエポック秒
     pub expiration: u64,
 }
 
+impl Claim {
+    /// クレイムを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `token_type` - トークンの種類
+    /// * `issued_at` - 発行日時
+    /// * `expired_at` - 有効期限
+    pub fn new(
+        user_id: UserId,
+        token_type: TokenType,
+        issued_at: OffsetDateTime,
+        expired_at: OffsetDateTime,
+    ) -> Self {
+        Self {
+            user_id,
+            token_type: Some(token_type),
+            issued_at: Some(issued_at.unix_timestamp() as u64),
+            expiration: expired_at.unix_timestamp() as u64,
+        }
+    }
+}
+
 /// JWTのアクセストークンとリフレッシュトークンを生成する。
 ///
 /// # 引数
 ///
 /// * `user_id` - ユーザーID
-/// * `access_max_age` - アクセストークンの最大有効期間（秒）
-/// * `refresh_max_age` - リフレッシュトークンの最大有効期間（秒）
-/// * `secret_key` - JWTを作成する秘密鍵
+/// * `issued_at` - トークンの発行日時
+/// * `access_expired_at` - アクセストークンの有効期限
+/// * `refresh_expired_at` - リフレッシュトークンの有効期限
+/// * `settings` - トークン設定
 pub fn generate_token_pair(
     user_id: UserId,
+    issued_at: OffsetDateTime,
     access_expired_at: OffsetDateTime,
     refresh_expired_at: OffsetDateTime,
-    secret_key: &SecretString,
+    settings: &TokenSettings,
 ) -> DomainResult<TokenPair> {
     // アクセストークンを生成
-    let claim = Claim {
-        user_id,
-        expiration: access_expired_at.unix_timestamp() as u64,
-    };
-    let access = generate_token(claim, secret_key)?;
+    let claim = Claim::new(user_id, TokenType::Access, issued_at, access_expired_at);
+    let access = generate_token(claim, settings)?;
     // リフレッシュトークンを生成
-    let claim = Claim {
-        user_id,
-        expiration: refresh_expired_at.unix_timestamp() as u64,
-    };
-    let refresh = generate_token(claim, secret_key)?;
+    let claim = Claim::new(user_id, TokenType::Refresh, issued_at, refresh_expired_at);
+    let refresh = generate_token(claim, settings)?;
     Ok(TokenPair {
         access: AccessToken(access),
         refresh: RefreshToken(refresh),
     })
 }
 
-/// ユーザーIDと有効期限を指定したJWTを生成する。
+/// クレイムを指定したJWTを生成する。
 ///
 /// # 引数
 ///
 /// * `claim` - クレイム
-/// * `secret_key` - JWTを生成するときの秘密鍵
+/// * `settings` - トークン設定
 ///
 /// # 戻り値
 ///
 /// JWT
-pub fn generate_token(claim: Claim, secret_key: &SecretString) -> DomainResult<SecretString> {
-    let key: HmacKey = generate_hmac_key(secret_key)?;
+pub fn generate_token(claim: Claim, settings: &TokenSettings) -> DomainResult<SecretString> {
+    let key: HmacKey = generate_hmac_key(&settings.jwt_secret)?;
     let header = Header {
-        algorithm: AlgorithmType::Hs384,
+        algorithm: SIGNING_ALGORITHM,
         ..Default::default()
     };
     let mut claims = BTreeMap::new();
     claims.insert(SUBJECT_KEY, claim.user_id.0.to_string());
     claims.insert(EXPIRATION_KEY, claim.expiration.to_string());
+    claims.insert(ISSUER_KEY, settings.issuer.clone());
+    if let Some(issued_at) = claim.issued_at {
+        claims.insert(ISSUED_AT_KEY, issued_at.to_string());
+    }
+    if let Some(token_type) = claim.token_type {
+        claims.insert(TOKEN_TYPE_KEY, token_type.to_string());
+    }
     let token = Token::new(header, claims)
         .sign_with_key(&key)
         .map_err(|e| DomainError {
@@ -102,30 +148,81 @@ fn generate_hmac_key(secret_key: &SecretString) -> DomainResult<HmacKey> {
     })
 }
 
-/// JWTからクレイムを取り出す。
+/// JWTを検証して、クレイムを取り出す。
+///
+/// 次の場合はJWTを拒否する。
+///
+/// * ヘッダーに記録されたアルゴリズムがHS384でない場合
+/// * 署名が正しくない場合
+/// * 発行者（`iss`）が設定と異なる場合
+/// * 発行者（`iss`）またはトークンの種類（`typ`）を含まず、以前に発行されたJWTを受け付けない設定の場合
 ///
 /// # 引数
 ///
 /// * `token` - JWT
-/// * `secret_key` - JWTを生成するときの秘密鍵
+/// * `settings` - トークン設定
 ///
 /// # 戻り値
 ///
 /// クレイム
 pub fn retrieve_claim_from_token(
     token: &SecretString,
-    secret_key: &SecretString,
+    settings: &TokenSettings,
 ) -> DomainResult<Claim> {
-    let key: HmacKey = generate_hmac_key(secret_key)?;
-    let claims: BTreeMap<String, String> =
-        token
-            .expose_secret()
-            .verify_with_key(&key)
-            .map_err(|e| DomainError {
-                kind: DomainErrorKind::Unexpected,
-                messages: vec!["Failed to verify JWT".into()],
+    let key: HmacKey = generate_hmac_key(&settings.jwt_secret)?;
+    let token: Token<Header, BTreeMap<String, String>, _> =
+        Token::parse_unverified(token.expose_secret()).map_err(|e| DomainError {
+            kind: DomainErrorKind::Unauthorized,
+            messages: vec!["Failed to parse JWT".into()],
+            source: e.into(),
+        })?;
+    // ヘッダーに記録されたアルゴリズムを検証
+    let algorithm = token.header().algorithm;
+    if algorithm != SIGNING_ALGORITHM {
+        let message = format!("The algorithm of JWT is not allowed: {algorithm:?}");
+        return Err(DomainError {
+            kind: DomainErrorKind::Unauthorized,
+            messages: vec![message.clone().into()],
+            source: anyhow::anyhow!(message),
+        });
+    }
+    // 署名を検証
+    let token = token.verify_with_key(&key).map_err(|e| DomainError {
+        kind: DomainErrorKind::Unauthorized,
+        messages: vec!["Failed to verify JWT".into()],
+        source: e.into(),
+    })?;
+    let claims = token.claims();
+    // 発行者を検証
+    match claims.get(ISSUER_KEY) {
+        Some(issuer) if *issuer == settings.issuer => {}
+        Some(issuer) => {
+            let message = format!("The issuer was not valid in claim: {issuer}");
+            return Err(DomainError {
+                kind: DomainErrorKind::Unauthorized,
+                messages: vec![message.clone().into()],
+                source: anyhow::anyhow!(message),
+            });
+        }
+        None => reject_legacy_token(settings, "The issuer was not found in claim")?,
+    }
+    // トークンの種類を取得
+    let token_type = match claims.get(TOKEN_TYPE_KEY) {
+        Some(token_type) => {
+            let token_type = TokenType::try_from(token_type.as_str()).map_err(|e| DomainError {
+                kind: DomainErrorKind::Unauthorized,
+                messages: vec![
+                    format!("The token type was not valid in claim: {token_type}").into(),
+                ],
                 source: e.into(),
             })?;
+            Some(token_type)
+        }
+        None => {
+            reject_legacy_token(settings, "The token type was not found in claim")?;
+            None
+        }
+    };
     // ユーザーIDを取得
     let user_id = claims.get(SUBJECT_KEY).ok_or_else(|| DomainError {
         kind: DomainErrorKind::Unexpected,
@@ -138,6 +235,17 @@ pub fn retrieve_claim_from_token(
         source: e.into(),
     })?;
     let user_id = UserId::from(user_id);
+    // 発行日時を取得
+    let issued_at = claims
+        .get(ISSUED_AT_KEY)
+        .map(|issued_at| {
+            issued_at.parse::<u64>().map_err(|e| DomainError {
+                kind: DomainErrorKind::Unexpected,
+                messages: vec![format!("The issued at was not valid in claim: {issued_at}").into()],
+                source: e.into(),
+            })
+        })
+        .transpose()?;
     // 有効期限を取得
     let expiration = claims.get(EXPIRATION_KEY).ok_or_else(|| DomainError {
         kind: DomainErrorKind::Unexpected,
@@ -151,38 +259,198 @@ pub fn retrieve_claim_from_token(
     })?;
     Ok(Claim {
         user_id,
+        token_type,
+        issued_at,
         expiration,
     })
 }
 
+/// 以前に発行されたJWTを受け付けない設定の場合は、エラーを返す。
+fn reject_legacy_token(settings: &TokenSettings, message: &'static str) -> DomainResult<()> {
+    if settings.accept_legacy_tokens {
+        return Ok(());
+    }
+    Err(DomainError {
+        kind: DomainErrorKind::Unauthorized,
+        messages: vec![message.into()],
+        source: anyhow::anyhow!(message),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use jwt::ToBase64 as _;
+    use sha2::Sha256;
     use time::Duration;
 
+    fn token_settings(accept_legacy_tokens: bool) -> TokenSettings {
+        TokenSettings {
+            jwt_secret: SecretString::new("super-secret-key".into()),
+            issuer: String::from("rusty-todo"),
+            accept_legacy_tokens,
+            ..Default::default()
+        }
+    }
+
+    /// クレイムのマップに署名したJWTを生成する。
+    fn sign_claims(claims: &BTreeMap<&str, String>, secret_key: &str) -> SecretString {
+        let key: HmacKey = Hmac::new_from_slice(secret_key.as_bytes()).unwrap();
+        let header = Header {
+            algorithm: AlgorithmType::Hs384,
+            ..Default::default()
+        };
+        let token = Token::new(header, claims.clone())
+            .sign_with_key(&key)
+            .unwrap();
+        SecretString::new(token.as_str().into())
+    }
+
+    fn legacy_claims(user_id: UserId) -> BTreeMap<&'static str, String> {
+        let expiration = OffsetDateTime::now_utc() + Duration::days(1);
+        let mut claims = BTreeMap::new();
+        claims.insert(SUBJECT_KEY, user_id.0.to_string());
+        claims.insert(EXPIRATION_KEY, expiration.unix_timestamp().to_string());
+        claims
+    }
+
     #[test]
     fn test_generate_valid_token_pair() -> anyhow::Result<()> {
         let requested_at = OffsetDateTime::now_utc();
         let user_id = UserId::from(Uuid::new_v4());
         let access_expired_at = requested_at + Duration::days(1);
         let refresh_expired_at = requested_at + Duration::days(30);
-        let secret_key = SecretString::new("super-secret-key".into());
+        let settings = token_settings(false);
 
-        let token_pair =
-            generate_token_pair(user_id, access_expired_at, refresh_expired_at, &secret_key)?;
-        let access_claim = retrieve_claim_from_token(&token_pair.access.0, &secret_key)?;
-        let refresh_claim = retrieve_claim_from_token(&token_pair.refresh.0, &secret_key)?;
+        let token_pair = generate_token_pair(
+            user_id,
+            requested_at,
+            access_expired_at,
+            refresh_expired_at,
+            &settings,
+        )?;
+        let access_claim = retrieve_claim_from_token(&token_pair.access.0, &settings)?;
+        let refresh_claim = retrieve_claim_from_token(&token_pair.refresh.0, &settings)?;
 
         assert_eq!(access_claim.user_id, user_id);
+        assert_eq!(access_claim.token_type, Some(TokenType::Access));
+        assert_eq!(
+            access_claim.issued_at,
+            Some(requested_at.unix_timestamp() as u64)
+        );
         assert_eq!(
             access_claim.expiration,
             access_expired_at.unix_timestamp() as u64
         );
         assert_eq!(refresh_claim.user_id, user_id);
+        assert_eq!(refresh_claim.token_type, Some(TokenType::Refresh));
+        assert_eq!(
+            refresh_claim.issued_at,
+            Some(requested_at.unix_timestamp() as u64)
+        );
         assert_eq!(
             refresh_claim.expiration,
             refresh_expired_at.unix_timestamp() as u64
         );
         Ok(())
     }
+
+    #[test]
+    fn test_reject_token_with_none_algorithm() {
+        let settings = token_settings(true);
+        let header = Header {
+            algorithm: AlgorithmType::None,
+            ..Default::default()
+        };
+        let mut claims = legacy_claims(UserId::from(Uuid::new_v4()));
+        claims.insert(ISSUER_KEY, settings.issuer.clone());
+        claims.insert(TOKEN_TYPE_KEY, TokenType::Refresh.to_string());
+        // 署名を空にしたJWTを偽造
+        let token = format!(
+            "{}.{}.",
+            header.to_base64().unwrap(),
+            claims.to_base64().unwrap()
+        );
+
+        let result = retrieve_claim_from_token(&SecretString::new(token.into()), &settings);
+
+        let err = result.unwrap_err();
+        assert_eq!(err.kind, DomainErrorKind::Unauthorized);
+    }
+
+    #[test]
+    fn test_reject_token_signed_with_other_algorithm() {
+        let settings = token_settings(true);
+        let key: Hmac<Sha256> =
+            Hmac::new_from_slice(settings.jwt_secret.expose_secret().as_bytes()).unwrap();
+        let header = Header {
+            algorithm: AlgorithmType::Hs256,
+            ..Default::default()
+        };
+        let mut claims = legacy_claims(UserId::from(Uuid::new_v4()));
+        claims.insert(ISSUER_KEY, settings.issuer.clone());
+        let token = Token::new(header, claims).sign_with_key(&key).unwrap();
+
+        let result =
+            retrieve_claim_from_token(&SecretString::new(token.as_str().into()), &settings);
+
+        let err = result.unwrap_err();
+        assert_eq!(err.kind, DomainErrorKind::Unauthorized);
+    }
+
+    #[test]
+    fn test_reject_token_with_wrong_issuer() {
+        let settings = token_settings(true);
+        let mut claims = legacy_claims(UserId::from(Uuid::new_v4()));
+        claims.insert(ISSUER_KEY, String::from("someone-else"));
+        claims.insert(TOKEN_TYPE_KEY, TokenType::Access.to_string());
+        let token = sign_claims(&claims, settings.jwt_secret.expose_secret());
+
+        let result = retrieve_claim_from_token(&token, &settings);
+
+        let err = result.unwrap_err();
+        assert_eq!(err.kind, DomainErrorKind::Unauthorized);
+    }
+
+    #[test]
+    fn test_reject_token_with_unknown_token_type() {
+        let settings = token_settings(true);
+        let mut claims = legacy_claims(UserId::from(Uuid::new_v4()));
+        claims.insert(ISSUER_KEY, settings.issuer.clone());
+        claims.insert(TOKEN_TYPE_KEY, String::from("unknown"));
+        let token = sign_claims(&claims, settings.jwt_secret.expose_secret());
+
+        let result = retrieve_claim_from_token(&token, &settings);
+
+        let err = result.unwrap_err();
+        assert_eq!(err.kind, DomainErrorKind::Unauthorized);
+    }
+
+    #[test]
+    fn test_accept_legacy_token_during_grace_period() -> anyhow::Result<()> {
+        let settings = token_settings(true);
+        let user_id = UserId::from(Uuid::new_v4());
+        let token = sign_claims(&legacy_claims(user_id), settings.jwt_secret.expose_secret());
+
+        let claim = retrieve_claim_from_token(&token, &settings)?;
+
+        assert_eq!(claim.user_id, user_id);
+        assert!(claim.token_type.is_none());
+        assert!(claim.issued_at.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_legacy_token_after_grace_period() {
+        let settings = token_settings(false);
+        let token = sign_claims(
+            &legacy_claims(UserId::from(Uuid::new_v4())),
+            settings.jwt_secret.expose_secret(),
+        );
+
+        let result = retrieve_claim_from_token(&token, &settings);
+
+        let err = result.unwrap_err();
+        assert_eq!(err.kind, DomainErrorKind::Unauthorized);
+    }
 }
@@ -1,15 +1,359 @@
+pub mod access_log;
+pub mod build_info;
 pub mod http;
 pub mod jwt;
+pub mod lookup_cache;
+pub mod mail;
+#[cfg(feature = "lite")]
+pub mod memory;
 pub mod password;
 pub mod postgres;
 pub mod redis;
 pub mod settings;
+#[cfg(feature = "lite")]
+pub mod sqlite;
 
-use settings::AppSettings;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use domain::{
+    DomainError, DomainErrorKind, DomainResult,
+    api::PoolStatus,
+    models::{Role, RoleCode, TodoStatus, TodoStatusCode},
+    repositories::{
+        DailyDigestRepository, LookupRepository, TodoCommentRepository, TodoRepository,
+        TokenRepository, UserRepository,
+    },
+};
+use use_case::{
+    comment::TodoCommentUseCase, digest::DailyDigestUseCase, todo::TodoUseCase, user::UserUseCase,
+};
+
+use crate::{
+    access_log::{AccessLogSink, StdoutAccessLogSink},
+    build_info::BuildInfo,
+    lookup_cache::LookupCache,
+    mail::Mailer,
+    postgres::repositories::{
+        PgDailyDigestRepository, PgRoleRepository, PgTodoCommentRepository, PgTodoRepository,
+        PgTodoStatusRepository, PgUserRepository,
+    },
+    redis::{circuit_breaker::CircuitBreaker, key::RedisKey, token::RedisTokenRepository},
+    settings::{AppSettings, PasswordSettings},
+};
+
+/// ユーザーユースケースの実装
+pub type UserUseCaseImpl = UserUseCase<Arc<dyn UserRepository>, Arc<dyn TokenRepository>>;
+
+/// Todoユースケースの実装
+pub type TodoUseCaseImpl = TodoUseCase<Arc<dyn TodoRepository>>;
+
+/// Todoコメントユースケースの実装
+pub type TodoCommentUseCaseImpl =
+    TodoCommentUseCase<Arc<dyn TodoRepository>, Arc<dyn TodoCommentRepository>>;
+
+/// 日次ダイジェストユースケースの実装
+pub type DailyDigestUseCaseImpl =
+    DailyDigestUseCase<Arc<dyn TodoRepository>, Arc<dyn DailyDigestRepository>>;
+
+/// ロールリポジトリ
+pub type RoleRepository = dyn LookupRepository<Entity = Role, Code = RoleCode>;
+
+/// Todo状態リポジトリ
+pub type TodoStatusRepository = dyn LookupRepository<Entity = TodoStatus, Code = TodoStatusCode>;
+
+/// データベースとRedisの疎通を確認するときのタイムアウト
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// データベースとトークンストアの接続先
+#[derive(Debug, Clone)]
+pub enum Backend {
+    /// PostgreSQLとRedis
+    Postgres {
+        pg_pool: sqlx::PgPool,
+        redis_pool: deadpool_redis::Pool,
+        /// Redisトークンリポジトリが共有するサーキットブレーカー
+        redis_circuit_breaker: Arc<CircuitBreaker>,
+    },
+    /// SQLiteとインプロセスのトークンストア
+    #[cfg(feature = "lite")]
+    Sqlite { pool: sqlx::SqlitePool },
+}
+
+/// データベースエンジンに応じて選択したリポジトリ
+#[derive(Debug, Clone)]
+pub struct Repositories {
+    pub user: Arc<dyn UserRepository>,
+    pub todo: Arc<dyn TodoRepository>,
+    pub comment: Arc<dyn TodoCommentRepository>,
+    pub digest: Arc<dyn DailyDigestRepository>,
+    pub role: Arc<RoleRepository>,
+    pub todo_status: Arc<TodoStatusRepository>,
+    pub token: Arc<dyn TokenRepository>,
+}
+
+impl Repositories {
+    /// PostgreSQLとRedisのリポジトリを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `pg_pool`: PostgreSQLコネクションプール
+    /// * `token_repo`: Redisトークンリポジトリ
+    pub fn postgres(pg_pool: sqlx::PgPool, token_repo: RedisTokenRepository) -> Self {
+        Self {
+            user: Arc::new(PgUserRepository::new(pg_pool.clone())),
+            todo: Arc::new(PgTodoRepository::new(pg_pool.clone())),
+            comment: Arc::new(PgTodoCommentRepository::new(pg_pool.clone())),
+            digest: Arc::new(PgDailyDigestRepository::new(pg_pool.clone())),
+            role: Arc::new(PgRoleRepository {
+                pool: pg_pool.clone(),
+            }),
+            todo_status: Arc::new(PgTodoStatusRepository { pool: pg_pool }),
+            token: Arc::new(token_repo),
+        }
+    }
+
+    /// SQLiteとインプロセスのトークンストアのリポジトリを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `pool`: SQLiteコネクションプール
+    #[cfg(feature = "lite")]
+    pub fn sqlite(pool: sqlx::SqlitePool) -> Self {
+        use crate::{
+            memory::token::MemoryTokenRepository,
+            sqlite::repositories::{
+                SqliteDailyDigestRepository, SqliteRoleRepository, SqliteTodoCommentRepository,
+                SqliteTodoRepository, SqliteTodoStatusRepository, SqliteUserRepository,
+            },
+        };
+
+        Self {
+            user: Arc::new(SqliteUserRepository::new(pool.clone())),
+            todo: Arc::new(SqliteTodoRepository::new(pool.clone())),
+            comment: Arc::new(SqliteTodoCommentRepository::new(pool.clone())),
+            digest: Arc::new(SqliteDailyDigestRepository::new(pool.clone())),
+            role: Arc::new(SqliteRoleRepository { pool: pool.clone() }),
+            todo_status: Arc::new(SqliteTodoStatusRepository { pool }),
+            token: Arc::new(MemoryTokenRepository::default()),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub app_settings: AppSettings,
-    pub pg_pool: sqlx::PgPool,
-    pub redis_pool: deadpool_redis::Pool,
+    /// パスワード設定
+    ///
+    /// ハンドラーがパスワードを検証、ハッシュ化するときに頻繁に参照するため、アプリケーション設定から複製して保持する。
+    pub password_settings: PasswordSettings,
+    /// データベースとトークンストアの接続先
+    pub backend: Backend,
+    /// リポジトリ
+    pub repos: Repositories,
+    /// ユーザーユースケース
+    pub user_use_case: UserUseCaseImpl,
+    /// Todoユースケース
+    pub todo_use_case: TodoUseCaseImpl,
+    /// Todoコメントユースケース
+    pub comment_use_case: TodoCommentUseCaseImpl,
+    /// 日次ダイジェストユースケース
+    pub digest_use_case: DailyDigestUseCaseImpl,
+    /// メール送信者
+    pub mailer: Arc<dyn Mailer>,
+    /// アクセスログの出力先
+    pub access_log_sink: Arc<dyn AccessLogSink>,
+    /// 参照データのキャッシュ
+    pub lookup_cache: LookupCache,
+    /// アプリケーションを起動した時刻
+    pub started_at: Instant,
+    /// アプリケーションのビルド情報
+    pub build_info: BuildInfo,
+}
+
+impl AppState {
+    /// PostgreSQLとRedisを使用するアプリケーションの状態を構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `app_settings`: アプリケーション設定
+    /// * `pg_pool`: PostgreSQLコネクションプール
+    /// * `redis_pool`: Redisコネクションプール
+    pub fn new(
+        app_settings: AppSettings,
+        pg_pool: sqlx::PgPool,
+        redis_pool: deadpool_redis::Pool,
+        mailer: Arc<dyn Mailer>,
+    ) -> Self {
+        let redis_circuit_breaker =
+            Arc::new(CircuitBreaker::new(app_settings.redis.circuit_breaker));
+        let token_repo = RedisTokenRepository::new(
+            redis_pool.clone(),
+            redis_circuit_breaker.clone(),
+            RedisKey::new(app_settings.redis.key_prefix.clone()),
+        );
+        let repos = Repositories::postgres(pg_pool.clone(), token_repo);
+        let backend = Backend::Postgres {
+            pg_pool,
+            redis_pool,
+            redis_circuit_breaker,
+        };
+        Self::with_backend(app_settings, backend, repos, mailer)
+    }
+
+    /// SQLiteとインプロセスのトークンストアを使用するアプリケーションの状態を構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `app_settings`: アプリケーション設定
+    /// * `pool`: SQLiteコネクションプール
+    #[cfg(feature = "lite")]
+    pub fn sqlite(
+        app_settings: AppSettings,
+        pool: sqlx::SqlitePool,
+        mailer: Arc<dyn Mailer>,
+    ) -> Self {
+        let repos = Repositories::sqlite(pool.clone());
+        Self::with_backend(app_settings, Backend::Sqlite { pool }, repos, mailer)
+    }
+
+    fn with_backend(
+        app_settings: AppSettings,
+        backend: Backend,
+        repos: Repositories,
+        mailer: Arc<dyn Mailer>,
+    ) -> Self {
+        let user_use_case = UserUseCase {
+            user_repo: repos.user.clone(),
+            token_repo: repos.token.clone(),
+        };
+        let todo_use_case = TodoUseCase {
+            todo_repo: repos.todo.clone(),
+            hide_cross_user_existence: app_settings.security.hide_cross_user_existence,
+            allow_past_due_date: app_settings.todo.allow_past_due_date,
+        };
+        let comment_use_case = TodoCommentUseCase {
+            todo_repo: repos.todo.clone(),
+            comment_repo: repos.comment.clone(),
+            hide_cross_user_existence: app_settings.security.hide_cross_user_existence,
+        };
+        let digest_use_case = DailyDigestUseCase {
+            todo_repo: repos.todo.clone(),
+            digest_repo: repos.digest.clone(),
+        };
+        Self {
+            password_settings: app_settings.password.clone(),
+            app_settings,
+            backend,
+            repos,
+            user_use_case,
+            todo_use_case,
+            comment_use_case,
+            digest_use_case,
+            mailer,
+            access_log_sink: Arc::new(StdoutAccessLogSink),
+            lookup_cache: LookupCache::default(),
+            started_at: Instant::now(),
+            build_info: BuildInfo::current(),
+        }
+    }
+
+    /// サーキットブレーカーを共有するRedisトークンリポジトリを返す。
+    ///
+    /// Redisを使用していない場合は`None`を返す。
+    pub fn redis_token_repo(&self) -> Option<RedisTokenRepository> {
+        match &self.backend {
+            Backend::Postgres {
+                redis_pool,
+                redis_circuit_breaker,
+                ..
+            } => Some(RedisTokenRepository::new(
+                redis_pool.clone(),
+                redis_circuit_breaker.clone(),
+                RedisKey::new(self.app_settings.redis.key_prefix.clone()),
+            )),
+            #[cfg(feature = "lite")]
+            Backend::Sqlite { .. } => None,
+        }
+    }
+
+    /// アプリケーションを起動してからの経過時間を返す。
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// データベースのコネクションプールの状態を返す。
+    pub fn database_pool_status(&self) -> PoolStatus {
+        match &self.backend {
+            Backend::Postgres { pg_pool, .. } => {
+                PoolStatus::new(pg_pool.size() as usize, pg_pool.num_idle())
+            }
+            #[cfg(feature = "lite")]
+            Backend::Sqlite { pool } => PoolStatus::new(pool.size() as usize, pool.num_idle()),
+        }
+    }
+
+    /// トークンストアのコネクションプールの状態を返す。
+    ///
+    /// インプロセスのトークンストアはコネクションを持たないため、すべて0を返す。
+    pub fn token_store_pool_status(&self) -> PoolStatus {
+        match &self.backend {
+            Backend::Postgres { redis_pool, .. } => {
+                let status = redis_pool.status();
+                PoolStatus::new(status.size, status.available)
+            }
+            #[cfg(feature = "lite")]
+            Backend::Sqlite { .. } => PoolStatus::new(0, 0),
+        }
+    }
+
+    /// データベースに`SELECT 1`を送信して、データベースに接続できるか確認する。
+    ///
+    /// 2秒以内に応答がない場合はエラーを返す。
+    pub async fn ping_database(&self) -> DomainResult<()> {
+        let result = match &self.backend {
+            Backend::Postgres { pg_pool, .. } => {
+                tokio::time::timeout(PING_TIMEOUT, sqlx::query("SELECT 1").execute(pg_pool))
+                    .await
+                    .map(|result| result.map(|_| ()))
+            }
+            #[cfg(feature = "lite")]
+            Backend::Sqlite { pool } => {
+                tokio::time::timeout(PING_TIMEOUT, sqlx::query("SELECT 1").execute(pool))
+                    .await
+                    .map(|result| result.map(|_| ()))
+            }
+        };
+        match result {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(DomainError {
+                kind: DomainErrorKind::Repository,
+                messages: vec!["Failed to send SELECT 1 to the database".into()],
+                source: e.into(),
+            }),
+            Err(e) => Err(ping_timed_out("the database", e)),
+        }
+    }
+
+    /// Redisに`PING`を送信して、トークンストアに接続できるか確認する。
+    ///
+    /// 2秒以内に応答がない場合はエラーを返す。インプロセスのトークンストアは、常に接続できる。
+    pub async fn ping_token_store(&self) -> DomainResult<()> {
+        let Some(token_repo) = self.redis_token_repo() else {
+            return Ok(());
+        };
+        tokio::time::timeout(PING_TIMEOUT, token_repo.ping())
+            .await
+            .map_err(|e| ping_timed_out("the redis", e))?
+    }
+}
+
+fn ping_timed_out(target: &str, e: tokio::time::error::Elapsed) -> DomainError {
+    DomainError {
+        kind: DomainErrorKind::Repository,
+        messages: vec![format!("The ping to {} timed out", target).into()],
+        source: e.into(),
+    }
 }
@@ -0,0 +1,136 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::RwLock;
+
+use domain::{
+    DomainResult,
+    models::{Role, TodoStatus},
+};
+
+/// 参照データのキャッシュ
+///
+/// ロールとTodo状態はほとんど変更されないため、プロセス内にキャッシュしてデータベースへの問い合わせを減らす。
+/// 参照データを変更する処理を追加する場合は、変更後に対応するキャッシュを`invalidate`で無効にすること。
+#[derive(Debug, Clone, Default)]
+pub struct LookupCache {
+    /// ロールのキャッシュ
+    pub roles: CachedList<Role>,
+    /// Todo状態のキャッシュ
+    pub todo_statuses: CachedList<TodoStatus>,
+}
+
+/// キャッシュしたリストと、キャッシュした時刻
+type CacheEntry<T> = Option<(Instant, Vec<T>)>;
+
+/// 有効期間付きでリストをキャッシュする。
+#[derive(Debug)]
+pub struct CachedList<T>(Arc<RwLock<CacheEntry<T>>>);
+
+impl<T> Clone for CachedList<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Default for CachedList<T> {
+    fn default() -> Self {
+        Self(Arc::new(RwLock::new(None)))
+    }
+}
+
+impl<T> CachedList<T>
+where
+    T: Clone,
+{
+    /// キャッシュしたリストを返す。
+    ///
+    /// キャッシュしていない場合、またはキャッシュの有効期間が過ぎている場合は、`load`でリストを取得してキャッシュする。
+    /// 有効期間が0の場合は、キャッシュせずに常に`load`でリストを取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `ttl`: キャッシュの有効期間
+    /// * `load`: リストを取得する関数
+    pub async fn get_or_try_load<F, Fut>(&self, ttl: Duration, load: F) -> DomainResult<Vec<T>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = DomainResult<Vec<T>>>,
+    {
+        if ttl.is_zero() {
+            return load().await;
+        }
+        let cached = self
+            .0
+            .read()
+            .await
+            .as_ref()
+            .filter(|(cached_at, _)| cached_at.elapsed() < ttl)
+            .map(|(_, entities)| entities.clone());
+        if let Some(entities) = cached {
+            return Ok(entities);
+        }
+        let entities = load().await?;
+        *self.0.write().await = Some((Instant::now(), entities.clone()));
+        Ok(entities)
+    }
+
+    /// キャッシュを無効にする。
+    pub async fn invalidate(&self) {
+        *self.0.write().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    async fn load(counter: &AtomicUsize) -> DomainResult<Vec<usize>> {
+        Ok(vec![counter.fetch_add(1, Ordering::SeqCst)])
+    }
+
+    #[tokio::test]
+    async fn cached_list_loads_once_within_ttl() {
+        let cache = CachedList::default();
+        let counter = AtomicUsize::new(0);
+        let ttl = Duration::from_secs(60);
+
+        let first = cache.get_or_try_load(ttl, || load(&counter)).await.unwrap();
+        let second = cache.get_or_try_load(ttl, || load(&counter)).await.unwrap();
+        assert_eq!(first, vec![0]);
+        assert_eq!(second, vec![0]);
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn cached_list_reloads_after_invalidation() {
+        let cache = CachedList::default();
+        let counter = AtomicUsize::new(0);
+        let ttl = Duration::from_secs(60);
+
+        cache.get_or_try_load(ttl, || load(&counter)).await.unwrap();
+        cache.invalidate().await;
+        let reloaded = cache.get_or_try_load(ttl, || load(&counter)).await.unwrap();
+        assert_eq!(reloaded, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn cached_list_does_not_cache_with_zero_ttl() {
+        let cache = CachedList::default();
+        let counter = AtomicUsize::new(0);
+
+        cache
+            .get_or_try_load(Duration::ZERO, || load(&counter))
+            .await
+            .unwrap();
+        cache
+            .get_or_try_load(Duration::ZERO, || load(&counter))
+            .await
+            .unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+}
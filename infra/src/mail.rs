@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport as _, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use secrecy::ExposeSecret as _;
+
+use domain::{DomainError, DomainErrorKind, DomainResult, models::Email};
+
+use crate::settings::{MailSettings, SmtpSettings};
+
+/// メール
+#[derive(Debug, Clone)]
+pub struct Mail {
+    /// 宛先
+    pub to: Email,
+    /// 件名
+    pub subject: String,
+    /// 本文
+    pub body: String,
+}
+
+/// メール送信者
+#[async_trait::async_trait]
+pub trait Mailer: std::fmt::Debug + Send + Sync {
+    /// メールを送信する。
+    async fn send(&self, mail: Mail) -> DomainResult<()>;
+
+    /// メールを宛先に届けられるかどうかを返す。
+    ///
+    /// 届けられない場合は、確認トークンをメールで送信する機能を提供できない。
+    fn delivers(&self) -> bool {
+        true
+    }
+}
+
+/// メールを送信せずに、ログに記録するメール送信者
+///
+/// SMTPサーバーを設定していない開発環境用の実装である。
+/// メールの本文には確認トークンなどの秘密情報が含まれるため、宛先と件名のみをデバッグレベルで記録する。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingMailer;
+
+#[async_trait::async_trait]
+impl Mailer for TracingMailer {
+    async fn send(&self, mail: Mail) -> DomainResult<()> {
+        tracing::debug!(
+            to = %mail.to,
+            subject = %mail.subject,
+            "The mail was logged instead of being sent"
+        );
+        Ok(())
+    }
+
+    fn delivers(&self) -> bool {
+        false
+    }
+}
+
+/// SMTPサーバーを経由してメールを送信するメール送信者
+#[derive(Debug, Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// SMTPサーバー設定から、メール送信者を構築する。
+    ///
+    /// SMTPサーバーには、最初のメールを送信するときに接続する。
+    pub fn new(settings: &SmtpSettings) -> anyhow::Result<Self> {
+        let builder = if settings.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host).with_context(
+                || format!("Failed to configure the SMTP server: {}", settings.host),
+            )?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+        };
+        let mut builder = builder.port(settings.port);
+        if let (Some(user), Some(password)) = (&settings.user, &settings.password) {
+            builder = builder.credentials(Credentials::new(
+                user.clone(),
+                password.expose_secret().to_string(),
+            ));
+        }
+        let from = settings
+            .from
+            .parse::<Mailbox>()
+            .with_context(|| format!("The sender of the mail is invalid: {}", settings.from))?;
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: Mail) -> DomainResult<()> {
+        let to = mail.to.0.parse::<Mailbox>().map_err(|e| DomainError {
+            kind: DomainErrorKind::Unexpected,
+            messages: vec!["The recipient of the mail is invalid".into()],
+            source: e.into(),
+        })?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(mail.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body)
+            .map_err(|e| DomainError {
+                kind: DomainErrorKind::Unexpected,
+                messages: vec!["Failed to build the mail".into()],
+                source: e.into(),
+            })?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| DomainError {
+                kind: DomainErrorKind::Unavailable,
+                messages: vec!["Failed to send the mail".into()],
+                source: e.into(),
+            })?;
+        Ok(())
+    }
+}
+
+/// メール設定から、メール送信者を作成する。
+///
+/// SMTPサーバーが設定されていない場合は、メールをログに記録するメール送信者を返す。
+pub fn create_mailer(settings: &MailSettings) -> anyhow::Result<Arc<dyn Mailer>> {
+    match &settings.smtp {
+        Some(smtp) => Ok(Arc::new(SmtpMailer::new(smtp)?)),
+        None => Ok(Arc::new(TracingMailer)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::SecretString;
+
+    use super::*;
+
+    fn smtp_settings() -> SmtpSettings {
+        SmtpSettings {
+            host: String::from("smtp.example.com"),
+            port: 587,
+            user: Some(String::from("todo")),
+            password: Some(SecretString::new("smtp-password".into())),
+            from: String::from("Rusty Todo <noreply@example.com>"),
+            starttls: true,
+        }
+    }
+
+    #[test]
+    fn create_mailer_returns_the_mailer_that_does_not_deliver_without_smtp() {
+        let mailer = create_mailer(&MailSettings::default()).unwrap();
+        assert!(!mailer.delivers());
+    }
+
+    #[test]
+    fn create_mailer_returns_the_smtp_mailer() {
+        let settings = MailSettings {
+            smtp: Some(smtp_settings()),
+        };
+        let mailer = create_mailer(&settings).unwrap();
+        assert!(mailer.delivers());
+    }
+
+    #[test]
+    fn smtp_mailer_rejects_the_invalid_sender() {
+        let settings = SmtpSettings {
+            from: String::from("not a mail address"),
+            ..smtp_settings()
+        };
+        assert!(SmtpMailer::new(&settings).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use secrecy::{ExposeSecret as _, SecretString};
+
+use domain::{
+    DomainResult,
+    models::{Email, UserId},
+    repositories::{
+        AuthTokenInfo, PendingEmail, TokenContent, TokenRepository, divide_auth_token_info,
+        divide_pending_email_value, generate_pending_email_key, generate_pending_email_value,
+    },
+};
+
+/// プロセス内のトークンリポジトリ
+///
+/// Redisの代わりに、キーと値、有効期限をプロセスのメモリに保持する。
+/// 有効期限が切れた値は、値を参照したときと、値を登録したときに削除する。
+/// プロセスを再起動するとすべてのトークンが失われるため、`lite`モードで1つのプロセスを起動する場合のみ使用すること。
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTokenRepository {
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+/// 有効期限付きの値
+#[derive(Debug, Clone)]
+struct Entry {
+    value: String,
+    expires_at: Instant,
+}
+
+impl Entry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at <= now
+    }
+}
+
+impl MemoryTokenRepository {
+    /// キーと値を保持するマップをロックする。
+    ///
+    /// 他のスレッドがロックを保持したままパニックした場合でも、マップの内容は一貫しているため、そのまま使用する。
+    fn entries(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 有効期限が切れた値を削除してから、キーと値を登録する。
+fn store(entries: &mut HashMap<String, Entry>, key: &str, value: &str, max_age: u64) {
+    let now = Instant::now();
+    entries.retain(|_, entry| !entry.is_expired(now));
+    entries.insert(
+        key.to_string(),
+        Entry {
+            value: value.to_string(),
+            expires_at: now + Duration::from_secs(max_age),
+        },
+    );
+}
+
+/// 有効期限が切れていない値を取得する。
+///
+/// 有効期限が切れている場合は、値を削除して`None`を返す。
+fn retrieve(entries: &mut HashMap<String, Entry>, key: &str) -> Option<Entry> {
+    let entry = entries.get(key)?;
+    if entry.is_expired(Instant::now()) {
+        entries.remove(key);
+        return None;
+    }
+    Some(entry.clone())
+}
+
+#[async_trait::async_trait]
+impl TokenRepository for MemoryTokenRepository {
+    /// アクセストークンとリフレッシュトークンを登録する。
+    async fn register_token_pair<'a>(
+        &self,
+        access_token_info: &AuthTokenInfo,
+        refresh_token_info: &AuthTokenInfo,
+    ) -> DomainResult<()> {
+        let mut entries = self.entries();
+        for token_info in [access_token_info, refresh_token_info] {
+            store(
+                &mut entries,
+                token_info.key.expose_secret(),
+                &token_info.value,
+                token_info.max_age,
+            );
+        }
+        Ok(())
+    }
+
+    /// 単独のトークンを登録する。
+    async fn register_token(&self, token_info: &AuthTokenInfo) -> DomainResult<()> {
+        store(
+            &mut self.entries(),
+            token_info.key.expose_secret(),
+            &token_info.value,
+            token_info.max_age,
+        );
+        Ok(())
+    }
+
+    /// トークンをハッシュ化した文字列からユーザーIDとトークンの種類を取得する。
+    async fn get_token_content(&self, key: &SecretString) -> DomainResult<Option<TokenContent>> {
+        retrieve(&mut self.entries(), key.expose_secret())
+            .map(|entry| divide_auth_token_info(&entry.value))
+            .transpose()
+    }
+
+    /// 認証情報の残りの生存期間（秒）を取得する。
+    async fn get_token_ttl(&self, key: &SecretString) -> DomainResult<Option<u64>> {
+        Ok(
+            retrieve(&mut self.entries(), key.expose_secret()).map(|entry| {
+                entry
+                    .expires_at
+                    .saturating_duration_since(Instant::now())
+                    .as_secs()
+            }),
+        )
+    }
+
+    /// 認証情報を削除する。
+    async fn delete_token_content(&self, key: &SecretString) -> DomainResult<()> {
+        self.entries().remove(key.expose_secret());
+        Ok(())
+    }
+
+    /// 確認待ちのEメールアドレスを登録する。
+    async fn register_pending_email(
+        &self,
+        user_id: UserId,
+        email: &Email,
+        token_info: &AuthTokenInfo,
+    ) -> DomainResult<()> {
+        let pending_key = generate_pending_email_key(user_id);
+        let mut entries = self.entries();
+        // 以前の確認トークンを無効化
+        if let Some(entry) = retrieve(&mut entries, &pending_key) {
+            let previous = divide_pending_email_value(&entry.value)?;
+            entries.remove(previous.token_key.expose_secret());
+        }
+        store(
+            &mut entries,
+            token_info.key.expose_secret(),
+            &token_info.value,
+            token_info.max_age,
+        );
+        store(
+            &mut entries,
+            &pending_key,
+            &generate_pending_email_value(email, &token_info.key),
+            token_info.max_age,
+        );
+        Ok(())
+    }
+
+    /// ユーザーの確認待ちのEメールアドレスを取得する。
+    async fn pending_email(&self, user_id: UserId) -> DomainResult<Option<PendingEmail>> {
+        retrieve(&mut self.entries(), &generate_pending_email_key(user_id))
+            .map(|entry| divide_pending_email_value(&entry.value))
+            .transpose()
+    }
+
+    /// ユーザーの確認待ちのEメールアドレスと、その確認トークンの認証情報を削除する。
+    async fn delete_pending_email(&self, user_id: UserId) -> DomainResult<()> {
+        let pending_key = generate_pending_email_key(user_id);
+        let mut entries = self.entries();
+        if let Some(entry) = entries.remove(&pending_key) {
+            let pending = divide_pending_email_value(&entry.value)?;
+            entries.remove(pending.token_key.expose_secret());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::repositories::{TokenType, generate_auth_token_info};
+
+    use super::*;
+
+    fn token_info(token: &str, max_age: u64) -> AuthTokenInfo {
+        generate_auth_token_info(
+            UserId::default(),
+            &SecretString::new(token.into()),
+            TokenType::Access,
+            max_age,
+        )
+    }
+
+    #[tokio::test]
+    async fn the_registered_token_can_be_retrieved_until_it_expires() {
+        let repo = MemoryTokenRepository::default();
+        let alive = token_info("alive-token", 60);
+        let expired = token_info("expired-token", 0);
+        repo.register_token(&alive).await.unwrap();
+        repo.register_token(&expired).await.unwrap();
+
+        let content = repo.get_token_content(&alive.key).await.unwrap().unwrap();
+        assert_eq!(content.token_type, TokenType::Access);
+        let ttl = repo.get_token_ttl(&alive.key).await.unwrap().unwrap();
+        assert!(ttl <= 60, "{ttl}");
+        assert!(
+            repo.get_token_content(&expired.key)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(repo.get_token_ttl(&expired.key).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn the_deleted_token_can_not_be_retrieved() {
+        let repo = MemoryTokenRepository::default();
+        let info = token_info("deleted-token", 60);
+        repo.register_token(&info).await.unwrap();
+        repo.delete_token_content(&info.key).await.unwrap();
+        assert!(repo.get_token_content(&info.key).await.unwrap().is_none());
+    }
+}
@@ -7,7 +7,10 @@ use argon2::{
 use secrecy::{ExposeSecret as _, SecretString};
 
 use domain::{
-    DomainError, DomainErrorKind, DomainResult, domain_error, models::PHCString,
+    DomainError, DomainErrorKind, DomainResult,
+    api::{CharacterClass, PasswordPolicy},
+    domain_error,
+    models::PHCString,
     starts_or_ends_with_whitespace,
 };
 
@@ -115,6 +118,25 @@ impl RawPassword {
     }
 }
 
+impl PasswordSettings {
+    /// クライアントに公開するパスワードポリシーを返す。
+    pub fn policy(&self) -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: self.min_length,
+            max_length: self.max_length,
+            required_character_classes: vec![
+                CharacterClass::Uppercase,
+                CharacterClass::Lowercase,
+                CharacterClass::Digit,
+                CharacterClass::Symbol,
+            ],
+            symbols: self.symbols.clone(),
+            max_same_chars: self.max_same_chars,
+            max_repeated_chars: self.max_repeated_chars,
+        }
+    }
+}
+
 /// パスワードをハッシュ化して、PHC文字列を生成する。
 ///
 /// # 引数
@@ -189,6 +211,46 @@ pub fn verify_password(
         .is_ok())
 }
 
+/// ブロッキング処理用のスレッドで、パスワードをハッシュ化して、PHC文字列を生成する。
+///
+/// パスワードのハッシュ化は、非同期ランタイムのワーカースレッドを長時間ブロックするため、ハンドラーからはこの関数を
+/// 呼び出すこと。引数は`create_hashed_password`と同じである。
+pub async fn hash_password_async(
+    settings: PasswordSettings,
+    raw_password: RawPassword,
+) -> DomainResult<PHCString> {
+    spawn_blocking(move || create_hashed_password(&settings, &raw_password)).await
+}
+
+/// ブロッキング処理用のスレッドで、パスワードを検証する。
+///
+/// パスワードの検証は、非同期ランタイムのワーカースレッドを長時間ブロックするため、ハンドラーからはこの関数を
+/// 呼び出すこと。引数は`verify_password`と同じである。
+pub async fn verify_password_async(
+    raw_password: RawPassword,
+    pepper: SecretString,
+    hashed_password: PHCString,
+) -> DomainResult<bool> {
+    spawn_blocking(move || verify_password(&raw_password, &pepper, &hashed_password)).await
+}
+
+/// ブロッキング処理用のスレッドで関数を実行する。
+///
+/// タスクがキャンセルされた場合やパニックした場合は、予期しないエラーを返す。
+async fn spawn_blocking<F, T>(f: F) -> DomainResult<T>
+where
+    F: FnOnce() -> DomainResult<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| DomainError {
+            kind: DomainErrorKind::Unexpected,
+            messages: vec![format!("Failed to join the password hashing task: {e}").into()],
+            source: anyhow::anyhow!(e),
+        })?
+}
+
 fn sprinkle_pepper(pepper: &SecretString, raw_password: &RawPassword) -> SecretString {
     let pepper = pepper.expose_secret();
     let password = raw_password.0.expose_secret();
@@ -282,6 +344,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn password_policy_does_not_expose_pepper_and_hash_parameters() {
+        let settings = password_settings();
+        let policy = settings.policy();
+        let value = serde_json::to_value(&policy).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "minLength": 8,
+                "maxLength": 32,
+                "requiredCharacterClasses": ["uppercase", "lowercase", "digit", "symbol"],
+                "symbols": "~!@#$%^&*()_-+={[}]|:;'<,>.?/",
+                "maxSameChars": 3,
+                "maxRepeatedChars": 2,
+            })
+        );
+        assert!(!value.to_string().contains(settings.pepper.expose_secret()));
+    }
+
     #[rstest::rstest]
     #[case("abcAbc123!@#", "!@#")]
     #[case("abcAbc123", "")]
@@ -347,4 +428,22 @@ mod tests {
         )?);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_hash_password_async_and_verify_async() -> anyhow::Result<()> {
+        let settings = password_settings();
+        let raw_password = RawPassword(SecretString::new("password123!".into()));
+        let hashed_password = hash_password_async(settings.clone(), raw_password.clone()).await?;
+        assert!(
+            verify_password_async(
+                raw_password,
+                settings.pepper.clone(),
+                hashed_password.clone()
+            )
+            .await?
+        );
+        let wrong_password = RawPassword(SecretString::new("password124!".into()));
+        assert!(!verify_password_async(wrong_password, settings.pepper, hashed_password).await?);
+        Ok(())
+    }
 }
@@ -0,0 +1,124 @@
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use domain::{
+    DomainError, DomainErrorKind, DomainResult,
+    models::{CommentBody, TodoComment, TodoCommentId, TodoId, UserId},
+    repositories::{TodoCommentListInput, TodoCommentRepository},
+};
+
+use super::{PgRepository, commit};
+
+pub type PgTodoCommentRepository = PgRepository<TodoComment>;
+
+#[async_trait::async_trait]
+impl TodoCommentRepository for PgTodoCommentRepository {
+    /// Todoのコメントを、作成日時の降順でリストする。
+    async fn list(&self, input: TodoCommentListInput) -> DomainResult<Vec<TodoComment>> {
+        sqlx::query_as!(
+            TodoCommentRow,
+            r#"
+            SELECT id, todo_id, user_id, body, created_at
+            FROM todo_comments
+            WHERE todo_id = $1
+            ORDER BY created_at DESC, id
+            LIMIT $2 OFFSET $3
+            "#,
+            input.todo_id.0,
+            input.page_size as i64,
+            input.offset() as i64
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(TodoComment::try_from)
+        .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// コメントを取得する。
+    async fn by_id(&self, id: TodoCommentId) -> DomainResult<Option<TodoComment>> {
+        let row = sqlx::query_as!(
+            TodoCommentRow,
+            r#"
+            SELECT id, todo_id, user_id, body, created_at
+            FROM todo_comments
+            WHERE id = $1
+            "#,
+            id.0
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(TodoComment::try_from).transpose()
+    }
+
+    /// Todoにコメントを登録する。
+    async fn create(
+        &self,
+        todo_id: TodoId,
+        user_id: UserId,
+        body: CommentBody,
+    ) -> DomainResult<TodoComment> {
+        let mut tx = self.begin().await?;
+        let row = sqlx::query_as!(
+            TodoCommentRow,
+            r#"
+            INSERT INTO todo_comments (todo_id, user_id, body)
+            VALUES ($1, $2, $3)
+            RETURNING id, todo_id, user_id, body, created_at
+            "#,
+            todo_id.0,
+            user_id.0,
+            body.0
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        TodoComment::try_from(row)
+    }
+
+    /// コメントを削除する。
+    async fn delete(&self, id: TodoCommentId) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query!(
+            r#"
+            DELETE FROM todo_comments
+            WHERE id = $1
+            "#,
+            id.0
+        )
+        .execute(&mut *tx)
+        .await?;
+        if query_result.rows_affected() == 0 {
+            let message = format!("Comment with id {} not found", id);
+            return Err(DomainError {
+                kind: DomainErrorKind::NotFound,
+                messages: vec![message.clone().into()],
+                source: anyhow::anyhow!(message),
+            });
+        }
+        commit(tx).await
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct TodoCommentRow {
+    id: Uuid,
+    todo_id: Uuid,
+    user_id: Uuid,
+    body: String,
+    created_at: OffsetDateTime,
+}
+
+impl TryFrom<TodoCommentRow> for TodoComment {
+    type Error = DomainError;
+
+    fn try_from(row: TodoCommentRow) -> Result<Self, Self::Error> {
+        Ok(TodoComment {
+            id: row.id.into(),
+            todo_id: row.todo_id.into(),
+            user_id: row.user_id.into(),
+            body: row.body.try_into()?,
+            created_at: row.created_at,
+        })
+    }
+}
@@ -0,0 +1,119 @@
+use time::Date;
+use uuid::Uuid;
+
+use domain::{
+    DomainError, DomainErrorKind, DomainResult, REPOSITORY_ERROR_MESSAGE,
+    models::{DigestTodo, UserDigest, UserId},
+    repositories::DailyDigestRepository,
+};
+
+use super::{PgRepository, commit};
+
+pub type PgDailyDigestRepository = PgRepository<UserDigest>;
+
+#[async_trait::async_trait]
+impl DailyDigestRepository for PgDailyDigestRepository {
+    /// 対象日の日次ダイジェストを登録する。
+    async fn upsert(&self, date: Date, digests: &[UserDigest]) -> DomainResult<()> {
+        let mut user_ids = Vec::with_capacity(digests.len());
+        let mut due_todays = Vec::with_capacity(digests.len());
+        let mut overdues = Vec::with_capacity(digests.len());
+        for digest in digests {
+            user_ids.push(digest.user_id.0);
+            due_todays.push(digest_todos_to_json(&digest.due_today)?);
+            overdues.push(digest_todos_to_json(&digest.overdue)?);
+        }
+        let mut tx = self.begin().await?;
+        // 対象のTodoがなくなったユーザーの対象日のダイジェストを削除
+        sqlx::query(
+            r#"
+            DELETE FROM daily_digests
+            WHERE digest_date = $1 AND user_id <> ALL($2::UUID[])
+            "#,
+        )
+        .bind(date)
+        .bind(&user_ids)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO daily_digests (user_id, digest_date, due_today, overdue)
+            SELECT user_id, $1, due_today, overdue
+            FROM UNNEST($2::UUID[], $3::JSONB[], $4::JSONB[]) AS d (user_id, due_today, overdue)
+            ON CONFLICT (user_id, digest_date) DO UPDATE
+            SET
+                due_today = EXCLUDED.due_today,
+                overdue = EXCLUDED.overdue
+            "#,
+        )
+        .bind(date)
+        .bind(&user_ids)
+        .bind(&due_todays)
+        .bind(&overdues)
+        .execute(&mut *tx)
+        .await?;
+        commit(tx).await
+    }
+
+    /// ユーザーの指定された対象日の日次ダイジェストを取得する。
+    async fn by_user_and_date(
+        &self,
+        user_id: UserId,
+        date: Date,
+    ) -> DomainResult<Option<UserDigest>> {
+        let row = sqlx::query_as!(
+            DailyDigestRow,
+            r#"
+            SELECT user_id, digest_date, due_today, overdue
+            FROM daily_digests
+            WHERE user_id = $1 AND digest_date = $2
+            "#,
+            user_id.0,
+            date
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(UserDigest::try_from).transpose()
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DailyDigestRow {
+    user_id: Uuid,
+    digest_date: Date,
+    due_today: serde_json::Value,
+    overdue: serde_json::Value,
+}
+
+impl TryFrom<DailyDigestRow> for UserDigest {
+    type Error = DomainError;
+
+    fn try_from(row: DailyDigestRow) -> Result<Self, Self::Error> {
+        Ok(UserDigest {
+            user_id: UserId::from(row.user_id),
+            digest_date: row.digest_date,
+            due_today: digest_todos_from_json(row.due_today)?,
+            overdue: digest_todos_from_json(row.overdue)?,
+        })
+    }
+}
+
+/// ダイジェストに含めるTodoを、データベースに保存するJSONにシリアライズする。
+fn digest_todos_to_json(todos: &[DigestTodo]) -> DomainResult<serde_json::Value> {
+    serde_json::to_value(todos).map_err(|e| DomainError {
+        kind: DomainErrorKind::Unexpected,
+        messages: vec!["Failed to serialize the daily digest".into()],
+        source: e.into(),
+    })
+}
+
+/// データベースに保存されたダイジェストに含めるTodoをデシリアライズする。
+///
+/// 保存されたJSONが不正な場合は、リポジトリエラーを返す。
+fn digest_todos_from_json(value: serde_json::Value) -> DomainResult<Vec<DigestTodo>> {
+    serde_json::from_value(value).map_err(|e| DomainError {
+        kind: DomainErrorKind::Repository,
+        messages: vec![REPOSITORY_ERROR_MESSAGE.into()],
+        source: anyhow::Error::new(e).context("Stored daily digest is malformed"),
+    })
+}
@@ -10,10 +10,9 @@ use domain::{
     repositories::LookupRepository,
 };
 
-use crate::postgres::repositories::repository_error;
-
 macro_rules! pg_lookup_repository {
     ($name:ident, $entity:ty, $code:ty, $code_ty: ty, $row:ty, $table:literal) => {
+        #[derive(Debug, Clone)]
         pub struct $name {
             pub pool: PgPool,
         }
@@ -33,8 +32,7 @@ macro_rules! pg_lookup_repository {
                     $table
                 ))
                 .fetch_all(&self.pool)
-                .await
-                .map_err(repository_error)?
+                .await?
                 .into_iter()
                 .map(<$entity>::try_from)
                 .collect::<Result<Vec<_>, _>>()
@@ -51,8 +49,7 @@ macro_rules! pg_lookup_repository {
                 ))
                 .bind(*code as $code_ty)
                 .fetch_optional(&self.pool)
-                .await
-                .map_err(repository_error)?
+                .await?
                 .map(<$entity>::try_from)
                 .transpose()
             }
@@ -72,7 +69,7 @@ pg_lookup_repository!(
 
 #[derive(Debug, sqlx::FromRow)]
 struct RoleRow {
-    code: i16,
+    code: RoleCode,
     name: String,
     description: Option<String>,
     display_order: i16,
@@ -85,10 +82,10 @@ impl TryFrom<RoleRow> for Role {
 
     fn try_from(row: RoleRow) -> Result<Self, Self::Error> {
         Ok(Role {
-            code: RoleCode::try_from(row.code)?,
+            code: row.code,
             name: RoleName::new(row.name)?,
             description: row.description.map(Description::new).transpose()?,
-            display_order: DisplayOrder::new(row.display_order)?,
+            display_order: DisplayOrder(row.display_order),
             created_at: row.created_at,
             updated_at: row.updated_at,
         })
@@ -97,7 +94,7 @@ impl TryFrom<RoleRow> for Role {
 
 #[derive(Debug, sqlx::FromRow)]
 struct TodoStatusRow {
-    code: i16,
+    code: TodoStatusCode,
     name: String,
     description: Option<String>,
     display_order: i16,
@@ -110,10 +107,10 @@ impl TryFrom<TodoStatusRow> for TodoStatus {
 
     fn try_from(row: TodoStatusRow) -> Result<Self, Self::Error> {
         Ok(TodoStatus {
-            code: TodoStatusCode::try_from(row.code)?,
+            code: row.code,
             name: TodoStatusName::new(row.name)?,
             description: row.description.map(Description::new).transpose()?,
-            display_order: DisplayOrder::new(row.display_order)?,
+            display_order: DisplayOrder(row.display_order),
             created_at: row.created_at,
             updated_at: row.updated_at,
         })
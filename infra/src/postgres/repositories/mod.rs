@@ -1,7 +1,11 @@
+mod comment;
+mod digest;
 mod lookup;
 mod todo;
 mod user;
 
+pub use comment::*;
+pub use digest::*;
 pub use lookup::*;
 pub use todo::*;
 pub use user::*;
@@ -10,13 +14,13 @@ use std::marker::PhantomData;
 
 use sqlx::{PgPool, Postgres, Transaction};
 
-use domain::{DomainError, DomainErrorKind, DomainResult};
+use domain::{DomainError, DomainResult};
 
 /// PostgreSQLトランザクション
 pub type PgTransaction<'a> = Transaction<'a, Postgres>;
 
 /// PostgreSQLリポジトリ
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct PgRepository<T> {
     pool: PgPool,
     _marker: PhantomData<T>,
@@ -36,7 +40,7 @@ impl<T> PgRepository<T> {
     ///
     /// トランザクション
     pub async fn begin(&self) -> DomainResult<PgTransaction<'_>> {
-        self.pool.begin().await.map_err(repository_error)
+        self.pool.begin().await.map_err(DomainError::from)
     }
 }
 
@@ -46,13 +50,5 @@ impl<T> PgRepository<T> {
 ///
 /// * `tx`: トランザクション
 pub async fn commit(tx: PgTransaction<'_>) -> DomainResult<()> {
-    tx.commit().await.map_err(repository_error)
-}
-
-fn repository_error(e: sqlx::Error) -> DomainError {
-    DomainError {
-        kind: DomainErrorKind::Repository,
-        messages: vec![format!("{e}").into()],
-        source: e.into(),
-    }
+    tx.commit().await.map_err(DomainError::from)
 }
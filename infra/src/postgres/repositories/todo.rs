@@ -1,16 +1,21 @@
 use sqlx::{PgTransaction, Postgres};
-use time::{Date, OffsetDateTime};
+use time::{Date, OffsetDateTime, format_description::well_known::Rfc3339};
 use uuid::Uuid;
 
 use domain::{
-    DomainError, DomainErrorKind, DomainResult,
+    DomainError, DomainErrorKind, DomainResult, domain_error,
     models::{
-        Role, Todo, TodoId, TodoStatus, TodoStatusCode, User, UserId, primitives::DisplayOrder,
+        COMPLETABLE_TODO_STATUS_CODES, DigestTodo, LockReason, Role, RoleCode, Todo, TodoId,
+        TodoStatus, TodoStatusCode, TodoTitle, User, UserDigest, UserId, primitives::DisplayOrder,
+    },
+    repositories::{
+        ArchivedFilter, TodoCreateInput, TodoImportInput, TodoListCursor, TodoListInput,
+        TodoListSort, TodoListView, TodoRepository, TodoUpdateInput,
     },
-    repositories::{TodoCreateInput, TodoListInput, TodoRepository, TodoUpdateInput},
 };
+use utils::time::DATE_FORMAT;
 
-use super::{PgRepository, commit, repository_error};
+use super::{PgRepository, commit};
 
 pub type PgTodoRepository = PgRepository<Todo>;
 
@@ -19,30 +24,19 @@ impl TodoRepository for PgTodoRepository {
     /// Todoをリストする。
     async fn list(&self, input: TodoListInput) -> DomainResult<Vec<Todo>> {
         let sql = format!(
-            r#"
-            SELECT
-                t.id, t.user_id,
-                u.family_name, u.given_name, u.email,
-                u.role_code, r.name role_name, r.description role_description,r.display_order role_display_order,
-                r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at user_created_at, u.updated_at user_updated_at,
-                t.title, t.description,
-                t.todo_status_code, ts.name todo_status_name, ts.description todo_status_description,
-                ts.display_order todo_status_display_order, ts.created_at todo_status_created_at, ts.updated_at todo_status_updated_at,
-                t.due_date, t.completed_at, t.archived, t.created_at, t.updated_at
-            FROM todos t
-            INNER JOIN users u ON t.user_id = u.id
-            INNER JOIN roles r ON u.role_code = r.code
-            INNER JOIN todo_statuses ts ON t.todo_status_code = ts.code
-            {}
-            ORDER BY t.due_date NULLS LAST, t.updated_at DESC, t.created_at DESC
-            "#,
-            list_where_clause(&input, "t")
+            "{}{}\n{}{}",
+            include_str!("../queries/todo_list.sql"),
+            list_where_clause(&input, "t"),
+            list_order_by_clause(input.sort, input.view, "t"),
+            list_limit_clause(&input)
         );
-        sqlx::query_as::<Postgres, TodoRow>(sql.as_str())
+        let mut query = sqlx::query_as::<Postgres, TodoRow>(sql.as_str());
+        if let Some(keyword) = &input.keyword {
+            query = query.bind(keyword_like_pattern(keyword));
+        }
+        query
             .fetch_all(&self.pool)
-            .await
-            .map_err(repository_error)?
+            .await?
             .into_iter()
             .map(Todo::try_from)
             .collect::<Result<Vec<_>, _>>()
@@ -50,62 +44,18 @@ impl TodoRepository for PgTodoRepository {
 
     /// Todoを取得する。
     async fn by_id(&self, id: TodoId) -> DomainResult<Option<Todo>> {
-        let row = sqlx::query_as!(
-            TodoRow,
-            r#"
-            SELECT
-                t.id, t.user_id,
-                u.family_name, u.given_name, u.email,
-                u.role_code, r.name role_name, r.description role_description,r.display_order role_display_order,
-                r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at user_created_at, u.updated_at user_updated_at,
-                t.title, t.description,
-                t.todo_status_code, ts.name todo_status_name, ts.description todo_status_description,
-                ts.display_order todo_status_display_order, ts.created_at todo_status_created_at, ts.updated_at todo_status_updated_at,
-                t.due_date, t.completed_at, t.archived, t.created_at, t.updated_at
-            FROM todos t
-            INNER JOIN users u ON t.user_id = u.id
-            INNER JOIN roles r ON u.role_code = r.code
-            INNER JOIN todo_statuses ts ON t.todo_status_code = ts.code
-            WHERE t.id = $1
-            "#,
-            id.0
-        )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(repository_error)?;
+        let row = sqlx::query_file_as!(TodoRow, "src/postgres/queries/todo_by_id.sql", id.0)
+            .fetch_optional(&self.pool)
+            .await?;
         row.map(Todo::try_from).transpose()
     }
 
     // Todoを新規作成する。
     async fn create(&self, user_id: UserId, input: TodoCreateInput) -> DomainResult<Todo> {
         let mut tx = self.begin().await?;
-        let row = sqlx::query_as!(
+        let row = sqlx::query_file_as!(
             TodoRow,
-            r#"
-            WITH inserted AS (
-                INSERT INTO todos (
-                    user_id, title, description, due_date, completed_at, created_at, updated_at
-                ) VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
-                RETURNING
-                    id, user_id, title, description, todo_status_code,
-                    due_date, completed_at, archived, created_at, updated_at
-            )
-            SELECT
-                t.id, t.user_id,
-                u.family_name, u.given_name, u.email,
-                u.role_code, r.name role_name, r.description role_description,r.display_order role_display_order,
-                r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at user_created_at, u.updated_at user_updated_at,
-                t.title, t.description,
-                t.todo_status_code, ts.name todo_status_name, ts.description todo_status_description,
-                ts.display_order todo_status_display_order, ts.created_at todo_status_created_at, ts.updated_at todo_status_updated_at,
-                t.due_date, t.completed_at, t.archived, t.created_at, t.updated_at
-            FROM inserted t
-            INNER JOIN users u ON t.user_id = u.id
-            INNER JOIN roles r ON u.role_code = r.code
-            INNER JOIN todo_statuses ts ON t.todo_status_code = ts.code
-            "#,
+            "src/postgres/queries/todo_create.sql",
             user_id.0,
             input.title.0,
             input.description.map(|d| d.0),
@@ -113,54 +63,51 @@ impl TodoRepository for PgTodoRepository {
             None::<OffsetDateTime> // completed_at is None for new todos
         )
         .fetch_one(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         todo_commit(tx, row).await
     }
 
+    /// ユーザーの完了しておらず、アーカイブされていないTodoのうち、タイトルが大文字と小文字を区別せずに
+    /// 一致するTodoをリストする。
+    async fn find_active_by_title(
+        &self,
+        user_id: UserId,
+        title: &TodoTitle,
+    ) -> DomainResult<Vec<Todo>> {
+        sqlx::query_file_as!(
+            TodoRow,
+            "src/postgres/queries/todo_find_active_by_title.sql",
+            user_id.0,
+            TodoStatusCode::Completed as i16,
+            title.0
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(Todo::try_from)
+        .collect::<Result<Vec<_>, _>>()
+    }
+
     /// Todoを更新する。
+    ///
+    /// 説明と完了予定日は、`Some(None)`が指定された場合に消去する。
     async fn update(&self, id: TodoId, todo: TodoUpdateInput) -> DomainResult<Todo> {
+        let update_description = todo.description.is_some();
+        let update_due_date = todo.due_date.is_some();
         let mut tx = self.begin().await?;
-        let row = sqlx::query_as!(
+        let row = sqlx::query_file_as!(
             TodoRow,
-            r#"
-            WITH updated AS (
-                UPDATE todos
-                SET
-                    title = COALESCE($1, title),
-                    description = COALESCE($2, description),
-                    todo_status_code = COALESCE($3, todo_status_code),
-                    due_date = COALESCE($4, due_date),
-                    updated_at = CURRENT_TIMESTAMP
-                WHERE id = $5
-                RETURNING
-                    id, user_id, title, description, todo_status_code,
-                    due_date, completed_at, archived, created_at, updated_at
-            )
-            SELECT
-                t.id, t.user_id,
-                u.family_name, u.given_name, u.email,
-                u.role_code, r.name role_name, r.description role_description,r.display_order role_display_order,
-                r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at user_created_at, u.updated_at user_updated_at,
-                t.title, t.description,
-                t.todo_status_code, ts.name todo_status_name, ts.description todo_status_description,
-                ts.display_order todo_status_display_order, ts.created_at todo_status_created_at, ts.updated_at todo_status_updated_at,
-                t.due_date, t.completed_at, t.archived, t.created_at, t.updated_at
-            FROM updated t
-            INNER JOIN users u ON t.user_id = u.id
-            INNER JOIN roles r ON u.role_code = r.code
-            INNER JOIN todo_statuses ts ON t.todo_status_code = ts.code
-            "#,
+            "src/postgres/queries/todo_update.sql",
             todo.title.map(|t| t.0),
-            todo.description.map(|d| d.0),
+            todo.description.flatten().map(|d| d.0),
             todo.status_code.map(|c| c as i16),
-            todo.due_date.map(|d| d),
-            id.0
+            todo.due_date.flatten(),
+            id.0,
+            update_description,
+            update_due_date
         )
         .fetch_optional(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match row {
             Some(row) => todo_commit(tx, row).await,
             None => todo_not_found(id),
@@ -168,136 +115,62 @@ impl TodoRepository for PgTodoRepository {
     }
 
     /// Todoを完了する。
+    ///
+    /// 同時に完了されることを防ぐため、未着手または進行中で、アーカイブされていないTodoのみを更新する。
     async fn complete(&self, id: TodoId) -> DomainResult<Todo> {
         let mut tx = self.begin().await?;
-        let row = sqlx::query_as!(
+        let completable = COMPLETABLE_TODO_STATUS_CODES.map(|code| code as i16);
+        let row = sqlx::query_file_as!(
             TodoRow,
-            r#"
-            WITH updated AS (
-                UPDATE todos
-                SET
-                    todo_status_code = $1,
-                    completed_at = CURRENT_TIMESTAMP,
-                    updated_at = CURRENT_TIMESTAMP
-                WHERE
-                    id = $2
-                RETURNING
-                    id, user_id, title, description, todo_status_code,
-                    due_date, completed_at, archived, created_at, updated_at
-            )
-            SELECT
-                t.id, t.user_id,
-                u.family_name, u.given_name, u.email,
-                u.role_code, r.name role_name, r.description role_description,r.display_order role_display_order,
-                r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at user_created_at, u.updated_at user_updated_at,
-                t.title, t.description,
-                t.todo_status_code, ts.name todo_status_name, ts.description todo_status_description,
-                ts.display_order todo_status_display_order, ts.created_at todo_status_created_at, ts.updated_at todo_status_updated_at,
-                t.due_date, t.completed_at, t.archived, t.created_at, t.updated_at
-            FROM updated t
-            INNER JOIN users u ON t.user_id = u.id
-            INNER JOIN roles r ON u.role_code = r.code
-            INNER JOIN todo_statuses ts ON t.todo_status_code = ts.code
-            "#,
+            "src/postgres/queries/todo_complete.sql",
             TodoStatusCode::Completed as i16,
-            id.0
+            id.0,
+            &completable[..]
         )
         .fetch_optional(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match row {
             Some(row) => todo_commit(tx, row).await,
-            None => todo_not_found(id),
+            None => todo_not_updated(tx, id, Todo::ensure_completable).await,
         }
     }
 
     /// 完了状態のTodoを他の状態に変更する。
+    ///
+    /// 同時に再開されることを防ぐため、完了していて、アーカイブされていないTodoのみを更新する。
     async fn reopen(&self, id: TodoId, status: TodoStatusCode) -> DomainResult<Todo> {
         let mut tx = self.begin().await?;
-        let row = sqlx::query_as!(
+        let row = sqlx::query_file_as!(
             TodoRow,
-            r#"
-            WITH updated AS (
-                UPDATE todos
-                SET
-                    todo_status_code = $1,
-                    completed_at = NULL,
-                    updated_at = CURRENT_TIMESTAMP
-                WHERE
-                    id = $2
-                RETURNING
-                    id, user_id, title, description, todo_status_code,
-                    due_date, completed_at, archived, created_at, updated_at
-            )
-            SELECT
-                t.id, t.user_id,
-                u.family_name, u.given_name, u.email,
-                u.role_code, r.name role_name, r.description role_description,r.display_order role_display_order,
-                r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at user_created_at, u.updated_at user_updated_at,
-                t.title, t.description,
-                t.todo_status_code, ts.name todo_status_name, ts.description todo_status_description,
-                ts.display_order todo_status_display_order, ts.created_at todo_status_created_at, ts.updated_at todo_status_updated_at,
-                t.due_date, t.completed_at, t.archived, t.created_at, t.updated_at
-            FROM updated t
-            INNER JOIN users u ON t.user_id = u.id
-            INNER JOIN roles r ON u.role_code = r.code
-            INNER JOIN todo_statuses ts ON t.todo_status_code = ts.code
-            "#,
+            "src/postgres/queries/todo_reopen.sql",
             status as i16,
-            id.0
+            id.0,
+            TodoStatusCode::Completed as i16
         )
         .fetch_optional(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match row {
             Some(row) => todo_commit(tx, row).await,
-            None => todo_not_found(id),
+            None => todo_not_updated(tx, id, Todo::ensure_reopenable).await,
         }
     }
 
     /// Todoをアーカイブする。
+    ///
+    /// アーカイブ状態が指定された状態と異なるTodoのみを更新する。
     async fn archive(&self, id: TodoId, archived: bool) -> DomainResult<Todo> {
         let mut tx = self.begin().await?;
-        let row = sqlx::query_as!(
+        let row = sqlx::query_file_as!(
             TodoRow,
-            r#"
-            WITH updated AS (
-                UPDATE todos
-                SET
-                    archived = $1,
-                    updated_at = CURRENT_TIMESTAMP
-                WHERE
-                    id = $2
-                RETURNING
-                    id, user_id, title, description, todo_status_code,
-                    due_date, completed_at, archived, created_at, updated_at
-            )
-            SELECT
-                t.id, t.user_id,
-                u.family_name, u.given_name, u.email,
-                u.role_code, r.name role_name, r.description role_description,r.display_order role_display_order,
-                r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at user_created_at, u.updated_at user_updated_at,
-                t.title, t.description,
-                t.todo_status_code, ts.name todo_status_name, ts.description todo_status_description,
-                ts.display_order todo_status_display_order, ts.created_at todo_status_created_at, ts.updated_at todo_status_updated_at,
-                t.due_date, t.completed_at, t.archived, t.created_at, t.updated_at
-            FROM updated t
-            INNER JOIN users u ON t.user_id = u.id
-            INNER JOIN roles r ON u.role_code = r.code
-            INNER JOIN todo_statuses ts ON t.todo_status_code = ts.code
-            "#,
+            "src/postgres/queries/todo_archive.sql",
             archived,
             id.0
         )
         .fetch_optional(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match row {
             Some(row) => todo_commit(tx, row).await,
-            None => todo_not_found(id),
+            None => todo_not_updated(tx, id, |todo| todo.ensure_archivable(archived)).await,
         }
     }
 
@@ -312,8 +185,7 @@ impl TodoRepository for PgTodoRepository {
             id.0
         )
         .execute(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match query_result.rows_affected() {
             0 => return todo_not_found(id),
             _ => {
@@ -322,15 +194,172 @@ impl TodoRepository for PgTodoRepository {
             }
         }
     }
+
+    /// ユーザーのTodoの所有者を、他のユーザーに変更する。
+    async fn transfer_ownership(
+        &self,
+        from: UserId,
+        to: UserId,
+        include_completed: bool,
+    ) -> DomainResult<u64> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query!(
+            r#"
+            UPDATE todos
+            SET
+                user_id = $2
+            WHERE
+                user_id = $1
+                AND ($3 OR todo_status_code <> $4)
+            "#,
+            from.0,
+            to.0,
+            include_completed,
+            TodoStatusCode::Completed as i16
+        )
+        .execute(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        Ok(query_result.rows_affected())
+    }
+
+    /// アーカイブされたTodoを含めて、ユーザーのすべてのTodoをリストする。
+    async fn list_all_including_archived(&self, user_id: UserId) -> DomainResult<Vec<Todo>> {
+        sqlx::query_file_as!(
+            TodoRow,
+            "src/postgres/queries/todo_list_all_including_archived.sql",
+            user_id.0
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(Todo::try_from)
+        .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// すべてのユーザーについて、指定された日付の日次ダイジェストを作成する。
+    async fn due_digest(&self, date: Date) -> DomainResult<Vec<UserDigest>> {
+        let completable = COMPLETABLE_TODO_STATUS_CODES.map(|code| code as i16);
+        let rows = sqlx::query_as!(
+            DigestTodoRow,
+            r#"
+            SELECT t.user_id, t.id, t.title, t.due_date "due_date!"
+            FROM todos t
+            INNER JOIN users u ON t.user_id = u.id
+            WHERE
+                u.active
+                AND NOT t.archived
+                AND t.todo_status_code = ANY($1)
+                AND t.due_date <= $2
+            ORDER BY t.user_id, t.due_date, t.created_at, t.id
+            "#,
+            &completable[..],
+            date
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        // ユーザーIDの順に並んでいるため、連続する行を同じユーザーのダイジェストにまとめる
+        let mut digests: Vec<UserDigest> = vec![];
+        for row in rows {
+            let user_id = UserId::from(row.user_id);
+            if digests
+                .last()
+                .is_none_or(|digest| digest.user_id != user_id)
+            {
+                digests.push(UserDigest::new(user_id, date));
+            }
+            digests.last_mut().unwrap().push(DigestTodo {
+                id: TodoId::from(row.id),
+                title: TodoTitle::new(row.title)?,
+                due_date: row.due_date,
+            });
+        }
+        Ok(digests)
+    }
+
+    /// Todoを一括で登録する。
+    async fn import(&self, user_id: UserId, inputs: Vec<TodoImportInput>) -> DomainResult<u64> {
+        if inputs.is_empty() {
+            return Ok(0);
+        }
+        let mut titles = Vec::with_capacity(inputs.len());
+        let mut descriptions = Vec::with_capacity(inputs.len());
+        let mut status_codes = Vec::with_capacity(inputs.len());
+        let mut due_dates = Vec::with_capacity(inputs.len());
+        let mut completed_ats = Vec::with_capacity(inputs.len());
+        let mut archives = Vec::with_capacity(inputs.len());
+        let mut created_ats = Vec::with_capacity(inputs.len());
+        let mut updated_ats = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            titles.push(input.title.0);
+            descriptions.push(input.description.map(|d| d.0));
+            status_codes.push(input.status_code as i16);
+            due_dates.push(input.due_date);
+            completed_ats.push(input.completed_at);
+            archives.push(input.archived);
+            created_ats.push(input.created_at);
+            updated_ats.push(input.updated_at);
+        }
+        let mut tx = self.begin().await?;
+        // 同じユーザーへのインポートが同時に実行されたときに、同じTodoを重複して登録しないように、
+        // ユーザーの行をロックしてインポートを直列化
+        sqlx::query!(
+            r#"
+            SELECT id FROM users WHERE id = $1 FOR UPDATE
+            "#,
+            user_id.0
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        // タイトルと完了予定日が同じTodoが既に存在する場合は登録しない
+        let query_result = sqlx::query(
+            r#"
+            INSERT INTO todos (
+                user_id, title, description, todo_status_code,
+                due_date, completed_at, archived, created_at, updated_at
+            )
+            SELECT $1::UUID, i.* FROM UNNEST(
+                $2::TEXT[], $3::TEXT[], $4::SMALLINT[],
+                $5::DATE[], $6::TIMESTAMPTZ[], $7::BOOLEAN[], $8::TIMESTAMPTZ[], $9::TIMESTAMPTZ[]
+            ) AS i (
+                title, description, todo_status_code,
+                due_date, completed_at, archived, created_at, updated_at
+            )
+            WHERE NOT EXISTS (
+                SELECT 1
+                FROM todos t
+                WHERE
+                    t.user_id = $1
+                    AND t.title = i.title
+                    AND t.due_date IS NOT DISTINCT FROM i.due_date
+            )
+            "#,
+        )
+        .bind(user_id.0)
+        .bind(&titles)
+        .bind(&descriptions)
+        .bind(&status_codes)
+        .bind(&due_dates)
+        .bind(&completed_ats)
+        .bind(&archives)
+        .bind(&created_ats)
+        .bind(&updated_ats)
+        .execute(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        Ok(query_result.rows_affected())
+    }
 }
 
+/// Todoをリストするときの条件を示すWHERE句を生成する。
+///
+/// キーワードはSQLに埋め込まず、`$1`にバインドする。バインドする値は`keyword_like_pattern`で生成すること。
 fn list_where_clause(input: &TodoListInput, todos_table: &str) -> String {
     let mut condition = format!("WHERE {}.user_id = '{}'", todos_table, input.user_id);
     if input.keyword.is_some() {
         condition.push_str(&format!(
-            " AND ({0}.title ILIKE '%{1}%' OR {0}.description ILIKE '%{1}%')",
+            " AND ({0}.title ILIKE $1 ESCAPE '\\' OR {0}.description ILIKE $1 ESCAPE '\\')",
             todos_table,
-            input.keyword.as_ref().unwrap()
         ));
     }
     if input.filter.is_some() {
@@ -341,8 +370,9 @@ fn list_where_clause(input: &TodoListInput, todos_table: &str) -> String {
             .sql(&format!("{}.due_date", todos_table));
         condition.push_str(&format!(" AND {due_date_condition}"));
     }
-    if let Some(statuses) = &input.statuses {
-        condition.push_str(&format!(
+    // 状態コードが指定されている場合は、表示範囲より状態コードを優先
+    match (&input.statuses, input.view) {
+        (Some(statuses), _) => condition.push_str(&format!(
             " AND {}.todo_status_code IN ({})",
             todos_table,
             statuses
@@ -350,19 +380,190 @@ fn list_where_clause(input: &TodoListInput, todos_table: &str) -> String {
                 .map(|s| (*s as i16).to_string())
                 .collect::<Vec<_>>()
                 .join(", ")
-        ));
+        )),
+        (None, TodoListView::Active) => condition.push_str(&format!(
+            " AND {}.todo_status_code <> {}",
+            todos_table,
+            TodoStatusCode::Completed as i16
+        )),
+        (None, TodoListView::Completed) => condition.push_str(&format!(
+            " AND {}.todo_status_code = {}",
+            todos_table,
+            TodoStatusCode::Completed as i16
+        )),
+        (None, TodoListView::All) => {}
     }
-    if let Some(archived) = input.archived {
+    match input.archived {
+        ArchivedFilter::Active => {
+            condition.push_str(&format!(" AND {todos_table}.archived = FALSE"))
+        }
+        ArchivedFilter::Archived => {
+            condition.push_str(&format!(" AND {todos_table}.archived = TRUE"))
+        }
+        ArchivedFilter::All => {}
+    }
+    if let Some(cursor) = input
+        .keyset
+        .as_ref()
+        .and_then(|keyset| keyset.after.as_ref())
+    {
         condition.push_str(&format!(
-            " AND {}.archived = {}",
-            todos_table,
-            if archived { "TRUE" } else { "FALSE" },
+            " AND {}",
+            list_keyset_condition(cursor, input.sort, input.view, todos_table)
         ));
     }
     condition.push(' ');
     condition
 }
 
+/// カーソルが示すTodoより後に並ぶTodoを絞り込む条件を生成する。
+///
+/// `list_order_by_clause`と同じ順序で並び順のキーを比較して、`(k1 > c1) OR (k1 = c1 AND k2 > c2) OR ...`の
+/// 形の条件を生成する。昇順と降順が混在して、完了予定日がないTodoを最後に並べるため、行値の比較は使用しない。
+/// カーソルの値は型が検証されているため、SQLに直接埋め込む。
+fn list_keyset_condition(
+    cursor: &TodoListCursor,
+    sort: TodoListSort,
+    view: TodoListView,
+    todos_table: &str,
+) -> String {
+    // 並び順のキーごとに、カーソルより後に並ぶ条件と、カーソルと等しい条件を格納
+    let mut keys: Vec<(String, String)> = vec![];
+    if view == TodoListView::All {
+        // 完了していないTodoを、完了したTodoより前に並べる
+        let completed = format!(
+            "({}.todo_status_code = {})",
+            todos_table,
+            TodoStatusCode::Completed as i16
+        );
+        let after = match cursor.completed {
+            true => String::from("FALSE"),
+            false => completed.clone(),
+        };
+        keys.push((after, format!("{} = {}", completed, cursor.completed)));
+    }
+    let due_date = match cursor.due_date {
+        Some(due_date) => {
+            let due_date = due_date.format(&DATE_FORMAT).unwrap();
+            (
+                format!(
+                    "({0}.due_date > '{1}' OR {0}.due_date IS NULL)",
+                    todos_table, due_date
+                ),
+                format!("{}.due_date = '{}'", todos_table, due_date),
+            )
+        }
+        // 完了予定日がないTodoは最後に並ぶ
+        None => (
+            String::from("FALSE"),
+            format!("{}.due_date IS NULL", todos_table),
+        ),
+    };
+    let descending = |column: &str, value: OffsetDateTime| {
+        let value = value.format(&Rfc3339).unwrap();
+        (
+            format!("{}.{} < '{}'::timestamptz", todos_table, column, value),
+            format!("{}.{} = '{}'::timestamptz", todos_table, column, value),
+        )
+    };
+    match sort {
+        TodoListSort::DueDate => {
+            keys.push(due_date);
+            keys.push(descending("updated_at", cursor.updated_at));
+            keys.push(descending("created_at", cursor.created_at));
+        }
+        TodoListSort::CreatedAt => keys.push(descending("created_at", cursor.created_at)),
+        TodoListSort::UpdatedAt => {
+            keys.push(descending("updated_at", cursor.updated_at));
+            keys.push(descending("created_at", cursor.created_at));
+        }
+    }
+    keys.push((
+        format!("{}.id > '{}'", todos_table, cursor.id),
+        format!("{}.id = '{}'", todos_table, cursor.id),
+    ));
+
+    let mut equals: Vec<String> = vec![];
+    let mut conditions: Vec<String> = vec![];
+    for (after, equal) in keys {
+        let mut condition = equals.clone();
+        condition.push(after);
+        conditions.push(format!("({})", condition.join(" AND ")));
+        equals.push(equal);
+    }
+    format!("({})", conditions.join(" OR "))
+}
+
+/// キーワードを部分一致で検索するILIKE（SQLiteではLIKE）のパターンを生成する。
+///
+/// キーワードに含まれる`%`、`_`及び`\`は、ワイルドカードではなく文字として検索するためにエスケープする。
+pub(crate) fn keyword_like_pattern(keyword: &str) -> String {
+    let mut pattern = String::with_capacity(keyword.len() + 2);
+    pattern.push('%');
+    for c in keyword.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Todoリストの並び順を指定するORDER BY句を生成する。
+///
+/// 表示範囲が`All`の場合は、完了していないTodoを完了したTodoより前に並べてから、指定された並び順で並べる。
+/// ページに分割したときに、ページ間でTodoが重複したり欠落したりしないように、最後にTodoのIDで並べる。
+pub(crate) fn list_order_by_clause(
+    sort: TodoListSort,
+    view: TodoListView,
+    todos_table: &str,
+) -> String {
+    let completed_last = match view {
+        TodoListView::All => format!(
+            "{}.todo_status_code = {}, ",
+            todos_table,
+            TodoStatusCode::Completed as i16
+        ),
+        TodoListView::Active | TodoListView::Completed => String::new(),
+    };
+    match sort {
+        TodoListSort::DueDate => format!(
+            "ORDER BY {1}{0}.due_date NULLS LAST, {0}.updated_at DESC, {0}.created_at DESC, {0}.id",
+            todos_table, completed_last
+        ),
+        TodoListSort::CreatedAt => format!(
+            "ORDER BY {1}{0}.created_at DESC, {0}.id",
+            todos_table, completed_last
+        ),
+        TodoListSort::UpdatedAt => format!(
+            "ORDER BY {1}{0}.updated_at DESC, {0}.created_at DESC, {0}.id",
+            todos_table, completed_last
+        ),
+    }
+}
+
+/// Todoリストのページを指定するLIMIT句とOFFSET句を生成する。
+///
+/// カーソルによるページの場合は、カーソルより後のTodoを`list_where_clause`で絞り込むため、LIMIT句のみを生成する。
+/// ページが指定されていない場合は、空文字列を返す。
+pub(crate) fn list_limit_clause(input: &TodoListInput) -> String {
+    match (input.page, &input.keyset) {
+        (Some(page), _) => format!("\nLIMIT {} OFFSET {}", page.page_size, page.offset()),
+        (None, Some(keyset)) => format!("\nLIMIT {}", keyset.limit),
+        (None, None) => String::new(),
+    }
+}
+
+/// 日次ダイジェストに含めるTodoの行
+#[derive(Debug, sqlx::FromRow)]
+struct DigestTodoRow {
+    user_id: Uuid,
+    id: Uuid,
+    title: String,
+    due_date: Date,
+}
+
 #[derive(Debug, sqlx::FromRow)]
 struct TodoRow {
     id: Uuid,
@@ -370,7 +571,7 @@ struct TodoRow {
     family_name: String,
     given_name: String,
     email: String,
-    role_code: i16,
+    role_code: RoleCode,
     role_name: String,
     role_description: Option<String>,
     role_display_order: i16,
@@ -378,11 +579,14 @@ struct TodoRow {
     role_updated_at: OffsetDateTime,
     active: bool,
     last_login_at: Option<OffsetDateTime>,
+    locked_at: Option<OffsetDateTime>,
+    lock_reason: Option<String>,
+    locale: Option<String>,
     user_created_at: OffsetDateTime,
     user_updated_at: OffsetDateTime,
     title: String,
     description: Option<String>,
-    todo_status_code: i16,
+    todo_status_code: TodoStatusCode,
     todo_status_name: String,
     todo_status_description: Option<String>,
     todo_status_display_order: i16,
@@ -405,7 +609,7 @@ impl TryFrom<TodoRow> for Todo {
             given_name: row.given_name.try_into()?,
             email: row.email.try_into()?,
             role: Role {
-                code: row.role_code.try_into()?,
+                code: row.role_code,
                 name: row.role_name.try_into()?,
                 description: row.role_description.map(|d| d.try_into()).transpose()?,
                 display_order: row.role_display_order.try_into()?,
@@ -414,11 +618,18 @@ impl TryFrom<TodoRow> for Todo {
             },
             active: row.active,
             last_login_at: row.last_login_at,
+            locked_at: row.locked_at,
+            lock_reason: row
+                .lock_reason
+                .as_deref()
+                .map(LockReason::try_from)
+                .transpose()?,
+            locale: row.locale,
             created_at: row.user_created_at,
             updated_at: row.user_updated_at,
         };
         let status = TodoStatus {
-            code: row.todo_status_code.try_into()?,
+            code: row.todo_status_code,
             name: row.todo_status_name.try_into()?,
             description: row
                 .todo_status_description
@@ -449,6 +660,29 @@ async fn todo_commit(tx: PgTransaction<'_>, row: TodoRow) -> DomainResult<Todo>
     Todo::try_from(row)
 }
 
+/// 条件付きの更新で、Todoが更新されなかった場合のエラーを返す。
+///
+/// Todoが存在しない場合は、`NotFound`エラーを返す。
+/// Todoが存在する場合は、`ensure`でTodoを更新できない理由を確認して、検証エラーを返す。
+async fn todo_not_updated<T>(
+    mut tx: PgTransaction<'_>,
+    id: TodoId,
+    ensure: impl FnOnce(&Todo) -> DomainResult<()>,
+) -> DomainResult<T> {
+    let row = sqlx::query_file_as!(TodoRow, "src/postgres/queries/todo_by_id.sql", id.0)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some(row) = row else {
+        return todo_not_found(id);
+    };
+    ensure(&Todo::try_from(row)?)?;
+    // 更新するときに条件を満たさなかったTodoが、確認するまでに条件を満たす状態に変更された場合
+    Err(domain_error(
+        DomainErrorKind::Validation,
+        "The todo was modified by another request",
+    ))
+}
+
 fn todo_not_found<T>(id: TodoId) -> DomainResult<T> {
     let message = format!("Todo with id {} not found", id);
     Err(DomainError {
@@ -457,3 +691,162 @@ fn todo_not_found<T>(id: TodoId) -> DomainResult<T> {
         source: anyhow::anyhow!(message),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use domain::repositories::{TodoListKeyset, TodoListPage};
+    use time::macros::{date, datetime};
+
+    use super::*;
+
+    #[rstest::rstest]
+    #[case("report", "%report%")]
+    #[case("%", "%\\%%")]
+    #[case("_", "%\\_%")]
+    #[case("100%_done", "%100\\%\\_done%")]
+    #[case("C:\\todo", "%C:\\\\todo%")]
+    fn keyword_like_pattern_escapes_wildcards(#[case] keyword: &str, #[case] expected: &str) {
+        assert_eq!(keyword_like_pattern(keyword), expected);
+    }
+
+    #[test]
+    fn list_where_clause_binds_keyword() {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        input.keyword = Some(String::from("' OR 1 = 1 --"));
+        let clause = list_where_clause(&input, "t");
+        assert!(!clause.contains("OR 1 = 1"), "{clause}");
+        assert!(clause.contains("t.title ILIKE $1 ESCAPE '\\'"), "{clause}");
+    }
+
+    #[rstest::rstest]
+    #[case(None, TodoListView::Active, "t.todo_status_code <> 3")]
+    #[case(None, TodoListView::Completed, "t.todo_status_code = 3")]
+    #[case(Some(vec![TodoStatusCode::Completed]), TodoListView::Active, "t.todo_status_code IN (3)")]
+    fn list_where_clause_prefers_statuses_to_view(
+        #[case] statuses: Option<Vec<TodoStatusCode>>,
+        #[case] view: TodoListView,
+        #[case] expected: &str,
+    ) {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        input.statuses = statuses;
+        input.view = view;
+        let clause = list_where_clause(&input, "t");
+        assert!(clause.contains(expected), "{clause}");
+        assert_eq!(clause.matches("todo_status_code").count(), 1, "{clause}");
+    }
+
+    #[test]
+    fn list_where_clause_does_not_filter_statuses_for_all_view() {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        input.view = TodoListView::All;
+        let clause = list_where_clause(&input, "t");
+        assert!(!clause.contains("todo_status_code"), "{clause}");
+    }
+
+    #[rstest::rstest]
+    #[case(TodoListView::Active, false)]
+    #[case(TodoListView::Completed, false)]
+    #[case(TodoListView::All, true)]
+    fn list_order_by_clause_puts_completed_todos_last_for_all_view(
+        #[case] view: TodoListView,
+        #[case] completed_last: bool,
+    ) {
+        let clause = list_order_by_clause(TodoListSort::DueDate, view, "t");
+        assert_eq!(
+            clause.starts_with("ORDER BY t.todo_status_code = 3, t.due_date"),
+            completed_last,
+            "{clause}"
+        );
+    }
+
+    #[test]
+    fn list_limit_clause_limits_the_todos_to_the_page() {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        assert_eq!(list_limit_clause(&input), "");
+        input.page = Some(TodoListPage {
+            page: 3,
+            page_size: 20,
+        });
+        assert_eq!(list_limit_clause(&input), "\nLIMIT 20 OFFSET 40");
+        input.page = None;
+        input.keyset = TodoListKeyset::new(None, Some(30)).unwrap();
+        assert_eq!(list_limit_clause(&input), "\nLIMIT 30");
+    }
+
+    fn create_cursor(due_date: Option<Date>, completed: bool) -> TodoListCursor {
+        TodoListCursor {
+            filter_hash: String::new(),
+            completed,
+            due_date,
+            updated_at: datetime!(2025-06-02 10:00:00 UTC),
+            created_at: datetime!(2025-06-01 09:00:00 UTC),
+            id: TodoId::from(Uuid::nil()),
+        }
+    }
+
+    #[test]
+    fn list_keyset_condition_compares_the_sort_keys_in_order() {
+        let cursor = create_cursor(None, false);
+        let condition =
+            list_keyset_condition(&cursor, TodoListSort::CreatedAt, TodoListView::Active, "t");
+        assert_eq!(
+            condition,
+            "((t.created_at < '2025-06-01T09:00:00Z'::timestamptz) \
+             OR (t.created_at = '2025-06-01T09:00:00Z'::timestamptz \
+             AND t.id > '00000000-0000-0000-0000-000000000000'))"
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::with_due_date(
+        Some(date!(2025 - 06 - 30)),
+        "(t.due_date > '2025-06-30' OR t.due_date IS NULL)",
+        "t.due_date = '2025-06-30' AND t.updated_at < "
+    )]
+    #[case::without_due_date(None, "FALSE", "t.due_date IS NULL AND t.updated_at < ")]
+    fn list_keyset_condition_puts_todos_without_due_date_last(
+        #[case] due_date: Option<Date>,
+        #[case] first: &str,
+        #[case] second: &str,
+    ) {
+        let cursor = create_cursor(due_date, false);
+        let condition =
+            list_keyset_condition(&cursor, TodoListSort::DueDate, TodoListView::Active, "t");
+        assert!(
+            condition.starts_with(&format!("(({first}) OR ")),
+            "{condition}"
+        );
+        assert!(condition.contains(second), "{condition}");
+    }
+
+    #[rstest::rstest]
+    #[case(false, "(t.todo_status_code = 3)")]
+    #[case(true, "FALSE")]
+    fn list_keyset_condition_puts_completed_todos_last_for_all_view(
+        #[case] completed: bool,
+        #[case] first: &str,
+    ) {
+        let cursor = create_cursor(None, completed);
+        let condition =
+            list_keyset_condition(&cursor, TodoListSort::UpdatedAt, TodoListView::All, "t");
+        assert!(
+            condition.starts_with(&format!("(({first}) OR ")),
+            "{condition}"
+        );
+        assert!(
+            condition.contains(&format!("(t.todo_status_code = 3) = {completed} AND")),
+            "{condition}"
+        );
+    }
+
+    #[test]
+    fn list_where_clause_adds_the_keyset_condition_only_with_the_cursor() {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        input.keyset = TodoListKeyset::new(None, Some(10)).unwrap();
+        let clause = list_where_clause(&input, "t");
+        assert!(!clause.contains("t.id >"), "{clause}");
+        input.keyset = TodoListKeyset::new(Some(create_cursor(None, false)), None).unwrap();
+        let clause = list_where_clause(&input, "t");
+        assert!(clause.contains("t.id >"), "{clause}");
+    }
+}
@@ -3,15 +3,16 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 
 use domain::{
-    DomainError, DomainErrorKind, DomainResult,
+    DomainError, DomainErrorKind, DomainResult, REPOSITORY_ERROR_MESSAGE,
     models::{
-        Email, LoginFailedHistory, PHCString, Role, RoleCode, RoleName, User, UserId,
+        Email, LockReason, LoginFailedHistory, PHCString, Role, RoleCode, RoleName, User, UserId,
+        UserPreferences,
         primitives::{Description, DisplayOrder},
     },
     repositories::{UpdateUserInput, UserInput, UserRepository, UserToken},
 };
 
-use super::{PgRepository, commit, repository_error};
+use super::{PgRepository, PgTransaction, commit};
 
 pub type PgUserRepository = PgRepository<User>;
 
@@ -31,13 +32,13 @@ impl UserRepository for PgUserRepository {
                 VALUES ($1, $2, $3, $4, $5, $6, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
                 RETURNING
                     id, family_name, given_name, email, role_code,
-                    active, last_login_at, created_at, updated_at
+                    active, last_login_at, locked_at, lock_reason, locale, created_at, updated_at
             )
             SELECT
-                u.id, u.family_name, u.given_name, u.email, u.role_code,
+                u.id, u.family_name, u.given_name, u.email, u.role_code "role_code: RoleCode",
                 r.name role_name, r.description role_description, r.display_order role_display_order,
                 r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at, u.updated_at
+                u.active, u.last_login_at, u.locked_at, u.lock_reason, u.locale, u.created_at, u.updated_at
             FROM inserted u
             INNER JOIN roles r ON u.role_code = r.code
             "#,
@@ -49,13 +50,7 @@ impl UserRepository for PgUserRepository {
             None::<OffsetDateTime>,
         )
         .fetch_one(&mut *tx)
-        .await
-        .map_err(|e| {
-            let mut e = repository_error(e);
-            e.messages
-                .push("The email address might already be in use".into());
-            e
-        })?;
+        .await?;
         commit(tx).await?;
         User::try_from(row)
     }
@@ -66,10 +61,10 @@ impl UserRepository for PgUserRepository {
             UserRow,
             r#"
             SELECT
-                u.id, u.family_name, u.given_name, u.email, u.role_code,
+                u.id, u.family_name, u.given_name, u.email, u.role_code "role_code: RoleCode",
                 r.name role_name, r.description role_description, r.display_order role_display_order,
                 r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at, u.updated_at
+                u.active, u.last_login_at, u.locked_at, u.lock_reason, u.locale, u.created_at, u.updated_at
             FROM users u
             INNER JOIN roles r ON u.role_code = r.code
             WHERE u.id = $1
@@ -77,8 +72,7 @@ impl UserRepository for PgUserRepository {
             id.0
         )
         .fetch_optional(&self.pool)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         row.map(User::try_from).transpose()
     }
 
@@ -88,10 +82,10 @@ impl UserRepository for PgUserRepository {
             UserRow,
             r#"
             SELECT
-                u.id, u.family_name, u.given_name, u.email, u.role_code,
+                u.id, u.family_name, u.given_name, u.email, u.role_code "role_code: RoleCode",
                 r.name role_name, r.description role_description, r.display_order role_display_order,
                 r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at, u.updated_at
+                u.active, u.last_login_at, u.locked_at, u.lock_reason, u.locale, u.created_at, u.updated_at
             FROM users u
             INNER JOIN roles r ON u.role_code = r.code
             WHERE email = $1
@@ -99,13 +93,14 @@ impl UserRepository for PgUserRepository {
             email.0
         )
         .fetch_optional(&self.pool)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         row.map(User::try_from).transpose()
     }
 
     /// ユーザーを更新する。
     async fn update(&self, id: UserId, user: UpdateUserInput) -> DomainResult<User> {
+        // ロケールは消去できるため、更新するかどうかと更新後の値を分けて渡す
+        let update_locale = user.locale.is_some();
         let mut tx = self.begin().await?;
         let row = sqlx::query_as!(
             UserRow,
@@ -115,29 +110,65 @@ impl UserRepository for PgUserRepository {
                 SET
                     family_name = COALESCE($1, family_name),
                     given_name = COALESCE($2, given_name),
-                    email = COALESCE($3, email),
-                    updated_at = CURRENT_TIMESTAMP
-                WHERE id = $4
+                    locale = CASE WHEN $3 THEN $4 ELSE locale END
+                WHERE id = $5
                 RETURNING
                     id, family_name, given_name, email, role_code, active,
-                    last_login_at, created_at, updated_at
+                    last_login_at, locked_at, lock_reason, locale, created_at, updated_at
             )
             SELECT
-                u.id, u.family_name, u.given_name, u.email, u.role_code,
+                u.id, u.family_name, u.given_name, u.email, u.role_code "role_code: RoleCode",
                 r.name role_name, r.description role_description, r.display_order role_display_order,
                 r.created_at role_created_at, r.updated_at role_updated_at,
-                u.active, u.last_login_at, u.created_at, u.updated_at
+                u.active, u.last_login_at, u.locked_at, u.lock_reason, u.locale, u.created_at, u.updated_at
             FROM updated u
             INNER JOIN roles r ON u.role_code = r.code
             "#,
             user.family_name.map(|f| f.0),
             user.given_name.map(|g| g.0),
-            user.email.map(|e| e.0),
+            update_locale,
+            user.locale.flatten(),
+            id.0
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        match row {
+            Some(row) => {
+                commit(tx).await?;
+                User::try_from(row)
+            }
+            None => user_not_found(id),
+        }
+    }
+
+    /// ユーザーのEメールアドレスを更新する。
+    async fn update_email(&self, id: UserId, email: Email) -> DomainResult<User> {
+        let mut tx = self.begin().await?;
+        let row = sqlx::query_as!(
+            UserRow,
+            r#"
+            WITH updated AS (
+                UPDATE users
+                SET
+                    email = $1
+                WHERE id = $2
+                RETURNING
+                    id, family_name, given_name, email, role_code, active,
+                    last_login_at, locked_at, lock_reason, locale, created_at, updated_at
+            )
+            SELECT
+                u.id, u.family_name, u.given_name, u.email, u.role_code "role_code: RoleCode",
+                r.name role_name, r.description role_description, r.display_order role_display_order,
+                r.created_at role_created_at, r.updated_at role_updated_at,
+                u.active, u.last_login_at, u.locked_at, u.lock_reason, u.locale, u.created_at, u.updated_at
+            FROM updated u
+            INNER JOIN roles r ON u.role_code = r.code
+            "#,
+            email.0,
             id.0
         )
         .fetch_optional(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match row {
             Some(row) => {
                 commit(tx).await?;
@@ -156,40 +187,34 @@ impl UserRepository for PgUserRepository {
         access_expired_at: OffsetDateTime,
         refresh_key: &SecretString,
         refresh_expired_at: OffsetDateTime,
-    ) -> DomainResult<()> {
+        max_sessions: u32,
+    ) -> DomainResult<Vec<SecretString>> {
         let mut tx = self.begin().await?;
         // ユーザーの最終ログイン日時を更新
         let row_affected = sqlx::query!(
             r#"
             UPDATE users
-            SET last_login_at = $1, updated_at = CURRENT_TIMESTAMP
+            SET last_login_at = $1
             WHERE id = $2
             "#,
             logged_in_at,
             id.0
         )
         .execute(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         if row_affected.rows_affected() == 0 {
             return user_not_found(id);
         }
         // 認証情報を登録
-        let ids = vec![id.0, id.0];
-        let keys = vec![access_key.expose_secret(), refresh_key.expose_secret()];
-        let expires = vec![access_expired_at, refresh_expired_at];
-        sqlx::query(
-            r#"
-            INSERT INTO user_tokens (user_id, token_key, expired_at)
-            SELECT * FROM UNNEST($1::UUID[], $2::TEXT[], $3::TIMESTAMPTZ[])
-            "#,
+        insert_user_tokens(
+            &mut tx,
+            id,
+            access_key,
+            access_expired_at,
+            refresh_key,
+            refresh_expired_at,
         )
-        .bind(&ids)
-        .bind(&keys)
-        .bind(&expires)
-        .execute(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         // ユーザーのログイン失敗履歴を削除
         sqlx::query!(
             r#"
@@ -199,8 +224,36 @@ impl UserRepository for PgUserRepository {
             id.0
         )
         .execute(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
+        // 同時にログインできるセッション数を超えた古いセッションを削除
+        let pruned_keys = if 0 < max_sessions {
+            delete_oldest_sessions(&mut tx, id, max_sessions).await?
+        } else {
+            vec![]
+        };
+        commit(tx).await?;
+        Ok(pruned_keys)
+    }
+
+    /// ユーザーの認証情報を登録する。
+    async fn register_token_pair_for_user(
+        &self,
+        id: UserId,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
+    ) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        insert_user_tokens(
+            &mut tx,
+            id,
+            access_key,
+            access_expired_at,
+            refresh_key,
+            refresh_expired_at,
+        )
+        .await?;
         commit(tx).await
     }
 
@@ -216,13 +269,24 @@ impl UserRepository for PgUserRepository {
             id.0
         )
         .fetch_all(&self.pool)
-        .await
-        .map_err(repository_error)?
+        .await?
         .into_iter()
         .map(UserToken::from)
         .collect())
     }
 
+    /// ユーザーの有効なセッションのうち、新しい`keep_n`個を残して古いセッションを削除する。
+    async fn prune_oldest_sessions(
+        &self,
+        id: UserId,
+        keep_n: u32,
+    ) -> DomainResult<Vec<SecretString>> {
+        let mut tx = self.begin().await?;
+        let keys = delete_oldest_sessions(&mut tx, id, keep_n).await?;
+        commit(tx).await?;
+        Ok(keys)
+    }
+
     /// ユーザーがログインしたときに生成したアクセストークンとリフレッシュトークンのキーを削除する。
     async fn delete_user_tokens_by_id(&self, id: UserId) -> DomainResult<Vec<SecretString>> {
         let mut tx = self.begin().await?;
@@ -235,8 +299,7 @@ impl UserRepository for PgUserRepository {
             id.0
         )
         .fetch_all(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         commit(tx).await?;
         Ok(rows
             .into_iter()
@@ -244,6 +307,57 @@ impl UserRepository for PgUserRepository {
             .collect())
     }
 
+    /// ユーザーの設定を取得する。
+    async fn preferences(&self, id: UserId) -> DomainResult<UserPreferences> {
+        let raw_preferences = sqlx::query_scalar!(
+            r#"
+            SELECT preferences
+            FROM users
+            WHERE id = $1
+            "#,
+            id.0
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        match raw_preferences {
+            Some(raw_preferences) => user_preferences_from_json(raw_preferences),
+            None => user_not_found(id),
+        }
+    }
+
+    /// ユーザーの設定を更新する。
+    async fn update_preferences(
+        &self,
+        id: UserId,
+        preferences: &UserPreferences,
+    ) -> DomainResult<UserPreferences> {
+        let raw_preferences = serde_json::to_value(preferences).map_err(|e| DomainError {
+            kind: DomainErrorKind::Unexpected,
+            messages: vec!["Failed to serialize user preferences".into()],
+            source: e.into(),
+        })?;
+        let mut tx = self.begin().await?;
+        let raw_preferences = sqlx::query_scalar!(
+            r#"
+            UPDATE users
+            SET preferences = $1
+            WHERE id = $2
+            RETURNING preferences
+            "#,
+            raw_preferences,
+            id.0
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        match raw_preferences {
+            Some(raw_preferences) => {
+                commit(tx).await?;
+                user_preferences_from_json(raw_preferences)
+            }
+            None => user_not_found(id),
+        }
+    }
+
     /// ユーザーのパスワードを取得する。
     async fn get_hashed_password(&self, id: UserId) -> DomainResult<PHCString> {
         let raw_hashed_password = sqlx::query_scalar!(
@@ -255,8 +369,7 @@ impl UserRepository for PgUserRepository {
             id.0
         )
         .fetch_optional(&self.pool)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match raw_hashed_password {
             Some(raw_hashed_password) => {
                 PHCString::new(SecretString::new(raw_hashed_password.into()))
@@ -275,15 +388,14 @@ impl UserRepository for PgUserRepository {
         let affected_rows = sqlx::query!(
             r#"
             UPDATE users
-            SET hashed_password = $1, updated_at = CURRENT_TIMESTAMP
+            SET hashed_password = $1
             WHERE id = $2
             "#,
             hashed_password.0.expose_secret(),
             id.0
         )
         .execute(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match affected_rows.rows_affected() {
             0 => user_not_found(id),
             _ => {
@@ -304,8 +416,7 @@ impl UserRepository for PgUserRepository {
             id.0
         )
         .execute(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match affected_rows.rows_affected() {
             0 => user_not_found(id),
             _ => {
@@ -330,6 +441,10 @@ impl UserRepository for PgUserRepository {
                 user_id, number_of_attempts, attempted_at, created_at, updated_at
             )
             VALUES ($1, $2, $3, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+            ON CONFLICT (user_id) DO UPDATE
+            SET
+                number_of_attempts = EXCLUDED.number_of_attempts,
+                attempted_at = EXCLUDED.attempted_at
             RETURNING
                 user_id, number_of_attempts, attempted_at, created_at, updated_at
             "#,
@@ -338,8 +453,7 @@ impl UserRepository for PgUserRepository {
             attempted_at
         )
         .fetch_one(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         commit(tx).await?;
         Ok(LoginFailedHistory::from(row))
     }
@@ -360,15 +474,14 @@ impl UserRepository for PgUserRepository {
             user_id.0
         )
         .fetch_optional(&self.pool)
-        .await
-        .map_err(repository_error)?
+        .await?
         .map(LoginFailedHistory::from))
     }
 
     /// ユーザーのログイン試行回数をインクリメントする。
     ///
     /// ユーザーのログイン試行回数をインクリメントして、インクリメント後のログイン試行回数が、最大ログイン試行回数を超えた
-    /// 場合は、ユーザーをロックする。
+    /// 場合は、ユーザーをロックして、ロックした日時と理由を記録する。
     async fn increment_number_of_login_attempts(
         &self,
         user_id: UserId,
@@ -380,23 +493,22 @@ impl UserRepository for PgUserRepository {
             r#"
             UPDATE login_failed_histories
             SET
-                number_of_attempts = number_of_attempts + 1,
-                updated_at = CURRENT_TIMESTAMP
+                number_of_attempts = number_of_attempts + 1
             WHERE user_id = $1
             "#,
             user_id.0
         )
         .execute(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
 
-        // ユーザーのログイン試行回数が最大ログイン試行回数を超えた場合は、ユーザーをロッユ
+        // ユーザーのログイン試行回数が最大ログイン試行回数を超えた場合は、ユーザーをロックして、ロックした日時と理由を記録
         sqlx::query!(
             r#"
             UPDATE users
             SET
                 active = FALSE,
-                updated_at = CURRENT_TIMESTAMP
+                locked_at = CURRENT_TIMESTAMP,
+                lock_reason = $3
             WHERE id = $1
                 AND (
                     SELECT number_of_attempts
@@ -406,14 +518,57 @@ impl UserRepository for PgUserRepository {
             "#,
             user_id.0,
             max_attempts as i32,
+            LockReason::TooManyFailedAttempts.as_str(),
         )
         .execute(&mut *tx)
-        .await
-        .map_err(repository_error)?;
-        tx.commit().await.map_err(repository_error)?;
+        .await?;
+        tx.commit().await?;
         Ok(())
     }
 
+    /// ログイン失敗によるロックが期限切れの場合は、ユーザーのロックを解除する。
+    async fn reactivate_if_lockout_expired(
+        &self,
+        id: UserId,
+        locked_before: OffsetDateTime,
+    ) -> DomainResult<bool> {
+        let mut tx = self.begin().await?;
+        // ロックした日時と理由を記録する前の古いロックは、ログイン失敗によるロックとみなして、更新日時をロックした日時とみなす
+        let affected_rows = sqlx::query!(
+            r#"
+            UPDATE users
+            SET
+                active = TRUE,
+                locked_at = NULL,
+                lock_reason = NULL
+            WHERE id = $1
+                AND active = FALSE
+                AND (lock_reason = $2 OR lock_reason IS NULL)
+                AND COALESCE(locked_at, updated_at) <= $3
+            "#,
+            id.0,
+            LockReason::TooManyFailedAttempts.as_str(),
+            locked_before
+        )
+        .execute(&mut *tx)
+        .await?;
+        if affected_rows.rows_affected() == 0 {
+            return Ok(false);
+        }
+        // ロックを解除した直後のログイン失敗で再びロックされないように、ログイン失敗履歴を削除
+        sqlx::query!(
+            r#"
+            DELETE FROM login_failed_histories
+            WHERE user_id = $1
+            "#,
+            id.0
+        )
+        .execute(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        Ok(true)
+    }
+
     /// ユーザーのログイン失敗履歴をリセットする。
     ///
     /// 連続ログイン試行回数を1に設定して、最初にログインを試行した日時を指定された日時に更新する。
@@ -428,24 +583,114 @@ impl UserRepository for PgUserRepository {
             UPDATE login_failed_histories
             SET
                 number_of_attempts = 1,
-                attempted_at = $1,
-                updated_at = CURRENT_TIMESTAMP
+                attempted_at = $1
             WHERE user_id = $2
             "#,
             attempted_at,
             user_id.0
         )
         .execute(&mut *tx)
-        .await
-        .map_err(repository_error)?;
+        .await?;
         match affected_rows.rows_affected() {
             0 => user_not_found(user_id),
             _ => {
-                tx.commit().await.map_err(repository_error)?;
+                tx.commit().await?;
                 Ok(())
             }
         }
     }
+
+    /// 古いログイン失敗履歴を削除する。
+    async fn delete_stale_login_failed_histories(
+        &self,
+        cutoff: OffsetDateTime,
+    ) -> DomainResult<u64> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query!(
+            r#"
+            DELETE FROM login_failed_histories
+            WHERE attempted_at < $1
+            "#,
+            cutoff
+        )
+        .execute(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        Ok(query_result.rows_affected())
+    }
+}
+
+/// ユーザーのアクセストークンとリフレッシュトークンのキーを登録する。
+async fn insert_user_tokens(
+    tx: &mut PgTransaction<'_>,
+    id: UserId,
+    access_key: &SecretString,
+    access_expired_at: OffsetDateTime,
+    refresh_key: &SecretString,
+    refresh_expired_at: OffsetDateTime,
+) -> DomainResult<()> {
+    let ids = vec![id.0, id.0];
+    let keys = vec![access_key.expose_secret(), refresh_key.expose_secret()];
+    let expires = vec![access_expired_at, refresh_expired_at];
+    sqlx::query(
+        r#"
+        INSERT INTO user_tokens (user_id, token_key, expired_at)
+        SELECT * FROM UNNEST($1::UUID[], $2::TEXT[], $3::TIMESTAMPTZ[])
+        "#,
+    )
+    .bind(&ids)
+    .bind(&keys)
+    .bind(&expires)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// ユーザーの有効なセッションのうち、新しい`keep_n`個を残して古いセッションを削除して、削除したキーを返す。
+///
+/// アクセストークンとリフレッシュトークンの組は同じトランザクションで登録されるため、作成日時が同じ行を1つの
+/// セッションとして扱い、リフレッシュトークンの有効期限が切れていないセッションを数える。
+async fn delete_oldest_sessions(
+    tx: &mut PgTransaction<'_>,
+    id: UserId,
+    keep_n: u32,
+) -> DomainResult<Vec<SecretString>> {
+    let rows = sqlx::query!(
+        r#"
+        WITH pruned_sessions AS (
+            SELECT created_at
+            FROM user_tokens
+            WHERE user_id = $1
+            GROUP BY created_at
+            HAVING CURRENT_TIMESTAMP < MAX(expired_at)
+            ORDER BY created_at DESC
+            OFFSET $2
+        )
+        DELETE FROM user_tokens
+        WHERE user_id = $1
+            AND created_at IN (SELECT created_at FROM pruned_sessions)
+        RETURNING token_key
+        "#,
+        id.0,
+        keep_n as i64
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| SecretString::new(row.token_key.into()))
+        .collect())
+}
+
+/// データベースに保存されたユーザーの設定をデシリアライズする。
+///
+/// 保存されたJSONが不正な場合は、リポジトリエラーを返す。
+fn user_preferences_from_json(value: serde_json::Value) -> DomainResult<UserPreferences> {
+    serde_json::from_value(value).map_err(|e| DomainError {
+        kind: DomainErrorKind::Repository,
+        messages: vec![REPOSITORY_ERROR_MESSAGE.into()],
+        source: anyhow::Error::new(e).context("Stored user preferences are malformed"),
+    })
 }
 
 fn user_not_found<T>(id: UserId) -> DomainResult<T> {
@@ -457,12 +702,17 @@ fn user_not_found<T>(id: UserId) -> DomainResult<T> {
     })
 }
 
+/// ユーザーとロールを結合した行
+///
+/// `sqlx::query_as!`でコンパイル時にクエリを検証するとともに、`sqlx::query_as`で実行時に組み立てたクエリの結果も
+/// 受け取れるように`sqlx::FromRow`を導出する。
+#[derive(Debug, sqlx::FromRow)]
 struct UserRow {
     id: Uuid,
     family_name: String,
     given_name: String,
     email: String,
-    role_code: i16,
+    role_code: RoleCode,
     role_name: String,
     role_description: Option<String>,
     role_display_order: i16,
@@ -470,6 +720,9 @@ struct UserRow {
     role_updated_at: OffsetDateTime,
     active: bool,
     last_login_at: Option<OffsetDateTime>,
+    locked_at: Option<OffsetDateTime>,
+    lock_reason: Option<String>,
+    locale: Option<String>,
     created_at: OffsetDateTime,
     updated_at: OffsetDateTime,
 }
@@ -484,7 +737,7 @@ impl TryFrom<UserRow> for User {
             given_name: row.given_name.try_into()?,
             email: row.email.try_into()?,
             role: Role {
-                code: RoleCode::try_from(row.role_code)?,
+                code: row.role_code,
                 name: RoleName::new(row.role_name)?,
                 description: row.role_description.map(Description::new).transpose()?,
                 display_order: DisplayOrder(row.role_display_order),
@@ -493,6 +746,13 @@ impl TryFrom<UserRow> for User {
             },
             active: row.active,
             last_login_at: row.last_login_at,
+            locked_at: row.locked_at,
+            lock_reason: row
+                .lock_reason
+                .as_deref()
+                .map(LockReason::try_from)
+                .transpose()?,
+            locale: row.locale,
             created_at: row.created_at,
             updated_at: row.updated_at,
         })
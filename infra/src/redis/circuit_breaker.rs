@@ -0,0 +1,156 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::settings::CircuitBreakerSettings;
+
+/// Redisへの接続の失敗が続いたときに、一定時間Redisに接続せずに失敗させるサーキットブレーカー
+///
+/// Redisが停止しているときに、リクエストごとにコネクションの取得を待たないようにするために使用する。
+/// クールダウンが終了した後の最初の接続に失敗した場合は、再びサーキットブレーカーを開く。
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// サーキットブレーカーを開く連続失敗回数
+    failure_threshold: u32,
+    /// 連続失敗回数を数える時間窓
+    failure_window: Duration,
+    /// サーキットブレーカーを開いたままにする時間
+    cooldown: Duration,
+    /// サーキットブレーカーの状態
+    state: Mutex<CircuitState>,
+}
+
+/// サーキットブレーカーの状態
+#[derive(Debug, Default)]
+struct CircuitState {
+    /// 連続して失敗した回数
+    consecutive_failures: u32,
+    /// 連続した失敗のうち、最初に失敗した時刻
+    first_failure_at: Option<Instant>,
+    /// Redisに接続せずに失敗させる期限
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(settings: CircuitBreakerSettings) -> Self {
+        Self {
+            failure_threshold: settings.failure_threshold,
+            failure_window: settings.failure_window(),
+            cooldown: settings.cooldown(),
+            state: Mutex::new(CircuitState::default()),
+        }
+    }
+
+    /// サーキットブレーカーが開いていて、Redisに接続せずに失敗させるべきか確認する。
+    pub fn is_open(&self) -> bool {
+        self.is_open_at(Instant::now())
+    }
+
+    /// Redisへの接続に成功したことを記録して、サーキットブレーカーを閉じる。
+    pub fn record_success(&self) {
+        *self.state.lock().unwrap() = CircuitState::default();
+    }
+
+    /// Redisへの接続に失敗したことを記録する。
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now());
+    }
+
+    fn is_open_at(&self, now: Instant) -> bool {
+        let state = self.state.lock().unwrap();
+        state.open_until.is_some_and(|until| now < until)
+    }
+
+    fn record_failure_at(&self, now: Instant) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        // クールダウンが終了した後の最初の接続に失敗した場合は、再びサーキットブレーカーを開く
+        if state.open_until.is_some() {
+            state.open_until = Some(now + self.cooldown);
+            return;
+        }
+        // 時間窓を過ぎた失敗は数えない
+        if state
+            .first_failure_at
+            .is_none_or(|at| now.duration_since(at) > self.failure_window)
+        {
+            state.consecutive_failures = 0;
+            state.first_failure_at = Some(now);
+        }
+        state.consecutive_failures += 1;
+        if self.failure_threshold <= state.consecutive_failures {
+            state.open_until = Some(now + self.cooldown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circuit_breaker(failure_threshold: u32) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerSettings {
+            failure_threshold,
+            failure_window_secs: 10,
+            cooldown_secs: 30,
+        })
+    }
+
+    #[test]
+    fn circuit_breaker_opens_after_consecutive_failures_within_the_window() {
+        let breaker = circuit_breaker(3);
+        let now = Instant::now();
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now + Duration::from_secs(1));
+        assert!(!breaker.is_open_at(now + Duration::from_secs(1)));
+        breaker.record_failure_at(now + Duration::from_secs(2));
+        assert!(breaker.is_open_at(now + Duration::from_secs(2)));
+        assert!(breaker.is_open_at(now + Duration::from_secs(31)));
+        // クールダウンが終了した後は、Redisへの接続を試みる
+        assert!(!breaker.is_open_at(now + Duration::from_secs(32)));
+    }
+
+    #[test]
+    fn circuit_breaker_does_not_count_failures_outside_the_window() {
+        let breaker = circuit_breaker(3);
+        let now = Instant::now();
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now + Duration::from_secs(1));
+        breaker.record_failure_at(now + Duration::from_secs(11));
+        assert!(!breaker.is_open_at(now + Duration::from_secs(11)));
+    }
+
+    #[test]
+    fn circuit_breaker_is_reset_by_a_success() {
+        let breaker = circuit_breaker(2);
+        let now = Instant::now();
+        breaker.record_failure_at(now);
+        breaker.record_success();
+        breaker.record_failure_at(now + Duration::from_secs(1));
+        assert!(!breaker.is_open_at(now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn circuit_breaker_reopens_when_the_trial_after_the_cooldown_fails() {
+        let breaker = circuit_breaker(1);
+        let now = Instant::now();
+        breaker.record_failure_at(now);
+        let after_cooldown = now + Duration::from_secs(31);
+        assert!(!breaker.is_open_at(after_cooldown));
+        breaker.record_failure_at(after_cooldown);
+        assert!(breaker.is_open_at(after_cooldown + Duration::from_secs(29)));
+    }
+
+    #[test]
+    fn circuit_breaker_never_opens_when_the_threshold_is_zero() {
+        let breaker = circuit_breaker(0);
+        let now = Instant::now();
+        for _ in 0..10 {
+            breaker.record_failure_at(now);
+        }
+        assert!(!breaker.is_open_at(now));
+    }
+}
@@ -0,0 +1,56 @@
+use std::borrow::Cow;
+
+/// 接頭辞とキーの区切り文字
+const KEY_SEPARATOR: char = ':';
+
+/// Redisのキーに、環境ごとの接頭辞を付与するヘルパー
+///
+/// 複数の環境や機能で同じRedisを共有するときに、キーの衝突を避けるため、Redisを読み書きするときは必ず
+/// このヘルパーでキーを変換する。PostgreSQLなどRedis以外に記録するキーには、接頭辞を付与しない。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedisKey {
+    /// キーの接頭辞
+    prefix: Option<String>,
+}
+
+impl RedisKey {
+    /// Redisのキーのヘルパーを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `prefix` - キーの接頭辞（空文字列の場合は接頭辞を付与しない）
+    pub fn new(prefix: Option<String>) -> Self {
+        Self {
+            prefix: prefix.filter(|prefix| !prefix.is_empty()),
+        }
+    }
+
+    /// キーに接頭辞を付与して、Redisに記録するキーを返す。
+    ///
+    /// 接頭辞とキーは`:`で区切る（例: `staging:<key>`）。
+    pub fn apply<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match &self.prefix {
+            Some(prefix) => Cow::Owned(format!("{}{}{}", prefix, KEY_SEPARATOR, key)),
+            None => Cow::Borrowed(key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_prefixes_the_key() {
+        let key = RedisKey::new(Some(String::from("staging")));
+        assert_eq!(key.apply("abc"), "staging:abc");
+    }
+
+    #[rstest::rstest]
+    #[case(None)]
+    #[case(Some(String::new()))]
+    fn apply_returns_the_key_as_is_without_prefix(#[case] prefix: Option<String>) {
+        let key = RedisKey::new(prefix);
+        assert!(matches!(key.apply("abc"), Cow::Borrowed("abc")));
+    }
+}
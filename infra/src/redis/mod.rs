@@ -1 +1,3 @@
+pub mod circuit_breaker;
+pub mod key;
 pub mod token;
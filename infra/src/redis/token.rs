@@ -1,17 +1,32 @@
+use std::sync::Arc;
+
 use deadpool_redis::{Connection as RedisConnection, Pool as RedisPool};
 use redis::AsyncCommands;
 use secrecy::{ExposeSecret, SecretString};
 
 use domain::{
-    repositories::{AuthTokenInfo, TokenContent, TokenRepository, divide_auth_token_info},
+    models::{Email, UserId},
+    repositories::{
+        AuthTokenInfo, PendingEmail, TokenContent, TokenRepository, divide_auth_token_info,
+        divide_pending_email_value, generate_pending_email_key, generate_pending_email_value,
+    },
     {DomainError, DomainErrorKind, DomainResult},
 };
 
+use crate::redis::{circuit_breaker::CircuitBreaker, key::RedisKey};
+
+/// Redisに接続できないときに、クライアントに返すメッセージ
+pub const REDIS_UNAVAILABLE_MESSAGE: &str = "Authentication service temporarily unavailable";
+
 /// Redisトークンリポジトリ
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct RedisTokenRepository {
     /// Redis接続プール
     pool: RedisPool,
+    /// Redisへの接続の失敗が続いたときに、Redisに接続せずに失敗させるサーキットブレーカー
+    circuit_breaker: Arc<CircuitBreaker>,
+    /// Redisのキーに接頭辞を付与するヘルパー
+    key: RedisKey,
 }
 
 impl RedisTokenRepository {
@@ -20,25 +35,69 @@ impl RedisTokenRepository {
     /// # 引数
     ///
     /// * `pool` - Redis接続プール
+    /// * `circuit_breaker` - リポジトリ間で共有するサーキットブレーカー
+    /// * `key` - Redisのキーに接頭辞を付与するヘルパー
     ///
     /// # 戻り値
     ///
     /// Redis接続プール
-    pub fn new(pool: RedisPool) -> Self {
-        Self { pool }
+    pub fn new(pool: RedisPool, circuit_breaker: Arc<CircuitBreaker>, key: RedisKey) -> Self {
+        Self {
+            pool,
+            circuit_breaker,
+            key,
+        }
+    }
+
+    /// Redisに`PING`コマンドを送信して、Redisに接続できるか確認する。
+    ///
+    /// Redisが`PONG`を返した場合は`Ok(())`を返す。
+    pub async fn ping(&self) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let reply: String = redis::cmd("PING")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| DomainError {
+                kind: DomainErrorKind::Repository,
+                messages: vec!["Failed to send PING to the redis".into()],
+                source: e.into(),
+            })?;
+        if reply != "PONG" {
+            return Err(DomainError {
+                kind: DomainErrorKind::Repository,
+                messages: vec!["The redis returned an unexpected reply to PING".into()],
+                source: anyhow::anyhow!("Unexpected reply to PING: {}", reply),
+            });
+        }
+        Ok(())
     }
 
     /// Redisに接続する。
     ///
+    /// Redisに接続できない場合、またはサーキットブレーカーが開いている場合は、`DomainErrorKind::Unavailable`の
+    /// エラーを返す。
+    ///
     /// # 戻り値
     ///
     /// Redis接続
     async fn connection(&self) -> DomainResult<RedisConnection> {
-        self.pool.get().await.map_err(|e| DomainError {
-            kind: DomainErrorKind::Repository,
-            messages: vec!["Failed to connect to the redis".into()],
-            source: e.into(),
-        })
+        if self.circuit_breaker.is_open() {
+            return Err(redis_unavailable(anyhow::anyhow!(
+                "The circuit breaker for the redis is open"
+            )));
+        }
+        match self.pool.get().await {
+            Ok(conn) => {
+                self.circuit_breaker.record_success();
+                Ok(conn)
+            }
+            Err(e) => {
+                self.circuit_breaker.record_failure();
+                Err(redis_unavailable(
+                    anyhow::Error::new(e).context("Failed to connect to the redis"),
+                ))
+            }
+        }
     }
 }
 
@@ -53,14 +112,14 @@ impl TokenRepository for RedisTokenRepository {
         let mut conn = self.connection().await?;
         store(
             &mut conn,
-            access_token_info.key.expose_secret(),
+            &self.key.apply(access_token_info.key.expose_secret()),
             &access_token_info.value,
             access_token_info.max_age,
         )
         .await?;
         store(
             &mut conn,
-            refresh_token_info.key.expose_secret(),
+            &self.key.apply(refresh_token_info.key.expose_secret()),
             &refresh_token_info.value,
             refresh_token_info.max_age,
         )
@@ -68,6 +127,18 @@ impl TokenRepository for RedisTokenRepository {
         Ok(())
     }
 
+    /// 単独のトークンを登録する。
+    async fn register_token(&self, token_info: &AuthTokenInfo) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        store(
+            &mut conn,
+            &self.key.apply(token_info.key.expose_secret()),
+            &token_info.value,
+            token_info.max_age,
+        )
+        .await
+    }
+
     /// トークンをハッシュ化した文字列からユーザーIDとトークンの種類を取得する。
     ///
     /// # 引数
@@ -80,21 +151,101 @@ impl TokenRepository for RedisTokenRepository {
     async fn get_token_content(&self, key: &SecretString) -> DomainResult<Option<TokenContent>> {
         tracing::trace!("Retrieving token content for key: {}", key.expose_secret());
         let mut conn = self.connection().await?;
-        let value = retrieve(&mut conn, key.expose_secret()).await?;
+        let value = retrieve(&mut conn, &self.key.apply(key.expose_secret())).await?;
         if value.is_none() {
             return Ok(None);
         }
-        let (user_id, token_type) = divide_auth_token_info(&value.unwrap())?;
-        Ok(Some(TokenContent {
-            user_id,
-            token_type,
-        }))
+        let content = divide_auth_token_info(&value.unwrap())?;
+        Ok(Some(content))
+    }
+
+    /// 認証情報の残りの生存期間（秒）を取得する。
+    async fn get_token_ttl(&self, key: &SecretString) -> DomainResult<Option<u64>> {
+        let mut conn = self.connection().await?;
+        time_to_live(&mut conn, &self.key.apply(key.expose_secret())).await
     }
 
     /// 認証情報を削除する。
     async fn delete_token_content(&self, key: &SecretString) -> DomainResult<()> {
         let mut conn = self.connection().await?;
-        delete(&mut conn, key.expose_secret()).await
+        delete(&mut conn, &self.key.apply(key.expose_secret())).await
+    }
+
+    /// 確認待ちのEメールアドレスを登録する。
+    async fn register_pending_email(
+        &self,
+        user_id: UserId,
+        email: &Email,
+        token_info: &AuthTokenInfo,
+    ) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let pending_key = self
+            .key
+            .apply(&generate_pending_email_key(user_id))
+            .into_owned();
+        // 以前の確認トークンを無効化
+        if let Some(value) = retrieve(&mut conn, &pending_key).await? {
+            let previous = divide_pending_email_value(&value)?;
+            delete(
+                &mut conn,
+                &self.key.apply(previous.token_key.expose_secret()),
+            )
+            .await?;
+        }
+        store(
+            &mut conn,
+            &self.key.apply(token_info.key.expose_secret()),
+            &token_info.value,
+            token_info.max_age,
+        )
+        .await?;
+        store(
+            &mut conn,
+            &pending_key,
+            &generate_pending_email_value(email, &token_info.key),
+            token_info.max_age,
+        )
+        .await
+    }
+
+    /// ユーザーの確認待ちのEメールアドレスを取得する。
+    async fn pending_email(&self, user_id: UserId) -> DomainResult<Option<PendingEmail>> {
+        let mut conn = self.connection().await?;
+        let pending_key = self
+            .key
+            .apply(&generate_pending_email_key(user_id))
+            .into_owned();
+        let value = retrieve(&mut conn, &pending_key).await?;
+        value
+            .map(|value| divide_pending_email_value(&value))
+            .transpose()
+    }
+
+    /// ユーザーの確認待ちのEメールアドレスと、その確認トークンの認証情報を削除する。
+    async fn delete_pending_email(&self, user_id: UserId) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let pending_key = self
+            .key
+            .apply(&generate_pending_email_key(user_id))
+            .into_owned();
+        if let Some(value) = retrieve(&mut conn, &pending_key).await? {
+            let pending = divide_pending_email_value(&value)?;
+            delete(
+                &mut conn,
+                &self.key.apply(pending.token_key.expose_secret()),
+            )
+            .await?;
+        }
+        delete(&mut conn, &pending_key).await
+    }
+}
+
+/// Redisに接続できないことを示すドメインエラーを返す。
+fn redis_unavailable(source: anyhow::Error) -> DomainError {
+    DomainError {
+        kind: DomainErrorKind::Unavailable,
+        messages: vec![REDIS_UNAVAILABLE_MESSAGE.into()],
+        source,
     }
 }
 
@@ -137,6 +288,18 @@ async fn retrieve(conn: &mut RedisConnection, key: &str) -> DomainResult<Option<
     Ok(value)
 }
 
+/// キーを基にRedisからレコードの残りの生存期間（秒）を取得する。
+///
+/// Redisは、キーが存在しない場合は-2、生存期間が設定されていない場合は-1を返すため、これらの場合は`None`を返す。
+async fn time_to_live(conn: &mut RedisConnection, key: &str) -> DomainResult<Option<u64>> {
+    let ttl: i64 = conn.ttl(key).await.map_err(|e| DomainError {
+        kind: DomainErrorKind::Repository,
+        messages: vec!["Failed to retrieve time to live from redis".into()],
+        source: e.into(),
+    })?;
+    Ok(u64::try_from(ttl).ok())
+}
+
 /// キーを基にRedisからレコードを削除する。
 async fn delete(conn: &mut RedisConnection, key: &str) -> DomainResult<()> {
     conn.del(key).await.map_err(|e| DomainError {
@@ -1,18 +1,26 @@
+use std::time::Duration;
+
 use anyhow::Context as _;
+use axum::http::{HeaderValue, Uri};
 use config::Config;
-use log::Level as LogLevel;
 use secrecy::{ExposeSecret as _, SecretString};
 use serde::{Deserialize, Deserializer};
-use sqlx::postgres::{PgConnectOptions, PgSslMode};
+use sqlx::{
+    ConnectOptions as _,
+    postgres::{PgConnectOptions, PgSslMode},
+};
+use tracing::Level as LogLevel;
 
 pub fn load_app_settings(path: &str) -> anyhow::Result<AppSettings> {
     let config = Config::builder()
         .add_source(config::File::with_name(path))
         .build()
         .context("Failed to read the app_settings.toml file")?;
-    config
+    let settings: AppSettings = config
         .try_deserialize()
-        .context("The contents of the app_settings.toml file is incorrect")
+        .context("The contents of the app_settings.toml file is incorrect")?;
+    settings.validate()?;
+    Ok(settings)
 }
 
 /// アプリケーション設定
@@ -34,13 +42,64 @@ pub struct AppSettings {
     pub login: LoginSettings,
     /// トークン設定
     pub token: TokenSettings,
+    /// Todo設定
+    #[serde(default)]
+    pub todo: TodoSettings,
+    /// セキュリティ設定
+    #[serde(default)]
+    pub security: SecuritySettings,
+    /// アクセスログ設定
+    #[serde(default)]
+    pub access_log: AccessLogSettings,
+    /// 参照データ設定
+    #[serde(default)]
+    pub lookup: LookupSettings,
+    /// 日次ダイジェスト設定
+    #[serde(default)]
+    pub digest: DigestSettings,
+    /// メール設定
+    #[serde(default)]
+    pub mail: MailSettings,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+/// テスト用のプレースホルダーを設定したアプリケーション設定を返す。
+///
+/// ループバックアドレスとポート番号`0`、ダミーのシークレットを設定するため、データベースやRedisに接続しない
+/// 単体テストで使用すること。本番環境では、`load_app_settings`で設定ファイルから読み込むこと。
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            log_level: LogLevel::INFO,
+            http: HttpSettings::default(),
+            database: DatabaseSettings::default(),
+            redis: RedisSettings::default(),
+            password: PasswordSettings::default(),
+            login: LoginSettings::default(),
+            token: TokenSettings::default(),
+            todo: TodoSettings::default(),
+            security: SecuritySettings::default(),
+            access_log: AccessLogSettings::default(),
+            lookup: LookupSettings::default(),
+            digest: DigestSettings::default(),
+            mail: MailSettings::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// 設定の値を検証する。
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.http.validate()?;
+        self.database.validate()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
 #[serde(rename = "protocol")]
 #[serde(rename_all = "lowercase")]
 pub enum HttpProtocol {
     /// HTTPプロトコル
+    #[default]
     Http,
     /// HTTPSプロトコル
     Https,
@@ -55,11 +114,80 @@ pub struct HttpSettings {
     pub host: String,
     /// ポート番号
     pub port: u16,
+    /// リクエストタイムアウト（秒）
+    pub request_timeout_secs: u64,
+    /// リクエストボディの最大サイズ（バイト）
+    #[serde(default = "default_max_body_size_bytes")]
+    pub max_body_size_bytes: usize,
+    /// 静的ファイル（フロントエンド）を配信するディレクトリ
+    ///
+    /// 指定されていない場合は、静的ファイルを配信しない。
+    #[serde(default)]
+    pub static_dir: Option<String>,
+    /// CORSでリクエストを許可するオリジン（例: `http://localhost:5173`）
+    ///
+    /// 空の場合は、CORSを無効にする。
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// ブラウザがCORSのプリフライトリクエストの結果をキャッシュする期間（秒）
+    #[serde(default = "default_cors_max_age_secs")]
+    pub cors_max_age_secs: u64,
+}
+
+/// テスト用のプレースホルダーを設定したHTTPサーバー設定を返す。
+///
+/// ループバックアドレスのランダムなポートにバインドする。
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            protocol: HttpProtocol::default(),
+            host: String::from("127.0.0.1"),
+            port: 0,
+            request_timeout_secs: 30,
+            max_body_size_bytes: default_max_body_size_bytes(),
+            static_dir: None,
+            allowed_origins: vec![],
+            cors_max_age_secs: default_cors_max_age_secs(),
+        }
+    }
+}
+
+/// リクエストボディの最大サイズの既定値（1MB）
+fn default_max_body_size_bytes() -> usize {
+    1024 * 1024
+}
+
+/// CORSのプリフライトリクエストの結果をキャッシュする期間の既定値（24時間）
+fn default_cors_max_age_secs() -> u64 {
+    24 * 60 * 60
+}
+
+/// データベースエンジン
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseEngine {
+    /// PostgreSQL
+    #[default]
+    Postgres,
+    /// SQLite
+    ///
+    /// `lite`フィーチャーを有効にしてビルドした場合のみ使用できる。
+    Sqlite,
 }
 
 /// データベース設定
 #[derive(Debug, Clone, Deserialize)]
 pub struct DatabaseSettings {
+    /// データベースエンジン
+    ///
+    /// `sqlite`の場合は、PostgreSQLとRedisの代わりに、SQLiteデータベースとプロセス内のトークンリポジトリを使用する。
+    #[serde(default)]
+    pub engine: DatabaseEngine,
+    /// SQLiteデータベースファイルのパス
+    ///
+    /// `engine`が`sqlite`の場合のみ使用する。ファイルが存在しない場合は作成する。
+    #[serde(default = "default_sqlite_path")]
+    pub sqlite_path: String,
     /// ホスト名
     pub host: String,
     /// ポート番号
@@ -76,6 +204,49 @@ pub struct DatabaseSettings {
     pub connection_timeout: u64,
     /// SSL/TLSを使用するかどうか
     pub use_ssl: bool,
+    /// 警告ログを出力する遅いクエリの実行時間の閾値（ミリ秒）
+    #[serde(default = "default_slow_query_ms")]
+    pub slow_query_ms: u64,
+    /// `pg_stat_activity`に表示するアプリケーション名
+    #[serde(default = "default_application_name")]
+    pub application_name: String,
+}
+
+/// テスト用のプレースホルダーを設定したデータベース設定を返す。
+///
+/// パスワードはダミーの値であるため、実際のデータベースに接続するときは設定ファイルから読み込むこと。
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            engine: DatabaseEngine::default(),
+            sqlite_path: default_sqlite_path(),
+            host: String::from("127.0.0.1"),
+            port: 5432,
+            user: String::from("postgres"),
+            password: SecretString::new("placeholder-password".into()),
+            name: String::from("todo_db"),
+            max_connections: 5,
+            connection_timeout: 5,
+            use_ssl: false,
+            slow_query_ms: default_slow_query_ms(),
+            application_name: default_application_name(),
+        }
+    }
+}
+
+/// SQLiteデータベースファイルのパスの既定値
+fn default_sqlite_path() -> String {
+    String::from("rusty_todo.sqlite3")
+}
+
+/// 遅いクエリの実行時間の閾値の既定値（1秒）
+fn default_slow_query_ms() -> u64 {
+    1_000
+}
+
+/// アプリケーション名の既定値
+fn default_application_name() -> String {
+    String::from("axum-sqlx-todo")
 }
 
 /// パスワード設定
@@ -101,6 +272,25 @@ pub struct PasswordSettings {
     pub hash_parallelism: u32,
 }
 
+/// テスト用のプレースホルダーを設定したパスワード設定を返す。
+///
+/// ペッパーはダミーの値であるため、本番環境では使用しないこと。
+impl Default for PasswordSettings {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 64,
+            symbols: String::from(r#"~!@#$%^&*()_-+={[}]|:;'<,>.?/"#),
+            max_same_chars: 3,
+            max_repeated_chars: 2,
+            pepper: SecretString::new("placeholder-pepper".into()),
+            hash_memory: 12288,
+            hash_iterations: 3,
+            hash_parallelism: 1,
+        }
+    }
+}
+
 /// ログイン設定
 #[derive(Debug, Clone, Copy, Deserialize)]
 pub struct LoginSettings {
@@ -108,6 +298,67 @@ pub struct LoginSettings {
     pub attempts_seconds: i64,
     /// 連続ログイン試行許容最大回数（秒）
     pub max_attempts: u32,
+    /// 古いログイン失敗履歴を削除する間隔（秒）
+    #[serde(default = "default_login_failed_history_cleanup_interval_seconds")]
+    pub cleanup_interval_seconds: u64,
+    /// ログイン時にアカウントがロックされていることを明かすかどうか
+    ///
+    /// `false`の場合、ロックされたアカウントでログインを試みると、パスワードが一致しない場合と同じ応答を返す。
+    #[serde(default = "default_reveal_lock_status")]
+    pub reveal_lock_status: bool,
+    /// ログイン失敗によるロックを自動的に解除するまでの時間（秒）
+    ///
+    /// 指定した場合、ロックされてからこの時間が経過したユーザーは、ログインを試みたときにロックが解除される。
+    /// 管理者によるロックは自動的に解除しない。指定しない場合は、管理者がロックを解除するまでロックされたままとなる。
+    #[serde(default)]
+    pub lockout_duration_secs: Option<u64>,
+}
+
+impl Default for LoginSettings {
+    fn default() -> Self {
+        Self {
+            attempts_seconds: 10 * 60,
+            max_attempts: 5,
+            cleanup_interval_seconds: default_login_failed_history_cleanup_interval_seconds(),
+            reveal_lock_status: default_reveal_lock_status(),
+            lockout_duration_secs: None,
+        }
+    }
+}
+
+/// 古いログイン失敗履歴を削除する間隔の既定値（1時間）
+fn default_login_failed_history_cleanup_interval_seconds() -> u64 {
+    60 * 60
+}
+
+/// ログイン時にアカウントがロックされていることを明かすかどうかの既定値
+fn default_reveal_lock_status() -> bool {
+    true
+}
+
+impl LoginSettings {
+    /// 削除するログイン失敗履歴の基準日時を返す。
+    ///
+    /// 最初にログインを試行した日時が、連続ログイン試行許容時間の10倍以上前のログイン失敗履歴を古いとみなす。
+    pub fn stale_login_failed_history_cutoff(
+        &self,
+        now: time::OffsetDateTime,
+    ) -> time::OffsetDateTime {
+        now - time::Duration::seconds(self.attempts_seconds * 10)
+    }
+
+    /// ロックが期限切れとなる基準日時を返す。
+    ///
+    /// この日時以前にロックされたユーザーのロックは期限切れとみなす。ロックを自動的に解除しない場合は`None`を返す。
+    pub fn lockout_expired_cutoff(
+        &self,
+        now: time::OffsetDateTime,
+    ) -> Option<time::OffsetDateTime> {
+        // 日時の範囲を超えるほど長い時間が指定された場合は、ロックを自動的に解除しない
+        self.lockout_duration_secs
+            .and_then(|secs| i64::try_from(secs).ok())
+            .and_then(|secs| now.checked_sub(time::Duration::seconds(secs)))
+    }
 }
 
 /// トークン設定
@@ -117,8 +368,252 @@ pub struct TokenSettings {
     pub access_max_age: i64,
     /// リフレッシュトークンの有効期限（秒）
     pub refresh_max_age: i64,
+    /// 管理者がユーザーになりすますためのアクセストークンの有効期限（秒）
+    pub impersonation_max_age: i64,
+    /// Eメールアドレスの変更を確認するトークンの有効期限（秒）
+    pub email_change_max_age: i64,
     /// JWTシークレットキー
     pub jwt_secret: SecretString,
+    /// ユーザーが同時にログインできるセッションの最大数
+    ///
+    /// 0の場合は無制限である。ログインしたときに最大数を超えた場合は、古いセッションから削除する。
+    #[serde(default)]
+    pub max_sessions_per_user: u32,
+    /// アクセストークンのリフレッシュを促す残りの有効期間（秒）
+    ///
+    /// アクセストークンの残りの有効期間がこの値を下回った場合は、レスポンスに`X-Token-Refresh-Suggested`ヘッダーを
+    /// 付与する。
+    #[serde(default = "default_refresh_suggested_threshold")]
+    pub refresh_suggested_threshold: i64,
+    /// JWTの発行者（`iss`クレイム）
+    #[serde(default = "default_token_issuer")]
+    pub issuer: String,
+    /// 発行者（`iss`）とトークンの種類（`typ`）のクレイムを含まない、以前に発行されたJWTを受け付けるかどうか
+    ///
+    /// クレイムを追加する前に発行されたトークンを有効期限まで使用できるように、移行期間は`true`にする。
+    /// 以前に発行されたトークンの有効期限がすべて切れた後は、`false`にすること。
+    #[serde(default = "default_accept_legacy_tokens")]
+    pub accept_legacy_tokens: bool,
+}
+
+/// アクセストークンのリフレッシュを促す残りの有効期間の既定値（秒）
+fn default_refresh_suggested_threshold() -> i64 {
+    5 * 60
+}
+
+/// JWTの発行者の既定値
+fn default_token_issuer() -> String {
+    String::from("rusty-todo")
+}
+
+/// 以前に発行されたJWTを受け付けるかどうかの既定値
+fn default_accept_legacy_tokens() -> bool {
+    true
+}
+
+/// テスト用のプレースホルダーを設定したトークン設定を返す。
+///
+/// JWTシークレットキーはダミーの値であるため、本番環境では使用しないこと。
+impl Default for TokenSettings {
+    fn default() -> Self {
+        Self {
+            access_max_age: 3 * 60 * 60,
+            refresh_max_age: 24 * 60 * 60,
+            impersonation_max_age: 15 * 60,
+            email_change_max_age: 24 * 60 * 60,
+            jwt_secret: SecretString::new("placeholder-jwt-secret".into()),
+            max_sessions_per_user: 0,
+            refresh_suggested_threshold: default_refresh_suggested_threshold(),
+            issuer: default_token_issuer(),
+            accept_legacy_tokens: default_accept_legacy_tokens(),
+        }
+    }
+}
+
+/// Todo設定
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct TodoSettings {
+    /// 完了予定日が過去のTodoの作成を許可するかどうか
+    ///
+    /// 既存のデータを移行するときなど、完了予定日が過去のTodoを登録する必要がある場合に`true`にする。
+    #[serde(default)]
+    pub allow_past_due_date: bool,
+}
+
+/// セキュリティ設定
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct SecuritySettings {
+    /// 他のユーザーが所有するTodoの存在を隠すかどうか
+    ///
+    /// `true`の場合、他のユーザーが所有するTodoを取得しようとしたときに、Todoが存在しない場合と同様に
+    /// 404 Not Foundを返して、IDの列挙を防止する。`false`の場合は403 Forbiddenを返す。
+    #[serde(default = "default_hide_cross_user_existence")]
+    pub hide_cross_user_existence: bool,
+}
+
+impl Default for SecuritySettings {
+    fn default() -> Self {
+        Self {
+            hide_cross_user_existence: default_hide_cross_user_existence(),
+        }
+    }
+}
+
+/// 他のユーザーが所有するTodoの存在を隠すかどうかの既定値
+fn default_hide_cross_user_existence() -> bool {
+    true
+}
+
+/// アクセスログ設定
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AccessLogSettings {
+    /// アクセスログを出力するかどうか
+    #[serde(default)]
+    pub enabled: bool,
+    /// アクセスログの書式
+    #[serde(default)]
+    pub format: AccessLogFormat,
+    /// アクセスログを追記するファイルのパス
+    ///
+    /// 指定されていない場合は、標準出力に出力する。
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// アクセスログの書式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogFormat {
+    /// 1行に1つのJSONオブジェクト
+    #[default]
+    Json,
+    /// Combined Log Format
+    Combined,
+}
+
+/// 参照データ設定
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct LookupSettings {
+    /// ロールとTodo状態をキャッシュする秒数
+    ///
+    /// 0を指定した場合はキャッシュしない。
+    #[serde(default = "default_lookup_cache_seconds")]
+    pub cache_seconds: u64,
+}
+
+impl Default for LookupSettings {
+    fn default() -> Self {
+        Self {
+            cache_seconds: default_lookup_cache_seconds(),
+        }
+    }
+}
+
+/// 参照データをキャッシュする秒数の既定値
+fn default_lookup_cache_seconds() -> u64 {
+    300
+}
+
+impl LookupSettings {
+    /// 参照データのキャッシュの有効期間を返す。
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache_seconds)
+    }
+
+    /// 参照データのレスポンスに付与する`Cache-Control`ヘッダーの値を返す。
+    pub fn cache_control(&self) -> String {
+        if self.cache_seconds == 0 {
+            String::from("no-cache")
+        } else {
+            format!("public, max-age={}", self.cache_seconds)
+        }
+    }
+}
+
+/// 日次ダイジェスト設定
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DigestSettings {
+    /// 日次ダイジェストを作成するジョブを起動するかどうか
+    #[serde(default)]
+    pub enabled: bool,
+    /// 日次ダイジェストを作成する時（UTC、0から23）
+    #[serde(default = "default_digest_hour")]
+    pub hour: u8,
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hour: default_digest_hour(),
+        }
+    }
+}
+
+/// 日次ダイジェストを作成する時の既定値（UTC）
+fn default_digest_hour() -> u8 {
+    6
+}
+
+impl DigestSettings {
+    /// 指定された日時の後で、次に日次ダイジェストを作成する日時を返す。
+    ///
+    /// 23より大きい時が指定された場合は、23時に作成する。
+    pub fn next_run_at(&self, now: time::OffsetDateTime) -> time::OffsetDateTime {
+        let now = now.to_offset(time::UtcOffset::UTC);
+        let time = time::Time::from_hms(self.hour.min(23), 0, 0).unwrap();
+        let today = now.replace_time(time);
+        if now < today {
+            today
+        } else {
+            today + time::Duration::days(1)
+        }
+    }
+}
+
+/// メール設定
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MailSettings {
+    /// SMTPサーバー設定
+    ///
+    /// 指定されていない場合は、メールを送信せずにログに記録するため、Eメールアドレスを変更できない。
+    #[serde(default)]
+    pub smtp: Option<SmtpSettings>,
+}
+
+/// SMTPサーバー設定
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpSettings {
+    /// ホスト
+    pub host: String,
+    /// ポート番号
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    /// ユーザー名
+    ///
+    /// 指定されていない場合は、認証せずにメールを送信する。
+    #[serde(default)]
+    pub user: Option<String>,
+    /// パスワード
+    #[serde(default)]
+    pub password: Option<SecretString>,
+    /// 送信者のメールアドレス（`Rusty Todo <noreply@example.com>`の形式も指定できる）
+    pub from: String,
+    /// STARTTLSで通信を暗号化するかどうか
+    ///
+    /// 開発用のSMTPサーバーなど、暗号化に対応していないサーバーに送信する場合のみ`false`を指定すること。
+    #[serde(default = "default_smtp_starttls")]
+    pub starttls: bool,
+}
+
+/// SMTPサーバーのポート番号の既定値（サブミッションポート）
+fn default_smtp_port() -> u16 {
+    587
+}
+
+/// STARTTLSで通信を暗号化するかどうかの既定値
+fn default_smtp_starttls() -> bool {
+    true
 }
 
 /// Redis設定
@@ -128,6 +623,95 @@ pub struct RedisSettings {
     pub port: u16,
     /// ホスト
     pub host: String,
+    /// コネクションプールからコネクションを取得するときのタイムアウト（ミリ秒）
+    #[serde(default = "default_redis_connection_timeout_ms")]
+    pub connection_timeout_ms: u64,
+    /// サーキットブレーカー設定
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerSettings,
+    /// 論理データベースの番号
+    ///
+    /// 指定されていない場合は、Redisの既定のデータベース（`0`）を使用する。
+    #[serde(default)]
+    pub db: Option<u8>,
+    /// キーの接頭辞
+    ///
+    /// 複数の環境で同じRedisを共有する場合に、キーの衝突を避けるため指定する。
+    /// 指定した場合、キーは`<key_prefix>:<key>`の形式でRedisに記録される。
+    #[serde(default)]
+    pub key_prefix: Option<String>,
+}
+
+/// テスト用のプレースホルダーを設定したRedis設定を返す。
+impl Default for RedisSettings {
+    fn default() -> Self {
+        Self {
+            port: 6379,
+            host: String::from("127.0.0.1"),
+            connection_timeout_ms: default_redis_connection_timeout_ms(),
+            circuit_breaker: CircuitBreakerSettings::default(),
+            db: None,
+            key_prefix: None,
+        }
+    }
+}
+
+/// Redisのコネクションを取得するときのタイムアウトの既定値（ミリ秒）
+fn default_redis_connection_timeout_ms() -> u64 {
+    1_000
+}
+
+/// サーキットブレーカー設定
+///
+/// 時間窓の中で、Redisへの接続に連続して指定された回数失敗した場合、クールダウンの間はRedisに接続せずに失敗させる。
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CircuitBreakerSettings {
+    /// サーキットブレーカーを開く連続失敗回数（`0`の場合はサーキットブレーカーを開かない）
+    #[serde(default = "default_circuit_breaker_failure_threshold")]
+    pub failure_threshold: u32,
+    /// 連続失敗回数を数える時間窓（秒）
+    #[serde(default = "default_circuit_breaker_failure_window_secs")]
+    pub failure_window_secs: u64,
+    /// サーキットブレーカーを開いたままにする時間（秒）
+    #[serde(default = "default_circuit_breaker_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+impl Default for CircuitBreakerSettings {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_circuit_breaker_failure_threshold(),
+            failure_window_secs: default_circuit_breaker_failure_window_secs(),
+            cooldown_secs: default_circuit_breaker_cooldown_secs(),
+        }
+    }
+}
+
+/// サーキットブレーカーを開く連続失敗回数の既定値
+fn default_circuit_breaker_failure_threshold() -> u32 {
+    5
+}
+
+/// 連続失敗回数を数える時間窓の既定値（秒）
+fn default_circuit_breaker_failure_window_secs() -> u64 {
+    10
+}
+
+/// サーキットブレーカーを開いたままにする時間の既定値（秒）
+fn default_circuit_breaker_cooldown_secs() -> u64 {
+    30
+}
+
+impl CircuitBreakerSettings {
+    /// 連続失敗回数を数える時間窓を返す。
+    pub fn failure_window(&self) -> Duration {
+        Duration::from_secs(self.failure_window_secs)
+    }
+
+    /// サーキットブレーカーを開いたままにする時間を返す。
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.cooldown_secs)
+    }
 }
 
 impl HttpSettings {
@@ -135,6 +719,49 @@ impl HttpSettings {
     pub fn bind_address(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
+
+    /// リクエストタイムアウトを返す。
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    /// CORSのプリフライトリクエストの結果をキャッシュする期間を返す。
+    pub fn cors_max_age(&self) -> Duration {
+        Duration::from_secs(self.cors_max_age_secs)
+    }
+
+    /// HTTPサーバー設定を検証する。
+    fn validate(&self) -> anyhow::Result<()> {
+        for origin in &self.allowed_origins {
+            validate_origin(origin).with_context(|| {
+                format!("http.allowed_origins contains an invalid origin: {origin}")
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// CORSで許可するオリジンを検証する。
+///
+/// オリジンは`http`または`https`のスキームとホスト、任意のポート番号で構成され、パスやクエリ、ユーザー情報を
+/// 含んではならない。
+fn validate_origin(origin: &str) -> anyhow::Result<()> {
+    let uri = origin
+        .parse::<Uri>()
+        .context("The origin is not a valid URL")?;
+    let scheme = uri.scheme_str().context("The origin has no scheme")?;
+    if scheme != "http" && scheme != "https" {
+        anyhow::bail!("The scheme of the origin must be http or https");
+    }
+    let authority = uri.authority().context("The origin has no host")?;
+    if authority.host().is_empty() || authority.as_str().contains('@') {
+        anyhow::bail!("The origin must have a host and must not contain user information");
+    }
+    if origin != format!("{scheme}://{authority}") {
+        anyhow::bail!("The origin must not contain a path, a query or a trailing slash");
+    }
+    HeaderValue::from_str(origin).context("The origin is not a valid header value")?;
+    Ok(())
 }
 
 impl std::fmt::Display for HttpProtocol {
@@ -147,7 +774,29 @@ impl std::fmt::Display for HttpProtocol {
 }
 
 impl DatabaseSettings {
+    /// `lite`フィーチャーを無効にしてビルドした場合に、SQLiteが指定されていないか確認する。
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.engine == DatabaseEngine::Sqlite && !cfg!(feature = "lite") {
+            anyhow::bail!(
+                "database.engine is sqlite, but the application was built without the lite feature"
+            );
+        }
+        Ok(())
+    }
+
+    /// `pg_stat_activity`に表示するアプリケーション名を変更した設定を返す。
+    ///
+    /// コネクションプールが枯渇したときなどに、どのプロセスの接続かを識別するために使用する。
+    pub fn with_application_name(self, application_name: impl Into<String>) -> Self {
+        Self {
+            application_name: application_name.into(),
+            ..self
+        }
+    }
+
     /// データベースURIを返す。
+    ///
+    /// 実行時間が`slow_query_ms`を超えたクエリは、SQLの先頭部分と実行時間とともに警告ログに出力する。
     pub fn connect_options(&self) -> PgConnectOptions {
         let ssl_mode = if self.use_ssl {
             PgSslMode::Require
@@ -161,13 +810,42 @@ impl DatabaseSettings {
             .password(self.password.expose_secret())
             .database(&self.name)
             .ssl_mode(ssl_mode)
+            .application_name(&self.application_name)
+            .log_slow_statements(
+                log::LevelFilter::Warn,
+                Duration::from_millis(self.slow_query_ms),
+            )
     }
 }
 
 impl RedisSettings {
     /// RedisURIを返す。
+    ///
+    /// 論理データベースの番号が指定されている場合は、URIのパスに含める。
     pub fn uri(&self) -> String {
-        format!("redis://{}:{}", self.host, self.port)
+        match self.db {
+            Some(db) => format!("redis://{}:{}/{}", self.host, self.port, db),
+            None => format!("redis://{}:{}", self.host, self.port),
+        }
+    }
+
+    /// コネクションプールからコネクションを取得するときのタイムアウトを返す。
+    pub fn connection_timeout(&self) -> Duration {
+        Duration::from_millis(self.connection_timeout_ms)
+    }
+}
+
+/// ログレベルを表す文字列を解析する。
+///
+/// 大文字と小文字を区別しない。`tracing::Level`の`FromStr`と異なり、数値によるログレベルの指定は受け付けない。
+pub fn parse_log_level(s: &str) -> Option<LogLevel> {
+    match s.to_lowercase().as_str() {
+        "error" => Some(LogLevel::ERROR),
+        "warn" => Some(LogLevel::WARN),
+        "info" => Some(LogLevel::INFO),
+        "debug" => Some(LogLevel::DEBUG),
+        "trace" => Some(LogLevel::TRACE),
+        _ => None,
     }
 }
 
@@ -176,15 +854,161 @@ where
     D: Deserializer<'de>,
 {
     let v = String::deserialize(deserializer)?;
-    match v.to_lowercase().as_str() {
-        "error" => Ok(LogLevel::Error),
-        "warn" => Ok(LogLevel::Warn),
-        "info" => Ok(LogLevel::Info),
-        "debug" => Ok(LogLevel::Debug),
-        "trace" => Ok(LogLevel::Trace),
-        _ => Err(serde::de::Error::custom(format!(
-            "Invalid log level: {}",
-            v
-        ))),
+    parse_log_level(&v).ok_or_else(|| serde::de::Error::custom(format!("Invalid log level: {}", v)))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct LogLevelSettings {
+        #[serde(deserialize_with = "deserialize_log_level")]
+        log_level: LogLevel,
+    }
+
+    #[rstest::rstest]
+    #[case("error", LogLevel::ERROR)]
+    #[case("warn", LogLevel::WARN)]
+    #[case("info", LogLevel::INFO)]
+    #[case("debug", LogLevel::DEBUG)]
+    #[case("trace", LogLevel::TRACE)]
+    #[case("ERROR", LogLevel::ERROR)]
+    #[case("Warn", LogLevel::WARN)]
+    #[case("InFo", LogLevel::INFO)]
+    #[case("DEBUG", LogLevel::DEBUG)]
+    #[case("tRACE", LogLevel::TRACE)]
+    fn deserialize_log_level_ok(#[case] value: &str, #[case] expected: LogLevel) {
+        let json = format!(r#"{{"log_level": "{value}"}}"#);
+        let settings = serde_json::from_str::<LogLevelSettings>(&json).unwrap();
+        assert_eq!(settings.log_level, expected);
+    }
+
+    #[rstest::rstest]
+    #[case("http://localhost:5173")]
+    #[case("https://todo.example.com")]
+    #[case("https://todo.example.com:8443")]
+    fn validate_allowed_origins_ok(#[case] origin: &str) {
+        let mut settings = AppSettings::default();
+        settings.http.allowed_origins = vec![origin.to_string()];
+        assert!(settings.validate().is_ok(), "{origin}");
+    }
+
+    #[rstest::rstest]
+    #[case("")]
+    #[case("localhost:5173")]
+    #[case("ftp://localhost")]
+    #[case("http://localhost:5173/")]
+    #[case("http://localhost:5173/app")]
+    #[case("http://localhost:5173?q=1")]
+    #[case("http://user@localhost:5173")]
+    #[case("*")]
+    fn validate_allowed_origins_ng(#[case] origin: &str) {
+        let mut settings = AppSettings::default();
+        settings.http.allowed_origins =
+            vec![String::from("http://localhost:5173"), origin.to_string()];
+        assert!(settings.validate().is_err(), "{origin}");
+    }
+
+    #[test]
+    fn connect_options_set_application_name() {
+        let settings = DatabaseSettings::default();
+        let options = settings.connect_options();
+        assert_eq!(options.get_application_name(), Some("axum-sqlx-todo"));
+
+        let options = settings
+            .with_application_name("test_todo_db_1")
+            .connect_options();
+        assert_eq!(options.get_application_name(), Some("test_todo_db_1"));
+    }
+
+    #[test]
+    fn validate_database_engine() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.database.engine, DatabaseEngine::Postgres);
+        assert!(settings.validate().is_ok());
+
+        settings.database.engine = DatabaseEngine::Sqlite;
+        assert_eq!(settings.validate().is_ok(), cfg!(feature = "lite"));
+    }
+
+    #[test]
+    fn default_app_settings_use_loopback_addresses_and_placeholder_secrets() {
+        let settings = AppSettings::default();
+        assert_eq!(settings.http.bind_address(), "127.0.0.1:0");
+        assert_eq!(settings.database.host, "127.0.0.1");
+        assert_eq!(settings.redis.uri(), "redis://127.0.0.1:6379");
+        assert!(settings.redis.key_prefix.is_none());
+        assert!(
+            settings
+                .password
+                .pepper
+                .expose_secret()
+                .starts_with("placeholder-")
+        );
+        assert!(
+            settings
+                .token
+                .jwt_secret
+                .expose_secret()
+                .starts_with("placeholder-")
+        );
+    }
+
+    #[test]
+    fn redis_uri_contains_the_logical_database() {
+        let settings = RedisSettings {
+            db: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(settings.uri(), "redis://127.0.0.1:6379/2");
+    }
+
+    #[rstest::rstest]
+    #[case(datetime!(2025-06-19 05:59:59 UTC), datetime!(2025-06-19 06:00:00 UTC))]
+    #[case(datetime!(2025-06-19 06:00:00 UTC), datetime!(2025-06-20 06:00:00 UTC))]
+    #[case(datetime!(2025-06-19 23:30:00 UTC), datetime!(2025-06-20 06:00:00 UTC))]
+    #[case(datetime!(2025-06-19 14:00:00 +9), datetime!(2025-06-19 06:00:00 UTC))]
+    fn digest_settings_next_run_at(
+        #[case] now: time::OffsetDateTime,
+        #[case] expected: time::OffsetDateTime,
+    ) {
+        let settings = DigestSettings::default();
+        assert_eq!(settings.next_run_at(now), expected);
+    }
+
+    #[rstest::rstest]
+    #[case(None, None)]
+    #[case(Some(0), Some(datetime!(2025-06-19 06:00:00 UTC)))]
+    #[case(Some(900), Some(datetime!(2025-06-19 05:45:00 UTC)))]
+    #[case(Some(u64::MAX), None)]
+    fn login_settings_lockout_expired_cutoff(
+        #[case] lockout_duration_secs: Option<u64>,
+        #[case] expected: Option<time::OffsetDateTime>,
+    ) {
+        let settings = LoginSettings {
+            lockout_duration_secs,
+            ..Default::default()
+        };
+        let now = datetime!(2025-06-19 06:00:00 UTC);
+        assert_eq!(settings.lockout_expired_cutoff(now), expected);
+    }
+
+    #[rstest::rstest]
+    #[case("")]
+    #[case("warning")]
+    #[case("fatal")]
+    #[case(" info")]
+    #[case("1")]
+    fn deserialize_log_level_err(#[case] value: &str) {
+        let json = format!(r#"{{"log_level": "{value}"}}"#);
+        let err = serde_json::from_str::<LogLevelSettings>(&json).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&format!("Invalid log level: {value}")),
+            "{err}"
+        );
     }
 }
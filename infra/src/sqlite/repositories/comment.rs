@@ -0,0 +1,122 @@
+use uuid::{Uuid, fmt::Hyphenated};
+
+use domain::{
+    DomainError, DomainErrorKind, DomainResult,
+    models::{CommentBody, TodoComment, TodoCommentId, TodoId, UserId},
+    repositories::{TodoCommentListInput, TodoCommentRepository},
+};
+
+use super::{SqliteRepository, commit, from_micros, now, to_micros};
+
+pub type SqliteTodoCommentRepository = SqliteRepository<TodoComment>;
+
+#[async_trait::async_trait]
+impl TodoCommentRepository for SqliteTodoCommentRepository {
+    /// Todoのコメントを、作成日時の降順でリストする。
+    async fn list(&self, input: TodoCommentListInput) -> DomainResult<Vec<TodoComment>> {
+        sqlx::query_as::<_, TodoCommentRow>(
+            r#"
+            SELECT id, todo_id, user_id, body, created_at
+            FROM todo_comments
+            WHERE todo_id = ?1
+            ORDER BY created_at DESC, id
+            LIMIT ?2 OFFSET ?3
+            "#,
+        )
+        .bind(input.todo_id.0.hyphenated())
+        .bind(input.page_size as i64)
+        .bind(input.offset() as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(TodoComment::try_from)
+        .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// コメントを取得する。
+    async fn by_id(&self, id: TodoCommentId) -> DomainResult<Option<TodoComment>> {
+        let row = sqlx::query_as::<_, TodoCommentRow>(
+            r#"
+            SELECT id, todo_id, user_id, body, created_at
+            FROM todo_comments
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(TodoComment::try_from).transpose()
+    }
+
+    /// Todoにコメントを登録する。
+    async fn create(
+        &self,
+        todo_id: TodoId,
+        user_id: UserId,
+        body: CommentBody,
+    ) -> DomainResult<TodoComment> {
+        let mut tx = self.begin().await?;
+        let row = sqlx::query_as::<_, TodoCommentRow>(
+            r#"
+            INSERT INTO todo_comments (id, todo_id, user_id, body, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            RETURNING id, todo_id, user_id, body, created_at
+            "#,
+        )
+        .bind(Uuid::new_v4().hyphenated())
+        .bind(todo_id.0.hyphenated())
+        .bind(user_id.0.hyphenated())
+        .bind(body.0)
+        .bind(to_micros(now()))
+        .fetch_one(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        TodoComment::try_from(row)
+    }
+
+    /// コメントを削除する。
+    async fn delete(&self, id: TodoCommentId) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            DELETE FROM todo_comments
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .execute(&mut *tx)
+        .await?;
+        if query_result.rows_affected() == 0 {
+            let message = format!("Comment with id {} not found", id);
+            return Err(DomainError {
+                kind: DomainErrorKind::NotFound,
+                messages: vec![message.clone().into()],
+                source: anyhow::anyhow!(message),
+            });
+        }
+        commit(tx).await
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct TodoCommentRow {
+    id: Hyphenated,
+    todo_id: Hyphenated,
+    user_id: Hyphenated,
+    body: String,
+    created_at: i64,
+}
+
+impl TryFrom<TodoCommentRow> for TodoComment {
+    type Error = DomainError;
+
+    fn try_from(row: TodoCommentRow) -> Result<Self, Self::Error> {
+        Ok(TodoComment {
+            id: row.id.into_uuid().into(),
+            todo_id: row.todo_id.into_uuid().into(),
+            user_id: row.user_id.into_uuid().into(),
+            body: row.body.try_into()?,
+            created_at: from_micros(row.created_at)?,
+        })
+    }
+}
@@ -0,0 +1,127 @@
+use time::Date;
+use uuid::fmt::Hyphenated;
+
+use domain::{
+    DomainError, DomainErrorKind, DomainResult, REPOSITORY_ERROR_MESSAGE,
+    models::{DigestTodo, UserDigest, UserId},
+    repositories::DailyDigestRepository,
+};
+
+use super::{SqliteRepository, commit, now, to_micros};
+
+pub type SqliteDailyDigestRepository = SqliteRepository<UserDigest>;
+
+#[async_trait::async_trait]
+impl DailyDigestRepository for SqliteDailyDigestRepository {
+    /// 対象日の日次ダイジェストを登録する。
+    async fn upsert(&self, date: Date, digests: &[UserDigest]) -> DomainResult<()> {
+        let now = to_micros(now());
+        // SQLiteは配列をバインドできないため、ユーザーIDをJSON配列として渡して展開する
+        let user_ids = digests
+            .iter()
+            .map(|digest| digest.user_id.0.hyphenated().to_string())
+            .collect::<Vec<_>>();
+        let user_ids = serde_json::to_string(&user_ids).map_err(|e| DomainError {
+            kind: DomainErrorKind::Unexpected,
+            messages: vec!["Failed to serialize the daily digest".into()],
+            source: e.into(),
+        })?;
+        let mut tx = self.begin().await?;
+        // 対象のTodoがなくなったユーザーの対象日のダイジェストを削除
+        sqlx::query(
+            r#"
+            DELETE FROM daily_digests
+            WHERE digest_date = ?1
+                AND user_id NOT IN (SELECT value FROM json_each(?2))
+            "#,
+        )
+        .bind(date)
+        .bind(user_ids)
+        .execute(&mut *tx)
+        .await?;
+        for digest in digests {
+            sqlx::query(
+                r#"
+                INSERT INTO daily_digests (
+                    user_id, digest_date, due_today, overdue, created_at, updated_at
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+                ON CONFLICT (user_id, digest_date) DO UPDATE
+                SET
+                    due_today = excluded.due_today,
+                    overdue = excluded.overdue,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(digest.user_id.0.hyphenated())
+            .bind(date)
+            .bind(digest_todos_to_json(&digest.due_today)?)
+            .bind(digest_todos_to_json(&digest.overdue)?)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        commit(tx).await
+    }
+
+    /// ユーザーの指定された対象日の日次ダイジェストを取得する。
+    async fn by_user_and_date(
+        &self,
+        user_id: UserId,
+        date: Date,
+    ) -> DomainResult<Option<UserDigest>> {
+        let row = sqlx::query_as::<_, DailyDigestRow>(
+            r#"
+            SELECT user_id, digest_date, due_today, overdue
+            FROM daily_digests
+            WHERE user_id = ?1 AND digest_date = ?2
+            "#,
+        )
+        .bind(user_id.0.hyphenated())
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(UserDigest::try_from).transpose()
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DailyDigestRow {
+    user_id: Hyphenated,
+    digest_date: Date,
+    due_today: String,
+    overdue: String,
+}
+
+impl TryFrom<DailyDigestRow> for UserDigest {
+    type Error = DomainError;
+
+    fn try_from(row: DailyDigestRow) -> Result<Self, Self::Error> {
+        Ok(UserDigest {
+            user_id: UserId::from(row.user_id.into_uuid()),
+            digest_date: row.digest_date,
+            due_today: digest_todos_from_json(&row.due_today)?,
+            overdue: digest_todos_from_json(&row.overdue)?,
+        })
+    }
+}
+
+/// ダイジェストに含めるTodoを、データベースに保存するJSONにシリアライズする。
+fn digest_todos_to_json(todos: &[DigestTodo]) -> DomainResult<String> {
+    serde_json::to_string(todos).map_err(|e| DomainError {
+        kind: DomainErrorKind::Unexpected,
+        messages: vec!["Failed to serialize the daily digest".into()],
+        source: e.into(),
+    })
+}
+
+/// データベースに保存されたダイジェストに含めるTodoをデシリアライズする。
+///
+/// 保存されたJSONが不正な場合は、リポジトリエラーを返す。
+fn digest_todos_from_json(value: &str) -> DomainResult<Vec<DigestTodo>> {
+    serde_json::from_str(value).map_err(|e| DomainError {
+        kind: DomainErrorKind::Repository,
+        messages: vec![REPOSITORY_ERROR_MESSAGE.into()],
+        source: anyhow::Error::new(e).context("Stored daily digest is malformed"),
+    })
+}
@@ -0,0 +1,119 @@
+use sqlx::SqlitePool;
+
+use domain::{
+    DomainError, DomainResult,
+    models::{
+        Role, RoleCode, RoleName, TodoStatus, TodoStatusCode, TodoStatusName,
+        primitives::{Description, DisplayOrder},
+    },
+    repositories::LookupRepository,
+};
+
+use super::from_micros;
+
+macro_rules! sqlite_lookup_repository {
+    ($name:ident, $entity:ty, $code:ty, $code_ty: ty, $row:ty, $table:literal) => {
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            pub pool: SqlitePool,
+        }
+
+        #[async_trait::async_trait]
+        impl LookupRepository for $name {
+            type Entity = $entity;
+            type Code = $code;
+
+            async fn list(&self) -> DomainResult<Vec<Self::Entity>> {
+                sqlx::query_as::<_, $row>(&format!(
+                    r#"
+                    SELECT code, name, description, display_order, created_at, updated_at
+                    FROM {}
+                    ORDER BY display_order
+                    "#,
+                    $table
+                ))
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .map(<$entity>::try_from)
+                .collect::<Result<Vec<_>, _>>()
+            }
+
+            async fn by_code(&self, code: &Self::Code) -> DomainResult<Option<Self::Entity>> {
+                sqlx::query_as::<_, $row>(&format!(
+                    r#"
+                    SELECT code, name, description, display_order, created_at, updated_at
+                    FROM {}
+                    WHERE code = ?1
+                    "#,
+                    $table
+                ))
+                .bind(*code as $code_ty)
+                .fetch_optional(&self.pool)
+                .await?
+                .map(<$entity>::try_from)
+                .transpose()
+            }
+        }
+    };
+}
+
+sqlite_lookup_repository!(SqliteRoleRepository, Role, RoleCode, i16, RoleRow, "roles");
+sqlite_lookup_repository!(
+    SqliteTodoStatusRepository,
+    TodoStatus,
+    TodoStatusCode,
+    i16,
+    TodoStatusRow,
+    "todo_statuses"
+);
+
+#[derive(Debug, sqlx::FromRow)]
+struct RoleRow {
+    code: i16,
+    name: String,
+    description: Option<String>,
+    display_order: i16,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl TryFrom<RoleRow> for Role {
+    type Error = DomainError;
+
+    fn try_from(row: RoleRow) -> Result<Self, Self::Error> {
+        Ok(Role {
+            code: RoleCode::try_from(row.code)?,
+            name: RoleName::new(row.name)?,
+            description: row.description.map(Description::new).transpose()?,
+            display_order: DisplayOrder(row.display_order),
+            created_at: from_micros(row.created_at)?,
+            updated_at: from_micros(row.updated_at)?,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct TodoStatusRow {
+    code: i16,
+    name: String,
+    description: Option<String>,
+    display_order: i16,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl TryFrom<TodoStatusRow> for TodoStatus {
+    type Error = DomainError;
+
+    fn try_from(row: TodoStatusRow) -> Result<Self, Self::Error> {
+        Ok(TodoStatus {
+            code: TodoStatusCode::try_from(row.code)?,
+            name: TodoStatusName::new(row.name)?,
+            description: row.description.map(Description::new).transpose()?,
+            display_order: DisplayOrder(row.display_order),
+            created_at: from_micros(row.created_at)?,
+            updated_at: from_micros(row.updated_at)?,
+        })
+    }
+}
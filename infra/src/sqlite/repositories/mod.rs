@@ -0,0 +1,102 @@
+mod comment;
+mod digest;
+mod lookup;
+mod todo;
+mod user;
+
+pub use comment::*;
+pub use digest::*;
+pub use lookup::*;
+pub use todo::*;
+pub use user::*;
+
+use std::marker::PhantomData;
+
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use time::OffsetDateTime;
+
+use domain::{DomainError, DomainErrorKind, DomainResult, REPOSITORY_ERROR_MESSAGE};
+
+/// SQLiteトランザクション
+pub type SqliteTransaction<'a> = Transaction<'a, Sqlite>;
+
+/// SQLiteリポジトリ
+///
+/// PostgreSQLリポジトリと同じ結果を返すように、UUIDを小文字のハイフン区切りの文字列、日付を`YYYY-MM-DD`形式の
+/// 文字列、日時をUNIX時間（マイクロ秒）の整数で保存する。
+#[derive(Debug, Clone)]
+pub struct SqliteRepository<T> {
+    pool: SqlitePool,
+    _marker: PhantomData<T>,
+}
+
+impl<T> SqliteRepository<T> {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            _marker: PhantomData,
+        }
+    }
+
+    /// トランザクションを開始する。
+    ///
+    /// # 戻り値
+    ///
+    /// トランザクション
+    pub async fn begin(&self) -> DomainResult<SqliteTransaction<'_>> {
+        self.pool.begin().await.map_err(DomainError::from)
+    }
+}
+
+/// トランザクションをコミットする。
+///
+/// # 引数
+///
+/// * `tx`: トランザクション
+pub async fn commit(tx: SqliteTransaction<'_>) -> DomainResult<()> {
+    tx.commit().await.map_err(DomainError::from)
+}
+
+/// 現在日時を、PostgreSQLの`TIMESTAMP WITH TIME ZONE`と同じマイクロ秒の精度で返す。
+///
+/// SQLiteには`updated_at`を更新するトリガーがないため、リポジトリが作成日時と更新日時に設定する。
+fn now() -> OffsetDateTime {
+    let now = OffsetDateTime::now_utc();
+    now.replace_nanosecond(now.nanosecond() / 1_000 * 1_000)
+        .unwrap()
+}
+
+/// 日時を、SQLiteに保存するUNIX時間（マイクロ秒）に変換する。
+fn to_micros(value: OffsetDateTime) -> i64 {
+    (value.unix_timestamp_nanos() / 1_000) as i64
+}
+
+/// SQLiteに保存されたUNIX時間（マイクロ秒）を、日時に変換する。
+///
+/// 日時で表現できない値が保存されている場合は、リポジトリエラーを返す。
+fn from_micros(value: i64) -> DomainResult<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp_nanos(value as i128 * 1_000).map_err(|e| DomainError {
+        kind: DomainErrorKind::Repository,
+        messages: vec![REPOSITORY_ERROR_MESSAGE.into()],
+        source: anyhow::Error::new(e).context("Stored timestamp is out of range"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn micros_round_trip() {
+        let value = datetime!(2025-06-19 05:59:59.123456 UTC);
+        assert_eq!(to_micros(value), 1_750_312_799_123_456);
+        assert_eq!(from_micros(to_micros(value)).unwrap(), value);
+    }
+
+    #[test]
+    fn now_is_truncated_to_microseconds() {
+        assert_eq!(now().nanosecond() % 1_000, 0);
+    }
+}
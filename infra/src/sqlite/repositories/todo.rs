@@ -0,0 +1,729 @@
+use sqlx::{Sqlite, SqliteExecutor};
+use time::{Date, OffsetDateTime};
+use uuid::{Uuid, fmt::Hyphenated};
+
+use domain::{
+    DomainError, DomainErrorKind, DomainResult, domain_error,
+    models::{
+        COMPLETABLE_TODO_STATUS_CODES, DigestTodo, LockReason, Role, RoleCode, Todo, TodoId,
+        TodoStatus, TodoStatusCode, TodoTitle, User, UserDigest, UserId, primitives::DisplayOrder,
+    },
+    repositories::{
+        ArchivedFilter, TodoCreateInput, TodoImportInput, TodoListCursor, TodoListInput,
+        TodoListSort, TodoListView, TodoRepository, TodoUpdateInput,
+    },
+};
+use utils::time::DATE_FORMAT;
+
+use super::{SqliteRepository, SqliteTransaction, commit, from_micros, now, to_micros};
+use crate::postgres::repositories::{
+    keyword_like_pattern, list_limit_clause, list_order_by_clause,
+};
+
+pub type SqliteTodoRepository = SqliteRepository<Todo>;
+
+/// Todoとユーザー、ロール及びTodoの状態を結合して取得するSELECT文
+const TODO_SELECT: &str = r#"
+    SELECT
+        t.id, t.user_id,
+        u.family_name, u.given_name, u.email,
+        u.role_code, r.name role_name, r.description role_description, r.display_order role_display_order,
+        r.created_at role_created_at, r.updated_at role_updated_at,
+        u.active, u.last_login_at, u.locked_at, u.lock_reason, u.locale, u.created_at user_created_at, u.updated_at user_updated_at,
+        t.title, t.description,
+        t.todo_status_code, ts.name todo_status_name, ts.description todo_status_description,
+        ts.display_order todo_status_display_order, ts.created_at todo_status_created_at, ts.updated_at todo_status_updated_at,
+        t.due_date, t.completed_at, t.archived, t.created_at, t.updated_at
+    FROM todos t
+    INNER JOIN users u ON t.user_id = u.id
+    INNER JOIN roles r ON u.role_code = r.code
+    INNER JOIN todo_statuses ts ON t.todo_status_code = ts.code
+"#;
+
+#[async_trait::async_trait]
+impl TodoRepository for SqliteTodoRepository {
+    /// Todoをリストする。
+    async fn list(&self, input: TodoListInput) -> DomainResult<Vec<Todo>> {
+        let sql = format!(
+            "{}{}\n{}{}",
+            TODO_SELECT,
+            list_where_clause(&input, "t"),
+            list_order_by_clause(input.sort, input.view, "t"),
+            list_limit_clause(&input)
+        );
+        let mut query = sqlx::query_as::<Sqlite, TodoRow>(sql.as_str());
+        if let Some(keyword) = &input.keyword {
+            query = query.bind(keyword_like_pattern(keyword));
+        }
+        query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(Todo::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Todoを取得する。
+    async fn by_id(&self, id: TodoId) -> DomainResult<Option<Todo>> {
+        todo_by_id(&self.pool, id).await
+    }
+
+    // Todoを新規作成する。
+    async fn create(&self, user_id: UserId, input: TodoCreateInput) -> DomainResult<Todo> {
+        let id = TodoId::from(Uuid::new_v4());
+        let mut tx = self.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO todos (
+                id, user_id, title, description, due_date, completed_at, created_at, updated_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, NULL, ?6, ?6)
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .bind(user_id.0.hyphenated())
+        .bind(input.title.0)
+        .bind(input.description.map(|d| d.0))
+        .bind(input.due_date)
+        .bind(to_micros(now()))
+        .execute(&mut *tx)
+        .await?;
+        todo_commit(tx, id).await
+    }
+
+    /// ユーザーの完了しておらず、アーカイブされていないTodoのうち、タイトルが大文字と小文字を区別せずに
+    /// 一致するTodoをリストする。
+    ///
+    /// SQLiteの`LOWER`関数は、ASCII文字のみを小文字に変換する。
+    async fn find_active_by_title(
+        &self,
+        user_id: UserId,
+        title: &TodoTitle,
+    ) -> DomainResult<Vec<Todo>> {
+        let sql = format!(
+            r#"
+            {TODO_SELECT}
+            WHERE
+                t.user_id = ?1
+                AND NOT t.archived
+                AND t.todo_status_code <> ?2
+                AND LOWER(t.title) = LOWER(?3)
+            ORDER BY t.created_at, t.id
+            "#
+        );
+        sqlx::query_as::<_, TodoRow>(&sql)
+            .bind(user_id.0.hyphenated())
+            .bind(TodoStatusCode::Completed as i16)
+            .bind(&title.0)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(Todo::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Todoを更新する。
+    ///
+    /// 説明と完了予定日は、`Some(None)`が指定された場合に消去する。
+    async fn update(&self, id: TodoId, todo: TodoUpdateInput) -> DomainResult<Todo> {
+        let update_description = todo.description.is_some();
+        let update_due_date = todo.due_date.is_some();
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            UPDATE todos
+            SET
+                title = COALESCE(?1, title),
+                description = CASE WHEN ?6 THEN ?2 ELSE description END,
+                todo_status_code = COALESCE(?3, todo_status_code),
+                due_date = CASE WHEN ?7 THEN ?4 ELSE due_date END,
+                updated_at = ?8
+            WHERE id = ?5
+            "#,
+        )
+        .bind(todo.title.map(|t| t.0))
+        .bind(todo.description.flatten().map(|d| d.0))
+        .bind(todo.status_code.map(|c| c as i16))
+        .bind(todo.due_date.flatten())
+        .bind(id.0.hyphenated())
+        .bind(update_description)
+        .bind(update_due_date)
+        .bind(to_micros(now()))
+        .execute(&mut *tx)
+        .await?;
+        match query_result.rows_affected() {
+            0 => todo_not_found(id),
+            _ => todo_commit(tx, id).await,
+        }
+    }
+
+    /// Todoを完了する。
+    ///
+    /// 同時に完了されることを防ぐため、未着手または進行中で、アーカイブされていないTodoのみを更新する。
+    async fn complete(&self, id: TodoId) -> DomainResult<Todo> {
+        let completable = COMPLETABLE_TODO_STATUS_CODES
+            .map(|code| (code as i16).to_string())
+            .join(", ");
+        let mut tx = self.begin().await?;
+        // 完了日時と更新日時が等しくなるように、同じ日時を設定
+        let query_result = sqlx::query(&format!(
+            r#"
+            UPDATE todos
+            SET
+                todo_status_code = ?1,
+                completed_at = ?3,
+                updated_at = ?3
+            WHERE
+                id = ?2
+                AND archived = FALSE
+                AND todo_status_code IN ({completable})
+            "#
+        ))
+        .bind(TodoStatusCode::Completed as i16)
+        .bind(id.0.hyphenated())
+        .bind(to_micros(now()))
+        .execute(&mut *tx)
+        .await?;
+        match query_result.rows_affected() {
+            0 => todo_not_updated(tx, id, Todo::ensure_completable).await,
+            _ => todo_commit(tx, id).await,
+        }
+    }
+
+    /// 完了状態のTodoを他の状態に変更する。
+    ///
+    /// 同時に再開されることを防ぐため、完了していて、アーカイブされていないTodoのみを更新する。
+    async fn reopen(&self, id: TodoId, status: TodoStatusCode) -> DomainResult<Todo> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            UPDATE todos
+            SET
+                todo_status_code = ?1,
+                completed_at = NULL,
+                updated_at = ?4
+            WHERE
+                id = ?2
+                AND archived = FALSE
+                AND todo_status_code = ?3
+            "#,
+        )
+        .bind(status as i16)
+        .bind(id.0.hyphenated())
+        .bind(TodoStatusCode::Completed as i16)
+        .bind(to_micros(now()))
+        .execute(&mut *tx)
+        .await?;
+        match query_result.rows_affected() {
+            0 => todo_not_updated(tx, id, Todo::ensure_reopenable).await,
+            _ => todo_commit(tx, id).await,
+        }
+    }
+
+    /// Todoをアーカイブする。
+    ///
+    /// アーカイブ状態が指定された状態と異なるTodoのみを更新する。
+    async fn archive(&self, id: TodoId, archived: bool) -> DomainResult<Todo> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            UPDATE todos
+            SET
+                archived = ?1,
+                updated_at = ?3
+            WHERE
+                id = ?2
+                AND archived <> ?1
+            "#,
+        )
+        .bind(archived)
+        .bind(id.0.hyphenated())
+        .bind(to_micros(now()))
+        .execute(&mut *tx)
+        .await?;
+        match query_result.rows_affected() {
+            0 => todo_not_updated(tx, id, |todo| todo.ensure_archivable(archived)).await,
+            _ => todo_commit(tx, id).await,
+        }
+    }
+
+    /// Todoを削除する
+    async fn delete(&self, id: TodoId) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            DELETE FROM todos
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .execute(&mut *tx)
+        .await?;
+        match query_result.rows_affected() {
+            0 => todo_not_found(id),
+            _ => commit(tx).await,
+        }
+    }
+
+    /// ユーザーのTodoの所有者を、他のユーザーに変更する。
+    async fn transfer_ownership(
+        &self,
+        from: UserId,
+        to: UserId,
+        include_completed: bool,
+    ) -> DomainResult<u64> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            UPDATE todos
+            SET
+                user_id = ?2,
+                updated_at = ?5
+            WHERE
+                user_id = ?1
+                AND (?3 OR todo_status_code <> ?4)
+            "#,
+        )
+        .bind(from.0.hyphenated())
+        .bind(to.0.hyphenated())
+        .bind(include_completed)
+        .bind(TodoStatusCode::Completed as i16)
+        .bind(to_micros(now()))
+        .execute(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        Ok(query_result.rows_affected())
+    }
+
+    /// アーカイブされたTodoを含めて、ユーザーのすべてのTodoをリストする。
+    async fn list_all_including_archived(&self, user_id: UserId) -> DomainResult<Vec<Todo>> {
+        let sql = format!("{TODO_SELECT} WHERE t.user_id = ?1 ORDER BY t.created_at, t.id");
+        sqlx::query_as::<_, TodoRow>(&sql)
+            .bind(user_id.0.hyphenated())
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(Todo::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// すべてのユーザーについて、指定された日付の日次ダイジェストを作成する。
+    async fn due_digest(&self, date: Date) -> DomainResult<Vec<UserDigest>> {
+        let completable = COMPLETABLE_TODO_STATUS_CODES
+            .map(|code| (code as i16).to_string())
+            .join(", ");
+        let rows = sqlx::query_as::<_, DigestTodoRow>(&format!(
+            r#"
+            SELECT t.user_id, t.id, t.title, t.due_date
+            FROM todos t
+            INNER JOIN users u ON t.user_id = u.id
+            WHERE
+                u.active
+                AND NOT t.archived
+                AND t.todo_status_code IN ({completable})
+                AND t.due_date <= ?1
+            ORDER BY t.user_id, t.due_date, t.created_at, t.id
+            "#
+        ))
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+        // ユーザーIDの順に並んでいるため、連続する行を同じユーザーのダイジェストにまとめる
+        let mut digests: Vec<UserDigest> = vec![];
+        for row in rows {
+            let user_id = UserId::from(row.user_id.into_uuid());
+            if digests
+                .last()
+                .is_none_or(|digest| digest.user_id != user_id)
+            {
+                digests.push(UserDigest::new(user_id, date));
+            }
+            digests.last_mut().unwrap().push(DigestTodo {
+                id: TodoId::from(row.id.into_uuid()),
+                title: TodoTitle::new(row.title)?,
+                due_date: row.due_date,
+            });
+        }
+        Ok(digests)
+    }
+
+    /// Todoを一括で登録する。
+    ///
+    /// SQLiteは書き込みを直列化するため、同じユーザーへのインポートが同時に実行されても、
+    /// 同じTodoを重複して登録しない。
+    async fn import(&self, user_id: UserId, inputs: Vec<TodoImportInput>) -> DomainResult<u64> {
+        if inputs.is_empty() {
+            return Ok(0);
+        }
+        let mut tx = self.begin().await?;
+        let mut rows_affected = 0;
+        for input in inputs {
+            // タイトルと完了予定日が同じTodoが既に存在する場合は登録しない
+            let query_result = sqlx::query(
+                r#"
+                INSERT INTO todos (
+                    id, user_id, title, description, todo_status_code,
+                    due_date, completed_at, archived, created_at, updated_at
+                )
+                SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10
+                WHERE NOT EXISTS (
+                    SELECT 1
+                    FROM todos t
+                    WHERE
+                        t.user_id = ?2
+                        AND t.title = ?3
+                        AND t.due_date IS ?6
+                )
+                "#,
+            )
+            .bind(Uuid::new_v4().hyphenated())
+            .bind(user_id.0.hyphenated())
+            .bind(input.title.0)
+            .bind(input.description.map(|d| d.0))
+            .bind(input.status_code as i16)
+            .bind(input.due_date)
+            .bind(input.completed_at.map(to_micros))
+            .bind(input.archived)
+            .bind(to_micros(input.created_at))
+            .bind(to_micros(input.updated_at))
+            .execute(&mut *tx)
+            .await?;
+            rows_affected += query_result.rows_affected();
+        }
+        commit(tx).await?;
+        Ok(rows_affected)
+    }
+}
+
+/// Todoをリストするときの条件を示すWHERE句を生成する。
+///
+/// キーワードはSQLに埋め込まず、`?1`にバインドする。バインドする値は`keyword_like_pattern`で生成すること。
+/// SQLiteの`LIKE`演算子は、ASCII文字の大文字と小文字を区別しない。
+fn list_where_clause(input: &TodoListInput, todos_table: &str) -> String {
+    let mut condition = format!(
+        "WHERE {}.user_id = '{}'",
+        todos_table,
+        input.user_id.0.hyphenated()
+    );
+    if input.keyword.is_some() {
+        condition.push_str(&format!(
+            " AND ({0}.title LIKE ?1 ESCAPE '\\' OR {0}.description LIKE ?1 ESCAPE '\\')",
+            todos_table,
+        ));
+    }
+    if let Some(filter) = &input.filter {
+        let due_date_condition = filter.sql(&format!("{}.due_date", todos_table));
+        condition.push_str(&format!(" AND {due_date_condition}"));
+    }
+    // 状態コードが指定されている場合は、表示範囲より状態コードを優先
+    match (&input.statuses, input.view) {
+        (Some(statuses), _) => condition.push_str(&format!(
+            " AND {}.todo_status_code IN ({})",
+            todos_table,
+            statuses
+                .iter()
+                .map(|s| (*s as i16).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        (None, TodoListView::Active) => condition.push_str(&format!(
+            " AND {}.todo_status_code <> {}",
+            todos_table,
+            TodoStatusCode::Completed as i16
+        )),
+        (None, TodoListView::Completed) => condition.push_str(&format!(
+            " AND {}.todo_status_code = {}",
+            todos_table,
+            TodoStatusCode::Completed as i16
+        )),
+        (None, TodoListView::All) => {}
+    }
+    match input.archived {
+        ArchivedFilter::Active => {
+            condition.push_str(&format!(" AND {todos_table}.archived = FALSE"))
+        }
+        ArchivedFilter::Archived => {
+            condition.push_str(&format!(" AND {todos_table}.archived = TRUE"))
+        }
+        ArchivedFilter::All => {}
+    }
+    if let Some(cursor) = input
+        .keyset
+        .as_ref()
+        .and_then(|keyset| keyset.after.as_ref())
+    {
+        condition.push_str(&format!(
+            " AND {}",
+            list_keyset_condition(cursor, input.sort, input.view, todos_table)
+        ));
+    }
+    condition.push(' ');
+    condition
+}
+
+/// カーソルが示すTodoより後に並ぶTodoを絞り込む条件を生成する。
+///
+/// PostgreSQLリポジトリと同じ条件を生成するが、日時はUNIX時間（マイクロ秒）の整数で比較する。
+fn list_keyset_condition(
+    cursor: &TodoListCursor,
+    sort: TodoListSort,
+    view: TodoListView,
+    todos_table: &str,
+) -> String {
+    // 並び順のキーごとに、カーソルより後に並ぶ条件と、カーソルと等しい条件を格納
+    let mut keys: Vec<(String, String)> = vec![];
+    if view == TodoListView::All {
+        // 完了していないTodoを、完了したTodoより前に並べる
+        let completed = format!(
+            "({}.todo_status_code = {})",
+            todos_table,
+            TodoStatusCode::Completed as i16
+        );
+        let after = match cursor.completed {
+            true => String::from("FALSE"),
+            false => completed.clone(),
+        };
+        keys.push((after, format!("{} = {}", completed, cursor.completed)));
+    }
+    let due_date = match cursor.due_date {
+        Some(due_date) => {
+            let due_date = due_date.format(&DATE_FORMAT).unwrap();
+            (
+                format!(
+                    "({0}.due_date > '{1}' OR {0}.due_date IS NULL)",
+                    todos_table, due_date
+                ),
+                format!("{}.due_date = '{}'", todos_table, due_date),
+            )
+        }
+        // 完了予定日がないTodoは最後に並ぶ
+        None => (
+            String::from("FALSE"),
+            format!("{}.due_date IS NULL", todos_table),
+        ),
+    };
+    let descending = |column: &str, value: OffsetDateTime| {
+        let value = to_micros(value);
+        (
+            format!("{}.{} < {}", todos_table, column, value),
+            format!("{}.{} = {}", todos_table, column, value),
+        )
+    };
+    match sort {
+        TodoListSort::DueDate => {
+            keys.push(due_date);
+            keys.push(descending("updated_at", cursor.updated_at));
+            keys.push(descending("created_at", cursor.created_at));
+        }
+        TodoListSort::CreatedAt => keys.push(descending("created_at", cursor.created_at)),
+        TodoListSort::UpdatedAt => {
+            keys.push(descending("updated_at", cursor.updated_at));
+            keys.push(descending("created_at", cursor.created_at));
+        }
+    }
+    let id = cursor.id.0.hyphenated();
+    keys.push((
+        format!("{}.id > '{}'", todos_table, id),
+        format!("{}.id = '{}'", todos_table, id),
+    ));
+
+    let mut equals: Vec<String> = vec![];
+    let mut conditions: Vec<String> = vec![];
+    for (after, equal) in keys {
+        let mut condition = equals.clone();
+        condition.push(after);
+        conditions.push(format!("({})", condition.join(" AND ")));
+        equals.push(equal);
+    }
+    format!("({})", conditions.join(" OR "))
+}
+
+/// Todoを取得する。
+async fn todo_by_id<'e, E>(executor: E, id: TodoId) -> DomainResult<Option<Todo>>
+where
+    E: SqliteExecutor<'e>,
+{
+    let sql = format!("{TODO_SELECT} WHERE t.id = ?1");
+    sqlx::query_as::<_, TodoRow>(&sql)
+        .bind(id.0.hyphenated())
+        .fetch_optional(executor)
+        .await?
+        .map(Todo::try_from)
+        .transpose()
+}
+
+/// 登録または更新したTodoを取得して、トランザクションをコミットする。
+///
+/// SQLiteの`RETURNING`句は他のテーブルと結合できないため、登録または更新した後にTodoを取得する。
+async fn todo_commit(mut tx: SqliteTransaction<'_>, id: TodoId) -> DomainResult<Todo> {
+    let Some(todo) = todo_by_id(&mut *tx, id).await? else {
+        return todo_not_found(id);
+    };
+    commit(tx).await?;
+    Ok(todo)
+}
+
+/// 条件付きの更新で、Todoが更新されなかった場合のエラーを返す。
+///
+/// Todoが存在しない場合は、`NotFound`エラーを返す。
+/// Todoが存在する場合は、`ensure`でTodoを更新できない理由を確認して、検証エラーを返す。
+async fn todo_not_updated<T>(
+    mut tx: SqliteTransaction<'_>,
+    id: TodoId,
+    ensure: impl FnOnce(&Todo) -> DomainResult<()>,
+) -> DomainResult<T> {
+    let Some(todo) = todo_by_id(&mut *tx, id).await? else {
+        return todo_not_found(id);
+    };
+    ensure(&todo)?;
+    // 更新するときに条件を満たさなかったTodoが、確認するまでに条件を満たす状態に変更された場合
+    Err(domain_error(
+        DomainErrorKind::Validation,
+        "The todo was modified by another request",
+    ))
+}
+
+fn todo_not_found<T>(id: TodoId) -> DomainResult<T> {
+    let message = format!("Todo with id {} not found", id);
+    Err(DomainError {
+        kind: DomainErrorKind::NotFound,
+        messages: vec![message.clone().into()],
+        source: anyhow::anyhow!(message),
+    })
+}
+
+/// 日次ダイジェストに含めるTodoの行
+#[derive(Debug, sqlx::FromRow)]
+struct DigestTodoRow {
+    user_id: Hyphenated,
+    id: Hyphenated,
+    title: String,
+    due_date: Date,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct TodoRow {
+    id: Hyphenated,
+    user_id: Hyphenated,
+    family_name: String,
+    given_name: String,
+    email: String,
+    role_code: i16,
+    role_name: String,
+    role_description: Option<String>,
+    role_display_order: i16,
+    role_created_at: i64,
+    role_updated_at: i64,
+    active: bool,
+    last_login_at: Option<i64>,
+    locked_at: Option<i64>,
+    lock_reason: Option<String>,
+    locale: Option<String>,
+    user_created_at: i64,
+    user_updated_at: i64,
+    title: String,
+    description: Option<String>,
+    todo_status_code: i16,
+    todo_status_name: String,
+    todo_status_description: Option<String>,
+    todo_status_display_order: i16,
+    todo_status_created_at: i64,
+    todo_status_updated_at: i64,
+    due_date: Option<Date>,
+    completed_at: Option<i64>,
+    archived: bool,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl TryFrom<TodoRow> for Todo {
+    type Error = DomainError;
+
+    fn try_from(row: TodoRow) -> Result<Self, Self::Error> {
+        let user = User {
+            id: row.user_id.into_uuid().into(),
+            family_name: row.family_name.try_into()?,
+            given_name: row.given_name.try_into()?,
+            email: row.email.try_into()?,
+            role: Role {
+                code: RoleCode::try_from(row.role_code)?,
+                name: row.role_name.try_into()?,
+                description: row.role_description.map(|d| d.try_into()).transpose()?,
+                display_order: row.role_display_order.try_into()?,
+                created_at: from_micros(row.role_created_at)?,
+                updated_at: from_micros(row.role_updated_at)?,
+            },
+            active: row.active,
+            last_login_at: row.last_login_at.map(from_micros).transpose()?,
+            locked_at: row.locked_at.map(from_micros).transpose()?,
+            lock_reason: row
+                .lock_reason
+                .as_deref()
+                .map(LockReason::try_from)
+                .transpose()?,
+            locale: row.locale,
+            created_at: from_micros(row.user_created_at)?,
+            updated_at: from_micros(row.user_updated_at)?,
+        };
+        let status = TodoStatus {
+            code: TodoStatusCode::try_from(row.todo_status_code)?,
+            name: row.todo_status_name.try_into()?,
+            description: row
+                .todo_status_description
+                .map(|d| d.try_into())
+                .transpose()?,
+            display_order: DisplayOrder(row.todo_status_display_order),
+            created_at: from_micros(row.todo_status_created_at)?,
+            updated_at: from_micros(row.todo_status_updated_at)?,
+        };
+
+        Todo::new(
+            row.id.into_uuid().into(),
+            user,
+            row.title.try_into()?,
+            row.description.map(|d| d.try_into()).transpose()?,
+            status,
+            row.due_date,
+            row.completed_at.map(from_micros).transpose()?,
+            row.archived,
+            from_micros(row.created_at)?,
+            from_micros(row.updated_at)?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn list_where_clause_binds_keyword_to_like() {
+        let mut input = TodoListInput::new_with_user_id(UserId::default());
+        input.keyword = Some(String::from("' OR 1 = 1 --"));
+        let clause = list_where_clause(&input, "t");
+        assert!(!clause.contains("OR 1 = 1"), "{clause}");
+        assert!(clause.contains("t.title LIKE ?1 ESCAPE '\\'"), "{clause}");
+    }
+
+    #[test]
+    fn list_keyset_condition_compares_timestamps_in_microseconds() {
+        let cursor = TodoListCursor {
+            filter_hash: String::new(),
+            completed: false,
+            due_date: None,
+            updated_at: datetime!(2025-06-02 10:00:00 UTC),
+            created_at: datetime!(2025-06-01 09:00:00 UTC),
+            id: TodoId::from(Uuid::nil()),
+        };
+        let condition =
+            list_keyset_condition(&cursor, TodoListSort::CreatedAt, TodoListView::Active, "t");
+        assert_eq!(
+            condition,
+            "((t.created_at < 1748768400000000) \
+             OR (t.created_at = 1748768400000000 \
+             AND t.id > '00000000-0000-0000-0000-000000000000'))"
+        );
+    }
+}
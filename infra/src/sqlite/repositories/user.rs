@@ -0,0 +1,802 @@
+use secrecy::{ExposeSecret as _, SecretString};
+use sqlx::SqliteExecutor;
+use time::OffsetDateTime;
+use uuid::{Uuid, fmt::Hyphenated};
+
+use domain::{
+    DomainError, DomainErrorKind, DomainResult, EMAIL_ALREADY_IN_USE_MESSAGE,
+    REPOSITORY_ERROR_MESSAGE,
+    models::{
+        Email, LockReason, LoginFailedHistory, PHCString, Role, RoleCode, RoleName, User, UserId,
+        UserPreferences,
+        primitives::{Description, DisplayOrder},
+    },
+    repositories::{UpdateUserInput, UserInput, UserRepository, UserToken},
+};
+
+use super::{SqliteRepository, SqliteTransaction, commit, from_micros, now, to_micros};
+
+pub type SqliteUserRepository = SqliteRepository<User>;
+
+/// ユーザーとロールを結合して取得するSELECT文
+const USER_SELECT: &str = r#"
+    SELECT
+        u.id, u.family_name, u.given_name, u.email, u.role_code,
+        r.name role_name, r.description role_description, r.display_order role_display_order,
+        r.created_at role_created_at, r.updated_at role_updated_at,
+        u.active, u.last_login_at, u.locked_at, u.lock_reason, u.locale, u.created_at, u.updated_at
+    FROM users u
+    INNER JOIN roles r ON u.role_code = r.code
+"#;
+
+#[async_trait::async_trait]
+impl UserRepository for SqliteUserRepository {
+    /// ユーザーを新規作成する。
+    async fn create(&self, user: UserInput, hashed_password: PHCString) -> DomainResult<User> {
+        let id = Uuid::new_v4();
+        let created_at = to_micros(now());
+        let mut tx = self.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO users (
+                id, family_name, given_name, email, hashed_password, active,
+                last_login_at, created_at, updated_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, TRUE, NULL, ?6, ?6)
+            "#,
+        )
+        .bind(id.hyphenated())
+        .bind(user.family_name.0)
+        .bind(user.given_name.0)
+        .bind(user.email.0)
+        .bind(hashed_password.0.expose_secret())
+        .bind(created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(email_in_use)?;
+        let Some(user) = user_by_id(&mut *tx, id).await? else {
+            return user_not_found(id.into());
+        };
+        commit(tx).await?;
+        Ok(user)
+    }
+
+    /// ユーザーをIDで取得する。
+    async fn by_id(&self, id: UserId) -> DomainResult<Option<User>> {
+        user_by_id(&self.pool, id.0).await
+    }
+
+    /// ユーザーをEメールアドレスで取得する。
+    async fn by_email(&self, email: &Email) -> DomainResult<Option<User>> {
+        let sql = format!("{USER_SELECT} WHERE u.email = ?1");
+        sqlx::query_as::<_, UserRow>(&sql)
+            .bind(&email.0)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(User::try_from)
+            .transpose()
+    }
+
+    /// ユーザーを更新する。
+    async fn update(&self, id: UserId, user: UpdateUserInput) -> DomainResult<User> {
+        // ロケールは消去できるため、更新するかどうかと更新後の値を分けて渡す
+        let update_locale = user.locale.is_some();
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            UPDATE users
+            SET
+                family_name = COALESCE(?1, family_name),
+                given_name = COALESCE(?2, given_name),
+                locale = CASE WHEN ?3 THEN ?4 ELSE locale END,
+                updated_at = ?5
+            WHERE id = ?6
+            "#,
+        )
+        .bind(user.family_name.map(|f| f.0))
+        .bind(user.given_name.map(|g| g.0))
+        .bind(update_locale)
+        .bind(user.locale.flatten())
+        .bind(to_micros(now()))
+        .bind(id.0.hyphenated())
+        .execute(&mut *tx)
+        .await?;
+        updated_user(tx, id, query_result.rows_affected()).await
+    }
+
+    /// ユーザーのEメールアドレスを更新する。
+    async fn update_email(&self, id: UserId, email: Email) -> DomainResult<User> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            UPDATE users
+            SET
+                email = ?1,
+                updated_at = ?2
+            WHERE id = ?3
+            "#,
+        )
+        .bind(email.0)
+        .bind(to_micros(now()))
+        .bind(id.0.hyphenated())
+        .execute(&mut *tx)
+        .await
+        .map_err(email_in_use)?;
+        updated_user(tx, id, query_result.rows_affected()).await
+    }
+
+    /// ユーザーの設定を取得する。
+    async fn preferences(&self, id: UserId) -> DomainResult<UserPreferences> {
+        let raw_preferences = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT preferences
+            FROM users
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .fetch_optional(&self.pool)
+        .await?;
+        match raw_preferences {
+            Some(raw_preferences) => user_preferences_from_json(&raw_preferences),
+            None => user_not_found(id),
+        }
+    }
+
+    /// ユーザーの設定を更新する。
+    async fn update_preferences(
+        &self,
+        id: UserId,
+        preferences: &UserPreferences,
+    ) -> DomainResult<UserPreferences> {
+        let raw_preferences = serde_json::to_string(preferences).map_err(|e| DomainError {
+            kind: DomainErrorKind::Unexpected,
+            messages: vec!["Failed to serialize user preferences".into()],
+            source: e.into(),
+        })?;
+        let mut tx = self.begin().await?;
+        let raw_preferences = sqlx::query_scalar::<_, String>(
+            r#"
+            UPDATE users
+            SET
+                preferences = ?1,
+                updated_at = ?2
+            WHERE id = ?3
+            RETURNING preferences
+            "#,
+        )
+        .bind(raw_preferences)
+        .bind(to_micros(now()))
+        .bind(id.0.hyphenated())
+        .fetch_optional(&mut *tx)
+        .await?;
+        match raw_preferences {
+            Some(raw_preferences) => {
+                commit(tx).await?;
+                user_preferences_from_json(&raw_preferences)
+            }
+            None => user_not_found(id),
+        }
+    }
+
+    /// ユーザーの最終ログイン日時を更新して、認証情報を登録するとともに、ログイン失敗履歴を削除する。
+    async fn handle_logged_in(
+        &self,
+        id: UserId,
+        logged_in_at: OffsetDateTime,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
+        max_sessions: u32,
+    ) -> DomainResult<Vec<SecretString>> {
+        let now = now();
+        let mut tx = self.begin().await?;
+        // ユーザーの最終ログイン日時を更新
+        let query_result = sqlx::query(
+            r#"
+            UPDATE users
+            SET
+                last_login_at = ?1,
+                updated_at = ?2
+            WHERE id = ?3
+            "#,
+        )
+        .bind(to_micros(logged_in_at))
+        .bind(to_micros(now))
+        .bind(id.0.hyphenated())
+        .execute(&mut *tx)
+        .await?;
+        if query_result.rows_affected() == 0 {
+            return user_not_found(id);
+        }
+        // 認証情報を登録
+        insert_user_tokens(
+            &mut tx,
+            id,
+            now,
+            (access_key, access_expired_at),
+            (refresh_key, refresh_expired_at),
+        )
+        .await?;
+        // ユーザーのログイン失敗履歴を削除
+        sqlx::query(
+            r#"
+            DELETE FROM login_failed_histories
+            WHERE user_id = ?1
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .execute(&mut *tx)
+        .await?;
+        // 同時にログインできるセッション数を超えた古いセッションを削除
+        let pruned_keys = if 0 < max_sessions {
+            delete_oldest_sessions(&mut tx, id, now, max_sessions).await?
+        } else {
+            vec![]
+        };
+        commit(tx).await?;
+        Ok(pruned_keys)
+    }
+
+    /// ユーザーの認証情報を登録する。
+    async fn register_token_pair_for_user(
+        &self,
+        id: UserId,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
+    ) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        insert_user_tokens(
+            &mut tx,
+            id,
+            now(),
+            (access_key, access_expired_at),
+            (refresh_key, refresh_expired_at),
+        )
+        .await?;
+        commit(tx).await
+    }
+
+    /// ユーザーがログインしたときに生成したアクセストークンとリフレッシュトークンを取得する。
+    async fn user_tokens_by_id(&self, id: UserId) -> DomainResult<Vec<UserToken>> {
+        sqlx::query_as::<_, UserTokenRow>(
+            r#"
+            SELECT id, user_id, token_key, expired_at, created_at, updated_at
+            FROM user_tokens
+            WHERE user_id = ?1
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(UserToken::try_from)
+        .collect()
+    }
+
+    /// ユーザーの有効なセッションのうち、新しい`keep_n`個を残して古いセッションを削除する。
+    async fn prune_oldest_sessions(
+        &self,
+        id: UserId,
+        keep_n: u32,
+    ) -> DomainResult<Vec<SecretString>> {
+        let mut tx = self.begin().await?;
+        let keys = delete_oldest_sessions(&mut tx, id, now(), keep_n).await?;
+        commit(tx).await?;
+        Ok(keys)
+    }
+
+    /// ユーザーがログインしたときに生成したアクセストークンとリフレッシュトークンのキーを削除する。
+    async fn delete_user_tokens_by_id(&self, id: UserId) -> DomainResult<Vec<SecretString>> {
+        let mut tx = self.begin().await?;
+        let keys = sqlx::query_scalar::<_, String>(
+            r#"
+            DELETE FROM user_tokens
+            WHERE user_id = ?1
+            RETURNING token_key
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .fetch_all(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        Ok(keys
+            .into_iter()
+            .map(|key| SecretString::new(key.into()))
+            .collect())
+    }
+
+    /// ユーザーのパスワードを取得する。
+    async fn get_hashed_password(&self, id: UserId) -> DomainResult<PHCString> {
+        let raw_hashed_password = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT hashed_password
+            FROM users
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .fetch_optional(&self.pool)
+        .await?;
+        match raw_hashed_password {
+            Some(raw_hashed_password) => {
+                PHCString::new(SecretString::new(raw_hashed_password.into()))
+            }
+            None => user_not_found(id),
+        }
+    }
+
+    /// ユーザーのパスワードを更新する。
+    async fn update_hashed_password(
+        &self,
+        id: UserId,
+        hashed_password: PHCString,
+    ) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            UPDATE users
+            SET
+                hashed_password = ?1,
+                updated_at = ?2
+            WHERE id = ?3
+            "#,
+        )
+        .bind(hashed_password.0.expose_secret())
+        .bind(to_micros(now()))
+        .bind(id.0.hyphenated())
+        .execute(&mut *tx)
+        .await?;
+        match query_result.rows_affected() {
+            0 => user_not_found(id),
+            _ => commit(tx).await,
+        }
+    }
+
+    /// ユーザーを削除する。
+    async fn delete(&self, id: UserId) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            DELETE FROM users
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .execute(&mut *tx)
+        .await?;
+        match query_result.rows_affected() {
+            0 => user_not_found(id),
+            _ => commit(tx).await,
+        }
+    }
+
+    /// ユーザーのログイン失敗履歴を登録する。
+    async fn create_login_failure_history(
+        &self,
+        user_id: UserId,
+        number_of_attempts: i32,
+        attempted_at: OffsetDateTime,
+    ) -> DomainResult<LoginFailedHistory> {
+        let mut tx = self.begin().await?;
+        let row = sqlx::query_as::<_, LoginFailedHistoryRow>(
+            r#"
+            INSERT INTO login_failed_histories (
+                user_id, number_of_attempts, attempted_at, created_at, updated_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?4)
+            ON CONFLICT (user_id) DO UPDATE
+            SET
+                number_of_attempts = excluded.number_of_attempts,
+                attempted_at = excluded.attempted_at,
+                updated_at = excluded.updated_at
+            RETURNING
+                user_id, number_of_attempts, attempted_at, created_at, updated_at
+            "#,
+        )
+        .bind(user_id.0.hyphenated())
+        .bind(number_of_attempts)
+        .bind(to_micros(attempted_at))
+        .bind(to_micros(now()))
+        .fetch_one(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        LoginFailedHistory::try_from(row)
+    }
+
+    /// ユーザーのログイン失敗履歴を取得する。
+    async fn get_login_failed_history(
+        &self,
+        user_id: UserId,
+    ) -> DomainResult<Option<LoginFailedHistory>> {
+        sqlx::query_as::<_, LoginFailedHistoryRow>(
+            r#"
+            SELECT
+                user_id, number_of_attempts, attempted_at, created_at, updated_at
+            FROM login_failed_histories
+            WHERE user_id = ?1
+            "#,
+        )
+        .bind(user_id.0.hyphenated())
+        .fetch_optional(&self.pool)
+        .await?
+        .map(LoginFailedHistory::try_from)
+        .transpose()
+    }
+
+    /// ユーザーのログイン試行回数をインクリメントする。
+    ///
+    /// ユーザーのログイン試行回数をインクリメントして、インクリメント後のログイン試行回数が、最大ログイン試行回数を超えた
+    /// 場合は、ユーザーをロックして、ロックした日時と理由を記録する。
+    async fn increment_number_of_login_attempts(
+        &self,
+        user_id: UserId,
+        max_attempts: u32,
+    ) -> DomainResult<()> {
+        let now = to_micros(now());
+        let mut tx = self.begin().await?;
+        // ユーザーのログイン試行回数をインクリメント
+        sqlx::query(
+            r#"
+            UPDATE login_failed_histories
+            SET
+                number_of_attempts = number_of_attempts + 1,
+                updated_at = ?2
+            WHERE user_id = ?1
+            "#,
+        )
+        .bind(user_id.0.hyphenated())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        // ユーザーのログイン試行回数が最大ログイン試行回数を超えた場合は、ユーザーをロックして、ロックした日時と理由を記録
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET
+                active = FALSE,
+                locked_at = ?4,
+                lock_reason = ?3,
+                updated_at = ?4
+            WHERE id = ?1
+                AND (
+                    SELECT number_of_attempts
+                    FROM login_failed_histories
+                    WHERE user_id = ?1
+                ) > ?2
+            "#,
+        )
+        .bind(user_id.0.hyphenated())
+        .bind(max_attempts as i32)
+        .bind(LockReason::TooManyFailedAttempts.as_str())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        commit(tx).await
+    }
+
+    /// ログイン失敗によるロックが期限切れの場合は、ユーザーのロックを解除する。
+    async fn reactivate_if_lockout_expired(
+        &self,
+        id: UserId,
+        locked_before: OffsetDateTime,
+    ) -> DomainResult<bool> {
+        let mut tx = self.begin().await?;
+        // ロックした理由が記録されていないロックは、ログイン失敗によるロックとみなして、更新日時をロックした日時とみなす
+        let query_result = sqlx::query(
+            r#"
+            UPDATE users
+            SET
+                active = TRUE,
+                locked_at = NULL,
+                lock_reason = NULL,
+                updated_at = ?4
+            WHERE id = ?1
+                AND active = FALSE
+                AND (lock_reason = ?2 OR lock_reason IS NULL)
+                AND COALESCE(locked_at, updated_at) <= ?3
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .bind(LockReason::TooManyFailedAttempts.as_str())
+        .bind(to_micros(locked_before))
+        .bind(to_micros(now()))
+        .execute(&mut *tx)
+        .await?;
+        if query_result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        // ロックを解除した直後のログイン失敗で再びロックされないように、ログイン失敗履歴を削除
+        sqlx::query(
+            r#"
+            DELETE FROM login_failed_histories
+            WHERE user_id = ?1
+            "#,
+        )
+        .bind(id.0.hyphenated())
+        .execute(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        Ok(true)
+    }
+
+    /// ユーザーのログイン失敗履歴をリセットする。
+    ///
+    /// 連続ログイン試行回数を1に設定して、最初にログインを試行した日時を指定された日時に更新する。
+    async fn reset_login_failed_history(
+        &self,
+        user_id: UserId,
+        attempted_at: OffsetDateTime,
+    ) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            UPDATE login_failed_histories
+            SET
+                number_of_attempts = 1,
+                attempted_at = ?1,
+                updated_at = ?2
+            WHERE user_id = ?3
+            "#,
+        )
+        .bind(to_micros(attempted_at))
+        .bind(to_micros(now()))
+        .bind(user_id.0.hyphenated())
+        .execute(&mut *tx)
+        .await?;
+        match query_result.rows_affected() {
+            0 => user_not_found(user_id),
+            _ => commit(tx).await,
+        }
+    }
+
+    /// 古いログイン失敗履歴を削除する。
+    async fn delete_stale_login_failed_histories(
+        &self,
+        cutoff: OffsetDateTime,
+    ) -> DomainResult<u64> {
+        let mut tx = self.begin().await?;
+        let query_result = sqlx::query(
+            r#"
+            DELETE FROM login_failed_histories
+            WHERE attempted_at < ?1
+            "#,
+        )
+        .bind(to_micros(cutoff))
+        .execute(&mut *tx)
+        .await?;
+        commit(tx).await?;
+        Ok(query_result.rows_affected())
+    }
+}
+
+/// ユーザーをIDで取得する。
+async fn user_by_id<'e, E>(executor: E, id: Uuid) -> DomainResult<Option<User>>
+where
+    E: SqliteExecutor<'e>,
+{
+    let sql = format!("{USER_SELECT} WHERE u.id = ?1");
+    sqlx::query_as::<_, UserRow>(&sql)
+        .bind(id.hyphenated())
+        .fetch_optional(executor)
+        .await?
+        .map(User::try_from)
+        .transpose()
+}
+
+/// 更新したユーザーを取得して、トランザクションをコミットする。
+///
+/// SQLiteの`RETURNING`句は他のテーブルと結合できないため、更新した後にロールと結合したユーザーを取得する。
+async fn updated_user(
+    mut tx: SqliteTransaction<'_>,
+    id: UserId,
+    rows_affected: u64,
+) -> DomainResult<User> {
+    if rows_affected == 0 {
+        return user_not_found(id);
+    }
+    let Some(user) = user_by_id(&mut *tx, id.0).await? else {
+        return user_not_found(id);
+    };
+    commit(tx).await?;
+    Ok(user)
+}
+
+/// ユーザーのアクセストークンとリフレッシュトークンのキーを登録する。
+///
+/// 同じセッションのアクセストークンとリフレッシュトークンを識別できるように、同じ作成日時を設定する。
+async fn insert_user_tokens(
+    tx: &mut SqliteTransaction<'_>,
+    id: UserId,
+    created_at: OffsetDateTime,
+    access: (&SecretString, OffsetDateTime),
+    refresh: (&SecretString, OffsetDateTime),
+) -> DomainResult<()> {
+    for (key, expired_at) in [access, refresh] {
+        sqlx::query(
+            r#"
+            INSERT INTO user_tokens (id, user_id, token_key, expired_at, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+            "#,
+        )
+        .bind(Uuid::new_v4().hyphenated())
+        .bind(id.0.hyphenated())
+        .bind(key.expose_secret())
+        .bind(to_micros(expired_at))
+        .bind(to_micros(created_at))
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// ユーザーの有効なセッションのうち、新しい`keep_n`個を残して古いセッションを削除して、削除したキーを返す。
+///
+/// 作成日時が同じ行を1つのセッションとして扱い、リフレッシュトークンの有効期限が`now`より後のセッションを数える。
+async fn delete_oldest_sessions(
+    tx: &mut SqliteTransaction<'_>,
+    id: UserId,
+    now: OffsetDateTime,
+    keep_n: u32,
+) -> DomainResult<Vec<SecretString>> {
+    // SQLiteはLIMIT句なしでOFFSET句を指定できないため、LIMIT -1で件数を制限しない
+    let keys = sqlx::query_scalar::<_, String>(
+        r#"
+        DELETE FROM user_tokens
+        WHERE user_id = ?1
+            AND created_at IN (
+                SELECT created_at
+                FROM user_tokens
+                WHERE user_id = ?1
+                GROUP BY created_at
+                HAVING ?2 < MAX(expired_at)
+                ORDER BY created_at DESC
+                LIMIT -1 OFFSET ?3
+            )
+        RETURNING token_key
+        "#,
+    )
+    .bind(id.0.hyphenated())
+    .bind(to_micros(now))
+    .bind(keep_n as i64)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(keys
+        .into_iter()
+        .map(|key| SecretString::new(key.into()))
+        .collect())
+}
+
+/// SQLiteは一意制約の名前を返さないため、Eメールアドレスの一意制約違反を、PostgreSQLと同じメッセージに変換する。
+fn email_in_use(e: sqlx::Error) -> DomainError {
+    let mut error = DomainError::from(e);
+    if error.kind == DomainErrorKind::Conflict {
+        error.messages = vec![EMAIL_ALREADY_IN_USE_MESSAGE.into()];
+    }
+    error
+}
+
+/// データベースに保存されたユーザーの設定をデシリアライズする。
+///
+/// 保存されたJSONが不正な場合は、リポジトリエラーを返す。
+fn user_preferences_from_json(value: &str) -> DomainResult<UserPreferences> {
+    serde_json::from_str(value).map_err(|e| DomainError {
+        kind: DomainErrorKind::Repository,
+        messages: vec![REPOSITORY_ERROR_MESSAGE.into()],
+        source: anyhow::Error::new(e).context("Stored user preferences are malformed"),
+    })
+}
+
+fn user_not_found<T>(id: UserId) -> DomainResult<T> {
+    let message = format!("User with id {} not found", id);
+    Err(DomainError {
+        kind: DomainErrorKind::NotFound,
+        messages: vec![message.clone().into()],
+        source: anyhow::anyhow!(message),
+    })
+}
+
+/// ユーザーとロールを結合した行
+#[derive(Debug, sqlx::FromRow)]
+struct UserRow {
+    id: Hyphenated,
+    family_name: String,
+    given_name: String,
+    email: String,
+    role_code: i16,
+    role_name: String,
+    role_description: Option<String>,
+    role_display_order: i16,
+    role_created_at: i64,
+    role_updated_at: i64,
+    active: bool,
+    last_login_at: Option<i64>,
+    locked_at: Option<i64>,
+    lock_reason: Option<String>,
+    locale: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl TryFrom<UserRow> for User {
+    type Error = DomainError;
+
+    fn try_from(row: UserRow) -> Result<Self, Self::Error> {
+        Ok(User {
+            id: row.id.into_uuid().into(),
+            family_name: row.family_name.try_into()?,
+            given_name: row.given_name.try_into()?,
+            email: row.email.try_into()?,
+            role: Role {
+                code: RoleCode::try_from(row.role_code)?,
+                name: RoleName::new(row.role_name)?,
+                description: row.role_description.map(Description::new).transpose()?,
+                display_order: DisplayOrder(row.role_display_order),
+                created_at: from_micros(row.role_created_at)?,
+                updated_at: from_micros(row.role_updated_at)?,
+            },
+            active: row.active,
+            last_login_at: row.last_login_at.map(from_micros).transpose()?,
+            locked_at: row.locked_at.map(from_micros).transpose()?,
+            lock_reason: row
+                .lock_reason
+                .as_deref()
+                .map(LockReason::try_from)
+                .transpose()?,
+            locale: row.locale,
+            created_at: from_micros(row.created_at)?,
+            updated_at: from_micros(row.updated_at)?,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct LoginFailedHistoryRow {
+    user_id: Hyphenated,
+    number_of_attempts: i32,
+    attempted_at: i64,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl TryFrom<LoginFailedHistoryRow> for LoginFailedHistory {
+    type Error = DomainError;
+
+    fn try_from(row: LoginFailedHistoryRow) -> Result<Self, Self::Error> {
+        Ok(LoginFailedHistory {
+            user_id: row.user_id.into_uuid().into(),
+            number_of_attempts: row.number_of_attempts as u32,
+            attempted_at: from_micros(row.attempted_at)?,
+            created_at: from_micros(row.created_at)?,
+            updated_at: from_micros(row.updated_at)?,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct UserTokenRow {
+    id: Hyphenated,
+    user_id: Hyphenated,
+    token_key: String,
+    expired_at: i64,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl TryFrom<UserTokenRow> for UserToken {
+    type Error = DomainError;
+
+    fn try_from(row: UserTokenRow) -> Result<Self, Self::Error> {
+        Ok(UserToken {
+            id: row.id.into_uuid(),
+            user_id: row.user_id.into_uuid().into(),
+            token_key: SecretString::new(row.token_key.into()),
+            expired_at: from_micros(row.expired_at)?,
+            created_at: from_micros(row.created_at)?,
+            updated_at: from_micros(row.updated_at)?,
+        })
+    }
+}
@@ -0,0 +1,125 @@
+//! マイグレーションを検証するバイナリ
+//!
+//! `app_settings.toml`のデータベース設定で接続するPostgreSQLサーバーに、空のデータベースを作成して、
+//! すべてのマイグレーションを実行する。統合テストを実行する前に、CIでマイグレーションの誤りを検出するために使用する。
+//!
+//! ```sh
+//! cargo run --bin check-migrations
+//! ```
+//!
+//! マイグレーションに失敗した場合は、失敗したマイグレーションのファイル名を出力して、終了コード1で終了する。
+use std::{path::Path, process::ExitCode};
+
+use anyhow::Context as _;
+use sqlx::{
+    Connection as _, Executor as _, PgConnection, PgPool,
+    migrate::{MigrateError, Migrator},
+};
+
+use infra::settings::{DatabaseSettings, load_app_settings};
+
+/// マイグレーションを検証するデータベースの名前の接頭辞
+const DATABASE_PREFIX: &str = "check_migrations_";
+
+/// マイグレーションファイルを格納したディレクトリ
+const MIGRATIONS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../migrations");
+
+static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => {
+            println!("All migrations succeeded");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run() -> anyhow::Result<()> {
+    let app_settings = load_app_settings("app_settings.toml")?;
+    let settings = DatabaseSettings {
+        name: format!("{}{}", DATABASE_PREFIX, uuid::Uuid::new_v4()).replace('-', "_"),
+        ..app_settings.database
+    };
+
+    // マイグレーションを検証する空のデータベースを作成
+    let mut conn = connect_to_postgres_database(&settings).await?;
+    conn.execute(format!("CREATE DATABASE {};", settings.name).as_str())
+        .await
+        .context("Failed to create the database to check migrations")?;
+
+    let result = migrate(&settings).await;
+
+    // マイグレーションの結果に関わらず、作成したデータベースを削除
+    conn.execute(format!("DROP DATABASE IF EXISTS {} WITH (FORCE);", settings.name).as_str())
+        .await
+        .context("Failed to drop the database to check migrations")?;
+
+    result
+}
+
+/// PostgreSQLサーバーの`postgres`データベースに接続する。
+async fn connect_to_postgres_database(settings: &DatabaseSettings) -> anyhow::Result<PgConnection> {
+    let postgres_settings = DatabaseSettings {
+        name: String::from("postgres"),
+        ..settings.clone()
+    };
+    PgConnection::connect_with(&postgres_settings.connect_options())
+        .await
+        .context("Failed to connect to the postgres database")
+}
+
+/// データベースにすべてのマイグレーションを実行する。
+async fn migrate(settings: &DatabaseSettings) -> anyhow::Result<()> {
+    let pool = PgPool::connect_with(settings.connect_options())
+        .await
+        .context("Failed to connect to the database to check migrations")?;
+    let result = MIGRATOR.run(&pool).await;
+    pool.close().await;
+    result.map_err(|e| {
+        let context = match failed_version(&e) {
+            Some(version) => format!(
+                "Failed to run the migration {}",
+                migration_file_name(version)
+            ),
+            None => String::from("Failed to run the migrations"),
+        };
+        anyhow::Error::new(e).context(context)
+    })
+}
+
+/// マイグレーションエラーから、失敗したマイグレーションのバージョンを取得する。
+fn failed_version(error: &MigrateError) -> Option<i64> {
+    match error {
+        MigrateError::ExecuteMigration(_, version)
+        | MigrateError::VersionMismatch(version)
+        | MigrateError::Dirty(version) => Some(*version),
+        _ => None,
+    }
+}
+
+/// マイグレーションのバージョンから、マイグレーションファイルの名前を取得する。
+///
+/// ファイルが見つからない場合は、バージョンと説明からファイル名を推測する。
+fn migration_file_name(version: i64) -> String {
+    let prefix = format!("{}_", version);
+    let file_name = std::fs::read_dir(Path::new(MIGRATIONS_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .find(|name| name.starts_with(&prefix) && !name.ends_with(".down.sql"));
+    file_name.unwrap_or_else(|| {
+        let description = MIGRATOR
+            .iter()
+            .find(|migration| migration.version == version)
+            .map(|migration| migration.description.replace(' ', "_"))
+            .unwrap_or_default();
+        format!("{}{}", prefix, description)
+    })
+}
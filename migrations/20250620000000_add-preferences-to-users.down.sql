@@ -0,0 +1,2 @@
+-- table: users
+ALTER TABLE users DROP COLUMN IF EXISTS preferences;
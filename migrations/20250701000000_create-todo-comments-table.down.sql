@@ -0,0 +1,3 @@
+-- table: todo_comments
+DROP INDEX IF EXISTS idx_todo_comments_todo_id_created_at;
+DROP TABLE IF EXISTS todo_comments;
@@ -0,0 +1,13 @@
+-- table: todo_comments
+CREATE TABLE IF NOT EXISTS todo_comments (
+    id UUID NOT NULL DEFAULT uuid_generate_v4(),
+    todo_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    body VARCHAR(1000) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT pk_todo_comments PRIMARY KEY (id),
+    CONSTRAINT fk_todo_comments_todo FOREIGN KEY (todo_id) REFERENCES todos (id) ON DELETE CASCADE,
+    CONSTRAINT fk_todo_comments_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_comments_todo_id_created_at ON todo_comments (todo_id, created_at DESC);
@@ -0,0 +1,2 @@
+-- table: daily_digests
+DROP TABLE IF EXISTS daily_digests;
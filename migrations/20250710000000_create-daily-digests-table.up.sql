@@ -0,0 +1,13 @@
+-- table: daily_digests
+-- This table stores the daily digest of each user, generated by the daily digest job.
+-- The todos due on the digest date and the overdue todos are stored as JSON arrays.
+CREATE TABLE IF NOT EXISTS daily_digests (
+    user_id UUID NOT NULL,
+    digest_date DATE NOT NULL,
+    due_today JSONB NOT NULL,
+    overdue JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT pk_daily_digests PRIMARY KEY (user_id, digest_date),
+    CONSTRAINT fk_daily_digests_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
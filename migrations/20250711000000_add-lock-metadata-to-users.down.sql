@@ -0,0 +1,4 @@
+-- table: users
+ALTER TABLE users
+    DROP COLUMN IF EXISTS lock_reason,
+    DROP COLUMN IF EXISTS locked_at;
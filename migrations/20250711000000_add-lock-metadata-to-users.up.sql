@@ -0,0 +1,5 @@
+-- table: users
+-- `locked_at` and `lock_reason` are set when the user is locked, and cleared when the user is unlocked.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS locked_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS lock_reason TEXT;
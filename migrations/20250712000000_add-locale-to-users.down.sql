@@ -0,0 +1,3 @@
+-- table: users
+ALTER TABLE users
+    DROP COLUMN IF EXISTS locale;
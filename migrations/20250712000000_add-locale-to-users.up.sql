@@ -0,0 +1,4 @@
+-- table: users
+-- `locale` is a language tag such as `ja` or `en-US`, which decides the order of the family and given names.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS locale TEXT;
@@ -0,0 +1,17 @@
+-- table: daily_digests
+DROP TRIGGER IF EXISTS trg_daily_digests_set_updated_at ON daily_digests;
+
+-- table: todos
+DROP TRIGGER IF EXISTS trg_todos_set_updated_at ON todos;
+
+-- table: login_failed_histories
+DROP TRIGGER IF EXISTS trg_login_failed_histories_set_updated_at ON login_failed_histories;
+
+-- table: user_tokens
+DROP TRIGGER IF EXISTS trg_user_tokens_set_updated_at ON user_tokens;
+
+-- table: users
+DROP TRIGGER IF EXISTS trg_users_set_updated_at ON users;
+
+-- function: set_updated_at
+DROP FUNCTION IF EXISTS set_updated_at();
@@ -0,0 +1,34 @@
+-- function: set_updated_at
+-- Sets `updated_at` to CURRENT_TIMESTAMP, which is the start time of the current transaction, whenever a row is
+-- updated, so queries do not need to maintain `updated_at` by themselves.
+CREATE OR REPLACE FUNCTION set_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at := CURRENT_TIMESTAMP;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- table: users
+CREATE OR REPLACE TRIGGER trg_users_set_updated_at
+    BEFORE UPDATE ON users
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- table: user_tokens
+CREATE OR REPLACE TRIGGER trg_user_tokens_set_updated_at
+    BEFORE UPDATE ON user_tokens
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- table: login_failed_histories
+CREATE OR REPLACE TRIGGER trg_login_failed_histories_set_updated_at
+    BEFORE UPDATE ON login_failed_histories
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- table: todos
+CREATE OR REPLACE TRIGGER trg_todos_set_updated_at
+    BEFORE UPDATE ON todos
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- table: daily_digests
+CREATE OR REPLACE TRIGGER trg_daily_digests_set_updated_at
+    BEFORE UPDATE ON daily_digests
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
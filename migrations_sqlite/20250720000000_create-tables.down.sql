@@ -0,0 +1,8 @@
+DROP TABLE IF EXISTS daily_digests;
+DROP TABLE IF EXISTS todo_comments;
+DROP TABLE IF EXISTS todos;
+DROP TABLE IF EXISTS todo_statuses;
+DROP TABLE IF EXISTS login_failed_histories;
+DROP TABLE IF EXISTS user_tokens;
+DROP TABLE IF EXISTS users;
+DROP TABLE IF EXISTS roles;
@@ -0,0 +1,128 @@
+-- The schema of the `lite` mode, which is equivalent to the PostgreSQL schema in the `migrations` directory.
+-- UUIDs are stored as lowercase hyphenated TEXT, dates as `YYYY-MM-DD` TEXT, and timestamps as INTEGER
+-- microseconds since the Unix epoch, so that they are compared in the same order as PostgreSQL.
+-- There is no `set_updated_at` trigger, so the repositories set `updated_at` by themselves.
+
+-- table: roles
+CREATE TABLE IF NOT EXISTS roles (
+    code INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    display_order INTEGER NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER) * 1000000),
+    updated_at INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER) * 1000000),
+    CONSTRAINT pk_roles PRIMARY KEY (code)
+);
+-- DO NOT CHANGE RECORDS BELOW
+-- If you need to change the code value, you must fix the RoleCode enum.
+INSERT INTO roles (code, name, description, display_order) VALUES
+    (1, '管理者', 'システム全体の管理を行う役割', 1),
+    (2, 'ユーザー', '通常のユーザーとしての役割', 2);
+
+-- table: users
+CREATE TABLE IF NOT EXISTS users (
+    id TEXT NOT NULL,
+    family_name TEXT NOT NULL,
+    given_name TEXT NOT NULL,
+    email TEXT NOT NULL,
+    hashed_password TEXT NOT NULL,
+    role_code INTEGER NOT NULL DEFAULT 2,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    last_login_at INTEGER,
+    preferences TEXT NOT NULL DEFAULT '{}',
+    locked_at INTEGER,
+    lock_reason TEXT,
+    locale TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    CONSTRAINT pk_users PRIMARY KEY (id),
+    CONSTRAINT fk_users_role FOREIGN KEY (role_code) REFERENCES roles (code) ON DELETE RESTRICT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email ON users (email);
+
+-- table: user_tokens
+-- This table aims to delete access/refresh tokens from the token repository when the user logs out.
+CREATE TABLE IF NOT EXISTS user_tokens (
+    id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    token_key TEXT NOT NULL,
+    expired_at INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    CONSTRAINT pk_user_tokens PRIMARY KEY (id),
+    CONSTRAINT fk_user_tokens_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+-- table: login_failed_histories
+CREATE TABLE IF NOT EXISTS login_failed_histories (
+    user_id TEXT NOT NULL,
+    attempted_at INTEGER NOT NULL,
+    number_of_attempts INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    CONSTRAINT pk_login_failed_histories PRIMARY KEY (user_id),
+    CONSTRAINT fk_login_failed_histories_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+-- table: todo_statuses
+-- If you need to change the code value, you must fix the TodoStatusCode enum.
+CREATE TABLE IF NOT EXISTS todo_statuses (
+    code INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    display_order INTEGER NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER) * 1000000),
+    updated_at INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER) * 1000000),
+    CONSTRAINT pk_todo_statuses PRIMARY KEY (code)
+);
+INSERT INTO todo_statuses (code, name, description, display_order) VALUES
+    (1, '未着手', 'タスクがまだ開始されていない状態', 1),
+    (2, '進行中', 'タスクが現在進行中の状態', 2),
+    (3, '完了', 'タスクが完了した状態', 3),
+    (4, '中止', 'タスクが中止された状態', 4),
+    (5, '保留', 'タスクが一時的に保留されている状態', 5);
+
+-- table: todos
+CREATE TABLE IF NOT EXISTS todos (
+    id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT,
+    todo_status_code INTEGER NOT NULL DEFAULT 1,
+    due_date TEXT,
+    completed_at INTEGER,
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    CONSTRAINT pk_todos PRIMARY KEY (id),
+    CONSTRAINT fk_todos_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    CONSTRAINT fk_todos_todo_status FOREIGN KEY (todo_status_code) REFERENCES todo_statuses (code) ON DELETE RESTRICT
+);
+
+-- table: todo_comments
+CREATE TABLE IF NOT EXISTS todo_comments (
+    id TEXT NOT NULL,
+    todo_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    CONSTRAINT pk_todo_comments PRIMARY KEY (id),
+    CONSTRAINT fk_todo_comments_todo FOREIGN KEY (todo_id) REFERENCES todos (id) ON DELETE CASCADE,
+    CONSTRAINT fk_todo_comments_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_comments_todo_id_created_at ON todo_comments (todo_id, created_at DESC);
+
+-- table: daily_digests
+-- The todos due on the digest date and the overdue todos are stored as JSON arrays.
+CREATE TABLE IF NOT EXISTS daily_digests (
+    user_id TEXT NOT NULL,
+    digest_date TEXT NOT NULL,
+    due_today TEXT NOT NULL,
+    overdue TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    CONSTRAINT pk_daily_digests PRIMARY KEY (user_id, digest_date),
+    CONSTRAINT fk_daily_digests_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
@@ -0,0 +1,276 @@
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use api_client::{TodoListQueryParams, TodoTransferRequestBody, TodoUpdateRequestBody};
+use app::routes::paths;
+use domain::{
+    api::DependencyStatus,
+    models::{TodoStatusCode, UserId},
+    repositories::{TodoRepository as _, TokenType},
+};
+use infra::postgres::repositories::PgTodoRepository;
+
+use crate::{
+    helpers::load_app_settings_for_testing,
+    test_case::{EnableTracing, HANAKO_USER_ID, InsertTestData, TARO_USER_ID, TestCase},
+};
+
+const ADMIN_USER_ID: &str = "3c369de2-a382-4d8a-aef9-bc8cb3ecd211";
+
+/// Check that the admin can impersonate the user and read their todos, but can not modify them.
+#[tokio::test]
+#[ignore]
+async fn the_admin_can_impersonate_the_user_with_a_read_only_session() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    let admin_user_id = UserId::from(Uuid::parse_str(ADMIN_USER_ID).unwrap());
+
+    test_case.login_admin().await;
+    let impersonation = test_case
+        .api
+        .admin_impersonate(*TARO_USER_ID)
+        .await
+        .unwrap();
+    let content = test_case
+        .token_content_from_token_repo(&impersonation.access_token)
+        .await
+        .unwrap();
+    assert_eq!(content.user_id, *TARO_USER_ID);
+    assert_eq!(content.token_type, TokenType::Impersonation);
+    assert_eq!(content.impersonated_by, Some(admin_user_id));
+
+    // The impersonation token is sent without cookies, so the middleware reads the Bearer token
+    let impersonated = test_case.bearer_api_client(&impersonation.access_token);
+    let me = impersonated.me().await.unwrap();
+    assert_eq!(me.id, *TARO_USER_ID);
+    let todos = impersonated
+        .todos_list(&TodoListQueryParams::default())
+        .await
+        .unwrap();
+    assert_eq!(todos.len(), 10);
+    assert!(todos.iter().all(|todo| todo.user.id == *TARO_USER_ID));
+
+    let body = TodoUpdateRequestBody {
+        title: Some(String::from("なりすましによる更新")),
+        ..Default::default()
+    };
+    let error = impersonated
+        .todo_update("4da95cdb-6898-4739-b2be-62ceaa174baf", &body)
+        .await
+        .unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::FORBIDDEN);
+    assert_eq!(error.messages, vec!["Impersonated sessions are read-only"]);
+
+    test_case.end().await;
+}
+
+/// Check that the user who is not an admin can not impersonate another user.
+#[tokio::test]
+#[ignore]
+async fn the_user_who_is_not_an_admin_can_not_impersonate_another_user() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let error = test_case
+        .api
+        .admin_impersonate(ADMIN_USER_ID)
+        .await
+        .unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::FORBIDDEN);
+    assert_eq!(
+        error.messages,
+        vec!["Administrator privileges are required"]
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the admin gets 404 Not Found when impersonating a user who does not exist.
+#[tokio::test]
+#[ignore]
+async fn the_admin_can_not_impersonate_the_user_who_does_not_exist() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_admin().await;
+    let error = test_case
+        .api
+        .admin_impersonate(Uuid::new_v4())
+        .await
+        .unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::NOT_FOUND);
+    assert_eq!(error.messages, vec!["User not found"]);
+
+    test_case.end().await;
+}
+
+/// Check that the admin can transfer the todos of a user to another user.
+#[tokio::test]
+#[ignore]
+async fn the_admin_can_transfer_todos_to_another_user() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    let todo_repo = PgTodoRepository::new(test_case.pg_pool.clone());
+    let taro_id = UserId::from(*TARO_USER_ID);
+    let hanako_id = UserId::from(*HANAKO_USER_ID);
+    let taro_todos = todo_repo
+        .list_all_including_archived(taro_id)
+        .await
+        .unwrap();
+    let hanako_todos = todo_repo
+        .list_all_including_archived(hanako_id)
+        .await
+        .unwrap();
+    let completed = taro_todos
+        .iter()
+        .filter(|todo| todo.status.code == TodoStatusCode::Completed)
+        .count();
+    assert!(0 < completed && completed < taro_todos.len());
+
+    test_case.login_admin().await;
+    // Transfer the todos except for the completed ones
+    let mut body = TodoTransferRequestBody {
+        from_user_id: *TARO_USER_ID,
+        to_user_id: *HANAKO_USER_ID,
+        include_completed: false,
+    };
+    let response = test_case.api.admin_transfer_todos(&body).await.unwrap();
+    assert_eq!(response.transferred as usize, taro_todos.len() - completed);
+    let remaining = todo_repo
+        .list_all_including_archived(taro_id)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), completed);
+    assert!(
+        remaining
+            .iter()
+            .all(|todo| todo.status.code == TodoStatusCode::Completed)
+    );
+
+    // Transfer the remaining completed todos
+    body.include_completed = true;
+    let response = test_case.api.admin_transfer_todos(&body).await.unwrap();
+    assert_eq!(response.transferred as usize, completed);
+    let remaining = todo_repo
+        .list_all_including_archived(taro_id)
+        .await
+        .unwrap();
+    assert!(remaining.is_empty());
+    let transferred = todo_repo
+        .list_all_including_archived(hanako_id)
+        .await
+        .unwrap();
+    assert_eq!(transferred.len(), hanako_todos.len() + taro_todos.len());
+
+    test_case.end().await;
+}
+
+/// Check that the admin can not transfer todos between the same user or a user who does not exist.
+#[tokio::test]
+#[ignore]
+async fn the_admin_can_not_transfer_todos_to_the_same_or_missing_user() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_admin().await;
+    let cases = [
+        (
+            *TARO_USER_ID,
+            StatusCode::BAD_REQUEST,
+            "The source and destination users must be different",
+        ),
+        (
+            Uuid::new_v4(),
+            StatusCode::NOT_FOUND,
+            "Destination user not found",
+        ),
+    ];
+    for (to_user_id, status_code, message) in cases {
+        let body = TodoTransferRequestBody {
+            from_user_id: *TARO_USER_ID,
+            to_user_id,
+            include_completed: true,
+        };
+        let error = test_case.api.admin_transfer_todos(&body).await.unwrap_err();
+        let error = error.api_error().unwrap();
+        assert_eq!(error.status_code, status_code);
+        assert_eq!(error.messages, vec![message]);
+    }
+
+    test_case.end().await;
+}
+
+/// Check that the user who is not an admin can not transfer todos.
+#[tokio::test]
+#[ignore]
+async fn the_user_who_is_not_an_admin_can_not_transfer_todos() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let body = TodoTransferRequestBody {
+        from_user_id: *HANAKO_USER_ID,
+        to_user_id: *TARO_USER_ID,
+        include_completed: true,
+    };
+    let error = test_case.api.admin_transfer_todos(&body).await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::FORBIDDEN
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the admin can get the status of the server with the connection pool stats.
+#[tokio::test]
+#[ignore]
+async fn the_admin_can_get_the_server_status() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_admin().await;
+    let uri = test_case.url(paths::ADMIN_STATUS);
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert!(
+        body["version"].as_str().is_some_and(|v| !v.is_empty()),
+        "{}",
+        body
+    );
+    assert!(body["uptimeSeconds"].is_u64(), "{}", body);
+    for dependency in ["postgres", "redis"] {
+        assert_eq!(body[dependency]["status"], "ok", "{}", body);
+        for key in ["size", "idle", "inUse"] {
+            assert!(body[dependency]["pool"][key].is_u64(), "{}", body);
+        }
+    }
+    // The typed client deserializes the same body
+    let status = test_case.api.admin_status().await.unwrap();
+    assert_eq!(status.postgres.status, DependencyStatus::Ok);
+    assert!(status.postgres.pool.size >= 1);
+
+    test_case.end().await;
+}
+
+/// Check that the user who is not an admin can not get the status of the server.
+#[tokio::test]
+#[ignore]
+async fn the_user_who_is_not_an_admin_can_not_get_the_server_status() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let error = test_case.api.admin_status().await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::FORBIDDEN
+    );
+
+    test_case.end().await;
+}
@@ -0,0 +1,132 @@
+//! Benchmarks of `PgTodoRepository::list` against 10,000 todos
+//!
+//! The benchmark creates its own database with `setup_database`, in the same way as the integration
+//! tests, and drops the database at the end. Run the benchmark as follows:
+//!
+//! ```sh
+//! cargo bench -p test_suite --bench todo_list
+//! ```
+#[allow(dead_code)]
+#[path = "../helpers.rs"]
+mod helpers;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use time::macros::date;
+use tokio::runtime::Runtime;
+
+use domain::{
+    NumericOperator,
+    models::UserId,
+    repositories::{TodoListInput, TodoRepository as _},
+};
+use infra::{postgres::repositories::PgTodoRepository, settings::DatabaseSettings};
+
+use helpers::{
+    TEST_DATABASE_PREFIX, load_app_settings_for_testing, setup_database, teardown_database,
+};
+
+/// The number of todos seeded for the user
+const TODO_COUNT: i32 = 10_000;
+
+/// The user who owns the seeded todos (Taro in the fixture)
+const USER_ID: &str = "47125c09-1dea-42b2-a14e-357e59acf3dc";
+
+/// Seeds the todos, where every tenth todo contains the keyword in the title, and the due dates
+/// spread over a year.
+const SEED_SQL: &str = r#"
+INSERT INTO todos (user_id, title, description, todo_status_code, due_date)
+SELECT
+    $1,
+    CASE WHEN n % 10 = 0 THEN 'Benchmark keyword ' || n ELSE 'Benchmark todo ' || n END,
+    'Seeded for the benchmark',
+    1 + n % 2,
+    DATE '2025-01-01' + (n % 365)
+FROM generate_series(1, $2) AS n
+"#;
+
+fn database_settings() -> DatabaseSettings {
+    let app_settings = load_app_settings_for_testing();
+    let database_name =
+        format!("{}{}", TEST_DATABASE_PREFIX, uuid::Uuid::new_v4()).replace('-', "_");
+    DatabaseSettings {
+        name: database_name.clone(),
+        ..app_settings.database
+    }
+    .with_application_name(database_name)
+}
+
+fn todo_list(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let settings = database_settings();
+    let user_id = USER_ID.parse::<uuid::Uuid>().unwrap();
+    let pg_pool = rt.block_on(async {
+        let pg_pool = setup_database(&settings).await;
+        let content = std::fs::read_to_string("./fixtures/test.sql").unwrap();
+        sqlx::raw_sql(&content).execute(&pg_pool).await.unwrap();
+        sqlx::query(SEED_SQL)
+            .bind(user_id)
+            .bind(TODO_COUNT)
+            .execute(&pg_pool)
+            .await
+            .unwrap();
+        sqlx::query("ANALYZE todos")
+            .execute(&pg_pool)
+            .await
+            .unwrap();
+        pg_pool
+    });
+    let repo = PgTodoRepository::new(pg_pool.clone());
+
+    let user_id = UserId::from(user_id);
+    let cases = [
+        (
+            "no_filter",
+            TodoListInput::new(user_id, None, None, None, None, None, None, None, None),
+        ),
+        (
+            "keyword",
+            TodoListInput::new(
+                user_id,
+                Some(String::from("keyword")),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ),
+        ),
+        (
+            "due_date_between",
+            TodoListInput::new(
+                user_id,
+                None,
+                Some(NumericOperator::Between),
+                Some(date!(2025 - 03 - 01)),
+                Some(date!(2025 - 05 - 31)),
+                None,
+                None,
+                None,
+                None,
+            ),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("todo_list");
+    for (name, input) in cases {
+        let input = input.unwrap();
+        // Report the throughput in the number of todos returned per iteration
+        let count = rt.block_on(repo.list(input.clone())).unwrap().len();
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_function(name, |b| {
+            b.to_async(&rt).iter(|| repo.list(input.clone()));
+        });
+    }
+    group.finish();
+
+    rt.block_on(teardown_database(pg_pool, &settings));
+}
+
+criterion_group!(benches, todo_list);
+criterion_main!(benches);
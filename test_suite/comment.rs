@@ -0,0 +1,211 @@
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use api_client::{ApiClientError, TodoCommentCreateRequestBody, TodoCommentListQueryParams};
+
+use crate::{
+    helpers::load_app_settings_for_testing,
+    test_case::{EnableTracing, HANAKO_USER_ID, InsertTestData, TARO_USER_ID, TestCase},
+};
+
+/// A todo owned by Taro
+const TARO_TODO_ID: &str = "4da95cdb-6898-4739-b2be-62ceaa174baf";
+
+fn comment_body(body: &str) -> TodoCommentCreateRequestBody {
+    TodoCommentCreateRequestBody {
+        body: body.to_string(),
+    }
+}
+
+fn assert_api_error(error: ApiClientError, status_code: StatusCode, message: &str) {
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, status_code);
+    assert_eq!(error.messages, vec![message]);
+}
+
+/// Check that the user can comment on their todo, and list the comments from newest to oldest.
+#[tokio::test]
+#[ignore]
+async fn user_can_comment_on_their_todo_and_list_the_comments_newest_first() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    for body in ["first", "second", "third"] {
+        let comment = test_case
+            .api
+            .todo_comment_create(TARO_TODO_ID, &comment_body(body))
+            .await
+            .unwrap();
+        assert_eq!(comment.todo_id, TARO_TODO_ID.parse::<Uuid>().unwrap());
+        assert_eq!(comment.user_id, *TARO_USER_ID);
+        assert_eq!(comment.body, body);
+    }
+
+    let comments = test_case
+        .api
+        .todo_comments(TARO_TODO_ID, &TodoCommentListQueryParams::default())
+        .await
+        .unwrap();
+    let bodies = comments
+        .iter()
+        .map(|c| c.body.0.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(bodies, vec!["third", "second", "first"]);
+
+    let params = TodoCommentListQueryParams {
+        page: Some(2),
+        page_size: Some(2),
+    };
+    let comments = test_case
+        .api
+        .todo_comments(TARO_TODO_ID, &params)
+        .await
+        .unwrap();
+    let bodies = comments
+        .iter()
+        .map(|c| c.body.0.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(bodies, vec!["first"]);
+
+    test_case.end().await;
+}
+
+/// Check that the user can not comment on, or list the comments of, another user's todo, and that the todo
+/// is reported as not found when the existence of other users' todos is hidden.
+#[rstest::rstest]
+#[case::hide_existence(
+    true,
+    StatusCode::NOT_FOUND,
+    "Todo not found",
+    StatusCode::NOT_FOUND,
+    "Todo not found"
+)]
+#[case::reveal_existence(
+    false,
+    StatusCode::FORBIDDEN,
+    "You are not authorized to comment on this todo",
+    StatusCode::FORBIDDEN,
+    "You are not authorized to access this todo"
+)]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_comment_on_another_users_todo(
+    #[case] hide_cross_user_existence: bool,
+    #[case] create_status_code: StatusCode,
+    #[case] create_message: &str,
+    #[case] list_status_code: StatusCode,
+    #[case] list_message: &str,
+) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.security.hide_cross_user_existence = hide_cross_user_existence;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_hanako().await;
+    let error = test_case
+        .api
+        .todo_comment_create(TARO_TODO_ID, &comment_body("Hello"))
+        .await
+        .unwrap_err();
+    assert_api_error(error, create_status_code, create_message);
+    let error = test_case
+        .api
+        .todo_comments(TARO_TODO_ID, &TodoCommentListQueryParams::default())
+        .await
+        .unwrap_err();
+    assert_api_error(error, list_status_code, list_message);
+
+    test_case.end().await;
+}
+
+/// Check that the user who is neither the author nor the owner of the todo can not delete the comment,
+/// and that the todo is reported as not found when the existence of other users' todos is hidden.
+#[rstest::rstest]
+#[case::hide_existence(true, StatusCode::NOT_FOUND, "Todo not found")]
+#[case::reveal_existence(
+    false,
+    StatusCode::FORBIDDEN,
+    "You are not authorized to delete this comment"
+)]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_delete_another_users_comment(
+    #[case] hide_cross_user_existence: bool,
+    #[case] expected_status_code: StatusCode,
+    #[case] expected_message: &str,
+) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.security.hide_cross_user_existence = hide_cross_user_existence;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let comment = test_case
+        .api
+        .todo_comment_create(TARO_TODO_ID, &comment_body("Taro's note"))
+        .await
+        .unwrap();
+    test_case.api.logout().await.unwrap();
+
+    test_case.login_hanako().await;
+    let error = test_case
+        .api
+        .todo_comment_delete(TARO_TODO_ID, comment.id)
+        .await
+        .unwrap_err();
+    assert_api_error(error, expected_status_code, expected_message);
+    // A comment that does not exist on another user's todo must not be distinguishable from an
+    // existing one when the existence is hidden
+    let error = test_case
+        .api
+        .todo_comment_delete(TARO_TODO_ID, Uuid::new_v4())
+        .await
+        .unwrap_err();
+    if hide_cross_user_existence {
+        assert_api_error(error, StatusCode::NOT_FOUND, "Todo not found");
+    } else {
+        assert_api_error(error, StatusCode::NOT_FOUND, "Comment not found");
+    }
+
+    test_case.end().await;
+}
+
+/// Check that the owner of the todo can delete the comment written by another user.
+#[tokio::test]
+#[ignore]
+async fn todo_owner_can_delete_a_comment_written_by_another_user() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    // Insert Hanako's comment directly, because Hanako can not comment on Taro's todo via the API
+    let comment_id = sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO todo_comments (todo_id, user_id, body) VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(TARO_TODO_ID.parse::<Uuid>().unwrap())
+    .bind(*HANAKO_USER_ID)
+    .bind("Hanako's note")
+    .fetch_one(&test_case.pg_pool)
+    .await
+    .unwrap();
+
+    test_case.login_taro().await;
+    test_case
+        .api
+        .todo_comment_delete(TARO_TODO_ID, comment_id)
+        .await
+        .unwrap();
+    let comments = test_case
+        .api
+        .todo_comments(TARO_TODO_ID, &TodoCommentListQueryParams::default())
+        .await
+        .unwrap();
+    assert!(comments.is_empty());
+    // The deleted comment can not be deleted again
+    let error = test_case
+        .api
+        .todo_comment_delete(TARO_TODO_ID, comment_id)
+        .await
+        .unwrap_err();
+    assert_api_error(error, StatusCode::NOT_FOUND, "Comment not found");
+
+    test_case.end().await;
+}
@@ -0,0 +1,139 @@
+use reqwest::StatusCode;
+use time::macros::date;
+use uuid::Uuid;
+
+use api_client::{DigestQueryParams, DigestTodo};
+
+use crate::{
+    helpers::load_app_settings_for_testing,
+    test_case::{EnableTracing, HANAKO_USER_ID, InsertTestData, TARO_USER_ID, TestCase},
+};
+
+/// Returns the sorted ids of the todos in the digest.
+fn sorted_ids(todos: &[DigestTodo]) -> Vec<Uuid> {
+    let mut ids = todos.iter().map(|todo| todo.id.0).collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+fn uuids(ids: &[&str]) -> Vec<Uuid> {
+    let mut ids = ids
+        .iter()
+        .map(|id| id.parse::<Uuid>().unwrap())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+/// Check that the generator summarizes the completable todos that are due on the date or overdue,
+/// for each active user.
+#[tokio::test]
+#[ignore]
+async fn generator_summarizes_the_due_and_overdue_todos_of_each_active_user() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    let date = date!(2025 - 06 - 19);
+    let digests = test_case
+        .app_state
+        .digest_use_case
+        .generate(date)
+        .await
+        .unwrap();
+
+    // Jiro is locked, so his todos are not summarized
+    let user_ids = digests.iter().map(|d| d.user_id.0).collect::<Vec<_>>();
+    assert_eq!(user_ids, vec![*TARO_USER_ID, *HANAKO_USER_ID]);
+
+    let taro = &digests[0];
+    assert_eq!(taro.digest_date, date);
+    assert_eq!(
+        sorted_ids(&taro.due_today),
+        uuids(&[
+            "e5f6a7b8-9012-3456-abcd-ef0123456789",
+            "91c6d97f-5ef8-4776-be93-03a2738759dd",
+        ])
+    );
+    assert!(taro.due_today.iter().all(|todo| todo.due_date == date));
+    // The completed, cancelled and on hold todos are not summarized
+    assert_eq!(
+        sorted_ids(&taro.overdue),
+        uuids(&[
+            "ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175",
+            "4da95cdb-6898-4739-b2be-62ceaa174baf",
+            "fefdc219-085b-496b-bbf6-72dc40814ab7",
+            "136327d4-62be-4f7c-ba72-0bf5a019d36c",
+        ])
+    );
+    // The overdue todos are sorted by the due date
+    assert!(
+        taro.overdue
+            .windows(2)
+            .all(|pair| pair[0].due_date <= pair[1].due_date)
+    );
+
+    let hanako = &digests[1];
+    assert!(hanako.due_today.is_empty());
+    assert_eq!(
+        sorted_ids(&hanako.overdue),
+        uuids(&["8d2555a7-2751-4d35-91e2-5de94df379c1"])
+    );
+
+    test_case.end().await;
+}
+
+/// Check that generating the digests for the same date twice stores the same digests once.
+#[tokio::test]
+#[ignore]
+async fn generator_is_idempotent_per_date() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    let date = date!(2025 - 06 - 19);
+    let use_case = &test_case.app_state.digest_use_case;
+    let first = use_case.generate(date).await.unwrap();
+    let second = use_case.generate(date).await.unwrap();
+    assert_eq!(first, second);
+
+    let count =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM daily_digests WHERE digest_date = $1")
+            .bind(date)
+            .fetch_one(&test_case.pg_pool)
+            .await
+            .unwrap();
+    assert_eq!(count, 2);
+
+    test_case.end().await;
+}
+
+/// Check that the user can get their stored digest, and gets 404 Not Found for a date without
+/// the digest.
+#[tokio::test]
+#[ignore]
+async fn user_can_get_their_digest() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    let date = date!(2025 - 06 - 19);
+    let digests = test_case
+        .app_state
+        .digest_use_case
+        .generate(date)
+        .await
+        .unwrap();
+
+    test_case.login_taro().await;
+    let params = DigestQueryParams { date: Some(date) };
+    let digest = test_case.api.digest(&params).await.unwrap();
+    assert_eq!(digest, digests[0]);
+
+    let params = DigestQueryParams {
+        date: Some(date!(2025 - 06 - 20)),
+    };
+    let error = test_case.api.digest(&params).await.unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::NOT_FOUND);
+    assert_eq!(error.messages, vec!["Digest not found"]);
+
+    test_case.end().await;
+}
@@ -1,16 +1,27 @@
-use std::{path::Path, thread::JoinHandle};
+use std::{
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
 
+use axum::Router;
 use sqlx::{Connection as _, Executor as _, PgConnection, PgPool};
 use tokio::{net::TcpListener, sync::oneshot};
 
 use app::{bind_address, create_redis_pool, routes::create_router};
 use infra::{
     AppState,
-    settings::{AppSettings, DatabaseSettings, load_app_settings},
+    settings::{AppSettings, DatabaseSettings, load_app_settings, parse_log_level},
 };
 
 pub const TEST_DATABASE_PREFIX: &str = "test_todo_db_";
 
+/// Environment variable to keep the test databases after the tests for debugging
+///
+/// When the variable is set to any value, the test databases are not dropped at the end of the tests.
+pub const KEEP_TEST_DATABASES_ENV: &str = "KEEP_TEST_DATABASES";
+
 pub struct TestApp {
     pub app_settings: AppSettings,
     pub listener: TcpListener,
@@ -23,27 +34,21 @@ pub fn load_app_settings_for_testing() -> AppSettings {
     let path = Path::new(&dir).join("..").join("app_settings.toml");
     let mut settings = load_app_settings(path.as_os_str().to_str().unwrap()).unwrap();
     if let Ok(rust_log) = std::env::var("RUST_LOG") {
-        settings.log_level = log_level_from_str(&rust_log);
+        settings.log_level = parse_log_level(&rust_log).unwrap_or(tracing::Level::INFO);
     }
     settings
 }
 
-fn log_level_from_str(s: &str) -> log::Level {
-    match s.to_lowercase().as_str() {
-        "error" => log::Level::Error,
-        "warn" => log::Level::Warn,
-        "info" => log::Level::Info,
-        "debug" => log::Level::Debug,
-        "trace" => log::Level::Trace,
-        _ => log::Level::Info,
-    }
-}
-
 pub async fn configure_test_app(mut app_settings: AppSettings) -> TestApp {
     // Set up the test database
     let database_name =
         format!("{}{}", TEST_DATABASE_PREFIX, uuid::Uuid::new_v4()).replace('-', "_");
-    app_settings.database.name = database_name; // テスト用のデータベース名を設定
+    // テスト用のデータベース名と、データベース名から導出したアプリケーション名を設定
+    app_settings.database = DatabaseSettings {
+        name: database_name.clone(),
+        ..app_settings.database
+    }
+    .with_application_name(database_name);
     let pg_pool = setup_database(&app_settings.database).await;
 
     // Set up the Redis connection pool
@@ -73,7 +78,7 @@ async fn connect_to_postgres_database(settings: &DatabaseSettings) -> PgConnecti
 }
 
 /// Sets up the PostgreSQL database for testing
-async fn setup_database(settings: &DatabaseSettings) -> PgPool {
+pub async fn setup_database(settings: &DatabaseSettings) -> PgPool {
     // Connect to the **postgres** database
     let mut conn = connect_to_postgres_database(settings).await;
 
@@ -91,33 +96,102 @@ async fn setup_database(settings: &DatabaseSettings) -> PgPool {
     pool
 }
 
+/// Drops the test database after closing the connection pool to it
+///
+/// The test database is kept when the `KEEP_TEST_DATABASES` environment variable is set.
+pub async fn teardown_database(pg_pool: PgPool, settings: &DatabaseSettings) {
+    pg_pool.close().await;
+    if std::env::var_os(KEEP_TEST_DATABASES_ENV).is_some() {
+        tracing::info!("Keeping the test database: {}", settings.name);
+        return;
+    }
+    let mut conn = connect_to_postgres_database(settings).await;
+    conn.execute(format!("DROP DATABASE IF EXISTS {} WITH (FORCE);", settings.name).as_str())
+        .await
+        .unwrap();
+}
+
 /// Spawns the application server in a separate thread
 ///
 /// Returns a tuple containing the thread handle and a sender to signal for graceful shutdown.
 pub async fn spawn_app(
     app_state: AppState,
     listener: TcpListener,
+) -> (JoinHandle<()>, oneshot::Sender<()>) {
+    spawn_router(create_router(app_state), listener).await
+}
+
+/// Spawns a server serving the specified router in a separate thread
+///
+/// Returns a tuple containing the thread handle and a sender to signal for graceful shutdown.
+pub async fn spawn_router(
+    router: Router,
+    listener: TcpListener,
 ) -> (JoinHandle<()>, oneshot::Sender<()>) {
     let (close_tx, close_rx) = oneshot::channel();
 
-    let handle = std::thread::spawn(|| run_server(app_state, listener, close_rx));
+    let handle = std::thread::spawn(|| run_server(router, listener, close_rx));
     (handle, close_tx)
 }
 
-/// Runs the application server with graceful shutdown support
-fn run_server(app_state: AppState, listener: TcpListener, close_rx: oneshot::Receiver<()>) {
-    let router = create_router(app_state);
+/// Spawns a server serving the specified router in a separate thread, where the specified subscriber
+/// collects the logs of the server
+///
+/// The server runs on a current thread runtime, so the subscriber collects all the logs emitted
+/// while handling requests.
+pub async fn spawn_router_with_subscriber<S>(
+    router: Router,
+    listener: TcpListener,
+    subscriber: S,
+) -> (JoinHandle<()>, oneshot::Sender<()>)
+where
+    S: tracing::Subscriber + Send + Sync + 'static,
+{
+    let (close_tx, close_rx) = oneshot::channel();
+
+    let handle = std::thread::spawn(|| {
+        tracing::subscriber::with_default(subscriber, || run_server(router, listener, close_rx))
+    });
+    (handle, close_tx)
+}
+
+/// Log sink that collects the logs in memory
+#[derive(Debug, Clone, Default)]
+pub struct LogSink(Arc<Mutex<Vec<u8>>>);
+
+impl LogSink {
+    pub fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl std::io::Write for LogSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs the server with graceful shutdown support
+fn run_server(router: Router, listener: TcpListener, close_rx: oneshot::Receiver<()>) {
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
         .unwrap();
     rt.block_on(async move {
-        axum::serve(listener, router)
-            .with_graceful_shutdown(async move {
-                _ = close_rx.await;
-            })
-            .await
-            .unwrap();
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move {
+            _ = close_rx.await;
+        })
+        .await
+        .unwrap();
     });
 }
 
@@ -137,3 +211,14 @@ pub async fn split_response(response: reqwest::Response) -> ResponseParts {
         body: response.text().await.unwrap().to_string(),
     }
 }
+
+/// Returns the messages in the body of an error response
+pub fn error_messages(body: &str) -> Vec<String> {
+    let body = serde_json::from_str::<serde_json::Value>(body).unwrap();
+    body["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| message.as_str().unwrap().to_string())
+        .collect()
+}
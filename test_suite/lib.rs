@@ -1,12 +1,35 @@
+mod admin;
+mod comment;
+mod digest;
 mod helpers;
+#[cfg(feature = "lite")]
+mod lite;
 mod lookup;
+mod middleware;
+mod schema;
+mod static_files;
 mod test_case;
 mod todo;
+mod todo_reopen;
 mod user;
 
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::sync::oneshot;
+
+use app::{
+    routes::{RouteAccess, api_route_table, paths},
+    serve,
+};
+use domain::api::DependencyStatus;
+use infra::http::handler::HealthCheckResponseBody;
+
 use crate::{
     helpers::load_app_settings_for_testing,
-    test_case::{EnableTracing, InsertTestData, TestCase},
+    test_case::{EnableTracing, InsertTestData, REQUEST_TIMEOUT, TestCase},
 };
 
 #[tokio::test]
@@ -15,21 +38,241 @@ async fn health_check() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
 
-    let uri = format!("{}/health-check", test_case.origin());
+    let uri = test_case.url(paths::HEALTH_CHECK);
     let response = test_case.http_client.get(&uri).send().await.unwrap();
     assert!(
         response.status().is_success(),
         "Health check failed: {}",
         response.status()
     );
+    let body = response.text().await.unwrap();
     assert!(
-        response
-            .text()
-            .await
-            .unwrap()
-            .contains("Ok, the server is running!"),
+        body.contains("Ok, the server is running!"),
         "Health check response did not contain 'Ok, the server is running!'"
     );
+    let body = serde_json::from_str::<HealthCheckResponseBody>(&body).unwrap();
+    assert_eq!(body.status, DependencyStatus::Ok);
+    assert_eq!(body.build, test_case.app_state.build_info);
 
     test_case.end().await;
 }
+
+/// Check that every response, including the error responses, has the `X-App-Version` header.
+#[tokio::test]
+#[ignore]
+async fn every_response_has_the_app_version_header() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let expected = test_case.app_state.build_info.version_with_git_sha();
+    for path in [paths::ROLES, paths::USERS_ME, "/no-such-resource"] {
+        let response = test_case
+            .http_client
+            .get(test_case.url(path))
+            .send()
+            .await
+            .unwrap();
+        let version = response
+            .headers()
+            .get("x-app-version")
+            .unwrap_or_else(|| panic!("{}: X-App-Version header is missing", path));
+        assert_eq!(version.to_str().unwrap(), expected, "{}", path);
+    }
+
+    test_case.end().await;
+}
+
+/// Check that every route in the route table is registered in the router with the method.
+///
+/// The requests are not authenticated and have no body, so the handlers reject them, but the router
+/// must not respond with 404 Not Found or 405 Method Not Allowed.
+/// The routes that require the authentication must respond with 401 Unauthorized.
+#[tokio::test]
+#[ignore]
+async fn every_route_in_the_route_table_is_registered() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    for route in api_route_table() {
+        // Fill the path parameters with dummy values
+        let path = route
+            .path
+            .split('/')
+            .map(|segment| match segment {
+                "{code}" => String::from("1"),
+                s if s.starts_with('{') => uuid::Uuid::nil().to_string(),
+                s => s.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let response = test_case
+            .http_client
+            .request(route.method.clone(), test_case.url(&path))
+            .send()
+            .await
+            .unwrap();
+        let status = response.status();
+        assert!(
+            ![
+                reqwest::StatusCode::NOT_FOUND,
+                reqwest::StatusCode::METHOD_NOT_ALLOWED
+            ]
+            .contains(&status),
+            "{} {}: {}",
+            route.method,
+            path,
+            status
+        );
+        if route.access != RouteAccess::Public {
+            assert_eq!(
+                status,
+                reqwest::StatusCode::UNAUTHORIZED,
+                "{} {}",
+                route.method,
+                path
+            );
+        }
+    }
+
+    test_case.end().await;
+}
+
+/// Check that the health check reports the degraded Redis when the server can not connect to Redis.
+#[tokio::test]
+#[ignore]
+async fn health_check_reports_degraded_redis() {
+    let mut app_settings = load_app_settings_for_testing();
+    // No Redis server listens on the port
+    app_settings.redis.port = 1;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let uri = test_case.url(paths::HEALTH_CHECK);
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+    let body = response.json::<HealthCheckResponseBody>().await.unwrap();
+    assert_eq!(body.redis, DependencyStatus::Degraded);
+
+    test_case.end().await;
+}
+
+/// Check that the metrics endpoint exposes the connection pool metrics of PostgreSQL and Redis.
+#[tokio::test]
+#[ignore]
+async fn metrics_exposes_connection_pool_gauges() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    // Use both pools before reading the metrics
+    test_case.login_taro().await;
+
+    let app_settings = &test_case.app_state.app_settings;
+    let uri = format!(
+        "{}://{}:{}{}",
+        app_settings.http.protocol,
+        app_settings.http.host,
+        app_settings.http.port,
+        paths::METRICS,
+    );
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let content_type = response.headers()[reqwest::header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(content_type.starts_with("text/plain"), "{}", content_type);
+    let body = response.text().await.unwrap();
+    for name in [
+        "pg_pool_size",
+        "pg_pool_idle",
+        "pg_pool_in_use",
+        "redis_pool_size",
+        "redis_pool_idle",
+        "redis_pool_in_use",
+    ] {
+        assert!(body.contains(&format!("# TYPE {} gauge", name)), "{}", body);
+        let value = body
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{} ", name)))
+            .unwrap_or_else(|| panic!("{} is missing: {}", name, body));
+        value.parse::<usize>().unwrap();
+    }
+
+    test_case.end().await;
+}
+
+/// Check that the connections to the test database are tagged with the application name derived
+/// from the test database name.
+#[tokio::test]
+#[ignore]
+async fn connections_are_tagged_with_the_application_name() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let database_settings = &test_case.app_state.app_settings.database;
+    assert_eq!(database_settings.application_name, database_settings.name);
+    let application_names = sqlx::query_scalar::<_, String>(
+        "SELECT application_name FROM pg_stat_activity WHERE datname = current_database()",
+    )
+    .fetch_all(&test_case.pg_pool)
+    .await
+    .unwrap();
+    assert!(!application_names.is_empty());
+    assert!(
+        application_names
+            .iter()
+            .all(|name| name == &database_settings.application_name),
+        "{:?}",
+        application_names
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the server completes the in-flight request, and the serve future completes without an error,
+/// when the shutdown future completes.
+#[tokio::test]
+#[ignore]
+async fn server_shuts_down_gracefully() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let origin = format!("http://{}", listener.local_addr().unwrap());
+    let (started_tx, started_rx) = oneshot::channel::<()>();
+    let started_tx = Arc::new(Mutex::new(Some(started_tx)));
+    let router = axum::Router::new().route(
+        "/slow",
+        axum::routing::get(move || {
+            let started_tx = started_tx.clone();
+            async move {
+                if let Some(started_tx) = started_tx.lock().unwrap().take() {
+                    _ = started_tx.send(());
+                }
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                "Ok"
+            }
+        }),
+    );
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve(listener, router, async move {
+        _ = shutdown_rx.await;
+    }));
+
+    let http_client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap();
+    let request = tokio::spawn(async move {
+        let response = http_client
+            .get(format!("{origin}/slow"))
+            .send()
+            .await
+            .unwrap();
+        (response.status(), response.text().await.unwrap())
+    });
+    // Start shutting down while the server is handling the request
+    started_rx.await.unwrap();
+    shutdown_tx.send(()).unwrap();
+
+    let (status_code, body) = request.await.unwrap();
+    assert_eq!(status_code, reqwest::StatusCode::OK);
+    assert_eq!(body, "Ok");
+    server.await.unwrap().unwrap();
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use secrecy::SecretString;
+
+use api_client::{
+    LoginRequestBody, SignUpRequestBody, TodoApiClient, TodoCreateRequestBody, TodoListQueryParams,
+};
+use app::{bind_address, create_sqlite_pool, routes::paths};
+use infra::{AppState, mail::TracingMailer, settings::DatabaseEngine};
+
+use crate::{
+    helpers::{load_app_settings_for_testing, spawn_app},
+    test_case::REQUEST_TIMEOUT,
+};
+
+/// Check that the user can sign up, log in, create a todo and list it in the lite mode.
+///
+/// The lite mode needs neither PostgreSQL nor Redis, so this test is not ignored.
+#[tokio::test]
+async fn the_user_can_create_and_list_todos_in_lite_mode() {
+    let mut app_settings = load_app_settings_for_testing();
+    let sqlite_path =
+        std::env::temp_dir().join(format!("test_todo_{}.sqlite3", uuid::Uuid::new_v4()));
+    app_settings.database.engine = DatabaseEngine::Sqlite;
+    app_settings.database.sqlite_path = sqlite_path.to_str().unwrap().to_string();
+    app_settings.http.port = 0;
+    let (listener, port) = bind_address(&app_settings.http).await.unwrap();
+    app_settings.http.port = port;
+    let pool = create_sqlite_pool(&app_settings.database).await.unwrap();
+    let origin = format!(
+        "{}://{}:{}{}",
+        app_settings.http.protocol,
+        app_settings.http.host,
+        app_settings.http.port,
+        paths::API_PREFIX,
+    );
+    let app_state = AppState::sqlite(app_settings, pool.clone(), Arc::new(TracingMailer));
+    let (app_handle, shutdown_signal) = spawn_app(app_state, listener).await;
+    let http_client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let api = TodoApiClient::new(origin, http_client);
+
+    let password = "ab12AB#$";
+    let body = SignUpRequestBody {
+        family_name: String::from("Doe"),
+        given_name: String::from("John"),
+        email: String::from("john@example.com"),
+        password: SecretString::new(password.into()),
+    };
+    api.sign_up(&body).await.unwrap();
+    let body = LoginRequestBody {
+        email: String::from("john@example.com"),
+        password: SecretString::new(password.into()),
+    };
+    api.login(&body).await.unwrap();
+    let body = TodoCreateRequestBody {
+        title: String::from("Buy milk"),
+        ..Default::default()
+    };
+    let created = api.todo_create(&body).await.unwrap();
+    let todos = api
+        .todos_list(&TodoListQueryParams::default())
+        .await
+        .unwrap();
+    assert_eq!(todos.len(), 1);
+    assert_eq!(todos[0].id, created.id);
+    assert_eq!(todos[0].title.0, "Buy milk");
+
+    shutdown_signal.send(()).unwrap();
+    app_handle.join().unwrap();
+    pool.close().await;
+    // Remove the database file, and the files of the write-ahead log
+    for suffix in ["", "-wal", "-shm"] {
+        let mut path = sqlite_path.clone().into_os_string();
+        path.push(suffix);
+        _ = std::fs::remove_file(path);
+    }
+}
@@ -2,7 +2,9 @@ use reqwest::StatusCode;
 
 use domain::models::{Role, RoleCode, TodoStatus, TodoStatusCode};
 
-use crate::helpers::{ResponseParts, load_app_settings_for_testing, split_response};
+use crate::helpers::{
+    ResponseParts, error_messages, load_app_settings_for_testing, split_response,
+};
 use crate::test_case::{EnableTracing, InsertTestData, TestCase};
 
 #[tokio::test]
@@ -67,6 +69,50 @@ async fn user_can_get_a_role_by_code() {
     test_case.end().await;
 }
 
+/// Check that the user can get a role by its snake_case name.
+#[rstest::rstest]
+#[case::admin("admin", RoleCode::Admin)]
+#[case::user("user", RoleCode::User)]
+#[tokio::test]
+#[ignore]
+async fn user_can_get_a_role_by_name(#[case] name: &str, #[case] expected: RoleCode) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case.role_by_code(name).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK, "{}", body);
+    let role = serde_json::from_str::<Role>(&body).unwrap();
+    assert_eq!(role.code, expected);
+
+    test_case.end().await;
+}
+
+/// Check that an unknown role code or name responds 404 with the entity name.
+#[rstest::rstest]
+#[case::unknown_code("99")]
+#[case::out_of_range_code("40000")]
+#[case::unknown_name("unknown")]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_get_an_unknown_role(#[case] code: &str) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case.role_by_code(code).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::NOT_FOUND, "{}", body);
+    assert_eq!(error_messages(&body), vec!["role not found"]);
+
+    test_case.end().await;
+}
+
 #[tokio::test]
 #[ignore]
 async fn anonymous_user_can_not_get_a_role_by_code() {
@@ -154,6 +200,50 @@ async fn user_can_get_a_todo_status_by_code() {
     test_case.end().await;
 }
 
+/// Check that the user can get a todo status by its snake_case name.
+#[rstest::rstest]
+#[case::not_started("not_started", TodoStatusCode::NotStarted)]
+#[case::in_progress("in_progress", TodoStatusCode::InProgress)]
+#[case::on_hold("on_hold", TodoStatusCode::OnHold)]
+#[tokio::test]
+#[ignore]
+async fn user_can_get_a_todo_status_by_name(#[case] name: &str, #[case] expected: TodoStatusCode) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case.todo_status_by_code(name).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK, "{}", body);
+    let todo_status = serde_json::from_str::<TodoStatus>(&body).unwrap();
+    assert_eq!(todo_status.code, expected);
+
+    test_case.end().await;
+}
+
+/// Check that an unknown todo status code or name responds 404 with the entity name.
+#[rstest::rstest]
+#[case::unknown_code("99")]
+#[case::unknown_name("unknown")]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_get_an_unknown_todo_status(#[case] code: &str) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case.todo_status_by_code(code).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::NOT_FOUND, "{}", body);
+    assert_eq!(error_messages(&body), vec!["todo status not found"]);
+
+    test_case.end().await;
+}
+
 #[tokio::test]
 #[ignore]
 async fn anonymous_user_can_not_get_a_todo_status_by_code() {
@@ -166,3 +256,82 @@ async fn anonymous_user_can_not_get_a_todo_status_by_code() {
 
     test_case.end().await;
 }
+
+#[tokio::test]
+#[ignore]
+async fn todo_status_list_is_served_from_cache_within_ttl() {
+    let app_settings = load_app_settings_for_testing();
+    let max_age = app_settings.lookup.cache_seconds;
+    assert!(
+        max_age > 0,
+        "The lookup cache must be enabled for this test"
+    );
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case.todo_status_list().await;
+    let ResponseParts {
+        status_code,
+        headers,
+        body,
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK);
+    assert_eq!(
+        headers.get(reqwest::header::CACHE_CONTROL).unwrap(),
+        &format!("public, max-age={}", max_age)
+    );
+    let first = serde_json::from_str::<Vec<TodoStatus>>(&body).unwrap();
+
+    // The second request must not touch the database, so it succeeds even if the table is gone
+    sqlx::query("DROP TABLE todo_statuses CASCADE")
+        .execute(&test_case.pg_pool)
+        .await
+        .unwrap();
+    let response = test_case.todo_status_list().await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK);
+    let second = serde_json::from_str::<Vec<TodoStatus>>(&body).unwrap();
+    assert_eq!(second.len(), first.len());
+
+    let response = test_case
+        .todo_status_by_code(TodoStatusCode::Completed as i16)
+        .await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK);
+    let todo_status = serde_json::from_str::<TodoStatus>(&body).unwrap();
+    assert_eq!(todo_status.code, TodoStatusCode::Completed);
+
+    test_case.end().await;
+}
+
+/// Check that a legacy row whose display order is out of range does not break the todo status list.
+#[tokio::test]
+#[ignore]
+async fn todo_status_list_tolerates_an_out_of_range_display_order() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    sqlx::query("UPDATE todo_statuses SET display_order = 0 WHERE code = $1")
+        .bind(TodoStatusCode::OnHold as i16)
+        .execute(&test_case.pg_pool)
+        .await
+        .unwrap();
+
+    test_case.login_taro().await;
+    let response = test_case.todo_status_list().await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK);
+    let todo_statuses = serde_json::from_str::<Vec<TodoStatus>>(&body).unwrap();
+    let on_hold = todo_statuses
+        .iter()
+        .find(|t| t.code == TodoStatusCode::OnHold)
+        .unwrap();
+    assert_eq!(on_hold.display_order, 0);
+
+    test_case.end().await;
+}
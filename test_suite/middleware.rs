@@ -0,0 +1,604 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{Extension, Router, middleware::from_fn, routing::get};
+use reqwest::StatusCode;
+use sqlx::PgPool;
+
+use api_client::{ApiClientError, LoginRequestBody, TodoApiClient, TodoListQueryParams};
+use app::{
+    get_subscriber,
+    routes::{apply_layers, cors_layer, create_api_routes, create_router, paths},
+};
+use infra::{
+    AppState,
+    access_log::AccessLogSink,
+    http::middleware::{ClientIp, RequestId, client_ip_middleware, request_id_middleware},
+    mail::TracingMailer,
+    redis::token::REDIS_UNAVAILABLE_MESSAGE,
+    settings::AccessLogFormat,
+};
+use secrecy::SecretString;
+
+use crate::{
+    helpers::{
+        LogSink, TestApp, configure_test_app, error_messages, load_app_settings_for_testing,
+        spawn_router, spawn_router_with_subscriber, teardown_database,
+    },
+    test_case::{EnableTracing, InsertTestData, REQUEST_TIMEOUT, TARO_USER_ID, TestCase},
+};
+
+/// The upper bound of the response time when the redis is down
+const REDIS_OUTAGE_RESPONSE_TIME: Duration = Duration::from_secs(2);
+
+/// A handler that takes longer than the request timeout overridden in the tests
+async fn slow_handler() -> &'static str {
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    "Too late"
+}
+
+/// A handler that runs a query taking longer than the request timeout overridden in the tests
+async fn slow_query_handler(pg_pool: PgPool) -> &'static str {
+    sqlx::query("SELECT pg_sleep(0.5)")
+        .execute(&pg_pool)
+        .await
+        .unwrap();
+    "Too late"
+}
+
+/// A handler that returns the client IP address stored by the client IP middleware
+async fn client_ip_handler(Extension(ClientIp(ip)): Extension<ClientIp>) -> String {
+    ip.to_string()
+}
+
+/// Check that the client IP middleware stores the IP address of the peer as a request extension.
+#[tokio::test]
+#[ignore]
+async fn client_ip_middleware_stores_the_peer_ip_address() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let origin = format!("http://{}", listener.local_addr().unwrap());
+    let router = Router::new()
+        .route("/client-ip", get(client_ip_handler))
+        .layer(from_fn(client_ip_middleware));
+    let (app_handle, shutdown_signal) = spawn_router(router, listener).await;
+
+    let http_client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap();
+    let response = http_client
+        .get(format!("{}/client-ip", origin))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await.unwrap(), "127.0.0.1");
+
+    shutdown_signal.send(()).unwrap();
+    app_handle.join().unwrap();
+}
+
+/// A handler that returns the request ID stored by the request ID middleware
+async fn request_id_handler(Extension(RequestId(request_id)): Extension<RequestId>) -> String {
+    request_id
+}
+
+/// Check that the request ID middleware stores the request ID as a request extension,
+/// and returns it in the response header even if the access log is disabled.
+#[tokio::test]
+#[ignore]
+async fn request_id_middleware_stores_and_returns_the_request_id() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let origin = format!("http://{}", listener.local_addr().unwrap());
+    let router = Router::new()
+        .route("/request-id", get(request_id_handler))
+        .layer(from_fn(request_id_middleware));
+    let (app_handle, shutdown_signal) = spawn_router(router, listener).await;
+
+    let http_client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap();
+    // A request with the request ID specified by the client
+    let response = http_client
+        .get(format!("{}/request-id", origin))
+        .header("x-request-id", "test-request-id")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-request-id"], "test-request-id");
+    assert_eq!(response.text().await.unwrap(), "test-request-id");
+    // A request without the request ID
+    let response = http_client
+        .get(format!("{}/request-id", origin))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let generated_request_id = response.headers()["x-request-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(uuid::Uuid::parse_str(&generated_request_id).is_ok());
+    assert_eq!(response.text().await.unwrap(), generated_request_id);
+
+    shutdown_signal.send(()).unwrap();
+    app_handle.join().unwrap();
+}
+
+/// Check that the server returns 503 Service Unavailable with an error message when a handler
+/// does not respond in time.
+#[rstest::rstest]
+#[case::slow_handler("/slow")]
+#[case::slow_query("/slow-query")]
+#[tokio::test]
+#[ignore]
+async fn server_returns_service_unavailable_when_the_handler_is_slow(#[case] path: &str) {
+    let app_settings = load_app_settings_for_testing();
+    let TestApp {
+        app_settings,
+        listener,
+        pg_pool,
+        redis_pool,
+    } = configure_test_app(app_settings).await;
+    let database_settings = app_settings.database.clone();
+    let app_state = AppState::new(
+        app_settings,
+        pg_pool.clone(),
+        redis_pool,
+        Arc::new(TracingMailer),
+    );
+    let origin = format!(
+        "{}://{}:{}",
+        app_state.app_settings.http.protocol,
+        app_state.app_settings.http.host,
+        app_state.app_settings.http.port,
+    );
+    // Override the request timeout to 1 ms
+    let routes = create_api_routes(app_state.clone())
+        .route("/slow", get(slow_handler))
+        .route("/slow-query", {
+            let pg_pool = pg_pool.clone();
+            get(move || slow_query_handler(pg_pool))
+        });
+    let max_body_size_bytes = app_state.app_settings.http.max_body_size_bytes;
+    let cors = cors_layer(&app_state.app_settings.http);
+    let router = apply_layers(routes, Duration::from_millis(1), max_body_size_bytes, cors)
+        .with_state(app_state);
+    let (app_handle, shutdown_signal) = spawn_router(router, listener).await;
+
+    let http_client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap();
+    let response = http_client
+        .get(format!("{}{}", origin, path))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let messages = error_messages(&response.text().await.unwrap());
+    assert_eq!(
+        messages,
+        vec!["The request timed out. Please try again later"]
+    );
+
+    shutdown_signal.send(()).unwrap();
+    app_handle.join().unwrap();
+    teardown_database(pg_pool, &database_settings).await;
+}
+
+/// Check that the server returns 413 Payload Too Large when the request body exceeds the limit.
+#[tokio::test]
+#[ignore]
+async fn server_returns_payload_too_large_when_the_body_exceeds_the_limit() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let max_body_size_bytes = test_case.app_state.app_settings.http.max_body_size_bytes;
+    let body = format!(
+        r#"{{"familyName": "{}", "givenName": "John", "email": "john@example.com", "password": "ab12$%AB"}}"#,
+        "a".repeat(max_body_size_bytes + 1)
+    );
+    let response = test_case.sign_up(body).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    test_case.end().await;
+}
+
+/// Check that the server echoes back only an allowed origin in the `Access-Control-Allow-Origin`
+/// header, and that CORS is disabled when no origins are allowed.
+#[rstest::rstest]
+#[case::allowed_origin(&["http://localhost:5173", "https://todo.example.com"], "https://todo.example.com", true)]
+#[case::disallowed_origin(&["http://localhost:5173"], "https://evil.example.com", false)]
+#[case::cors_disabled(&[], "http://localhost:5173", false)]
+#[tokio::test]
+#[ignore]
+async fn cors_allows_only_the_allowed_origins(
+    #[case] allowed_origins: &[&str],
+    #[case] origin: &str,
+    #[case] allowed: bool,
+) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.http.allowed_origins = allowed_origins.iter().map(|o| o.to_string()).collect();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let uri = test_case.url(paths::HEALTH_CHECK);
+    // Simple request
+    let response = test_case
+        .http_client
+        .get(&uri)
+        .header(reqwest::header::ORIGIN, origin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let allow_origin = response
+        .headers()
+        .get(reqwest::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .map(|value| value.to_str().unwrap().to_string());
+    assert_eq!(allow_origin, allowed.then(|| origin.to_string()));
+    // Preflight request
+    let response = test_case
+        .http_client
+        .request(reqwest::Method::OPTIONS, &uri)
+        .header(reqwest::header::ORIGIN, origin)
+        .header(reqwest::header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .send()
+        .await
+        .unwrap();
+    let allow_origin = response
+        .headers()
+        .get(reqwest::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .map(|value| value.to_str().unwrap().to_string());
+    assert_eq!(allow_origin, allowed.then(|| origin.to_string()));
+
+    test_case.end().await;
+}
+
+/// Check that the preflight response has the `Access-Control-Max-Age` header with the configured value.
+#[tokio::test]
+#[ignore]
+async fn cors_preflight_response_has_the_configured_max_age() {
+    let mut app_settings = load_app_settings_for_testing();
+    let origin = "http://localhost:5173";
+    app_settings.http.allowed_origins = vec![origin.to_string()];
+    app_settings.http.cors_max_age_secs = 600;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let response = test_case
+        .http_client
+        .request(reqwest::Method::OPTIONS, test_case.url(paths::TODOS))
+        .header(reqwest::header::ORIGIN, origin)
+        .header(reqwest::header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{}", response.status());
+    let max_age = response
+        .headers()
+        .get(reqwest::header::ACCESS_CONTROL_MAX_AGE)
+        .map(|value| value.to_str().unwrap().to_string());
+    assert_eq!(max_age.as_deref(), Some("600"));
+
+    test_case.end().await;
+}
+
+/// Access log sink that records the lines in memory
+#[derive(Debug, Default)]
+struct MemoryAccessLogSink {
+    lines: Mutex<Vec<String>>,
+}
+
+impl AccessLogSink for MemoryAccessLogSink {
+    fn write(&self, line: &str) {
+        self.lines.lock().unwrap().push(line.to_string());
+    }
+}
+
+/// Check that the access log middleware writes a JSON line with the required fields for each request.
+#[tokio::test]
+#[ignore]
+async fn access_log_middleware_writes_json_lines_to_the_sink() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.access_log.enabled = true;
+    app_settings.access_log.format = AccessLogFormat::Json;
+    let TestApp {
+        app_settings,
+        listener,
+        pg_pool,
+        redis_pool,
+    } = configure_test_app(app_settings).await;
+    // The client connects over the loopback interface, so the peer has the address the server is bound to
+    let client_ip = listener.local_addr().unwrap().ip().to_string();
+    let sink = Arc::new(MemoryAccessLogSink::default());
+    let database_settings = app_settings.database.clone();
+    let mut app_state = AppState::new(
+        app_settings,
+        pg_pool.clone(),
+        redis_pool,
+        Arc::new(TracingMailer),
+    );
+    app_state.access_log_sink = sink.clone();
+    let origin = format!(
+        "{}://{}:{}",
+        app_state.app_settings.http.protocol,
+        app_state.app_settings.http.host,
+        app_state.app_settings.http.port,
+    );
+    let (app_handle, shutdown_signal) = spawn_router(create_router(app_state), listener).await;
+
+    let http_client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap();
+    // A request with the request ID specified by the client
+    let response = http_client
+        .get(format!(
+            "{}{}{}",
+            origin,
+            paths::API_PREFIX,
+            paths::HEALTH_CHECK
+        ))
+        .header("x-request-id", "test-request-id")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-request-id"], "test-request-id");
+    // A request without the request ID, which is rejected by the authorization middleware
+    let response = http_client
+        .get(format!(
+            "{}{}{}?keyword=secret",
+            origin,
+            paths::API_PREFIX,
+            paths::TODOS
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let generated_request_id = response.headers()["x-request-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    shutdown_signal.send(()).unwrap();
+    app_handle.join().unwrap();
+    teardown_database(pg_pool, &database_settings).await;
+
+    let lines = sink.lines.lock().unwrap().clone();
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    let entries = lines
+        .iter()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(entries[0]["method"], "GET");
+    assert_eq!(
+        entries[0]["path"],
+        format!("{}{}", paths::API_PREFIX, paths::HEALTH_CHECK)
+    );
+    assert_eq!(entries[0]["status"], 200);
+    assert!(entries[0]["duration_ms"].is_u64());
+    assert_eq!(entries[0]["ip"], client_ip.as_str());
+    assert_eq!(entries[0]["request_id"], "test-request-id");
+    // The query string is not recorded in the access log
+    assert_eq!(
+        entries[1]["path"],
+        format!("{}{}", paths::API_PREFIX, paths::TODOS)
+    );
+    assert_eq!(entries[1]["status"], 401);
+    assert_eq!(entries[1]["request_id"], generated_request_id.as_str());
+}
+
+/// Check that the request log middleware emits exactly one event per request, which records the
+/// path template instead of the actual path, the final status, the latency, the authenticated user
+/// and the response size.
+#[tokio::test]
+#[ignore]
+async fn request_log_records_the_path_template_and_the_authenticated_user() {
+    let app_settings = load_app_settings_for_testing();
+    let TestApp {
+        app_settings,
+        listener,
+        pg_pool,
+        redis_pool,
+    } = configure_test_app(app_settings).await;
+    let content = std::fs::read_to_string("./fixtures/test.sql").unwrap();
+    sqlx::raw_sql(&content).execute(&pg_pool).await.unwrap();
+    let database_settings = app_settings.database.clone();
+    let app_state = AppState::new(
+        app_settings,
+        pg_pool.clone(),
+        redis_pool,
+        Arc::new(TracingMailer),
+    );
+    let origin = format!(
+        "{}://{}:{}{}",
+        app_state.app_settings.http.protocol,
+        app_state.app_settings.http.host,
+        app_state.app_settings.http.port,
+        paths::API_PREFIX,
+    );
+    let sink = LogSink::default();
+    let writer = sink.clone();
+    let subscriber = get_subscriber("test".into(), tracing::Level::INFO, move || writer.clone());
+    let (app_handle, shutdown_signal) =
+        spawn_router_with_subscriber(create_router(app_state), listener, subscriber).await;
+
+    let http_client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let api = TodoApiClient::new(origin, http_client);
+    let body = LoginRequestBody {
+        email: String::from("taro@example.com"),
+        password: SecretString::new("ab12AB#$".into()),
+    };
+    api.login(&body).await.unwrap();
+    api.todo_get("4da95cdb-6898-4739-b2be-62ceaa174baf")
+        .await
+        .unwrap();
+    // A request to the todo that does not exist, which the handler rejects with an API error
+    let error = api.todo_get(uuid::Uuid::new_v4()).await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::NOT_FOUND
+    );
+
+    shutdown_signal.send(()).unwrap();
+    app_handle.join().unwrap();
+    teardown_database(pg_pool, &database_settings).await;
+
+    let logs = sink.contents();
+    let template = format!("{}{}", paths::API_PREFIX, paths::TODO_BY_ID);
+    let events = logs
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|log| log["msg"] == "Request completed")
+        .collect::<Vec<_>>();
+    // The login request is logged without the user, because it is not authenticated yet
+    let login = events
+        .iter()
+        .find(|event| event["path"] == format!("{}{}", paths::API_PREFIX, paths::USERS_LOGIN))
+        .unwrap_or_else(|| panic!("No request log of the login: {}", logs));
+    assert!(login.get("user_id").is_none(), "{}", login);
+    let todo_events = events
+        .iter()
+        .filter(|event| event["path"] == template.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(todo_events.len(), 2, "{}", logs);
+    for (event, status) in todo_events.iter().zip([200, 404]) {
+        assert_eq!(event["method"], "GET");
+        assert_eq!(event["status"], status);
+        assert_eq!(event["user_id"], TARO_USER_ID.to_string());
+        assert!(event["latency_ms"].is_u64(), "{}", event);
+        assert!(0 < event["response_size"].as_u64().unwrap(), "{}", event);
+    }
+}
+
+/// Check that the error log of a repository error contains the whole error chain including the
+/// database error.
+#[tokio::test]
+#[ignore]
+async fn repository_error_log_contains_error_chain() {
+    let app_settings = load_app_settings_for_testing();
+    let TestApp {
+        app_settings,
+        listener,
+        pg_pool,
+        redis_pool,
+    } = configure_test_app(app_settings).await;
+    let content = std::fs::read_to_string("./fixtures/test.sql").unwrap();
+    sqlx::raw_sql(&content).execute(&pg_pool).await.unwrap();
+    // Force the database error by dropping the table of the test database
+    sqlx::query("DROP TABLE todos CASCADE")
+        .execute(&pg_pool)
+        .await
+        .unwrap();
+    let database_settings = app_settings.database.clone();
+    let app_state = AppState::new(
+        app_settings,
+        pg_pool.clone(),
+        redis_pool,
+        Arc::new(TracingMailer),
+    );
+    let origin = format!(
+        "{}://{}:{}{}",
+        app_state.app_settings.http.protocol,
+        app_state.app_settings.http.host,
+        app_state.app_settings.http.port,
+        paths::API_PREFIX,
+    );
+    let sink = LogSink::default();
+    let writer = sink.clone();
+    let subscriber = get_subscriber("test".into(), tracing::Level::INFO, move || writer.clone());
+    let (app_handle, shutdown_signal) =
+        spawn_router_with_subscriber(create_router(app_state), listener, subscriber).await;
+
+    let http_client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let api = TodoApiClient::new(origin, http_client);
+    let body = LoginRequestBody {
+        email: String::from("taro@example.com"),
+        password: SecretString::new("ab12AB#$".into()),
+    };
+    api.login(&body).await.unwrap();
+    let error = api
+        .todos_list(&TodoListQueryParams::default())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+
+    shutdown_signal.send(()).unwrap();
+    app_handle.join().unwrap();
+    teardown_database(pg_pool, &database_settings).await;
+
+    let logs = sink.contents();
+    let error_log = logs
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find(|log| log.get("error_chain").is_some())
+        .unwrap_or_else(|| panic!("No error log with error_chain: {}", logs));
+    let error_chain = error_log["error_chain"].as_str().unwrap();
+    assert!(
+        error_chain.contains(r#"relation "todos" does not exist"#),
+        "{}",
+        error_chain
+    );
+    assert_eq!(error_log["status_code"], "500 Internal Server Error");
+    assert_eq!(error_log["kind"], "InternalServerError");
+}
+
+/// Check that the server returns 503 Service Unavailable quickly when the redis is down, and stops
+/// connecting to the redis after consecutive failures.
+#[tokio::test]
+#[ignore]
+async fn server_returns_service_unavailable_quickly_when_the_redis_is_down() {
+    let mut app_settings = load_app_settings_for_testing();
+    // Point the redis settings at a closed port
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    app_settings.redis.host = String::from("127.0.0.1");
+    app_settings.redis.port = listener.local_addr().unwrap().port();
+    drop(listener);
+    app_settings.redis.connection_timeout_ms = 200;
+    app_settings.redis.circuit_breaker.failure_threshold = 2;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    let assert_unavailable = |error: ApiClientError| {
+        let error = error.api_error().unwrap();
+        assert_eq!(error.status_code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(error.messages, vec![REDIS_UNAVAILABLE_MESSAGE]);
+    };
+    // Login stores the tokens in the redis
+    let body = LoginRequestBody {
+        email: String::from("taro@example.com"),
+        password: SecretString::new("ab12AB#$".into()),
+    };
+    let started_at = std::time::Instant::now();
+    assert_unavailable(test_case.api.login(&body).await.unwrap_err());
+    assert!(started_at.elapsed() < REDIS_OUTAGE_RESPONSE_TIME);
+    // The authorization middleware looks up the access token in the redis
+    let api = test_case.bearer_api_client(&SecretString::new("access-token".into()));
+    let started_at = std::time::Instant::now();
+    assert_unavailable(api.me().await.unwrap_err());
+    assert!(started_at.elapsed() < REDIS_OUTAGE_RESPONSE_TIME);
+    // The circuit breaker is open after the consecutive failures, so the server fails without
+    // connecting to the redis
+    assert!(test_case.redis_circuit_breaker().is_open());
+    let started_at = std::time::Instant::now();
+    assert_unavailable(api.me().await.unwrap_err());
+    assert!(started_at.elapsed() < REDIS_OUTAGE_RESPONSE_TIME);
+
+    test_case.end().await;
+}
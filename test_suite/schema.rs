@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+
+use crate::helpers::load_app_settings_for_testing;
+use crate::test_case::{EnableTracing, InsertTestData, TestCase};
+
+/// Whether the Rust field mapped to a column is an `Option`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// The field is not an `Option`, so the column must be `NOT NULL`
+    Required,
+    /// The field is an `Option`, so the column must be nullable
+    Optional,
+}
+
+use Field::{Optional, Required};
+
+/// Mapping from the columns of the tables to the Rust fields of the domain models and the rows
+///
+/// Update the mapping when a migration adds, drops or changes the nullability of a column.
+const COLUMN_FIELDS: &[(&str, &[(&str, Field)])] = &[
+    (
+        "roles",
+        &[
+            ("code", Required),
+            ("name", Required),
+            ("description", Optional),
+            ("display_order", Required),
+            ("created_at", Required),
+            ("updated_at", Required),
+        ],
+    ),
+    (
+        "users",
+        &[
+            ("id", Required),
+            ("family_name", Required),
+            ("given_name", Required),
+            ("email", Required),
+            ("hashed_password", Required),
+            ("role_code", Required),
+            ("active", Required),
+            ("last_login_at", Optional),
+            ("preferences", Required),
+            ("locked_at", Optional),
+            ("lock_reason", Optional),
+            ("locale", Optional),
+            ("created_at", Required),
+            ("updated_at", Required),
+        ],
+    ),
+    (
+        "user_tokens",
+        &[
+            ("id", Required),
+            ("user_id", Required),
+            ("token_key", Required),
+            ("expired_at", Required),
+            ("created_at", Required),
+            ("updated_at", Required),
+        ],
+    ),
+    (
+        "login_failed_histories",
+        &[
+            ("user_id", Required),
+            ("attempted_at", Required),
+            ("number_of_attempts", Required),
+            ("created_at", Required),
+            ("updated_at", Required),
+        ],
+    ),
+    (
+        "todo_statuses",
+        &[
+            ("code", Required),
+            ("name", Required),
+            ("description", Optional),
+            ("display_order", Required),
+            ("created_at", Required),
+            ("updated_at", Required),
+        ],
+    ),
+    (
+        "todos",
+        &[
+            ("id", Required),
+            ("user_id", Required),
+            ("title", Required),
+            ("description", Optional),
+            ("todo_status_code", Required),
+            ("due_date", Optional),
+            ("completed_at", Optional),
+            ("archived", Required),
+            ("created_at", Required),
+            ("updated_at", Required),
+        ],
+    ),
+    (
+        "todo_comments",
+        &[
+            ("id", Required),
+            ("todo_id", Required),
+            ("user_id", Required),
+            ("body", Required),
+            ("created_at", Required),
+        ],
+    ),
+    (
+        "daily_digests",
+        &[
+            ("user_id", Required),
+            ("digest_date", Required),
+            ("due_today", Required),
+            ("overdue", Required),
+            ("created_at", Required),
+            ("updated_at", Required),
+        ],
+    ),
+];
+
+/// Check that the nullability of every column matches whether the mapped Rust field is an `Option`.
+///
+/// This test also fails when a column is missing from the mapping, or the mapping contains a
+/// column that does not exist.
+#[tokio::test]
+#[ignore]
+async fn column_nullability_matches_rust_fields() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let tables = COLUMN_FIELDS
+        .iter()
+        .map(|(table, _)| table.to_string())
+        .collect::<Vec<_>>();
+    // The columns of information_schema are domain types, so cast them to text
+    let rows = sqlx::query_as::<_, (String, String, String)>(
+        r#"
+        SELECT table_name::text, column_name::text, is_nullable::text
+        FROM information_schema.columns
+        WHERE table_schema = 'public' AND table_name = ANY($1)
+        "#,
+    )
+    .bind(&tables)
+    .fetch_all(&test_case.pg_pool)
+    .await
+    .unwrap();
+    let actual = rows
+        .into_iter()
+        .map(|(table, column, is_nullable)| {
+            let field = if is_nullable == "YES" {
+                Optional
+            } else {
+                Required
+            };
+            ((table, column), field)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let expected = COLUMN_FIELDS
+        .iter()
+        .flat_map(|(table, columns)| {
+            columns
+                .iter()
+                .map(|(column, field)| ((table.to_string(), column.to_string()), *field))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let mut mismatches = Vec::new();
+    for (key, field) in &expected {
+        match actual.get(key) {
+            Some(column) if column == field => {}
+            Some(column) => mismatches.push(format!(
+                "{}.{}: the Rust field is {:?}, but the column is {:?}",
+                key.0, key.1, field, column
+            )),
+            None => mismatches.push(format!("{}.{}: the column does not exist", key.0, key.1)),
+        }
+    }
+    for key in actual.keys().filter(|key| !expected.contains_key(*key)) {
+        mismatches.push(format!("{}.{}: the column is not mapped", key.0, key.1));
+    }
+    assert!(mismatches.is_empty(), "{:#?}", mismatches);
+
+    test_case.end().await;
+}
@@ -0,0 +1,75 @@
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use app::routes::paths;
+
+use crate::{
+    helpers::{ResponseParts, load_app_settings_for_testing, split_response},
+    test_case::{EnableTracing, InsertTestData, TestCase},
+};
+
+const INDEX_HTML: &str = "<!doctype html><html><body>rusty-todo</body></html>";
+
+/// Check that the static files are served and unknown non-API paths fall back to index.html,
+/// while unknown API paths still return a JSON API error.
+#[tokio::test]
+#[ignore]
+async fn static_files_are_served_with_spa_fallback() {
+    let static_dir = std::env::temp_dir().join(format!("rusty-todo-static-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), INDEX_HTML).unwrap();
+    std::fs::write(static_dir.join("app.js"), "console.log('rusty-todo');").unwrap();
+
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.http.static_dir = Some(static_dir.to_str().unwrap().to_string());
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+    let settings = &test_case.app_state.app_settings.http;
+    let root = format!(
+        "{}://{}:{}",
+        settings.protocol, settings.host, settings.port
+    );
+
+    // The index.html is served at the root
+    let response = test_case.http_client.get(&root).send().await.unwrap();
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK);
+    assert_eq!(body, INDEX_HTML);
+
+    // The existing static file is served
+    let uri = format!("{}/app.js", root);
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK);
+    assert_eq!(body, "console.log('rusty-todo');");
+
+    // The unknown non-API path falls back to the index.html
+    let uri = format!("{}/todos/some-client-side-route", root);
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK);
+    assert_eq!(body, INDEX_HTML);
+
+    // The API routes are still served
+    let uri = test_case.url(paths::HEALTH_CHECK);
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The unknown API path returns a JSON API error instead of the index.html
+    let uri = format!("{}/unknown", test_case.origin());
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::NOT_FOUND);
+    let body = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+    assert!(body["messages"].is_array());
+
+    test_case.end().await;
+    std::fs::remove_dir_all(&static_dir).unwrap();
+}
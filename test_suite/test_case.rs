@@ -10,43 +10,61 @@
 //!
 //! [NOTICE]
 //!
-//! A test database is created for each test run.
-//! So you must run the `bin/drop_test_dbs.sh` script to drop all the test databases.
-use std::{thread::JoinHandle, time::Duration};
+//! A test database is created for each test case, and `TestCase::end` drops it.
+//! Set the `KEEP_TEST_DATABASES` environment variable to keep the test databases for debugging,
+//! and run the `bin/drop_test_dbs.sh` script to drop them afterwards.
+//! The test databases of the failed tests are not dropped, because the tests panic before `TestCase::end`.
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
 
 use once_cell::sync::Lazy;
-use secrecy::SecretString;
+use secrecy::{ExposeSecret as _, SecretString};
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
-use app::{get_subscriber, init_subscriber};
+use api_client::{LoginRequestBody, TodoApiClient, TodoListQueryParams};
+use app::{get_subscriber, init_subscriber, routes::paths};
 use domain::{
-    models::{LoginFailedHistory, User, UserId},
+    DomainResult,
+    models::{LockReason, LoginFailedHistory, User, UserId},
     repositories::{
         TokenContent, TokenRepository, UserRepository, UserToken, generate_auth_token_info_key,
     },
 };
 use infra::{
-    AppState, http::handler::todo::TodoListQueryParams, postgres::repositories::PgUserRepository,
-    redis::token::RedisTokenRepository, settings::AppSettings,
+    AppState, Backend,
+    mail::{Mail, Mailer},
+    redis::circuit_breaker::CircuitBreaker,
+    settings::AppSettings,
 };
 
-use crate::helpers::{TestApp, configure_test_app, spawn_app};
+use crate::helpers::{TestApp, configure_test_app, spawn_app, teardown_database};
 
 pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub static TARO_USER_ID: Lazy<Uuid> =
     Lazy::new(|| Uuid::parse_str("47125c09-1dea-42b2-a14e-357e59acf3dc").unwrap());
 
+pub static HANAKO_USER_ID: Lazy<Uuid> =
+    Lazy::new(|| Uuid::parse_str("dcae7076-8c5a-4d4c-8894-bcaca68131c6").unwrap());
+
 /// Test case for integration tests
 ///
 /// ```
 /// #[tokio::test]
 /// async fn integration_test_case_skeleton() {
 ///     // Initialize the test case
-///     let test_case = TestCase::begin(true).await;
-///     println!("Test application started on port: {}", test_case.port());
+///     let app_settings = load_app_settings_for_testing();
+///     let test_case = TestCase::begin(app_settings, EnableTracing::Yes, InsertTestData::No).await;
+///     println!("Test application started on: {}", test_case.origin());
 ///
 ///     /************************************************************
 ///
@@ -58,15 +76,57 @@ pub static TARO_USER_ID: Lazy<Uuid> =
 ///     println!("Waiting for 3 seconds before sending graceful shutdown signal...");
 ///     std::thread::sleep(std::time::Duration::from_secs(3));
 ///
-///     // Terminate the test case gracefully
+///     // Terminate the test case gracefully, and drop the test database
 ///     test_case.end().await;
 /// }
 /// ```
 pub struct TestCase {
     pub app_state: AppState,
+    pub pg_pool: PgPool,
+    pub redis_pool: deadpool_redis::Pool,
+    pub mailer: Arc<RecordingMailer>,
     app_handle: JoinHandle<()>,
     shutdown_signal: oneshot::Sender<()>,
     pub http_client: reqwest::Client,
+    pub api: TodoApiClient,
+}
+
+/// Mailer that records the mails instead of sending them
+#[derive(Debug, Default)]
+pub struct RecordingMailer {
+    mails: Mutex<Vec<Mail>>,
+    undeliverable: AtomicBool,
+}
+
+impl RecordingMailer {
+    /// Makes the mailer behave like the one used when the mail server is not configured
+    pub fn disable_delivery(&self) {
+        self.undeliverable.store(true, Ordering::SeqCst);
+    }
+
+    pub fn mails(&self) -> Vec<Mail> {
+        self.mails.lock().unwrap().clone()
+    }
+
+    /// Returns the confirmation token in the last mail, which is written on the last line of the body.
+    pub fn last_token(&self) -> SecretString {
+        let mails = self.mails.lock().unwrap();
+        let mail = mails.last().expect("No mail has been sent");
+        let token = mail.body.lines().last().unwrap();
+        SecretString::new(token.into())
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for RecordingMailer {
+    async fn send(&self, mail: Mail) -> DomainResult<()> {
+        self.mails.lock().unwrap().push(mail);
+        Ok(())
+    }
+
+    fn delivers(&self) -> bool {
+        !self.undeliverable.load(Ordering::SeqCst)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -107,22 +167,30 @@ impl TestCase {
             pg_pool,
             redis_pool,
         } = app;
-        let app_state = AppState {
+        let mailer = Arc::new(RecordingMailer::default());
+        let app_state = AppState::new(
             app_settings,
-            pg_pool,
-            redis_pool,
-        };
+            pg_pool.clone(),
+            redis_pool.clone(),
+            mailer.clone(),
+        );
         let (app_handle, shutdown_signal) = spawn_app(app_state.clone(), listener).await;
         let http_client = reqwest::Client::builder()
             .timeout(REQUEST_TIMEOUT)
             .cookie_store(true)
             .build()
             .unwrap();
+        // The typed API client shares the cookie store with the raw HTTP client
+        let api = TodoApiClient::new(origin(&app_state.app_settings), http_client.clone());
         Self {
             app_state,
+            pg_pool,
+            redis_pool,
+            mailer,
             app_handle,
             shutdown_signal,
             http_client,
+            api,
         }
     }
 
@@ -132,29 +200,42 @@ impl TestCase {
         tracing::trace!("Waiting for server to gracefully shutdown...");
         self.app_handle.join().unwrap();
         tracing::trace!("Server has gracefully shutdown.");
+        teardown_database(self.pg_pool, &self.app_state.app_settings.database).await;
     }
 
     pub fn origin(&self) -> String {
-        format!(
-            "{}://{}:{}/api/v1",
-            self.app_state.app_settings.http.protocol,
-            self.app_state.app_settings.http.host,
-            self.app_state.app_settings.http.port,
-        )
+        origin(&self.app_state.app_settings)
+    }
+
+    /// Returns the URL of the API path
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.origin(), path)
+    }
+
+    /// Returns the circuit breaker shared by the Redis token repositories
+    pub fn redis_circuit_breaker(&self) -> &CircuitBreaker {
+        match &self.app_state.backend {
+            Backend::Postgres {
+                redis_circuit_breaker,
+                ..
+            } => redis_circuit_breaker,
+            #[cfg(feature = "lite")]
+            Backend::Sqlite { .. } => panic!("The test case does not use the redis"),
+        }
     }
 
     pub async fn user_by_id(&self, user_id: UserId) -> Option<User> {
-        let user_repo = PgUserRepository::new(self.app_state.pg_pool.clone());
+        let user_repo = self.app_state.repos.user.clone();
         user_repo.by_id(user_id).await.unwrap()
     }
 
     pub async fn get_login_failed_history(&self, user_id: UserId) -> Option<LoginFailedHistory> {
-        let user_repo = PgUserRepository::new(self.app_state.pg_pool.clone());
+        let user_repo = self.app_state.repos.user.clone();
         user_repo.get_login_failed_history(user_id).await.unwrap()
     }
 
     pub async fn user_tokens_from_user_repo(&self, user_id: UserId) -> Vec<UserToken> {
-        let user_repo = PgUserRepository::new(self.app_state.pg_pool.clone());
+        let user_repo = self.app_state.repos.user.clone();
         user_repo.user_tokens_by_id(user_id).await.unwrap()
     }
 
@@ -162,13 +243,13 @@ impl TestCase {
         &self,
         token: &SecretString,
     ) -> Option<TokenContent> {
-        let token_repo = RedisTokenRepository::new(self.app_state.redis_pool.clone());
+        let token_repo = self.app_state.repos.token.clone();
         let key = generate_auth_token_info_key(token);
         token_repo.get_token_content(&key).await.unwrap()
     }
 
     pub async fn set_user_active_status(&self, user_id: UserId, active: bool) {
-        let mut tx = self.app_state.pg_pool.begin().await.unwrap();
+        let mut tx = self.pg_pool.begin().await.unwrap();
         sqlx::query!(
             "UPDATE users SET active = $1 WHERE id = $2",
             active,
@@ -180,8 +261,25 @@ impl TestCase {
         tx.commit().await.unwrap();
     }
 
+    pub async fn lock_user(&self, user_id: UserId, reason: LockReason) {
+        let mut tx = self.pg_pool.begin().await.unwrap();
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET active = FALSE, locked_at = CURRENT_TIMESTAMP, lock_reason = $1
+            WHERE id = $2
+            "#,
+            reason.as_str(),
+            user_id.0
+        )
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        tx.commit().await.unwrap();
+    }
+
     pub async fn sign_up(&self, body: String) -> reqwest::Response {
-        let uri = format!("{}/users/sign-up", self.origin());
+        let uri = self.url(paths::USERS_SIGN_UP);
         self.http_client
             .post(&uri)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
@@ -192,7 +290,7 @@ impl TestCase {
     }
 
     pub async fn login(&self, body: String) -> reqwest::Response {
-        let uri = format!("{}/users/login", self.origin());
+        let uri = self.url(paths::USERS_LOGIN);
         self.http_client
             .post(&uri)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
@@ -203,14 +301,29 @@ impl TestCase {
     }
 
     pub async fn me(&self) -> reqwest::Response {
-        let uri = format!("{}/users/me", self.origin());
+        let uri = self.url(paths::USERS_ME);
+        self.http_client.get(&uri).send().await.unwrap()
+    }
+
+    pub async fn refresh_tokens(&self) -> reqwest::Response {
+        let uri = self.url(paths::USERS_REFRESH_TOKENS);
+        self.http_client.post(&uri).send().await.unwrap()
+    }
+
+    pub async fn logout(&self) -> reqwest::Response {
+        let uri = self.url(paths::USERS_LOGOUT);
+        self.http_client.post(&uri).send().await.unwrap()
+    }
+
+    pub async fn export_account(&self) -> reqwest::Response {
+        let uri = self.url(paths::USERS_ME_EXPORT);
         self.http_client.get(&uri).send().await.unwrap()
     }
 
-    pub async fn update_user(&self, body: String) -> reqwest::Response {
-        let uri = format!("{}/users/me", self.origin());
+    pub async fn import_account(&self, body: String) -> reqwest::Response {
+        let uri = self.url(paths::USERS_ME_IMPORT);
         self.http_client
-            .patch(&uri)
+            .post(&uri)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
             .send()
@@ -218,27 +331,48 @@ impl TestCase {
             .unwrap()
     }
 
-    pub async fn refresh_tokens(&self) -> reqwest::Response {
-        let uri = format!("{}/users/refresh-tokens", self.origin());
-        self.http_client.post(&uri).send().await.unwrap()
+    pub async fn login_taro(&self) {
+        let body = LoginRequestBody {
+            email: String::from("taro@example.com"),
+            password: SecretString::new("ab12AB#$".into()),
+        };
+        self.api.login(&body).await.unwrap();
     }
 
-    pub async fn logout(&self) -> reqwest::Response {
-        let uri = format!("{}/users/logout", self.origin());
-        self.http_client.post(&uri).send().await.unwrap()
+    pub async fn login_hanako(&self) {
+        let body = LoginRequestBody {
+            email: String::from("hanako@example.com"),
+            password: SecretString::new("aaBB33@@".into()),
+        };
+        self.api.login(&body).await.unwrap();
     }
 
-    pub async fn login_taro(&self) {
-        let body = RawLoginRequestBody {
-            email: String::from("taro@example.com"),
-            password: String::from("ab12AB#$"),
+    pub async fn login_admin(&self) {
+        let body = LoginRequestBody {
+            email: String::from("admin@example.com"),
+            password: SecretString::new("Adminst0r@tor".into()),
         };
-        let body = serde_json::to_string(&body).unwrap();
-        self.login(body).await;
+        self.api.login(&body).await.unwrap();
+    }
+
+    /// Creates a typed API client that sends the access token as a Bearer token without cookies.
+    pub fn bearer_api_client(&self, access_token: &SecretString) -> TodoApiClient {
+        let mut headers = reqwest::header::HeaderMap::new();
+        let value = format!("Bearer {}", access_token.expose_secret());
+        headers.insert(
+            reqwest::header::AUTHORIZATION,
+            reqwest::header::HeaderValue::from_str(&value).unwrap(),
+        );
+        let http_client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        TodoApiClient::new(self.origin(), http_client)
     }
 
     pub async fn todo_list(&self, params: Option<TodoListQueryParams>) -> reqwest::Response {
-        let uri = format!("{}/todos", self.origin());
+        let uri = self.url(paths::TODOS);
         match params {
             Some(body) => {
                 let params = body.to_string();
@@ -250,12 +384,12 @@ impl TestCase {
     }
 
     pub async fn todo_get_by_id(&self, tood_id: &str) -> reqwest::Response {
-        let uri = format!("{}/todos/{}", self.origin(), tood_id);
+        let uri = self.url(&paths::todo_by_id(tood_id));
         self.http_client.get(&uri).send().await.unwrap()
     }
 
     pub async fn todo_create(&self, body: String) -> reqwest::Response {
-        let uri = format!("{}/todos", self.origin());
+        let uri = self.url(paths::TODOS);
         self.http_client
             .post(&uri)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
@@ -266,7 +400,7 @@ impl TestCase {
     }
 
     pub async fn todo_update(&self, todo_id: &str, body: String) -> reqwest::Response {
-        let uri = format!("{}/todos/{}", self.origin(), todo_id);
+        let uri = self.url(&paths::todo_by_id(todo_id));
         self.http_client
             .patch(&uri)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
@@ -277,12 +411,12 @@ impl TestCase {
     }
 
     pub async fn todo_complete(&self, todo_id: &str) -> reqwest::Response {
-        let uri = format!("{}/todos/{}/complete", self.origin(), todo_id);
+        let uri = self.url(&paths::todo_complete(todo_id));
         self.http_client.post(&uri).send().await.unwrap()
     }
 
     pub async fn todo_reopen(&self, todo_id: &str, body: String) -> reqwest::Response {
-        let uri = format!("{}/todos/{}/reopen", self.origin(), todo_id);
+        let uri = self.url(&paths::todo_reopen(todo_id));
         self.http_client
             .post(&uri)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
@@ -292,8 +426,18 @@ impl TestCase {
             .unwrap()
     }
 
+    /// Reopens the todo with the status specified by the status code.
+    pub async fn todo_reopen_with_status(
+        &self,
+        todo_id: &str,
+        status_code: i16,
+    ) -> reqwest::Response {
+        let body = format!(r#"{{"todoStatusCode": {status_code}}}"#);
+        self.todo_reopen(todo_id, body).await
+    }
+
     pub async fn todo_archive(&self, todo_id: &str, body: String) -> reqwest::Response {
-        let uri = format!("{}/todos/{}/archive", self.origin(), todo_id);
+        let uri = self.url(&paths::todo_archive(todo_id));
         self.http_client
             .post(&uri)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
@@ -304,31 +448,41 @@ impl TestCase {
     }
 
     pub async fn todo_delete(&self, todo_id: &str) -> reqwest::Response {
-        let uri = format!("{}/todos/{}", self.origin(), todo_id);
+        let uri = self.url(&paths::todo_by_id(todo_id));
         self.http_client.delete(&uri).send().await.unwrap()
     }
 
     pub async fn role_list(&self) -> reqwest::Response {
-        let uri = format!("{}/roles", self.origin());
+        let uri = self.url(paths::ROLES);
         self.http_client.get(&uri).send().await.unwrap()
     }
 
-    pub async fn role_by_code(&self, code: i16) -> reqwest::Response {
-        let uri = format!("{}/roles/{}", self.origin(), code);
+    pub async fn role_by_code(&self, code: impl std::fmt::Display) -> reqwest::Response {
+        let uri = self.url(&paths::role_by_code(code));
         self.http_client.get(&uri).send().await.unwrap()
     }
 
     pub async fn todo_status_list(&self) -> reqwest::Response {
-        let uri = format!("{}/todo-statuses", self.origin());
+        let uri = self.url(paths::TODO_STATUSES);
         self.http_client.get(&uri).send().await.unwrap()
     }
 
-    pub async fn todo_status_by_code(&self, code: i16) -> reqwest::Response {
-        let uri = format!("{}/todo-statuses/{}", self.origin(), code);
+    pub async fn todo_status_by_code(&self, code: impl std::fmt::Display) -> reqwest::Response {
+        let uri = self.url(&paths::todo_status_by_code(code));
         self.http_client.get(&uri).send().await.unwrap()
     }
 }
 
+fn origin(app_settings: &AppSettings) -> String {
+    format!(
+        "{}://{}:{}{}",
+        app_settings.http.protocol,
+        app_settings.http.host,
+        app_settings.http.port,
+        paths::API_PREFIX,
+    )
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RawSignUpRequestBody {
@@ -1,19 +1,39 @@
 use reqwest::StatusCode;
+use secrecy::SecretString;
 use time::{
-    OffsetDateTime,
+    Date, OffsetDateTime, UtcOffset,
+    format_description::well_known::Rfc3339,
     macros::{date, datetime},
 };
-use utils::time::DATE_FORMAT;
 use uuid::Uuid;
 
-use domain::models::{Todo, TodoStatusCode};
-use infra::http::handler::todo::TodoListQueryParams;
+use api_client::{
+    LoginRequestBody, Patch, SignUpRequestBody, TodoArchiveRequestBody, TodoCreateRequestBody,
+    TodoListQueryParams, TodoUpdateRequestBody, UserPreferences,
+};
+use app::routes::paths;
+use domain::{
+    DomainErrorKind, NumericOperator,
+    models::{Todo, TodoId, TodoStatusCode, UserId},
+    repositories::{
+        ArchivedFilter, TODO_LIST_CURSOR_FILTER_CHANGED, TODO_LIST_PAGE_SIZE_TOO_LARGE,
+        TODO_LIST_PAGE_SIZE_TOO_SMALL, TODO_LIST_PAGE_TOO_SMALL, TodoListInput, TodoListSort,
+        TodoListView, TodoRepository as _,
+    },
+};
+use infra::postgres::repositories::PgTodoRepository;
+use utils::serde::normalize_offset_datetime;
 
 use crate::{
-    helpers::{ResponseParts, load_app_settings_for_testing, split_response},
+    helpers::{ResponseParts, error_messages, load_app_settings_for_testing, split_response},
     test_case::{EnableTracing, InsertTestData, REQUEST_TIMEOUT, TARO_USER_ID, TestCase},
 };
 
+/// Returns the date a week after today, which is always within the allowed range of due dates
+fn due_date_in_a_week() -> Date {
+    OffsetDateTime::now_utc().date() + time::Duration::days(7)
+}
+
 /// Check that the user can get their own todo list.
 #[tokio::test]
 #[ignore]
@@ -27,8 +47,9 @@ async fn the_user_can_get_their_own_todo_list() {
         status_code, body, ..
     } = split_response(response).await;
     assert_eq!(status_code, StatusCode::OK, "{}", body);
+    // The completed todos are excluded by default
     let todos = serde_json::from_str::<Vec<Todo>>(&body).unwrap();
-    assert_eq!(todos.len(), 14);
+    assert_eq!(todos.len(), 10);
     let todos = serde_json::from_str::<Vec<Todo>>(&body).unwrap();
     let todo = todos
         .iter()
@@ -50,41 +71,394 @@ async fn the_user_can_get_their_own_todo_list() {
     assert_eq!(todo.created_at, datetime!(2025-06-08 06:30:00 +09:00));
     assert_eq!(todo.updated_at, datetime!(2025-06-08 07:00:00 +09:00));
 
-    let body = TodoListQueryParams::default();
-    let response = test_case.todo_list(Some(body)).await;
+    let params = TodoListQueryParams::default();
+    let todos = test_case.api.todos_list(&params).await.unwrap();
+    assert_eq!(todos.len(), 10);
+
+    test_case.end().await;
+}
+
+/// Check that the user who has no todos gets an empty todo list instead of an error.
+#[tokio::test]
+#[ignore]
+async fn the_user_gets_empty_list_when_no_todos_exist() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    // Register a new user, who has no todos, and log in
+    let password = "ab12$%AB";
+    let body = SignUpRequestBody {
+        family_name: String::from("Doe"),
+        given_name: String::from("John"),
+        email: String::from("john@example.com"),
+        password: SecretString::new(password.into()),
+    };
+    test_case.api.sign_up(&body).await.unwrap();
+    let body = LoginRequestBody {
+        email: String::from("john@example.com"),
+        password: SecretString::new(password.into()),
+    };
+    test_case.api.login(&body).await.unwrap();
+
+    let response = test_case.todo_list(None).await;
     let ResponseParts {
         status_code, body, ..
     } = split_response(response).await;
     assert_eq!(status_code, StatusCode::OK, "{}", body);
-    let todos = serde_json::from_str::<Vec<Todo>>(&body).unwrap();
-    assert_eq!(todos.len(), 14);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+        serde_json::json!([])
+    );
 
     test_case.end().await;
 }
 
-/// Check that the user can get their own todo list by specifying the keyword.
+/// Check that the user gets an empty todo list instead of an error when no todos match the
+/// conditions.
 #[tokio::test]
 #[ignore]
-async fn the_user_can_get_their_own_todo_list_by_keyword() {
+async fn the_user_gets_empty_list_for_nonexistent_filter_combination() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
 
-    test_case.login_taro().await;
-    let body = TodoListQueryParams {
-        keyword: Some(String::from("書籍")),
+    // Hanako has no not started or cancelled todos
+    test_case.login_hanako().await;
+    let params = TodoListQueryParams {
+        statuses: Some(vec![
+            TodoStatusCode::NotStarted as i16,
+            TodoStatusCode::Cancelled as i16,
+        ]),
         ..Default::default()
     };
-    let response = test_case.todo_list(Some(body)).await;
+    let response = test_case.todo_list(Some(params.clone())).await;
     let ResponseParts {
         status_code, body, ..
     } = split_response(response).await;
     assert_eq!(status_code, StatusCode::OK, "{}", body);
-    let todos = serde_json::from_str::<Vec<Todo>>(&body).unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+        serde_json::json!([])
+    );
+
+    let todos = test_case.api.todos_list(&params).await.unwrap();
+    assert!(todos.is_empty(), "{}", params);
+
+    test_case.end().await;
+}
+
+/// Check that the user can get their own todo list by specifying the keyword.
+#[tokio::test]
+#[ignore]
+async fn the_user_can_get_their_own_todo_list_by_keyword() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let params = TodoListQueryParams {
+        keyword: Some(String::from("書籍")),
+        ..Default::default()
+    };
+    let todos = test_case.api.todos_list(&params).await.unwrap();
     assert_eq!(todos.len(), 2);
 
     test_case.end().await;
 }
 
+/// Check that the wildcards in the keyword are matched literally.
+#[rstest::rstest]
+#[case::percent("%", &["達成率100%", "進捗50%_確認"])]
+#[case::underscore("_", &["snake_caseの命名", "進捗50%_確認"])]
+#[case::percent_and_underscore("%_", &["進捗50%_確認"])]
+#[tokio::test]
+#[ignore]
+async fn the_user_can_search_todos_by_keyword_containing_wildcards(
+    #[case] keyword: &str,
+    #[case] expected_titles: &[&str],
+) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    for title in ["達成率100%", "snake_caseの命名", "進捗50%_確認"] {
+        let request_body = TodoCreateRequestBody {
+            title: String::from(title),
+            description: None,
+            due_date: None,
+            allow_duplicate: false,
+        };
+        test_case.api.todo_create(&request_body).await.unwrap();
+    }
+    let params = TodoListQueryParams {
+        keyword: Some(String::from(keyword)),
+        ..Default::default()
+    };
+    let todos = test_case.api.todos_list(&params).await.unwrap();
+    let mut titles = todos.iter().map(|t| t.title.as_str()).collect::<Vec<_>>();
+    titles.sort_unstable();
+    let mut expected_titles = expected_titles.to_vec();
+    expected_titles.sort_unstable();
+    assert_eq!(titles, expected_titles);
+
+    test_case.end().await;
+}
+
+/// Check that the user can not search todos with a keyword longer than 100 characters.
+#[tokio::test]
+#[ignore]
+async fn the_user_can_not_search_todos_with_too_long_keyword() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let params = TodoListQueryParams {
+        keyword: Some("あ".repeat(101)),
+        ..Default::default()
+    };
+    let error = test_case.api.todos_list(&params).await.unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error.messages,
+        vec!["keyword: must be 100 characters or less"]
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the user can get their own todo list page by page, and the pages do not overlap.
+#[tokio::test]
+#[ignore]
+async fn the_user_can_get_their_own_todo_list_by_page() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let all = test_case
+        .api
+        .todos_list(&TodoListQueryParams::default())
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 10);
+
+    let mut paged = vec![];
+    for (page, expected) in [(1, 3), (2, 3), (3, 3), (4, 1), (5, 0)] {
+        let params = TodoListQueryParams {
+            page: Some(page),
+            page_size: Some(3),
+            ..Default::default()
+        };
+        let todos = test_case.api.todos_list(&params).await.unwrap();
+        assert_eq!(todos.len(), expected, "{}", params);
+        paged.extend(todos.into_iter().map(|todo| todo.id));
+    }
+    // The pages follow the same order as the unpaged list
+    assert_eq!(paged, all.iter().map(|todo| todo.id).collect::<Vec<_>>());
+
+    // The largest page size is accepted
+    let params = TodoListQueryParams {
+        page_size: Some(200),
+        ..Default::default()
+    };
+    let todos = test_case.api.todos_list(&params).await.unwrap();
+    assert_eq!(todos.len(), 10);
+
+    test_case.end().await;
+}
+
+/// Check that the user gets 400 Bad Request for the page number or page size out of range.
+#[rstest::rstest]
+#[case::page_zero(Some(0), None, TODO_LIST_PAGE_TOO_SMALL)]
+#[case::page_size_zero(None, Some(0), TODO_LIST_PAGE_SIZE_TOO_SMALL)]
+#[case::page_size_too_large(None, Some(201), TODO_LIST_PAGE_SIZE_TOO_LARGE)]
+#[case::page_size_far_too_large(Some(1), Some(999_999), TODO_LIST_PAGE_SIZE_TOO_LARGE)]
+#[tokio::test]
+#[ignore]
+async fn the_user_can_not_get_their_own_todo_list_with_an_invalid_page(
+    #[case] page: Option<u32>,
+    #[case] page_size: Option<u32>,
+    #[case] expected: &str,
+) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let params = TodoListQueryParams {
+        page,
+        page_size,
+        ..Default::default()
+    };
+    let error = test_case.api.todos_list(&params).await.unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::BAD_REQUEST);
+    assert_eq!(error.messages, vec![expected]);
+
+    test_case.end().await;
+}
+
+/// Fetches the todo list of the logged in user page by page with the cursor, and returns the IDs of the
+/// todos in the order they were returned.
+///
+/// `between_pages` is called after the first page is fetched.
+async fn todo_ids_by_cursor(
+    test_case: &TestCase,
+    filters: &TodoListQueryParams,
+    limit: u32,
+    between_pages: impl AsyncFnOnce(),
+) -> Vec<TodoId> {
+    let mut ids = vec![];
+    let mut cursor = None;
+    let mut between_pages = Some(between_pages);
+    loop {
+        let params = TodoListQueryParams {
+            cursor: cursor.clone(),
+            limit: Some(limit),
+            ..filters.clone()
+        };
+        let (todos, next_cursor) = test_case.api.todos_list_by_cursor(&params).await.unwrap();
+        assert!(todos.len() <= limit as usize, "{}", params);
+        ids.extend(todos.into_iter().map(|todo| todo.id));
+        if let Some(between_pages) = between_pages.take() {
+            between_pages().await;
+        }
+        match next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => break,
+        }
+        assert!(
+            ids.len() <= 100,
+            "The cursor did not reach the end of the list"
+        );
+    }
+    ids
+}
+
+/// Check that the user can get their own todo list page by page with the cursor, and the pages follow
+/// the same order as the unpaged list for every sort order.
+#[rstest::rstest]
+#[case::due_date_all(TodoListSort::DueDate, TodoListView::All)]
+#[case::created_at_all(TodoListSort::CreatedAt, TodoListView::All)]
+#[case::updated_at_active(TodoListSort::UpdatedAt, TodoListView::Active)]
+#[tokio::test]
+#[ignore]
+async fn the_user_can_get_their_own_todo_list_by_cursor(
+    #[case] sort: TodoListSort,
+    #[case] view: TodoListView,
+) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let filters = TodoListQueryParams {
+        archived: Some(ArchivedFilter::All),
+        view: Some(view),
+        sort: Some(sort),
+        ..Default::default()
+    };
+    let all = test_case.api.todos_list(&filters).await.unwrap();
+    let paged = todo_ids_by_cursor(&test_case, &filters, 3, async || {}).await;
+    assert_eq!(paged, all.iter().map(|todo| todo.id).collect::<Vec<_>>());
+
+    test_case.end().await;
+}
+
+/// Check that the todos created while the user is getting the todo list with the cursor do not shift
+/// the following pages, so that no todo is duplicated or skipped.
+#[tokio::test]
+#[ignore]
+async fn the_todo_list_by_cursor_is_stable_while_todos_are_created() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    // The newest todo comes first, so the todo created after the first page would shift the offset
+    let filters = TodoListQueryParams {
+        sort: Some(TodoListSort::CreatedAt),
+        ..Default::default()
+    };
+    let all = test_case.api.todos_list(&filters).await.unwrap();
+    let mut created = None;
+    let paged = todo_ids_by_cursor(&test_case, &filters, 3, async || {
+        let body = TodoCreateRequestBody {
+            title: String::from("ページの途中で作成したTodo"),
+            ..Default::default()
+        };
+        created = Some(test_case.api.todo_create(&body).await.unwrap());
+    })
+    .await;
+    assert_eq!(paged, all.iter().map(|todo| todo.id).collect::<Vec<_>>());
+    // The created todo is listed from the beginning
+    let created = created.unwrap();
+    assert!(!paged.contains(&created.id));
+    let todos = test_case.api.todos_list(&filters).await.unwrap();
+    assert_eq!(todos[0].id, created.id);
+
+    test_case.end().await;
+}
+
+/// Check that the user gets 400 Bad Request for the invalid cursor or limit.
+#[rstest::rstest]
+#[case::malformed_cursor(Some("malformed"), Some(3), None, "cursor: malformed")]
+#[case::limit_zero(None, Some(0), None, "limit: must be between 1 and 200")]
+#[case::limit_too_large(None, Some(201), None, "limit: must be between 1 and 200")]
+#[case::with_page(
+    None,
+    Some(3),
+    Some(1),
+    "cursor: can not be combined with page and pageSize"
+)]
+#[tokio::test]
+#[ignore]
+async fn the_user_can_not_get_their_own_todo_list_with_an_invalid_cursor(
+    #[case] cursor: Option<&str>,
+    #[case] limit: Option<u32>,
+    #[case] page: Option<u32>,
+    #[case] expected: &str,
+) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let params = TodoListQueryParams {
+        cursor: cursor.map(String::from),
+        limit,
+        page,
+        ..Default::default()
+    };
+    let error = test_case.api.todos_list(&params).await.unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::BAD_REQUEST);
+    assert_eq!(error.messages, vec![expected]);
+
+    test_case.end().await;
+}
+
+/// Check that the cursor can not be replayed against the todo list with different filters.
+#[tokio::test]
+#[ignore]
+async fn the_user_can_not_use_the_cursor_with_different_filters() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let params = TodoListQueryParams {
+        limit: Some(3),
+        ..Default::default()
+    };
+    let (_, next_cursor) = test_case.api.todos_list_by_cursor(&params).await.unwrap();
+    let params = TodoListQueryParams {
+        keyword: Some(String::from("会議")),
+        cursor: next_cursor,
+        limit: Some(3),
+        ..Default::default()
+    };
+    let error = test_case.api.todos_list(&params).await.unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::BAD_REQUEST);
+    assert_eq!(error.messages, vec![TODO_LIST_CURSOR_FILTER_CHANGED]);
+
+    test_case.end().await;
+}
+
 /// Check that the user can get their own todo list by specifying due date.
 #[tokio::test]
 #[ignore]
@@ -108,7 +482,7 @@ async fn the_user_can_get_their_own_todo_list_by_due_date() {
                 to: None,
                 ..Default::default()
             },
-            12,
+            6,
         ),
         (
             TodoListQueryParams {
@@ -117,7 +491,7 @@ async fn the_user_can_get_their_own_todo_list_by_due_date() {
                 to: None,
                 ..Default::default()
             },
-            6,
+            4,
         ),
         (
             TodoListQueryParams {
@@ -126,7 +500,7 @@ async fn the_user_can_get_their_own_todo_list_by_due_date() {
                 to: None,
                 ..Default::default()
             },
-            8,
+            6,
         ),
         (
             TodoListQueryParams {
@@ -135,7 +509,7 @@ async fn the_user_can_get_their_own_todo_list_by_due_date() {
                 to: None,
                 ..Default::default()
             },
-            7,
+            6,
         ),
         (
             TodoListQueryParams {
@@ -144,7 +518,7 @@ async fn the_user_can_get_their_own_todo_list_by_due_date() {
                 to: None,
                 ..Default::default()
             },
-            8,
+            6,
         ),
         (
             TodoListQueryParams {
@@ -153,7 +527,7 @@ async fn the_user_can_get_their_own_todo_list_by_due_date() {
                 to: Some(date!(2025 - 06 - 18)),
                 ..Default::default()
             },
-            5,
+            4,
         ),
         (
             TodoListQueryParams {
@@ -162,19 +536,29 @@ async fn the_user_can_get_their_own_todo_list_by_due_date() {
                 to: Some(date!(2025 - 06 - 18)),
                 ..Default::default()
             },
+            6,
+        ),
+        (
+            TodoListQueryParams {
+                op: Some(domain::NumericOperator::IsNull),
+                ..Default::default()
+            },
+            2,
+        ),
+        (
+            TodoListQueryParams {
+                op: Some(domain::NumericOperator::IsNotNull),
+                ..Default::default()
+            },
             8,
         ),
     ];
 
+    // The completed todos are excluded by default
     test_case.login_taro().await;
-    for (param, expected) in cases {
-        let response = test_case.todo_list(Some(param.clone())).await;
-        let ResponseParts {
-            status_code, body, ..
-        } = split_response(response).await;
-        assert_eq!(status_code, StatusCode::OK, "{}", body);
-        let todos = serde_json::from_str::<Vec<Todo>>(&body).unwrap();
-        assert_eq!(todos.len(), expected, "{}", param);
+    for (params, expected) in cases {
+        let todos = test_case.api.todos_list(&params).await.unwrap();
+        assert_eq!(todos.len(), expected, "{}", params);
     }
 
     test_case.end().await;
@@ -199,215 +583,748 @@ async fn the_user_can_get_their_own_todo_list_by_todo_statuses() {
                 statuses: Some(vec![1, 3, 4]),
                 ..Default::default()
             },
+            9,
+        ),
+    ];
+
+    test_case.login_taro().await;
+    for (params, expected) in cases {
+        let todos = test_case.api.todos_list(&params).await.unwrap();
+        assert_eq!(todos.len(), expected, "{}", params);
+    }
+
+    test_case.end().await;
+}
+
+/// Check that the user can get their own todo list by specifying the archived state,
+/// and that archived todos are excluded by default.
+#[tokio::test]
+#[ignore]
+async fn the_user_can_get_their_own_todo_list_by_archived() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    let cases = [
+        (TodoListQueryParams::default(), 10),
+        (
+            TodoListQueryParams {
+                archived: Some(ArchivedFilter::Active),
+                ..Default::default()
+            },
+            10,
+        ),
+        (
+            TodoListQueryParams {
+                archived: Some(ArchivedFilter::Archived),
+                ..Default::default()
+            },
+            1,
+        ),
+        (
+            TodoListQueryParams {
+                archived: Some(ArchivedFilter::All),
+                ..Default::default()
+            },
             11,
         ),
+        (
+            TodoListQueryParams {
+                archived: Some(ArchivedFilter::All),
+                view: Some(TodoListView::All),
+                ..Default::default()
+            },
+            14,
+        ),
     ];
 
-    test_case.login_taro().await;
-    for (body, expected) in cases {
-        let response = test_case.todo_list(Some(body)).await;
-        let ResponseParts {
-            status_code, body, ..
-        } = split_response(response).await;
-        assert_eq!(status_code, StatusCode::OK, "{}", body);
-        let todos = serde_json::from_str::<Vec<Todo>>(&body).unwrap();
-        assert_eq!(todos.len(), expected, "{}", body);
-    }
+    test_case.login_taro().await;
+    for (params, expected) in cases {
+        let todos = test_case.api.todos_list(&params).await.unwrap();
+        assert_eq!(todos.len(), expected, "{}", params);
+    }
+
+    test_case.end().await;
+}
+
+/// Check that the user can get their own todo list by specifying the view, and that the todos are
+/// ordered with the completed todos last in the `all` view.
+#[tokio::test]
+#[ignore]
+async fn the_user_can_get_their_own_todo_list_by_view() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    let cases = [
+        (None, 10),
+        (Some(TodoListView::Active), 10),
+        (Some(TodoListView::Completed), 2),
+        (Some(TodoListView::All), 12),
+    ];
+
+    test_case.login_taro().await;
+    for (view, expected) in cases {
+        let params = TodoListQueryParams {
+            view,
+            ..Default::default()
+        };
+        let todos = test_case.api.todos_list(&params).await.unwrap();
+        assert_eq!(todos.len(), expected, "{}", params);
+        match view {
+            Some(TodoListView::Completed) => assert!(
+                todos
+                    .iter()
+                    .all(|todo| todo.status.code == TodoStatusCode::Completed)
+            ),
+            Some(TodoListView::All) => {
+                let first_completed = todos
+                    .iter()
+                    .position(|todo| todo.status.code == TodoStatusCode::Completed)
+                    .unwrap();
+                assert!(
+                    todos[first_completed..]
+                        .iter()
+                        .all(|todo| todo.status.code == TodoStatusCode::Completed),
+                    "the completed todos must be last"
+                );
+            }
+            _ => assert!(
+                todos
+                    .iter()
+                    .all(|todo| todo.status.code != TodoStatusCode::Completed)
+            ),
+        }
+    }
+
+    test_case.end().await;
+}
+
+/// Check that the explicit todo statuses win over the view, and that the view then only affects the
+/// ordering.
+#[tokio::test]
+#[ignore]
+async fn the_todo_statuses_take_precedence_over_the_view() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    // The completed todos are returned even though the active view is the default
+    let params = TodoListQueryParams {
+        statuses: Some(vec![1, 3]),
+        ..Default::default()
+    };
+    let todos = test_case.api.todos_list(&params).await.unwrap();
+    assert_eq!(todos.len(), 8);
+    // The completed view does not exclude the not started todos
+    let params = TodoListQueryParams {
+        statuses: Some(vec![1, 3]),
+        view: Some(TodoListView::Completed),
+        ..Default::default()
+    };
+    let todos = test_case.api.todos_list(&params).await.unwrap();
+    assert_eq!(todos.len(), 8);
+    // The all view puts the completed todos last
+    let params = TodoListQueryParams {
+        statuses: Some(vec![1, 3]),
+        view: Some(TodoListView::All),
+        ..Default::default()
+    };
+    let todos = test_case.api.todos_list(&params).await.unwrap();
+    let codes = todos
+        .iter()
+        .map(|todo| todo.status.code)
+        .collect::<Vec<_>>();
+    assert_eq!(codes[..6], [TodoStatusCode::NotStarted; 6]);
+    assert_eq!(codes[6..], [TodoStatusCode::Completed; 2]);
+
+    test_case.end().await;
+}
+
+/// Check that the todo list falls back to the user's preferences when the query parameters are absent,
+/// and that explicit query parameters always win.
+#[tokio::test]
+#[ignore]
+async fn the_todo_list_falls_back_to_the_user_preferences() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let preferences = test_case.api.preferences().await.unwrap();
+    assert_eq!(preferences, UserPreferences::default());
+
+    let preferences = UserPreferences {
+        default_statuses: vec![TodoStatusCode::NotStarted],
+        ..Default::default()
+    };
+    let updated = test_case
+        .api
+        .update_preferences(&preferences)
+        .await
+        .unwrap();
+    assert_eq!(updated, preferences);
+    assert_eq!(test_case.api.preferences().await.unwrap(), preferences);
+
+    let cases = [
+        (TodoListQueryParams::default(), 6),
+        (
+            TodoListQueryParams {
+                statuses: Some(vec![1, 3, 4]),
+                ..Default::default()
+            },
+            9,
+        ),
+    ];
+    for (params, expected) in cases {
+        let todos = test_case.api.todos_list(&params).await.unwrap();
+        assert_eq!(todos.len(), expected, "{}", params);
+    }
+
+    // Archived todos are included by default when the user prefers to show them
+    let preferences = UserPreferences {
+        show_archived: true,
+        ..Default::default()
+    };
+    test_case
+        .api
+        .update_preferences(&preferences)
+        .await
+        .unwrap();
+    let todos = test_case
+        .api
+        .todos_list(&TodoListQueryParams::default())
+        .await
+        .unwrap();
+    assert_eq!(todos.len(), 11);
+    let params = TodoListQueryParams {
+        archived: Some(ArchivedFilter::Active),
+        ..Default::default()
+    };
+    let todos = test_case.api.todos_list(&params).await.unwrap();
+    assert_eq!(todos.len(), 10);
+
+    test_case.end().await;
+}
+
+/// Check that the user can not store preferences with duplicated todo statuses.
+#[tokio::test]
+#[ignore]
+async fn user_can_not_store_preferences_with_duplicated_statuses() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let preferences = UserPreferences {
+        default_statuses: vec![TodoStatusCode::NotStarted, TodoStatusCode::NotStarted],
+        ..Default::default()
+    };
+    let Err(err) = test_case.api.update_preferences(&preferences).await else {
+        panic!("the duplicated statuses were stored");
+    };
+    let api_error = err.api_error().unwrap();
+    assert_eq!(api_error.status_code, StatusCode::BAD_REQUEST);
+    assert!(api_error.messages[0].starts_with("defaultStatuses: "));
+    assert_eq!(
+        test_case.api.preferences().await.unwrap(),
+        UserPreferences::default()
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the anonymous user can not access the todo list endpoint.
+#[tokio::test]
+#[ignore]
+async fn anonymous_user_can_not_access_the_todo_list_endpoint() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let uri = test_case.url(paths::TODOS);
+    let response = client.get(&uri).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    test_case.end().await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn get_todo_by_id_integration_test() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let valid_todo_id = "ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175";
+    // If the user specifies the ID of a todo that belongs to them, they can get the todo.
+    let response = test_case.todo_get_by_id(valid_todo_id).await;
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "{}",
+        response.text().await.unwrap()
+    );
+
+    // If the ID of a todo is not invalid format, the user gets an error.
+    let response = test_case.todo_get_by_id("invalid-todo-id").await;
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "{}",
+        response.text().await.unwrap()
+    );
+
+    // If the todo with the user's specified ID belongs to another user, the user gets the same error
+    // as when the todo does not exist, because the existence of the todo is hidden by default.
+    let response = test_case
+        .todo_get_by_id("653acf81-a2e6-43cb-b4b4-9cdb822c740e")
+        .await;
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "{}",
+        response.text().await.unwrap()
+    );
+
+    // If the user specifies the ID of a todo that does not exist, they get an error.
+    let todo_id = Uuid::new_v4().to_string();
+    let response = test_case.todo_get_by_id(&todo_id).await;
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "{}",
+        response.text().await.unwrap()
+    );
+
+    // If an anonymous user tries to get a todo, they get an error.
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let uri = test_case.url(&paths::todo_by_id(valid_todo_id));
+    let response = client.get(&uri).send().await.unwrap();
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "{}",
+        response.text().await.unwrap()
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the user gets 403 Forbidden when getting a todo that belongs to another user,
+/// if the existence of todos that belong to other users is not hidden.
+#[tokio::test]
+#[ignore]
+async fn get_todo_that_belongs_to_another_user_without_hiding_existence() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.security.hide_cross_user_existence = false;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let error = test_case
+        .api
+        .todo_get("653acf81-a2e6-43cb-b4b4-9cdb822c740e")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::FORBIDDEN
+    );
+
+    // The todo that does not exist is still reported as not found
+    let error = test_case.api.todo_get(Uuid::new_v4()).await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::NOT_FOUND
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the user can not distinguish a todo that belongs to another user from a todo that does not exist,
+/// if the existence of todos that belong to other users is hidden.
+#[tokio::test]
+#[ignore]
+async fn get_todo_that_belongs_to_another_user_with_hiding_existence() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.security.hide_cross_user_existence = true;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let error = test_case
+        .api
+        .todo_get("653acf81-a2e6-43cb-b4b4-9cdb822c740e")
+        .await
+        .unwrap_err();
+    let another_users_todo = error.api_error().unwrap().clone();
+    let error = test_case.api.todo_get(Uuid::new_v4()).await.unwrap_err();
+    let missing_todo = error.api_error().unwrap().clone();
+    assert_eq!(another_users_todo.status_code, StatusCode::NOT_FOUND);
+    assert_eq!(another_users_todo, missing_todo);
+
+    test_case.end().await;
+}
+
+/// Check that the user can create a todo with a due date.
+#[tokio::test]
+#[ignore]
+async fn create_todo_with_due_date() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let due_date = OffsetDateTime::now_utc().date() + time::Duration::days(7);
+    let request_body = TodoCreateRequestBody {
+        title: String::from("Rustの学習"),
+        description: Some(String::from("Rustの非同期処理を学ぶ")),
+        due_date: Some(due_date),
+        allow_duplicate: false,
+    };
+    let todo = test_case.api.todo_create(&request_body).await.unwrap();
+    assert_eq!(todo.user.id, *TARO_USER_ID);
+    assert_eq!(todo.title, "Rustの学習");
+    assert_eq!(
+        todo.description.as_ref().unwrap(),
+        &"Rustの非同期処理を学ぶ"
+    );
+    assert_eq!(todo.status.code, TodoStatusCode::NotStarted);
+    assert_eq!(todo.due_date, Some(due_date));
+    assert_eq!(todo.completed_at, None);
+
+    test_case.end().await;
+}
+
+// Check that the user can create a todo without a due date.
+#[tokio::test]
+#[ignore]
+async fn create_todo_without_description_and_due_date() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let request_bodies = vec![
+        String::from(
+            r#"
+            {
+                "title": "Rustの学習",
+                "description": null,
+                "dueDate": null
+            }
+            "#,
+        ),
+        String::from(
+            r#"
+            {
+                "title": "Rustの復習"
+            }
+            "#,
+        ),
+    ];
+    for request_body in request_bodies {
+        let response = test_case.todo_create(request_body.clone()).await;
+        let ResponseParts { body, .. } = split_response(response).await;
+        let todo = serde_json::from_str::<Todo>(&body).unwrap();
+        assert!(todo.description.is_none());
+        assert!(todo.due_date.is_none());
+    }
+
+    test_case.end().await;
+}
+
+/// Check that the user can not create a todo whose title matches an active todo case-insensitively, and
+/// that the conflict lists the ids and titles of the duplicates.
+#[rstest::rstest]
+#[case::same_title("Weekly Report")]
+#[case::different_case("weekly REPORT")]
+#[case::surrounding_whitespace("  Weekly Report\t")]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_create_a_todo_with_the_title_of_an_active_todo(#[case] title: &str) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let request_body = TodoCreateRequestBody {
+        title: String::from("Weekly Report"),
+        ..Default::default()
+    };
+    let existing = test_case.api.todo_create(&request_body).await.unwrap();
+    let request_body = TodoCreateRequestBody {
+        title: String::from(title),
+        ..Default::default()
+    };
+    let error = test_case.api.todo_create(&request_body).await.unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::CONFLICT);
+    assert_eq!(
+        error.messages,
+        vec![
+            String::from(
+                "A todo with the same title already exists. Set allowDuplicate to true to create it anyway"
+            ),
+            format!("{}: Weekly Report", existing.id),
+        ]
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the user can create a todo whose title matches an active todo with `allowDuplicate`.
+#[tokio::test]
+#[ignore]
+async fn user_can_create_a_todo_with_the_title_of_an_active_todo_if_duplicates_are_allowed() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let request_body = TodoCreateRequestBody {
+        title: String::from("レポート提出"),
+        allow_duplicate: true,
+        ..Default::default()
+    };
+    let todo = test_case.api.todo_create(&request_body).await.unwrap();
+    assert_eq!(todo.title, "レポート提出");
+    assert_ne!(
+        todo.id,
+        TodoId::from(Uuid::parse_str("ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175").unwrap())
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the `allowDuplicate` field of the request body decides whether a todo whose title matches
+/// an active todo is rejected or created, and that both todos are listed after it is created.
+#[tokio::test]
+#[ignore]
+async fn allow_duplicate_flag_decides_whether_a_duplicate_title_is_created() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    // The flag defaults to false when it is omitted
+    let response = test_case
+        .todo_create(String::from(r#"{"title": "レポート提出"}"#))
+        .await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::CONFLICT, "{}", body);
+    let response = test_case
+        .todo_create(String::from(
+            r#"{"title": "レポート提出", "allowDuplicate": false}"#,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = test_case
+        .todo_create(String::from(
+            r#"{"title": "レポート提出", "allowDuplicate": true}"#,
+        ))
+        .await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::CREATED, "{}", body);
+    let created = serde_json::from_str::<Todo>(&body).unwrap();
+    assert_eq!(created.title, "レポート提出");
+
+    let params = TodoListQueryParams {
+        keyword: Some(String::from("レポート提出")),
+        ..Default::default()
+    };
+    let todos = test_case.api.todos_list(&params).await.unwrap();
+    let titles = todos
+        .iter()
+        .filter(|todo| todo.title == "レポート提出")
+        .count();
+    assert_eq!(titles, 2, "{:?}", todos);
+    assert!(todos.iter().any(|todo| todo.id == created.id));
 
     test_case.end().await;
 }
 
+/// Check that completed todos, archived todos and the todos of other users do not prevent the user from
+/// creating a todo with the same title.
+#[rstest::rstest]
+#[case::completed("会議資料の準備")]
+#[case::archived("旅行の準備")]
+#[case::completed_and_archived("プロジェクトのレビュー")]
+#[case::other_user("掃除する")]
 #[tokio::test]
 #[ignore]
-async fn the_user_can_get_their_own_todo_list_by_archived() {
+async fn user_can_create_a_todo_with_the_title_of_an_inactive_todo(#[case] title: &str) {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
-    let cases = [
-        (
-            TodoListQueryParams {
-                archived: Some(false),
-                ..Default::default()
-            },
-            12,
-        ),
-        (
-            TodoListQueryParams {
-                archived: Some(true),
-                ..Default::default()
-            },
-            2,
-        ),
-    ];
 
     test_case.login_taro().await;
-    for (body, expected) in cases {
-        let response = test_case.todo_list(Some(body)).await;
-        let ResponseParts {
-            status_code, body, ..
-        } = split_response(response).await;
-        assert_eq!(status_code, StatusCode::OK, "{}", body);
-        let todos = serde_json::from_str::<Vec<Todo>>(&body).unwrap();
-        assert_eq!(todos.len(), expected, "{}", body);
-    }
+    let request_body = TodoCreateRequestBody {
+        title: String::from(title),
+        ..Default::default()
+    };
+    let todo = test_case.api.todo_create(&request_body).await.unwrap();
+    assert_eq!(todo.title, title);
 
     test_case.end().await;
 }
 
-/// Check that the anonymous user can not access the todo list endpoint.
+/// Check that the user can create a todo due today or later, but can not create a todo due in the past.
+#[rstest::rstest]
+#[case::yesterday(-1, false)]
+#[case::today(0, true)]
+#[case::tomorrow(1, true)]
 #[tokio::test]
 #[ignore]
-async fn anonymous_user_can_not_access_the_todo_list_endpoint() {
+async fn user_can_not_create_a_todo_with_a_past_due_date(#[case] days: i64, #[case] created: bool) {
     let app_settings = load_app_settings_for_testing();
-    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
 
-    let client = reqwest::Client::builder()
-        .timeout(REQUEST_TIMEOUT)
-        .cookie_store(true)
-        .build()
-        .unwrap();
-    let uri = format!("{}/todos", test_case.origin());
-    let response = client.get(&uri).send().await.unwrap();
-    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    test_case.login_taro().await;
+    let due_date = OffsetDateTime::now_utc().date() + time::Duration::days(days);
+    let request_body = TodoCreateRequestBody {
+        title: String::from("Rustの学習"),
+        description: None,
+        due_date: Some(due_date),
+        allow_duplicate: false,
+    };
+    let result = test_case.api.todo_create(&request_body).await;
+    if created {
+        assert_eq!(result.unwrap().due_date, Some(due_date));
+    } else {
+        let error = result.unwrap_err();
+        let error = error.api_error().unwrap();
+        assert_eq!(error.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(error.messages, vec!["due_date must not be in the past"]);
+    }
 
     test_case.end().await;
 }
 
+/// Check that the user can create a todo due in the past, if the settings allow past due dates.
 #[tokio::test]
 #[ignore]
-async fn get_todo_by_id_integration_test() {
-    let app_settings = load_app_settings_for_testing();
+async fn user_can_create_a_todo_with_a_past_due_date_if_allowed() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.todo.allow_past_due_date = true;
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
 
     test_case.login_taro().await;
-    let valid_todo_id = "ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175";
-    // If the user specifies the ID of a todo that belongs to them, they can get the todo.
-    let response = test_case.todo_get_by_id(valid_todo_id).await;
-    assert_eq!(
-        response.status(),
-        StatusCode::OK,
-        "{}",
-        response.text().await.unwrap()
-    );
-
-    // If the ID of a todo is not invalid format, the user gets an error.
-    let response = test_case.todo_get_by_id("invalid-todo-id").await;
-    assert_eq!(
-        response.status(),
-        StatusCode::BAD_REQUEST,
-        "{}",
-        response.text().await.unwrap()
-    );
-
-    // If the todo with the user's specified ID belongs to another user, the user gets an error.
-    let response = test_case
-        .todo_get_by_id("653acf81-a2e6-43cb-b4b4-9cdb822c740e")
-        .await;
-    assert_eq!(
-        response.status(),
-        StatusCode::FORBIDDEN,
-        "{}",
-        response.text().await.unwrap()
-    );
-
-    // If the user specifies the ID of a todo that does not exist, they get an error.
-    let todo_id = Uuid::new_v4().to_string();
-    let response = test_case.todo_get_by_id(&todo_id).await;
-    assert_eq!(
-        response.status(),
-        StatusCode::NOT_FOUND,
-        "{}",
-        response.text().await.unwrap()
-    );
-
-    // If an anonymous user tries to get a todo, they get an error.
-    let client = reqwest::Client::builder()
-        .timeout(REQUEST_TIMEOUT)
-        .cookie_store(true)
-        .build()
-        .unwrap();
-    let uri = format!("{}/todos/{}", test_case.origin(), valid_todo_id);
-    let response = client.get(&uri).send().await.unwrap();
-    assert_eq!(
-        response.status(),
-        StatusCode::UNAUTHORIZED,
-        "{}",
-        response.text().await.unwrap()
-    );
+    let due_date = OffsetDateTime::now_utc().date() - time::Duration::days(7);
+    let request_body = TodoCreateRequestBody {
+        title: String::from("Rustの学習"),
+        description: None,
+        due_date: Some(due_date),
+        allow_duplicate: false,
+    };
+    let todo = test_case.api.todo_create(&request_body).await.unwrap();
+    assert_eq!(todo.due_date, Some(due_date));
 
     test_case.end().await;
 }
 
-/// Check that the user can create a todo with a due date.
+/// Check that every invalid field is reported when the user creates a todo with multiple invalid fields.
 #[tokio::test]
 #[ignore]
-async fn create_todo_with_due_date() {
+async fn user_can_not_create_a_todo_with_multiple_invalid_fields() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
 
     test_case.login_taro().await;
-    let due_date = OffsetDateTime::now_utc().date() + time::Duration::days(7);
     let request_body = format!(
         r#"
         {{
-            "title": "Rustの学習",
-            "description": "Rustの非同期処理を学ぶ",
-            "dueDate": "{}"
+            "title": "",
+            "description": "{}"
         }}
         "#,
-        due_date.format(&DATE_FORMAT).unwrap()
+        "a".repeat(401)
     );
     let response = test_case.todo_create(request_body).await;
     let ResponseParts {
         status_code, body, ..
     } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::CREATED, "{}", body);
-    let todo = serde_json::from_str::<Todo>(&body).unwrap();
-    assert_eq!(todo.user.id, *TARO_USER_ID);
-    assert_eq!(todo.title, "Rustの学習");
-    assert_eq!(
-        todo.description.as_ref().unwrap(),
-        &"Rustの非同期処理を学ぶ"
+    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
+    let body = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 2, "{}", body);
+    assert!(messages[0].as_str().unwrap().starts_with("title: "));
+    assert!(messages[1].as_str().unwrap().starts_with("description: "));
+
+    test_case.end().await;
+}
+
+/// Check that the user can not create a todo with an invalid field, and gets 400 Bad Request with
+/// the message of the field.
+///
+/// The title and description are validated by the primitives, and the due date in the past is
+/// rejected by the use case.
+#[rstest::rstest]
+#[case::empty_title(r#"{"title": ""}"#.to_string(), "title: ")]
+#[case::blank_title(r#"{"title": "   "}"#.to_string(), "title: ")]
+#[case::too_long_title(format!(r#"{{"title": "{}"}}"#, "a".repeat(101)), "title: ")]
+#[case::too_long_description(
+    format!(r#"{{"title": "Rustの学習", "description": "{}"}}"#, "a".repeat(401)),
+    "description: "
+)]
+#[case::past_due_date(
+    format!(
+        r#"{{"title": "Rustの学習", "dueDate": "{}"}}"#,
+        OffsetDateTime::now_utc().date() - time::Duration::days(1)
+    ),
+    "due_date must not be in the past"
+)]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_create_a_todo_with_an_invalid_field(
+    #[case] request_body: String,
+    #[case] expected_prefix: &str,
+) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case.todo_create(request_body.clone()).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", request_body);
+    let messages = error_messages(&body);
+    assert_eq!(messages.len(), 1, "{:?}", messages);
+    assert!(
+        messages[0].starts_with(expected_prefix),
+        "{}: {}",
+        request_body,
+        messages[0]
     );
-    assert_eq!(todo.status.code, TodoStatusCode::NotStarted);
-    assert_eq!(todo.due_date, Some(due_date));
-    assert_eq!(todo.completed_at, None);
 
     test_case.end().await;
 }
 
-// Check that the user can create a todo without a due date.
+/// Check that the leading and trailing whitespaces of the title and description are removed when
+/// the user creates a todo.
+///
+/// The whitespaces are removed intentionally, so do not change the behavior without updating the API
+/// documentation.
 #[tokio::test]
 #[ignore]
-async fn create_todo_without_description_and_due_date() {
+async fn title_and_description_of_a_created_todo_are_trimmed() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
 
     test_case.login_taro().await;
-    let request_bodies = vec![
-        String::from(
-            r#"
-            {
-                "title": "Rustの学習",
-                "description": null,
-                "dueDate": null
-            }
-            "#,
-        ),
-        String::from(
-            r#"
-            {
-                "title": "Rustの学習"
-            }
-            "#,
-        ),
-    ];
-    for request_body in request_bodies {
-        let response = test_case.todo_create(request_body.clone()).await;
-        let ResponseParts { body, .. } = split_response(response).await;
-        let todo = serde_json::from_str::<Todo>(&body).unwrap();
-        assert!(todo.description.is_none());
-        assert!(todo.due_date.is_none());
-    }
+    let request_body = TodoCreateRequestBody {
+        title: String::from("  Rustの学習\t"),
+        description: Some(String::from("\n Rustの非同期処理を学ぶ ")),
+        due_date: None,
+        allow_duplicate: false,
+    };
+    let todo = test_case.api.todo_create(&request_body).await.unwrap();
+    assert_eq!(todo.title, "Rustの学習");
+    assert_eq!(
+        todo.description.as_ref().unwrap(),
+        &"Rustの非同期処理を学ぶ"
+    );
 
     test_case.end().await;
 }
@@ -431,7 +1348,7 @@ async fn anonymous_user_can_not_access_the_create_todo_endpoint() {
             }
             "#,
     );
-    let uri = format!("{}/todos", test_case.origin());
+    let uri = test_case.url(paths::TODOS);
     let response = client
         .post(&uri)
         .header(reqwest::header::CONTENT_TYPE, "application/json")
@@ -453,24 +1370,18 @@ async fn user_can_update_todo() {
 
     test_case.login_taro().await;
     let todo_id = "4da95cdb-6898-4739-b2be-62ceaa174baf";
-    let request_body = format!(
-        r#"
-        {{
-            "title": "Rustの学習を深める",
-            "description": "Rustの非同期処理とエラーハンドリングを学ぶ",
-            "statusCode": {},
-            "dueDate": "2025-06-30"
-        }}
-        "#,
-        TodoStatusCode::NotStarted as i16
-    );
+    let request_body = TodoUpdateRequestBody {
+        title: Some(String::from("Rustの学習を深める")),
+        description: Patch::Value(String::from("Rustの非同期処理とエラーハンドリングを学ぶ")),
+        status_code: Some(TodoStatusCode::NotStarted.into()),
+        due_date: Patch::Value(due_date_in_a_week()),
+    };
     let requested_at = OffsetDateTime::now_utc();
-    let response = test_case.todo_update(todo_id, request_body).await;
-    let ResponseParts {
-        status_code, body, ..
-    } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::OK, "{}", body);
-    let todo = serde_json::from_str::<Todo>(&body).unwrap();
+    let todo = test_case
+        .api
+        .todo_update(todo_id, &request_body)
+        .await
+        .unwrap();
 
     assert_eq!(todo.title, "Rustの学習を深める");
     assert_eq!(
@@ -478,8 +1389,71 @@ async fn user_can_update_todo() {
         "Rustの非同期処理とエラーハンドリングを学ぶ"
     );
     assert_eq!(todo.status.code, TodoStatusCode::NotStarted);
-    assert_eq!(todo.due_date.unwrap(), date!(2025 - 06 - 30));
-    assert!(todo.updated_at > requested_at);
+    assert_eq!(todo.due_date, Some(due_date_in_a_week()));
+    // The response truncates the timestamps to the precision of the serialization policy
+    assert!(todo.updated_at >= normalize_offset_datetime(requested_at));
+
+    test_case.end().await;
+}
+
+/// Check that the user can clear the description and the due date of the todo with JSON Merge Patch,
+/// and the other fields are not changed.
+#[tokio::test]
+#[ignore]
+async fn user_can_clear_the_description_and_due_date_of_todo_with_merge_patch() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let todo_id = "4da95cdb-6898-4739-b2be-62ceaa174baf";
+    let request_body = TodoUpdateRequestBody {
+        description: Patch::Null,
+        due_date: Patch::Null,
+        ..Default::default()
+    };
+    let todo = test_case
+        .api
+        .merge_patch_todo(todo_id, &request_body)
+        .await
+        .unwrap();
+
+    assert_eq!(todo.title, "チームミーティング");
+    assert!(todo.description.is_none());
+    assert_eq!(todo.status.code, TodoStatusCode::InProgress);
+    assert!(todo.due_date.is_none());
+
+    test_case.end().await;
+}
+
+/// Check that the `set_updated_at` trigger maintains `updated_at` of a todo even if the update query does not
+/// mention it.
+#[tokio::test]
+#[ignore]
+async fn updated_at_of_a_todo_is_set_by_the_trigger() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    let todo_id = Uuid::parse_str("ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175").unwrap();
+    let pool = &test_case.pg_pool;
+    let select_updated_at = "SELECT updated_at FROM todos WHERE id = $1";
+
+    let before = sqlx::query_scalar::<_, OffsetDateTime>(select_updated_at)
+        .bind(todo_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    let requested_at = OffsetDateTime::now_utc();
+    sqlx::query("UPDATE todos SET title = 'Updated without updated_at' WHERE id = $1")
+        .bind(todo_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    let after = sqlx::query_scalar::<_, OffsetDateTime>(select_updated_at)
+        .bind(todo_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert!(after > before, "before: {}, after: {}", before, after);
+    assert!((after - requested_at).abs() < REQUEST_TIMEOUT);
 
     test_case.end().await;
 }
@@ -559,12 +1533,13 @@ async fn user_can_update_todo_with_each_specified_field() {
     assert_eq!(todo.due_date.unwrap(), date!(2025 - 06 - 12));
 
     // Update only the due date of the todo
-    let request_body = String::from(
+    let request_body = format!(
         r#"
-        {
-            "dueDate": "2025-06-30"
-        }
+        {{
+            "dueDate": "{}"
+        }}
         "#,
+        due_date_in_a_week()
     );
     let response = test_case.todo_update(todo_id, request_body).await;
     let ResponseParts {
@@ -578,7 +1553,7 @@ async fn user_can_update_todo_with_each_specified_field() {
         "Rustの非同期処理とエラーハンドリングを学ぶ"
     );
     assert_eq!(todo.status.code, TodoStatusCode::NotStarted);
-    assert_eq!(todo.due_date.unwrap(), date!(2025 - 06 - 30));
+    assert_eq!(todo.due_date.unwrap(), due_date_in_a_week());
 
     test_case.end().await;
 }
@@ -617,7 +1592,7 @@ async fn user_can_not_update_todo_without_specifying_any_fields() {
         assert_eq!(todo.description.unwrap(), "プロジェクトの進捗確認");
         assert_eq!(todo.status.code, TodoStatusCode::InProgress);
         assert_eq!(todo.due_date.unwrap(), date!(2025 - 06 - 12));
-        assert!(todo.updated_at > requested_at);
+        assert!(todo.updated_at >= normalize_offset_datetime(requested_at));
     }
 
     test_case.end().await;
@@ -634,15 +1609,16 @@ async fn user_can_not_update_if_todo_is_completed_or_archived() {
 
     test_case.login_taro().await;
     for todo_id in [completed_todo_id, archived_todo_id] {
-        let request_body = String::from(
+        let request_body = format!(
             r#"
-            {
+            {{
                 "title": "更新できないタイトル",
                 "description": "更新できない説明",
                 "statusCode": 1,
-                "dueDate": "2025-06-30"
-            }
+                "dueDate": "{}"
+            }}
             "#,
+            due_date_in_a_week()
         );
         let response = test_case.todo_update(todo_id, request_body).await;
         let ResponseParts {
@@ -659,24 +1635,38 @@ async fn user_can_not_update_if_todo_is_completed_or_archived() {
     test_case.end().await;
 }
 
-/// Check that the user can not update a todo that belongs to another user.
+/// Check that the user can not update a todo that belongs to another user, and can not tell it from a todo
+/// that does not exist if the existence of todos that belong to other users is hidden.
+#[rstest::rstest]
+#[case::hide_existence(true, StatusCode::NOT_FOUND, "Todo not found")]
+#[case::reveal_existence(
+    false,
+    StatusCode::FORBIDDEN,
+    "You are not authorized to update this todo"
+)]
 #[tokio::test]
 #[ignore]
-async fn user_can_not_update_todo_that_belongs_to_another_user() {
-    let app_settings = load_app_settings_for_testing();
+async fn user_can_not_update_todo_that_belongs_to_another_user(
+    #[case] hide_cross_user_existence: bool,
+    #[case] expected_status_code: StatusCode,
+    #[case] expected_message: &str,
+) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.security.hide_cross_user_existence = hide_cross_user_existence;
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
 
     test_case.login_taro().await;
     let another_user_todo_id = "653acf81-a2e6-43cb-b4b4-9cdb822c740e";
-    let request_body = String::from(
+    let request_body = format!(
         r#"
-        {
+        {{
             "title": "更新できないタイトル",
             "description": "更新できない説明",
             "statusCode": 1,
-            "dueDate": "2025-06-30"
-        }
+            "dueDate": "{}"
+        }}
         "#,
+        due_date_in_a_week()
     );
     let response = test_case
         .todo_update(another_user_todo_id, request_body)
@@ -684,12 +1674,8 @@ async fn user_can_not_update_todo_that_belongs_to_another_user() {
     let ResponseParts {
         status_code, body, ..
     } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::FORBIDDEN, "{}", body);
-    assert!(
-        body.contains("You are not authorized to update this todo"),
-        "{}",
-        body
-    );
+    assert_eq!(status_code, expected_status_code, "{}", body);
+    assert_eq!(error_messages(&body), vec![expected_message], "{}", body);
 
     test_case.end().await;
 }
@@ -703,15 +1689,16 @@ async fn user_can_not_update_todo_that_is_not_recorded_in_any_todos() {
 
     test_case.login_taro().await;
     let todo_id = Uuid::new_v4().to_string();
-    let request_body = String::from(
+    let request_body = format!(
         r#"
-        {
+        {{
             "title": "更新できないタイトル",
             "description": "更新できない説明",
             "statusCode": 1,
-            "dueDate": "2025-06-30"
-        }
+            "dueDate": "{}"
+        }}
         "#,
+        due_date_in_a_week()
     );
     let response = test_case.todo_update(&todo_id, request_body).await;
     let ResponseParts {
@@ -731,16 +1718,17 @@ async fn user_can_not_update_todo_if_user_specifies_invalid_todo_id() {
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
 
     test_case.login_taro().await;
-    let request_body = String::from(
+    let request_body = format!(
         r#"
-        {
+        {{
             "id": "invalid-todo-id",
             "title": "更新できないタイトル",
             "description": "更新できない説明",
             "statusCode": -32768,
-            "dueDate": "2025-06-30"
-        }
+            "dueDate": "{}"
+        }}
         "#,
+        due_date_in_a_week()
     );
     let response = test_case.todo_update("invalid-todo-id", request_body).await;
     let ResponseParts {
@@ -761,15 +1749,16 @@ async fn user_can_not_update_todo_if_user_specifies_an_invalid_status_code() {
 
     test_case.login_taro().await;
     let todo_id = "4da95cdb-6898-4739-b2be-62ceaa174baf";
-    let request_body = String::from(
+    let request_body = format!(
         r#"
-        {
+        {{
             "title": "更新できないタイトル",
             "description": "更新できない説明",
             "statusCode": -32768,
-            "dueDate": "2025-06-30"
-        }
+            "dueDate": "{}"
+        }}
         "#,
+        due_date_in_a_week()
     );
     let response = test_case.todo_update(todo_id, request_body).await;
     let ResponseParts {
@@ -831,7 +1820,7 @@ async fn anonymous_user_can_not_access_the_update_todo_endpoint() {
         "#,
         TodoStatusCode::NotStarted as i16
     );
-    let uri = format!("{}/todos/{}", test_case.origin(), todo_id);
+    let uri = test_case.url(&paths::todo_by_id(todo_id));
     let response = client
         .patch(&uri)
         .header(reqwest::header::CONTENT_TYPE, "application/json")
@@ -857,155 +1846,88 @@ async fn user_can_complete_todo() {
     test_case.login_taro().await;
     for todo_id in completable_todo_ids {
         let requested_at = OffsetDateTime::now_utc();
-        let response = test_case.todo_complete(todo_id).await;
-        let ResponseParts {
-            status_code, body, ..
-        } = split_response(response).await;
-        assert_eq!(status_code, StatusCode::OK, "{}", body);
-        let todo = serde_json::from_str::<Todo>(&body).unwrap();
+        let todo = test_case.api.todo_complete(todo_id).await.unwrap();
         assert_eq!(todo.status.code, TodoStatusCode::Completed);
         assert!((todo.completed_at.unwrap() - requested_at).abs() < REQUEST_TIMEOUT);
+        // `updated_at` is set by the trigger, and must equal `completed_at` set by the query
+        assert_eq!(todo.completed_at.unwrap(), todo.updated_at);
     }
 
     test_case.end().await;
 }
 
-/// Check that the user can not complete a completed or archived todo.
-#[tokio::test]
-#[ignore]
-async fn user_can_not_complete_a_completed_or_archived_todo() {
-    let app_settings = load_app_settings_for_testing();
-    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
-    let completed_todo_id = "a61301fa-bb2a-490b-84aa-7dae6c4e086a";
-    let cancelled_todo_id = "b1c2d3e4-5f6a-7890-abcd-ef0123456789";
-    let on_hold_todo_id = "a61301fa-bb2a-490b-84aa-7dae6c4e086a";
-    let archived_todo_id = "94904cc3-fff5-44c5-a290-0a6cd54902cd";
-    let non_completable_todo_ids = [
-        completed_todo_id,
-        cancelled_todo_id,
-        on_hold_todo_id,
-        archived_todo_id,
-    ];
-
-    test_case.login_taro().await;
-    for todo_id in non_completable_todo_ids {
-        let response = test_case.todo_complete(todo_id).await;
-        let ResponseParts {
-            status_code, body, ..
-        } = split_response(response).await;
-        assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
-        assert!(
-            body.contains("Only todos with status 'NotStarted' or 'InProgress'"),
-            "{}",
-            body
-        );
-    }
-
-    test_case.end().await;
-}
-
-/// Check that the user can not complete a todo that belongs to another user.
+/// Check that the completion time of a completed todo is returned in UTC with whole seconds, and
+/// round-trips without any change when it is deserialized and the todo is retrieved again.
 #[tokio::test]
 #[ignore]
-async fn user_can_not_complete_a_todo_that_belongs_to_another_user() {
+async fn completed_at_of_a_completed_todo_is_returned_in_utc() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
-
-    test_case.login_taro().await;
-    let another_user_todo_id = "653acf81-a2e6-43cb-b4b4-9cdb822c740e";
-    let response = test_case.todo_complete(another_user_todo_id).await;
-    let ResponseParts {
-        status_code, body, ..
-    } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::FORBIDDEN, "{}", body);
-    assert!(
-        body.contains("You are not authorized to update this todo"),
-        "{}",
-        body
-    );
-
-    test_case.end().await;
-}
-
-/// Check that anonymous user can not access the endpoint to complete a todo.
-#[tokio::test]
-#[ignore]
-async fn anonymous_user_can_not_access_the_complete_todo_endpoint() {
-    let app_settings = load_app_settings_for_testing();
-    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
     let todo_id = "ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175";
 
-    let client = reqwest::Client::builder()
-        .timeout(REQUEST_TIMEOUT)
-        .cookie_store(true)
-        .build()
-        .unwrap();
-    let uri = format!("{}/todos/{}/complete", test_case.origin(), todo_id);
-    let response = client.post(&uri).send().await.unwrap();
-    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
-
-    test_case.end().await;
-}
-
-/// Check that the user can reopen a todo that was previously completed.
-#[tokio::test]
-#[ignore]
-async fn user_can_reopen_a_completed_todo() {
-    let app_settings = load_app_settings_for_testing();
-    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
-    let completed_todo_id = "a0c1b2d3-4e5f-6789-abcd-ef0123456789";
-
     test_case.login_taro().await;
     let requested_at = OffsetDateTime::now_utc();
-    let request_body = String::from(
-        r#"
-        {
-            "todoStatusCode": 1
-        }
-        "#,
-    );
-    let response = test_case.todo_reopen(completed_todo_id, request_body).await;
+    let response = test_case.todo_complete(todo_id).await;
     let ResponseParts {
         status_code, body, ..
     } = split_response(response).await;
     assert_eq!(status_code, StatusCode::OK, "{}", body);
+
+    // RFC 3339 represents the UTC offset `+00:00` as `Z`
+    let value = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+    let serialized = value["completedAt"].as_str().unwrap().to_string();
+    assert!(serialized.ends_with('Z'), "{}", serialized);
+    let completed_at = OffsetDateTime::parse(&serialized, &Rfc3339).unwrap();
+    assert_eq!(completed_at.offset(), UtcOffset::UTC);
+    assert!((completed_at - requested_at).abs() < REQUEST_TIMEOUT);
+
+    // The deserialized todo has the same completion time
     let todo = serde_json::from_str::<Todo>(&body).unwrap();
-    assert_eq!(todo.status.code, TodoStatusCode::NotStarted);
-    assert_eq!(todo.completed_at, None);
-    assert!((todo.updated_at - requested_at).abs() < REQUEST_TIMEOUT);
+    assert_eq!(todo.completed_at, Some(completed_at));
+    assert_eq!(todo.completed_at.unwrap().offset(), UtcOffset::UTC);
+
+    // The retrieved todo returns the same serialized completion time
+    let response = test_case.todo_get_by_id(todo_id).await;
+    let ResponseParts { body, .. } = split_response(response).await;
+    let value = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+    assert_eq!(value["completedAt"].as_str().unwrap(), serialized);
+
+    // Every timestamp is truncated to whole seconds and rendered with the `Z` offset
+    for key in ["createdAt", "updatedAt", "completedAt"] {
+        let serialized = value[key].as_str().unwrap();
+        assert!(serialized.ends_with('Z'), "{}: {}", key, serialized);
+        assert!(!serialized.contains('.'), "{}: {}", key, serialized);
+    }
 
     test_case.end().await;
 }
 
-/// Check that the user can not reopen a todo that was not previously completed.
+/// Check that the user can not complete a completed or archived todo.
 #[tokio::test]
 #[ignore]
-async fn user_can_not_reopen_a_todo_that_was_not_completed() {
+async fn user_can_not_complete_a_completed_or_archived_todo() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
-    let uncompleted_todo_ids = [
-        "ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175", // Not started
-        "4da95cdb-6898-4739-b2be-62ceaa174baf", // In progress
-        "b1c2d3e4-5f6a-7890-abcd-ef0123456789", // Cancelled
-        "a61301fa-bb2a-490b-84aa-7dae6c4e086a", // On hold
+    let completed_todo_id = "a61301fa-bb2a-490b-84aa-7dae6c4e086a";
+    let cancelled_todo_id = "b1c2d3e4-5f6a-7890-abcd-ef0123456789";
+    let on_hold_todo_id = "a61301fa-bb2a-490b-84aa-7dae6c4e086a";
+    let archived_todo_id = "94904cc3-fff5-44c5-a290-0a6cd54902cd";
+    let non_completable_todo_ids = [
+        completed_todo_id,
+        cancelled_todo_id,
+        on_hold_todo_id,
+        archived_todo_id,
     ];
 
     test_case.login_taro().await;
-    let request_body = String::from(
-        r#"
-        {
-            "todoStatusCode": 1
-        }
-        "#,
-    );
-    for todo_id in uncompleted_todo_ids {
-        let response = test_case.todo_reopen(todo_id, request_body.clone()).await;
+    for todo_id in non_completable_todo_ids {
+        let response = test_case.todo_complete(todo_id).await;
         let ResponseParts {
             status_code, body, ..
         } = split_response(response).await;
         assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
         assert!(
-            body.contains("Only completed todos can be reopened"),
+            body.contains("Only todos with status 'NotStarted' or 'InProgress'"),
             "{}",
             body
         );
@@ -1014,126 +1936,96 @@ async fn user_can_not_reopen_a_todo_that_was_not_completed() {
     test_case.end().await;
 }
 
-/// Check that the user can not reopen an archived todo.
+/// Check that only one of two concurrent requests completes a todo.
 #[tokio::test]
 #[ignore]
-async fn user_can_not_reopen_an_archived_todo() {
+async fn concurrent_completes_complete_the_todo_only_once() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
-    let archived_todo_id = "6459a7ba-5b05-412d-8a39-64a7740f4b7a";
+    let todo_id = "ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175";
 
+    // Through the endpoint
     test_case.login_taro().await;
-    let request_body = String::from(
-        r#"
-        {
-            "todoStatusCode": 1
-        }
-        "#,
-    );
-    let response = test_case.todo_reopen(archived_todo_id, request_body).await;
-    let ResponseParts {
-        status_code, body, ..
-    } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
-    assert!(
-        body.contains("Archived todos cannot be reopened"),
-        "{}",
-        body
+    let (first, second) = tokio::join!(
+        test_case.todo_complete(todo_id),
+        test_case.todo_complete(todo_id)
     );
+    let mut status_codes = [first.status(), second.status()];
+    status_codes.sort();
+    assert_eq!(status_codes, [StatusCode::OK, StatusCode::BAD_REQUEST]);
+
+    // Through the repository, which must enforce the precondition without the check of the use case
+    let todo_id = TodoId::from(Uuid::parse_str("4da95cdb-6898-4739-b2be-62ceaa174baf").unwrap());
+    let todo_repo = PgTodoRepository::new(test_case.pg_pool.clone());
+    let (first, second) = tokio::join!(todo_repo.complete(todo_id), todo_repo.complete(todo_id));
+    let (completed, rejected) = match (first, second) {
+        (Ok(todo), Err(e)) | (Err(e), Ok(todo)) => (todo, e),
+        (first, second) => panic!(
+            "Exactly one complete must succeed: {:?}, {:?}",
+            first.map(|todo| todo.id),
+            second.map(|todo| todo.id)
+        ),
+    };
+    assert_eq!(completed.status.code, TodoStatusCode::Completed);
+    assert_eq!(rejected.kind, DomainErrorKind::Validation);
 
-    test_case.end().await;
-}
-
-/// Check that the user can not reopen a todo with a completed status code.
-#[tokio::test]
-#[ignore]
-async fn user_can_not_reopen_a_todo_with_completed_status_code() {
-    let app_settings = load_app_settings_for_testing();
-    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
-    let completed_todo_id = "a0c1b2d3-4e5f-6789-abcd-ef0123456789";
-
-    test_case.login_taro().await;
-    let request_body = String::from(
-        r#"
-        {
-            "todoStatusCode": 3
-        }
-        "#,
-    );
-    let response = test_case.todo_reopen(completed_todo_id, request_body).await;
-    let ResponseParts {
-        status_code, body, ..
-    } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
-    assert!(
-        body.contains("Cannot reopen a todo with status 'Completed'"),
-        "{}",
-        body
-    );
+    // The repository returns NotFound for a todo that does not exist
+    let e = todo_repo
+        .complete(TodoId::from(Uuid::new_v4()))
+        .await
+        .unwrap_err();
+    assert_eq!(e.kind, DomainErrorKind::NotFound);
 
     test_case.end().await;
 }
 
-/// Check that the user can not reopen a todo that belongs to another user.
+/// Check that the user can not complete a todo that belongs to another user, and can not tell it from a todo
+/// that does not exist if the existence of todos that belong to other users is hidden.
+#[rstest::rstest]
+#[case::hide_existence(true, StatusCode::NOT_FOUND, "Todo not found")]
+#[case::reveal_existence(
+    false,
+    StatusCode::FORBIDDEN,
+    "You are not authorized to update this todo"
+)]
 #[tokio::test]
 #[ignore]
-async fn user_can_not_reopen_a_todo_that_belongs_to_another_user() {
-    let app_settings = load_app_settings_for_testing();
+async fn user_can_not_complete_a_todo_that_belongs_to_another_user(
+    #[case] hide_cross_user_existence: bool,
+    #[case] expected_status_code: StatusCode,
+    #[case] expected_message: &str,
+) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.security.hide_cross_user_existence = hide_cross_user_existence;
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
 
     test_case.login_taro().await;
-    let another_user_todo_id = "7e4c5d0e-3213-4063-abfc-ba833add774b";
-    let request_body = String::from(
-        r#"
-        {
-            "todoStatusCode": 1
-        }
-        "#,
-    );
-    let response = test_case
-        .todo_reopen(another_user_todo_id, request_body)
-        .await;
+    let another_user_todo_id = "653acf81-a2e6-43cb-b4b4-9cdb822c740e";
+    let response = test_case.todo_complete(another_user_todo_id).await;
     let ResponseParts {
         status_code, body, ..
     } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::FORBIDDEN, "{}", body);
-    assert!(
-        body.contains("You are not authorized to update this todo"),
-        "{}",
-        body
-    );
+    assert_eq!(status_code, expected_status_code, "{}", body);
+    assert_eq!(error_messages(&body), vec![expected_message], "{}", body);
 
     test_case.end().await;
 }
 
-/// Check that anonymous user can not access the endpoint to reopen a todo.
+/// Check that anonymous user can not access the endpoint to complete a todo.
 #[tokio::test]
 #[ignore]
-async fn anonymous_user_can_not_access_to_reopen_todo_endpoint() {
+async fn anonymous_user_can_not_access_the_complete_todo_endpoint() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
-    let todo_id = "a0c1b2d3-4e5f-6789-abcd-ef0123456789";
+    let todo_id = "ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175";
 
     let client = reqwest::Client::builder()
         .timeout(REQUEST_TIMEOUT)
         .cookie_store(true)
         .build()
         .unwrap();
-    let request_body = String::from(
-        r#"
-        {
-            "todoStatusCode": 1
-        }
-        "#,
-    );
-    let uri = format!("{}/todos/{}/reopen", test_case.origin(), todo_id);
-    let response = client
-        .post(&uri)
-        .header(reqwest::header::CONTENT_TYPE, "application/json")
-        .body(request_body)
-        .send()
-        .await
-        .unwrap();
+    let uri = test_case.url(&paths::todo_complete(todo_id));
+    let response = client.post(&uri).send().await.unwrap();
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 
     test_case.end().await;
@@ -1149,19 +2041,12 @@ async fn user_can_archive_a_todo() {
 
     test_case.login_taro().await;
     let requested_at = OffsetDateTime::now_utc();
-    let request_body = String::from(
-        r#"
-        {
-            "archived": true
-        }
-        "#,
-    );
-    let response = test_case.todo_archive(todo_id, request_body).await;
-    let ResponseParts {
-        status_code, body, ..
-    } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::OK, "{}", body);
-    let todo = serde_json::from_str::<Todo>(&body).unwrap();
+    let request_body = TodoArchiveRequestBody { archived: true };
+    let todo = test_case
+        .api
+        .todo_archive(todo_id, &request_body)
+        .await
+        .unwrap();
     assert!(todo.archived);
     assert!((todo.updated_at - requested_at).abs() < REQUEST_TIMEOUT);
 
@@ -1240,41 +2125,158 @@ async fn user_can_delete_owned_todo() {
 
     test_case.login_taro().await;
     let todo_id = "4da95cdb-6898-4739-b2be-62ceaa174baf";
-    let response = test_case.todo_delete(todo_id).await;
-    let ResponseParts {
-        status_code, body, ..
-    } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::NO_CONTENT, "{}", body);
+    test_case.api.todo_delete(todo_id).await.unwrap();
 
     // Check that the todo is actually deleted
-    let response = test_case.todo_get_by_id(todo_id).await;
+    let Err(err) = test_case.api.todo_get(todo_id).await else {
+        panic!("the deleted todo was returned");
+    };
+    let api_error = err.api_error().unwrap();
+    assert_eq!(api_error.status_code, StatusCode::NOT_FOUND);
+
+    test_case.end().await;
+}
+
+/// Check that the user can not delete a todo that belongs to another user, and can not tell it from a todo
+/// that does not exist if the existence of todos that belong to other users is hidden.
+#[rstest::rstest]
+#[case::hide_existence(true, StatusCode::NOT_FOUND, "Todo not found")]
+#[case::reveal_existence(
+    false,
+    StatusCode::FORBIDDEN,
+    "You are not authorized to update this todo"
+)]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_delete_todo_that_belongs_to_another_user(
+    #[case] hide_cross_user_existence: bool,
+    #[case] expected_status_code: StatusCode,
+    #[case] expected_message: &str,
+) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.security.hide_cross_user_existence = hide_cross_user_existence;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let another_user_todo_id = "653acf81-a2e6-43cb-b4b4-9cdb822c740e";
+    let response = test_case.todo_delete(another_user_todo_id).await;
     let ResponseParts {
         status_code, body, ..
     } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::NOT_FOUND, "{}", body);
+    assert_eq!(status_code, expected_status_code, "{}", body);
+    assert_eq!(error_messages(&body), vec![expected_message], "{}", body);
 
     test_case.end().await;
 }
 
-/// Check That the user can not delete a todo that belongs to another user.
+/// Check that an out-of-range todo status code stored in the database surfaces as a repository error.
 #[tokio::test]
 #[ignore]
-async fn user_can_not_delete_todo_that_belongs_to_another_user() {
+async fn out_of_range_todo_status_code_in_database_is_a_repository_error() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
 
+    let todo_id = "ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175";
+    sqlx::raw_sql(&format!(
+        r#"
+        INSERT INTO todo_statuses (code, name, display_order, created_at, updated_at)
+        VALUES (99, '不明', 99, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP);
+        UPDATE todos SET todo_status_code = 99 WHERE id = '{}';
+        "#,
+        todo_id
+    ))
+    .execute(&test_case.pg_pool)
+    .await
+    .unwrap();
+
+    let todo_repo = PgTodoRepository::new(test_case.pg_pool.clone());
+    let error = todo_repo
+        .by_id(TodoId::from(Uuid::parse_str(todo_id).unwrap()))
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind, DomainErrorKind::Repository);
+
+    // The API must not leak the SQL error details to the client
     test_case.login_taro().await;
-    let another_user_todo_id = "653acf81-a2e6-43cb-b4b4-9cdb822c740e";
-    let response = test_case.todo_delete(another_user_todo_id).await;
+    let response = test_case.todo_get_by_id(todo_id).await;
     let ResponseParts {
         status_code, body, ..
     } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::FORBIDDEN, "{}", body);
-    assert!(
-        body.contains("You are not authorized to update this todo"),
-        "{}",
-        body
-    );
+    assert_eq!(status_code, StatusCode::INTERNAL_SERVER_ERROR, "{}", body);
+    assert_eq!(error_messages(&body), vec!["Internal server error"]);
+
+    test_case.end().await;
+}
+
+/// Check that the todo list query, which is built at runtime and is not checked at compile time,
+/// is valid for every combination of the conditions.
+#[tokio::test]
+#[ignore]
+async fn todo_list_query_is_valid_for_every_combination_of_conditions() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    let todo_repo = PgTodoRepository::new(test_case.pg_pool.clone());
+    let operators = [
+        None,
+        Some(NumericOperator::Eq),
+        Some(NumericOperator::Ne),
+        Some(NumericOperator::Gt),
+        Some(NumericOperator::Gte),
+        Some(NumericOperator::Lt),
+        Some(NumericOperator::Lte),
+        Some(NumericOperator::Between),
+        Some(NumericOperator::NotBetween),
+        Some(NumericOperator::IsNull),
+        Some(NumericOperator::IsNotNull),
+    ];
+    let statuses = [
+        None,
+        Some(vec![TodoStatusCode::NotStarted, TodoStatusCode::InProgress]),
+    ];
+    let archived = [
+        ArchivedFilter::Active,
+        ArchivedFilter::Archived,
+        ArchivedFilter::All,
+    ];
+    let views = [
+        TodoListView::Active,
+        TodoListView::Completed,
+        TodoListView::All,
+    ];
+    let sorts = [
+        TodoListSort::DueDate,
+        TodoListSort::CreatedAt,
+        TodoListSort::UpdatedAt,
+    ];
+    for keyword in [None, Some(String::from("100%_'done"))] {
+        for op in operators {
+            for statuses in statuses.iter() {
+                for archived in archived {
+                    for view in views {
+                        for sort in sorts {
+                            let mut input = TodoListInput::new(
+                                UserId::from(*TARO_USER_ID),
+                                keyword.clone(),
+                                op,
+                                Some(date!(2025 - 06 - 01)),
+                                Some(date!(2025 - 06 - 30)),
+                                statuses.clone(),
+                                Some(archived),
+                                None,
+                                None,
+                            )
+                            .unwrap();
+                            input.view = view;
+                            input.sort = sort;
+                            let result = todo_repo.list(input.clone()).await;
+                            assert!(result.is_ok(), "{:?}: {:?}", input, result.err());
+                        }
+                    }
+                }
+            }
+        }
+    }
 
     test_case.end().await;
 }
@@ -0,0 +1,191 @@
+use reqwest::StatusCode;
+use time::OffsetDateTime;
+
+use app::routes::paths;
+use domain::models::{Todo, TodoStatusCode};
+
+use crate::{
+    helpers::{ResponseParts, error_messages, load_app_settings_for_testing, split_response},
+    test_case::{EnableTracing, InsertTestData, REQUEST_TIMEOUT, TestCase},
+};
+
+/// The completed todo of Taro that is not archived
+const COMPLETED_TODO_ID: &str = "a0c1b2d3-4e5f-6789-abcd-ef0123456789";
+
+/// The completed todo of Taro that is archived
+const ARCHIVED_COMPLETED_TODO_ID: &str = "6459a7ba-5b05-412d-8a39-64a7740f4b7a";
+
+/// The completed todo of Hanako that is not archived
+const ANOTHER_USER_COMPLETED_TODO_ID: &str = "7e4c5d0e-3213-4063-abfc-ba833add774b";
+
+/// Check that the user can reopen a completed todo with every status except completed.
+#[rstest::rstest]
+#[case::not_started(TodoStatusCode::NotStarted)]
+#[case::in_progress(TodoStatusCode::InProgress)]
+#[case::cancelled(TodoStatusCode::Cancelled)]
+#[case::on_hold(TodoStatusCode::OnHold)]
+#[tokio::test]
+#[ignore]
+async fn user_can_reopen_a_completed_todo(#[case] status_code: TodoStatusCode) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let requested_at = OffsetDateTime::now_utc();
+    let response = test_case
+        .todo_reopen_with_status(COMPLETED_TODO_ID, status_code as i16)
+        .await;
+    let ResponseParts {
+        status_code: response_status_code,
+        body,
+        ..
+    } = split_response(response).await;
+    assert_eq!(response_status_code, StatusCode::OK, "{}", body);
+    let todo = serde_json::from_str::<Todo>(&body).unwrap();
+    assert_eq!(todo.id.to_string(), COMPLETED_TODO_ID);
+    assert_eq!(todo.status.code, status_code);
+    assert_eq!(todo.completed_at, None);
+    assert!(!todo.archived);
+    assert!((todo.updated_at - requested_at).abs() < REQUEST_TIMEOUT);
+
+    test_case.end().await;
+}
+
+/// Check that the user can not reopen a completed todo with the completed status.
+#[tokio::test]
+#[ignore]
+async fn user_can_not_reopen_a_todo_with_completed_status_code() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case
+        .todo_reopen_with_status(COMPLETED_TODO_ID, TodoStatusCode::Completed as i16)
+        .await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
+    assert!(
+        body.contains("Cannot reopen a todo with status 'Completed'"),
+        "{}",
+        body
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the user can not reopen a todo that was not completed.
+#[rstest::rstest]
+#[case::not_started("ee0f5a08-87c3-48d9-81b0-3f3e7bd8c175")]
+#[case::in_progress("4da95cdb-6898-4739-b2be-62ceaa174baf")]
+#[case::cancelled("b1c2d3e4-5f6a-7890-abcd-ef0123456789")]
+#[case::on_hold("a61301fa-bb2a-490b-84aa-7dae6c4e086a")]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_reopen_a_todo_that_was_not_completed(#[case] todo_id: &str) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case
+        .todo_reopen_with_status(todo_id, TodoStatusCode::NotStarted as i16)
+        .await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
+    assert!(
+        body.contains("Only completed todos can be reopened"),
+        "{}",
+        body
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the user can not reopen a completed todo that is archived.
+#[tokio::test]
+#[ignore]
+async fn user_can_not_reopen_an_archived_todo() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case
+        .todo_reopen_with_status(
+            ARCHIVED_COMPLETED_TODO_ID,
+            TodoStatusCode::NotStarted as i16,
+        )
+        .await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
+    assert!(
+        body.contains("Archived todos cannot be reopened"),
+        "{}",
+        body
+    );
+
+    test_case.end().await;
+}
+
+/// Check that the user can not reopen a completed todo that belongs to another user, and that the todo
+/// stays completed.
+#[tokio::test]
+#[ignore]
+async fn user_can_not_reopen_a_todo_that_belongs_to_another_user() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let response = test_case
+        .todo_reopen_with_status(
+            ANOTHER_USER_COMPLETED_TODO_ID,
+            TodoStatusCode::NotStarted as i16,
+        )
+        .await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::FORBIDDEN, "{}", body);
+    assert_eq!(
+        error_messages(&body),
+        vec!["You are not authorized to update this todo"]
+    );
+
+    test_case.login_hanako().await;
+    let response = test_case
+        .todo_get_by_id(ANOTHER_USER_COMPLETED_TODO_ID)
+        .await;
+    let todo = response.json::<Todo>().await.unwrap();
+    assert_eq!(todo.status.code, TodoStatusCode::Completed);
+    assert!(todo.completed_at.is_some());
+
+    test_case.end().await;
+}
+
+/// Check that an anonymous user can not reopen a todo.
+#[tokio::test]
+#[ignore]
+async fn anonymous_user_can_not_reopen_a_todo() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap();
+    let uri = test_case.url(&paths::todo_reopen(COMPLETED_TODO_ID));
+    let response = client
+        .post(&uri)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(r#"{"todoStatusCode": 1}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    test_case.end().await;
+}
@@ -4,21 +4,34 @@ use cookie::{Cookie, SameSite};
 use reqwest::{StatusCode, Url};
 use secrecy::{ExposeSecret as _, SecretString};
 use sqlx::types::time::OffsetDateTime;
-use time::Duration;
+use time::{Duration, format_description::well_known::Rfc3339};
 
+use api_client::{
+    AccountBackupBody, CharacterClass, ConfirmEmailRequestBody, ImportResponseBody,
+    LoginRequestBody, PasswordPolicy, Patch, TodoApiClient, UpdateUserRequestBody, UserProfile,
+};
+use app::routes::paths;
 use domain::{
-    models::{RoleCode, User},
-    repositories::{TokenType, generate_auth_token_info_key},
+    EMAIL_ALREADY_IN_USE_MESSAGE,
+    models::{LockReason, RoleCode, User, UserId},
+    repositories::{TokenType, UserRepository as _, generate_auth_token_info_key},
 };
 use infra::{
-    http::{COOKIE_ACCESS_TOKEN_KEY, COOKIE_REFRESH_TOKEN_KEY},
+    http::{
+        COOKIE_ACCESS_TOKEN_KEY, COOKIE_REFRESH_TOKEN_KEY,
+        handler::user::EMAIL_CHANGE_NOT_AVAILABLE_MESSAGE,
+    },
     jwt::{Claim, generate_token},
-    settings::HttpProtocol,
+    postgres::repositories::PgUserRepository,
+    settings::{HttpProtocol, TokenSettings},
 };
 
 use crate::{
-    helpers::{ResponseParts, load_app_settings_for_testing, split_response},
-    test_case::{EnableTracing, InsertTestData, REQUEST_TIMEOUT, RawLoginResponseBody, TestCase},
+    helpers::{ResponseParts, error_messages, load_app_settings_for_testing, split_response},
+    test_case::{
+        EnableTracing, HANAKO_USER_ID, InsertTestData, REQUEST_TIMEOUT, RawLoginResponseBody,
+        TARO_USER_ID, TestCase,
+    },
 };
 
 /// Check that a user can register, log in, retrieve their information, and log out successfully.
@@ -86,6 +99,7 @@ async fn user_use_case_test() {
     assert_eq!(access_cookie.value(), access_token.expose_secret());
     inspect_token_cookie_spec(
         access_cookie,
+        &test_case.app_state.app_settings.http.host,
         SameSite::Strict,
         test_case.app_state.app_settings.http.protocol == HttpProtocol::Https,
         true,
@@ -95,6 +109,7 @@ async fn user_use_case_test() {
     assert_eq!(refresh_cookie.value(), refresh_token.expose_secret());
     inspect_token_cookie_spec(
         refresh_cookie,
+        &test_case.app_state.app_settings.http.host,
         SameSite::Strict,
         test_case.app_state.app_settings.http.protocol == HttpProtocol::Https,
         true,
@@ -139,6 +154,8 @@ async fn user_use_case_test() {
     let user: User = serde_json::from_str(&body).unwrap();
     assert_eq!(user.family_name.0, "Doe");
     assert_eq!(user.given_name.0, "John");
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["fullName"], "Doe John");
     assert_eq!(user.email.0, "john@example.com");
     assert!(user.active);
     assert!((user.last_login_at.unwrap() - login_requested_at).abs() < REQUEST_TIMEOUT);
@@ -166,6 +183,7 @@ async fn user_use_case_test() {
     assert_eq!(access_cookie.value(), "");
     inspect_token_cookie_spec(
         access_cookie,
+        &test_case.app_state.app_settings.http.host,
         SameSite::Strict,
         test_case.app_state.app_settings.http.protocol == HttpProtocol::Https,
         true,
@@ -175,6 +193,7 @@ async fn user_use_case_test() {
     assert_eq!(refresh_cookie.value(), "");
     inspect_token_cookie_spec(
         refresh_cookie,
+        &test_case.app_state.app_settings.http.host,
         SameSite::Strict,
         test_case.app_state.app_settings.http.protocol == HttpProtocol::Https,
         true,
@@ -207,6 +226,234 @@ async fn user_use_case_test() {
     test_case.end().await;
 }
 
+/// Check that the tokens are stored in Redis with the key prefix and the user can log out, while the
+/// token keys stored in PostgreSQL have no prefix.
+#[tokio::test]
+#[ignore]
+async fn tokens_are_stored_in_redis_with_the_key_prefix() {
+    let mut app_settings = load_app_settings_for_testing();
+    let prefix = format!("test-{}", uuid::Uuid::new_v4());
+    app_settings.redis.key_prefix = Some(prefix.clone());
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    let body = LoginRequestBody {
+        email: String::from("taro@example.com"),
+        password: SecretString::new("ab12AB#$".into()),
+    };
+    let tokens = test_case.api.login(&body).await.unwrap();
+    let keys = [
+        generate_auth_token_info_key(&tokens.access_token),
+        generate_auth_token_info_key(&tokens.refresh_token),
+    ];
+    for key in &keys {
+        let prefixed_key = format!("{}:{}", prefix, key.expose_secret());
+        assert!(redis_key_exists(&test_case, &prefixed_key).await);
+        assert!(!redis_key_exists(&test_case, key.expose_secret()).await);
+    }
+    // The token keys in PostgreSQL have no prefix
+    let user_tokens = test_case
+        .user_tokens_from_user_repo(UserId::from(*TARO_USER_ID))
+        .await;
+    for key in &keys {
+        assert!(
+            user_tokens
+                .iter()
+                .any(|ut| ut.token_key.expose_secret() == key.expose_secret())
+        );
+    }
+    // The authenticated request finds the token content with the prefixed key
+    test_case.api.me().await.unwrap();
+
+    // Logging out deletes the prefixed keys
+    test_case.api.logout().await.unwrap();
+    for key in &keys {
+        let prefixed_key = format!("{}:{}", prefix, key.expose_secret());
+        assert!(!redis_key_exists(&test_case, &prefixed_key).await);
+    }
+
+    test_case.end().await;
+}
+
+/// Check that logging out of all sessions revokes the sessions of every client the user logged in from.
+#[tokio::test]
+#[ignore]
+async fn user_can_logout_from_all_sessions() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    let user_id = UserId::from(*TARO_USER_ID);
+    let credentials = LoginRequestBody {
+        email: String::from("taro@example.com"),
+        password: SecretString::new("ab12AB#$".into()),
+    };
+
+    // Log in from two different clients that do not share the cookie store
+    let first_session = test_case.api.login(&credentials).await.unwrap();
+    let other_client = TodoApiClient::new(
+        test_case.origin(),
+        reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .cookie_store(true)
+            .build()
+            .unwrap(),
+    );
+    let second_session = other_client.login(&credentials).await.unwrap();
+    assert_eq!(test_case.user_tokens_from_user_repo(user_id).await.len(), 4);
+
+    // Log out from all sessions using the first client
+    test_case.api.logout_all().await.unwrap();
+
+    // Check that the tokens of both sessions are deleted from postgres and redis
+    assert!(
+        test_case
+            .user_tokens_from_user_repo(user_id)
+            .await
+            .is_empty(),
+        "User tokens should be deleted after logging out from all sessions"
+    );
+    for token in [
+        &first_session.access_token,
+        &first_session.refresh_token,
+        &second_session.access_token,
+        &second_session.refresh_token,
+    ] {
+        assert!(
+            test_case
+                .token_content_from_token_repo(token)
+                .await
+                .is_none()
+        );
+    }
+
+    // Check that neither client can access the protected resources anymore
+    let error = test_case.api.me().await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::UNAUTHORIZED
+    );
+    let error = other_client.me().await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::UNAUTHORIZED
+    );
+
+    test_case.end().await;
+}
+
+/// Check that logging in beyond the sessions cap revokes the oldest session of the user.
+#[tokio::test]
+#[ignore]
+async fn login_beyond_the_sessions_cap_revokes_the_oldest_session() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.token.max_sessions_per_user = 2;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    let user_id = UserId::from(*TARO_USER_ID);
+    let credentials = LoginRequestBody {
+        email: String::from("taro@example.com"),
+        password: SecretString::new("ab12AB#$".into()),
+    };
+
+    // Log in three times from different clients that do not share the cookie store
+    let clients = (0..3)
+        .map(|_| {
+            TodoApiClient::new(
+                test_case.origin(),
+                reqwest::Client::builder()
+                    .timeout(REQUEST_TIMEOUT)
+                    .cookie_store(true)
+                    .build()
+                    .unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut sessions = vec![];
+    for client in clients.iter() {
+        sessions.push(client.login(&credentials).await.unwrap());
+    }
+
+    // Check that only the tokens of the two newest sessions remain
+    assert_eq!(test_case.user_tokens_from_user_repo(user_id).await.len(), 4);
+    for token in [&sessions[0].access_token, &sessions[0].refresh_token] {
+        assert!(
+            test_case
+                .token_content_from_token_repo(token)
+                .await
+                .is_none()
+        );
+    }
+
+    // Check that the first session no longer works, but the others still do
+    let error = clients[0].me().await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::UNAUTHORIZED
+    );
+    clients[1].me().await.unwrap();
+    clients[2].me().await.unwrap();
+
+    test_case.end().await;
+}
+
+/// Check that authenticated responses tell the remaining lifetime of the access token,
+/// and suggest refreshing the tokens when the access token is about to expire.
+#[tokio::test]
+#[ignore]
+async fn authenticated_responses_tell_the_remaining_lifetime_of_the_access_token() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.token.access_max_age = 10;
+    app_settings.token.refresh_suggested_threshold = 60;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    test_case.login_taro().await;
+    let uri = test_case.url(paths::USERS_ME);
+
+    let expires_in = |response: &reqwest::Response| -> u64 {
+        response.headers()["x-token-expires-in"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap()
+    };
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let first = expires_in(&response);
+    assert!(0 < first && first <= 10, "unexpected expires in: {first}");
+    assert_eq!(response.headers()["x-token-refresh-suggested"], "true");
+
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let second = expires_in(&response);
+    assert!(second < first, "{second} should be less than {first}");
+
+    test_case.end().await;
+}
+
+/// Check that the refresh of the tokens is not suggested while the access token has enough lifetime.
+#[tokio::test]
+#[ignore]
+async fn authenticated_responses_do_not_suggest_refresh_while_the_access_token_is_fresh() {
+    let app_settings = load_app_settings_for_testing();
+    let access_max_age = app_settings.token.access_max_age as u64;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    test_case.login_taro().await;
+
+    let response = test_case
+        .http_client
+        .get(test_case.url(paths::USERS_ME))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let expires_in = response.headers()["x-token-expires-in"]
+        .to_str()
+        .unwrap()
+        .parse::<u64>()
+        .unwrap();
+    assert!(expires_in <= access_max_age);
+    assert!(!response.headers().contains_key("x-token-refresh-suggested"));
+
+    test_case.end().await;
+}
+
 /// Check that entering an incorrect email address or password when logging in returns an error.
 /// And ensure that the login failed history is recorded correctly if the email address is correct but the password is incorrect.
 #[tokio::test]
@@ -243,18 +490,156 @@ async fn user_can_not_login_with_invalid_credentials() {
     test_case.end().await;
 }
 
-/// Check that the user can not login when the user is locked.
+/// Check that parallel logins all succeed within a sane wall-clock bound.
+///
+/// The password verification runs off the async runtime, so the logins do not block each other on
+/// the worker thread of the test server.
 #[tokio::test]
 #[ignore]
-async fn user_can_not_login_when_user_is_locked() {
+async fn parallel_logins_succeed_within_time_bound() {
+    const NUMBER_OF_LOGINS: usize = 20;
     let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    let started_at = std::time::Instant::now();
+    let mut logins = tokio::task::JoinSet::new();
+    for _ in 0..NUMBER_OF_LOGINS {
+        let api = TodoApiClient::new(test_case.origin(), reqwest::Client::new());
+        logins.spawn(async move {
+            let body = LoginRequestBody {
+                email: String::from("taro@example.com"),
+                password: SecretString::new("ab12AB#$".into()),
+            };
+            api.login(&body).await
+        });
+    }
+    let results = logins.join_all().await;
+    let elapsed = started_at.elapsed();
+
+    assert_eq!(results.len(), NUMBER_OF_LOGINS);
+    for result in results {
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+    assert!(elapsed < REQUEST_TIMEOUT, "{:?}", elapsed);
+
+    test_case.end().await;
+}
+
+/// Check that the user can not login when the user is locked, and that the lock status is revealed only
+/// when the setting allows it.
+#[rstest::rstest]
+#[case::reveal_lock_status(true, StatusCode::LOCKED, "User is locked")]
+#[case::hide_lock_status(
+    false,
+    StatusCode::BAD_REQUEST,
+    "Login failed. Please check your email and password"
+)]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_login_when_user_is_locked(
+    #[case] reveal_lock_status: bool,
+    #[case] expected_status_code: StatusCode,
+    #[case] expected_message: &str,
+) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.login.reveal_lock_status = reveal_lock_status;
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
 
     let (user, ..) = create_user_and_login(&test_case).await;
     test_case.set_user_active_status(user.id, false).await;
 
+    let response = test_case.login(john_credentials()).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, expected_status_code, "{}", body);
+    assert_eq!(error_messages(&body), vec![expected_message]);
+
+    test_case.end().await;
+}
+
+/// Check that the lock caused by too many failed login attempts is released automatically when the user
+/// logs in after the lockout duration has passed.
+#[tokio::test]
+#[ignore]
+async fn user_can_login_after_the_lockout_duration_has_passed() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.login.lockout_duration_secs = Some(1);
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let (user, ..) = create_user_and_login(&test_case).await;
+    for _ in 0..=test_case.app_state.app_settings.login.max_attempts {
+        let response = test_case.login(john_incorrect_credential()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+    let user = test_case.user_by_id(user.id).await.unwrap();
+    assert!(!user.active);
+    assert_eq!(user.lock_reason, Some(LockReason::TooManyFailedAttempts));
+    // The user is still locked within the lockout duration
+    let response = test_case.login(john_credentials()).await;
+    assert_eq!(response.status(), StatusCode::LOCKED);
+
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let response = test_case.login(john_credentials()).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK, "{}", body);
+    let user = test_case.user_by_id(user.id).await.unwrap();
+    assert!(user.active);
+    assert_eq!(user.locked_at, None);
+    assert_eq!(user.lock_reason, None);
+    assert!(test_case.get_login_failed_history(user.id).await.is_none());
+
+    test_case.end().await;
+}
+
+/// Check that the lock recorded before the lock reason was introduced, which has no lock reason and no lock
+/// date, is released automatically when the user logs in after the lockout duration has passed.
+#[tokio::test]
+#[ignore]
+async fn user_locked_without_lock_reason_can_login_after_the_lockout_duration_has_passed() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.login.lockout_duration_secs = Some(1);
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let (user, ..) = create_user_and_login(&test_case).await;
+    // Lock the user in the same way as before the lock reason was introduced
+    test_case.set_user_active_status(user.id, false).await;
+    let user = test_case.user_by_id(user.id).await.unwrap();
+    assert!(!user.active);
+    assert_eq!(user.locked_at, None);
+    assert_eq!(user.lock_reason, None);
+
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let response = test_case.login(john_credentials()).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK, "{}", body);
+    let user = test_case.user_by_id(user.id).await.unwrap();
+    assert!(user.active);
+
+    test_case.end().await;
+}
+
+/// Check that the lock by an administrator is not released automatically even if the lockout duration
+/// has passed.
+#[tokio::test]
+#[ignore]
+async fn user_locked_by_an_administrator_is_not_released_after_the_lockout_duration() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.login.lockout_duration_secs = Some(0);
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let (user, ..) = create_user_and_login(&test_case).await;
+    test_case.lock_user(user.id, LockReason::AdminAction).await;
+
     let response = test_case.login(john_credentials()).await;
     assert_eq!(response.status(), StatusCode::LOCKED);
+    let user = test_case.user_by_id(user.id).await.unwrap();
+    assert!(!user.active);
+    assert_eq!(user.lock_reason, Some(LockReason::AdminAction));
 
     test_case.end().await;
 }
@@ -288,10 +673,22 @@ async fn user_is_not_locked_after_user_attempts_to_login_in_max_attempt_times()
 }
 
 /// Check that the user was locked after exceeding the maximum number of login attempts within the allowed time
+#[rstest::rstest]
+#[case::reveal_lock_status(true, StatusCode::LOCKED, "User is locked")]
+#[case::hide_lock_status(
+    false,
+    StatusCode::BAD_REQUEST,
+    "Login failed. Please check your email and password"
+)]
 #[tokio::test]
 #[ignore]
-async fn user_is_locked_after_use_attempts_to_login_exceeding_max_attempts() {
-    let app_settings = load_app_settings_for_testing();
+async fn user_is_locked_after_use_attempts_to_login_exceeding_max_attempts(
+    #[case] reveal_lock_status: bool,
+    #[case] expected_status_code: StatusCode,
+    #[case] expected_message: &str,
+) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.login.reveal_lock_status = reveal_lock_status;
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
 
     let (user, ..) = create_user_and_login(&test_case).await;
@@ -302,15 +699,36 @@ async fn user_is_locked_after_use_attempts_to_login_exceeding_max_attempts() {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
         assert_eq!(history.number_of_attempts, times + 1);
     }
-    // Check that the user is locked
+    // Check that the user is locked, and when and why the user was locked are recorded
     let user = test_case.user_by_id(user.id).await.unwrap();
     assert!(
         !user.active,
         "User should be locked after exceeding max login attempts"
     );
+    assert_eq!(user.lock_reason, Some(LockReason::TooManyFailedAttempts));
+    let locked_at = user.locked_at.unwrap();
+    assert!((OffsetDateTime::now_utc() - locked_at).abs() < REQUEST_TIMEOUT);
     // The user log in failed , if attempt to log in with the correct password
+    // The response does not reveal when and why the user was locked, nor whether the user is locked
+    // unless the setting allows it
     let response = test_case.login(john_credentials()).await;
-    assert_eq!(response.status(), StatusCode::LOCKED);
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, expected_status_code);
+    assert_eq!(error_messages(&body), vec![expected_message]);
+    // The owner of the account, who has the access token, gets when and why the user was locked regardless
+    // of the setting
+    let response = test_case.me().await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::LOCKED);
+    let expected = format!(
+        "Your account was locked on {} due to too many failed login attempts",
+        locked_at.format(&Rfc3339).unwrap()
+    );
+    assert_eq!(error_messages(&body), vec![expected]);
 
     test_case.end().await;
 }
@@ -374,11 +792,16 @@ async fn user_login_failed_history_is_reset_after_max_attempt_time() {
     test_case.end().await;
 }
 
-/// Check that the user who is locked can not get their information.
+/// Check that the user who is locked can not get their information, regardless of whether the lock status
+/// is revealed on login.
+#[rstest::rstest]
+#[case::reveal_lock_status(true)]
+#[case::hide_lock_status(false)]
 #[tokio::test]
 #[ignore]
-async fn user_can_not_get_user_information_when_user_is_locked() {
-    let app_settings = load_app_settings_for_testing();
+async fn user_can_not_get_user_information_when_user_is_locked(#[case] reveal_lock_status: bool) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.login.reveal_lock_status = reveal_lock_status;
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
 
     let (user, ..) = create_user_and_login(&test_case).await;
@@ -421,73 +844,334 @@ async fn user_can_update_user_information_with_credentials() {
 
     // Update user information
     let requested_at = OffsetDateTime::now_utc();
-    let request_body = String::from(
-        r#"
-        {
-            "familyName": "Smith",
-            "givenName": "Jane",
-            "email": "jane@example.com"
-
-        }
-        "#,
-    );
-    let response = test_case.update_user(request_body).await;
-    let ResponseParts {
-        status_code, body, ..
-    } = split_response(response).await;
-    assert_eq!(status_code, StatusCode::OK);
-    let updated_user: User = serde_json::from_str(&body).unwrap();
+    let request_body = UpdateUserRequestBody {
+        family_name: Patch::Value(String::from("Smith")),
+        given_name: Patch::Value(String::from("Jane")),
+        email: Patch::Value(String::from("jane@example.com")),
+        locale: Patch::Absent,
+    };
+    let updated_user = test_case.api.update_user(&request_body).await.unwrap();
     assert_eq!(updated_user.id, user.id);
     assert_eq!(updated_user.family_name, "Smith");
     assert_eq!(updated_user.given_name, "Jane",);
-    assert_eq!(updated_user.email, "jane@example.com");
+    // The email address is not changed until it is confirmed
+    assert_eq!(updated_user.email, "john@example.com");
+    assert_eq!(pending_email(&updated_user), Some("jane@example.com"));
     assert!((updated_user.updated_at - requested_at).abs() < REQUEST_TIMEOUT);
 
     // Update family name only
-    let family_name_only = String::from(
-        r#"
-        {
-            "familyName": "Schmo"
-        }
-        "#,
-    );
-    let response = test_case.update_user(family_name_only).await;
-    let updated_user: User = response.json().await.unwrap();
+    let family_name_only = UpdateUserRequestBody {
+        family_name: Patch::Value(String::from("Schmo")),
+        ..Default::default()
+    };
+    let updated_user = test_case.api.update_user(&family_name_only).await.unwrap();
     assert_eq!(updated_user.family_name, "Schmo");
     assert_eq!(updated_user.given_name, "Jane");
-    assert_eq!(updated_user.email, "jane@example.com");
+    assert_eq!(updated_user.email, "john@example.com");
+    assert_eq!(pending_email(&updated_user), Some("jane@example.com"));
 
     // Update given name only
-    let given_name_only = String::from(
-        r#"
-        {
-            "givenName": "Alice"
-        }
-        "#,
-    );
-    let response = test_case.update_user(given_name_only).await;
-    let updated_user: User = response.json().await.unwrap();
+    let given_name_only = UpdateUserRequestBody {
+        given_name: Patch::Value(String::from("Alice")),
+        ..Default::default()
+    };
+    let updated_user = test_case.api.update_user(&given_name_only).await.unwrap();
     assert_eq!(updated_user.family_name, "Schmo");
     assert_eq!(updated_user.given_name, "Alice");
-    assert_eq!(updated_user.email, "jane@example.com");
+    assert_eq!(updated_user.email, "john@example.com");
 
-    // Update email only
-    let email_only = String::from(
-        r#"
-        {
-            "email": "alice@example.com"
-        }
-        "#,
-    );
-    let response = test_case.update_user(email_only).await;
-    let updated_user: User = response.json().await.unwrap();
+    // Request to change email only, which replaces the pending email address
+    let email_only = UpdateUserRequestBody {
+        email: Patch::Value(String::from("alice@example.com")),
+        ..Default::default()
+    };
+    let updated_user = test_case.api.update_user(&email_only).await.unwrap();
     assert_eq!(updated_user.family_name, "Schmo");
     assert_eq!(updated_user.given_name, "Alice");
-    assert_eq!(updated_user.email, "alice@example.com");
+    assert_eq!(updated_user.email, "john@example.com");
+    assert_eq!(pending_email(&updated_user), Some("alice@example.com"));
+
+    // Confirm the email address
+    let body = ConfirmEmailRequestBody {
+        token: test_case.mailer.last_token(),
+    };
+    let confirmed_user = test_case.api.confirm_email(&body).await.unwrap();
+    assert_eq!(confirmed_user.email, "alice@example.com");
+    assert_eq!(pending_email(&confirmed_user), None);
+
+    test_case.end().await;
+}
+
+/// Check that null clears a field only when the body is sent as JSON Merge Patch.
+///
+/// The given name can not be cleared, so null under JSON Merge Patch is rejected, while null in plain
+/// JSON is ignored as before.
+#[tokio::test]
+#[ignore]
+async fn user_update_treats_null_by_content_type() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+    create_user_and_login(&test_case).await;
+
+    let request_body = UpdateUserRequestBody {
+        family_name: Patch::Value(String::from("Smith")),
+        given_name: Patch::Null,
+        ..Default::default()
+    };
+
+    // Null in plain JSON is ignored
+    let updated_user = test_case.api.update_user(&request_body).await.unwrap();
+    assert_eq!(updated_user.family_name, "Smith");
+    assert_eq!(updated_user.given_name, "John");
+
+    // Null under JSON Merge Patch clears the field, which the given name does not allow
+    let error = test_case
+        .api
+        .merge_patch_user(&request_body)
+        .await
+        .unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::BAD_REQUEST);
+    assert_eq!(error.messages, vec!["givenName: must not be null"]);
+    let user = test_case.api.me().await.unwrap();
+    assert_eq!(user.given_name, "John");
+
+    // JSON Merge Patch without null updates the fields
+    let request_body = UpdateUserRequestBody {
+        given_name: Patch::Value(String::from("Jane")),
+        ..Default::default()
+    };
+    let updated_user = test_case.api.merge_patch_user(&request_body).await.unwrap();
+    assert_eq!(updated_user.family_name, "Smith");
+    assert_eq!(updated_user.given_name, "Jane");
+
+    // Other content types are not supported
+    let response = test_case
+        .http_client
+        .patch(test_case.url(paths::USERS_ME))
+        .header(reqwest::header::CONTENT_TYPE, "text/plain")
+        .body(r#"{"givenName": "Alice"}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    test_case.end().await;
+}
+
+/// Check that the display name orders the family and given names by the locale of the user.
+#[rstest::rstest]
+#[case::ja(Some("ja"), "Doe John")]
+#[case::ja_jp(Some("ja-JP"), "Doe John")]
+#[case::en_us(Some("en-US"), "John Doe")]
+#[case::no_locale(None, "John Doe")]
+#[tokio::test]
+#[ignore]
+async fn user_display_name_follows_the_locale(
+    #[case] locale: Option<&str>,
+    #[case] expected: &str,
+) {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+    create_user_and_login(&test_case).await;
+
+    if let Some(locale) = locale {
+        let request_body = UpdateUserRequestBody {
+            locale: Patch::Value(String::from(locale)),
+            ..Default::default()
+        };
+        let updated_user = test_case.api.update_user(&request_body).await.unwrap();
+        assert_eq!(updated_user.locale.as_deref(), Some(locale));
+    }
+    let response = test_case.me().await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK, "{}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["displayName"], expected);
+    // The full name is always ordered from the family name
+    assert_eq!(json["fullName"], "Doe John");
+
+    test_case.end().await;
+}
+
+/// Check that the locale can be cleared with JSON Merge Patch, and that an invalid locale is rejected.
+#[tokio::test]
+#[ignore]
+async fn user_can_clear_the_locale_and_can_not_set_an_invalid_one() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+    create_user_and_login(&test_case).await;
+
+    let request_body = UpdateUserRequestBody {
+        locale: Patch::Value(String::from("ja")),
+        ..Default::default()
+    };
+    test_case.api.update_user(&request_body).await.unwrap();
+
+    // An invalid locale is rejected, and the locale is not changed
+    let request_body = UpdateUserRequestBody {
+        locale: Patch::Value(String::from("ja_JP")),
+        ..Default::default()
+    };
+    let error = test_case.api.update_user(&request_body).await.unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error.messages,
+        vec!["locale: must be a language tag such as ja or en-US"]
+    );
+    let user = test_case.api.me().await.unwrap();
+    assert_eq!(user.locale.as_deref(), Some("ja"));
+
+    // Null in plain JSON is ignored
+    let request_body = UpdateUserRequestBody {
+        locale: Patch::Null,
+        ..Default::default()
+    };
+    let updated_user = test_case.api.update_user(&request_body).await.unwrap();
+    assert_eq!(updated_user.locale.as_deref(), Some("ja"));
+
+    // Null under JSON Merge Patch clears the locale
+    let updated_user = test_case.api.merge_patch_user(&request_body).await.unwrap();
+    assert_eq!(updated_user.locale, None);
+
+    test_case.end().await;
+}
+
+/// Check that the email address is changed only after the user confirms the new one.
+#[tokio::test]
+#[ignore]
+async fn user_can_change_email_after_confirming_new_address() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    create_user_and_login(&test_case).await;
+    let request_body = UpdateUserRequestBody {
+        email: Patch::Value(String::from("jane@example.com")),
+        ..Default::default()
+    };
+    test_case.api.update_user(&request_body).await.unwrap();
+
+    // Check that the confirmation token was sent to the new email address
+    let mails = test_case.mailer.mails();
+    assert_eq!(mails.len(), 1);
+    assert_eq!(mails[0].to, "jane@example.com");
+
+    // Check that the user can still log in with the old email address, but not with the new one
+    let response = test_case.login(john_credentials()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = test_case.login(jane_credentials()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Check that a forged token is rejected
+    let body = ConfirmEmailRequestBody {
+        token: SecretString::new("forged-token".into()),
+    };
+    let error = test_case.api.confirm_email(&body).await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::BAD_REQUEST
+    );
+
+    // Confirm the new email address
+    let body = ConfirmEmailRequestBody {
+        token: test_case.mailer.last_token(),
+    };
+    let user = test_case.api.confirm_email(&body).await.unwrap();
+    assert_eq!(user.email, "jane@example.com");
+    assert_eq!(pending_email(&user), None);
+
+    // Check that the token can not be reused
+    let error = test_case.api.confirm_email(&body).await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::BAD_REQUEST
+    );
+
+    // Check that the user can log in with the new email address, but not with the old one
+    let response = test_case.login(jane_credentials()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = test_case.login(john_credentials()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    test_case.end().await;
+}
+
+/// Check that the email address is not changed when the confirmation token has expired.
+#[tokio::test]
+#[ignore]
+async fn user_can_not_confirm_email_with_expired_token() {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.token.email_change_max_age = 1;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    create_user_and_login(&test_case).await;
+    let request_body = UpdateUserRequestBody {
+        email: Patch::Value(String::from("jane@example.com")),
+        ..Default::default()
+    };
+    test_case.api.update_user(&request_body).await.unwrap();
+
+    // Wait for the confirmation token to expire
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    let body = ConfirmEmailRequestBody {
+        token: test_case.mailer.last_token(),
+    };
+    let error = test_case.api.confirm_email(&body).await.unwrap_err();
+    assert_eq!(
+        error.api_error().unwrap().status_code,
+        StatusCode::BAD_REQUEST
+    );
+    let user = test_case.api.me().await.unwrap();
+    assert_eq!(user.email, "john@example.com");
+    assert_eq!(pending_email(&user), None);
+
+    test_case.end().await;
+}
+
+/// Check that the email address change is rejected with 501 Not Implemented when the mail server is not
+/// configured, because the confirmation token could never reach the user.
+#[tokio::test]
+#[ignore]
+async fn user_can_not_change_email_without_mail_server() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+    test_case.mailer.disable_delivery();
+
+    create_user_and_login(&test_case).await;
+    let request_body = UpdateUserRequestBody {
+        family_name: Patch::Value(String::from("Smith")),
+        email: Patch::Value(String::from("jane@example.com")),
+        ..Default::default()
+    };
+    let error = test_case.api.update_user(&request_body).await.unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::NOT_IMPLEMENTED);
+    assert_eq!(error.messages, vec![EMAIL_CHANGE_NOT_AVAILABLE_MESSAGE]);
+    assert!(test_case.mailer.mails().is_empty());
+
+    // Nothing is updated, and the other fields can still be updated without the email address
+    let user = test_case.api.me().await.unwrap();
+    assert_eq!(user.email, "john@example.com");
+    assert_eq!(user.family_name, "Doe");
+    assert_eq!(pending_email(&user), None);
+    let request_body = UpdateUserRequestBody {
+        family_name: Patch::Value(String::from("Smith")),
+        ..Default::default()
+    };
+    let user = test_case.api.update_user(&request_body).await.unwrap();
+    assert_eq!(user.family_name, "Smith");
 
     test_case.end().await;
 }
 
+fn pending_email(profile: &UserProfile) -> Option<&str> {
+    profile.pending_email.as_ref().map(|email| email.0.as_str())
+}
+
 /// Check that an anonymous user can not access an user update endpoint.
 #[tokio::test]
 #[ignore]
@@ -508,7 +1192,13 @@ async fn user_can_refresh_tokens_with_valid_refresh_token_in_the_cookie() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
 
-    let _ = create_user_and_login(&test_case).await;
+    let (user, _) = create_user_and_login(&test_case).await;
+    let logged_in_user = test_case.user_by_id(user.id).await.unwrap();
+    // Fail to log in once to create a login failure history
+    let response = test_case.login(john_incorrect_credential()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let history = test_case.get_login_failed_history(user.id).await.unwrap();
+
     let response = test_case.refresh_tokens().await;
     let ResponseParts {
         status_code,
@@ -517,6 +1207,17 @@ async fn user_can_refresh_tokens_with_valid_refresh_token_in_the_cookie() {
     } = split_response(response).await;
     assert!(status_code.is_success());
     let body: RawLoginResponseBody = serde_json::from_str(&body).unwrap();
+
+    // Check that refreshing the tokens does not move the last login date and time
+    let refreshed_user = test_case.user_by_id(user.id).await.unwrap();
+    assert_eq!(refreshed_user.last_login_at, logged_in_user.last_login_at);
+    // Check that refreshing the tokens does not delete the login failure history
+    let refreshed_history = test_case.get_login_failed_history(user.id).await.unwrap();
+    assert_eq!(
+        refreshed_history.number_of_attempts,
+        history.number_of_attempts
+    );
+    assert_eq!(refreshed_history.attempted_at, history.attempted_at);
     let set_cookie_values = headers.get_all(reqwest::header::SET_COOKIE);
     let mut set_cookies: HashMap<String, Cookie> = HashMap::new();
     for value in set_cookie_values {
@@ -545,7 +1246,7 @@ async fn user_can_refresh_tokens_with_valid_refresh_token_in_the_body() {
         .cookie_store(true)
         .build()
         .unwrap();
-    let uri = format!("{}/users/refresh-tokens", test_case.origin());
+    let uri = test_case.url(paths::USERS_REFRESH_TOKENS);
     let body = format!(
         r#"
         {{
@@ -580,7 +1281,7 @@ async fn user_can_not_refresh_tokens_without_refresh_token() {
         .cookie_store(true)
         .build()
         .unwrap();
-    let uri = format!("{}/users/refresh-tokens", test_case.origin());
+    let uri = test_case.url(paths::USERS_REFRESH_TOKENS);
     let response = client
         .post(&uri)
         .header(reqwest::header::CONTENT_TYPE, "application/json")
@@ -603,6 +1304,8 @@ async fn user_can_not_refresh_tokens_invalid_refresh_token_in_the_cookie() {
     let (user, _) = create_user_and_login(&test_case).await;
     let claim = Claim {
         user_id: user.id,
+        token_type: Some(TokenType::Refresh),
+        issued_at: None,
         expiration: 3000,
     };
     let url = Url::parse(&format!(
@@ -610,7 +1313,14 @@ async fn user_can_not_refresh_tokens_invalid_refresh_token_in_the_cookie() {
         app_settings.http.protocol, app_settings.http.host
     ))
     .unwrap();
-    let refresh_token = generate_token(claim, &SecretString::new("secret-key".into())).unwrap();
+    let refresh_token = generate_token(
+        claim,
+        &TokenSettings {
+            jwt_secret: SecretString::new("secret-key".into()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
     let cookie_jar = reqwest::cookie::Jar::default();
     cookie_jar.add_cookie_str(
         &format!(
@@ -625,7 +1335,7 @@ async fn user_can_not_refresh_tokens_invalid_refresh_token_in_the_cookie() {
         .cookie_provider(Arc::new(cookie_jar))
         .build()
         .unwrap();
-    let uri = format!("{}/users/refresh-tokens", test_case.origin());
+    let uri = test_case.url(paths::USERS_REFRESH_TOKENS);
     let response = client.post(&uri).send().await.unwrap();
     assert_eq!(
         response.status(),
@@ -679,20 +1389,72 @@ async fn user_can_not_refresh_tokens_with_access_token_in_the_cookie() {
         .cookie_provider(Arc::new(cookie_jar))
         .build()
         .unwrap();
-    let uri = format!("{}/users/refresh-tokens", test_case.origin());
+    let uri = test_case.url(paths::USERS_REFRESH_TOKENS);
     let response = client.post(&uri).send().await.unwrap();
     assert_eq!(response.status(), StatusCode::BAD_REQUEST,);
 
     test_case.end().await;
 }
 
-/// Check that the access and refresh tokens are not refreshed when the user who requested them is locked.
+/// Check that an access token is rejected by the refresh endpoint from its type claim even after it was removed from the token repository.
 #[tokio::test]
 #[ignore]
-async fn user_can_not_refresh_tokens_when_the_user_is_locked() {
+async fn user_can_not_refresh_tokens_with_access_token_removed_from_the_token_repository() {
     let app_settings = load_app_settings_for_testing();
     let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
 
+    let (.., tokens) = create_user_and_login(&test_case).await;
+    let response = test_case.logout().await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let access_token = SecretString::new(tokens.access_token.clone().into());
+    assert!(
+        test_case
+            .token_content_from_token_repo(&access_token)
+            .await
+            .is_none()
+    );
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap();
+    let uri = test_case.url(paths::USERS_REFRESH_TOKENS);
+    let body = format!(
+        r#"
+        {{
+            "refreshToken": "{}"
+        }}
+        "#,
+        tokens.access_token
+    );
+    let response = client
+        .post(&uri)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(error_messages(&body), vec!["Invalid refresh token"]);
+
+    test_case.end().await;
+}
+
+/// Check that the access and refresh tokens are not refreshed when the user who requested them is locked,
+/// regardless of whether the lock status is revealed on login.
+#[rstest::rstest]
+#[case::reveal_lock_status(true)]
+#[case::hide_lock_status(false)]
+#[tokio::test]
+#[ignore]
+async fn user_can_not_refresh_tokens_when_the_user_is_locked(#[case] reveal_lock_status: bool) {
+    let mut app_settings = load_app_settings_for_testing();
+    app_settings.login.reveal_lock_status = reveal_lock_status;
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
     let (user, _) = create_user_and_login(&test_case).await;
     test_case.set_user_active_status(user.id, false).await;
     let response = test_case.refresh_tokens().await;
@@ -712,6 +1474,17 @@ fn john_credentials() -> String {
     )
 }
 
+fn jane_credentials() -> String {
+    String::from(
+        r#"
+        {
+            "email": "jane@example.com",
+            "password": "ab12$%AB"
+        }
+        "#,
+    )
+}
+
 fn john_incorrect_credential() -> String {
     String::from(
         r#"
@@ -745,22 +1518,281 @@ async fn create_user_and_login(test_case: &TestCase) -> (User, RawLoginResponseB
     (user, response_body)
 }
 
+/// Check that the user gets 409 Conflict with the same message as the unique violation fallback when signing up
+/// with an email address that is already registered.
+#[tokio::test]
+#[ignore]
+async fn user_can_not_sign_up_with_an_email_address_in_use() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let response = test_case.sign_up(create_sign_up_request_body()).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = test_case.sign_up(create_sign_up_request_body()).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::CONFLICT, "{}", body);
+    assert_eq!(error_messages(&body), vec![EMAIL_ALREADY_IN_USE_MESSAGE]);
+
+    test_case.end().await;
+}
+
+/// Check that the anonymous user can get the password policy, and the policy does not expose the pepper.
+#[tokio::test]
+#[ignore]
+async fn anonymous_user_can_get_the_password_policy() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let settings = &test_case.app_state.password_settings;
+    let policy = test_case.api.password_policy().await.unwrap();
+    assert_eq!(policy.min_length, settings.min_length);
+    assert_eq!(policy.max_length, settings.max_length);
+    assert_eq!(
+        policy.required_character_classes,
+        vec![
+            CharacterClass::Uppercase,
+            CharacterClass::Lowercase,
+            CharacterClass::Digit,
+            CharacterClass::Symbol,
+        ]
+    );
+    assert_eq!(policy.symbols, settings.symbols);
+    assert_eq!(policy.max_same_chars, settings.max_same_chars);
+    assert_eq!(policy.max_repeated_chars, settings.max_repeated_chars);
+
+    let uri = test_case.url(paths::USERS_PASSWORD_POLICY);
+    let response = test_case.http_client.get(&uri).send().await.unwrap();
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK);
+    assert!(!body.contains(settings.pepper.expose_secret()), "{}", body);
+
+    test_case.end().await;
+}
+
+/// Check that the 400 Bad Request response to a sign-up with an invalid password contains the password
+/// policy, but not the pepper.
+#[tokio::test]
+#[ignore]
+async fn user_gets_the_password_policy_when_signing_up_with_an_invalid_password() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let request_body = create_sign_up_request_body().replace("ab12$%AB", "password");
+    let response = test_case.sign_up(request_body).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(
+        error_messages(&body),
+        vec!["The password must contain an uppercase letter"]
+    );
+    let settings = &test_case.app_state.password_settings;
+    assert!(!body.contains(settings.pepper.expose_secret()), "{}", body);
+    let value = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+    let policy = serde_json::from_value::<PasswordPolicy>(value["passwordPolicy"].clone()).unwrap();
+    assert_eq!(policy, settings.policy());
+
+    test_case.end().await;
+}
+
+/// Check that the user gets 400 Bad Request when updating their email address with an invalid one.
+#[tokio::test]
+#[ignore]
+async fn user_can_not_update_user_information_with_invalid_email() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    test_case.login_taro().await;
+    let request_body = UpdateUserRequestBody {
+        email: Patch::Value(String::from("invalid-email")),
+        ..Default::default()
+    };
+    let error = test_case.api.update_user(&request_body).await.unwrap_err();
+    let error = error.api_error().unwrap();
+    assert_eq!(error.status_code, StatusCode::BAD_REQUEST);
+    assert_eq!(error.messages.len(), 1, "{:?}", error.messages);
+    assert!(
+        error.messages[0].starts_with("email: "),
+        "{:?}",
+        error.messages
+    );
+
+    // Check that the user information is not changed
+    let user = test_case.api.me().await.unwrap();
+    assert_eq!(user.email.0, "taro@example.com");
+
+    test_case.end().await;
+}
+
+/// Check that every invalid field is reported when a user signs up with multiple invalid fields.
+#[tokio::test]
+#[ignore]
+async fn user_can_not_sign_up_with_multiple_invalid_fields() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::No).await;
+
+    let body = String::from(
+        r#"
+        {
+            "familyName": "",
+            "givenName": "John",
+            "email": "invalid-email",
+            "password": "ab12$%AB"
+        }
+        "#,
+    );
+    let response = test_case.sign_up(body).await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST, "{}", body);
+    let messages = error_messages(&body);
+    assert_eq!(messages.len(), 2, "{}", body);
+    assert!(messages[0].starts_with("familyName: "), "{}", body);
+    assert!(messages[1].starts_with("email: "), "{}", body);
+
+    test_case.end().await;
+}
+
+/// Check that the user can export their todos, and import them again after deleting all of them.
+#[tokio::test]
+#[ignore]
+async fn user_can_export_and_import_their_todos() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    test_case.login_taro().await;
+
+    // Export the account, including the archived todos
+    let response = test_case.export_account().await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK, "{}", body);
+    let backup = serde_json::from_str::<AccountBackupBody>(&body).unwrap();
+    assert_eq!(backup.user.email, "taro@example.com");
+    assert_eq!(backup.todos.len(), 14);
+    assert_eq!(backup.todos.iter().filter(|t| t.archived).count(), 2);
+    assert!(!body.contains("password"));
+
+    // Delete all todos
+    for todo in backup.todos.iter() {
+        let response = test_case.todo_delete(&todo.id.to_string()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+    let response = test_case.export_account().await;
+    let ResponseParts { body, .. } = split_response(response).await;
+    let emptied = serde_json::from_str::<AccountBackupBody>(&body).unwrap();
+    assert!(emptied.todos.is_empty());
+
+    // Import the exported document
+    let response = test_case
+        .import_account(serde_json::to_string(&backup).unwrap())
+        .await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK, "{}", body);
+    let summary = serde_json::from_str::<ImportResponseBody>(&body).unwrap();
+    assert_eq!(summary.created, 14);
+    assert_eq!(summary.skipped, 0);
+
+    // The todos are recreated with new ids
+    let response = test_case.export_account().await;
+    let ResponseParts { body, .. } = split_response(response).await;
+    let restored = serde_json::from_str::<AccountBackupBody>(&body).unwrap();
+    assert_eq!(restored.todos.len(), backup.todos.len());
+    assert_eq!(restored.todos.iter().filter(|t| t.archived).count(), 2);
+    assert!(
+        restored
+            .todos
+            .iter()
+            .all(|r| backup.todos.iter().all(|b| b.id != r.id))
+    );
+
+    // Importing the same document again skips all duplicates
+    let response = test_case
+        .import_account(serde_json::to_string(&backup).unwrap())
+        .await;
+    let ResponseParts {
+        status_code, body, ..
+    } = split_response(response).await;
+    assert_eq!(status_code, StatusCode::OK, "{}", body);
+    let summary = serde_json::from_str::<ImportResponseBody>(&body).unwrap();
+    assert_eq!(summary.created, 0);
+    assert_eq!(summary.skipped, 14);
+
+    test_case.end().await;
+}
+
+/// Check that importing the same document twice at the same time does not duplicate the todos.
+#[tokio::test]
+#[ignore]
+async fn concurrent_imports_of_the_same_document_do_not_duplicate_todos() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+    test_case.login_taro().await;
+
+    // Export the account, and delete all todos
+    let response = test_case.export_account().await;
+    let ResponseParts { body, .. } = split_response(response).await;
+    let backup = serde_json::from_str::<AccountBackupBody>(&body).unwrap();
+    for todo in backup.todos.iter() {
+        let response = test_case.todo_delete(&todo.id.to_string()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    // Import the exported document twice at the same time
+    let document = serde_json::to_string(&backup).unwrap();
+    let (first, second) = tokio::join!(
+        test_case.import_account(document.clone()),
+        test_case.import_account(document),
+    );
+    let mut created = 0;
+    for response in [first, second] {
+        let ResponseParts {
+            status_code, body, ..
+        } = split_response(response).await;
+        assert_eq!(status_code, StatusCode::OK, "{}", body);
+        let summary = serde_json::from_str::<ImportResponseBody>(&body).unwrap();
+        assert_eq!(summary.created + summary.skipped, backup.todos.len());
+        created += summary.created;
+    }
+    assert_eq!(created, backup.todos.len());
+
+    // Each todo is imported only once
+    let response = test_case.export_account().await;
+    let ResponseParts { body, .. } = split_response(response).await;
+    let restored = serde_json::from_str::<AccountBackupBody>(&body).unwrap();
+    assert_eq!(restored.todos.len(), backup.todos.len());
+
+    test_case.end().await;
+}
+
 /// Check that the cookie specification for access/refresh tokens is correct
 ///
 /// # Arguments
 ///
 /// * `cookie` - cookie that contains the access/refresh token
+/// * `expected_domain` - expected `Domain` attribute
 /// * `expected_same_site` - expected `SameSite` attribute
 /// * `expected_secure` - expected `Secure` attribute`
 /// * `expected_http_only` - expected `HttpOnly` attribute
 /// * `expected_max_age` - expected `MaxAge` for access/refresh token
 fn inspect_token_cookie_spec(
     cookie: &Cookie<'_>,
+    expected_domain: &str,
     expected_same_site: SameSite,
     expected_secure: bool,
     expected_http_only: bool,
     expected_max_age: i64,
 ) {
+    assert_eq!(Some(expected_domain), cookie.domain(), "Domain mismatch");
+    assert_eq!(Some("/"), cookie.path(), "Path mismatch");
     assert_eq!(
         expected_same_site.to_string(),
         cookie.same_site().unwrap().to_string()
@@ -780,4 +1812,92 @@ fn inspect_token_cookie_spec(
         cookie.max_age().unwrap().whole_seconds(),
         "Cookie expiration mismatch"
     );
+    if expected_max_age == 0 {
+        // Clearing cookies must also carry an expired `Expires` for clients that ignore `Max-Age`
+        assert_eq!(
+            Some(OffsetDateTime::UNIX_EPOCH),
+            cookie.expires_datetime(),
+            "Clearing cookie must expire at the epoch"
+        );
+    }
+}
+
+/// Check that creating a login failure history for a user that already has one updates the existing history.
+#[tokio::test]
+#[ignore]
+async fn creating_login_failure_history_twice_upserts_the_history() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case = TestCase::begin(app_settings, EnableTracing::No, InsertTestData::Yes).await;
+
+    let user_id = UserId::from(*TARO_USER_ID);
+    let user_repo = PgUserRepository::new(test_case.pg_pool.clone());
+    let first_attempted_at = OffsetDateTime::now_utc() - Duration::minutes(5);
+    user_repo
+        .create_login_failure_history(user_id, 1, first_attempted_at)
+        .await
+        .unwrap();
+    let second_attempted_at = OffsetDateTime::now_utc();
+    let history = user_repo
+        .create_login_failure_history(user_id, 2, second_attempted_at)
+        .await
+        .unwrap();
+    assert_eq!(history.number_of_attempts, 2);
+    assert!((history.attempted_at - second_attempted_at).abs() < Duration::milliseconds(1));
+    let history = test_case.get_login_failed_history(user_id).await.unwrap();
+    assert_eq!(history.number_of_attempts, 2);
+
+    test_case.end().await;
+}
+
+/// Check that only the stale login failure histories are deleted by the sweep.
+#[tokio::test]
+#[ignore]
+async fn stale_login_failure_histories_are_deleted() {
+    let app_settings = load_app_settings_for_testing();
+    let test_case =
+        TestCase::begin(app_settings.clone(), EnableTracing::No, InsertTestData::Yes).await;
+
+    let now = OffsetDateTime::now_utc();
+    let cutoff = app_settings.login.stale_login_failed_history_cutoff(now);
+    let stale_user_id = UserId::from(*TARO_USER_ID);
+    let fresh_user_id = UserId::from(*HANAKO_USER_ID);
+    let user_repo = PgUserRepository::new(test_case.pg_pool.clone());
+    user_repo
+        .create_login_failure_history(stale_user_id, 1, cutoff - Duration::seconds(1))
+        .await
+        .unwrap();
+    user_repo
+        .create_login_failure_history(fresh_user_id, 1, now)
+        .await
+        .unwrap();
+
+    let deleted = user_repo
+        .delete_stale_login_failed_histories(cutoff)
+        .await
+        .unwrap();
+    assert_eq!(deleted, 1);
+    assert!(
+        test_case
+            .get_login_failed_history(stale_user_id)
+            .await
+            .is_none()
+    );
+    assert!(
+        test_case
+            .get_login_failed_history(fresh_user_id)
+            .await
+            .is_some()
+    );
+
+    test_case.end().await;
+}
+
+/// Returns whether the key exists in Redis, without applying the key prefix.
+async fn redis_key_exists(test_case: &TestCase, key: &str) -> bool {
+    let mut conn = test_case.redis_pool.get().await.unwrap();
+    deadpool_redis::redis::cmd("EXISTS")
+        .arg(key)
+        .query_async::<bool>(&mut conn)
+        .await
+        .unwrap()
 }
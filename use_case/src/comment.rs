@@ -0,0 +1,118 @@
+use domain::{
+    DomainError, DomainErrorKind, DomainResult, domain_error,
+    models::{CommentBody, Todo, TodoComment, TodoCommentId, TodoId},
+    repositories::{TodoCommentListInput, TodoCommentRepository, TodoRepository},
+};
+
+use crate::AuthorizedUser;
+
+#[derive(Debug, Clone)]
+pub struct TodoCommentUseCase<TR, CR>
+where
+    TR: TodoRepository,
+    CR: TodoCommentRepository,
+{
+    pub todo_repo: TR,
+    pub comment_repo: CR,
+    /// 他のユーザーが所有するTodoの存在を隠すかどうか
+    pub hide_cross_user_existence: bool,
+}
+
+impl<TR, CR> TodoCommentUseCase<TR, CR>
+where
+    TR: TodoRepository,
+    CR: TodoCommentRepository,
+{
+    /// Todoのコメントを、作成日時の降順でリストする。
+    ///
+    /// 認証されたユーザーが所有するTodoのコメントのみをリストできる。
+    /// 所有していない場合、`hide_cross_user_existence`が`true`であればTodoが存在しない場合と同様に`NotFound`エラーを返し、
+    /// `false`であれば`Forbidden`エラーを返す。
+    pub async fn list(
+        &self,
+        auth_user: AuthorizedUser,
+        input: TodoCommentListInput,
+    ) -> DomainResult<Vec<TodoComment>> {
+        let todo = get_todo(&self.todo_repo, input.todo_id).await?;
+        if todo.user.id != auth_user.id {
+            // IDの列挙を防止するため、Todoが存在しない場合と区別できないようにする
+            if self.hide_cross_user_existence {
+                return Err(todo_not_found());
+            }
+            return Err(domain_error(
+                DomainErrorKind::Forbidden,
+                "You are not authorized to access this todo",
+            ));
+        }
+        self.comment_repo.list(input).await
+    }
+
+    /// Todoにコメントを登録する。
+    ///
+    /// 認証されたユーザーが所有するTodoにのみコメントできる。
+    /// 所有していない場合、`hide_cross_user_existence`が`true`であればTodoが存在しない場合と同様に`NotFound`エラーを返し、
+    /// `false`であれば`Forbidden`エラーを返す。
+    pub async fn create(
+        &self,
+        auth_user: AuthorizedUser,
+        todo_id: TodoId,
+        body: CommentBody,
+    ) -> DomainResult<TodoComment> {
+        let todo = get_todo(&self.todo_repo, todo_id).await?;
+        if todo.user.id != auth_user.id {
+            // IDの列挙を防止するため、Todoが存在しない場合と区別できないようにする
+            if self.hide_cross_user_existence {
+                return Err(todo_not_found());
+            }
+            return Err(domain_error(
+                DomainErrorKind::Forbidden,
+                "You are not authorized to comment on this todo",
+            ));
+        }
+        self.comment_repo.create(todo_id, auth_user.id, body).await
+    }
+
+    /// コメントを削除する。
+    ///
+    /// コメントしたユーザー、またはTodoを所有するユーザーのみがコメントを削除できる。
+    /// どちらでもない場合、`hide_cross_user_existence`が`true`であれば、コメントの有無に関わらずTodoが存在しない
+    /// 場合と同様に`NotFound`エラーを返し、`false`であれば`Forbidden`エラーを返す。
+    pub async fn delete(
+        &self,
+        auth_user: AuthorizedUser,
+        todo_id: TodoId,
+        comment_id: TodoCommentId,
+    ) -> DomainResult<()> {
+        let todo = get_todo(&self.todo_repo, todo_id).await?;
+        // 指定されたTodoのコメントでない場合は、コメントが存在しない場合と同様に扱う
+        let comment = self
+            .comment_repo
+            .by_id(comment_id)
+            .await?
+            .filter(|comment| comment.todo_id == todo_id);
+        let is_author = comment
+            .as_ref()
+            .is_some_and(|comment| comment.user_id == auth_user.id);
+        // IDの列挙を防止するため、コメントの有無も含めて、Todoが存在しない場合と区別できないようにする
+        if self.hide_cross_user_existence && !is_author && todo.user.id != auth_user.id {
+            return Err(todo_not_found());
+        }
+        let comment =
+            comment.ok_or_else(|| domain_error(DomainErrorKind::NotFound, "Comment not found"))?;
+        if comment.user_id != auth_user.id && todo.user.id != auth_user.id {
+            return Err(domain_error(
+                DomainErrorKind::Forbidden,
+                "You are not authorized to delete this comment",
+            ));
+        }
+        self.comment_repo.delete(comment_id).await
+    }
+}
+
+async fn get_todo<TR: TodoRepository>(todo_repo: &TR, todo_id: TodoId) -> DomainResult<Todo> {
+    todo_repo.by_id(todo_id).await?.ok_or_else(todo_not_found)
+}
+
+fn todo_not_found() -> DomainError {
+    domain_error(DomainErrorKind::NotFound, "Todo not found")
+}
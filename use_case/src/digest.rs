@@ -0,0 +1,43 @@
+use time::Date;
+
+use domain::{
+    DomainResult,
+    models::UserDigest,
+    repositories::{DailyDigestRepository, TodoRepository},
+};
+
+use crate::AuthorizedUser;
+
+#[derive(Debug, Clone)]
+pub struct DailyDigestUseCase<TR, DR>
+where
+    TR: TodoRepository,
+    DR: DailyDigestRepository,
+{
+    pub todo_repo: TR,
+    pub digest_repo: DR,
+}
+
+impl<TR, DR> DailyDigestUseCase<TR, DR>
+where
+    TR: TodoRepository,
+    DR: DailyDigestRepository,
+{
+    /// すべてのユーザーについて、指定された日付の日次ダイジェストを作成して登録する。
+    ///
+    /// 同じ日付で何度実行しても、同じダイジェストが登録される。
+    pub async fn generate(&self, date: Date) -> DomainResult<Vec<UserDigest>> {
+        let digests = self.todo_repo.due_digest(date).await?;
+        self.digest_repo.upsert(date, &digests).await?;
+        Ok(digests)
+    }
+
+    /// 認証されたユーザーの、指定された日付の日次ダイジェストを取得する。
+    pub async fn by_date(
+        &self,
+        auth_user: AuthorizedUser,
+        date: Date,
+    ) -> DomainResult<Option<UserDigest>> {
+        self.digest_repo.by_user_and_date(auth_user.id, date).await
+    }
+}
@@ -1,8 +1,107 @@
+pub mod comment;
+pub mod digest;
 pub mod lookup;
 pub mod todo;
+pub mod token;
 pub mod user;
 
-use domain::models::User;
+use std::ops::Deref;
 
+use domain::models::{User, UserId};
+
+/// 認証されたユーザー
 #[derive(Debug, Clone)]
-pub struct AuthorizedUser(pub User);
+pub struct AuthorizedUser {
+    /// ユーザー
+    pub user: User,
+    /// なりすましている管理者のユーザーID
+    ///
+    /// 管理者がなりすましトークンでユーザーになりすましている場合のみ`Some`になる。
+    pub impersonated_by: Option<UserId>,
+}
+
+impl AuthorizedUser {
+    /// 管理者がなりすましているユーザーを構築する。
+    pub fn impersonated(user: User, impersonated_by: UserId) -> Self {
+        Self {
+            user,
+            impersonated_by: Some(impersonated_by),
+        }
+    }
+
+    /// 管理者がなりすましているかどうかを返す。
+    pub fn is_impersonated(&self) -> bool {
+        self.impersonated_by.is_some()
+    }
+}
+
+impl From<User> for AuthorizedUser {
+    fn from(user: User) -> Self {
+        Self {
+            user,
+            impersonated_by: None,
+        }
+    }
+}
+
+impl Deref for AuthorizedUser {
+    type Target = User;
+
+    fn deref(&self) -> &Self::Target {
+        &self.user
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use domain::models::{
+        Email, FamilyName, GivenName, Role, RoleCode, RoleName, UserId, primitives::DisplayOrder,
+    };
+
+    use super::*;
+
+    fn create_user(id: UserId) -> User {
+        User {
+            id,
+            family_name: FamilyName::new(String::from("Doe")).unwrap(),
+            given_name: GivenName::new(String::from("John")).unwrap(),
+            email: Email::new(String::from("doe@example.com")).unwrap(),
+            role: Role {
+                code: RoleCode::User,
+                name: RoleName(String::from("ユーザー")),
+                description: None,
+                display_order: DisplayOrder(2),
+                created_at: OffsetDateTime::now_utc(),
+                updated_at: OffsetDateTime::now_utc(),
+            },
+            active: true,
+            last_login_at: None,
+            locked_at: None,
+            lock_reason: None,
+            locale: None,
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    #[test]
+    fn authorized_user_derefs_to_user() {
+        let id = UserId::default();
+        let auth_user = AuthorizedUser::from(create_user(id));
+        assert_eq!(auth_user.id, id);
+        assert_eq!(auth_user.email.0, "doe@example.com");
+        assert!(!auth_user.is_impersonated());
+    }
+
+    #[test]
+    fn impersonated_authorized_user_keeps_the_impersonator() {
+        let id = UserId::default();
+        let admin_id = UserId::from(uuid::Uuid::new_v4());
+        let auth_user = AuthorizedUser::impersonated(create_user(id), admin_id);
+        assert_eq!(auth_user.id, id);
+        assert_eq!(auth_user.impersonated_by, Some(admin_id));
+        assert!(auth_user.is_impersonated());
+    }
+}
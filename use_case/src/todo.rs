@@ -1,16 +1,43 @@
+use std::{borrow::Cow, collections::HashSet};
+
+use time::OffsetDateTime;
+
 use domain::{
-    DomainErrorKind, DomainResult, domain_error,
-    models::{COMPLETABLE_TODO_STATUS_CODES, Todo, TodoId, TodoStatusCode},
-    repositories::{TodoCreateInput, TodoListInput, TodoRepository, TodoUpdateInput},
+    DomainError, DomainErrorKind, DomainResult, domain_error,
+    models::{Todo, TodoId, TodoStatusCode, UserId},
+    repositories::{
+        TodoCreateInput, TodoImportInput, TodoListInput, TodoRepository, TodoUpdateInput,
+    },
 };
 
 use crate::AuthorizedUser;
 
+/// 一度にインポートできるTodoの最大数
+pub const MAX_IMPORT_TODOS: usize = 1_000;
+
+/// タイトルが重複しているTodoがあるときに返すメッセージ
+pub const DUPLICATE_TODO_TITLE_MESSAGE: &str =
+    "A todo with the same title already exists. Set allowDuplicate to true to create it anyway";
+
+/// Todoのインポート結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TodoImportSummary {
+    /// 登録したTodoの数
+    pub created: usize,
+    /// 重複していたためスキップしたTodoの数
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone)]
 pub struct TodoUseCase<R>
 where
     R: TodoRepository,
 {
     pub todo_repo: R,
+    /// 他のユーザーが所有するTodoの存在を隠すかどうか
+    pub hide_cross_user_existence: bool,
+    /// 完了予定日が過去のTodoの作成を許可するかどうか
+    pub allow_past_due_date: bool,
 }
 
 impl<R> TodoUseCase<R>
@@ -24,12 +51,18 @@ where
 
     /// Todoを取得する。
     ///
-    /// 認証されたユーザーが所有するTodoのみを返し、所有していない場合はエラーを返す。
+    /// 認証されたユーザーが所有するTodoのみを返す。
+    /// 所有していない場合、`hide_cross_user_existence`が`true`であればTodoが存在しない場合と同様に`None`を返し、
+    /// `false`であればエラーを返す。
     pub async fn by_id(&self, auth_user: AuthorizedUser, id: TodoId) -> DomainResult<Option<Todo>> {
         let todo = self.todo_repo.by_id(id).await?;
         match todo {
             Some(todo) => {
-                if todo.user.id != auth_user.0.id {
+                if todo.user.id != auth_user.id {
+                    // IDの列挙を防止するため、Todoが存在しない場合と区別できないようにする
+                    if self.hide_cross_user_existence {
+                        return Ok(None);
+                    }
                     return Err(domain_error(
                         DomainErrorKind::Forbidden,
                         "You are not authorized to access this todo",
@@ -42,12 +75,29 @@ where
     }
 
     /// Todoを新規作成する。
+    ///
+    /// `allow_past_due_date`が`false`の場合、完了予定日が今日より前のTodoは作成できない。
+    /// `allow_duplicate`が`false`の場合、完了しておらず、アーカイブされていないTodoに、タイトルが大文字と小文字を
+    /// 区別せずに一致するTodoがあれば、重複しているTodoのIDとタイトルを含めた競合エラーを返す。
     pub async fn create(
         &self,
         auth_user: AuthorizedUser,
         input: TodoCreateInput,
+        allow_duplicate: bool,
     ) -> DomainResult<Todo> {
-        self.todo_repo.create(auth_user.0.id, input).await
+        if !self.allow_past_due_date {
+            input.validate(OffsetDateTime::now_utc().date())?;
+        }
+        if !allow_duplicate {
+            let duplicates = self
+                .todo_repo
+                .find_active_by_title(auth_user.id, &input.title)
+                .await?;
+            if !duplicates.is_empty() {
+                return Err(duplicate_todos_error(&duplicates));
+            }
+        }
+        self.todo_repo.create(auth_user.id, input).await
     }
 
     /// Todoを更新する。
@@ -63,7 +113,7 @@ where
         input: TodoUpdateInput,
     ) -> DomainResult<Todo> {
         // Todoを取得して、認証されたユーザーが所有するTodoが確認
-        let todo = get_authorized_user_own_todo(&self.todo_repo, &auth_user, todo_id).await?;
+        let todo = self.authorized_user_own_todo(&auth_user, todo_id).await?;
         // 完了したTodoまたはアーカイブされたTodoは更新不可
         if todo.status.code == TodoStatusCode::Completed || todo.archived {
             return Err(domain_error(
@@ -71,19 +121,18 @@ where
                 "Cannot update completed or archived todo",
             ));
         }
+        // 取得したTodoに更新内容を重ねて、更新後のすべての項目を確定してから保存
+        let input = input.merge(&todo);
         self.todo_repo.update(todo_id, input).await
     }
 
     pub async fn complete(&self, auth_user: AuthorizedUser, todo_id: TodoId) -> DomainResult<Todo> {
         // Todoを取得して、認証されたユーザーが所有するTodoが確認
-        let todo = get_authorized_user_own_todo(&self.todo_repo, &auth_user, todo_id).await?;
+        let todo = self.authorized_user_own_todo(&auth_user, todo_id).await?;
         // 未着手、進行中のTodo以外またはアーカイブされたTodoは完了不可
-        if !COMPLETABLE_TODO_STATUS_CODES.contains(&todo.status.code) || todo.archived {
-            return Err(domain_error(
-                DomainErrorKind::Validation,
-                "Only todos with status 'NotStarted' or 'InProgress' can be completed, and archived todos cannot be completed",
-            ));
-        }
+        //
+        // 同時に状態が変更された場合に備えて、リポジトリも更新するときに同じ条件を確認する。
+        todo.ensure_completable()?;
         self.todo_repo.complete(todo_id).await
     }
 
@@ -101,21 +150,9 @@ where
             ));
         }
         // Todoを取得して、認証されたユーザーが所有するTodoが確認
-        let todo = get_authorized_user_own_todo(&self.todo_repo, &auth_user, todo_id).await?;
-        // Todoの状態が完了済みであることを確認
-        if todo.status.code != TodoStatusCode::Completed {
-            return Err(domain_error(
-                DomainErrorKind::Validation,
-                "Only completed todos can be reopened",
-            ));
-        }
-        // Todoがアーカイブされていないことを確認
-        if todo.archived {
-            return Err(domain_error(
-                DomainErrorKind::Validation,
-                "Archived todos cannot be reopened",
-            ));
-        }
+        let todo = self.authorized_user_own_todo(&auth_user, todo_id).await?;
+        // Todoが完了済みで、アーカイブされていないことを確認
+        todo.ensure_reopenable()?;
         self.todo_repo.reopen(todo_id, status).await
     }
 
@@ -126,39 +163,137 @@ where
         archived: bool,
     ) -> DomainResult<Todo> {
         // Todoを取得して、認証されたユーザーが所有するTodoが確認
-        let todo = get_authorized_user_own_todo(&self.todo_repo, &auth_user, todo_id).await?;
+        let todo = self.authorized_user_own_todo(&auth_user, todo_id).await?;
         // アーカイブする場合は、Todoがアーカイブ済みでないこと、アーカイブを解除する場合はTodoがアーカイブ済みであることを確認
-        if archived && todo.archived {
+        todo.ensure_archivable(archived)?;
+        self.todo_repo.archive(todo_id, archived).await
+    }
+
+    pub async fn delete(&self, auth_user: AuthorizedUser, todo_id: TodoId) -> DomainResult<Todo> {
+        let todo = self.authorized_user_own_todo(&auth_user, todo_id).await?;
+        self.todo_repo.delete(todo.id).await?;
+        Ok(todo)
+    }
+
+    /// 認証されたユーザーが所有するTodoを取得する。
+    ///
+    /// 他のユーザーが所有するTodoの扱いは、`by_id`と同様に`hide_cross_user_existence`に従う。
+    async fn authorized_user_own_todo(
+        &self,
+        auth_user: &AuthorizedUser,
+        todo_id: TodoId,
+    ) -> DomainResult<Todo> {
+        let todo = self.todo_repo.by_id(todo_id).await?;
+        ensure_own_todo(todo, auth_user, self.hide_cross_user_existence)
+    }
+
+    /// ユーザーのTodoを、他のユーザーに移管する。
+    ///
+    /// 移管元と移管先のユーザーが同じ場合は、エラーを返す。
+    /// ユーザーが存在するかは、呼び出し側で確認すること。
+    ///
+    /// # 引数
+    ///
+    /// * `from`: 移管元のユーザーID
+    /// * `to`: 移管先のユーザーID
+    /// * `include_completed`: 完了したTodoも移管するかどうか
+    ///
+    /// # 戻り値
+    ///
+    /// 移管したTodoの数
+    pub async fn transfer_ownership(
+        &self,
+        from: UserId,
+        to: UserId,
+        include_completed: bool,
+    ) -> DomainResult<u64> {
+        if from == to {
             return Err(domain_error(
                 DomainErrorKind::Validation,
-                "Todo is already archived",
+                "The source and destination users must be different",
             ));
-        } else if !archived && !todo.archived {
+        }
+        self.todo_repo
+            .transfer_ownership(from, to, include_completed)
+            .await
+    }
+
+    /// 認証されたユーザーのすべてのTodoを、アーカイブされたTodoを含めてエクスポートする。
+    pub async fn export(&self, auth_user: AuthorizedUser) -> DomainResult<Vec<Todo>> {
+        self.todo_repo
+            .list_all_including_archived(auth_user.id)
+            .await
+    }
+
+    /// エクスポートしたTodoを、認証されたユーザーのTodoとしてインポートする。
+    ///
+    /// Todoは新しいIDで登録する。
+    /// タイトルと完了予定日が同じTodoが既に存在する場合、またはインポートするTodoの中で重複している場合は、
+    /// そのTodoをスキップする。
+    pub async fn import(
+        &self,
+        auth_user: AuthorizedUser,
+        todos: Vec<Todo>,
+    ) -> DomainResult<TodoImportSummary> {
+        if MAX_IMPORT_TODOS < todos.len() {
             return Err(domain_error(
                 DomainErrorKind::Validation,
-                "Todo is not archived",
+                "Too many todos to import",
             ));
         }
-        self.todo_repo.archive(todo_id, archived).await
+        // インポートするTodoがドメインルールを満たしているか確認
+        for todo in todos.iter() {
+            todo.validate()?;
+        }
+        // インポートするTodoの中で重複しているTodoを除外
+        // 既に存在するTodoとの重複は、同時にインポートされた場合も検出できるように、リポジトリが登録時に除外する
+        let mut keys = HashSet::new();
+        let total = todos.len();
+        let inputs = todos
+            .into_iter()
+            .filter(|todo| keys.insert((todo.title.0.clone(), todo.due_date)))
+            .map(TodoImportInput::from)
+            .collect::<Vec<_>>();
+        let created = self.todo_repo.import(auth_user.id, inputs).await? as usize;
+        Ok(TodoImportSummary {
+            created,
+            skipped: total - created,
+        })
     }
+}
 
-    pub async fn delete(&self, auth_user: AuthorizedUser, todo_id: TodoId) -> DomainResult<Todo> {
-        let todo = get_authorized_user_own_todo(&self.todo_repo, &auth_user, todo_id).await?;
-        self.todo_repo.delete(todo.id).await?;
-        Ok(todo)
+/// タイトルが重複しているTodoがあることを示す競合エラーを返す。
+///
+/// 最初のメッセージで重複していることを示し、続くメッセージで重複しているTodoのIDとタイトルを示す。
+fn duplicate_todos_error(duplicates: &[Todo]) -> DomainError {
+    let mut messages: Vec<Cow<'static, str>> = vec![DUPLICATE_TODO_TITLE_MESSAGE.into()];
+    messages.extend(
+        duplicates
+            .iter()
+            .map(|todo| format!("{}: {}", todo.id, todo.title.0).into()),
+    );
+    DomainError {
+        kind: DomainErrorKind::Conflict,
+        messages,
+        source: anyhow::anyhow!(DUPLICATE_TODO_TITLE_MESSAGE),
     }
 }
 
-async fn get_authorized_user_own_todo<TR: TodoRepository>(
-    todo_repo: &TR,
+/// Todoが存在して、認証されたユーザーが所有していることを確認する。
+///
+/// 所有していない場合、`hide_cross_user_existence`が`true`であればTodoが存在しない場合と同じエラーを返し、
+/// `false`であれば禁止された操作であることを示すエラーを返す。
+fn ensure_own_todo(
+    todo: Option<Todo>,
     auth_user: &AuthorizedUser,
-    todo_id: TodoId,
+    hide_cross_user_existence: bool,
 ) -> DomainResult<Todo> {
-    let todo = todo_repo
-        .by_id(todo_id)
-        .await?
-        .ok_or_else(|| domain_error(DomainErrorKind::NotFound, "Todo not found"))?;
-    if todo.user.id != auth_user.0.id {
+    let todo = todo.ok_or_else(todo_not_found)?;
+    if todo.user.id != auth_user.id {
+        // IDの列挙を防止するため、Todoが存在しない場合と区別できないようにする
+        if hide_cross_user_existence {
+            return Err(todo_not_found());
+        }
         return Err(domain_error(
             DomainErrorKind::Forbidden,
             "You are not authorized to update this todo",
@@ -166,3 +301,112 @@ async fn get_authorized_user_own_todo<TR: TodoRepository>(
     }
     Ok(todo)
 }
+
+fn todo_not_found() -> DomainError {
+    domain_error(DomainErrorKind::NotFound, "Todo not found")
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{OffsetDateTime, macros::date};
+    use uuid::Uuid;
+
+    use domain::models::{
+        Email, FamilyName, GivenName, Role, RoleCode, RoleName, TodoStatus, TodoStatusName,
+        TodoTitle, User, primitives::DisplayOrder,
+    };
+
+    use super::*;
+
+    fn create_user() -> User {
+        User {
+            id: UserId::from(Uuid::new_v4()),
+            family_name: FamilyName::new(String::from("Doe")).unwrap(),
+            given_name: GivenName::new(String::from("John")).unwrap(),
+            email: Email::new(String::from("doe@example.com")).unwrap(),
+            role: Role {
+                code: RoleCode::User,
+                name: RoleName(String::from("ユーザー")),
+                description: None,
+                display_order: DisplayOrder(2),
+                created_at: OffsetDateTime::now_utc(),
+                updated_at: OffsetDateTime::now_utc(),
+            },
+            active: true,
+            last_login_at: None,
+            locked_at: None,
+            lock_reason: None,
+            locale: None,
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn create_todo(owner: User) -> Todo {
+        let status = TodoStatus {
+            code: TodoStatusCode::NotStarted,
+            name: TodoStatusName(String::from("未着手")),
+            description: None,
+            display_order: DisplayOrder(1),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        };
+        Todo::new(
+            TodoId::default(),
+            owner,
+            TodoTitle::new(String::from("todo")).unwrap(),
+            None,
+            status,
+            Some(date!(2999 - 12 - 31)),
+            None,
+            false,
+            OffsetDateTime::now_utc(),
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap()
+    }
+
+    #[rstest::rstest]
+    #[case::hide_existence(true)]
+    #[case::reveal_existence(false)]
+    fn ensure_own_todo_returns_the_todo_of_the_user(#[case] hide_cross_user_existence: bool) {
+        let owner = AuthorizedUser::from(create_user());
+        let todo = create_todo(owner.user.clone());
+        let result = ensure_own_todo(Some(todo.clone()), &owner, hide_cross_user_existence);
+        assert_eq!(result.unwrap().id, todo.id);
+    }
+
+    #[rstest::rstest]
+    #[case::hide_existence(true)]
+    #[case::reveal_existence(false)]
+    fn ensure_own_todo_returns_not_found_for_the_missing_todo(
+        #[case] hide_cross_user_existence: bool,
+    ) {
+        let owner = AuthorizedUser::from(create_user());
+        let error = ensure_own_todo(None, &owner, hide_cross_user_existence).unwrap_err();
+        assert_eq!(error.kind, DomainErrorKind::NotFound);
+        assert_eq!(error.messages, vec!["Todo not found"]);
+    }
+
+    /// 他のユーザーのTodoを更新、完了、再オープン、アーカイブ、削除しようとした場合に、存在を隠すときは
+    /// 存在しないTodoと同じエラーを返す。
+    #[rstest::rstest]
+    #[case::hide_existence(true, DomainErrorKind::NotFound, "Todo not found")]
+    #[case::reveal_existence(
+        false,
+        DomainErrorKind::Forbidden,
+        "You are not authorized to update this todo"
+    )]
+    fn ensure_own_todo_rejects_the_todo_of_another_user(
+        #[case] hide_cross_user_existence: bool,
+        #[case] expected_kind: DomainErrorKind,
+        #[case] expected_message: &str,
+    ) {
+        let owner = create_user();
+        let other = AuthorizedUser::from(create_user());
+        let todo = create_todo(owner);
+        let error = ensure_own_todo(Some(todo), &other, hide_cross_user_existence).unwrap_err();
+        assert_eq!(error.kind, expected_kind);
+        assert_eq!(error.messages, vec![expected_message]);
+    }
+}
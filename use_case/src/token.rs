@@ -0,0 +1,473 @@
+use secrecy::SecretString;
+use time::OffsetDateTime;
+
+use domain::{
+    DomainResult,
+    models::UserId,
+    repositories::{AuthTokenInfo, TokenRepository, UserRepository},
+};
+
+/// トークンを発行する契機
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenIssuance {
+    /// ログイン
+    Login {
+        /// ログインした日時
+        logged_in_at: OffsetDateTime,
+        /// 同時にログインできるセッション数（0の場合は無制限）
+        max_sessions: u32,
+    },
+    /// トークンのリフレッシュ
+    Refresh,
+}
+
+/// 発行するアクセストークンとリフレッシュトークンの組
+pub struct TokenPairToIssue<'a> {
+    /// アクセストークンの認証情報
+    pub access: &'a AuthTokenInfo,
+    /// アクセストークンの有効期限
+    pub access_expired_at: OffsetDateTime,
+    /// リフレッシュトークンの認証情報
+    pub refresh: &'a AuthTokenInfo,
+    /// リフレッシュトークンの有効期限
+    pub refresh_expired_at: OffsetDateTime,
+}
+
+/// トークンの発行で使用するトークンリポジトリの操作
+#[async_trait::async_trait]
+pub trait IssuedTokenStore: Send + Sync {
+    /// アクセストークンとリフレッシュトークンの認証情報を登録する。
+    async fn register_token_pair(
+        &self,
+        access_token_info: &AuthTokenInfo,
+        refresh_token_info: &AuthTokenInfo,
+    ) -> DomainResult<()>;
+
+    /// 認証情報を削除する。
+    async fn delete_token_content(&self, key: &SecretString) -> DomainResult<()>;
+}
+
+#[async_trait::async_trait]
+impl<T> IssuedTokenStore for T
+where
+    T: TokenRepository,
+{
+    async fn register_token_pair(
+        &self,
+        access_token_info: &AuthTokenInfo,
+        refresh_token_info: &AuthTokenInfo,
+    ) -> DomainResult<()> {
+        TokenRepository::register_token_pair(self, access_token_info, refresh_token_info).await
+    }
+
+    async fn delete_token_content(&self, key: &SecretString) -> DomainResult<()> {
+        TokenRepository::delete_token_content(self, key).await
+    }
+}
+
+/// トークンの発行で使用するユーザーリポジトリの操作
+#[async_trait::async_trait]
+pub trait UserSessionStore: Send + Sync {
+    /// ユーザーがログインしたときの処理をする。
+    ///
+    /// 詳細は`UserRepository::handle_logged_in`を参照すること。
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_logged_in(
+        &self,
+        id: UserId,
+        logged_in_at: OffsetDateTime,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
+        max_sessions: u32,
+    ) -> DomainResult<Vec<SecretString>>;
+
+    /// ユーザーの認証情報を登録する。
+    async fn register_token_pair_for_user(
+        &self,
+        id: UserId,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
+    ) -> DomainResult<()>;
+}
+
+#[async_trait::async_trait]
+impl<T> UserSessionStore for T
+where
+    T: UserRepository + Send + Sync,
+{
+    async fn handle_logged_in(
+        &self,
+        id: UserId,
+        logged_in_at: OffsetDateTime,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
+        max_sessions: u32,
+    ) -> DomainResult<Vec<SecretString>> {
+        UserRepository::handle_logged_in(
+            self,
+            id,
+            logged_in_at,
+            access_key,
+            access_expired_at,
+            refresh_key,
+            refresh_expired_at,
+            max_sessions,
+        )
+        .await
+    }
+
+    async fn register_token_pair_for_user(
+        &self,
+        id: UserId,
+        access_key: &SecretString,
+        access_expired_at: OffsetDateTime,
+        refresh_key: &SecretString,
+        refresh_expired_at: OffsetDateTime,
+    ) -> DomainResult<()> {
+        UserRepository::register_token_pair_for_user(
+            self,
+            id,
+            access_key,
+            access_expired_at,
+            refresh_key,
+            refresh_expired_at,
+        )
+        .await
+    }
+}
+
+/// アクセストークンとリフレッシュトークンを、トークンリポジトリとユーザーリポジトリに登録する。
+///
+/// ログアウトするときは、ユーザーリポジトリに登録されたキーでトークンリポジトリの認証情報を削除する。
+/// したがって、ユーザーリポジトリへの登録に失敗した場合は、ログアウトで無効にできないセッションが残らないように、
+/// トークンリポジトリに登録した認証情報を削除する。
+#[derive(Debug, Clone)]
+pub struct TokenIssuer<UR, TR>
+where
+    UR: UserSessionStore,
+    TR: IssuedTokenStore,
+{
+    /// ユーザーリポジトリ
+    pub user_repo: UR,
+    /// トークンリポジトリ
+    pub token_repo: TR,
+}
+
+impl<UR, TR> TokenIssuer<UR, TR>
+where
+    UR: UserSessionStore,
+    TR: IssuedTokenStore,
+{
+    /// アクセストークンとリフレッシュトークンを登録する。
+    ///
+    /// ログインの場合は、同時にログインできるセッション数を超えた古いセッションを削除して、削除したセッションの
+    /// アクセストークンとリフレッシュトークンのキーを返す。トークンのリフレッシュの場合は、空のベクタを返す。
+    pub async fn issue(
+        &self,
+        user_id: UserId,
+        tokens: &TokenPairToIssue<'_>,
+        issuance: TokenIssuance,
+    ) -> DomainResult<Vec<SecretString>> {
+        self.token_repo
+            .register_token_pair(tokens.access, tokens.refresh)
+            .await?;
+        let result = match issuance {
+            TokenIssuance::Login {
+                logged_in_at,
+                max_sessions,
+            } => {
+                // ユーザーの最終ログイン日時を更新して、認証情報を登録するとともに、ログイン失敗履歴を削除
+                // 同時にログインできるセッション数を超えた場合は、古いセッションを削除
+                self.user_repo
+                    .handle_logged_in(
+                        user_id,
+                        logged_in_at,
+                        &tokens.access.key,
+                        tokens.access_expired_at,
+                        &tokens.refresh.key,
+                        tokens.refresh_expired_at,
+                        max_sessions,
+                    )
+                    .await
+            }
+            TokenIssuance::Refresh => {
+                // 認証情報のみを登録して、最終ログイン日時とログイン失敗履歴は変更しない
+                self.user_repo
+                    .register_token_pair_for_user(
+                        user_id,
+                        &tokens.access.key,
+                        tokens.access_expired_at,
+                        &tokens.refresh.key,
+                        tokens.refresh_expired_at,
+                    )
+                    .await
+                    .map(|_| vec![])
+            }
+        };
+        let pruned_keys = match result {
+            Ok(pruned_keys) => pruned_keys,
+            Err(e) => {
+                // ユーザーリポジトリに登録できなかった認証情報を、トークンリポジトリから削除
+                for key in [&tokens.access.key, &tokens.refresh.key] {
+                    if let Err(delete_error) = self.token_repo.delete_token_content(key).await {
+                        tracing::error!(
+                            %user_id,
+                            "Failed to delete the token that could not be registered: {:#}",
+                            delete_error.source
+                        );
+                    }
+                }
+                return Err(e);
+            }
+        };
+        for key in pruned_keys.iter() {
+            self.token_repo.delete_token_content(key).await?;
+        }
+        Ok(pruned_keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use secrecy::ExposeSecret as _;
+    use time::Duration;
+    use uuid::Uuid;
+
+    use domain::{
+        DomainErrorKind, domain_error,
+        repositories::{TokenType, generate_auth_token_info},
+    };
+
+    use super::*;
+
+    /// 認証情報をメモリに保存するトークンリポジトリ
+    #[derive(Debug, Clone, Default)]
+    struct InMemoryTokenRepository {
+        entries: Arc<Mutex<HashMap<String, String>>>,
+    }
+
+    impl InMemoryTokenRepository {
+        fn keys(&self) -> Vec<String> {
+            let mut keys = self
+                .entries
+                .lock()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>();
+            keys.sort();
+            keys
+        }
+
+        fn register(&self, token_info: &AuthTokenInfo) {
+            self.entries.lock().unwrap().insert(
+                token_info.key.expose_secret().to_string(),
+                token_info.value.clone(),
+            );
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl IssuedTokenStore for InMemoryTokenRepository {
+        async fn register_token_pair(
+            &self,
+            access_token_info: &AuthTokenInfo,
+            refresh_token_info: &AuthTokenInfo,
+        ) -> DomainResult<()> {
+            self.register(access_token_info);
+            self.register(refresh_token_info);
+            Ok(())
+        }
+
+        async fn delete_token_content(&self, key: &SecretString) -> DomainResult<()> {
+            self.entries.lock().unwrap().remove(key.expose_secret());
+            Ok(())
+        }
+    }
+
+    /// 登録した認証情報のキーをメモリに保存するユーザーリポジトリ
+    ///
+    /// `fails`が`true`の場合は、認証情報の登録に失敗する。
+    #[derive(Debug, Clone, Default)]
+    struct InMemoryUserRepository {
+        fails: bool,
+        pruned_keys: Vec<String>,
+        keys: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl InMemoryUserRepository {
+        fn register(
+            &self,
+            access_key: &SecretString,
+            refresh_key: &SecretString,
+        ) -> DomainResult<()> {
+            if self.fails {
+                return Err(domain_error(
+                    DomainErrorKind::Repository,
+                    "Failed to register the tokens",
+                ));
+            }
+            let mut keys = self.keys.lock().unwrap();
+            keys.push(access_key.expose_secret().to_string());
+            keys.push(refresh_key.expose_secret().to_string());
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl UserSessionStore for InMemoryUserRepository {
+        async fn handle_logged_in(
+            &self,
+            _id: UserId,
+            _logged_in_at: OffsetDateTime,
+            access_key: &SecretString,
+            _access_expired_at: OffsetDateTime,
+            refresh_key: &SecretString,
+            _refresh_expired_at: OffsetDateTime,
+            _max_sessions: u32,
+        ) -> DomainResult<Vec<SecretString>> {
+            self.register(access_key, refresh_key)?;
+            Ok(self
+                .pruned_keys
+                .iter()
+                .map(|key| SecretString::new(key.as_str().into()))
+                .collect())
+        }
+
+        async fn register_token_pair_for_user(
+            &self,
+            _id: UserId,
+            access_key: &SecretString,
+            _access_expired_at: OffsetDateTime,
+            refresh_key: &SecretString,
+            _refresh_expired_at: OffsetDateTime,
+        ) -> DomainResult<()> {
+            self.register(access_key, refresh_key)
+        }
+    }
+
+    fn token_info(user_id: UserId, token: &str, token_type: TokenType) -> AuthTokenInfo {
+        generate_auth_token_info(user_id, &SecretString::new(token.into()), token_type, 60)
+    }
+
+    fn issuances() -> [TokenIssuance; 2] {
+        [
+            TokenIssuance::Login {
+                logged_in_at: OffsetDateTime::now_utc(),
+                max_sessions: 0,
+            },
+            TokenIssuance::Refresh,
+        ]
+    }
+
+    #[tokio::test]
+    async fn token_issuer_registers_the_tokens_in_both_repositories() {
+        let user_id = UserId::from(Uuid::new_v4());
+        let access = token_info(user_id, "access", TokenType::Access);
+        let refresh = token_info(user_id, "refresh", TokenType::Refresh);
+        let tokens = TokenPairToIssue {
+            access: &access,
+            access_expired_at: OffsetDateTime::now_utc() + Duration::minutes(1),
+            refresh: &refresh,
+            refresh_expired_at: OffsetDateTime::now_utc() + Duration::minutes(1),
+        };
+        let mut expected = vec![
+            access.key.expose_secret().to_string(),
+            refresh.key.expose_secret().to_string(),
+        ];
+        expected.sort();
+
+        for issuance in issuances() {
+            let issuer = TokenIssuer {
+                user_repo: InMemoryUserRepository::default(),
+                token_repo: InMemoryTokenRepository::default(),
+            };
+            let pruned_keys = issuer.issue(user_id, &tokens, issuance).await.unwrap();
+            assert!(pruned_keys.is_empty());
+            assert_eq!(issuer.token_repo.keys(), expected);
+            let mut keys = issuer.user_repo.keys.lock().unwrap().clone();
+            keys.sort();
+            assert_eq!(keys, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn token_issuer_deletes_the_tokens_when_the_user_repository_fails() {
+        let user_id = UserId::from(Uuid::new_v4());
+        let access = token_info(user_id, "access", TokenType::Access);
+        let refresh = token_info(user_id, "refresh", TokenType::Refresh);
+        let tokens = TokenPairToIssue {
+            access: &access,
+            access_expired_at: OffsetDateTime::now_utc() + Duration::minutes(1),
+            refresh: &refresh,
+            refresh_expired_at: OffsetDateTime::now_utc() + Duration::minutes(1),
+        };
+
+        for issuance in issuances() {
+            let issuer = TokenIssuer {
+                user_repo: InMemoryUserRepository {
+                    fails: true,
+                    ..Default::default()
+                },
+                token_repo: InMemoryTokenRepository::default(),
+            };
+            // The token that was registered before the issuance must survive
+            let other = token_info(user_id, "other", TokenType::Access);
+            issuer.token_repo.register(&other);
+
+            let error = issuer.issue(user_id, &tokens, issuance).await.unwrap_err();
+            assert_eq!(error.kind, DomainErrorKind::Repository);
+            assert_eq!(
+                issuer.token_repo.keys(),
+                vec![other.key.expose_secret().to_string()]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn token_issuer_deletes_the_pruned_sessions_from_the_token_repository() {
+        let user_id = UserId::from(Uuid::new_v4());
+        let old = token_info(user_id, "old", TokenType::Access);
+        let access = token_info(user_id, "access", TokenType::Access);
+        let refresh = token_info(user_id, "refresh", TokenType::Refresh);
+        let tokens = TokenPairToIssue {
+            access: &access,
+            access_expired_at: OffsetDateTime::now_utc() + Duration::minutes(1),
+            refresh: &refresh,
+            refresh_expired_at: OffsetDateTime::now_utc() + Duration::minutes(1),
+        };
+        let issuer = TokenIssuer {
+            user_repo: InMemoryUserRepository {
+                pruned_keys: vec![old.key.expose_secret().to_string()],
+                ..Default::default()
+            },
+            token_repo: InMemoryTokenRepository::default(),
+        };
+        issuer.token_repo.register(&old);
+
+        let issuance = TokenIssuance::Login {
+            logged_in_at: OffsetDateTime::now_utc(),
+            max_sessions: 1,
+        };
+        let pruned_keys = issuer.issue(user_id, &tokens, issuance).await.unwrap();
+        assert_eq!(pruned_keys.len(), 1);
+        assert!(
+            !issuer
+                .token_repo
+                .keys()
+                .contains(&old.key.expose_secret().to_string())
+        );
+        assert_eq!(issuer.token_repo.keys().len(), 2);
+    }
+}
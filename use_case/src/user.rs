@@ -1,12 +1,18 @@
+use secrecy::{ExposeSecret as _, SecretString};
+
 use domain::{
-    DomainResult,
-    models::{PHCString, User},
-    repositories::{TokenRepository, UpdateUserInput, UserInput, UserRepository},
+    DomainErrorKind, DomainResult, EMAIL_ALREADY_IN_USE_MESSAGE, domain_error,
+    models::{Email, PHCString, User, UserPreferences, UserProfile},
+    repositories::{
+        AuthTokenInfo, TokenRepository, TokenType, UpdateUserInput, UserInput, UserRepository,
+        generate_auth_token_info_key,
+    },
 };
 
 use crate::AuthorizedUser;
 
 /// ユーザーユースケース
+#[derive(Debug, Clone)]
 pub struct UserUseCase<UR, TR>
 where
     UR: UserRepository,
@@ -24,17 +30,25 @@ where
     TR: TokenRepository,
 {
     /// ユーザーをサインアップする。
+    ///
+    /// Eメールアドレスが既に登録されている場合は、ユーザーを登録せずに競合エラーを返す。
     pub async fn sign_up(
         &self,
         input: UserInput,
         hashed_password: PHCString,
     ) -> DomainResult<User> {
+        if self.user_repo.by_email(&input.email).await?.is_some() {
+            return Err(domain_error(
+                DomainErrorKind::Conflict,
+                EMAIL_ALREADY_IN_USE_MESSAGE,
+            ));
+        }
         self.user_repo.create(input, hashed_password).await
     }
 
     /// ユーザー自信の情報を取得する。
     pub fn me(&self, auth_user: AuthorizedUser) -> User {
-        auth_user.0
+        auth_user.user
     }
 
     /// ユーザーを更新する。
@@ -43,7 +57,102 @@ where
         auth_user: AuthorizedUser,
         input: UpdateUserInput,
     ) -> DomainResult<User> {
-        let user = self.user_repo.update(auth_user.0.id, input).await?;
+        let user = self.user_repo.update(auth_user.id, input).await?;
+        Ok(user)
+    }
+
+    /// ユーザーのプロファイルを取得する。
+    ///
+    /// 確認待ちのEメールアドレスが存在する場合は、プロファイルに含める。
+    pub async fn profile(&self, user: User) -> DomainResult<UserProfile> {
+        let pending_email = self.token_repo.pending_email(user.id).await?;
+        Ok(UserProfile {
+            user,
+            pending_email: pending_email.map(|pending| pending.email),
+        })
+    }
+
+    /// Eメールアドレスの変更を要求する。
+    ///
+    /// Eメールアドレスはすぐに変更せず、確認トークンで確認されるまで確認待ちとして登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `auth_user` - 認証されたユーザー
+    /// * `email` - 新しいEメールアドレス
+    /// * `token_info` - 確認トークンの情報
+    pub async fn request_email_change(
+        &self,
+        auth_user: &AuthorizedUser,
+        email: &Email,
+        token_info: &AuthTokenInfo,
+    ) -> DomainResult<()> {
+        if auth_user.email == *email {
+            return Err(domain_error(
+                DomainErrorKind::Validation,
+                "email: the new email address is the same as the current one",
+            ));
+        }
+        self.token_repo
+            .register_pending_email(auth_user.id, email, token_info)
+            .await
+    }
+
+    /// 確認トークンを検証して、確認待ちのEメールアドレスでユーザーのEメールアドレスを更新する。
+    ///
+    /// 確認トークンが無効、期限切れ、他のユーザーのもの、または最新の確認トークンでない場合はエラーを返す。
+    pub async fn confirm_email(
+        &self,
+        auth_user: AuthorizedUser,
+        token: &SecretString,
+    ) -> DomainResult<User> {
+        let invalid_token = || {
+            domain_error(
+                DomainErrorKind::Validation,
+                "Invalid or expired email confirmation token",
+            )
+        };
+        let token_key = generate_auth_token_info_key(token);
+        let content = self
+            .token_repo
+            .get_token_content(&token_key)
+            .await?
+            .ok_or_else(invalid_token)?;
+        if content.token_type != TokenType::EmailChange || content.user_id != auth_user.id {
+            return Err(invalid_token());
+        }
+        let pending = self
+            .token_repo
+            .pending_email(auth_user.id)
+            .await?
+            .ok_or_else(invalid_token)?;
+        if pending.token_key.expose_secret() != token_key.expose_secret() {
+            return Err(invalid_token());
+        }
+        let user = self
+            .user_repo
+            .update_email(auth_user.id, pending.email)
+            .await?;
+        self.token_repo.delete_pending_email(auth_user.id).await?;
         Ok(user)
     }
+
+    /// ユーザーの設定を取得する。
+    pub async fn preferences(&self, auth_user: &AuthorizedUser) -> DomainResult<UserPreferences> {
+        self.user_repo.preferences(auth_user.id).await
+    }
+
+    /// ユーザーの設定を更新する。
+    ///
+    /// 設定を検証して、検証に失敗した場合は更新しない。
+    pub async fn update_preferences(
+        &self,
+        auth_user: AuthorizedUser,
+        preferences: UserPreferences,
+    ) -> DomainResult<UserPreferences> {
+        preferences.validate()?;
+        self.user_repo
+            .update_preferences(auth_user.id, &preferences)
+            .await
+    }
 }
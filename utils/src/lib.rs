@@ -1,2 +1,5 @@
+pub mod log;
 pub mod serde;
 pub mod time;
+
+pub use serde_with;
@@ -0,0 +1,58 @@
+/// ログやレスポンスに含めるメッセージの最大文字数
+pub const MAX_LOG_MESSAGE_LENGTH: usize = 500;
+
+/// ログやレスポンスに含めるメッセージから、ログの解析を妨げる文字を取り除く。
+///
+/// メッセージにはキーワードなどのユーザーが入力した文字列が含まれることがあるため、改行などを含むメッセージで
+/// JSON形式のログの解析が妨げられないように、次の通り変換する。
+///
+/// - 改行とタブは、空白に置き換える。
+/// - それ以外の制御文字と、双方向テキストの制御文字、行区切り文字及び段落区切り文字は取り除く。
+/// - `MAX_LOG_MESSAGE_LENGTH`文字を超える部分は切り捨てる。
+pub fn sanitize_log_message(s: &str) -> String {
+    s.chars()
+        .filter_map(|c| match c {
+            '\n' | '\r' | '\t' => Some(' '),
+            c if c.is_control() || is_invisible_format_char(c) => None,
+            c => Some(c),
+        })
+        .take(MAX_LOG_MESSAGE_LENGTH)
+        .collect()
+}
+
+/// 双方向テキストの制御文字、行区切り文字、または段落区切り文字か確認する。
+fn is_invisible_format_char(c: char) -> bool {
+    matches!(c, '\u{200E}' | '\u{200F}' | '\u{2028}' | '\u{2029}')
+        || ('\u{202A}'..='\u{202E}').contains(&c)
+        || ('\u{2066}'..='\u{2069}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rstest::rstest]
+    #[case("keyword", "keyword")]
+    #[case("line1\nline2", "line1 line2")]
+    #[case("line1\r\nline2", "line1  line2")]
+    #[case("a\tb", "a b")]
+    #[case("null\0byte", "nullbyte")]
+    #[case("escape\u{1b}[31m", "escape[31m")]
+    #[case("delete\u{7f}", "delete")]
+    #[case("c1\u{85}control", "c1control")]
+    #[case("rtl\u{202E}override", "rtloverride")]
+    #[case("isolate\u{2066}text\u{2069}", "isolatetext")]
+    #[case("line\u{2028}separator", "lineseparator")]
+    #[case(r#"{"injected": true}"#, r#"{"injected": true}"#)]
+    #[case("日本語のキーワード", "日本語のキーワード")]
+    fn sanitize_log_message_removes_control_characters(#[case] s: &str, #[case] expected: &str) {
+        assert_eq!(sanitize_log_message(s), expected);
+    }
+
+    #[test]
+    fn sanitize_log_message_truncates_long_message() {
+        let message = "あ".repeat(MAX_LOG_MESSAGE_LENGTH + 1);
+        let sanitized = sanitize_log_message(&message);
+        assert_eq!(sanitized.chars().count(), MAX_LOG_MESSAGE_LENGTH);
+    }
+}
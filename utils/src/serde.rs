@@ -2,10 +2,18 @@ use std::{fmt::Display, str::FromStr};
 
 use secrecy::{ExposeSecret as _, SecretString};
 use serde::{Deserialize, Deserializer, Serializer, de::Error};
-use time::{Date, OffsetDateTime, serde::rfc3339};
+use serde_with::{DeserializeAs, SerializeAs};
+use time::{Date, OffsetDateTime, UtcOffset, serde::rfc3339};
 
 use crate::time::DATE_FORMAT;
 
+pub fn serialize_date<S>(dt: &Date, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&dt.format(DATE_FORMAT).unwrap())
+}
+
 pub fn serialize_option_date<S>(dt: &Option<Date>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -35,6 +43,51 @@ where
     Ok(value.map(|Wrapper(dt)| dt))
 }
 
+/// 日時をシリアライズするときの秒未満の精度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampPrecision {
+    /// 秒
+    Seconds,
+    /// ミリ秒
+    Milliseconds,
+}
+
+/// レスポンスなどに出力する日時の秒未満の精度
+pub const TIMESTAMP_PRECISION: TimestampPrecision = TimestampPrecision::Seconds;
+
+/// 日時をUTCに変換して、`TIMESTAMP_PRECISION`の精度に切り捨てる。
+pub fn normalize_offset_datetime(dt: OffsetDateTime) -> OffsetDateTime {
+    let dt = dt.to_offset(UtcOffset::UTC);
+    let nanosecond = match TIMESTAMP_PRECISION {
+        TimestampPrecision::Seconds => 0,
+        TimestampPrecision::Milliseconds => dt.millisecond() as u32 * 1_000_000,
+    };
+    // 0から999,999,999の範囲に収まるため失敗しない
+    dt.replace_nanosecond(nanosecond).unwrap()
+}
+
+/// 日時を`normalize_offset_datetime`で正規化して、RFC 3339形式でシリアライズ及びデシリアライズするモジュール
+///
+/// `#[serde(with = "utils::serde::timestamp")]`のように使用する。デシリアライズするときは、任意のオフセットを
+/// 受け付けて、精度を切り捨てない。
+pub mod timestamp {
+    use super::*;
+
+    pub fn serialize<S>(dt: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        rfc3339::serialize(&normalize_offset_datetime(*dt), serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<OffsetDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        rfc3339::deserialize(deserializer)
+    }
+}
+
 pub fn serialize_option_offset_datetime<S>(
     dt: &Option<OffsetDateTime>,
     serializer: S,
@@ -43,7 +96,7 @@ where
     S: Serializer,
 {
     match dt {
-        Some(dt) => rfc3339::serialize(dt, serializer),
+        Some(dt) => timestamp::serialize(dt, serializer),
         None => serializer.serialize_none(),
     }
 }
@@ -61,19 +114,28 @@ where
     Ok(value.map(|Wrapper(dt)| dt))
 }
 
-pub fn serialize_secret_string<S>(s: &SecretString, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    serializer.serialize_str(s.expose_secret())
+/// `SecretString`を文字列としてシリアライズ及びデシリアライズする`serde_with`のアダプタ
+///
+/// `#[serde_as(as = "SecretStringSerde")]`のように使用する。
+pub struct SecretStringSerde;
+
+impl SerializeAs<SecretString> for SecretStringSerde {
+    fn serialize_as<S>(source: &SecretString, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(source.expose_secret())
+    }
 }
 
-pub fn deserialize_secret_string<'de, D>(deserializer: D) -> Result<SecretString, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let value: String = String::deserialize(deserializer)?;
-    Ok(SecretString::new(value.into()))
+impl<'de> DeserializeAs<'de, SecretString> for SecretStringSerde {
+    fn deserialize_as<D>(deserializer: D) -> Result<SecretString, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value: String = String::deserialize(deserializer)?;
+        Ok(SecretString::new(value.into()))
+    }
 }
 
 pub fn deserialize_split_comma<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
@@ -123,7 +185,7 @@ mod tests {
     }
 
     #[rstest::rstest]
-    #[case(Some(datetime!(2025-10-01 12:00:00 +9)), "{\"value\":\"2025-10-01T12:00:00+09:00\"}")]
+    #[case(Some(datetime!(2025-10-01 03:00:00 UTC)), "{\"value\":\"2025-10-01T03:00:00Z\"}")]
     #[case(None, "{\"value\":null}")]
     fn serialize_and_deserialize_option_offset_datetime_ok(
         #[case] dt: Option<OffsetDateTime>,
@@ -136,10 +198,54 @@ mod tests {
         assert_eq!(actual_de, value);
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    struct Timestamp {
+        #[serde(with = "timestamp")]
+        value: OffsetDateTime,
+    }
+
+    #[rstest::rstest]
+    #[case(datetime!(2025-10-01 03:00:00 UTC), "2025-10-01T03:00:00Z")]
+    #[case(datetime!(2025-10-01 12:00:00 +9), "2025-10-01T03:00:00Z")]
+    #[case(datetime!(2025-10-01 03:00:00.123456 UTC), "2025-10-01T03:00:00Z")]
+    #[case(datetime!(2025-10-01 03:00:00.999999999 -5), "2025-10-01T08:00:00Z")]
+    fn serialize_timestamp_in_utc_with_the_precision(
+        #[case] dt: OffsetDateTime,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(TIMESTAMP_PRECISION, TimestampPrecision::Seconds);
+        let actual = serde_json::to_value(Timestamp { value: dt }).unwrap();
+        assert_eq!(actual["value"], expected);
+        // The option serializer follows the same policy
+        let actual = serde_json::to_value(OptionOffsetDateTime { value: Some(dt) }).unwrap();
+        assert_eq!(actual["value"], expected);
+    }
+
+    #[rstest::rstest]
+    #[case("2025-10-01T03:00:00Z", datetime!(2025-10-01 03:00:00 UTC))]
+    #[case("2025-10-01T12:00:00+09:00", datetime!(2025-10-01 12:00:00 +9))]
+    #[case("2025-10-01T03:00:00.123456Z", datetime!(2025-10-01 03:00:00.123456 UTC))]
+    fn deserialize_timestamp_accepts_any_offset_and_precision(
+        #[case] serialized: &str,
+        #[case] expected: OffsetDateTime,
+    ) {
+        let json = format!(r#"{{"value": "{serialized}"}}"#);
+        let actual = serde_json::from_str::<Timestamp>(&json).unwrap();
+        assert_eq!(actual.value, expected);
+        assert_eq!(actual.value.offset(), expected.offset());
+    }
+
+    #[test]
+    fn normalize_offset_datetime_is_idempotent() {
+        let dt = normalize_offset_datetime(datetime!(2025-10-01 12:34:56.789 +9));
+        assert_eq!(dt, datetime!(2025-10-01 03:34:56 UTC));
+        assert_eq!(normalize_offset_datetime(dt), dt);
+    }
+
+    #[serde_with::serde_as]
     #[derive(Debug, Clone, Serialize, Deserialize)]
     struct SecretStringWrapper {
-        #[serde(serialize_with = "serialize_secret_string")]
-        #[serde(deserialize_with = "deserialize_secret_string")]
+        #[serde_as(as = "SecretStringSerde")]
         value: SecretString,
     }
 